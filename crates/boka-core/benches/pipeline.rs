@@ -0,0 +1,150 @@
+//! The CPU-bound steps of a translation job, on inputs the size of a long
+//! story: segmentation, parsing and repairing span plans, and building the
+//! doc after every planned segment the way a job emits partial docs.
+//!
+//! Run with `cargo bench -p boka-core`.
+
+use boka_core::anthropic::{PlannedBlock, PlannedSegment, PlannedSpan, PlannedVariant};
+use boka_core::gui_types::TextDirection;
+use boka_core::openai_compat::{parse_planned_blocks, sanitize_json_trailing_commas};
+use boka_core::policy::ContentPolicy;
+use boka_core::translation::{build_doc_from_blocks, split_into_segments, DocBuilder};
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use serde_json::json;
+
+const SENTENCES: [&str; 8] = [
+    "Every evening, the old keeper climbed the stairs of the lighthouse.",
+    "He lit the lamp and watched the boats come home.",
+    "One night, a storm broke the big window!",
+    "\"Who is there?\" he called, but only the wind answered.",
+    "The keeper repaired it before dawn, and the ships stayed safe.",
+    "Mr. Hale, the harbour master, thanked him at 9 a.m. the next day.",
+    "Years later… children still told the story.",
+    "Was it true? Nobody could say for sure.",
+];
+
+/// `paragraphs` paragraphs of four sentences each.
+fn story(paragraphs: usize) -> String {
+    (0..paragraphs)
+        .map(|p| (0..4).map(|s| SENTENCES[(p + s) % SENTENCES.len()]).collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// A span-plan response of `blocks` blocks with three spans of three
+/// variants each, as models return it: fenced and with trailing commas.
+fn plan_response(blocks: usize) -> String {
+    let variant = |text: &str, register: &str| {
+        json!({ "text": text, "register": register, "note": "", "difficulty": 2 })
+    };
+    let block = |i: usize| {
+        let mut segments = Vec::new();
+        for s in 0..3 {
+            segments.push(json!({ "type": "static", "text": if s == 0 { "Chaque soir, " } else { " et " } }));
+            segments.push(json!({
+                "type": "swappable",
+                "id": format!("s{}", s + 1),
+                "variants": [
+                    variant("le vieux gardien", "neutral"),
+                    variant("le vieil homme", "literary"),
+                    variant("le papi", "colloquial"),
+                ],
+            }));
+        }
+        json!({ "id": format!("b{}", i + 1), "segments": segments })
+    };
+    let json = serde_json::to_string(&(0..blocks).map(block).collect::<Vec<_>>()).unwrap();
+    format!("```json\n{}\n```", json.replace("}]", "},\n]"))
+}
+
+fn planned_blocks(blocks: usize) -> Vec<PlannedBlock> {
+    let variant = |text: &str, register: &str| PlannedVariant {
+        text: text.to_string(),
+        register: register.to_string(),
+        note: String::new(),
+        difficulty: 2,
+    };
+    (0..blocks)
+        .map(|i| PlannedBlock {
+            id: format!("b{}", i + 1),
+            segments: (0..3)
+                .flat_map(|s| {
+                    [
+                        PlannedSegment::Static("Chaque *soir*, ".to_string()),
+                        PlannedSegment::Swappable(PlannedSpan {
+                            id: format!("s{}", s + 1),
+                            variants: vec![
+                                variant("le vieux gardien", "neutral"),
+                                variant("le vieil homme", "literary"),
+                                variant("le papi", "colloquial"),
+                            ],
+                            provenance: vec![],
+                        }),
+                    ]
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+fn segmentation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("split_into_segments");
+    for paragraphs in [50, 500] {
+        let text = story(paragraphs);
+        group.bench_with_input(BenchmarkId::from_parameter(paragraphs), &text, |b, text| {
+            b.iter(|| split_into_segments(black_box(text)))
+        });
+    }
+    group.finish();
+}
+
+fn parsing(c: &mut Criterion) {
+    let response = plan_response(200);
+    let repaired = sanitize_json_trailing_commas(&response);
+    assert_eq!(parse_planned_blocks(&response).unwrap().len(), 200);
+    assert_eq!(parse_planned_blocks(&repaired).unwrap().len(), 200);
+
+    c.bench_function("sanitize_json_trailing_commas/200", |b| {
+        b.iter(|| sanitize_json_trailing_commas(black_box(&response)))
+    });
+    c.bench_function("parse_planned_blocks/200", |b| b.iter(|| parse_planned_blocks(black_box(&repaired))));
+    // A reply that fails to parse as is and is repaired on the way.
+    c.bench_function("parse_planned_blocks/200_repaired", |b| {
+        b.iter(|| parse_planned_blocks(black_box(&response)))
+    });
+}
+
+fn doc_building(c: &mut Criterion) {
+    let policy = ContentPolicy::default();
+    let mut group = c.benchmark_group("build_doc_from_blocks");
+    for count in [20, 150] {
+        let blocks = planned_blocks(count);
+        group.bench_with_input(BenchmarkId::new("whole", count), &blocks, |b, blocks| {
+            b.iter(|| build_doc_from_blocks(black_box(blocks), &policy, TextDirection::Ltr))
+        });
+        // A partial doc after each planned segment, rebuilt from scratch...
+        group.bench_with_input(BenchmarkId::new("every_step", count), &blocks, |b, blocks| {
+            b.iter(|| {
+                for done in 1..=blocks.len() {
+                    black_box(build_doc_from_blocks(&blocks[..done], &policy, TextDirection::Ltr));
+                }
+            })
+        });
+        // ...and grown the way a job does it.
+        group.bench_with_input(BenchmarkId::new("incremental", count), &blocks, |b, blocks| {
+            b.iter(|| {
+                let mut builder = DocBuilder::new(&policy, TextDirection::Ltr);
+                for block in blocks {
+                    builder.update_last(block);
+                    builder.push(block);
+                    black_box(builder.doc());
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, segmentation, parsing, doc_building);
+criterion_main!(benches);
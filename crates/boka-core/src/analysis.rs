@@ -0,0 +1,124 @@
+use super::lemma;
+use super::limits::estimate_job_tokens;
+use super::translation::split_into_segments;
+
+use serde::Serialize;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextStats {
+    pub char_count: u32,
+    pub word_count: u32,
+    pub sentence_count: u32,
+    pub unique_words: u32,
+    pub unique_lemmas: u32,
+    pub avg_sentence_words: f32,
+    pub reading_time_seconds: u32,
+    /// Heuristic estimate (A1–C2) from sentence length, word length and
+    /// lexical variety. Treat it as a hint, not an assessment.
+    pub estimated_cefr: String,
+    pub estimated_input_tokens: u32,
+    pub estimated_output_tokens: u32,
+}
+
+/// Approximate tokens for a piece of text (≈4 chars/token, ≈1.5 chars/token for CJK).
+pub fn estimate_tokens(text: &str) -> u32 {
+    let mut cjk = 0u32;
+    let mut other = 0u32;
+    for c in text.chars() {
+        if c as u32 >= 0x2E80 {
+            cjk += 1;
+        } else {
+            other += 1;
+        }
+    }
+    (other / 4) + (cjk * 2 / 3) + 1
+}
+
+pub fn analyze_text(text: &str, language: &str) -> TextStats {
+    let sentences = split_into_segments(text);
+    let words = lemma::words(text, language);
+
+    let word_count = words.len() as u32;
+    let sentence_count = sentences.len() as u32;
+
+    let unique_words: HashSet<String> = words.iter().map(|w| w.to_lowercase()).collect();
+    let unique_lemmas: HashSet<String> = words.iter().map(|w| lemma::lemma(w, language)).collect();
+
+    let avg_sentence_words = if sentence_count == 0 {
+        0.0
+    } else {
+        word_count as f32 / sentence_count as f32
+    };
+
+    // Learner reading speeds: ~120 wpm for spaced scripts, ~250 chars/min otherwise.
+    let per_minute = if lemma::is_unspaced_script(language) { 250.0 } else { 120.0 };
+    let reading_time_seconds = (word_count as f32 / per_minute * 60.0).round() as u32;
+
+    let (estimated_input_tokens, estimated_output_tokens) = estimate_job_tokens(text);
+
+    TextStats {
+        char_count: text.chars().count() as u32,
+        word_count,
+        sentence_count,
+        unique_words: unique_words.len() as u32,
+        unique_lemmas: unique_lemmas.len() as u32,
+        avg_sentence_words,
+        reading_time_seconds,
+        estimated_cefr: estimate_cefr(&words, avg_sentence_words, unique_lemmas.len(), language).to_string(),
+        estimated_input_tokens,
+        estimated_output_tokens,
+    }
+}
+
+/// Rough difficulty of a single segment in 0.0..=1.0, from its length and
+/// the share of long (usually rarer) words. Used to size variant counts.
+pub fn segment_difficulty(text: &str, language: &str) -> f32 {
+    let words = lemma::words(text, language);
+    if words.is_empty() {
+        return 0.0;
+    }
+
+    // Unspaced scripts yield one "word" per character, so there is no
+    // word-length signal; length alone has to do.
+    if lemma::is_unspaced_script(language) {
+        return (words.len() as f32 / 60.0).min(1.0);
+    }
+
+    let length = (words.len() as f32 / 25.0).min(1.0);
+    let rare = words.iter().filter(|w| w.chars().count() >= 8).count() as f32 / words.len() as f32;
+    // A third of the words being long already reads as hard.
+    let rarity = (rare * 3.0).min(1.0);
+
+    0.5 * length + 0.5 * rarity
+}
+
+fn estimate_cefr(words: &[&str], avg_sentence_words: f32, unique_lemmas: usize, language: &str) -> &'static str {
+    if words.is_empty() {
+        return "A1";
+    }
+
+    let avg_word_chars = words.iter().map(|w| w.chars().count()).sum::<usize>() as f32 / words.len() as f32;
+    // Type/token ratio shrinks as texts grow; normalise with a sqrt (Guiraud's index).
+    let guiraud = unique_lemmas as f32 / (words.len() as f32).sqrt();
+
+    let (sentence_scale, word_scale) = if lemma::is_unspaced_script(language) {
+        (40.0, 1.0)
+    } else {
+        (25.0, 7.0)
+    };
+
+    let score = (avg_sentence_words / sentence_scale).min(1.5)
+        + (avg_word_chars / word_scale).min(1.5)
+        + (guiraud / 10.0).min(1.5);
+
+    match score {
+        s if s < 1.2 => "A1",
+        s if s < 1.6 => "A2",
+        s if s < 2.0 => "B1",
+        s if s < 2.4 => "B2",
+        s if s < 2.8 => "C1",
+        _ => "C2",
+    }
+}
@@ -0,0 +1,237 @@
+//! Annotation passes: furigana, pinyin, romanization, frequency bands,
+//! CEFR levels, glosses. Each is an [`AnnotationPass`] that marks up the
+//! text of every variant of a finished doc; the passes a job asks for (its
+//! [`AnnotationPlan`]) run in order after translation and leave one
+//! [`AnnotationLayer`] each in `InteractiveDoc::annotations`.
+//!
+//! A new annotation is a type implementing the trait, registered in
+//! [`AnnotationRegistry::builtin`]; the translation pipeline only ever sees
+//! the plan.
+
+use super::frequency::{FreqBand, FreqList};
+use super::gui_types::InteractiveDoc;
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AnnotationError {
+    #[error("Unknown annotation pass: {0}")]
+    UnknownPass(String),
+
+    #[error("The {pass} annotation does not support {language}")]
+    Unsupported { pass: String, language: String },
+
+    #[error("The {pass} annotation failed: {message}")]
+    Failed { pass: String, message: String },
+}
+
+/// A piece of a variant's text and what the pass says about it. Joined in
+/// order, the pieces give back the text; the text between marked words
+/// (spaces, punctuation) has no value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotatedToken {
+    pub text: String,
+    /// A reading, a band, a level or a gloss, depending on the pass.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+/// What one pass made of a doc.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotationLayer {
+    /// Span id to the tokens of each of its variants, in variant order.
+    pub spans: HashMap<String, Vec<Vec<AnnotatedToken>>>,
+}
+
+impl AnnotationLayer {
+    /// A layer with `annotate` run on the text of every variant.
+    pub fn by_variant(doc: &InteractiveDoc, mut annotate: impl FnMut(&str) -> Vec<AnnotatedToken>) -> Self {
+        let spans = doc
+            .spans
+            .iter()
+            .map(|(id, span)| (id.clone(), span.variants.iter().map(|v| annotate(&v.text)).collect()))
+            .collect();
+        AnnotationLayer { spans }
+    }
+}
+
+/// What a pass knows about the doc beyond its text.
+#[derive(Debug, Clone, Copy)]
+pub struct AnnotationContext<'a> {
+    /// Language of the doc.
+    pub language: &'a str,
+    pub source_language: Option<&'a str>,
+    /// For data of the user's, such as frequency lists; built-in data only
+    /// when unset.
+    pub data_dir: Option<&'a Path>,
+}
+
+pub trait AnnotationPass: Send + Sync {
+    /// Stable id, used in plans, job metadata and `InteractiveDoc::annotations`.
+    fn id(&self) -> &'static str;
+
+    /// Whether the pass has anything to say about text in `context.language`.
+    fn supports(&self, context: &AnnotationContext) -> bool;
+
+    fn annotate(&self, doc: &InteractiveDoc, context: &AnnotationContext) -> Result<AnnotationLayer, AnnotationError>;
+}
+
+/// The passes that can be asked for, by id.
+#[derive(Default, Clone)]
+pub struct AnnotationRegistry {
+    passes: Vec<Arc<dyn AnnotationPass>>,
+}
+
+impl AnnotationRegistry {
+    /// The passes that ship with the app.
+    pub fn builtin() -> Self {
+        let mut registry = Self::default();
+        registry.register(Arc::new(FrequencyPass));
+        registry
+    }
+
+    /// Add `pass`, replacing one with the same id.
+    pub fn register(&mut self, pass: Arc<dyn AnnotationPass>) {
+        self.passes.retain(|p| p.id() != pass.id());
+        self.passes.push(pass);
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Arc<dyn AnnotationPass>> {
+        self.passes.iter().find(|p| p.id() == id)
+    }
+
+    /// Ids of the registered passes, in registration order.
+    pub fn ids(&self) -> Vec<&'static str> {
+        self.passes.iter().map(|p| p.id()).collect()
+    }
+
+    /// Ids of the passes that support `context.language`.
+    pub fn available(&self, context: &AnnotationContext) -> Vec<&'static str> {
+        self.passes.iter().filter(|p| p.supports(context)).map(|p| p.id()).collect()
+    }
+}
+
+/// The passes a job runs on its doc.
+#[derive(Clone, Default)]
+pub struct AnnotationPlan {
+    /// Pass ids, run in this order; each runs once.
+    pub passes: Vec<String>,
+    /// See [`AnnotationContext::data_dir`].
+    pub data_dir: Option<PathBuf>,
+    /// Where the passes are looked up; [`AnnotationRegistry::builtin`] when
+    /// unset.
+    pub registry: Option<Arc<AnnotationRegistry>>,
+}
+
+impl AnnotationPlan {
+    pub fn new(passes: Vec<String>) -> Self {
+        Self {
+            passes,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.passes.is_empty()
+    }
+}
+
+/// Run the passes of `plan` on `doc`, in `language`, replacing the layers
+/// they left before. A pass that is unknown, does not support the language
+/// or fails is skipped; the others still run. Returns what was skipped.
+pub fn annotate_doc(
+    doc: &mut InteractiveDoc,
+    plan: &AnnotationPlan,
+    language: &str,
+    source_language: Option<&str>,
+) -> Vec<AnnotationError> {
+    let context = AnnotationContext {
+        language,
+        source_language,
+        data_dir: plan.data_dir.as_deref(),
+    };
+    let builtin;
+    let registry = match plan.registry.as_deref() {
+        Some(registry) => registry,
+        None => {
+            builtin = AnnotationRegistry::builtin();
+            &builtin
+        }
+    };
+    let mut skipped = Vec::new();
+    let mut done: Vec<&str> = Vec::new();
+    for id in &plan.passes {
+        if done.contains(&id.as_str()) {
+            continue;
+        }
+        done.push(id);
+        let Some(pass) = registry.get(id) else {
+            skipped.push(AnnotationError::UnknownPass(id.clone()));
+            continue;
+        };
+        if !pass.supports(&context) {
+            skipped.push(AnnotationError::Unsupported {
+                pass: id.clone(),
+                language: language.to_string(),
+            });
+            continue;
+        }
+        match pass.annotate(doc, &context) {
+            Ok(layer) => {
+                doc.annotations.insert(id.clone(), layer);
+            }
+            Err(e) => skipped.push(e),
+        }
+    }
+    skipped
+}
+
+/// Frequency bands (`top-1k`, `top-5k`, `rare`) from the user's list for the
+/// language, else the built-in one; see [`frequency`](super::frequency).
+pub struct FrequencyPass;
+
+impl AnnotationPass for FrequencyPass {
+    fn id(&self) -> &'static str {
+        "frequency"
+    }
+
+    fn supports(&self, context: &AnnotationContext) -> bool {
+        match context.data_dir {
+            Some(dir) => FreqList::available(dir, context.language),
+            None => FreqList::builtin(context.language).is_some(),
+        }
+    }
+
+    fn annotate(&self, doc: &InteractiveDoc, context: &AnnotationContext) -> Result<AnnotationLayer, AnnotationError> {
+        let list = match context.data_dir {
+            Some(dir) => FreqList::load(dir, context.language).map_err(|e| AnnotationError::Failed {
+                pass: self.id().to_string(),
+                message: e.to_string(),
+            })?,
+            None => FreqList::builtin(context.language),
+        };
+        let list = list.unwrap_or_default();
+        Ok(AnnotationLayer::by_variant(doc, |text| {
+            list.tokens(text, context.language)
+                .into_iter()
+                .map(|t| AnnotatedToken {
+                    text: t.text,
+                    value: t.freq_band.map(|band| band_name(band).to_string()),
+                })
+                .collect()
+        }))
+    }
+}
+
+fn band_name(band: FreqBand) -> &'static str {
+    match band {
+        FreqBand::Top1k => "top-1k",
+        FreqBand::Top5k => "top-5k",
+        FreqBand::Rare => "rare",
+    }
+}
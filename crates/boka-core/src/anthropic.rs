@@ -0,0 +1,459 @@
+use super::cassette;
+use super::gui_types::CallProvenance;
+use super::import::PageImage;
+use super::judge::{self, JudgeVerdict};
+use super::length_guard::LengthGuard;
+use super::openai_compat::{parse_planned_blocks, parse_variants};
+use super::policy::ContentPolicy;
+use super::prompts::{self, PromptSet};
+use super::refusal;
+use super::simple_format::{parse_simple_plan, parse_simple_variants, StructuredFormat};
+use super::types::{
+    ApiConfig, ApiError, ImageSource, LlmProviderPreset, Message, MessageContent, MessagePart, MessagesRequest,
+    MessagesResponse, ModelCapabilities, ModelRegistry, Role, Usage,
+};
+
+use std::time::Duration;
+
+const API_URL: &str = "https://api.anthropic.com/v1/messages";
+const API_VERSION: &str = "2023-06-01";
+
+pub struct AnthropicClient {
+    client: reqwest::Client,
+    api_key: String,
+    model: String,
+    caps: ModelCapabilities,
+    prompts: PromptSet,
+    config: ApiConfig,
+}
+
+impl AnthropicClient {
+    pub fn new(config: ApiConfig) -> Result<Self, ApiError> {
+        let api_key = config
+            .provider
+            .api_key
+            .clone()
+            .and_then(|k| {
+                let t = k.trim().to_string();
+                if t.is_empty() { None } else { Some(t) }
+            })
+            .or_else(|| {
+                // Replayed traffic never reaches the API, so no key is needed.
+                let replay = config.cassette.as_ref().is_some_and(|c| c.is_replay());
+                replay.then(String::new)
+            })
+            .ok_or_else(|| ApiError::NoApiKey {
+                provider: "anthropic".to_string(),
+            })?;
+
+        let registry = ModelRegistry::current();
+        let model = config
+            .provider
+            .model
+            .clone()
+            .and_then(|m| {
+                let t = m.trim().to_string();
+                if t.is_empty() { None } else { Some(t) }
+            })
+            .or_else(|| registry.default_model(LlmProviderPreset::Anthropic).map(str::to_string))
+            .ok_or_else(|| ApiError::Parse("No default Anthropic model in the model registry".to_string()))?;
+        let caps = registry.capabilities(LlmProviderPreset::Anthropic, &model);
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(60))
+            .build()?;
+
+        Ok(Self {
+            client,
+            api_key,
+            model,
+            caps,
+            prompts: PromptSet::for_config(&config, false),
+            config,
+        })
+    }
+
+    pub fn api_url(&self) -> &'static str {
+        API_URL
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    pub fn length_guard(&self) -> LengthGuard {
+        self.config.length_guard
+    }
+
+    pub fn capabilities(&self) -> &ModelCapabilities {
+        &self.caps
+    }
+
+    async fn post(&self, mut request: MessagesRequest) -> Result<String, ApiError> {
+        request.system = prompts::assemble_system(self.prompts.preamble.as_deref(), &request.system);
+        let body = serde_json::to_value(&request).map_err(|e| ApiError::Parse(e.to_string()))?;
+        let (status, text) = cassette::post_json(
+            &self.client,
+            self.config.cassette.as_deref(),
+            self.config.transcript.as_deref(),
+            API_URL,
+            &[("x-api-key", &self.api_key), ("anthropic-version", API_VERSION)],
+            Some(&self.api_key),
+            &body,
+        )
+        .await?;
+
+        if !(200..300).contains(&status) {
+            return Err(ApiError::ApiResponse { status, message: text });
+        }
+
+        Ok(text)
+    }
+
+    async fn send(&self, request: MessagesRequest) -> Result<MessagesResponse, ApiError> {
+        let text = self.post(request).await?;
+        let resp: MessagesResponse =
+            serde_json::from_str(&text).map_err(|e| ApiError::Parse(format!("Response body: {}", e)))?;
+        let reply: String = resp.content.iter().filter_map(|b| b.text.as_deref()).collect();
+        let flagged = resp.stop_reason.as_deref() == Some("refusal");
+        refusal::check("anthropic", &self.model, &reply, flagged)?;
+        Ok(resp)
+    }
+
+    pub async fn test_connection(&self) -> Result<(), ApiError> {
+        let messages = vec![Message {
+            role: Role::User,
+            content: "ping".to_string().into(),
+        }];
+
+        let request = MessagesRequest {
+            model: self.model.clone(),
+            max_tokens: 1,
+            system: "You are a connectivity test. Reply with OK.".to_string(),
+            messages,
+            temperature: self.config.sampling.temperature,
+            top_p: self.config.sampling.top_p,
+        };
+
+        self.post(request).await?;
+        Ok(())
+    }
+
+    /// Base translation of `segment`; `note` asks again after a reply failed
+    /// the length guard.
+    pub async fn translate_base_segment(
+        &self,
+        full_story: &str,
+        segment: &str,
+        note: Option<&str>,
+    ) -> Result<(String, Usage), ApiError> {
+        let system = self.prompts.base_translation.clone();
+        let content = prompts::base_translation_user_content(full_story, segment, self.caps.story_context_budget());
+        let content = prompts::with_revision_note(content, note);
+
+        self.complete_text(system, content.into(), 512).await
+    }
+
+    /// Graded-reader version of `segment`; needs `ApiConfig::simplify_level`.
+    pub async fn translate_simplified_segment(
+        &self,
+        full_story: &str,
+        segment: &str,
+        note: Option<&str>,
+    ) -> Result<(String, Usage), ApiError> {
+        let system = self
+            .prompts
+            .simplified_translation
+            .clone()
+            .ok_or_else(|| ApiError::Parse("No simplify level configured".to_string()))?;
+        let content = prompts::simplified_translation_user_content(
+            full_story,
+            segment,
+            self.caps.story_context_budget(),
+            note,
+        );
+
+        self.complete_text(system, content.into(), 512).await
+    }
+
+    /// Transcribe the text on one page image.
+    pub async fn transcribe_image(&self, image: &PageImage) -> Result<(String, Usage), ApiError> {
+        let content = MessageContent::Parts(vec![
+            MessagePart::Image {
+                source: ImageSource {
+                    kind: "base64".to_string(),
+                    media_type: image.media_type.to_string(),
+                    data: image.data.clone(),
+                },
+            },
+            MessagePart::Text {
+                text: prompts::TRANSCRIPTION_REQUEST.to_string(),
+            },
+        ]);
+
+        self.complete_text(prompts::transcription_system_prompt(), content, 4096).await
+    }
+
+    /// Shelf title, synopsis and cover emoji for a translated story, as the
+    /// model's JSON (see `story_meta`).
+    pub async fn describe_story(&self, source: &str, translation: &str) -> Result<(String, Usage), ApiError> {
+        let source_language = self.config.source_language.as_deref();
+        let system = prompts::story_description_system_prompt(&self.config.target_language, source_language);
+        let content = prompts::story_description_user_content(source, translation);
+
+        self.complete_text(system, content.into(), 512).await
+    }
+
+    pub async fn comprehension_check(&self, passage: &str) -> Result<(String, Usage), ApiError> {
+        let source_language = self.config.source_language.as_deref();
+        let system = prompts::comprehension_check_system_prompt(&self.config.target_language, source_language);
+
+        self.complete_text(system, passage.to_string().into(), 256).await
+    }
+
+    /// `notes` in `ui_language`, as the model's `{ "notes": [...] }`.
+    pub async fn translate_notes(&self, notes: &[String], ui_language: &str) -> Result<(String, Usage), ApiError> {
+        let system = prompts::note_translation_system_prompt(&self.config.target_language, ui_language);
+        let content = prompts::note_translation_user_content(notes);
+
+        self.complete_text(system, content.into(), 4096).await
+    }
+
+    /// About `words` more words of `story`, in its own language.
+    pub async fn continue_story(
+        &self,
+        story: &str,
+        direction_hint: Option<&str>,
+        words: u32,
+    ) -> Result<(String, Usage), ApiError> {
+        let source_language = self.config.source_language.as_deref();
+        let system = prompts::continuation_system_prompt(source_language, words, &self.config.content_policy);
+        let content = prompts::continuation_user_content(story, direction_hint);
+
+        self.complete_text(system, content.into(), words * 4).await
+    }
+
+    /// Who says each dialogue line, as the model's `{ "lines": [...] }` (see
+    /// `speakers`).
+    pub async fn attribute_speakers(
+        &self,
+        blocks: &[(u32, String)],
+        lines: &[(String, String)],
+        max_speakers: usize,
+    ) -> Result<(String, Usage), ApiError> {
+        let system = prompts::speaker_attribution_system_prompt(&self.config.target_language, max_speakers);
+        let content = prompts::speaker_attribution_user_content(blocks, lines);
+
+        self.complete_text(system, content.into(), 2048).await
+    }
+
+    pub async fn answer_question(
+        &self,
+        source: &str,
+        translation: &str,
+        earlier: &[(&str, &str)],
+        question: &str,
+        ui_language: Option<&str>,
+        policy: Option<&ContentPolicy>,
+    ) -> Result<(String, Usage), ApiError> {
+        let source_language = self.config.source_language.as_deref();
+        let system = prompts::tutor_system_prompt(&self.config.target_language, source_language, ui_language, policy);
+        let content = prompts::tutor_user_content(source, translation, earlier, question);
+
+        self.complete_text(system, content.into(), 600).await
+    }
+
+    async fn complete_text(
+        &self,
+        system: String,
+        content: MessageContent,
+        max_tokens: u32,
+    ) -> Result<(String, Usage), ApiError> {
+        let messages = vec![Message {
+            role: Role::User,
+            content,
+        }];
+
+        let request = MessagesRequest {
+            model: self.model.clone(),
+            max_tokens: self.caps.max_tokens(max_tokens),
+            system,
+            messages,
+            temperature: self.config.sampling.temperature,
+            top_p: self.config.sampling.top_p,
+        };
+
+        let resp = self.send(request).await?;
+        let text = resp
+            .content
+            .iter()
+            .filter_map(|b| b.text.as_deref())
+            .collect::<Vec<_>>()
+            .join("")
+            .trim()
+            .to_string();
+
+        let usage = resp.usage();
+        Ok((text, usage))
+    }
+
+    pub async fn score_translation(&self, source: &str, translation: &str) -> Result<(JudgeVerdict, Usage), ApiError> {
+        let request = MessagesRequest {
+            model: self.model.clone(),
+            max_tokens: self.caps.max_tokens(256),
+            system: prompts::judge_system_prompt(&self.config.target_language, self.config.source_language.as_deref()),
+            messages: vec![Message {
+                role: Role::User,
+                content: prompts::judge_user_content(source, translation).into(),
+            }],
+            temperature: self.config.sampling.temperature,
+            top_p: self.config.sampling.top_p,
+        };
+
+        let resp = self.send(request).await?;
+        let text = resp
+            .content
+            .iter()
+            .filter_map(|b| b.text.as_deref())
+            .collect::<Vec<_>>()
+            .join("");
+
+        let usage = resp.usage();
+        Ok((judge::parse_verdict(&text)?, usage))
+    }
+
+    pub async fn plan_block_from_base(
+        &self,
+        base_text: &str,
+        format: StructuredFormat,
+    ) -> Result<(PlannedBlock, Usage), ApiError> {
+        if format == StructuredFormat::Simple {
+            let system = prompts::simple_span_planning_system_prompt(&self.config);
+            let (text, usage) = self.complete_text(system, base_text.to_string().into(), 2048).await?;
+            return Ok((parse_simple_plan(&text, base_text)?, usage));
+        }
+        let system = self.prompts.span_planning.clone();
+
+        let messages = vec![Message {
+            role: Role::User,
+            content: base_text.to_string().into(),
+        }];
+
+        let request = MessagesRequest {
+            model: self.model.clone(),
+            max_tokens: self.caps.max_tokens(2048),
+            system,
+            messages,
+            temperature: self.config.sampling.temperature,
+            top_p: self.config.sampling.top_p,
+        };
+
+        let resp = self.send(request).await?;
+        let text = resp
+            .content
+            .iter()
+            .filter_map(|b| b.text.as_deref())
+            .collect::<Vec<_>>()
+            .join("");
+
+        let usage = resp.usage();
+        let mut blocks = parse_planned_blocks(&text)?;
+        let block = blocks
+            .drain(..)
+            .next()
+            .ok_or_else(|| ApiError::Parse("No block returned".to_string()))?;
+
+        Ok((block, usage))
+    }
+
+    pub async fn generate_span_variants(
+        &self,
+        segment_context: &str,
+        anchor_phrase: &str,
+        variant_count: u32,
+        format: StructuredFormat,
+    ) -> Result<(Vec<PlannedVariant>, Usage), ApiError> {
+        let content = format!(
+            "SEGMENT CONTEXT:\n{}\n\nANCHOR PHRASE:\n{}",
+            segment_context, anchor_phrase
+        );
+        if format == StructuredFormat::Simple {
+            let system = prompts::simple_span_variants_system_prompt(&self.config, variant_count);
+            let (text, usage) = self.complete_text(system, content.into(), 2048).await?;
+            return Ok((parse_simple_variants(&text)?, usage));
+        }
+        let system = self.prompts.span_variants_for(variant_count);
+
+        let messages = vec![Message {
+            role: Role::User,
+            content: content.into(),
+        }];
+
+        let request = MessagesRequest {
+            model: self.model.clone(),
+            max_tokens: self.caps.max_tokens(2048),
+            system,
+            messages,
+            temperature: self.config.sampling.temperature,
+            top_p: self.config.sampling.top_p,
+        };
+
+        let resp = self.send(request).await?;
+        let text = resp
+            .content
+            .iter()
+            .filter_map(|b| b.text.as_deref())
+            .collect::<Vec<_>>()
+            .join("");
+
+        let usage = resp.usage();
+
+        Ok((parse_variants(&text)?, usage))
+    }
+
+    /// Critique-and-fix pass over `variants`; needs `ApiConfig::refine_variants`.
+    pub async fn refine_span_variants(
+        &self,
+        segment_context: &str,
+        anchor_phrase: &str,
+        variants: &[PlannedVariant],
+    ) -> Result<(Vec<PlannedVariant>, Usage), ApiError> {
+        let system = self
+            .prompts
+            .variant_critique
+            .clone()
+            .ok_or_else(|| ApiError::Parse("Variant refinement not enabled".to_string()))?;
+        let content = prompts::variant_critique_user_content(segment_context, anchor_phrase, variants);
+
+        let (text, usage) = self.complete_text(system, content.into(), 2048).await?;
+        Ok((parse_variants(&text)?, usage))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PlannedBlock {
+    pub id: String,
+    pub segments: Vec<PlannedSegment>,
+}
+
+#[derive(Debug, Clone)]
+pub enum PlannedSegment {
+    Static(String),
+    Swappable(PlannedSpan),
+}
+
+#[derive(Debug, Clone)]
+pub struct PlannedSpan {
+    pub id: String,
+    pub variants: Vec<PlannedVariant>,
+    /// Calls that generated `variants`; filled in by the pipeline.
+    pub provenance: Vec<CallProvenance>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PlannedVariant {
+    pub text: String,
+    pub register: String,
+    pub note: String,
+    pub difficulty: u8,
+}
@@ -0,0 +1,1354 @@
+use super::audio_types::{
+    AlignedPhoneme, AudioModelStatus, AudioStage, BlockAudioStatus, ControlRange, DetectedPhoneme, DocAudioStatus,
+    ExpectedPhoneme, PauseOptions, PhonemeMatch, PronunciationComparison, VariantAudioStatus, VoiceControls, VoiceInfo,
+    VoiceStyle,
+};
+use super::content_hash::ContentHash;
+use super::gui_types::{DocToken, InteractiveDoc};
+use super::system_tts::{SystemEngine, SYSTEM_MODEL_ID};
+use super::tts_models::{TtsEngineKind, TtsModelEntry};
+
+use base64::Engine as _;
+use kokorox::tts::koko::TTSKoko;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::future::Future;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Longest piece of text handed to the engine in one call.
+pub const MAX_CHUNK_CHARS: usize = 300;
+
+/// Longest text spoken as a word clip; see [`generate_word_audio`].
+pub const MAX_WORD_CHARS: usize = 60;
+
+/// Silence left at each end of a word clip.
+pub const WORD_PADDING_MS: u32 = 40;
+
+/// Longest render kept in one response; beyond this the text must be split
+/// by the caller (e.g. per block).
+pub const MAX_AUDIO_MS: u64 = 30 * 60 * 1000;
+
+fn max_samples(sample_rate: u32) -> u64 {
+    sample_rate as u64 * MAX_AUDIO_MS / 1000
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AudioError {
+    #[error("TTS model not loaded — call preload_model first")]
+    ModelNotLoaded,
+
+    #[error("TTS model {0} is not installed")]
+    ModelNotInstalled(String),
+
+    #[error("TTS generation failed: {0}")]
+    GenerationFailed(String),
+
+    #[error("Generation cancelled")]
+    Cancelled,
+
+    #[error("Cache I/O error: {0}")]
+    CacheIo(String),
+
+    #[error("WAV encoding error: {0}")]
+    WavEncode(String),
+
+    #[error("Text too long to speak at once (over {0} minutes of audio)")]
+    TooLong(u64),
+
+    #[error("Unsupported voice setting: {0}")]
+    InvalidStyle(String),
+
+    #[error("Word audio is for a word or short phrase of at most {0} characters")]
+    NotAWord(usize),
+}
+
+/// A speech engine the app can drive. Implementations own their model
+/// files; callers only see PCM samples, so models can be swapped per
+/// language without touching them.
+pub trait TtsEngine: Send {
+    /// Registry id of the model.
+    fn model_id(&self) -> &str;
+
+    /// The model and voice pack version, the audio cache namespace.
+    fn model_version(&self) -> String {
+        self.model_id().to_string()
+    }
+
+    /// Load the model; a no-op when already loaded.
+    fn load_model(&mut self) -> Pin<Box<dyn Future<Output = Result<(), AudioError>> + Send + '_>>;
+
+    fn is_loaded(&self) -> bool;
+
+    /// Generate speech audio from text. Returns f32 PCM samples at [`Self::sample_rate`].
+    fn generate(&self, text: &str, voice_id: &str, speed: f32, language: &str) -> Result<Vec<f32>, AudioError>;
+
+    fn sample_rate(&self) -> u32;
+
+    fn status(&self) -> AudioModelStatus;
+
+    fn default_voice(&self, language: &str) -> String;
+
+    fn voices(&self) -> Vec<VoiceInfo>;
+
+    /// What can be tuned on `voice_id`; speed alone unless the engine
+    /// says more.
+    fn controls(&self, _voice_id: &str) -> VoiceControls {
+        VoiceControls::default()
+    }
+
+    /// The voice id to generate and cache with for `voice_id` spoken at
+    /// `speed` in `style`, after checking both against [`Self::controls`].
+    /// Engines that support styles encode them in the id, so styled audio
+    /// never shares cache entries with the plain voice.
+    fn styled_voice(&self, voice_id: &str, speed: f32, style: &VoiceStyle) -> Result<String, AudioError> {
+        check_style(&self.controls(voice_id), speed, style)?;
+        Ok(voice_id.to_string())
+    }
+}
+
+/// Check `speed` and `style` against what a voice supports.
+pub fn check_style(controls: &VoiceControls, speed: f32, style: &VoiceStyle) -> Result<(), AudioError> {
+    let invalid = |message: String| Err(AudioError::InvalidStyle(message));
+    if !controls.speed.contains(speed) {
+        return invalid(format!(
+            "speed {} is outside {}–{}",
+            speed, controls.speed.min, controls.speed.max
+        ));
+    }
+    if style.force_style && !controls.force_style {
+        return invalid("this voice has no style to force".to_string());
+    }
+    if style.blend.is_empty() {
+        return Ok(());
+    }
+    let Some(weights) = controls.blend_weight.filter(|_| controls.max_blend > 0) else {
+        return invalid("this voice can't be blended".to_string());
+    };
+    if style.blend.len() > controls.max_blend as usize {
+        return invalid(format!("at most {} voices can be blended in", controls.max_blend));
+    }
+    if let Some(mix) = style.blend.iter().find(|m| !weights.contains(m.weight)) {
+        return invalid(format!(
+            "weight {} of {} is outside {}–{}",
+            mix.weight, mix.voice_id, weights.min, weights.max
+        ));
+    }
+    if style.blend.iter().map(|m| m.weight).sum::<f32>() > weights.max {
+        return invalid(format!("blended voices can weigh at most {} together", weights.max));
+    }
+    Ok(())
+}
+
+/// Build the engine for a registry entry. Nothing is loaded yet.
+pub fn engine_for(model: &TtsModelEntry, model_dir: &Path) -> Box<dyn TtsEngine> {
+    match model.engine {
+        TtsEngineKind::Kokoro => Box::new(KokoroEngine::from_entry(model, model_dir)),
+    }
+}
+
+/// Engines by model id, created on first use and kept loaded.
+#[derive(Default)]
+pub struct TtsEngines {
+    engines: HashMap<String, Box<dyn TtsEngine>>,
+}
+
+impl TtsEngines {
+    pub fn get(&mut self, model: &TtsModelEntry, model_dir: &Path) -> &mut dyn TtsEngine {
+        self.engines
+            .entry(model.id.clone())
+            .or_insert_with(|| engine_for(model, model_dir))
+            .as_mut()
+    }
+
+    /// The OS synthesizer, for when no model is ready.
+    pub fn system(&mut self) -> &mut dyn TtsEngine {
+        self.engines
+            .entry(SYSTEM_MODEL_ID.to_string())
+            .or_insert_with(|| Box::new(SystemEngine::new()))
+            .as_mut()
+    }
+
+    /// Ids of the models whose engine has loaded them.
+    pub fn loaded(&self) -> Vec<String> {
+        let mut ids: Vec<String> =
+            self.engines.iter().filter(|(_, engine)| engine.is_loaded()).map(|(id, _)| id.clone()).collect();
+        ids.sort();
+        ids
+    }
+}
+
+/// Kokoro TTS engine backed by kokorox + ort 2.0.
+/// The stock model and voice data are downloaded from HuggingFace on first
+/// load; other Kokoro variants (e.g. quantized) load from installed files.
+pub struct KokoroEngine {
+    model_id: String,
+    version: String,
+    files: Option<(PathBuf, PathBuf)>,
+    size_bytes: u64,
+    tts: Option<TTSKoko>,
+}
+
+impl Default for KokoroEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KokoroEngine {
+    /// The stock Kokoro-82M model from the HuggingFace hub.
+    pub fn new() -> Self {
+        Self {
+            model_id: "kokoro-82m".to_string(),
+            version: "kokoro-82m@v1.0".to_string(),
+            files: None,
+            size_bytes: 350_000_000,
+            tts: None,
+        }
+    }
+
+    pub fn from_entry(model: &TtsModelEntry, model_dir: &Path) -> Self {
+        Self {
+            model_id: model.id.clone(),
+            version: model.version_key(),
+            files: model.files(model_dir),
+            size_bytes: model.size_bytes,
+            tts: None,
+        }
+    }
+
+    /// Pick a default voice appropriate for the given language code.
+    /// Kokoro voice IDs encode language in their prefix:
+    ///   af_ = American English Female, am_ = American English Male
+    ///   bf_ = British English Female,  bm_ = British English Male
+    ///   ff_ = French Female,           jf_ = Japanese Female, etc.
+    pub fn default_voice_for_language(language: &str) -> &'static str {
+        match language {
+            "fr" => "ff_siwis",
+            "ja" | "jp" => "jf_alpha",
+            "zh" | "cn" => "zf_xiaobei",
+            "ko" => "af_bella",        // No native Korean voices yet — fallback
+            "es" => "ef_dora",
+            "de" => "af_bella",        // No native German voices yet — fallback
+            "it" => "if_sara",
+            "pt" => "pf_dora",
+            "hi" => "hf_alpha",
+            "en" | "en-us" => "af_bella",
+            "en-gb" => "bf_emma",
+            _ => "af_bella",
+        }
+    }
+
+}
+
+impl TtsEngine for KokoroEngine {
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    fn model_version(&self) -> String {
+        self.version.clone()
+    }
+
+    /// Download (if needed) and load the Kokoro ONNX model + voice data.
+    /// This may take a moment on first run (~350MB download).
+    fn load_model(&mut self) -> Pin<Box<dyn Future<Output = Result<(), AudioError>> + Send + '_>> {
+        Box::pin(async move {
+            if self.tts.is_some() {
+                return Ok(());
+            }
+            let tts = match &self.files {
+                // TTSKoko::new with None paths triggers a HuggingFace Hub download
+                // into the hub cache (`BokaPaths::hf_cache_dir`, ~/.cache/huggingface/hub by default)
+                None => TTSKoko::new(None, None).await,
+                Some((model, voices)) => {
+                    if !model.is_file() || !voices.is_file() {
+                        return Err(AudioError::ModelNotInstalled(self.model_id.clone()));
+                    }
+                    TTSKoko::new(Some(&model.to_string_lossy()), Some(&voices.to_string_lossy())).await
+                }
+            };
+            self.tts = Some(tts);
+            Ok(())
+        })
+    }
+
+    fn is_loaded(&self) -> bool {
+        self.tts.is_some()
+    }
+
+    fn generate(&self, text: &str, voice_id: &str, speed: f32, language: &str) -> Result<Vec<f32>, AudioError> {
+        let tts = self.tts.as_ref().ok_or(AudioError::ModelNotLoaded)?;
+        // See `styled_voice`.
+        let (voice_id, force_style) = match voice_id.strip_prefix(FORCE_STYLE_MARK) {
+            Some(voice_id) => (voice_id, true),
+            None => (voice_id, false),
+        };
+
+        // Map language codes to kokorox language identifiers
+        let lan = match language {
+            "ja" | "jp" => "ja",
+            "zh" | "cn" => "zh",
+            "en" | "en-us" | "en-gb" => "en-us",
+            "fr" => "fr-fr",
+            "es" => "es",
+            "de" => "de",
+            "it" => "it",
+            "pt" => "pt-br",
+            "ko" => "ko",
+            other => other,
+        };
+
+        let samples = tts
+            .tts_raw_audio(
+                text,
+                lan,
+                voice_id,
+                speed,
+                None,        // initial_silence
+                true,        // auto_detect_language
+                force_style, // keep the voice's style in other languages
+                false,       // phonemes input
+            )
+            .map_err(|e| AudioError::GenerationFailed(e.to_string()))?;
+
+        Ok(samples)
+    }
+
+    fn sample_rate(&self) -> u32 {
+        24000
+    }
+
+    fn status(&self) -> AudioModelStatus {
+        let downloaded = match &self.files {
+            Some((model, voices)) => model.is_file() && voices.is_file(),
+            None => self.tts.is_some(),
+        };
+        AudioModelStatus {
+            downloaded,
+            loading: false,
+            ready: self.tts.is_some(),
+            model_size_bytes: downloaded.then_some(self.size_bytes),
+            error: None,
+        }
+    }
+
+    fn default_voice(&self, language: &str) -> String {
+        Self::default_voice_for_language(language).to_string()
+    }
+
+    fn voices(&self) -> Vec<VoiceInfo> {
+        KOKORO_VOICES
+            .iter()
+            .map(|&(id, name, language)| VoiceInfo {
+                id: id.into(),
+                name: name.into(),
+                language: language.into(),
+                sample_url: None,
+                controls: self.controls(id),
+            })
+            .collect()
+    }
+
+    /// Every voice blends with up to two others, in tenths, and can force
+    /// its style.
+    fn controls(&self, _voice_id: &str) -> VoiceControls {
+        VoiceControls {
+            blend_weight: Some(ControlRange {
+                min: 0.1,
+                max: 0.9,
+                default: 0.3,
+                step: Some(0.1),
+            }),
+            max_blend: 2,
+            force_style: true,
+            ..VoiceControls::default()
+        }
+    }
+
+    /// kokorox's mix syntax, `af_bella.7+af_sarah.3` (weights in tenths),
+    /// prefixed with `!` when the style is forced.
+    fn styled_voice(&self, voice_id: &str, speed: f32, style: &VoiceStyle) -> Result<String, AudioError> {
+        check_style(&self.controls(voice_id), speed, style)?;
+        let known = |id: &str| KOKORO_VOICES.iter().any(|(v, _, _)| *v == id);
+        if let Some(unknown) = std::iter::once(voice_id)
+            .chain(style.blend.iter().map(|m| m.voice_id.as_str()))
+            .find(|id| !known(id))
+        {
+            return Err(AudioError::InvalidStyle(format!("unknown voice {}", unknown)));
+        }
+
+        let mut spec = String::new();
+        if style.force_style {
+            spec.push(FORCE_STYLE_MARK);
+        }
+        if style.blend.is_empty() {
+            spec.push_str(voice_id);
+            return Ok(spec);
+        }
+        let tenths: Vec<u32> = style.blend.iter().map(|m| (m.weight * 10.0).round() as u32).collect();
+        let own = 10u32.saturating_sub(tenths.iter().sum());
+        if own == 0 {
+            return Err(AudioError::InvalidStyle(format!("{} would not be heard", voice_id)));
+        }
+        let parts: Vec<String> = std::iter::once(format!("{}.{}", voice_id, own))
+            .chain(style.blend.iter().zip(&tenths).map(|(m, t)| format!("{}.{}", m.voice_id, t)))
+            .collect();
+        spec.push_str(&parts.join("+"));
+        Ok(spec)
+    }
+}
+
+/// Starts a Kokoro voice id whose style is forced; see
+/// [`KokoroEngine::styled_voice`](TtsEngine::styled_voice).
+const FORCE_STYLE_MARK: char = '!';
+
+/// Kokoro voices: id, name, language.
+const KOKORO_VOICES: [(&str, &str, &str); 15] = [
+    // English (American)
+    ("af_bella", "Bella (F, EN-US)", "en"),
+    ("af_sarah", "Sarah (F, EN-US)", "en"),
+    ("am_adam", "Adam (M, EN-US)", "en"),
+    // English (British)
+    ("bf_emma", "Emma (F, EN-GB)", "en-gb"),
+    ("bm_george", "George (M, EN-GB)", "en-gb"),
+    // French
+    ("ff_siwis", "Siwis (F, FR)", "fr"),
+    // Japanese
+    ("jf_alpha", "Alpha (F, JA)", "ja"),
+    ("jm_kumo", "Kumo (M, JA)", "ja"),
+    // Chinese
+    ("zf_xiaobei", "Xiaobei (F, ZH)", "zh"),
+    ("zm_yunxi", "Yunxi (M, ZH)", "zh"),
+    // Spanish
+    ("ef_dora", "Dora (F, ES)", "es"),
+    // Italian
+    ("if_sara", "Sara (F, IT)", "it"),
+    ("im_nicola", "Nicola (M, IT)", "it"),
+    // Portuguese
+    ("pf_dora", "Dora (F, PT)", "pt"),
+    // Hindi
+    ("hf_alpha", "Alpha (F, HI)", "hi"),
+];
+
+/// Disk-based WAV cache keyed by the [`ContentHash`] of model, text, voice
+/// and speed, where `model` is the engine's [`TtsEngine::model_version`]. Entries live
+/// in `<root>/<profile>/<model>/`: profiles don't share audio, and a new
+/// model or voice pack version starts an empty namespace.
+///
+/// Each `<key>.wav` has a `<key>.sha256` sidecar with the digest of its
+/// bytes. Reads check it, so a truncated or corrupted file is deleted and
+/// treated as a miss, to be generated again, instead of served as garbled
+/// audio. Entries written before sidecars existed are adopted if they
+/// decode.
+pub struct AudioCache {
+    root: PathBuf,
+    profile: String,
+}
+
+/// One profile's audio from one model version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheNamespace {
+    pub profile: String,
+    pub model: String,
+    pub entries: u32,
+    pub size_bytes: u64,
+}
+
+/// What [`AudioCache::verify`] found.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheCheck {
+    pub checked: u32,
+    /// Invalid entries deleted; they are generated again when next played.
+    pub removed: u32,
+    /// Sidecars and partial writes left without their WAV, deleted.
+    pub orphans: u32,
+}
+
+impl AudioCache {
+    /// The cache of `profile` under `root`, usually
+    /// [`BokaPaths::audio_cache_dir`](crate::paths::BokaPaths).
+    ///
+    /// Entries from before namespacing sit loose in `root`. Nothing says
+    /// which model made them, so they are deleted.
+    pub fn new(root: &Path, profile: &str) -> Result<Self, AudioError> {
+        fs::create_dir_all(root.join(namespace_dir(profile))).map_err(|e| AudioError::CacheIo(e.to_string()))?;
+        for path in fs::read_dir(root).into_iter().flatten().flatten().map(|entry| entry.path()) {
+            if path.is_file() {
+                let _ = fs::remove_file(path);
+            }
+        }
+        Ok(Self {
+            root: root.to_path_buf(),
+            profile: profile.to_string(),
+        })
+    }
+
+    /// The same sentence in two docs, spacing aside, is one entry.
+    fn cache_key(model: &str, text: &str, voice_id: &str, speed: f32) -> String {
+        ContentHash::of(&[model, &ContentHash::text(text), voice_id, &speed.to_string()]).hex()
+    }
+
+    fn profile_dir(&self) -> PathBuf {
+        self.root.join(namespace_dir(&self.profile))
+    }
+
+    fn cache_path(&self, model: &str, key: &str) -> PathBuf {
+        self.profile_dir().join(namespace_dir(model)).join(format!("{}.wav", key))
+    }
+
+    /// The WAV at `path` if it matches its sidecar; otherwise both files
+    /// are deleted.
+    fn read_verified(path: &Path) -> Option<Vec<u8>> {
+        let bytes = fs::read(path).ok()?;
+        let sidecar = path.with_extension("sha256");
+        let valid = match fs::read_to_string(&sidecar) {
+            Ok(digest) => digest.trim() == checksum(&bytes),
+            Err(_) => decode_wav(&bytes).is_some() && fs::write(&sidecar, checksum(&bytes)).is_ok(),
+        };
+        if valid {
+            return Some(bytes);
+        }
+        let _ = fs::remove_file(path);
+        let _ = fs::remove_file(sidecar);
+        None
+    }
+
+    /// Write `bytes` under `path` with its sidecar. The sidecar goes last,
+    /// so a write cut short leaves an entry that fails verification.
+    fn write_entry(path: &Path, bytes: &[u8]) -> Result<(), AudioError> {
+        let sidecar = path.with_extension("sha256");
+        let _ = fs::remove_file(&sidecar);
+        create_parent(path)?;
+        fs::write(path, bytes).map_err(|e| AudioError::CacheIo(e.to_string()))?;
+        fs::write(sidecar, checksum(bytes)).map_err(|e| AudioError::CacheIo(e.to_string()))
+    }
+
+    fn get_wav(&self, model: &str, text: &str, voice_id: &str, speed: f32) -> Option<Vec<u8>> {
+        let key = Self::cache_key(model, text, voice_id, speed);
+        Self::read_verified(&self.cache_path(model, &key))
+    }
+
+    /// Look up cached WAV and return as base64 if found and intact.
+    pub fn get(&self, model: &str, text: &str, voice_id: &str, speed: f32) -> Option<CachedAudio> {
+        let bytes = self.get_wav(model, text, voice_id, speed)?;
+        let b64 = base64::engine::general_purpose::STANDARD.encode(&bytes);
+        // Parse WAV header to get duration info
+        let (duration_ms, sample_rate) = wav_info(&bytes).unwrap_or((0, 24000));
+        Some(CachedAudio {
+            audio_base64: b64,
+            duration_ms,
+            sample_rate,
+        })
+    }
+
+    /// How long the cached audio of each of `texts` is, `None` where there
+    /// is none. The model's directory is listed once and only the headers
+    /// of entries found are read; entries are not verified against their
+    /// sidecars, playback does that.
+    pub fn probe(&self, model: &str, texts: &[&str], voice_id: &str, speed: f32) -> Vec<Option<u64>> {
+        let dir = self.profile_dir().join(namespace_dir(model));
+        let names: HashSet<String> = fs::read_dir(&dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect();
+        texts
+            .iter()
+            .map(|text| {
+                let name = format!("{}.wav", Self::cache_key(model, text, voice_id, speed));
+                if !names.contains(&name) {
+                    return None;
+                }
+                let reader = hound::WavReader::open(dir.join(name)).ok()?;
+                Some(reader.len() as u64 * 1000 / reader.spec().sample_rate as u64)
+            })
+            .collect()
+    }
+
+    /// Check every entry of this profile against its sidecar, deleting the
+    /// ones that fail and files left without a WAV.
+    pub fn verify(&self) -> Result<CacheCheck, AudioError> {
+        let models = match fs::read_dir(self.profile_dir()) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(CacheCheck::default()),
+            models => models.map_err(|e| AudioError::CacheIo(e.to_string()))?,
+        };
+        let files = models
+            .flatten()
+            .flat_map(|model| fs::read_dir(model.path()).into_iter().flatten().flatten())
+            .map(|entry| entry.path());
+        let mut check = CacheCheck::default();
+        for path in files.filter(|p| p.is_file()) {
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            let orphan =
+                name.ends_with(".wav.tmp") || (name.ends_with(".sha256") && !path.with_extension("wav").exists());
+            if name.ends_with(".wav") {
+                check.checked += 1;
+                if Self::read_verified(&path).is_none() {
+                    check.removed += 1;
+                }
+            } else if orphan && fs::remove_file(&path).is_ok() {
+                check.orphans += 1;
+            }
+        }
+        Ok(check)
+    }
+
+    /// Write PCM f32 samples as WAV to cache and return base64.
+    pub fn put(
+        &self,
+        model: &str,
+        text: &str,
+        voice_id: &str,
+        speed: f32,
+        samples: &[f32],
+        sample_rate: u32,
+    ) -> Result<CachedAudio, AudioError> {
+        let key = Self::cache_key(model, text, voice_id, speed);
+        let path = self.cache_path(model, &key);
+
+        let wav_bytes =
+            encode_wav(samples, sample_rate).map_err(|e| AudioError::WavEncode(e.to_string()))?;
+
+        Self::write_entry(&path, &wav_bytes)?;
+
+        let duration_ms = (samples.len() as u64 * 1000) / sample_rate as u64;
+        let b64 = base64::engine::general_purpose::STANDARD.encode(&wav_bytes);
+
+        Ok(CachedAudio {
+            audio_base64: b64,
+            duration_ms,
+            sample_rate,
+        })
+    }
+
+    /// Stream a long render into the cache instead of building it in memory.
+    fn writer(
+        &self,
+        model: &str,
+        text: &str,
+        voice_id: &str,
+        speed: f32,
+        sample_rate: u32,
+    ) -> Result<CacheWriter, AudioError> {
+        let path = self.cache_path(model, &Self::cache_key(model, text, voice_id, speed));
+        create_parent(&path)?;
+        let tmp = path.with_extension("wav.tmp");
+        let writer =
+            hound::WavWriter::create(&tmp, wav_spec(sample_rate)).map_err(|e| AudioError::CacheIo(e.to_string()))?;
+        Ok(CacheWriter {
+            writer: Some(writer),
+            tmp,
+            path,
+            samples: 0,
+            sample_rate,
+        })
+    }
+
+    /// Calculate this profile's cache size and entry count.
+    pub fn stats(&self) -> (f64, u32) {
+        let namespaces: Vec<CacheNamespace> =
+            self.namespaces().into_iter().filter(|n| n.profile == self.profile).collect();
+        let total_bytes: u64 = namespaces.iter().map(|n| n.size_bytes).sum();
+        let count = namespaces.iter().map(|n| n.entries).sum();
+        let size_mb = total_bytes as f64 / (1024.0 * 1024.0);
+        (size_mb, count)
+    }
+
+    /// Every namespace under the root, of all profiles, by profile then
+    /// model. Sidecars count towards the size only.
+    pub fn namespaces(&self) -> Vec<CacheNamespace> {
+        let mut namespaces = Vec::new();
+        for profile in subdirs(&self.root) {
+            for model in subdirs(&self.root.join(&profile)) {
+                let dir = self.root.join(&profile).join(&model);
+                let mut namespace = CacheNamespace {
+                    profile: profile.clone(),
+                    model,
+                    entries: 0,
+                    size_bytes: 0,
+                };
+                for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+                    let Ok(meta) = entry.metadata() else {
+                        continue;
+                    };
+                    if meta.is_file() {
+                        namespace.size_bytes += meta.len();
+                        if entry.path().extension().is_some_and(|ext| ext == "wav") {
+                            namespace.entries += 1;
+                        }
+                    }
+                }
+                namespaces.push(namespace);
+            }
+        }
+        namespaces
+    }
+
+    /// Delete a profile's audio, or with `model` only that model version's.
+    pub fn clear(&self, profile: &str, model: Option<&str>) -> Result<(), AudioError> {
+        let mut dir = self.root.join(namespace_dir(profile));
+        if let Some(model) = model {
+            dir.push(namespace_dir(model));
+        }
+        match fs::remove_dir_all(&dir) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(AudioError::CacheIo(e.to_string())),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A profile id or model version as one path component.
+fn namespace_dir(name: &str) -> String {
+    match name.replace(['/', '\\'], "_") {
+        dots if dots.chars().all(|c| c == '.') => "_".repeat(dots.len().max(1)),
+        name => name,
+    }
+}
+
+/// Names of the directories in `dir`, sorted.
+fn subdirs(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    names.sort();
+    names
+}
+
+fn create_parent(path: &Path) -> Result<(), AudioError> {
+    match path.parent() {
+        Some(dir) => fs::create_dir_all(dir).map_err(|e| AudioError::CacheIo(e.to_string())),
+        None => Ok(()),
+    }
+}
+
+/// A cache entry being written chunk by chunk. Only [`CacheWriter::finish`]
+/// moves it into place; dropping it early (error, cancellation) deletes it.
+struct CacheWriter {
+    writer: Option<hound::WavWriter<std::io::BufWriter<fs::File>>>,
+    tmp: PathBuf,
+    path: PathBuf,
+    samples: u64,
+    sample_rate: u32,
+}
+
+impl CacheWriter {
+    fn push(&mut self, samples: &[f32]) -> Result<(), AudioError> {
+        self.samples += samples.len() as u64;
+        if self.samples > max_samples(self.sample_rate) {
+            return Err(AudioError::TooLong(MAX_AUDIO_MS / 60_000));
+        }
+        let Some(writer) = self.writer.as_mut() else {
+            return Ok(());
+        };
+        for &sample in samples {
+            writer
+                .write_sample(to_i16(sample))
+                .map_err(|e| AudioError::WavEncode(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<CachedAudio, AudioError> {
+        if let Some(writer) = self.writer.take() {
+            writer.finalize().map_err(|e| AudioError::WavEncode(e.to_string()))?;
+        }
+        let sidecar = self.path.with_extension("sha256");
+        let _ = fs::remove_file(&sidecar);
+        fs::rename(&self.tmp, &self.path).map_err(|e| AudioError::CacheIo(e.to_string()))?;
+        let bytes = fs::read(&self.path).map_err(|e| AudioError::CacheIo(e.to_string()))?;
+        fs::write(sidecar, checksum(&bytes)).map_err(|e| AudioError::CacheIo(e.to_string()))?;
+        Ok(CachedAudio {
+            audio_base64: base64::engine::general_purpose::STANDARD.encode(&bytes),
+            duration_ms: self.samples * 1000 / self.sample_rate as u64,
+            sample_rate: self.sample_rate,
+        })
+    }
+}
+
+impl Drop for CacheWriter {
+    fn drop(&mut self) {
+        if let Some(writer) = self.writer.take() {
+            drop(writer);
+            let _ = fs::remove_file(&self.tmp);
+        }
+    }
+}
+
+pub struct CachedAudio {
+    pub audio_base64: String,
+    pub duration_ms: u64,
+    pub sample_rate: u32,
+}
+
+/// The top-level function that orchestrates speech generation.
+/// Checks cache first, then generates via engine, then caches result.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_speech(
+    engine: &dyn TtsEngine,
+    cache: &AudioCache,
+    text: &str,
+    voice_id: &str,
+    speed: f32,
+    language: &str,
+    cancelled: &Arc<AtomicBool>,
+    mut on_progress: impl FnMut(AudioStage),
+) -> Result<CachedAudio, AudioError> {
+    // Check cancellation
+    if cancelled.load(Ordering::Relaxed) {
+        return Err(AudioError::Cancelled);
+    }
+
+    // Check cache
+    if let Some(cached) = cache.get(&engine.model_version(), text, voice_id, speed) {
+        on_progress(AudioStage::CacheHit);
+        return Ok(cached);
+    }
+
+    // Check model is loaded
+    if !engine.is_loaded() {
+        return Err(AudioError::ModelNotLoaded);
+    }
+
+    if cancelled.load(Ordering::Relaxed) {
+        return Err(AudioError::Cancelled);
+    }
+
+    on_progress(AudioStage::Generating);
+    let chunks = synthesis_chunks(text, MAX_CHUNK_CHARS);
+    if chunks.len() > 1 {
+        // Long text: one sentence chunk in memory at a time, straight to disk.
+        let mut writer = cache.writer(&engine.model_version(), text, voice_id, speed, engine.sample_rate())?;
+        for chunk in &chunks {
+            if cancelled.load(Ordering::Relaxed) {
+                return Err(AudioError::Cancelled);
+            }
+            writer.push(&engine.generate(chunk, voice_id, speed, language)?)?;
+        }
+        on_progress(AudioStage::Encoding);
+        return writer.finish();
+    }
+
+    let samples = engine.generate(text, voice_id, speed, language)?;
+
+    if cancelled.load(Ordering::Relaxed) {
+        return Err(AudioError::Cancelled);
+    }
+
+    on_progress(AudioStage::Encoding);
+    let result = cache.put(&engine.model_version(), text, voice_id, speed, &samples, engine.sample_rate())?;
+
+    Ok(result)
+}
+
+/// A short clip of `word` for flashcards and exports: spoken at normal
+/// speed with the silence the engine leaves around it trimmed (see
+/// [`trim_silence`]). Clips are cached in their own namespace per model
+/// version, `words@<version>`, so clearing them leaves sentence audio alone.
+pub fn generate_word_audio(
+    engine: &dyn TtsEngine,
+    cache: &AudioCache,
+    word: &str,
+    voice_id: &str,
+    language: &str,
+) -> Result<CachedAudio, AudioError> {
+    let word = word.trim();
+    if word.is_empty() || word.chars().count() > MAX_WORD_CHARS || word.contains('\n') {
+        return Err(AudioError::NotAWord(MAX_WORD_CHARS));
+    }
+    let namespace = words_namespace(&engine.model_version());
+    if let Some(cached) = cache.get(&namespace, word, voice_id, 1.0) {
+        return Ok(cached);
+    }
+    if !engine.is_loaded() {
+        return Err(AudioError::ModelNotLoaded);
+    }
+    let samples = engine.generate(word, voice_id, 1.0, language)?;
+    let clip = trim_silence(&samples, engine.sample_rate(), WORD_PADDING_MS);
+    cache.put(&namespace, word, voice_id, 1.0, &clip, engine.sample_rate())
+}
+
+/// The cache namespace of word clips made with model version `model`.
+pub fn words_namespace(model: &str) -> String {
+    format!("words@{}", model)
+}
+
+/// `samples` without the quiet at either end, keeping `padding_ms` of it,
+/// with a short fade so the cut doesn't click. Quiet is anything under 5%
+/// of the peak; audio that is all quiet comes back empty.
+pub fn trim_silence(samples: &[f32], sample_rate: u32, padding_ms: u32) -> Vec<f32> {
+    let peak = samples.iter().fold(0f32, |peak, s| peak.max(s.abs()));
+    let threshold = peak * 0.05;
+    let (Some(first), Some(last)) = (
+        samples.iter().position(|s| s.abs() > threshold),
+        samples.iter().rposition(|s| s.abs() > threshold),
+    ) else {
+        return Vec::new();
+    };
+    let padding = (sample_rate as u64 * padding_ms as u64 / 1000) as usize;
+    let start = first.saturating_sub(padding);
+    let end = (last + 1 + padding).min(samples.len());
+    let mut clip = samples[start..end].to_vec();
+
+    let fade = (sample_rate as usize / 200).min(clip.len() / 2); // 5 ms
+    let len = clip.len();
+    for i in 0..fade {
+        let gain = i as f32 / fade as f32;
+        clip[i] *= gain;
+        clip[len - 1 - i] *= gain;
+    }
+    clip
+}
+
+/// Which blocks and variants of `doc` have audio cached for `voice_id` at
+/// `speed` with model version `model`, probed in one pass (see
+/// [`AudioCache::probe`]). Blocks are keyed by their text as
+/// [`InteractiveDoc::block_texts`] reads it, variants by their own text,
+/// as the reader asks for them.
+pub fn doc_audio_status(
+    cache: &AudioCache,
+    model: &str,
+    doc_id: &str,
+    doc: &InteractiveDoc,
+    voice_id: &str,
+    speed: f32,
+) -> DocAudioStatus {
+    let mut block_spans: Vec<Vec<&str>> = vec![vec![]];
+    for token in &doc.tokens {
+        match token {
+            DocToken::Text { value, .. } if value == "\n\n" => block_spans.push(vec![]),
+            DocToken::Span { span_id, .. } => block_spans.last_mut().unwrap().push(span_id),
+            _ => {}
+        }
+    }
+    let block_texts = doc.block_texts();
+    let mut texts: Vec<&str> = block_texts.iter().map(String::as_str).collect();
+    let mut variants: Vec<(u32, &str, u32)> = Vec::new();
+    for (block, spans) in block_spans.iter().enumerate() {
+        for span in spans.iter().filter_map(|id| doc.spans.get(*id)) {
+            for (i, variant) in span.variants.iter().enumerate() {
+                variants.push((block as u32, &span.id, i as u32));
+                texts.push(&variant.text);
+            }
+        }
+    }
+    let durations = cache.probe(model, &texts, voice_id, speed);
+
+    let mut blocks: Vec<BlockAudioStatus> = durations[..block_texts.len()]
+        .iter()
+        .enumerate()
+        .map(|(block, &duration_ms)| BlockAudioStatus {
+            block: block as u32,
+            duration_ms,
+            variants: Vec::new(),
+        })
+        .collect();
+    for (&(block, span_id, variant_index), &duration_ms) in variants.iter().zip(&durations[block_texts.len()..]) {
+        blocks[block as usize].variants.push(VariantAudioStatus {
+            span_id: span_id.to_string(),
+            variant_index,
+            duration_ms,
+        });
+    }
+    DocAudioStatus {
+        doc_id: doc_id.to_string(),
+        model_version: model.to_string(),
+        voice_id: voice_id.to_string(),
+        speed,
+        blocks,
+        cached: durations.iter().filter(|d| d.is_some()).count() as u32,
+        total: durations.len() as u32,
+    }
+}
+
+/// Split `text` at sentence ends into chunks of at most `max_chars`
+/// characters, packing short sentences together. Longer sentences are
+/// split at the last space (or anywhere, for unspaced scripts).
+pub fn synthesis_chunks(text: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for sentence in sentences(text) {
+        for piece in split_long(sentence, max_chars) {
+            let fits = current.trim().chars().count() + piece.trim_end().chars().count() <= max_chars;
+            if !fits && !current.trim().is_empty() {
+                chunks.push(current.trim().to_string());
+                current.clear();
+            }
+            current.push_str(piece);
+        }
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current.trim().to_string());
+    }
+    chunks
+}
+
+/// Sentences of `text`, each with its closing quotes and trailing space.
+fn sentences(text: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((_, c)) = chars.next() {
+        if !matches!(c, '.' | '!' | '?' | '…' | '。' | '！' | '？' | '\n') {
+            continue;
+        }
+        while let Some(&(_, next)) = chars.peek() {
+            if matches!(next, '"' | '\'' | '”' | '’' | '»' | ')' | ']' | '」' | '』' | '）') {
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        // "3.5" or "e.g." don't end a sentence.
+        let breaks = chars.peek().map_or(true, |(_, n)| n.is_whitespace()) || !c.is_ascii() || c == '\n';
+        if !breaks {
+            continue;
+        }
+        while let Some(&(_, next)) = chars.peek() {
+            if next.is_whitespace() {
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        let end = chars.peek().map_or(text.len(), |&(i, _)| i);
+        out.push(&text[start..end]);
+        start = end;
+    }
+    if start < text.len() {
+        out.push(&text[start..]);
+    }
+    out
+}
+
+fn split_long(sentence: &str, max_chars: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut rest = sentence;
+    while rest.trim_end().chars().count() > max_chars {
+        let limit = rest.char_indices().nth(max_chars).map_or(rest.len(), |(i, _)| i);
+        let cut = rest[..limit]
+            .rfind(char::is_whitespace)
+            .filter(|&i| i > 0)
+            .map(|i| i + rest[i..].chars().next().map_or(1, char::len_utf8))
+            .unwrap_or(limit);
+        pieces.push(&rest[..cut]);
+        rest = &rest[cut..];
+    }
+    if !rest.is_empty() {
+        pieces.push(rest);
+    }
+    pieces
+}
+
+/// Generate `text` a chunk at a time, checking for cancellation between chunks.
+fn synthesize(
+    engine: &dyn TtsEngine,
+    text: &str,
+    voice_id: &str,
+    speed: f32,
+    language: &str,
+    cancelled: &Arc<AtomicBool>,
+) -> Result<Vec<f32>, AudioError> {
+    let mut samples = Vec::new();
+    for chunk in synthesis_chunks(text, MAX_CHUNK_CHARS) {
+        if cancelled.load(Ordering::Relaxed) {
+            return Err(AudioError::Cancelled);
+        }
+        samples.extend(engine.generate(&chunk, voice_id, speed, language)?);
+    }
+    Ok(samples)
+}
+
+/// Split blocks where silence goes: each chunk with the pause after it.
+/// Punctuation only splits when its pause is non-zero, so default options
+/// keep every block as one chunk; the last chunk never gets a pause.
+pub fn plan_pauses(blocks: &[String], pauses: &PauseOptions) -> Vec<(String, u32)> {
+    let mut plan: Vec<(String, u32)> = Vec::new();
+    for block in blocks.iter().map(|b| b.trim()).filter(|b| !b.is_empty()) {
+        if let Some(last) = plan.last_mut() {
+            last.1 = last.1.max(pauses.paragraph_ms);
+        }
+        let mut chunk = String::new();
+        let mut chars = block.chars().peekable();
+        while let Some(c) = chars.next() {
+            chunk.push(c);
+            let pause = match c {
+                ',' | ';' | ':' | '、' | '，' | '；' | '：' => pauses.comma_ms,
+                '.' | '!' | '?' | '…' | '。' | '！' | '？' => pauses.sentence_ms,
+                _ => continue,
+            };
+            // Keep closing quotes and brackets with their sentence.
+            while let Some(&next) = chars.peek() {
+                if matches!(next, '"' | '\'' | '”' | '’' | '»' | ')' | ']' | '」' | '』' | '）') {
+                    chunk.push(next);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            // "3.5" or "e.g." have no break after the mark.
+            let breaks = chars.peek().map_or(true, |n| n.is_whitespace()) || !c.is_ascii();
+            if pause > 0 && breaks && !chunk.trim().is_empty() {
+                plan.push((chunk.trim().to_string(), pause));
+                chunk.clear();
+            }
+        }
+        if !chunk.trim().is_empty() {
+            plan.push((chunk.trim().to_string(), 0));
+        }
+    }
+    if let Some(last) = plan.last_mut() {
+        last.1 = 0;
+    }
+    plan
+}
+
+/// Speak several blocks as one WAV, inserting silence per `pauses`. Each
+/// chunk is cached on its own, so changing pause lengths re-renders
+/// nothing; the joined audio is not cached.
+#[allow(clippy::too_many_arguments)]
+pub fn render_with_pauses(
+    engine: &dyn TtsEngine,
+    cache: &AudioCache,
+    blocks: &[String],
+    voice_id: &str,
+    speed: f32,
+    language: &str,
+    pauses: &PauseOptions,
+    cancelled: &Arc<AtomicBool>,
+    on_progress: impl FnMut(AudioStage),
+) -> Result<CachedAudio, AudioError> {
+    let plan = plan_pauses(blocks, pauses);
+    if let [(text, _)] = plan.as_slice() {
+        return generate_speech(engine, cache, text, voice_id, speed, language, cancelled, on_progress);
+    }
+    let plan: Vec<(String, &str, u32)> = plan.into_iter().map(|(text, pause)| (text, voice_id, pause)).collect();
+    render_plan(engine, cache, &plan, speed, language, cancelled, on_progress)
+}
+
+/// Speak `parts`, each `(text, voice)`, as one WAV, e.g. a block whose
+/// dialogue is read by other voices than its narration (see
+/// [`speakers`](super::speakers)). Silence goes in per `pauses`, with the
+/// comma pause where the voice changes; chunks are cached as in
+/// [`render_with_pauses`].
+#[allow(clippy::too_many_arguments)]
+pub fn render_voices(
+    engine: &dyn TtsEngine,
+    cache: &AudioCache,
+    parts: &[(String, String)],
+    speed: f32,
+    language: &str,
+    pauses: &PauseOptions,
+    cancelled: &Arc<AtomicBool>,
+    on_progress: impl FnMut(AudioStage),
+) -> Result<CachedAudio, AudioError> {
+    let mut plan: Vec<(String, &str, u32)> = Vec::new();
+    for (text, voice) in parts {
+        if let Some(last) = plan.last_mut() {
+            last.2 = last.2.max(pauses.comma_ms);
+        }
+        let chunks = plan_pauses(std::slice::from_ref(text), pauses);
+        plan.extend(chunks.into_iter().map(|(text, pause)| (text, voice.as_str(), pause)));
+    }
+    if let [(text, voice, _)] = plan.as_slice() {
+        return generate_speech(engine, cache, text, voice, speed, language, cancelled, on_progress);
+    }
+    render_plan(engine, cache, &plan, speed, language, cancelled, on_progress)
+}
+
+/// Speak each `(text, voice, pause after)` of `plan` and join them.
+fn render_plan(
+    engine: &dyn TtsEngine,
+    cache: &AudioCache,
+    plan: &[(String, &str, u32)],
+    speed: f32,
+    language: &str,
+    cancelled: &Arc<AtomicBool>,
+    mut on_progress: impl FnMut(AudioStage),
+) -> Result<CachedAudio, AudioError> {
+    let sample_rate = engine.sample_rate();
+    let mut samples: Vec<f32> = Vec::new();
+    for (text, voice_id, pause_ms) in plan {
+        if cancelled.load(Ordering::Relaxed) {
+            return Err(AudioError::Cancelled);
+        }
+        match cache.get_wav(&engine.model_version(), text, voice_id, speed).and_then(|bytes| decode_wav(&bytes)) {
+            Some(cached) => {
+                on_progress(AudioStage::CacheHit);
+                samples.extend(cached);
+            }
+            None => {
+                if !engine.is_loaded() {
+                    return Err(AudioError::ModelNotLoaded);
+                }
+                on_progress(AudioStage::Generating);
+                let chunk = synthesize(engine, text, voice_id, speed, language, cancelled)?;
+                cache.put(&engine.model_version(), text, voice_id, speed, &chunk, sample_rate)?;
+                samples.extend(chunk);
+            }
+        }
+        let silence = (sample_rate as u64 * *pause_ms as u64 / 1000) as usize;
+        samples.resize(samples.len() + silence, 0.0);
+        if samples.len() as u64 > max_samples(sample_rate) {
+            return Err(AudioError::TooLong(MAX_AUDIO_MS / 60_000));
+        }
+    }
+
+    on_progress(AudioStage::Encoding);
+    let wav = encode_wav(&samples, sample_rate).map_err(|e| AudioError::WavEncode(e.to_string()))?;
+    Ok(CachedAudio {
+        audio_base64: base64::engine::general_purpose::STANDARD.encode(&wav),
+        duration_ms: samples.len() as u64 * 1000 / sample_rate as u64,
+        sample_rate,
+    })
+}
+
+/// Align the phonemes of a recorded attempt with the expected ones (edit
+/// distance, every mismatch costing 1) so the UI can mark which syllables
+/// were off. Phonemes compare case-insensitively, ignoring stress and
+/// length marks.
+pub fn align_phonemes(expected: &[ExpectedPhoneme], detected: &[DetectedPhoneme]) -> PronunciationComparison {
+    let key = |p: &str| -> String {
+        p.chars()
+            .filter(|c| !matches!(c, 'ˈ' | 'ˌ' | 'ː' | 'ˑ'))
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    let exp: Vec<String> = expected.iter().map(|p| key(&p.phoneme)).collect();
+    let det: Vec<String> = detected.iter().map(|p| key(&p.phoneme)).collect();
+    let (n, m) = (exp.len(), det.len());
+
+    // cost[i][j]: aligning the first i expected with the first j detected.
+    let mut cost = vec![vec![0u32; m + 1]; n + 1];
+    for (i, row) in cost.iter_mut().enumerate() {
+        row[0] = i as u32;
+    }
+    cost[0] = (0..=m as u32).collect();
+    for i in 1..=n {
+        for j in 1..=m {
+            let diagonal = cost[i - 1][j - 1] + u32::from(exp[i - 1] != det[j - 1]);
+            cost[i][j] = diagonal.min(cost[i - 1][j] + 1).min(cost[i][j - 1] + 1);
+        }
+    }
+
+    // Walk back, preferring the diagonal so substitutions beat a miss plus an extra.
+    let mut steps = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (n, m);
+    while i > 0 || j > 0 {
+        if i > 0 && j > 0 && cost[i][j] == cost[i - 1][j - 1] + u32::from(exp[i - 1] != det[j - 1]) {
+            let status = if exp[i - 1] == det[j - 1] {
+                PhonemeMatch::Correct
+            } else {
+                PhonemeMatch::Substituted
+            };
+            steps.push((status, Some(i - 1), Some(j - 1)));
+            i -= 1;
+            j -= 1;
+        } else if i > 0 && cost[i][j] == cost[i - 1][j] + 1 {
+            steps.push((PhonemeMatch::Missing, Some(i - 1), None));
+            i -= 1;
+        } else {
+            steps.push((PhonemeMatch::Extra, None, Some(j - 1)));
+            j -= 1;
+        }
+    }
+    steps.reverse();
+
+    let mut phonemes = Vec::with_capacity(steps.len());
+    let mut syllables_off: Vec<u32> = Vec::new();
+    let mut last_syllable = None;
+    for (status, e, d) in steps {
+        let e = e.map(|e| &expected[e]);
+        let d = d.map(|d| &detected[d]);
+        // Extras belong to the syllable being said when they were heard.
+        let syllable = e.map(|e| e.syllable).or(last_syllable);
+        last_syllable = syllable;
+        if status != PhonemeMatch::Correct {
+            if let Some(s) = syllable.filter(|s| !syllables_off.contains(s)) {
+                syllables_off.push(s);
+            }
+        }
+        phonemes.push(AlignedPhoneme {
+            status,
+            expected: e.map(|e| e.phoneme.clone()),
+            detected: d.map(|d| d.phoneme.clone()),
+            syllable,
+            start_ms: d.map(|d| d.start_ms),
+            end_ms: d.map(|d| d.end_ms),
+        });
+    }
+    syllables_off.sort_unstable();
+
+    let correct = phonemes.iter().filter(|p| p.status == PhonemeMatch::Correct).count();
+    PronunciationComparison {
+        phonemes,
+        syllables_off,
+        score: if n == 0 { 0.0 } else { correct as f32 / n as f32 },
+    }
+}
+
+/// Encode f32 PCM samples as WAV bytes.
+fn encode_wav(samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, hound::Error> {
+    let mut cursor = Cursor::new(Vec::new());
+    {
+        let mut writer = hound::WavWriter::new(&mut cursor, wav_spec(sample_rate))?;
+        for &sample in samples {
+            writer.write_sample(to_i16(sample))?;
+        }
+        writer.finalize()?;
+    }
+
+    Ok(cursor.into_inner())
+}
+
+/// 16-bit mono, as every cached file is written.
+fn wav_spec(sample_rate: u32) -> hound::WavSpec {
+    hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    }
+}
+
+fn to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+}
+
+/// Parse WAV header to extract duration and sample rate.
+fn wav_info(bytes: &[u8]) -> Option<(u64, u32)> {
+    let cursor = Cursor::new(bytes);
+    let reader = hound::WavReader::new(cursor).ok()?;
+    let spec = reader.spec();
+    let num_samples = reader.len() as u64;
+    let duration_ms = (num_samples * 1000) / spec.sample_rate as u64;
+    Some((duration_ms, spec.sample_rate))
+}
+
+/// Hex SHA256 of a cache entry, as kept in its sidecar.
+fn checksum(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Decode WAV written by [`encode_wav`] back to f32 samples.
+fn decode_wav(bytes: &[u8]) -> Option<Vec<f32>> {
+    let reader = hound::WavReader::new(Cursor::new(bytes)).ok()?;
+    reader
+        .into_samples::<i16>()
+        .map(|s| s.ok().map(|s| s as f32 / i16::MAX as f32))
+        .collect()
+}
@@ -0,0 +1,285 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioRequest {
+    pub text: String,
+    pub language: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voice_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f32>,
+    #[serde(default, skip_serializing_if = "VoiceStyle::is_plain")]
+    pub style: VoiceStyle,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioResponse {
+    pub request_id: String,
+    pub audio_base64: String,
+    pub duration_ms: u64,
+    pub sample_rate: u32,
+    /// Model that produced the audio: a TTS registry id, or `system`.
+    pub model_id: String,
+    /// Spoken by the OS synthesizer because the model wasn't ready.
+    #[serde(default)]
+    pub fallback: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AudioStage {
+    ModelLoading,
+    Generating,
+    Encoding,
+    CacheHit,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioProgressEvent {
+    pub request_id: String,
+    pub stage: AudioStage,
+    /// Stable key from `i18n::MessageKey`.
+    pub message_key: String,
+    /// Localized for the requesting UI locale.
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioErrorEvent {
+    pub request_id: String,
+    pub message_key: String,
+    pub message: String,
+    /// Untranslated technical detail, for logs and bug reports.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioModelStatus {
+    pub downloaded: bool,
+    pub loading: bool,
+    pub ready: bool,
+    pub model_size_bytes: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoiceInfo {
+    pub id: String,
+    pub name: String,
+    pub language: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_url: Option<String>,
+    /// What can be tuned on the voice, with valid ranges.
+    #[serde(default)]
+    pub controls: VoiceControls,
+}
+
+/// Another voice mixed into the one speaking.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoiceMix {
+    pub voice_id: String,
+    /// Share of the blend, within [`VoiceControls::blend_weight`]; the
+    /// speaking voice keeps the rest.
+    pub weight: f32,
+}
+
+/// How a voice is shaped beyond its speed. What an engine supports is in
+/// each voice's [`VoiceControls`]; the plain style works everywhere.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VoiceStyle {
+    /// Voices blended into the speaking one, for a timbre between them.
+    pub blend: Vec<VoiceMix>,
+    /// Keep the voice's own style (accent, intonation) when the text is in
+    /// another language than the voice's.
+    pub force_style: bool,
+}
+
+impl VoiceStyle {
+    pub fn is_plain(&self) -> bool {
+        self.blend.is_empty() && !self.force_style
+    }
+}
+
+/// Valid values of a control.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ControlRange {
+    pub min: f32,
+    pub max: f32,
+    pub default: f32,
+    /// Values between steps are rounded to the nearest one; any value in
+    /// range works when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<f32>,
+}
+
+impl ControlRange {
+    pub fn contains(&self, value: f32) -> bool {
+        (self.min..=self.max).contains(&value)
+    }
+}
+
+/// What can be tuned on a voice. By default only its speed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoiceControls {
+    pub speed: ControlRange,
+    /// Weight of each blended voice; `None` when the voice can't be blended.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blend_weight: Option<ControlRange>,
+    /// Most voices blended into this one.
+    #[serde(default)]
+    pub max_blend: u32,
+    #[serde(default)]
+    pub force_style: bool,
+}
+
+impl Default for VoiceControls {
+    fn default() -> Self {
+        Self {
+            speed: ControlRange {
+                min: 0.5,
+                max: 2.0,
+                default: 1.0,
+                step: None,
+            },
+            blend_weight: None,
+            max_blend: 0,
+            force_style: false,
+        }
+    }
+}
+
+/// Silence inserted when rendering longer text, in milliseconds. A zero
+/// duration leaves that kind of break to the voice's own phrasing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PauseOptions {
+    /// After , ; : and their CJK forms.
+    pub comma_ms: u32,
+    /// After . ! ? and their CJK forms.
+    pub sentence_ms: u32,
+    /// Between blocks (paragraphs).
+    pub paragraph_ms: u32,
+}
+
+/// What caused the TTS model to load.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WarmupTrigger {
+    Startup,
+    DocOpen,
+    FirstUse,
+}
+
+/// Emitted once a model load finishes, successfully or not.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioEngineReadyEvent {
+    pub trigger: WarmupTrigger,
+    /// Registry id of the model that was loaded.
+    pub model_id: String,
+    pub ready: bool,
+    pub elapsed_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Which of a doc's audio is already cached, for play and download
+/// indicators: each block as the reader speaks it, with its active
+/// variants, and each variant of its spans on its own. Only entries for
+/// `voice_id` at `speed` with the current model count.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocAudioStatus {
+    pub doc_id: String,
+    pub model_version: String,
+    pub voice_id: String,
+    pub speed: f32,
+    pub blocks: Vec<BlockAudioStatus>,
+    /// Blocks and variants with audio, out of `total`.
+    pub cached: u32,
+    pub total: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockAudioStatus {
+    pub block: u32,
+    /// Length of the cached audio; unset when there is none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    pub variants: Vec<VariantAudioStatus>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariantAudioStatus {
+    pub span_id: String,
+    pub variant_index: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+}
+
+/// A phoneme the learner was meant to say, with the syllable it belongs to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpectedPhoneme {
+    pub phoneme: String,
+    pub syllable: u32,
+}
+
+/// A phoneme recognised in a recorded attempt, with where it was heard.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetectedPhoneme {
+    pub phoneme: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PhonemeMatch {
+    Correct,
+    /// Said, but as a different phoneme.
+    Substituted,
+    /// Expected but not heard.
+    Missing,
+    /// Heard but not expected.
+    Extra,
+}
+
+/// One step of the alignment. Timing comes from the detected phoneme, so
+/// `Missing` steps have none.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlignedPhoneme {
+    pub status: PhonemeMatch,
+    pub expected: Option<String>,
+    pub detected: Option<String>,
+    /// Syllable of the expected phoneme; for `Extra`, the syllable it was inserted into.
+    pub syllable: Option<u32>,
+    pub start_ms: Option<u64>,
+    pub end_ms: Option<u64>,
+}
+
+/// Expected vs detected pronunciation, for the practice overlay.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PronunciationComparison {
+    pub phonemes: Vec<AlignedPhoneme>,
+    /// Syllables with at least one phoneme that was not `Correct`, in order.
+    pub syllables_off: Vec<u32>,
+    /// Share of expected phonemes said correctly, 0.0–1.0.
+    pub score: f32,
+}
@@ -0,0 +1,262 @@
+//! Who may drive the app from outside its window. The GUI is trusted; every
+//! other entry point (a CLI, a local HTTP server, deep links) is an
+//! [`Interface`] that needs a grant: a token issued from the GUI and an
+//! allowlist of [`Action`]s. Actions that spend provider tokens ask the user
+//! first, through [`Confirmations`], so a local process can't run up a bill
+//! by itself.
+//!
+//! Grants are machine-wide and live in `authz.json` in the top-level data
+//! dir. Only a SHA-256 of each token is stored; the token itself is shown
+//! once, when issued.
+
+use super::paths;
+use super::stories::now_ms;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tokio::sync::oneshot::{self, Receiver, Sender};
+
+const AUTHZ_FILE: &str = "authz.json";
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuthzError {
+    #[error("Authorization I/O error: {0}")]
+    Io(String),
+
+    #[error("Failed to parse authorization grants: {0}")]
+    Parse(String),
+
+    #[error("{0:?} has no access; issue it a token in Settings")]
+    NoGrant(Interface),
+
+    #[error("Missing or wrong token for {0:?}")]
+    BadToken(Interface),
+
+    #[error("{interface:?} is not allowed to {action:?}")]
+    NotAllowed { interface: Interface, action: Action },
+
+    #[error("The request was declined")]
+    Declined,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Interface {
+    Gui,
+    Cli,
+    Http,
+    DeepLink,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Action {
+    ReadLibrary,
+    ModifyLibrary,
+    Export,
+    GenerateSpeech,
+    StartTranslation,
+}
+
+impl Action {
+    /// Actions a new grant allows: reading, not changing or spending.
+    pub const READ_ONLY: [Action; 2] = [Action::ReadLibrary, Action::Export];
+
+    /// Spends provider tokens, so an outside request waits for the user.
+    pub fn needs_confirmation(self) -> bool {
+        matches!(self, Action::StartTranslation)
+    }
+}
+
+/// An authorized request may go ahead, or first needs the user's yes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Allow,
+    Confirm,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Grant {
+    pub interface: Interface,
+    pub token_sha256: String,
+    pub allow: Vec<Action>,
+    pub issued_at: u64,
+}
+
+/// A grant as the settings screen shows it, without the token hash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrantView {
+    pub interface: Interface,
+    pub allow: Vec<Action>,
+    pub issued_at: u64,
+}
+
+/// `authz.json`: at most one grant per interface.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Authz {
+    pub grants: Vec<Grant>,
+}
+
+impl Authz {
+    /// `root` is the top-level data dir, not a profile's.
+    pub fn path(root: &Path) -> PathBuf {
+        root.join(AUTHZ_FILE)
+    }
+
+    /// No file means no grants: only the GUI has access.
+    pub fn load(root: &Path) -> Result<Self, AuthzError> {
+        let path = Self::path(root);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(&path).map_err(|e| AuthzError::Io(e.to_string()))?;
+        serde_json::from_str(&raw).map_err(|e| AuthzError::Parse(e.to_string()))
+    }
+
+    /// Atomic write: tmp file, then rename.
+    pub fn save(&self, root: &Path) -> Result<(), AuthzError> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| AuthzError::Parse(e.to_string()))?;
+        paths::write_atomic(&Self::path(root), json.as_bytes())
+            .map_err(|e| AuthzError::Io(e.to_string()))
+    }
+
+    pub fn views(&self) -> Vec<GrantView> {
+        self.grants
+            .iter()
+            .map(|grant| GrantView {
+                interface: grant.interface,
+                allow: grant.allow.clone(),
+                issued_at: grant.issued_at,
+            })
+            .collect()
+    }
+
+    /// Give `interface` a fresh token allowed `allow`, replacing its old
+    /// grant. Returns the token; it can't be read back later.
+    pub fn issue(&mut self, interface: Interface, allow: &[Action]) -> Result<String, AuthzError> {
+        let token = new_token()?;
+        let mut allow = allow.to_vec();
+        allow.sort_by_key(|action| *action as u8);
+        allow.dedup();
+        self.revoke(interface);
+        self.grants.push(Grant {
+            interface,
+            token_sha256: token_hash(&token),
+            allow,
+            issued_at: now_ms(),
+        });
+        Ok(token)
+    }
+
+    /// Whether there was a grant to remove.
+    pub fn revoke(&mut self, interface: Interface) -> bool {
+        let before = self.grants.len();
+        self.grants.retain(|grant| grant.interface != interface);
+        self.grants.len() != before
+    }
+
+    /// Check a request from `interface` carrying `token`. The GUI needs no
+    /// token and never confirms.
+    pub fn authorize(&self, interface: Interface, token: Option<&str>, action: Action) -> Result<Decision, AuthzError> {
+        if interface == Interface::Gui {
+            return Ok(Decision::Allow);
+        }
+        let grant = self
+            .grants
+            .iter()
+            .find(|grant| grant.interface == interface)
+            .ok_or(AuthzError::NoGrant(interface))?;
+        if token.map(token_hash).as_deref() != Some(grant.token_sha256.as_str()) {
+            return Err(AuthzError::BadToken(interface));
+        }
+        if !grant.allow.contains(&action) {
+            return Err(AuthzError::NotAllowed { interface, action });
+        }
+        Ok(if action.needs_confirmation() {
+            Decision::Confirm
+        } else {
+            Decision::Allow
+        })
+    }
+}
+
+fn token_hash(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.trim().as_bytes()))
+}
+
+/// 32 bytes from the OS's secure random source, as hex.
+fn new_token() -> Result<String, AuthzError> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(|e| AuthzError::Io(format!("no randomness for a token: {}", e)))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// An outside request waiting for the user.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalRequest {
+    pub id: String,
+    pub interface: Interface,
+    pub action: Action,
+    /// One line for the prompt, e.g. the story title and target language.
+    pub summary: String,
+    pub requested_at: u64,
+}
+
+/// Outside requests the user hasn't answered yet. The entry point awaits
+/// the receiver from [`Confirmations::request`] (see [`await_confirmation`])
+/// and goes ahead on `true`; a request dropped unanswered reads as declined.
+#[derive(Debug, Default)]
+pub struct Confirmations {
+    next: u64,
+    pending: Vec<(ExternalRequest, Sender<bool>)>,
+}
+
+impl Confirmations {
+    pub fn request(
+        &mut self,
+        interface: Interface,
+        action: Action,
+        summary: &str,
+    ) -> (ExternalRequest, Receiver<bool>) {
+        self.next += 1;
+        let request = ExternalRequest {
+            id: format!("ext-{}", self.next),
+            interface,
+            action,
+            summary: summary.to_string(),
+            requested_at: now_ms(),
+        };
+        let (tx, rx) = oneshot::channel();
+        self.pending.push((request.clone(), tx));
+        (request, rx)
+    }
+
+    /// Oldest first. Requests nobody waits for any more are dropped.
+    pub fn pending(&mut self) -> Vec<ExternalRequest> {
+        self.pending.retain(|(_, tx)| !tx.is_closed());
+        self.pending.iter().map(|(request, _)| request.clone()).collect()
+    }
+
+    /// Answer a request; false when it is unknown, already answered or
+    /// nobody waits for it any more.
+    pub fn resolve(&mut self, id: &str, approved: bool) -> bool {
+        match self.pending.iter().position(|(request, _)| request.id == id) {
+            Some(i) => self.pending.remove(i).1.send(approved).is_ok(),
+            None => false,
+        }
+    }
+}
+
+/// Wait for the user to answer `rx`, turning a no into an error.
+pub async fn await_confirmation(rx: Receiver<bool>) -> Result<(), AuthzError> {
+    match rx.await {
+        Ok(true) => Ok(()),
+        _ => Err(AuthzError::Declined),
+    }
+}
@@ -0,0 +1,239 @@
+//! Scheduling for heavy work nobody is waiting on: TTS model downloads and
+//! warm-ups that were not asked for by a speech request. Such work is queued
+//! and held until the [`BackgroundPolicy`] allows it: by default once the app
+//! has been idle for a while, and never on a metered connection.
+//!
+//! The scheduler is plain state. The app reports foreground work (jobs,
+//! speech), polls [`BackgroundScheduler::status`] and runs what
+//! [`BackgroundScheduler::take_runnable`] hands out.
+
+use super::audio_types::WarmupTrigger;
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BackgroundMode {
+    /// Run as soon as queued.
+    Always,
+    /// Run once the app has been idle for the policy's `idle_secs`.
+    #[default]
+    WhenIdle,
+    /// Hold everything until the mode changes.
+    Paused,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackgroundPolicy {
+    #[serde(default)]
+    pub mode: BackgroundMode,
+    /// Seconds without foreground work before the app counts as idle.
+    #[serde(default = "default_idle_secs")]
+    pub idle_secs: u32,
+    /// Hold work while the connection is metered. Only some systems say
+    /// (see [`detect_metered`]); elsewhere connections count as unmetered.
+    #[serde(default = "default_avoid_metered")]
+    pub avoid_metered: bool,
+}
+
+fn default_idle_secs() -> u32 {
+    30
+}
+
+fn default_avoid_metered() -> bool {
+    true
+}
+
+impl BackgroundPolicy {
+    pub const MAX_IDLE_SECS: u32 = 3_600;
+}
+
+impl Default for BackgroundPolicy {
+    fn default() -> Self {
+        Self {
+            mode: BackgroundMode::default(),
+            idle_secs: default_idle_secs(),
+            avoid_metered: default_avoid_metered(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum BackgroundTask {
+    /// Download (when missing) and load the TTS model for `language`; the
+    /// default model for `None`.
+    TtsWarmup {
+        language: Option<String>,
+        trigger: WarmupTrigger,
+    },
+}
+
+/// Why queued work is not running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BackgroundHold {
+    Paused,
+    Metered,
+    /// Foreground work is running or ended less than `idle_secs` ago.
+    Busy,
+}
+
+/// Payload of `boka:background:status`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackgroundStatus {
+    pub policy: BackgroundPolicy,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub held_by: Option<BackgroundHold>,
+    /// `None` when the system does not say.
+    pub metered: Option<bool>,
+    pub pending: Vec<BackgroundTask>,
+    pub running: Vec<BackgroundTask>,
+}
+
+pub struct BackgroundScheduler {
+    policy: BackgroundPolicy,
+    metered: Option<bool>,
+    /// Foreground work in progress.
+    busy: u32,
+    last_active: Instant,
+    pending: Vec<BackgroundTask>,
+    running: Vec<BackgroundTask>,
+}
+
+impl BackgroundScheduler {
+    /// Launch counts as activity, so idle-only work waits out `idle_secs`
+    /// after startup too.
+    pub fn new(policy: BackgroundPolicy) -> Self {
+        Self {
+            policy,
+            metered: None,
+            busy: 0,
+            last_active: Instant::now(),
+            pending: Vec::new(),
+            running: Vec::new(),
+        }
+    }
+
+    pub fn policy(&self) -> BackgroundPolicy {
+        self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: BackgroundPolicy) {
+        self.policy = policy;
+    }
+
+    pub fn set_metered(&mut self, metered: Option<bool>) {
+        self.metered = metered;
+    }
+
+    pub fn begin_foreground(&mut self) {
+        self.busy += 1;
+        self.last_active = Instant::now();
+    }
+
+    pub fn end_foreground(&mut self) {
+        self.busy = self.busy.saturating_sub(1);
+        self.last_active = Instant::now();
+    }
+
+    /// Queue `task` unless the same task is already queued or running.
+    pub fn queue(&mut self, task: BackgroundTask) -> bool {
+        if self.pending.contains(&task) || self.running.contains(&task) {
+            return false;
+        }
+        self.pending.push(task);
+        true
+    }
+
+    /// Mark a task handed out by [`take_runnable`](Self::take_runnable) as done.
+    pub fn finish(&mut self, task: &BackgroundTask) {
+        if let Some(i) = self.running.iter().position(|t| t == task) {
+            self.running.remove(i);
+        }
+    }
+
+    pub fn hold_at(&self, now: Instant) -> Option<BackgroundHold> {
+        if self.policy.mode == BackgroundMode::Paused {
+            return Some(BackgroundHold::Paused);
+        }
+        if self.policy.avoid_metered && self.metered == Some(true) {
+            return Some(BackgroundHold::Metered);
+        }
+        let idle_for = now.saturating_duration_since(self.last_active);
+        let idle = self.busy == 0 && idle_for >= Duration::from_secs(self.policy.idle_secs.into());
+        if self.policy.mode == BackgroundMode::WhenIdle && !idle {
+            return Some(BackgroundHold::Busy);
+        }
+        None
+    }
+
+    pub fn status_at(&self, now: Instant) -> BackgroundStatus {
+        BackgroundStatus {
+            policy: self.policy,
+            held_by: self.hold_at(now),
+            metered: self.metered,
+            pending: self.pending.clone(),
+            running: self.running.clone(),
+        }
+    }
+
+    pub fn status(&self) -> BackgroundStatus {
+        self.status_at(Instant::now())
+    }
+
+    /// The queued tasks, now counted as running, when nothing holds them.
+    pub fn take_runnable_at(&mut self, now: Instant) -> Vec<BackgroundTask> {
+        if self.hold_at(now).is_some() {
+            return Vec::new();
+        }
+        let tasks = std::mem::take(&mut self.pending);
+        self.running.extend(tasks.iter().cloned());
+        tasks
+    }
+
+    pub fn take_runnable(&mut self) -> Vec<BackgroundTask> {
+        self.take_runnable_at(Instant::now())
+    }
+}
+
+/// Whether the connection is metered, where the system says: NetworkManager
+/// on Linux. `None` elsewhere, or when `nmcli` is missing. Blocks on a
+/// subprocess.
+pub fn detect_metered() -> Option<bool> {
+    #[cfg(target_os = "linux")]
+    {
+        let output = std::process::Command::new("nmcli")
+            .args(["-t", "-f", "GENERAL.METERED", "device", "show"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        parse_nmcli_metered(&String::from_utf8_lossy(&output.stdout))
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+/// Read `nmcli -t -f GENERAL.METERED device show`: one `GENERAL.METERED:<value>`
+/// line per device, with `yes`, `no`, `yes (guessed)`, `no (guessed)` or
+/// `unknown`. Any metered device makes the connection metered.
+pub fn parse_nmcli_metered(output: &str) -> Option<bool> {
+    let values: Vec<&str> = output
+        .lines()
+        .filter_map(|l| l.trim().strip_prefix("GENERAL.METERED:"))
+        .collect();
+    if values.iter().any(|v| v.starts_with("yes")) {
+        Some(true)
+    } else if values.iter().any(|v| v.starts_with("no")) {
+        Some(false)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,43 @@
+use super::gui_types::TextDirection;
+use super::lemma::base_language;
+
+/// Writing direction of a language code; unknown codes are left-to-right.
+/// Accepts BCP 47 style tags, so "ku-Arab" is RTL while plain "ku" is not.
+pub fn direction_for_language(code: &str) -> TextDirection {
+    let lower = code.trim().to_lowercase();
+    if lower.split(['-', '_']).any(|sub| matches!(sub, "arab" | "hebr" | "syrc" | "thaa" | "nkoo" | "adlm")) {
+        return TextDirection::Rtl;
+    }
+    match base_language(&lower) {
+        "ar" | "he" | "iw" | "fa" | "ur" | "yi" | "ji" | "ps" | "sd" | "ug" | "dv" | "ckb" | "syr" | "arc" => {
+            TextDirection::Rtl
+        }
+        _ => TextDirection::Ltr,
+    }
+}
+
+/// Direction of the first strongly directional character (the Unicode
+/// bidi "first strong" rule, without the full algorithm). `None` when the
+/// text has only digits, punctuation or whitespace.
+pub fn detect_direction(text: &str) -> Option<TextDirection> {
+    text.chars().find_map(|c| {
+        if is_rtl_char(c) {
+            Some(TextDirection::Rtl)
+        } else if c.is_alphabetic() {
+            Some(TextDirection::Ltr)
+        } else {
+            None
+        }
+    })
+}
+
+/// Letters from right-to-left scripts: Hebrew, Arabic, Syriac, Thaana, NKo
+/// and their presentation forms.
+pub fn is_rtl_char(c: char) -> bool {
+    matches!(c as u32,
+        0x0590..=0x08FF
+        | 0xFB1D..=0xFDFF
+        | 0xFE70..=0xFEFF
+        | 0x1E900..=0x1E95F
+    ) && c.is_alphabetic()
+}
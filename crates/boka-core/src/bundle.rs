@@ -0,0 +1,372 @@
+//! `.boka` bundles: one finished doc with its shelf metadata and, if wanted,
+//! its audio, for handing prepared stories to other Callibella users (a
+//! teacher giving the class the week's reading). Imported docs are marked
+//! `shared` on their translation entry and are read-only in the GUI.
+//!
+//! A bundle is the 8-byte [`SIGNATURE`], the format version as a
+//! little-endian `u32`, then one zstd frame holding a stored zip with:
+//!
+//! - `bundle.json`: the [`BundleManifest`], listing every other file with
+//!   its size and SHA-256;
+//! - `doc.jsonl`: the doc in the JSON Lines interchange format (see
+//!   [`jsonl`](super::jsonl));
+//! - `meta.json`: its [`StoryMeta`], when it has one;
+//! - `audio/<block>.wav`: audio for each block, when exported with audio.
+
+use super::export::{self, readalong::BlockAudio, zip::ZipWriter};
+use super::jsonl::{from_jsonl, to_jsonl};
+use super::stories::{self, now_ms, DocId, StoryDoc, StoryError};
+use super::story_meta::{meta_of, StoryMeta};
+
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+pub const SIGNATURE: [u8; 8] = *b"\x89BOKA\r\n\x1a";
+/// Bumped when the layout changes; older apps refuse newer bundles.
+pub const BUNDLE_VERSION: u32 = 1;
+pub const EXTENSION: &str = "boka";
+
+const MANIFEST: &str = "bundle.json";
+const DOC: &str = "doc.jsonl";
+const META: &str = "meta.json";
+/// Largest unpacked bundle accepted, so a crafted file cannot fill memory.
+const MAX_UNPACKED: u64 = 1 << 30;
+/// Imported audio, per doc: `shared_audio/<storyId>/<language>/<block>.wav`.
+const SHARED_AUDIO_DIR: &str = "shared_audio";
+
+#[derive(Debug, thiserror::Error)]
+pub enum BundleError {
+    #[error("Bundle I/O error: {0}")]
+    Io(String),
+
+    #[error("Not a .boka bundle")]
+    NotABundle,
+
+    #[error("Bundle version {0} is newer than this app supports ({BUNDLE_VERSION}); update the app first")]
+    UnsupportedVersion(u32),
+
+    #[error("Damaged bundle: {0}")]
+    Corrupt(String),
+
+    #[error("Cannot bundle this doc: {0}")]
+    Invalid(String),
+
+    #[error(transparent)]
+    Story(#[from] StoryError),
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BundleOptions {
+    /// Shown to whoever imports the bundle.
+    pub author: Option<String>,
+    pub note: Option<String>,
+    /// Render and include audio for every block; needs the TTS engine.
+    pub include_audio: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleFile {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleManifest {
+    pub title: String,
+    pub language: String,
+    pub source_language: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub app_version: String,
+    pub created_at: u64,
+    /// Duration in ms of each block's audio, `None` for a block without;
+    /// empty when the bundle has no audio.
+    #[serde(default)]
+    pub audio: Vec<Option<u64>>,
+    pub files: Vec<BundleFile>,
+}
+
+/// A checked bundle, ready to import.
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    /// Stable for the same doc, so importing a bundle twice updates the
+    /// first copy.
+    pub id: String,
+    pub manifest: BundleManifest,
+    pub story: StoryDoc,
+    pub meta: Option<StoryMeta>,
+    /// WAV bytes per block, as in `manifest.audio`.
+    pub audio: Vec<Option<Vec<u8>>>,
+}
+
+/// Where an imported doc came from; stored as `shared` on its translation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedFrom {
+    pub bundle_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub bundled_at: u64,
+    pub imported_at: u64,
+    /// Durations of the audio that came with it; see [`shared_audio`].
+    #[serde(default)]
+    pub audio: Vec<Option<u64>>,
+}
+
+/// Audio for one block of a shared doc.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedAudio {
+    pub audio_base64: String,
+    pub duration_ms: u64,
+}
+
+/// Pack the doc `doc_id` of `stories` with its metadata and `audio` (one
+/// entry per block, or empty for none).
+pub fn write_bundle(
+    stories: &Value,
+    doc_id: &DocId,
+    audio: &[Option<BlockAudio>],
+    options: &BundleOptions,
+) -> Result<Vec<u8>, BundleError> {
+    let story = stories::find_doc(stories, doc_id)?;
+    let meta = translation_of(stories, doc_id).and_then(meta_of);
+
+    let mut files: Vec<(String, Vec<u8>)> = Vec::new();
+    let doc = to_jsonl(&story).map_err(|e| BundleError::Invalid(e.to_string()))?;
+    files.push((DOC.to_string(), doc.into_bytes()));
+    if let Some(meta) = &meta {
+        let json = serde_json::to_vec_pretty(meta).map_err(|e| BundleError::Invalid(e.to_string()))?;
+        files.push((META.to_string(), json));
+    }
+    let mut durations = Vec::with_capacity(audio.len());
+    for (block, clip) in audio.iter().enumerate() {
+        let Some(clip) = clip else {
+            durations.push(None);
+            continue;
+        };
+        let wav = base64::engine::general_purpose::STANDARD
+            .decode(&clip.wav_base64)
+            .map_err(|e| BundleError::Invalid(format!("audio for block {}: {}", block, e)))?;
+        files.push((format!("audio/{}.wav", block), wav));
+        durations.push(Some(clip.duration_ms));
+    }
+
+    let manifest = BundleManifest {
+        title: story.title.clone(),
+        language: story.language.clone(),
+        source_language: story.source_language.clone(),
+        author: options.author.clone().filter(|a| !a.trim().is_empty()),
+        note: options.note.clone().filter(|n| !n.trim().is_empty()),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: now_ms(),
+        audio: durations,
+        files: files
+            .iter()
+            .map(|(path, bytes)| BundleFile {
+                path: path.clone(),
+                size: bytes.len() as u64,
+                sha256: sha256(bytes),
+            })
+            .collect(),
+    };
+
+    let mut zip = ZipWriter::default();
+    let manifest_json = serde_json::to_vec_pretty(&manifest).map_err(|e| BundleError::Invalid(e.to_string()))?;
+    zip.add(MANIFEST, &manifest_json);
+    for (path, bytes) in &files {
+        zip.add(path, bytes);
+    }
+    let packed = zstd::encode_all(zip.finish().as_slice(), zstd::DEFAULT_COMPRESSION_LEVEL)
+        .map_err(|e| BundleError::Io(e.to_string()))?;
+
+    let mut out = Vec::with_capacity(SIGNATURE.len() + 4 + packed.len());
+    out.extend_from_slice(&SIGNATURE);
+    out.extend_from_slice(&BUNDLE_VERSION.to_le_bytes());
+    out.extend_from_slice(&packed);
+    Ok(out)
+}
+
+/// Unpack a bundle and check it against its manifest.
+pub fn read_bundle(bytes: &[u8]) -> Result<Bundle, BundleError> {
+    let header = SIGNATURE.len() + 4;
+    if bytes.len() < header || bytes[..SIGNATURE.len()] != SIGNATURE {
+        return Err(BundleError::NotABundle);
+    }
+    let version = u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+    if version > BUNDLE_VERSION {
+        return Err(BundleError::UnsupportedVersion(version));
+    }
+
+    let mut unpacked = Vec::new();
+    zstd::stream::Decoder::new(&bytes[header..])
+        .and_then(|decoder| decoder.take(MAX_UNPACKED + 1).read_to_end(&mut unpacked))
+        .map_err(|e| BundleError::Corrupt(e.to_string()))?;
+    if unpacked.len() as u64 > MAX_UNPACKED {
+        return Err(BundleError::Corrupt("too large".to_string()));
+    }
+    let mut entries = export::zip::read_entries(&unpacked).map_err(BundleError::Corrupt)?;
+
+    let manifest_index = entries
+        .iter()
+        .position(|(name, _)| name == MANIFEST)
+        .ok_or_else(|| BundleError::Corrupt(format!("no {}", MANIFEST)))?;
+    let (_, manifest_bytes) = entries.remove(manifest_index);
+    let manifest: BundleManifest = serde_json::from_slice(&manifest_bytes)
+        .map_err(|e| BundleError::Corrupt(format!("{}: {}", MANIFEST, e)))?;
+    if entries.len() != manifest.files.len() {
+        return Err(BundleError::Corrupt(format!(
+            "{} files in the bundle, {} in the manifest",
+            entries.len(),
+            manifest.files.len()
+        )));
+    }
+    for file in &manifest.files {
+        let data = entry(&entries, &file.path)
+            .ok_or_else(|| BundleError::Corrupt(format!("`{}` is missing", file.path)))?;
+        if data.len() as u64 != file.size || sha256(data) != file.sha256 {
+            return Err(BundleError::Corrupt(format!("`{}` does not match the manifest", file.path)));
+        }
+    }
+
+    let doc = entry(&entries, DOC).ok_or_else(|| BundleError::Corrupt(format!("no {}", DOC)))?;
+    let id = sha256(doc)[..16].to_string();
+    let doc = std::str::from_utf8(doc).map_err(|e| BundleError::Corrupt(format!("{}: {}", DOC, e)))?;
+    let story = from_jsonl(doc).map_err(|e| BundleError::Corrupt(format!("{}: {}", DOC, e)))?;
+    if story.language.is_empty() || !story.language.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(BundleError::Corrupt(format!("language `{}`", story.language)));
+    }
+    let meta = entry(&entries, META)
+        .map(|m| serde_json::from_slice(m).map_err(|e| BundleError::Corrupt(format!("{}: {}", META, e))))
+        .transpose()?;
+    let audio = manifest
+        .audio
+        .iter()
+        .enumerate()
+        .map(|(block, duration)| {
+            if duration.is_none() {
+                return Ok(None);
+            }
+            let path = format!("audio/{}.wav", block);
+            entry(&entries, &path)
+                .map(|wav| Some(wav.to_vec()))
+                .ok_or_else(|| BundleError::Corrupt(format!("`{}` is missing", path)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Bundle {
+        id,
+        manifest,
+        story,
+        meta,
+        audio,
+    })
+}
+
+/// Add a bundle's doc to `stories` as story `shared-<bundle id>`, marked
+/// [`SharedFrom`], and write its audio under `data_dir`. Only `stories` is
+/// modified otherwise; the caller saves it.
+pub fn import_bundle(stories: &mut Value, data_dir: &Path, bundle: Bundle) -> Result<DocId, BundleError> {
+    let mut story = bundle.story;
+    story.story_id = format!("shared-{}", bundle.id);
+    let doc_id = stories::put_doc(stories, &story)?;
+
+    let audio_dir = shared_audio_dir(data_dir, &doc_id);
+    for (block, wav) in bundle.audio.iter().enumerate() {
+        if let Some(wav) = wav {
+            export::write_atomic(&audio_dir.join(format!("{}.wav", block)), wav)
+                .map_err(|e| BundleError::Io(e.to_string()))?;
+        }
+    }
+
+    if let Some(meta) = &bundle.meta {
+        stories::set_meta(stories, &doc_id, meta)?;
+    }
+    let shared = SharedFrom {
+        bundle_id: bundle.id,
+        author: bundle.manifest.author,
+        note: bundle.manifest.note,
+        bundled_at: bundle.manifest.created_at,
+        imported_at: now_ms(),
+        audio: bundle.manifest.audio,
+    };
+    let translation = translation_of_mut(stories, &doc_id).ok_or_else(|| StoryError::NotFound(doc_id.to_string()))?;
+    translation["shared"] = serde_json::to_value(&shared).map_err(|e| BundleError::Invalid(e.to_string()))?;
+    Ok(doc_id)
+}
+
+/// Where a translation came from, when it was imported from a bundle.
+pub fn shared_of(translation: &Value) -> Option<SharedFrom> {
+    translation
+        .get("shared")
+        .and_then(|s| serde_json::from_value(s.clone()).ok())
+}
+
+/// The audio that came with the shared doc `doc_id`, per block; empty for
+/// a doc that is not shared or came without audio.
+pub fn shared_audio(stories: &Value, data_dir: &Path, doc_id: &DocId) -> Result<Vec<Option<SharedAudio>>, BundleError> {
+    let translation = translation_of(stories, doc_id).ok_or_else(|| StoryError::NotFound(doc_id.to_string()))?;
+    let Some(shared) = shared_of(translation) else {
+        return Ok(Vec::new());
+    };
+    let dir = shared_audio_dir(data_dir, doc_id);
+    Ok(shared
+        .audio
+        .iter()
+        .enumerate()
+        .map(|(block, duration)| {
+            let duration_ms = (*duration)?;
+            let wav = fs::read(dir.join(format!("{}.wav", block))).ok()?;
+            Some(SharedAudio {
+                audio_base64: base64::engine::general_purpose::STANDARD.encode(wav),
+                duration_ms,
+            })
+        })
+        .collect())
+}
+
+fn shared_audio_dir(data_dir: &Path, doc_id: &DocId) -> PathBuf {
+    data_dir
+        .join(SHARED_AUDIO_DIR)
+        .join(&doc_id.story_id)
+        .join(&doc_id.language)
+}
+
+fn translation_of<'a>(stories: &'a Value, doc_id: &DocId) -> Option<&'a Value> {
+    stories
+        .as_array()?
+        .iter()
+        .find(|s| s.get("id").and_then(Value::as_str) == Some(doc_id.story_id.as_str()))?
+        .get("translations")?
+        .get(&doc_id.language)
+}
+
+fn translation_of_mut<'a>(stories: &'a mut Value, doc_id: &DocId) -> Option<&'a mut Value> {
+    stories
+        .as_array_mut()?
+        .iter_mut()
+        .find(|s| s.get("id").and_then(Value::as_str) == Some(doc_id.story_id.as_str()))?
+        .get_mut("translations")?
+        .get_mut(&doc_id.language)
+}
+
+fn entry<'a>(entries: &'a [(String, Vec<u8>)], name: &str) -> Option<&'a [u8]> {
+    entries.iter().find(|(n, _)| n == name).map(|(_, data)| data.as_slice())
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
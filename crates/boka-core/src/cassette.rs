@@ -0,0 +1,213 @@
+use super::types::ApiError;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const REDACTED: &str = "[REDACTED]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Hit the network and append every exchange to the cassette file.
+    Record,
+    /// Serve exchanges from the cassette file; never touch the network.
+    Replay,
+}
+
+/// One recorded provider exchange, stored as a single JSON line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CassetteEntry {
+    pub url: String,
+    /// Hash of url + request body. Hand-written cassettes may leave it empty.
+    #[serde(default)]
+    pub key: String,
+    #[serde(default)]
+    pub request: Value,
+    pub status: u16,
+    pub response: String,
+}
+
+/// Record-and-replay store for LLM HTTP traffic (JSON Lines on disk).
+///
+/// Enabled with `BOKA_CASSETTE_RECORD=<file>` or `BOKA_CASSETTE_REPLAY=<file>`.
+/// API keys are never written: header values are not recorded and any
+/// occurrence of the key in the url, request or response is redacted.
+#[derive(Debug)]
+pub struct Cassette {
+    mode: CassetteMode,
+    path: PathBuf,
+    replay: Mutex<Option<Vec<(CassetteEntry, bool)>>>,
+    write_lock: Mutex<()>,
+}
+
+impl Cassette {
+    pub fn new(mode: CassetteMode, path: impl Into<PathBuf>) -> Self {
+        Self {
+            mode,
+            path: path.into(),
+            replay: Mutex::new(None),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn from_env() -> Option<Self> {
+        let non_empty = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+
+        if let Some(path) = non_empty("BOKA_CASSETTE_REPLAY") {
+            return Some(Self::new(CassetteMode::Replay, path));
+        }
+        non_empty("BOKA_CASSETTE_RECORD").map(|path| Self::new(CassetteMode::Record, path))
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    pub fn is_replay(&self) -> bool {
+        self.mode == CassetteMode::Replay
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn key(url: &str, body: &Value) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(url);
+        hasher.update("\n");
+        hasher.update(body.to_string());
+        format!("{:x}", hasher.finalize())
+    }
+
+    fn load(&self) -> Result<Vec<(CassetteEntry, bool)>, ApiError> {
+        let raw = fs::read_to_string(&self.path)
+            .map_err(|e| ApiError::Parse(format!("Cassette {}: {}", self.path.display(), e)))?;
+
+        raw.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str::<CassetteEntry>(line)
+                    .map(|e| (e, false))
+                    .map_err(|e| {
+                        ApiError::Parse(format!("Cassette {} line {}: {}", self.path.display(), i + 1, e))
+                    })
+            })
+            .collect()
+    }
+
+    /// Serve the recorded exchange for this request: an exact key match if
+    /// one is unused, otherwise the next unused entry for the same url.
+    pub fn replay(&self, url: &str, body: &Value) -> Result<(u16, String), ApiError> {
+        let mut guard = self.replay.lock().unwrap_or_else(|e| e.into_inner());
+        if guard.is_none() {
+            *guard = Some(self.load()?);
+        }
+        let entries = guard.as_mut().expect("cassette loaded above");
+
+        let key = Self::key(url, body);
+        let idx = entries
+            .iter()
+            .position(|(e, used)| !used && e.key == key)
+            .or_else(|| entries.iter().position(|(e, used)| !used && e.url == url))
+            .ok_or_else(|| ApiError::Parse(format!("Cassette has no recorded response for {}", url)))?;
+
+        let (entry, used) = &mut entries[idx];
+        *used = true;
+        Ok((entry.status, entry.response.clone()))
+    }
+
+    pub fn record(
+        &self,
+        url: &str,
+        body: &Value,
+        status: u16,
+        response: &str,
+        secret: Option<&str>,
+    ) -> Result<(), ApiError> {
+        let redact = |s: &str| match secret.filter(|k| !k.is_empty()) {
+            Some(k) => s.replace(k, REDACTED),
+            None => s.to_string(),
+        };
+
+        let request: Value = serde_json::from_str(&redact(&body.to_string())).unwrap_or(Value::Null);
+        let entry = CassetteEntry {
+            url: redact(url),
+            key: Self::key(url, body),
+            request,
+            status,
+            response: redact(response),
+        };
+        let line = serde_json::to_string(&entry).map_err(|e| ApiError::Parse(e.to_string()))?;
+
+        let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(dir) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(|e| ApiError::Parse(format!("Cassette dir: {}", e)))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| ApiError::Parse(format!("Cassette {}: {}", self.path.display(), e)))?;
+        writeln!(file, "{}", line).map_err(|e| ApiError::Parse(format!("Cassette write: {}", e)))?;
+        Ok(())
+    }
+}
+
+/// POST a JSON body, going through the cassette when one is configured.
+/// Every exchange, replayed or not, is also appended to `transcript`.
+/// Returns the HTTP status and raw response body.
+pub(crate) async fn post_json(
+    client: &reqwest::Client,
+    cassette: Option<&Cassette>,
+    transcript: Option<&Cassette>,
+    url: &str,
+    headers: &[(&str, &str)],
+    secret: Option<&str>,
+    body: &Value,
+) -> Result<(u16, String), ApiError> {
+    let (status, text) = match cassette.filter(|c| c.is_replay()) {
+        Some(c) => c.replay(url, body)?,
+        None => send_json(client, cassette, url, headers, secret, body).await?,
+    };
+    if let Some(t) = transcript {
+        if let Err(e) = t.record(url, body, status, &text, secret) {
+            eprintln!("[TRANSCRIPT] Failed to record exchange: {e}");
+        }
+    }
+    Ok((status, text))
+}
+
+async fn send_json(
+    client: &reqwest::Client,
+    cassette: Option<&Cassette>,
+    url: &str,
+    headers: &[(&str, &str)],
+    secret: Option<&str>,
+    body: &Value,
+) -> Result<(u16, String), ApiError> {
+    let mut req = client
+        .post(url)
+        .header("content-type", "application/json")
+        .json(body);
+    for (name, value) in headers {
+        req = req.header(*name, *value);
+    }
+
+    let response = req.send().await?;
+    let status = response.status().as_u16();
+    let text = response.text().await?;
+
+    if let Some(c) = cassette {
+        if let Err(e) = c.record(url, body, status, &text, secret) {
+            eprintln!("[CASSETTE] Failed to record exchange: {e}");
+        }
+    }
+
+    Ok((status, text))
+}
@@ -0,0 +1,152 @@
+//! The error every Tauri command returns. `code` is stable and meant for
+//! branching in the frontend; `message` is the technical text for logs and
+//! fallbacks; `details` carries whatever numbers the code implies (HTTP
+//! status, limits, segment counts) so the UI doesn't parse them back out of
+//! the message.
+//!
+//! Pipeline and audio errors reuse their [`MessageKey`] codes, so a command
+//! error and a progress event for the same failure agree.
+
+use super::authz::AuthzError;
+use super::i18n::{self, Locale, MessageKey};
+use super::stories::StoryError;
+use super::types::ApiError;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fmt;
+
+/// For failures with nothing more specific to say.
+pub const FAILED: &str = "command.failed";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandError {
+    pub code: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+}
+
+impl CommandError {
+    pub fn new(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            code: code.into(),
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    /// Any other error, under [`FAILED`].
+    pub fn other(err: impl fmt::Display) -> Self {
+        Self::new(FAILED, err.to_string())
+    }
+
+    fn keyed(key: MessageKey, err: impl fmt::Display) -> Self {
+        Self::new(key.as_str(), err.to_string())
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        Self::new(FAILED, message)
+    }
+}
+
+impl From<&str> for CommandError {
+    fn from(message: &str) -> Self {
+        Self::new(FAILED, message)
+    }
+}
+
+impl From<ApiError> for CommandError {
+    fn from(err: ApiError) -> Self {
+        // The locale only picks the message text, which is dropped here.
+        let (key, _) = i18n::api_error(&err, Locale::En);
+        let details = match &err {
+            ApiError::NoApiKey { provider } => Some(json!({ "provider": provider })),
+            ApiError::ApiResponse { status, .. } => Some(json!({ "status": status })),
+            ApiError::InputTooLarge { chars, limit } => Some(json!({ "chars": chars, "limit": limit })),
+            ApiError::ConfirmationRequired { segments } => Some(json!({ "segments": segments })),
+            ApiError::BudgetExceeded { tokens } => Some(json!({ "tokens": tokens })),
+            // Another model or provider is the way out; retrying won't help.
+            ApiError::Refused { provider, model, reason } => Some(json!({
+                "provider": provider,
+                "model": model,
+                "reason": reason,
+                "suggestion": "switchProvider",
+            })),
+            ApiError::Http(_) | ApiError::Parse(_) => None,
+        };
+        Self {
+            details,
+            ..Self::keyed(key, err)
+        }
+    }
+}
+
+#[cfg(feature = "tts")]
+impl From<super::audio::AudioError> for CommandError {
+    fn from(err: super::audio::AudioError) -> Self {
+        use super::audio::AudioError;
+
+        let (key, _) = i18n::audio_error(&err, Locale::En);
+        let details = match &err {
+            AudioError::ModelNotInstalled(model) => Some(json!({ "model": model })),
+            AudioError::TooLong(minutes) => Some(json!({ "maxMinutes": minutes })),
+            AudioError::NotAWord(chars) => Some(json!({ "maxChars": chars })),
+            _ => None,
+        };
+        Self {
+            details,
+            ..Self::keyed(key, err)
+        }
+    }
+}
+
+impl From<StoryError> for CommandError {
+    fn from(err: StoryError) -> Self {
+        let (code, details) = match &err {
+            StoryError::Io(_) => ("storage.io", None),
+            StoryError::Parse(_) => ("storage.corrupt", None),
+            StoryError::InvalidDocId(id) => ("storage.invalidDocId", Some(json!({ "docId": id }))),
+            StoryError::NotFound(id) => ("storage.notFound", Some(json!({ "docId": id }))),
+            StoryError::UnknownRegister(register) => ("storage.unknownRegister", Some(json!({ "register": register }))),
+            StoryError::BlockOutOfRange { doc, block, blocks } => (
+                "storage.blockOutOfRange",
+                Some(json!({ "docId": doc, "block": block, "blocks": blocks })),
+            ),
+        };
+        Self {
+            details,
+            ..Self::new(code, err.to_string())
+        }
+    }
+}
+
+impl From<AuthzError> for CommandError {
+    fn from(err: AuthzError) -> Self {
+        let (code, details) = match &err {
+            AuthzError::Io(_) | AuthzError::Parse(_) => (FAILED, None),
+            AuthzError::NoGrant(interface) => ("authz.noGrant", Some(json!({ "interface": interface }))),
+            AuthzError::BadToken(interface) => ("authz.badToken", Some(json!({ "interface": interface }))),
+            AuthzError::NotAllowed { interface, action } => (
+                "authz.notAllowed",
+                Some(json!({ "interface": interface, "action": action })),
+            ),
+            AuthzError::Declined => ("authz.declined", None),
+        };
+        Self {
+            details,
+            ..Self::new(code, err.to_string())
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! Inline comprehension checks: a true/false question after every few
+//! blocks of a translated doc, written by the model from the source text of
+//! those blocks. Stored as [`DocToken::Check`] at the end of the block it
+//! follows, so block indices and span ids are unaffected.
+
+use super::gui_types::{DocToken, InteractiveDoc};
+use super::text;
+use super::types::ApiError;
+
+use serde_json::Value;
+use std::ops::Range;
+
+/// The blocks each check covers when one follows every `every` blocks of a
+/// doc with `blocks` blocks. A trailing group shorter than `every` gets no
+/// check.
+pub fn check_groups(blocks: usize, every: u32) -> Vec<Range<usize>> {
+    let every = every as usize;
+    if every == 0 {
+        return vec![];
+    }
+    (0..blocks / every).map(|g| g * every..(g + 1) * every).collect()
+}
+
+/// Put each `(block, check)` at the end of that block, after any check
+/// already there. Checks for blocks the doc does not have are dropped.
+pub fn insert_checks(doc: &mut InteractiveDoc, mut checks: Vec<(usize, DocToken)>) {
+    checks.sort_by_key(|(block, _)| *block);
+    let mut checks = checks.into_iter().peekable();
+    let mut tokens = Vec::with_capacity(doc.tokens.len() + checks.len());
+    let mut block = 0;
+    for token in std::mem::take(&mut doc.tokens) {
+        if matches!(&token, DocToken::Text { value, .. } if value == "\n\n") {
+            while let Some((_, check)) = checks.next_if(|(b, _)| *b == block) {
+                tokens.push(check);
+            }
+            block += 1;
+        }
+        tokens.push(token);
+    }
+    tokens.extend(checks.filter(|(b, _)| *b == block).map(|(_, check)| check));
+    doc.tokens = tokens;
+}
+
+/// Parse `{ "question", "answer", "explanation" }`, tolerating code fences
+/// and an answer sent as a string.
+pub(crate) fn parse_check(reply: &str) -> Result<DocToken, ApiError> {
+    let cleaned = reply
+        .trim()
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim();
+    let output = || text::excerpt(cleaned, text::EXCERPT_LEN);
+    let value: Value = serde_json::from_str(cleaned)
+        .map_err(|e| ApiError::Parse(format!("Check JSON parse: {} | output: {}", e, output())))?;
+
+    let field = |key: &str| value.get(key).and_then(Value::as_str).map(|s| s.trim().to_string());
+    let question = field("question")
+        .filter(|q| !q.is_empty())
+        .ok_or_else(|| ApiError::Parse(format!("Check JSON parse: missing `question` | output: {}", output())))?;
+    let answer = match value.get("answer") {
+        Some(Value::Bool(b)) => Some(*b),
+        Some(Value::String(s)) => match s.trim().to_lowercase().as_str() {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+    .ok_or_else(|| ApiError::Parse(format!("Check JSON parse: missing `answer` | output: {}", output())))?;
+
+    Ok(DocToken::Check {
+        question,
+        answer,
+        explanation: field("explanation").filter(|e| !e.is_empty()),
+    })
+}
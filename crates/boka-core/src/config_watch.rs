@@ -0,0 +1,141 @@
+//! Watches the user-editable files in the data dir so edits take effect
+//! without restarting the app. Settings, prompts (`prompts.json` and the
+//! per-language addenda) and templates are read fresh whenever a job or
+//! export starts, so the watcher only has to report what changed; callers
+//! reload whatever they cache (the model registries) and tell the frontend.
+
+use super::export::template::templates_dir;
+use super::prompts::{addenda_dir, PromptOverrides};
+use super::settings::Settings;
+
+use notify::{RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// Editors often save in several steps (truncate, write, rename); changes
+/// are reported once the directory has been quiet this long.
+const SETTLE: Duration = Duration::from_millis(250);
+
+#[derive(Debug, thiserror::Error)]
+pub enum WatchError {
+    #[error("Failed to watch {path}: {message}")]
+    Watch { path: String, message: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfigFile {
+    Settings,
+    Prompts,
+    Templates,
+    /// `models.json`, the LLM model registry.
+    Models,
+    /// `tts_models.json`.
+    TtsModels,
+}
+
+impl ConfigFile {
+    /// Which config `path` belongs to, if any. Temp files written next to
+    /// a config during an atomic save are not config.
+    pub fn classify(data_dir: &Path, path: &Path) -> Option<Self> {
+        if path.starts_with(templates_dir(data_dir)) {
+            let hidden = path.file_name().map_or(true, |n| n.to_string_lossy().starts_with('.'));
+            return (!hidden).then_some(ConfigFile::Templates);
+        }
+        if path.starts_with(addenda_dir(data_dir)) {
+            return Some(ConfigFile::Prompts);
+        }
+        if path.parent() != Some(data_dir) {
+            return None;
+        }
+        if path == Settings::path(data_dir) {
+            return Some(ConfigFile::Settings);
+        }
+        if path == PromptOverrides::path(data_dir) {
+            return Some(ConfigFile::Prompts);
+        }
+        match path.file_name()?.to_str()? {
+            "models.json" => Some(ConfigFile::Models),
+            "tts_models.json" => Some(ConfigFile::TtsModels),
+            _ => None,
+        }
+    }
+
+    /// Check that the file still parses, so a bad edit is reported as soon
+    /// as it is saved rather than when the next job fails.
+    pub fn check(self, data_dir: &Path) -> Result<(), String> {
+        match self {
+            ConfigFile::Settings => Settings::load(data_dir).map(|_| ()).map_err(|e| e.to_string()),
+            ConfigFile::Prompts => PromptOverrides::load(data_dir).map(|_| ()).map_err(|e| e.to_string()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Payload of `boka:config:reloaded`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigReloadedEvent {
+    pub files: Vec<ConfigFile>,
+    /// Problems found while reloading, one per file; the previous config
+    /// stays in effect for those.
+    pub errors: Vec<String>,
+}
+
+/// Keeps watching until dropped.
+pub struct ConfigWatcher {
+    _watcher: notify::RecommendedWatcher,
+}
+
+impl ConfigWatcher {
+    /// Watch `data_dir` and call `on_change` from a background thread with
+    /// the configs that changed, once per burst of edits.
+    pub fn spawn(
+        data_dir: &Path,
+        on_change: impl Fn(BTreeSet<ConfigFile>) + Send + 'static,
+    ) -> Result<Self, WatchError> {
+        let failed = |e: &dyn std::fmt::Display| WatchError::Watch {
+            path: data_dir.display().to_string(),
+            message: e.to_string(),
+        };
+        std::fs::create_dir_all(data_dir).map_err(|e| failed(&e))?;
+
+        let (tx, rx) = mpsc::channel::<PathBuf>();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event {
+                if !event.kind.is_access() {
+                    for path in event.paths {
+                        let _ = tx.send(path);
+                    }
+                }
+            }
+        })
+        .map_err(|e| failed(&e))?;
+        watcher
+            .watch(data_dir, RecursiveMode::Recursive)
+            .map_err(|e| failed(&e))?;
+
+        // Some backends (FSEvents) report canonical paths.
+        let dirs = [data_dir.to_path_buf(), data_dir.canonicalize().map_err(|e| failed(&e))?];
+        let classify = move |path: &Path| dirs.iter().find_map(|dir| ConfigFile::classify(dir, path));
+        thread::spawn(move || {
+            // Ends when the watcher, and with it the sender, is dropped.
+            while let Ok(first) = rx.recv() {
+                let mut changed = BTreeSet::new();
+                changed.extend(classify(&first));
+                while let Ok(path) = rx.recv_timeout(SETTLE) {
+                    changed.extend(classify(&path));
+                }
+                if !changed.is_empty() {
+                    on_change(changed);
+                }
+            }
+        });
+
+        Ok(Self { _watcher: watcher })
+    }
+}
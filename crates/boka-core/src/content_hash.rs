@@ -0,0 +1,38 @@
+//! Content addressing shared by the audio and translation caches: an entry
+//! is named by a hash of everything that shapes it, so the same sentence in
+//! two stories, or twice in one, is produced once.
+
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+/// SHA-256 over length-prefixed parts, so `["ab", "c"]` and `["a", "bc"]`
+/// differ. Text parts go through [`ContentHash::text`] first so spacing
+/// differences between docs don't split entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ContentHash([u8; 32]);
+
+impl ContentHash {
+    pub fn of(parts: &[&str]) -> Self {
+        let mut hasher = Sha256::new();
+        for part in parts {
+            hasher.update((part.len() as u64).to_le_bytes());
+            hasher.update(part.as_bytes());
+        }
+        Self(hasher.finalize().into())
+    }
+
+    /// `text` trimmed with each run of whitespace made one space.
+    pub fn text(text: &str) -> String {
+        text.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    pub fn hex(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for ContentHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+    }
+}
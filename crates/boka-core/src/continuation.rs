@@ -0,0 +1,265 @@
+//! Co-writing: the model writes the next part of a story in its source
+//! language, and only that part goes through the translation pipeline,
+//! with the settings of the job that made the doc. Its segments and blocks
+//! are added after the existing ones, so the doc grows in place; progress
+//! is reported as the whole, extended job and doc. A content policy set
+//! now (child-safe mode) wins over the job's, and under a moderated policy
+//! a new part that fails [`moderation::screen`] is refused.
+
+use super::annotate::AnnotationPlan;
+use super::gui_types::{InteractiveDoc, TranslationJob};
+use super::limits;
+use super::lint::lint_doc;
+use super::moderation;
+use super::policy::ContentPolicy;
+use super::prompts::PromptOverrides;
+use super::report::RunReport;
+use super::stories::now_ms;
+use super::translation::{
+    fill_anthropic_key, run_translation_with_report, Client, DocSink, JobSink, PromptOptions, TranslationArgs,
+    TranslationResult,
+};
+use super::translation_cache::TranslationCache;
+use super::types::{ApiError, LlmProviderConfig};
+
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ContinuationLength {
+    Short,
+    #[default]
+    Medium,
+    Long,
+}
+
+impl ContinuationLength {
+    /// Words asked of the model; it is a target, not a limit.
+    pub fn words(self) -> u32 {
+        match self {
+            ContinuationLength::Short => 150,
+            ContinuationLength::Medium => 350,
+            ContinuationLength::Long => 800,
+        }
+    }
+}
+
+/// Input of [`continue_story`]. Everything else is taken from the job's
+/// metadata.
+pub struct ContinueArgs {
+    pub job: TranslationJob,
+    pub doc: InteractiveDoc,
+    /// The story so far, in its source language.
+    pub story_text: String,
+    /// Where the learner wants the story to go; the model's choice when unset.
+    pub direction_hint: Option<String>,
+    pub length: ContinuationLength,
+    pub prompt_overrides: PromptOverrides,
+    /// Defaults to the job's provider and model.
+    pub provider: Option<LlmProviderConfig>,
+    /// Overrides the job's policy, as child-safe mode does.
+    pub content_policy: Option<ContentPolicy>,
+    pub translation_cache: Option<Arc<TranslationCache>>,
+    /// Appended to, like `TranslationArgs::transcript`.
+    pub transcript: Option<PathBuf>,
+    /// For the user's data the job's annotation passes read; built-in data
+    /// only when unset.
+    pub annotation_data_dir: Option<PathBuf>,
+    pub cancelled: Arc<AtomicBool>,
+    pub on_job: Box<dyn JobSink>,
+    pub on_doc: Box<dyn DocSink>,
+}
+
+pub struct Continuation {
+    /// The new part, as the model wrote it.
+    pub text: String,
+    /// `story_text` with the new part after a blank line.
+    pub story_text: String,
+    /// The extended job and doc. `usage` includes writing the new part.
+    pub result: TranslationResult,
+    /// Report of the run that translated the new part.
+    pub report: RunReport,
+}
+
+/// Write the next part of the story and translate it onto the doc. A part
+/// that fails to translate leaves the job and doc as they were.
+pub async fn continue_story(args: ContinueArgs) -> Result<Continuation, ApiError> {
+    let ContinueArgs {
+        job,
+        doc,
+        story_text,
+        direction_hint,
+        length,
+        prompt_overrides,
+        provider,
+        content_policy,
+        translation_cache,
+        transcript,
+        annotation_data_dir,
+        cancelled,
+        on_job,
+        on_doc,
+    } = args;
+
+    let Some(meta) = job.metadata.clone() else {
+        return Err(ApiError::Parse("Job has no metadata to continue with".to_string()));
+    };
+    if story_text.trim().is_empty() {
+        return Err(ApiError::Parse("The story has no source text to continue".to_string()));
+    }
+    let provider = provider.unwrap_or_else(|| LlmProviderConfig {
+        preset: meta.provider,
+        model: Some(meta.model.clone()),
+        ..Default::default()
+    });
+    let content_policy = content_policy.unwrap_or_else(|| meta.content_policy.clone());
+
+    let mut cfg = PromptOptions {
+        target_language: meta.target_language.clone(),
+        source_language: meta.source_language.clone(),
+        adult_mode: false,
+        content_policy: Some(content_policy.clone()),
+        dense_spans: meta.dense_spans,
+        simplify_level: meta.simplify_level,
+        prompt_overrides: prompt_overrides.clone(),
+        refine_variants: meta.refine_variants,
+        granularity: meta.granularity,
+        ui_language: meta.ui_language.clone(),
+        provider: provider.clone(),
+    }
+    .config();
+    cfg.transcript = transcript.as_deref().map(|path| Arc::new(super::transcript::recorder(path)));
+    fill_anthropic_key(&mut cfg);
+    // A long story is chunked for translation; its last chapter is enough
+    // to go on from.
+    let chapters = limits::chunk_chapters(&story_text, limits::CHAPTER_CHARS);
+    let context = chapters.last().map_or(story_text.as_str(), String::as_str);
+    let client = Client::new(cfg)?;
+    let (text, writing) = client
+        .continue_story(context, direction_hint.as_deref(), length.words())
+        .await?;
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err(ApiError::Parse("The model wrote no continuation".to_string()));
+    }
+    if let Some(word) = content_policy.moderation.then(|| moderation::screen(&text)).flatten() {
+        return Err(ApiError::Refused {
+            provider: format!("{:?}", client.preset()).to_lowercase(),
+            model: client.model().to_string(),
+            reason: format!("moderation: `{}`", word),
+        });
+    }
+
+    let (result, report) = run_translation_with_report(TranslationArgs {
+        story_text: text.clone(),
+        // Its own run report; the job keeps its id.
+        job_id: format!("{}-part-{}", job.id, now_ms()),
+        target_language: meta.target_language.clone(),
+        source_language: meta.source_language.clone(),
+        adult_mode: false,
+        content_policy: Some(content_policy),
+        dense_spans: meta.dense_spans,
+        reproducible: meta.reproducible,
+        prompt_overrides,
+        judge: None,
+        variant_bounds: meta.variant_bounds,
+        length_guard: meta.length_guard,
+        simplify_level: meta.simplify_level,
+        dual_output: meta.dual_output,
+        refine_variants: meta.refine_variants,
+        granularity: meta.granularity,
+        comprehension_every: meta.comprehension_every,
+        annotations: AnnotationPlan {
+            passes: meta.annotations.clone(),
+            data_dir: annotation_data_dir,
+            registry: None,
+        },
+        review: None,
+        error_policy: meta.error_policy,
+        budget: None,
+        budget_gate: None,
+        confirmation: None,
+        translation_cache,
+        transcript,
+        ui_language: meta.ui_language.clone(),
+        provider,
+        cancelled,
+        on_job: Box::new(Extended {
+            base: job.clone(),
+            inner: on_job,
+        }),
+        on_doc: Box::new(Extended {
+            base: doc.clone(),
+            inner: on_doc,
+        }),
+    })
+    .await;
+    let part = result?;
+
+    let mut usage = part.usage;
+    usage += writing;
+    let mut result = TranslationResult {
+        job: extend_job(&job, &part.job),
+        doc: extend_doc(&doc, &part.doc),
+        usage,
+    };
+    result.job.lints = lint_doc(&result.doc);
+    Ok(Continuation {
+        story_text: format!("{}\n\n{}", story_text.trim_end(), text),
+        text,
+        result,
+        report,
+    })
+}
+
+/// `job` with the segments of `part` after its own, numbered on from its
+/// last segment and chapter.
+pub fn extend_job(job: &TranslationJob, part: &TranslationJob) -> TranslationJob {
+    let mut extended = job.clone();
+    let first_chapter = job.segments.iter().map(|s| s.chapter + 1).max().unwrap_or(0);
+    for segment in &part.segments {
+        let mut segment = segment.clone();
+        segment.id = format!("seg-{}", extended.segments.len() + 1);
+        segment.chapter += first_chapter;
+        extended.segments.push(segment);
+    }
+    extended.ready = job.ready && part.ready;
+    extended.awaiting_review = part.awaiting_review;
+    extended
+}
+
+/// `doc` with the blocks of `part` after its own.
+pub fn extend_doc(doc: &InteractiveDoc, part: &InteractiveDoc) -> InteractiveDoc {
+    let mut extended = doc.clone();
+    extended.append_blocks(part.clone());
+    extended
+}
+
+/// Forwards the part's progress as the extended job or doc.
+struct Extended<T, S: ?Sized> {
+    base: T,
+    inner: Box<S>,
+}
+
+impl JobSink for Extended<TranslationJob, dyn JobSink> {
+    fn call<'a>(&'a mut self, part: &'a TranslationJob) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>> {
+        Box::pin(async move {
+            let job = extend_job(&self.base, part);
+            self.inner.call(&job).await;
+        })
+    }
+}
+
+impl DocSink for Extended<InteractiveDoc, dyn DocSink> {
+    fn call<'a>(&'a mut self, part: &'a InteractiveDoc) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>> {
+        Box::pin(async move {
+            let doc = extend_doc(&self.base, part);
+            self.inner.call(&doc).await;
+        })
+    }
+}
@@ -0,0 +1,113 @@
+//! Offline demo: a short bundled story with hand-written translations, span
+//! plans and variants, replayed by the mock provider when its base URL is
+//! [`DEMO_BASE_URL`]. New users can try the interactive doc and audio before
+//! configuring a provider; no API key is needed.
+//!
+//! Segments outside the demo story, and target languages it has no
+//! translation for, get the unscripted mock's echo.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+/// Mock provider `baseUrl` that selects the bundled demo instead of a
+/// fixture file.
+pub const DEMO_BASE_URL: &str = "demo";
+
+const BUNDLED_DEMO: &str = include_str!("../data/demo.json");
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DemoData {
+    title: String,
+    source_language: String,
+    text: String,
+    languages: BTreeMap<String, Vec<DemoSegment>>,
+}
+
+/// One sentence of the story in one language. The first variant of each
+/// span is its text in `text`.
+#[derive(Debug, Deserialize)]
+struct DemoSegment {
+    source: String,
+    text: String,
+    spans: Vec<Vec<Value>>,
+}
+
+fn data() -> &'static DemoData {
+    static DATA: OnceLock<DemoData> = OnceLock::new();
+    DATA.get_or_init(|| serde_json::from_str(BUNDLED_DEMO).expect("bundled demo.json is valid"))
+}
+
+/// The demo story, to be added to the library and translated with the
+/// demo provider into one of `languages`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DemoStory {
+    pub title: String,
+    pub source_language: String,
+    pub text: String,
+    pub languages: Vec<String>,
+}
+
+pub fn demo_story() -> DemoStory {
+    let data = data();
+    DemoStory {
+        title: data.title.clone(),
+        source_language: data.source_language.clone(),
+        text: data.text.clone(),
+        languages: data.languages.keys().cloned().collect(),
+    }
+}
+
+/// The demo's replies for one target language, in the form a model would
+/// send them.
+pub(crate) struct DemoBook {
+    segments: &'static [DemoSegment],
+}
+
+impl DemoBook {
+    pub(crate) fn new(language: &str) -> Self {
+        Self {
+            segments: data().languages.get(language).map_or(&[], Vec::as_slice),
+        }
+    }
+
+    pub(crate) fn translation(&self, source: &str) -> Option<String> {
+        let source = source.trim();
+        self.segments.iter().find(|s| s.source == source).map(|s| s.text.clone())
+    }
+
+    /// A span plan for `base`: static text around each span's first variant.
+    pub(crate) fn plan(&self, base: &str) -> Option<String> {
+        let segment = self.segments.iter().find(|s| s.text == base.trim())?;
+        let mut parts = Vec::new();
+        let mut rest = segment.text.as_str();
+        for (i, variants) in segment.spans.iter().enumerate() {
+            let anchor = variants.first()?.get("text")?.as_str()?;
+            let at = rest.find(anchor)?;
+            if at > 0 {
+                parts.push(json!({ "type": "static", "text": &rest[..at] }));
+            }
+            parts.push(json!({
+                "type": "swappable",
+                "id": format!("s{}", i + 1),
+                "variants": [variants[0]],
+            }));
+            rest = &rest[at + anchor.len()..];
+        }
+        if !rest.is_empty() {
+            parts.push(json!({ "type": "static", "text": rest }));
+        }
+        Some(json!([{ "id": "b1", "segments": parts }]).to_string())
+    }
+
+    pub(crate) fn variants(&self, anchor: &str) -> Option<String> {
+        self.segments
+            .iter()
+            .flat_map(|s| &s.spans)
+            .find(|variants| variants.first().and_then(|v| v.get("text")).and_then(Value::as_str) == Some(anchor))
+            .map(|variants| Value::Array(variants.clone()).to_string())
+    }
+}
@@ -0,0 +1,128 @@
+//! Parsed docs kept in memory for the session, least recently used dropped
+//! first, so opening or exporting the same docs again does not re-read and
+//! re-parse the whole of `stories.json`.
+//!
+//! Everything is dropped when `stories.json` changes: after any
+//! [`stories::save`] in this process, and when the file's size or
+//! modification time moves (a TUI write, a sync tool).
+
+use super::stories::{self, DocId, StoryDoc, StoryError};
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocCacheStats {
+    pub capacity: u32,
+    pub len: u32,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// What the cached docs were parsed from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Stamp {
+    data_dir: PathBuf,
+    saves: u64,
+    len: Option<u64>,
+    modified: Option<SystemTime>,
+}
+
+impl Stamp {
+    pub(crate) fn of(data_dir: &Path) -> Self {
+        let saves = stories::save_count();
+        let meta = fs::metadata(stories::path(data_dir)).ok();
+        Self {
+            data_dir: data_dir.to_path_buf(),
+            saves,
+            len: meta.as_ref().map(|m| m.len()),
+            modified: meta.and_then(|m| m.modified().ok()),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DocCache {
+    capacity: usize,
+    stamp: Option<Stamp>,
+    /// Most recently used last.
+    entries: Vec<(DocId, Arc<StoryDoc>)>,
+    hits: u64,
+    misses: u64,
+}
+
+impl DocCache {
+    /// Keeps up to `capacity` docs; 0 keeps none.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ..Default::default()
+        }
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        let excess = self.entries.len().saturating_sub(capacity);
+        self.entries.drain(..excess);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.stamp = None;
+    }
+
+    /// The doc `doc_id` in `data_dir`, parsed from `stories.json` only when
+    /// it is not cached or the file changed.
+    pub fn get(&mut self, data_dir: &Path, doc_id: &DocId) -> Result<Arc<StoryDoc>, StoryError> {
+        // Taken before reading: a write in between changes the stamp, so the
+        // next call starts over instead of trusting what was read.
+        let stamp = Stamp::of(data_dir);
+        if self.stamp.as_ref() != Some(&stamp) {
+            self.entries.clear();
+            self.stamp = Some(stamp);
+        }
+
+        if let Some(index) = self.entries.iter().position(|(id, _)| id == doc_id) {
+            self.hits += 1;
+            let entry = self.entries.remove(index);
+            let doc = entry.1.clone();
+            self.entries.push(entry);
+            return Ok(doc);
+        }
+
+        self.misses += 1;
+        let doc = Arc::new(stories::find_doc(&stories::load(data_dir)?, doc_id)?);
+        if self.capacity > 0 {
+            if self.entries.len() >= self.capacity {
+                self.entries.remove(0);
+            }
+            self.entries.push((doc_id.clone(), doc.clone()));
+        }
+        Ok(doc)
+    }
+
+    pub fn stats(&self) -> DocCacheStats {
+        DocCacheStats {
+            capacity: self.capacity as u32,
+            len: self.entries.len() as u32,
+            hits: self.hits,
+            misses: self.misses,
+        }
+    }
+
+    /// Rough memory held by the cached docs: their text and serialized size.
+    pub fn approx_bytes(&self) -> u64 {
+        self.entries
+            .iter()
+            .map(|(_, doc)| {
+                let json = serde_json::to_vec(&doc.doc).map_or(0, |v| v.len())
+                    + doc.job.as_ref().and_then(|job| serde_json::to_vec(job).ok()).map_or(0, |v| v.len());
+                (doc.source_text.len() + doc.title.len() + json) as u64
+            })
+            .sum()
+    }
+}
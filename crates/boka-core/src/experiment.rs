@@ -0,0 +1,184 @@
+use super::gui_types::{ErrorPolicy, Granularity, InteractiveDoc, TranslationJob};
+use super::length_guard::LengthGuard;
+use super::policy::ContentPolicy;
+use super::prompts::PromptOverrides;
+use super::settings::VariantBounds;
+use super::translation::{run_translation, split_into_segments, TranslationArgs};
+use super::types::{ApiError, LlmProviderConfig, ModelRegistry, Usage};
+
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::time::Instant;
+
+/// One side of a prompt experiment: a provider/model plus optional prompt overrides.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExperimentArm {
+    pub label: String,
+    pub provider: LlmProviderConfig,
+    #[serde(default)]
+    pub prompts: PromptOverrides,
+}
+
+pub struct ExperimentArgs {
+    pub story_text: String,
+    pub target_language: String,
+    pub source_language: Option<String>,
+    pub content_policy: Option<ContentPolicy>,
+    pub dense_spans: bool,
+    /// Only run the first N segments, to keep experiments cheap.
+    pub max_segments: Option<usize>,
+    pub arms: Vec<ExperimentArm>,
+    pub cancelled: Arc<AtomicBool>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArmOutcome {
+    pub label: String,
+    pub model: String,
+    pub usage: Usage,
+    /// `None` when the model has no pricing in the registry.
+    pub estimated_cost_usd: Option<f64>,
+    pub elapsed_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job: Option<TranslationJob>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc: Option<InteractiveDoc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// One source segment with each arm's base translation, in arm order.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparisonRow {
+    pub segment_id: String,
+    pub source: String,
+    pub outputs: Vec<Option<String>>,
+    pub identical: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExperimentReport {
+    pub arms: Vec<ArmOutcome>,
+    pub rows: Vec<ComparisonRow>,
+}
+
+/// Run the same segments through every arm, one after the other, and line
+/// the results up side by side. A failing arm is reported, not fatal.
+pub async fn run_prompt_experiment(args: ExperimentArgs) -> Result<ExperimentReport, ApiError> {
+    if args.arms.len() < 2 {
+        return Err(ApiError::Parse("An experiment needs at least two arms".to_string()));
+    }
+
+    let mut segments = split_into_segments(&args.story_text);
+    if segments.is_empty() {
+        return Err(ApiError::Parse("No segments".to_string()));
+    }
+    if let Some(n) = args.max_segments {
+        segments.truncate(n.max(1));
+    }
+    let story_text = segments.join(" ");
+
+    let registry = ModelRegistry::current();
+    let mut outcomes = Vec::with_capacity(args.arms.len());
+
+    for (i, arm) in args.arms.into_iter().enumerate() {
+        if args.cancelled.load(Ordering::Relaxed) {
+            return Err(ApiError::Parse("Cancelled".to_string()));
+        }
+
+        let t0 = Instant::now();
+        let result = run_translation(TranslationArgs {
+            story_text: story_text.clone(),
+            job_id: format!("experiment-arm-{}", i + 1),
+            target_language: args.target_language.clone(),
+            source_language: args.source_language.clone(),
+            adult_mode: false,
+            content_policy: args.content_policy.clone(),
+            dense_spans: args.dense_spans,
+            // Arms should differ only in what the experiment varies.
+            reproducible: true,
+            prompt_overrides: arm.prompts,
+            judge: None,
+            variant_bounds: VariantBounds::default(),
+            length_guard: LengthGuard::default(),
+            simplify_level: None,
+            dual_output: false,
+            refine_variants: false,
+            granularity: Granularity::Sentence,
+            comprehension_every: None,
+            annotations: Default::default(),
+            review: None,
+            error_policy: ErrorPolicy::Abort,
+            budget: None,
+            budget_gate: None,
+            confirmation: None,
+            translation_cache: None,
+            transcript: None,
+            ui_language: None,
+            provider: arm.provider.clone(),
+            cancelled: args.cancelled.clone(),
+            on_job: Box::new(|_: &TranslationJob| async {}),
+            on_doc: Box::new(|_: &InteractiveDoc| async {}),
+        })
+        .await;
+        let elapsed_ms = t0.elapsed().as_millis() as u64;
+
+        let outcome = match result {
+            Ok(done) => {
+                let meta = done.job.metadata.as_ref();
+                let model = meta.map(|m| m.model.clone()).unwrap_or_default();
+                let cost = meta
+                    .and_then(|m| registry.pricing(m.provider, &m.model))
+                    .map(|p| p.cost_usd(&done.usage));
+                ArmOutcome {
+                    label: arm.label,
+                    model,
+                    usage: done.usage,
+                    estimated_cost_usd: cost,
+                    elapsed_ms,
+                    job: Some(done.job),
+                    doc: Some(done.doc),
+                    error: None,
+                }
+            }
+            Err(e) => ArmOutcome {
+                label: arm.label,
+                model: arm.provider.model.unwrap_or_default(),
+                usage: Usage::default(),
+                estimated_cost_usd: None,
+                elapsed_ms,
+                job: None,
+                doc: None,
+                error: Some(e.to_string()),
+            },
+        };
+        outcomes.push(outcome);
+    }
+
+    let rows = segments
+        .iter()
+        .enumerate()
+        .map(|(si, source)| {
+            let outputs: Vec<Option<String>> = outcomes
+                .iter()
+                .map(|o| o.job.as_ref()?.segments.get(si)?.base_text.clone())
+                .collect();
+            let identical = outputs.iter().all(|o| o.is_some() && *o == outputs[0]);
+            ComparisonRow {
+                segment_id: format!("seg-{}", si + 1),
+                source: source.clone(),
+                outputs,
+                identical,
+            }
+        })
+        .collect();
+
+    Ok(ExperimentReport { arms: outcomes, rows })
+}
@@ -0,0 +1,250 @@
+//! Classroom pack: one zip with everything a teacher needs for a lesson on
+//! a finished doc — the doc itself, a printable copy, a cloze worksheet, a
+//! vocabulary list and a translation quiz.
+//!
+//! Given the user's [`KnownWords`], the cloze worksheet leaves mastered
+//! words in place and the quiz asks about spans with unknown words first.
+
+use super::readalong::readalong_html;
+use super::table::{table, Delimiter, TableKind};
+use super::zip::ZipWriter;
+use super::{pdf, word_ranges, ExportError};
+use crate::gui_types::{DocToken, InteractiveDoc};
+use crate::lemma;
+use crate::stories::StoryDoc;
+use crate::vocab_ledger::KnownWords;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ClassroomPackOptions {
+    /// Blank every n-th eligible word in the cloze worksheet (at least 2).
+    pub cloze_every: usize,
+    pub quiz_questions: usize,
+    /// Apply the vocabulary ledger; off for a pack meant for a whole class.
+    pub skip_known_words: bool,
+    /// Where to write the zip; the exports folder when unset.
+    pub output_path: Option<String>,
+}
+
+impl Default for ClassroomPackOptions {
+    fn default() -> Self {
+        Self {
+            cloze_every: 7,
+            quiz_questions: 10,
+            skip_known_words: true,
+            output_path: None,
+        }
+    }
+}
+
+pub struct ClassroomPack {
+    /// Zip file bytes.
+    pub bytes: Vec<u8>,
+    /// Names of the files inside, in zip order.
+    pub files: Vec<String>,
+}
+
+/// Build the pack. The printable copy is `story.pdf`, or `story.html` when
+/// the text uses a script the built-in PDF fonts cannot show. The quiz is
+/// left out when the doc has fewer than two distinct spans. `known` is used
+/// only with `options.skip_known_words`.
+pub fn classroom_pack(
+    story: &StoryDoc,
+    options: &ClassroomPackOptions,
+    known: &KnownWords,
+) -> Result<ClassroomPack, ExportError> {
+    let none = KnownWords::default();
+    let known = if options.skip_known_words { known } else { &none };
+    let mut zip = ZipWriter::default();
+    let mut files = Vec::new();
+    let mut add = |name: &str, data: &[u8]| {
+        zip.add(name, data);
+        files.push(name.to_string());
+    };
+
+    let doc_json = serde_json::to_vec_pretty(&story.doc).map_err(|e| ExportError::Serialize(e.to_string()))?;
+    add("doc.json", &doc_json);
+
+    let blocks = story.doc.block_texts();
+    let title = display_title(story);
+    if pdf::is_printable(title) && blocks.iter().all(|b| pdf::is_printable(b)) {
+        add("story.pdf", &pdf::text_pdf(title, &blocks));
+    } else {
+        add("story.html", readalong_html(story, &[])?.as_bytes());
+    }
+
+    add("cloze.txt", render_cloze(title, &cloze_worksheet(story, options.cloze_every, known)).as_bytes());
+    add("vocab.csv", table(story, TableKind::Vocab, Delimiter::Comma).as_bytes());
+
+    let questions = quiz(&story.doc, options.quiz_questions, known);
+    if !questions.is_empty() {
+        add("quiz.txt", render_quiz(title, &questions).as_bytes());
+    }
+
+    Ok(ClassroomPack {
+        bytes: zip.finish(),
+        files,
+    })
+}
+
+fn display_title(story: &StoryDoc) -> &str {
+    let title = story.title.trim();
+    if title.is_empty() {
+        "Story"
+    } else {
+        title
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cloze {
+    /// Blocks with gaps written as "(1) ________".
+    pub blocks: Vec<String>,
+    /// Removed words, in gap order.
+    pub answers: Vec<String>,
+}
+
+/// Blank every `every`-th eligible word. Capitalised words (mostly names),
+/// known words and, in spaced scripts, words under four letters are never
+/// blanked.
+pub fn cloze_worksheet(story: &StoryDoc, every: usize, known: &KnownWords) -> Cloze {
+    let every = every.max(2);
+    let unspaced = lemma::is_unspaced_script(&story.language);
+    let mut answers = Vec::new();
+    let mut seen = 0;
+
+    let blocks = story
+        .doc
+        .block_texts()
+        .iter()
+        .map(|text| {
+            let mut out = String::new();
+            let mut cursor = 0;
+            for (start, word) in word_ranges(text, &story.language) {
+                let eligible = !word.chars().next().is_some_and(char::is_uppercase)
+                    && !word.chars().any(|c| c.is_ascii_digit())
+                    && (unspaced || word.chars().count() >= 4)
+                    && !known.is_known(word);
+                if !eligible {
+                    continue;
+                }
+                seen += 1;
+                if seen % every == 0 {
+                    answers.push(word.to_string());
+                    out.push_str(&text[cursor..start]);
+                    out.push_str(&format!("({}) ________", answers.len()));
+                    cursor = start + word.len();
+                }
+            }
+            out.push_str(&text[cursor..]);
+            out
+        })
+        .collect();
+
+    Cloze { blocks, answers }
+}
+
+fn render_cloze(title: &str, cloze: &Cloze) -> String {
+    let mut bank = cloze.answers.clone();
+    bank.sort_by_key(|w| w.to_lowercase());
+    bank.dedup();
+
+    let mut out = format!("{}\nFill in the gaps with words from the box.\n\n[ {} ]\n\n", title, bank.join(" · "));
+    out.push_str(&cloze.blocks.join("\n\n"));
+    out.push_str("\n\n----------------------------------------\nAnswer key\n\n");
+    for (i, answer) in cloze.answers.iter().enumerate() {
+        out.push_str(&format!("{}. {}\n", i + 1, answer));
+    }
+    out
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuizQuestion {
+    /// Source text of a span.
+    pub prompt: String,
+    /// Translations to choose from; one is the span's own.
+    pub options: Vec<String>,
+    /// Index of the correct option.
+    pub answer: usize,
+}
+
+/// "Choose the translation" questions, spread evenly through the doc. The
+/// wrong options are other spans' translations closest in length, so the
+/// right one cannot be spotted by size alone. Spans whose words are all
+/// known are asked about only when the others run out.
+pub fn quiz(doc: &InteractiveDoc, count: usize, known: &KnownWords) -> Vec<QuizQuestion> {
+    let mut pairs: Vec<(&str, &str)> = Vec::new();
+    for token in &doc.tokens {
+        let DocToken::Span { span_id, .. } = token else {
+            continue;
+        };
+        let Some(span) = doc.spans.get(span_id) else {
+            continue;
+        };
+        let source = span.source_text.trim();
+        let target = span.active_text().unwrap_or_default().trim();
+        if !source.is_empty() && !target.is_empty() && !pairs.iter().any(|(_, t)| *t == target) {
+            pairs.push((source, target));
+        }
+    }
+    if pairs.len() < 2 {
+        return vec![];
+    }
+
+    let count = count.min(pairs.len());
+    let (fresh, stale): (Vec<usize>, Vec<usize>) =
+        (0..pairs.len()).partition(|&i| known.is_empty() || known.unknown_count(pairs[i].1) > 0);
+    let mut asked = spread(&fresh, count);
+    asked.extend(spread(&stale, count - asked.len()));
+    asked.sort_unstable();
+
+    asked
+        .into_iter()
+        .enumerate()
+        .map(|(q, idx)| {
+            let (source, target) = pairs[idx];
+            let len = target.chars().count() as isize;
+
+            let mut others: Vec<usize> = (0..pairs.len()).filter(|&j| j != idx).collect();
+            others.sort_by_key(|&j| (pairs[j].1.chars().count() as isize - len).abs());
+            let mut options: Vec<String> = others.iter().take(3).map(|&j| pairs[j].1.to_string()).collect();
+
+            let answer = q % (options.len() + 1);
+            options.insert(answer, target.to_string());
+            QuizQuestion {
+                prompt: source.to_string(),
+                options,
+                answer,
+            }
+        })
+        .collect()
+}
+
+/// Up to `count` of `items`, evenly spaced.
+fn spread(items: &[usize], count: usize) -> Vec<usize> {
+    let count = count.min(items.len());
+    (0..count).map(|q| items[q * items.len() / count]).collect()
+}
+
+fn option_letter(i: usize) -> char {
+    (b'a' + i as u8) as char
+}
+
+fn render_quiz(title: &str, questions: &[QuizQuestion]) -> String {
+    let mut out = format!("{}\nChoose the best translation.\n\n", title);
+    for (i, q) in questions.iter().enumerate() {
+        out.push_str(&format!("{}. {}\n", i + 1, q.prompt));
+        for (j, option) in q.options.iter().enumerate() {
+            out.push_str(&format!("   {}) {}\n", option_letter(j), option));
+        }
+        out.push('\n');
+    }
+    out.push_str("----------------------------------------\nAnswer key\n\n");
+    for (i, q) in questions.iter().enumerate() {
+        out.push_str(&format!("{}. {}\n", i + 1, option_letter(q.answer)));
+    }
+    out
+}
@@ -0,0 +1,85 @@
+//! File exports of finished docs. Exporters set `dir` from the doc's and
+//! each block's [`TextDirection`](crate::gui_types::TextDirection).
+
+pub mod classroom;
+mod pdf;
+pub mod readalong;
+pub mod table;
+pub mod template;
+pub mod text;
+pub mod vocab;
+pub mod zip;
+
+use super::lemma;
+use super::naming::slugify;
+use super::paths;
+use super::stories::{StoryDoc, StoryError};
+
+use std::path::{Path, PathBuf};
+
+pub(crate) const EXPORTS_DIR: &str = "exports";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ExportError {
+    #[error("Export I/O error: {0}")]
+    Io(String),
+
+    #[error("Failed to serialize export: {0}")]
+    Serialize(String),
+
+    #[error("Unknown template `{0}`")]
+    UnknownTemplate(String),
+
+    #[error("Template error: {0}")]
+    Template(String),
+
+    #[error(transparent)]
+    Story(#[from] StoryError),
+}
+
+/// [`paths::write_atomic`], failing with an [`ExportError`].
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), ExportError> {
+    paths::write_atomic(path, bytes).map_err(|e| ExportError::Io(e.to_string()))
+}
+
+/// Filesystem-safe default name: "My Story" in fr -> "my-story-fr".
+pub fn file_stem(title: &str, language: &str) -> String {
+    format!("{}-{}", slugify(title), language)
+}
+
+/// Where an export goes when no path is given: `<data_dir>/exports/<stem>.<ext>`,
+/// named after the story's slug.
+pub fn default_path(data_dir: &Path, story: &StoryDoc, ext: &str) -> PathBuf {
+    data_dir
+        .join(EXPORTS_DIR)
+        .join(format!("{}.{}", file_stem(&story.slug, &story.language), ext))
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// JSON that is safe inside a `<script>` element.
+pub(crate) fn script_json<T: serde::Serialize>(value: &T) -> Result<String, ExportError> {
+    let json = serde_json::to_string(value).map_err(|e| ExportError::Serialize(e.to_string()))?;
+    Ok(json.replace("</", "<\\/"))
+}
+
+/// Words of `text` with their byte offsets.
+pub(crate) fn word_ranges<'a>(text: &'a str, language: &str) -> Vec<(usize, &'a str)> {
+    lemma::words(text, language)
+        .into_iter()
+        .map(|w| (w.as_ptr() as usize - text.as_ptr() as usize, w))
+        .collect()
+}
@@ -0,0 +1,151 @@
+//! Minimal text-only PDF writer using the built-in Helvetica fonts, so no
+//! font files are embedded. Those fonts only cover Western European
+//! scripts; callers check [`is_printable`] and fall back to HTML otherwise.
+
+const PAGE_WIDTH: f32 = 595.0; // A4, in points
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 56.0;
+const BODY_SIZE: f32 = 12.0;
+const TITLE_SIZE: f32 = 18.0;
+const LEADING: f32 = 1.4;
+/// Helvetica's average glyph width, in ems; good enough to wrap lines.
+const AVG_GLYPH_EM: f32 = 0.5;
+
+/// Whether every character of `text` has a WinAnsi code.
+pub(crate) fn is_printable(text: &str) -> bool {
+    text.chars().all(|c| c == '\n' || win_ansi(c).is_some())
+}
+
+fn win_ansi(c: char) -> Option<u8> {
+    match c {
+        ' '..='~' | '\u{A0}'..='\u{FF}' => Some(c as u8),
+        '€' => Some(0x80),
+        '…' => Some(0x85),
+        '‘' => Some(0x91),
+        '’' => Some(0x92),
+        '“' => Some(0x93),
+        '”' => Some(0x94),
+        '•' => Some(0x95),
+        '–' => Some(0x96),
+        '—' => Some(0x97),
+        'œ' => Some(0x9C),
+        'Œ' => Some(0x8C),
+        _ => None,
+    }
+}
+
+/// PDF string literal; characters without a WinAnsi code become '?'.
+fn pdf_string(text: &str) -> Vec<u8> {
+    let mut out = vec![b'('];
+    for c in text.chars() {
+        let b = win_ansi(c).unwrap_or(b'?');
+        if matches!(b, b'(' | b')' | b'\\') {
+            out.push(b'\\');
+        }
+        out.push(b);
+    }
+    out.push(b')');
+    out
+}
+
+fn wrap(paragraph: &str, size: f32) -> Vec<String> {
+    let max_chars = ((PAGE_WIDTH - 2.0 * MARGIN) / (size * AVG_GLYPH_EM)) as usize;
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in paragraph.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > max_chars {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// One page per content stream; text is laid out top to bottom.
+fn layout(title: &str, paragraphs: &[String]) -> Vec<Vec<u8>> {
+    let mut pages: Vec<Vec<u8>> = Vec::new();
+    let mut page = Vec::new();
+    let mut y = PAGE_HEIGHT - MARGIN;
+
+    let mut put = |page: &mut Vec<u8>, y: &mut f32, font: &str, size: f32, text: &str| {
+        if *y - size < MARGIN {
+            pages.push(std::mem::take(page));
+            *y = PAGE_HEIGHT - MARGIN;
+        }
+        *y -= size;
+        page.extend_from_slice(format!("BT /{} {} Tf {} {} Td ", font, size, MARGIN, *y).as_bytes());
+        page.extend_from_slice(&pdf_string(text));
+        page.extend_from_slice(b" Tj ET\n");
+        *y -= size * (LEADING - 1.0);
+    };
+
+    for line in wrap(title, TITLE_SIZE) {
+        put(&mut page, &mut y, "F2", TITLE_SIZE, &line);
+    }
+    y -= TITLE_SIZE;
+    for paragraph in paragraphs {
+        for line in paragraph.lines() {
+            for wrapped in wrap(line, BODY_SIZE) {
+                put(&mut page, &mut y, "F1", BODY_SIZE, &wrapped);
+            }
+        }
+        y -= BODY_SIZE * LEADING;
+    }
+    pages.push(page);
+    pages
+}
+
+/// A4 PDF with a bold title and the paragraphs below it.
+pub(crate) fn text_pdf(title: &str, paragraphs: &[String]) -> Vec<u8> {
+    let pages = layout(title, paragraphs);
+    // Objects: 1 catalog, 2 page tree, 3-4 fonts, then a page and its
+    // content stream per page.
+    let mut objects: Vec<Vec<u8>> = Vec::new();
+    let kids: Vec<String> = (0..pages.len()).map(|i| format!("{} 0 R", 5 + 2 * i)).collect();
+    objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+    objects.push(format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()).into_bytes());
+    for font in ["Helvetica", "Helvetica-Bold"] {
+        objects.push(
+            format!("<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>", font).into_bytes(),
+        );
+    }
+    for (i, content) in pages.iter().enumerate() {
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                PAGE_WIDTH,
+                PAGE_HEIGHT,
+                6 + 2 * i
+            )
+            .into_bytes(),
+        );
+        let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+        stream.extend_from_slice(content);
+        stream.extend_from_slice(b"\nendstream");
+        objects.push(stream);
+    }
+
+    let mut out = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        out.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+        out.extend_from_slice(object);
+        out.extend_from_slice(b"\nendobj\n");
+    }
+    let xref = out.len();
+    out.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+    for offset in offsets {
+        out.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    out.extend_from_slice(
+        format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref).as_bytes(),
+    );
+    out
+}
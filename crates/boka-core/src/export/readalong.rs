@@ -0,0 +1,165 @@
+//! Karaoke-style read-along page: one self-contained HTML file with the
+//! doc text, each block's audio inlined, and word timings for highlighting.
+
+use super::{escape_html, script_json, word_ranges, ExportError};
+use crate::stories::StoryDoc;
+
+use serde::Serialize;
+
+/// WAV audio for one block, as produced by the TTS cache.
+#[derive(Debug, Clone)]
+pub struct BlockAudio {
+    pub wav_base64: String,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WordTiming {
+    pub word: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockTimings {
+    pub block: usize,
+    pub duration_ms: u64,
+    /// The TTS engine reports no alignment, so timings are spread over the
+    /// audio by word length and punctuation pauses.
+    pub estimated: bool,
+    pub words: Vec<WordTiming>,
+}
+
+/// Spread `duration_ms` over the words of `text`: each word weighs its
+/// length, plus a pause for punctuation right after it.
+pub fn estimate_word_timings(text: &str, language: &str, duration_ms: u64) -> Vec<WordTiming> {
+    let words = word_ranges(text, language);
+    let weights: Vec<u64> = words
+        .iter()
+        .enumerate()
+        .map(|(i, (start, w))| {
+            let end = start + w.len();
+            let next = words.get(i + 1).map_or(text.len(), |(s, _)| *s);
+            let gap = &text[end..next];
+            let pause = if gap.contains(['.', '!', '?', '。', '！', '？']) {
+                6
+            } else if gap.contains([',', ';', ':', '、', '，']) {
+                3
+            } else {
+                0
+            };
+            w.chars().count() as u64 + 1 + pause
+        })
+        .collect();
+    let total: u64 = weights.iter().sum::<u64>().max(1);
+
+    let mut elapsed = 0u64;
+    words
+        .iter()
+        .zip(&weights)
+        .map(|((_, w), weight)| {
+            let start_ms = duration_ms * elapsed / total;
+            elapsed += weight;
+            WordTiming {
+                word: w.to_string(),
+                start_ms,
+                end_ms: duration_ms * elapsed / total,
+            }
+        })
+        .collect()
+}
+
+/// Build the read-along page. `audio` is indexed by block; blocks without
+/// audio are shown as plain text.
+pub fn readalong_html(story: &StoryDoc, audio: &[Option<BlockAudio>]) -> Result<String, ExportError> {
+    let doc = &story.doc;
+    let language = &story.language;
+    let title = if story.title.trim().is_empty() { "Story" } else { story.title.trim() };
+
+    let mut body = String::new();
+    let mut timings: Vec<BlockTimings> = Vec::new();
+    for (b, text) in doc.block_texts().iter().enumerate() {
+        let dir = doc.block_direction(b).as_str();
+        body.push_str(&format!("<section class=\"block\" data-block=\"{}\" dir=\"{}\">\n<p>", b, dir));
+
+        let mut cursor = 0;
+        for (i, (start, word)) in word_ranges(text, language).into_iter().enumerate() {
+            body.push_str(&escape_html(&text[cursor..start]));
+            body.push_str(&format!("<span class=\"w\" id=\"w{}-{}\">{}</span>", b, i, escape_html(word)));
+            cursor = start + word.len();
+        }
+        body.push_str(&escape_html(&text[cursor..]));
+        body.push_str("</p>\n");
+
+        if let Some(Some(a)) = audio.get(b) {
+            body.push_str(&format!(
+                "<audio id=\"a{}\" controls preload=\"none\" src=\"data:audio/wav;base64,{}\"></audio>\n",
+                b, a.wav_base64
+            ));
+            timings.push(BlockTimings {
+                block: b,
+                duration_ms: a.duration_ms,
+                estimated: true,
+                words: estimate_word_timings(text, language, a.duration_ms),
+            });
+        }
+        body.push_str("</section>\n");
+    }
+
+    Ok(format!(
+        r#"<!DOCTYPE html>
+<html lang="{lang}" dir="{dir}">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+body {{ font: 1.25rem/1.7 Georgia, serif; max-width: 40rem; margin: 2rem auto; padding: 0 1rem; color: #222; }}
+.block {{ margin-bottom: 1.5rem; }}
+.block audio {{ width: 100%; height: 2rem; }}
+.w {{ border-radius: 3px; transition: background 0.1s; }}
+.w.on {{ background: #ffe27a; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+{body}<script type="application/json" id="boka-doc">{doc_json}</script>
+<script type="application/json" id="boka-timings">{timings_json}</script>
+<script>
+(function () {{
+  var timings = JSON.parse(document.getElementById('boka-timings').textContent);
+  var order = timings.map(function (t) {{ return t.block; }});
+  timings.forEach(function (t, n) {{
+    var audio = document.getElementById('a' + t.block);
+    var lit = null;
+    audio.addEventListener('timeupdate', function () {{
+      var ms = audio.currentTime * 1000;
+      var i = t.words.findIndex(function (w) {{ return ms >= w.startMs && ms < w.endMs; }});
+      var el = i < 0 ? null : document.getElementById('w' + t.block + '-' + i);
+      if (el === lit) return;
+      if (lit) lit.classList.remove('on');
+      if (el) el.classList.add('on');
+      lit = el;
+    }});
+    audio.addEventListener('ended', function () {{
+      if (lit) lit.classList.remove('on');
+      lit = null;
+      var next = order[n + 1];
+      if (next !== undefined) document.getElementById('a' + next).play();
+    }});
+  }});
+}})();
+</script>
+</body>
+</html>
+"#,
+        lang = escape_html(language),
+        dir = doc.direction.as_str(),
+        title = escape_html(title),
+        body = body,
+        doc_json = script_json(doc)?,
+        timings_json = script_json(&timings)?,
+    ))
+}
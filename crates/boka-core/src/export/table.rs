@@ -0,0 +1,129 @@
+//! CSV/TSV tables of a doc for spreadsheets and SRS tools.
+
+use super::vocab::vocabulary;
+use crate::gui_types::{DocToken, InteractiveDoc, Span, Variant};
+use crate::stories::StoryDoc;
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TableKind {
+    /// One row per span: source, neutral translation, every variant, notes
+    /// and difficulty.
+    Spans,
+    /// One row per lemma: word, gloss and frequency.
+    Vocab,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delimiter {
+    Comma,
+    Tab,
+}
+
+impl Delimiter {
+    /// Tab for `.tsv`/`.tab` paths, comma otherwise.
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref() {
+            Some("tsv" | "tab") => Delimiter::Tab,
+            _ => Delimiter::Comma,
+        }
+    }
+
+    /// One line without the line break. CSV quotes fields (RFC 4180); TSV
+    /// has no quoting, so tabs and line breaks in fields become spaces.
+    fn row(self, fields: &[&str]) -> String {
+        match self {
+            Delimiter::Comma => fields
+                .iter()
+                .map(|f| {
+                    if f.contains([',', '"', '\n', '\r']) {
+                        format!("\"{}\"", f.replace('"', "\"\""))
+                    } else {
+                        f.to_string()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(","),
+            Delimiter::Tab => fields
+                .iter()
+                .map(|f| f.replace(['\t', '\n', '\r'], " "))
+                .collect::<Vec<_>>()
+                .join("\t"),
+        }
+    }
+}
+
+/// The whole table, header first. CSV starts with a UTF-8 byte order mark
+/// so spreadsheet apps do not guess a legacy encoding.
+pub fn table(story: &StoryDoc, kind: TableKind, delimiter: Delimiter) -> String {
+    let rows = match kind {
+        TableKind::Spans => span_rows(&story.doc),
+        TableKind::Vocab => {
+            let mut rows = vec![strings(&["lemma", "word", "gloss", "frequency"])];
+            rows.extend(
+                vocabulary(&story.doc, &story.language)
+                    .into_iter()
+                    .map(|e| vec![e.lemma, e.word, e.gloss, e.frequency.to_string()]),
+            );
+            rows
+        }
+    };
+
+    let mut out = String::new();
+    if delimiter == Delimiter::Comma {
+        out.push('\u{FEFF}');
+    }
+    for row in rows {
+        let fields: Vec<&str> = row.iter().map(String::as_str).collect();
+        out.push_str(&delimiter.row(&fields));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+fn strings(fields: &[&str]) -> Vec<String> {
+    fields.iter().map(|f| f.to_string()).collect()
+}
+
+/// Spans in reading order. Variants and notes are packed into one cell each
+/// as "register: text", separated by " | ". Difficulty is the neutral
+/// variant's, else the first rated variant's.
+fn span_rows(doc: &InteractiveDoc) -> Vec<Vec<String>> {
+    let mut rows = vec![strings(&["source", "neutral", "variants", "notes", "difficulty"])];
+    for token in &doc.tokens {
+        let DocToken::Span { span_id, .. } = token else {
+            continue;
+        };
+        let Some(span) = doc.spans.get(span_id) else {
+            continue;
+        };
+        let neutral = neutral_variant(span);
+        rows.push(vec![
+            span.source_text.trim().to_string(),
+            neutral.map_or_else(|| span.active_text().unwrap_or_default().to_string(), |v| v.text.clone()),
+            span.variants
+                .iter()
+                .map(|v| format!("{}: {}", v.register, v.text))
+                .collect::<Vec<_>>()
+                .join(" | "),
+            span.variants
+                .iter()
+                .filter_map(|v| v.note.as_ref().map(|n| format!("{}: {}", v.register, n)))
+                .collect::<Vec<_>>()
+                .join(" | "),
+            neutral
+                .and_then(|v| v.difficulty)
+                .or_else(|| span.variants.iter().find_map(|v| v.difficulty))
+                .map(|d| d.to_string())
+                .unwrap_or_default(),
+        ]);
+    }
+    rows
+}
+
+fn neutral_variant(span: &Span) -> Option<&Variant> {
+    span.variants.iter().find(|v| v.register == "neutral")
+}
@@ -0,0 +1,233 @@
+//! Template-driven exports. Built-in `html` and `markdown` templates ship
+//! with the app; users add their own as files in `<data_dir>/templates/`,
+//! named by file name (e.g. `anki.txt`). A user file named like a built-in
+//! replaces it. Templates use Jinja syntax (minijinja); names ending in
+//! `.html` are HTML-escaped automatically.
+//!
+//! Template context:
+//! - `story`: `id`, `title`, `language`, `sourceLanguage`, `sourceText`, `direction`
+//! - `blocks`: paragraphs in order, each with `index`, `direction`, `text`
+//!   and `parts`; a part has `text` and `span` (null for plain text)
+//! - `spans`: every span in reading order: `id`, `sourceText`, `text`,
+//!   `register`, `note`, `difficulty` (all from the active variant) and `variants`
+//! - `vocab`: `lemma`, `word`, `gloss`, `frequency`, most frequent first
+//! - `segments`: the job's `index`, `chapter`, `source`, `text`, `simplifiedText`
+
+use super::vocab::{vocabulary, VocabEntry};
+use super::ExportError;
+use crate::gui_types::{DocToken, Span, TextDirection, Variant};
+use crate::jsonl::{segment_lines, SegmentLine};
+use crate::stories::StoryDoc;
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const TEMPLATES_DIR: &str = "templates";
+
+/// Built-in templates: name, name given to the engine (its extension picks
+/// auto-escaping), source.
+const BUILTIN: [(&str, &str, &str); 2] = [
+    ("html", "story.html", include_str!("../../data/templates/story.html")),
+    ("markdown", "story.md", include_str!("../../data/templates/story.md")),
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateInfo {
+    pub name: String,
+    pub builtin: bool,
+    /// Extension for the exported file.
+    pub extension: String,
+}
+
+/// `<data_dir>/templates`.
+pub fn templates_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(TEMPLATES_DIR)
+}
+
+/// Built-ins plus the user's templates, sorted by name; a user template
+/// replacing a built-in is listed once, as the user's.
+pub fn list_templates(data_dir: &Path) -> Vec<TemplateInfo> {
+    let mut list: Vec<TemplateInfo> = Vec::new();
+    if let Ok(entries) = fs::read_dir(templates_dir(data_dir)) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if entry.path().is_file() && !name.starts_with('.') {
+                list.push(TemplateInfo {
+                    extension: extension_of(&name),
+                    name,
+                    builtin: false,
+                });
+            }
+        }
+    }
+    for (name, engine_name, _) in BUILTIN {
+        if !list.iter().any(|t| t.name == name) {
+            list.push(TemplateInfo {
+                name: name.to_string(),
+                builtin: true,
+                extension: extension_of(engine_name),
+            });
+        }
+    }
+    list.sort_by(|a, b| a.name.cmp(&b.name));
+    list
+}
+
+fn extension_of(name: &str) -> String {
+    Path::new(name)
+        .extension()
+        .map_or_else(|| "txt".to_string(), |e| e.to_string_lossy().into_owned())
+}
+
+/// Source of `name` and the name to compile it under.
+fn load_template(data_dir: &Path, name: &str) -> Result<(String, String), ExportError> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err(ExportError::UnknownTemplate(name.to_string()));
+    }
+    let path = templates_dir(data_dir).join(name);
+    if path.is_file() {
+        let source = fs::read_to_string(&path).map_err(|e| ExportError::Io(e.to_string()))?;
+        return Ok((name.to_string(), source));
+    }
+    BUILTIN
+        .iter()
+        .find(|(builtin, _, _)| *builtin == name)
+        .map(|(_, engine_name, source)| (engine_name.to_string(), source.to_string()))
+        .ok_or_else(|| ExportError::UnknownTemplate(name.to_string()))
+}
+
+/// Render `story` with the template called `name`. Returns the text and
+/// the extension the exported file should have.
+pub fn render_template(data_dir: &Path, name: &str, story: &StoryDoc) -> Result<(String, String), ExportError> {
+    let (engine_name, source) = load_template(data_dir, name)?;
+    let text = render_source(&engine_name, &source, story)?;
+    Ok((text, extension_of(&engine_name)))
+}
+
+/// Render template `source`; `name` decides auto-escaping by extension.
+pub fn render_source(name: &str, source: &str, story: &StoryDoc) -> Result<String, ExportError> {
+    let mut env = minijinja::Environment::new();
+    env.set_undefined_behavior(minijinja::UndefinedBehavior::Strict);
+    env.add_template(name, source)
+        .map_err(|e| ExportError::Template(e.to_string()))?;
+    env.get_template(name)
+        .and_then(|t| t.render(context(story)))
+        .map_err(|e| ExportError::Template(e.to_string()))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Context<'a> {
+    story: StoryContext<'a>,
+    blocks: Vec<BlockContext<'a>>,
+    spans: Vec<SpanContext<'a>>,
+    vocab: Vec<VocabEntry>,
+    segments: Vec<SegmentLine>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StoryContext<'a> {
+    id: &'a str,
+    title: &'a str,
+    language: &'a str,
+    source_language: &'a str,
+    source_text: &'a str,
+    direction: TextDirection,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BlockContext<'a> {
+    index: usize,
+    direction: TextDirection,
+    text: String,
+    parts: Vec<PartContext<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PartContext<'a> {
+    text: &'a str,
+    span: Option<SpanContext<'a>>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpanContext<'a> {
+    id: &'a str,
+    source_text: &'a str,
+    text: &'a str,
+    register: &'a str,
+    note: Option<&'a str>,
+    difficulty: Option<u8>,
+    variants: &'a [Variant],
+}
+
+impl<'a> SpanContext<'a> {
+    fn new(span: &'a Span) -> Self {
+        let active = span.variants.get(span.active_variant_index);
+        Self {
+            id: &span.id,
+            source_text: &span.source_text,
+            text: span.active_text().unwrap_or_default(),
+            register: active.map_or("neutral", |v| v.register.as_str()),
+            note: active.and_then(|v| v.note.as_deref()),
+            difficulty: active.and_then(|v| v.difficulty),
+            variants: &span.variants,
+        }
+    }
+}
+
+fn context(story: &StoryDoc) -> Context<'_> {
+    let doc = &story.doc;
+    let texts = doc.block_texts();
+    let mut blocks: Vec<BlockContext> = texts
+        .into_iter()
+        .enumerate()
+        .map(|(index, text)| BlockContext {
+            index,
+            direction: doc.block_direction(index),
+            text,
+            parts: vec![],
+        })
+        .collect();
+    let mut spans = Vec::new();
+
+    let mut block = 0;
+    for token in &doc.tokens {
+        match token {
+            DocToken::Text { value, .. } if value == "\n\n" => block += 1,
+            DocToken::Text { value, .. } => blocks[block].parts.push(PartContext { text: value, span: None }),
+            DocToken::Span { span_id, .. } => {
+                let Some(span) = doc.spans.get(span_id) else {
+                    continue;
+                };
+                let span = SpanContext::new(span);
+                blocks[block].parts.push(PartContext {
+                    text: span.text,
+                    span: Some(span.clone()),
+                });
+                spans.push(span);
+            }
+            DocToken::Check { .. } => {}
+        }
+    }
+
+    Context {
+        story: StoryContext {
+            id: &story.story_id,
+            title: &story.title,
+            language: &story.language,
+            source_language: &story.source_language,
+            source_text: &story.source_text,
+            direction: doc.direction,
+        },
+        blocks,
+        spans,
+        vocab: vocabulary(doc, &story.language),
+        segments: story.job.as_ref().map(segment_lines).unwrap_or_default(),
+    }
+}
@@ -0,0 +1,116 @@
+//! A doc as plain text or Markdown, for "copy story". The frontend used to
+//! build this string, and what got copied depended on the platform and on
+//! which window asked; rendering it here gives the same text everywhere.
+
+use crate::gui_types::{DocToken, InteractiveDoc, TextStyle};
+use crate::render;
+use crate::stories::StoryDoc;
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextFormat {
+    #[default]
+    Plain,
+    /// Title as a heading, emphasis kept, source lines as quotes.
+    Markdown,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DocTextOptions {
+    pub format: TextFormat,
+    /// Read every span in the variant closest to this register (see
+    /// [`InteractiveDoc::switch_register`]); the saved choices when unset.
+    pub register: Option<String>,
+    /// List the notes of the variants read under each block.
+    pub notes: bool,
+    /// Put the source text under each block.
+    pub source: bool,
+}
+
+/// The story's title and doc as `options` ask. Blocks are separated by a
+/// blank line; comprehension checks are left out. Source text goes under
+/// each block when the blocks are still the job's segments, else at the end.
+pub fn doc_text(story: &StoryDoc, options: &DocTextOptions) -> String {
+    let mut doc = story.doc.clone();
+    if let Some(register) = options.register.as_deref() {
+        doc.switch_register(register);
+    }
+    let markdown = options.format == TextFormat::Markdown;
+    let blocks: Vec<&[DocToken]> = render::blocks(&doc).collect();
+    let sources: Option<Vec<&str>> = story
+        .job
+        .as_ref()
+        .filter(|job| options.source && job.segments.len() == blocks.len())
+        .map(|job| job.segments.iter().map(|s| s.source.trim()).collect());
+
+    let mut parts = Vec::with_capacity(blocks.len() + 1);
+    let title = story.title.trim();
+    if !title.is_empty() {
+        parts.push(if markdown { format!("# {}", title) } else { title.to_string() });
+    }
+    for (i, tokens) in blocks.iter().enumerate() {
+        let text = if markdown {
+            render::render_styled(&doc, tokens, &BTreeMap::new(), emphasize)
+        } else {
+            render::render_tokens(&doc, tokens, &BTreeMap::new())
+        };
+        // Without selections no variant can be missing.
+        let mut part = text.unwrap_or_default().trim().to_string();
+        if let Some(source) = sources.as_ref().and_then(|s| s.get(i)).filter(|s| !s.is_empty()) {
+            part.push_str(if markdown { "\n\n> " } else { "\n" });
+            part.push_str(source);
+        }
+        if options.notes {
+            for (span, note) in block_notes(&doc, tokens) {
+                let line = if markdown {
+                    format!("\n- *{}*: {}", span, note)
+                } else {
+                    format!("\n- {}: {}", span, note)
+                };
+                part.push_str(&line);
+            }
+        }
+        parts.push(part);
+    }
+    let source = story.source_text.trim();
+    if options.source && sources.is_none() && !source.is_empty() {
+        parts.push(if markdown { "## Source".to_string() } else { "Source".to_string() });
+        parts.push(source.to_string());
+    }
+    parts.join("\n\n")
+}
+
+/// `value` wrapped in Markdown emphasis, with surrounding spaces left
+/// outside the marks so they still open and close. Blank runs stay bare.
+fn emphasize(value: &str, style: TextStyle) -> String {
+    if value.trim().is_empty() {
+        return value.to_string();
+    }
+    let mark = match style {
+        TextStyle::Emphasis => "*",
+        TextStyle::Strong => "**",
+    };
+    let start = value.len() - value.trim_start().len();
+    let end = value.trim_end().len();
+    format!("{}{}{}{}{}", &value[..start], mark, &value[start..end], mark, &value[end..])
+}
+
+/// Text and note of each span in `tokens` whose active variant has a note.
+fn block_notes<'a>(doc: &'a InteractiveDoc, tokens: &[DocToken]) -> Vec<(&'a str, &'a str)> {
+    tokens
+        .iter()
+        .filter_map(|token| {
+            let DocToken::Span { span_id, .. } = token else {
+                return None;
+            };
+            let span = doc.spans.get(span_id)?;
+            let variant = span.variants.get(span.active_variant_index)?;
+            let note = variant.note.as_deref().map(str::trim).filter(|n| !n.is_empty())?;
+            Some((variant.text.trim(), note))
+        })
+        .collect()
+}
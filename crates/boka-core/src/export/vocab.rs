@@ -0,0 +1,56 @@
+//! Word list of a doc, shared by the classroom pack and spreadsheet exports.
+
+use crate::gui_types::{DocToken, InteractiveDoc};
+use crate::lemma;
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VocabEntry {
+    pub lemma: String,
+    /// First surface form met in the text.
+    pub word: String,
+    /// Source text of the span the word first appears in. Spans are
+    /// phrase-sized, so this glosses the phrase rather than the word.
+    pub gloss: String,
+    pub frequency: u32,
+}
+
+/// Every lemma in the doc's active text, most frequent first, ties in
+/// order of first appearance.
+pub fn vocabulary(doc: &InteractiveDoc, language: &str) -> Vec<VocabEntry> {
+    let mut entries: Vec<VocabEntry> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for token in &doc.tokens {
+        let (text, gloss) = match token {
+            DocToken::Text { value, .. } => (value.as_str(), ""),
+            DocToken::Span { span_id, .. } => match doc.spans.get(span_id) {
+                Some(span) => (span.active_text().unwrap_or_default(), span.source_text.trim()),
+                None => continue,
+            },
+            DocToken::Check { .. } => continue,
+        };
+        for word in lemma::words(text, language) {
+            let key = lemma::lemma(word, language);
+            match index.get(&key) {
+                Some(&i) => entries[i].frequency += 1,
+                None => {
+                    index.insert(key.clone(), entries.len());
+                    entries.push(VocabEntry {
+                        lemma: key,
+                        word: word.to_string(),
+                        gloss: gloss.to_string(),
+                        frequency: 1,
+                    });
+                }
+            }
+        }
+    }
+
+    // Stable sort keeps first-appearance order within a frequency.
+    entries.sort_by_key(|e| std::cmp::Reverse(e.frequency));
+    entries
+}
@@ -0,0 +1,149 @@
+//! Minimal ZIP writer and reader: stored (uncompressed) entries only. Export
+//! packs and profiles are mostly small text files, so compression would not
+//! be worth it; `.boka` bundles compress the whole archive instead.
+
+/// Fixed DOS timestamp (1980-01-01 00:00) so identical packs are identical bytes.
+const DOS_TIME: u16 = 0;
+const DOS_DATE: u16 = (1 << 5) | 1;
+/// General purpose flag bit 11: names are UTF-8.
+const UTF8_NAMES: u16 = 1 << 11;
+
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIR: u32 = 0x0605_4b50;
+
+struct Entry {
+    name: String,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+/// Builds an archive in memory. Names are written as given, unchecked.
+#[derive(Default)]
+pub struct ZipWriter {
+    buf: Vec<u8>,
+    entries: Vec<Entry>,
+}
+
+impl ZipWriter {
+    pub fn add(&mut self, name: &str, data: &[u8]) {
+        let entry = Entry {
+            name: name.to_string(),
+            crc: crc32(data),
+            size: data.len() as u32,
+            offset: self.buf.len() as u32,
+        };
+
+        self.buf.extend_from_slice(&LOCAL_HEADER.to_le_bytes());
+        self.header_fields(&entry);
+        self.buf.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+        self.buf.extend_from_slice(name.as_bytes());
+        self.buf.extend_from_slice(data);
+        self.entries.push(entry);
+    }
+
+    /// Fields shared by local and central headers, from "version needed"
+    /// through the file name length.
+    fn header_fields(&mut self, entry: &Entry) {
+        for v in [20u16, UTF8_NAMES, 0, DOS_TIME, DOS_DATE] {
+            self.buf.extend_from_slice(&v.to_le_bytes());
+        }
+        for v in [entry.crc, entry.size, entry.size] {
+            self.buf.extend_from_slice(&v.to_le_bytes());
+        }
+        self.buf.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        let entries = std::mem::take(&mut self.entries);
+        let central_start = self.buf.len() as u32;
+        for entry in &entries {
+            self.buf.extend_from_slice(&CENTRAL_HEADER.to_le_bytes());
+            self.buf.extend_from_slice(&20u16.to_le_bytes()); // version made by
+            self.header_fields(entry);
+            // extra, comment, disk number, internal attrs
+            for _ in 0..4 {
+                self.buf.extend_from_slice(&0u16.to_le_bytes());
+            }
+            self.buf.extend_from_slice(&0u32.to_le_bytes()); // external attrs
+            self.buf.extend_from_slice(&entry.offset.to_le_bytes());
+            self.buf.extend_from_slice(entry.name.as_bytes());
+        }
+        let central_size = self.buf.len() as u32 - central_start;
+
+        self.buf.extend_from_slice(&END_OF_CENTRAL_DIR.to_le_bytes());
+        for v in [0u16, 0, entries.len() as u16, entries.len() as u16] {
+            self.buf.extend_from_slice(&v.to_le_bytes());
+        }
+        self.buf.extend_from_slice(&central_size.to_le_bytes());
+        self.buf.extend_from_slice(&central_start.to_le_bytes());
+        self.buf.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        self.buf
+    }
+}
+
+/// The entries of an archive, in directory order. Reads what [`ZipWriter`]
+/// writes: stored entries, checked against their CRC.
+pub(crate) fn read_entries(bytes: &[u8]) -> Result<Vec<(String, Vec<u8>)>, String> {
+    let truncated = || "truncated archive".to_string();
+    let u16_at = |i: usize| {
+        bytes
+            .get(i..i + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+            .ok_or_else(truncated)
+    };
+    let u32_at = |i: usize| {
+        bytes
+            .get(i..i + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or_else(truncated)
+    };
+
+    // The end record is 22 bytes plus a comment of up to 64 KiB.
+    let eocd = (0..=bytes.len().saturating_sub(22))
+        .rev()
+        .take(22 + u16::MAX as usize)
+        .find(|&i| u32_at(i) == Ok(END_OF_CENTRAL_DIR))
+        .ok_or_else(|| "not a ZIP archive".to_string())?;
+    let count = u16_at(eocd + 10)?;
+    let mut pos = u32_at(eocd + 16)? as usize;
+
+    let mut entries = Vec::with_capacity(count);
+    for _ in 0..count {
+        if u32_at(pos)? != CENTRAL_HEADER {
+            return Err("corrupt central directory".to_string());
+        }
+        let (method, crc, size) = (u16_at(pos + 10)?, u32_at(pos + 16)?, u32_at(pos + 20)? as usize);
+        let (name_len, extra_len, comment_len) = (u16_at(pos + 28)?, u16_at(pos + 30)?, u16_at(pos + 32)?);
+        let offset = u32_at(pos + 42)? as usize;
+        let name = bytes.get(pos + 46..pos + 46 + name_len).ok_or_else(truncated)?;
+        let name = String::from_utf8(name.to_vec()).map_err(|_| "entry name is not UTF-8".to_string())?;
+        if method != 0 {
+            return Err(format!("`{}` is compressed; only stored entries can be read", name));
+        }
+
+        if u32_at(offset)? != LOCAL_HEADER {
+            return Err(format!("corrupt local header for `{}`", name));
+        }
+        let start = offset + 30 + u16_at(offset + 26)? + u16_at(offset + 28)?;
+        let data = bytes.get(start..start + size).ok_or_else(truncated)?;
+        if crc32(data) != crc {
+            return Err(format!("`{}` is corrupt (CRC mismatch)", name));
+        }
+        entries.push((name, data.to_vec()));
+        pos += 46 + name_len + extra_len + comment_len;
+    }
+    Ok(entries)
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
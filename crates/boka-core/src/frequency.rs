@@ -0,0 +1,246 @@
+//! Word frequency bands, so the reader can dim common words and highlight
+//! the ones worth studying.
+//!
+//! A frequency list is a text file with one word per line, most frequent
+//! first; blank lines and lines starting with `#` are skipped. Short starter
+//! lists ship with the app (`data/frequency/`): they place the most common
+//! words in [`FreqBand::Top1k`] and leave every other word without a band.
+//! [`FreqBand::Top5k`] and [`FreqBand::Rare`] need a full list of 5000+
+//! words, which users add as `<data_dir>/frequency/<language>.txt`; it
+//! replaces the built-in list for that language.
+
+use super::gui_types::{DocToken, InteractiveDoc};
+use super::lemma::{self, base_language};
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const FREQUENCY_DIR: &str = "frequency";
+
+/// Ranks below this are [`FreqBand::Top1k`].
+const TOP_1K: usize = 1000;
+/// Ranks below this are [`FreqBand::Top5k`]; a list this long also marks
+/// every word it does not have as [`FreqBand::Rare`].
+const TOP_5K: usize = 5000;
+
+/// Built-in lists: base language, source.
+const BUILTIN: [(&str, &str); 4] = [
+    ("de", include_str!("../data/frequency/de.txt")),
+    ("en", include_str!("../data/frequency/en.txt")),
+    ("es", include_str!("../data/frequency/es.txt")),
+    ("fr", include_str!("../data/frequency/fr.txt")),
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum FrequencyError {
+    #[error("Frequency list I/O error: {0}")]
+    Io(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FreqBand {
+    #[serde(rename = "top-1k")]
+    Top1k,
+    #[serde(rename = "top-5k")]
+    Top5k,
+    #[serde(rename = "rare")]
+    Rare,
+}
+
+/// A piece of a variant's text. Words carry their band; the text between
+/// them (spaces, punctuation) does not, and neither do words a short list
+/// cannot place. Joined in order, the pieces give back the text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FreqToken {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub freq_band: Option<FreqBand>,
+}
+
+/// Frequency tokens of a whole doc.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocFrequency {
+    pub language: String,
+    /// Whether a list was found for `language`; without one no token has a band.
+    pub has_list: bool,
+    /// Words in the list used; under 5000 means no word is marked rare.
+    pub list_len: u32,
+    /// Span id to the tokens of each of its variants, in variant order.
+    pub spans: HashMap<String, Vec<Vec<FreqToken>>>,
+    /// The doc's `tokens`, each text run with words split into one text
+    /// token per word (carrying its band) and per stretch between words.
+    pub tokens: Vec<DocToken>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FreqList {
+    /// Lowercased word to rank, 0 for the most frequent.
+    ranks: HashMap<String, usize>,
+    /// Best rank per lemma, for inflections the list does not have.
+    lemma_ranks: HashMap<String, usize>,
+    len: usize,
+}
+
+impl FreqList {
+    pub fn parse(text: &str, language: &str) -> Self {
+        let mut list = Self::default();
+        for word in text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            let word = word.to_lowercase();
+            if list.ranks.contains_key(&word) {
+                continue;
+            }
+            list.lemma_ranks.entry(lemma::lemma(&word, language)).or_insert(list.len);
+            list.ranks.insert(word, list.len);
+            list.len += 1;
+        }
+        list
+    }
+
+    pub fn builtin(language: &str) -> Option<Self> {
+        let base = base_language(language).to_lowercase();
+        BUILTIN
+            .iter()
+            .find(|(code, _)| *code == base)
+            .map(|(_, text)| Self::parse(text, language))
+    }
+
+    /// The user's list for `language` (or its base language) in
+    /// `<data_dir>/frequency/`, else the built-in one.
+    pub fn load(data_dir: &Path, language: &str) -> Result<Option<Self>, FrequencyError> {
+        let language = language.to_lowercase();
+        if let Some(path) = user_list(data_dir, &language) {
+            let text = fs::read_to_string(&path).map_err(|e| FrequencyError::Io(e.to_string()))?;
+            return Ok(Some(Self::parse(&text, &language)));
+        }
+        Ok(Self::builtin(&language))
+    }
+
+    /// Whether [`FreqList::load`] would find a list, without reading it.
+    pub fn available(data_dir: &Path, language: &str) -> bool {
+        let language = language.to_lowercase();
+        let base = base_language(&language);
+        user_list(data_dir, &language).is_some() || BUILTIN.iter().any(|(code, _)| *code == base)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Band of `word`, looked up as written and then by lemma. `None` for a
+    /// word missing from a list too short to call it rare.
+    pub fn band(&self, word: &str, language: &str) -> Option<FreqBand> {
+        let word = word.to_lowercase();
+        let rank = self
+            .ranks
+            .get(&word)
+            .or_else(|| self.lemma_ranks.get(&lemma::lemma(&word, language)));
+        match rank {
+            Some(&rank) if rank < TOP_1K => Some(FreqBand::Top1k),
+            Some(&rank) if rank < TOP_5K => Some(FreqBand::Top5k),
+            _ if self.len >= TOP_5K => Some(FreqBand::Rare),
+            _ => None,
+        }
+    }
+
+    /// Split `text` into words (with their band) and the text between them.
+    pub fn tokens(&self, text: &str, language: &str) -> Vec<FreqToken> {
+        let mut tokens = Vec::new();
+        let mut at = 0;
+        for word in lemma::words(text, language) {
+            // `words` hands back slices of `text`, in order.
+            let start = word.as_ptr() as usize - text.as_ptr() as usize;
+            if start > at {
+                tokens.push(FreqToken {
+                    text: text[at..start].to_string(),
+                    freq_band: None,
+                });
+            }
+            tokens.push(FreqToken {
+                text: word.to_string(),
+                freq_band: self.band(word, language),
+            });
+            at = start + word.len();
+        }
+        if at < text.len() {
+            tokens.push(FreqToken {
+                text: text[at..].to_string(),
+                freq_band: None,
+            });
+        }
+        tokens
+    }
+}
+
+/// `<data_dir>/frequency`.
+pub fn frequency_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(FREQUENCY_DIR)
+}
+
+/// `language`'s file in `<data_dir>/frequency/`, else its base language's.
+fn user_list(data_dir: &Path, language: &str) -> Option<PathBuf> {
+    let base = base_language(language);
+    [language, base]
+        .into_iter()
+        .map(|name| frequency_dir(data_dir).join(format!("{}.txt", name)))
+        .find(|path| path.is_file())
+}
+
+/// Languages with a list of the user's in `<data_dir>/frequency/`.
+pub fn user_languages(data_dir: &Path) -> Vec<String> {
+    let mut languages: Vec<String> = fs::read_dir(frequency_dir(data_dir))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_lowercase()))
+        .collect();
+    languages.sort();
+    languages
+}
+
+/// Tokens of every variant of every span in `doc`, and the doc's own tokens
+/// with their words split out, banded with `list`.
+pub fn doc_frequency(doc: &InteractiveDoc, language: &str, list: Option<&FreqList>) -> DocFrequency {
+    let empty = FreqList::default();
+    let banding = list.unwrap_or(&empty);
+    let spans = doc
+        .spans
+        .iter()
+        .map(|(id, span)| {
+            let variants = span.variants.iter().map(|v| banding.tokens(&v.text, language)).collect();
+            (id.clone(), variants)
+        })
+        .collect();
+    let tokens = doc
+        .tokens
+        .iter()
+        .flat_map(|token| match token {
+            DocToken::Text { value, style, .. } if !lemma::words(value, language).is_empty() => banding
+                .tokens(value, language)
+                .into_iter()
+                .map(|piece| DocToken::Text {
+                    value: piece.text,
+                    style: *style,
+                    freq_band: piece.freq_band,
+                })
+                .collect(),
+            _ => vec![token.clone()],
+        })
+        .collect();
+    DocFrequency {
+        language: language.to_string(),
+        has_list: list.is_some(),
+        list_len: list.map_or(0, |l| l.len() as u32),
+        spans,
+        tokens,
+    }
+}
@@ -0,0 +1,494 @@
+use super::annotate::AnnotationLayer;
+use super::frequency::FreqBand;
+use super::length_guard::LengthGuard;
+use super::limits::JobBudget;
+use super::lint::DocLint;
+use super::policy::{register_fallback, ContentPolicy};
+use super::prompts::PromptSet;
+use super::render;
+use super::settings::VariantBounds;
+use super::simplify::{CefrLevel, VocabularyCheck};
+use super::types::{LlmProviderPreset, SamplingParams};
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SegmentStage {
+    Pending,
+    Ready,
+    Error,
+}
+
+/// What a job does when a segment keeps failing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorPolicy {
+    /// Stop the job at the first failed segment.
+    #[default]
+    Abort,
+    /// Mark the segment `Error`, show it as plain text and carry on.
+    Skip,
+    /// Try the failed step once more before skipping it.
+    RetryThenSkip,
+}
+
+/// How much text one translation segment, and so one doc block, holds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Granularity {
+    /// Clauses: sentences cut again after commas, semicolons, colons and
+    /// dashes, for learners who want short spans.
+    Clause,
+    #[default]
+    Sentence,
+    /// Paragraphs, as separated by blank lines; for classroom reading.
+    Paragraph,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslationSegment {
+    pub id: String,
+    pub source: String,
+    /// Index of the chapter the segment belongs to; 0 unless the story was chunked.
+    #[serde(default)]
+    pub chapter: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_text: Option<String>,
+    pub base_stage: SegmentStage,
+    pub span_stage: SegmentStage,
+    pub variant_count: u32,
+    /// Variants requested per span, sized by the segment's difficulty.
+    #[serde(default)]
+    pub variant_target: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<SegmentScore>,
+    /// Graded-reader version of the segment in dual-output jobs;
+    /// `base_text` then stays the faithful translation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub simplified_text: Option<String>,
+    /// Vocabulary check of a graded-reader translation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub simplification: Option<VocabularyCheck>,
+    /// `base_text` was corrected at the review gate.
+    #[serde(default)]
+    pub edited: bool,
+    /// The source was too long for one call and was translated in pieces,
+    /// cut at clause boundaries and joined back up.
+    #[serde(default)]
+    pub auto_split: bool,
+    /// The model declined the segment's content, in its words; the segment
+    /// is in `Error` and another model or provider may take it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refused: Option<String>,
+    /// Base-translation calls made for the segment, retries included.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub provenance: Vec<CallProvenance>,
+    /// Progress of each planned span while variants are generated; empty
+    /// until span planning returns.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spans: Vec<SpanProgress>,
+}
+
+/// Which provider and model answered one call of a job, so jobs that
+/// retried or switched models can be traced back segment by segment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallProvenance {
+    pub provider: LlmProviderPreset,
+    pub model: String,
+    /// 1 for the first try, 2 for the first retry, and so on.
+    pub attempt: u32,
+    pub duration_ms: u64,
+}
+
+/// One span of a segment on its way through variant generation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpanProgress {
+    /// The span's text as planned.
+    pub anchor: String,
+    pub stage: SpanStage,
+    /// Variants generated so far; after QA, the ones kept.
+    pub variants: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpanStage {
+    Pending,
+    /// Variants are being generated.
+    Variants,
+    /// The variant critique is reviewing the generated list.
+    Qa,
+    Ready,
+    Error,
+}
+
+/// Judge model's assessment of a segment's base translation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentScore {
+    pub adequacy: u8,
+    pub fluency: u8,
+    pub rationale: String,
+    pub judge_model: String,
+    /// Below the job's minimum score even after any retry.
+    pub needs_review: bool,
+    /// Base translations tried; 2 when an auto-retry happened.
+    pub attempts: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslationJob {
+    pub id: String,
+    pub segments: Vec<TranslationSegment>,
+    pub ready: bool,
+    /// Base translations are done and the job waits for them to be
+    /// approved before span planning.
+    #[serde(default)]
+    pub awaiting_review: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<JobMetadata>,
+    /// Lints of the finished doc; see [`lint`](super::lint).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lints: Vec<DocLint>,
+}
+
+/// A reviewer's replacement for one segment's base translation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentEdit {
+    pub segment_id: String,
+    pub base_text: String,
+}
+
+/// Everything that shaped a job's output, so past jobs can be audited and
+/// reproducible ones re-run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobMetadata {
+    pub reproducible: bool,
+    pub provider: LlmProviderPreset,
+    pub model: String,
+    pub sampling: SamplingParams,
+    pub target_language: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_language: Option<String>,
+    pub dense_spans: bool,
+    pub content_policy: ContentPolicy,
+    pub prompts: PromptSet,
+    #[serde(default)]
+    pub variant_bounds: VariantBounds,
+    #[serde(default)]
+    pub length_guard: LengthGuard,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub simplify_level: Option<CefrLevel>,
+    #[serde(default)]
+    pub dual_output: bool,
+    #[serde(default)]
+    pub refine_variants: bool,
+    #[serde(default)]
+    pub granularity: Granularity,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comprehension_every: Option<u32>,
+    #[serde(default)]
+    pub review_required: bool,
+    #[serde(default)]
+    pub error_policy: ErrorPolicy,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<JobBudget>,
+    /// Language of the learner notes; English when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ui_language: Option<String>,
+    /// Annotation passes asked for, by id.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<String>,
+    pub app_version: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Variant {
+    pub id: String,
+    pub register: String,
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<u8>,
+    /// Set when the variant breaks the job's content policy but had to be kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flagged: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Span {
+    pub id: String,
+    pub source_text: String,
+    pub variants: Vec<Variant>,
+    pub active_variant_index: usize,
+    /// Variant-generation calls that produced `variants`, regenerations
+    /// included; empty for spans not made by a job.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub provenance: Vec<CallProvenance>,
+}
+
+impl Span {
+    /// Text of the active variant, or the source text when it is missing.
+    pub fn active_text(&self) -> Option<&str> {
+        self.variants
+            .get(self.active_variant_index)
+            .map(|v| v.text.as_str())
+            .or(Some(self.source_text.as_str()).filter(|s| !s.is_empty()))
+    }
+}
+
+/// Emphasis carried over from Markdown in the source (see
+/// [`markup`](super::markup)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextStyle {
+    Emphasis,
+    Strong,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum DocToken {
+    #[serde(rename_all = "camelCase")]
+    Text {
+        value: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        style: Option<TextStyle>,
+        /// Set on the word tokens of [`DocFrequency`](super::frequency::DocFrequency)
+        /// only; saved docs keep whole text runs.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        freq_band: Option<FreqBand>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Span {
+        span_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        style: Option<TextStyle>,
+    },
+    /// True/false comprehension question closing a block (see
+    /// [`comprehension`](super::comprehension)). Not part of the text.
+    Check {
+        question: String,
+        answer: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        explanation: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InteractiveDoc {
+    pub tokens: Vec<DocToken>,
+    pub spans: std::collections::HashMap<String, Span>,
+    /// Base direction of the target language.
+    #[serde(default)]
+    pub direction: TextDirection,
+    /// One entry per block (blocks are separated by a "\n\n" text token).
+    /// A block can differ from `direction`, e.g. an English quotation left
+    /// untranslated in an Arabic story. Empty in docs saved before this
+    /// field existed; readers fall back to `direction`.
+    #[serde(default)]
+    pub block_directions: Vec<TextDirection>,
+    /// Layers left by the job's annotation passes, by pass id; see
+    /// [`annotate`](super::annotate).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, AnnotationLayer>,
+}
+
+impl InteractiveDoc {
+    /// Plain text of each block, reading every span's active variant; see
+    /// [`render`](super::render).
+    pub fn block_texts(&self) -> Vec<String> {
+        let none = BTreeMap::new();
+        // Without selections no variant can be missing.
+        render::blocks(self)
+            .map(|tokens| render::render_tokens(self, tokens, &none).unwrap_or_default())
+            .collect()
+    }
+
+    /// Swap block `index` for the only block of `block`, giving its spans
+    /// ids after the doc's highest `span-N`. The block's comprehension check
+    /// stays. Returns false when the doc has no such block.
+    pub fn replace_block(&mut self, index: usize, block: InteractiveDoc) -> bool {
+        let mut blocks: Vec<Vec<DocToken>> = vec![vec![]];
+        for token in std::mem::take(&mut self.tokens) {
+            match token {
+                DocToken::Text { value, .. } if value == "\n\n" => blocks.push(vec![]),
+                token => blocks.last_mut().unwrap().push(token),
+            }
+        }
+        if index >= blocks.len() {
+            self.tokens = join_blocks(blocks);
+            return false;
+        }
+
+        let mut checks = Vec::new();
+        for token in std::mem::take(&mut blocks[index]) {
+            match token {
+                DocToken::Span { span_id, .. } => {
+                    self.spans.remove(&span_id);
+                    for layer in self.annotations.values_mut() {
+                        layer.spans.remove(&span_id);
+                    }
+                }
+                token @ DocToken::Check { .. } => checks.push(token),
+                DocToken::Text { .. } => {}
+            }
+        }
+        let direction = block.block_directions.first().copied().unwrap_or(block.direction);
+        let mut tokens = self.adopt_spans(block);
+        tokens.extend(checks);
+        blocks[index] = tokens;
+        self.tokens = join_blocks(blocks);
+
+        if let Some(slot) = self.block_directions.get_mut(index) {
+            *slot = direction;
+        }
+        true
+    }
+
+    /// Add the blocks of `other` after the last block, its spans renumbered
+    /// after the doc's highest `span-N` as in [`Self::replace_block`].
+    pub fn append_blocks(&mut self, other: InteractiveDoc) {
+        if other.tokens.is_empty() {
+            return;
+        }
+        // Directions line up with the blocks, older docs having none.
+        let count = if self.tokens.is_empty() { 0 } else { self.block_texts().len() };
+        self.block_directions.resize(count, self.direction);
+        let added = other.block_texts().len();
+        let mut directions = other.block_directions.clone();
+        directions.resize(added, other.direction);
+
+        let tokens = self.adopt_spans(other);
+        if !self.tokens.is_empty() {
+            self.tokens.push(DocToken::Text {
+                value: "\n\n".to_string(),
+                style: None,
+                freq_band: None,
+            });
+        }
+        self.tokens.extend(tokens);
+        self.block_directions.extend(directions);
+    }
+
+    /// Move the spans of `block`, and their annotations, into the doc under
+    /// ids after its highest `span-N`, returning `block`'s tokens pointing at
+    /// the new ids.
+    fn adopt_spans(&mut self, mut block: InteractiveDoc) -> Vec<DocToken> {
+        let mut next = self
+            .spans
+            .keys()
+            .filter_map(|id| id.strip_prefix("span-")?.parse::<usize>().ok())
+            .max()
+            .unwrap_or(0);
+
+        let mut tokens = Vec::with_capacity(block.tokens.len());
+        let mut spans = block.spans;
+        for token in block.tokens {
+            let DocToken::Span { span_id, style } = token else {
+                tokens.push(token);
+                continue;
+            };
+            let Some(mut span) = spans.remove(&span_id) else {
+                continue;
+            };
+            next += 1;
+            let new_id = format!("span-{}", next);
+            for variant in &mut span.variants {
+                if let Some(rest) = variant.id.strip_prefix(&span_id) {
+                    variant.id = format!("{}{}", new_id, rest);
+                }
+            }
+            for (pass, layer) in &mut block.annotations {
+                if let Some(tokens) = layer.spans.remove(&span_id) {
+                    let layer = self.annotations.entry(pass.clone()).or_default();
+                    layer.spans.insert(new_id.clone(), tokens);
+                }
+            }
+            span.id = new_id.clone();
+            self.spans.insert(new_id.clone(), span);
+            tokens.push(DocToken::Span { span_id: new_id, style });
+        }
+        tokens
+    }
+
+    /// Direction of block `index`, falling back to the doc's.
+    pub fn block_direction(&self, index: usize) -> TextDirection {
+        self.block_directions.get(index).copied().unwrap_or(self.direction)
+    }
+
+    /// Point every span at its variant closest to `register` (see
+    /// [`register_fallback`]), skipping policy-flagged variants unless
+    /// nothing else is left. Returns how many spans changed.
+    pub fn switch_register(&mut self, register: &str) -> usize {
+        let order = register_fallback(register);
+        let rank = |v: &Variant| {
+            let distance = order.iter().position(|r| *r == v.register).unwrap_or(order.len());
+            (v.flagged.is_some(), distance)
+        };
+
+        let mut changed = 0;
+        for span in self.spans.values_mut() {
+            let best = span
+                .variants
+                .iter()
+                .enumerate()
+                .min_by_key(|(i, v)| (rank(v), *i))
+                .map(|(i, _)| i);
+            if let Some(i) = best.filter(|i| *i != span.active_variant_index) {
+                span.active_variant_index = i;
+                changed += 1;
+            }
+        }
+        changed
+    }
+}
+
+fn join_blocks(blocks: Vec<Vec<DocToken>>) -> Vec<DocToken> {
+    let mut tokens = Vec::new();
+    for (i, block) in blocks.into_iter().enumerate() {
+        if i > 0 {
+            tokens.push(DocToken::Text {
+                value: "\n\n".to_string(),
+                style: None,
+                freq_band: None,
+            });
+        }
+        tokens.extend(block);
+    }
+    tokens
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextDirection {
+    #[default]
+    Ltr,
+    Rtl,
+}
+
+impl TextDirection {
+    pub fn is_rtl(self) -> bool {
+        self == TextDirection::Rtl
+    }
+
+    /// Value for an HTML `dir` attribute.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TextDirection::Ltr => "ltr",
+            TextDirection::Rtl => "rtl",
+        }
+    }
+}
@@ -0,0 +1,300 @@
+use super::types::ApiError;
+
+/// UI locales with a message catalog. Anything else falls back to English.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    Fr,
+    Es,
+    De,
+    Ja,
+}
+
+impl Locale {
+    /// "fr-CA" -> Fr; unknown or missing -> En.
+    pub fn from_code(code: Option<&str>) -> Self {
+        let base = code
+            .map(|c| c.split(['-', '_']).next().unwrap_or(c).to_lowercase())
+            .unwrap_or_default();
+        match base.as_str() {
+            "fr" => Locale::Fr,
+            "es" => Locale::Es,
+            "de" => Locale::De,
+            "ja" | "jp" => Locale::Ja,
+            _ => Locale::En,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Stable identifiers for user-facing backend messages. The frontend may
+/// switch on the key; the wire form (`as_str`) never changes once shipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageKey {
+    AudioModelLoading,
+    AudioGenerating,
+    AudioEncoding,
+    AudioCacheHit,
+    AudioCacheUnavailable,
+    AudioModelNotLoaded,
+    AudioGenerationFailed,
+    AudioCancelled,
+    AudioCacheIo,
+    AudioWavEncode,
+    TranslationCancelled,
+    TranslationNoApiKey,
+    TranslationNetwork,
+    TranslationProviderError,
+    TranslationBadResponse,
+    TranslationInputTooLarge,
+    TranslationConfirmationRequired,
+    TranslationBudgetExceeded,
+    TranslationRefused,
+}
+
+impl MessageKey {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MessageKey::AudioModelLoading => "audio.modelLoading",
+            MessageKey::AudioGenerating => "audio.generating",
+            MessageKey::AudioEncoding => "audio.encoding",
+            MessageKey::AudioCacheHit => "audio.cacheHit",
+            MessageKey::AudioCacheUnavailable => "audio.cacheUnavailable",
+            MessageKey::AudioModelNotLoaded => "audio.modelNotLoaded",
+            MessageKey::AudioGenerationFailed => "audio.generationFailed",
+            MessageKey::AudioCancelled => "audio.cancelled",
+            MessageKey::AudioCacheIo => "audio.cacheIo",
+            MessageKey::AudioWavEncode => "audio.wavEncode",
+            MessageKey::TranslationCancelled => "translation.cancelled",
+            MessageKey::TranslationNoApiKey => "translation.noApiKey",
+            MessageKey::TranslationNetwork => "translation.network",
+            MessageKey::TranslationProviderError => "translation.providerError",
+            MessageKey::TranslationBadResponse => "translation.badResponse",
+            MessageKey::TranslationInputTooLarge => "translation.inputTooLarge",
+            MessageKey::TranslationConfirmationRequired => "translation.confirmationRequired",
+            MessageKey::TranslationBudgetExceeded => "translation.budgetExceeded",
+            MessageKey::TranslationRefused => "translation.refused",
+        }
+    }
+
+    /// Catalog entry in en, fr, es, de, ja order. `{0}`, `{1}` are arguments.
+    fn catalog(self) -> [&'static str; 5] {
+        match self {
+            MessageKey::AudioModelLoading => [
+                "Loading voice model...",
+                "Chargement du modèle vocal...",
+                "Cargando el modelo de voz...",
+                "Sprachmodell wird geladen...",
+                "音声モデルを読み込み中...",
+            ],
+            MessageKey::AudioGenerating => [
+                "Generating speech...",
+                "Génération de la voix...",
+                "Generando voz...",
+                "Sprache wird erzeugt...",
+                "音声を生成中...",
+            ],
+            MessageKey::AudioEncoding => [
+                "Encoding audio...",
+                "Encodage de l'audio...",
+                "Codificando audio...",
+                "Audio wird kodiert...",
+                "音声をエンコード中...",
+            ],
+            MessageKey::AudioCacheHit => [
+                "Found in cache",
+                "Trouvé dans le cache",
+                "Encontrado en caché",
+                "Im Cache gefunden",
+                "キャッシュから取得しました",
+            ],
+            MessageKey::AudioCacheUnavailable => [
+                "Audio cache is not available",
+                "Le cache audio n'est pas disponible",
+                "La caché de audio no está disponible",
+                "Der Audio-Cache ist nicht verfügbar",
+                "音声キャッシュを利用できません",
+            ],
+            MessageKey::AudioModelNotLoaded => [
+                "The voice model is not loaded yet",
+                "Le modèle vocal n'est pas encore chargé",
+                "El modelo de voz aún no está cargado",
+                "Das Sprachmodell ist noch nicht geladen",
+                "音声モデルがまだ読み込まれていません",
+            ],
+            MessageKey::AudioGenerationFailed => [
+                "Speech generation failed",
+                "La génération de la voix a échoué",
+                "Falló la generación de voz",
+                "Spracherzeugung fehlgeschlagen",
+                "音声の生成に失敗しました",
+            ],
+            MessageKey::AudioCancelled => [
+                "Speech generation cancelled",
+                "Génération de la voix annulée",
+                "Generación de voz cancelada",
+                "Spracherzeugung abgebrochen",
+                "音声の生成をキャンセルしました",
+            ],
+            MessageKey::AudioCacheIo => [
+                "Could not read or write the audio cache",
+                "Impossible de lire ou d'écrire le cache audio",
+                "No se pudo leer ni escribir la caché de audio",
+                "Audio-Cache konnte nicht gelesen oder geschrieben werden",
+                "音声キャッシュの読み書きに失敗しました",
+            ],
+            MessageKey::AudioWavEncode => [
+                "Could not encode the audio",
+                "Impossible d'encoder l'audio",
+                "No se pudo codificar el audio",
+                "Audio konnte nicht kodiert werden",
+                "音声をエンコードできませんでした",
+            ],
+            MessageKey::TranslationCancelled => [
+                "Translation cancelled",
+                "Traduction annulée",
+                "Traducción cancelada",
+                "Übersetzung abgebrochen",
+                "翻訳をキャンセルしました",
+            ],
+            MessageKey::TranslationNoApiKey => [
+                "No API key is set for {0}",
+                "Aucune clé API n'est définie pour {0}",
+                "No hay clave de API para {0}",
+                "Für {0} ist kein API-Schlüssel gesetzt",
+                "{0} のAPIキーが設定されていません",
+            ],
+            MessageKey::TranslationNetwork => [
+                "Could not reach the provider",
+                "Impossible de joindre le fournisseur",
+                "No se pudo contactar con el proveedor",
+                "Der Anbieter ist nicht erreichbar",
+                "プロバイダーに接続できませんでした",
+            ],
+            MessageKey::TranslationProviderError => [
+                "The provider returned an error ({0})",
+                "Le fournisseur a renvoyé une erreur ({0})",
+                "El proveedor devolvió un error ({0})",
+                "Der Anbieter hat einen Fehler gemeldet ({0})",
+                "プロバイダーがエラーを返しました（{0}）",
+            ],
+            MessageKey::TranslationBadResponse => [
+                "The provider's response could not be read",
+                "La réponse du fournisseur est illisible",
+                "No se pudo leer la respuesta del proveedor",
+                "Die Antwort des Anbieters konnte nicht gelesen werden",
+                "プロバイダーの応答を読み取れませんでした",
+            ],
+            MessageKey::TranslationInputTooLarge => [
+                "The text is too long: {0} characters (limit {1})",
+                "Le texte est trop long : {0} caractères (limite {1})",
+                "El texto es demasiado largo: {0} caracteres (límite {1})",
+                "Der Text ist zu lang: {0} Zeichen (Grenze {1})",
+                "テキストが長すぎます：{0}文字（上限{1}文字）",
+            ],
+            MessageKey::TranslationConfirmationRequired => [
+                "This long text ({0} segments) needs confirmation before translating",
+                "Ce long texte ({0} segments) doit être confirmé avant la traduction",
+                "Este texto largo ({0} segmentos) requiere confirmación antes de traducir",
+                "Dieser lange Text ({0} Segmente) muss vor dem Übersetzen bestätigt werden",
+                "この長いテキスト（{0}セグメント）は翻訳前に確認が必要です",
+            ],
+            MessageKey::TranslationBudgetExceeded => [
+                "Translation stopped at its budget ({0} tokens used)",
+                "Traduction arrêtée au budget prévu ({0} jetons utilisés)",
+                "Traducción detenida al alcanzar su presupuesto ({0} tokens usados)",
+                "Übersetzung beim Budget angehalten ({0} Tokens verbraucht)",
+                "予算に達したため翻訳を停止しました（{0}トークン使用）",
+            ],
+            MessageKey::TranslationRefused => [
+                "{0} declined this text; try another model or provider",
+                "{0} a refusé ce texte ; essayez un autre modèle ou fournisseur",
+                "{0} rechazó este texto; prueba otro modelo o proveedor",
+                "{0} hat diesen Text abgelehnt; versuchen Sie ein anderes Modell oder einen anderen Anbieter",
+                "{0} がこのテキストを拒否しました。別のモデルまたはプロバイダーをお試しください",
+            ],
+        }
+    }
+}
+
+/// Localized text for `key`, with `{0}`, `{1}`, ... replaced by `args`.
+pub fn message(key: MessageKey, locale: Locale, args: &[&str]) -> String {
+    let mut text = key.catalog()[locale.index()].to_string();
+    for (i, arg) in args.iter().enumerate() {
+        text = text.replace(&format!("{{{}}}", i), arg);
+    }
+    text
+}
+
+/// Key and localized message for a pipeline error. Technical details stay
+/// out of the message; callers pass `err.to_string()` along as the detail.
+pub fn api_error(err: &ApiError, locale: Locale) -> (MessageKey, String) {
+    match err {
+        ApiError::NoApiKey { provider } => {
+            let key = MessageKey::TranslationNoApiKey;
+            (key, message(key, locale, &[provider]))
+        }
+        ApiError::Http(_) => (MessageKey::TranslationNetwork, message(MessageKey::TranslationNetwork, locale, &[])),
+        ApiError::ApiResponse { status, .. } => {
+            let key = MessageKey::TranslationProviderError;
+            (key, message(key, locale, &[&status.to_string()]))
+        }
+        ApiError::Parse(msg) if msg == "Cancelled" => (
+            MessageKey::TranslationCancelled,
+            message(MessageKey::TranslationCancelled, locale, &[]),
+        ),
+        ApiError::Parse(_) => (
+            MessageKey::TranslationBadResponse,
+            message(MessageKey::TranslationBadResponse, locale, &[]),
+        ),
+        ApiError::InputTooLarge { chars, limit } => {
+            let key = MessageKey::TranslationInputTooLarge;
+            (key, message(key, locale, &[&chars.to_string(), &limit.to_string()]))
+        }
+        ApiError::ConfirmationRequired { segments } => {
+            let key = MessageKey::TranslationConfirmationRequired;
+            (key, message(key, locale, &[&segments.to_string()]))
+        }
+        ApiError::BudgetExceeded { tokens } => {
+            let key = MessageKey::TranslationBudgetExceeded;
+            (key, message(key, locale, &[&tokens.to_string()]))
+        }
+        ApiError::Refused { model, .. } => {
+            let key = MessageKey::TranslationRefused;
+            (key, message(key, locale, &[model]))
+        }
+    }
+}
+
+#[cfg(feature = "tts")]
+pub fn audio_stage(stage: super::audio_types::AudioStage, locale: Locale) -> (MessageKey, String) {
+    use super::audio_types::AudioStage;
+
+    let key = match stage {
+        AudioStage::ModelLoading => MessageKey::AudioModelLoading,
+        AudioStage::Generating => MessageKey::AudioGenerating,
+        AudioStage::Encoding => MessageKey::AudioEncoding,
+        AudioStage::CacheHit => MessageKey::AudioCacheHit,
+    };
+    (key, message(key, locale, &[]))
+}
+
+#[cfg(feature = "tts")]
+pub fn audio_error(err: &super::audio::AudioError, locale: Locale) -> (MessageKey, String) {
+    use super::audio::AudioError;
+
+    let key = match err {
+        AudioError::ModelNotLoaded | AudioError::ModelNotInstalled(_) => MessageKey::AudioModelNotLoaded,
+        AudioError::GenerationFailed(_)
+        | AudioError::TooLong(_)
+        | AudioError::InvalidStyle(_)
+        | AudioError::NotAWord(_) => MessageKey::AudioGenerationFailed,
+        AudioError::Cancelled => MessageKey::AudioCancelled,
+        AudioError::CacheIo(_) => MessageKey::AudioCacheIo,
+        AudioError::WavEncode(_) => MessageKey::AudioWavEncode,
+    };
+    (key, message(key, locale, &[]))
+}
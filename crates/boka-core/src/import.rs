@@ -0,0 +1,174 @@
+use super::translation::{fill_anthropic_key, Client};
+use super::types::{ApiConfig, ApiError, LlmProviderConfig, Usage};
+
+use base64::Engine as _;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Anthropic rejects larger images; OpenAI accepts more, but one limit keeps
+/// behaviour the same across providers.
+pub const MAX_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("No images given")]
+    Empty,
+
+    #[error("Could not read {path}: {message}")]
+    Io { path: String, message: String },
+
+    #[error("Unsupported image format: {0} (use PNG, JPEG, WebP or GIF)")]
+    UnsupportedFormat(String),
+
+    #[error("{path} is {bytes} bytes; images must be at most {limit} bytes")]
+    TooLarge { path: String, bytes: u64, limit: u64 },
+
+    #[error("Model {0} cannot read images; pick a vision model")]
+    NoVision(String),
+
+    #[error("No text found in the images")]
+    NoText,
+
+    #[error(transparent)]
+    Api(#[from] ApiError),
+}
+
+/// One page photo, ready to send inline.
+#[derive(Debug, Clone)]
+pub struct PageImage {
+    pub media_type: &'static str,
+    /// Base64 of the file bytes.
+    pub data: String,
+}
+
+impl PageImage {
+    pub fn load(path: &Path) -> Result<Self, ImportError> {
+        let display = path.display().to_string();
+        let media_type = media_type(path).ok_or_else(|| ImportError::UnsupportedFormat(display.clone()))?;
+
+        let io = |e: std::io::Error| ImportError::Io {
+            path: display.clone(),
+            message: e.to_string(),
+        };
+        let bytes = std::fs::metadata(path).map_err(io)?.len();
+        if bytes > MAX_IMAGE_BYTES {
+            return Err(ImportError::TooLarge {
+                path: display,
+                bytes,
+                limit: MAX_IMAGE_BYTES,
+            });
+        }
+        let raw = std::fs::read(path).map_err(io)?;
+
+        Ok(Self {
+            media_type,
+            data: base64::engine::general_purpose::STANDARD.encode(raw),
+        })
+    }
+}
+
+fn media_type(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    match ext.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "webp" => Some("image/webp"),
+        "gif" => Some("image/gif"),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedStory {
+    /// Cleaned text, ready for `run_translation`.
+    pub text: String,
+    pub pages: u32,
+    pub model: String,
+    pub usage: Usage,
+}
+
+/// Transcribe page photos in order with a vision model and clean the result
+/// into story text. Every file is validated before anything is sent.
+pub async fn import_images(paths: &[PathBuf], provider: LlmProviderConfig) -> Result<ImportedStory, ImportError> {
+    if paths.is_empty() {
+        return Err(ImportError::Empty);
+    }
+    let images = paths
+        .iter()
+        .map(|p| PageImage::load(p))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut cfg = ApiConfig::from_env("", None, false, false);
+    cfg.provider = provider;
+    fill_anthropic_key(&mut cfg);
+    let client = Client::new(cfg)?;
+    if !client.supports_vision() {
+        return Err(ImportError::NoVision(client.model().to_string()));
+    }
+
+    let mut pages = Vec::with_capacity(images.len());
+    let mut usage = Usage::default();
+    for image in &images {
+        let (text, u) = client.transcribe_image(image).await?;
+        usage += u;
+        pages.push(text);
+    }
+
+    // Pages are joined with a plain line break: a paragraph (or a hyphenated
+    // word) running over a page turn is far more common than one ending
+    // exactly at the bottom of a page.
+    let text = clean_transcription(&pages.join("\n"));
+    if text.is_empty() {
+        return Err(ImportError::NoText);
+    }
+
+    Ok(ImportedStory {
+        text,
+        pages: images.len() as u32,
+        model: client.model().to_string(),
+        usage,
+    })
+}
+
+/// Undo print layout: rejoin words hyphenated across lines, turn line breaks
+/// inside a paragraph into spaces, drop bare page numbers, and keep blank
+/// lines as paragraph breaks.
+pub fn clean_transcription(raw: &str) -> String {
+    let raw = raw.replace("\r\n", "\n").replace('\u{00AD}', "");
+    let mut paragraphs: Vec<String> = Vec::new();
+    let mut current = String::new();
+
+    for line in raw.lines().map(str::trim) {
+        if line.is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if line.chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+
+        let rejoin = current
+            .strip_suffix('-')
+            .filter(|before| before.chars().last().is_some_and(char::is_alphabetic))
+            .is_some()
+            && line.chars().next().is_some_and(char::is_lowercase);
+        if rejoin {
+            current.pop();
+        } else if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(line);
+    }
+    if !current.is_empty() {
+        paragraphs.push(current);
+    }
+
+    paragraphs
+        .iter()
+        .map(|p| p.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
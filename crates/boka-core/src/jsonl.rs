@@ -0,0 +1,422 @@
+//! JSON Lines interchange format for translated stories, shared by the GUI,
+//! the TUI/CLI and third-party scripts. It is versioned separately from the
+//! app's internal structs, which may change between releases.
+//!
+//! One doc (a story in one target language) per file. Every line is a JSON
+//! object with a `type` field; fields are camelCase and unknown fields are
+//! rejected. Blank lines are ignored. Lines come in this order:
+//!
+//! 1. Exactly one `story` line, first:
+//!    `{"type":"story","format":"boka-jsonl","version":1,"storyId":"s1",
+//!    "title":"…","sourceLanguage":"en","sourceText":"…","language":"fr"}`,
+//!    with an optional `"direction":"ltr"|"rtl"` (derived from `language`
+//!    when absent).
+//! 2. Zero or more `segment` lines, the per-sentence translation record:
+//!    `{"type":"segment","index":0,"chapter":0,"source":"…","text":"…"}`,
+//!    with an optional `simplifiedText`. `index` counts up from 0.
+//! 3. The doc itself, in reading order, as `text` and `span` lines:
+//!    `{"type":"text","value":" "}` is literal text between spans, and a
+//!    `"\n\n"` value starts a new block;
+//!    `{"type":"span","id":"span-1","sourceText":"…","activeVariantIndex":0,
+//!    "variants":[{"id":"v1","register":"neutral","text":"…"}]}` is one
+//!    interactive span. Variants may also carry `note`, `difficulty` (1–5)
+//!    and `flagged`. At least one span is required. Text and span lines may
+//!    carry a `"style":"emphasis"|"strong"`.
+//!    `{"type":"check","question":"…","answer":true}` is a comprehension
+//!    question at the end of a block, with an optional `explanation`.
+
+use super::bidi::{detect_direction, direction_for_language};
+use super::gui_types::{
+    DocToken, InteractiveDoc, SegmentStage, Span, TextDirection, TextStyle, TranslationJob, TranslationSegment,
+    Variant,
+};
+use super::naming;
+use super::policy::ALL_REGISTERS;
+use super::stories::StoryDoc;
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+pub const FORMAT: &str = "boka-jsonl";
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, thiserror::Error)]
+pub enum JsonlError {
+    #[error("Line {line}: {message}")]
+    Invalid { line: usize, message: String },
+
+    #[error("Empty file: expected a story line")]
+    Empty,
+
+    #[error("Failed to serialize JSONL: {0}")]
+    Serialize(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum JsonlLine {
+    Story(StoryLine),
+    Segment(SegmentLine),
+    Text(TextLine),
+    Span(SpanLine),
+    Check(CheckLine),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct StoryLine {
+    pub format: String,
+    pub version: u32,
+    pub story_id: String,
+    pub title: String,
+    pub source_language: String,
+    pub source_text: String,
+    pub language: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direction: Option<TextDirection>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SegmentLine {
+    pub index: u32,
+    #[serde(default)]
+    pub chapter: u32,
+    pub source: String,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub simplified_text: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TextLine {
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<TextStyle>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SpanLine {
+    pub id: String,
+    pub source_text: String,
+    pub active_variant_index: usize,
+    pub variants: Vec<VariantLine>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<TextStyle>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CheckLine {
+    pub question: String,
+    pub answer: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct VariantLine {
+    pub id: String,
+    pub register: String,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flagged: Option<String>,
+}
+
+/// Write a doc, and its job's segments when it has one, as JSON Lines.
+pub fn to_jsonl(story: &StoryDoc) -> Result<String, JsonlError> {
+    let mut lines = vec![JsonlLine::Story(StoryLine {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        story_id: story.story_id.clone(),
+        title: story.title.clone(),
+        source_language: story.source_language.clone(),
+        source_text: story.source_text.clone(),
+        language: story.language.clone(),
+        direction: Some(story.doc.direction),
+    })];
+
+    if let Some(job) = &story.job {
+        lines.extend(segment_lines(job).into_iter().map(JsonlLine::Segment));
+    }
+
+    for token in &story.doc.tokens {
+        match token {
+            DocToken::Text { value, style, .. } => lines.push(JsonlLine::Text(TextLine {
+                value: value.clone(),
+                style: *style,
+            })),
+            DocToken::Span { span_id, style } => {
+                let Some(span) = story.doc.spans.get(span_id) else {
+                    continue;
+                };
+                lines.push(JsonlLine::Span(SpanLine {
+                    id: span.id.clone(),
+                    source_text: span.source_text.clone(),
+                    active_variant_index: span.active_variant_index,
+                    variants: span
+                        .variants
+                        .iter()
+                        .map(|v| VariantLine {
+                            id: v.id.clone(),
+                            register: v.register.clone(),
+                            text: v.text.clone(),
+                            note: v.note.clone(),
+                            difficulty: v.difficulty,
+                            flagged: v.flagged.clone(),
+                        })
+                        .collect(),
+                    style: *style,
+                }));
+            }
+            DocToken::Check {
+                question,
+                answer,
+                explanation,
+            } => lines.push(JsonlLine::Check(CheckLine {
+                question: question.clone(),
+                answer: *answer,
+                explanation: explanation.clone(),
+            })),
+        }
+    }
+
+    let mut out = String::new();
+    for line in &lines {
+        out.push_str(&serde_json::to_string(line).map_err(|e| JsonlError::Serialize(e.to_string()))?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// A job's segments in interchange form.
+pub(crate) fn segment_lines(job: &TranslationJob) -> Vec<SegmentLine> {
+    job.segments
+        .iter()
+        .enumerate()
+        .map(|(index, s)| SegmentLine {
+            index: index as u32,
+            chapter: s.chapter,
+            source: s.source.clone(),
+            text: s.base_text.clone().unwrap_or_default(),
+            simplified_text: s.simplified_text.clone(),
+        })
+        .collect()
+}
+
+/// Parse and validate a JSON Lines doc. The whole file is checked before
+/// anything is returned; the first problem found is reported with its line.
+pub fn from_jsonl(text: &str) -> Result<StoryDoc, JsonlError> {
+    let mut header: Option<StoryLine> = None;
+    let mut segments: Vec<SegmentLine> = Vec::new();
+    let mut tokens: Vec<DocToken> = Vec::new();
+    let mut spans: HashMap<String, Span> = HashMap::new();
+    let mut in_doc = false;
+
+    for (i, raw) in text.lines().enumerate() {
+        let line = i + 1;
+        let invalid = |message: String| JsonlError::Invalid { line, message };
+        if raw.trim().is_empty() {
+            continue;
+        }
+        let parsed: JsonlLine = serde_json::from_str(raw).map_err(|e| invalid(e.to_string()))?;
+
+        if header.is_none() && !matches!(parsed, JsonlLine::Story(_)) {
+            return Err(invalid("the first line must be the story line".to_string()));
+        }
+        match parsed {
+            JsonlLine::Story(story) => {
+                if header.is_some() {
+                    return Err(invalid("only one story line is allowed".to_string()));
+                }
+                validate_story(&story).map_err(invalid)?;
+                header = Some(story);
+            }
+            JsonlLine::Segment(segment) => {
+                if in_doc {
+                    return Err(invalid("segment lines must come before text and span lines".to_string()));
+                }
+                if segment.index as usize != segments.len() {
+                    return Err(invalid(format!(
+                        "segment index {} out of order, expected {}",
+                        segment.index,
+                        segments.len()
+                    )));
+                }
+                segments.push(segment);
+            }
+            JsonlLine::Text(t) => {
+                in_doc = true;
+                if t.value.is_empty() {
+                    return Err(invalid("text value must not be empty".to_string()));
+                }
+                tokens.push(DocToken::Text {
+                    value: t.value,
+                    style: t.style,
+                    freq_band: None,
+                });
+            }
+            JsonlLine::Span(span) => {
+                in_doc = true;
+                let style = span.style;
+                let span = validate_span(span).map_err(invalid)?;
+                if spans.contains_key(&span.id) {
+                    return Err(invalid(format!("duplicate span id `{}`", span.id)));
+                }
+                tokens.push(DocToken::Span {
+                    span_id: span.id.clone(),
+                    style,
+                });
+                spans.insert(span.id.clone(), span);
+            }
+            JsonlLine::Check(check) => {
+                in_doc = true;
+                if check.question.trim().is_empty() {
+                    return Err(invalid("check question must not be empty".to_string()));
+                }
+                tokens.push(DocToken::Check {
+                    question: check.question,
+                    answer: check.answer,
+                    explanation: check.explanation,
+                });
+            }
+        }
+    }
+
+    let header = header.ok_or(JsonlError::Empty)?;
+    if spans.is_empty() {
+        return Err(JsonlError::Invalid {
+            line: text.lines().count(),
+            message: "a doc needs at least one span line".to_string(),
+        });
+    }
+
+    let direction = header.direction.unwrap_or_else(|| direction_for_language(&header.language));
+    let mut doc = InteractiveDoc {
+        tokens,
+        spans,
+        direction,
+        block_directions: vec![],
+        annotations: Default::default(),
+    };
+    doc.block_directions = doc
+        .block_texts()
+        .iter()
+        .map(|b| detect_direction(b).unwrap_or(direction))
+        .collect();
+
+    let job = (!segments.is_empty()).then(|| TranslationJob {
+        id: format!("import-{}-{}", header.story_id, header.language),
+        segments: segments
+            .into_iter()
+            .map(|s| TranslationSegment {
+                id: format!("seg-{}", s.index),
+                source: s.source,
+                chapter: s.chapter,
+                base_text: Some(s.text),
+                base_stage: SegmentStage::Ready,
+                span_stage: SegmentStage::Ready,
+                variant_count: 0,
+                variant_target: 0,
+                score: None,
+                simplified_text: s.simplified_text,
+                simplification: None,
+                edited: false,
+                auto_split: false,
+                refused: None,
+                provenance: vec![],
+                spans: vec![],
+            })
+            .collect(),
+        ready: true,
+        awaiting_review: false,
+        metadata: None,
+        lints: Vec::new(),
+    });
+
+    Ok(StoryDoc {
+        story_id: header.story_id,
+        slug: naming::slugify(&header.title),
+        title: header.title,
+        source_text: header.source_text,
+        source_language: header.source_language,
+        language: header.language,
+        doc,
+        job,
+    })
+}
+
+fn validate_story(story: &StoryLine) -> Result<(), String> {
+    if story.format != FORMAT {
+        return Err(format!("format must be `{}`, got `{}`", FORMAT, story.format));
+    }
+    if story.version != VERSION {
+        return Err(format!("unsupported version {} (this build reads version {})", story.version, VERSION));
+    }
+    if story.story_id.trim().is_empty() {
+        return Err("storyId must not be empty".to_string());
+    }
+    if story.language.trim().is_empty() || story.language.contains(':') {
+        return Err(format!("invalid language `{}`", story.language));
+    }
+    Ok(())
+}
+
+fn validate_span(span: SpanLine) -> Result<Span, String> {
+    if span.id.trim().is_empty() {
+        return Err("span id must not be empty".to_string());
+    }
+    if span.variants.is_empty() {
+        return Err(format!("span `{}` has no variants", span.id));
+    }
+    if span.active_variant_index >= span.variants.len() {
+        return Err(format!(
+            "span `{}`: activeVariantIndex {} out of range for {} variants",
+            span.id,
+            span.active_variant_index,
+            span.variants.len()
+        ));
+    }
+
+    let mut ids = HashSet::new();
+    for v in &span.variants {
+        if !ids.insert(v.id.as_str()) {
+            return Err(format!("span `{}`: duplicate variant id `{}`", span.id, v.id));
+        }
+        if !ALL_REGISTERS.contains(&v.register.as_str()) {
+            return Err(format!("span `{}`: unknown register `{}`", span.id, v.register));
+        }
+        if v.text.trim().is_empty() {
+            return Err(format!("span `{}`: variant `{}` has no text", span.id, v.id));
+        }
+        if v.difficulty.is_some_and(|d| !(1..=5).contains(&d)) {
+            return Err(format!("span `{}`: difficulty must be 1-5", span.id));
+        }
+    }
+
+    Ok(Span {
+        id: span.id,
+        source_text: span.source_text,
+        active_variant_index: span.active_variant_index,
+        provenance: vec![],
+        variants: span
+            .variants
+            .into_iter()
+            .map(|v| Variant {
+                id: v.id,
+                register: v.register,
+                text: v.text,
+                note: v.note,
+                difficulty: v.difficulty,
+                flagged: v.flagged,
+            })
+            .collect(),
+    })
+}
@@ -0,0 +1,71 @@
+use super::types::{ApiError, LlmProviderConfig};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Optional evaluation pass over base translations.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JudgeConfig {
+    /// Model that scores; `None` reuses the job's provider.
+    #[serde(default)]
+    pub provider: Option<LlmProviderConfig>,
+    /// Segments whose lower score is below this are flagged for review.
+    #[serde(default = "default_min_score")]
+    pub min_score: u8,
+    /// Retranslate a low-scoring segment once and keep the better attempt.
+    #[serde(default)]
+    pub auto_retry: bool,
+}
+
+fn default_min_score() -> u8 {
+    3
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JudgeVerdict {
+    pub adequacy: u8,
+    pub fluency: u8,
+    pub rationale: String,
+}
+
+impl JudgeVerdict {
+    /// The weaker of the two axes; a fluent mistranslation is still a mistranslation.
+    pub fn overall(&self) -> u8 {
+        self.adequacy.min(self.fluency)
+    }
+}
+
+/// Parse `{ "adequacy", "fluency", "rationale" }`, tolerating code fences and
+/// scores sent as strings or floats. Scores are clamped to 1–5.
+pub(crate) fn parse_verdict(text: &str) -> Result<JudgeVerdict, ApiError> {
+    let cleaned = text
+        .trim()
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim();
+
+    let value: Value = serde_json::from_str(cleaned)
+        .map_err(|e| ApiError::Parse(format!("Judge JSON parse: {} | output: {}", e, cleaned)))?;
+
+    let score = |key: &str| -> Result<u8, ApiError> {
+        let v = value.get(key);
+        let n = v
+            .and_then(Value::as_f64)
+            .or_else(|| v.and_then(Value::as_str).and_then(|s| s.trim().parse().ok()))
+            .ok_or_else(|| ApiError::Parse(format!("Judge JSON parse: missing `{}` | output: {}", key, cleaned)))?;
+        Ok(n.round().clamp(1.0, 5.0) as u8)
+    };
+
+    Ok(JudgeVerdict {
+        adequacy: score("adequacy")?,
+        fluency: score("fluency")?,
+        rationale: value
+            .get("rationale")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+    })
+}
@@ -0,0 +1,67 @@
+//! What the app can do in each language, in one table, so the GUI can stop
+//! offering combinations that quietly degrade: a language the prompts don't
+//! name, speech with no model for it, frequency bands with no list.
+//!
+//! Languages are the ones the prompts know, plus any a TTS model or a
+//! user's frequency list adds.
+
+use super::bidi::direction_for_language;
+use super::frequency::{self, FreqList};
+use super::gui_types::TextDirection;
+use super::prompts::{language_name, KNOWN_LANGUAGES};
+use super::translation::{segmentation_tier, SegmentationTier};
+use super::tts_models::TtsModelRegistry;
+
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageSupport {
+    pub code: String,
+    pub name: String,
+    /// The prompts name the language; other codes are sent to the model as is.
+    pub translation: bool,
+    /// TTS models that speak the language, installed or not.
+    pub tts_models: Vec<String>,
+    /// No romanizer ships yet; always false.
+    pub romanization: bool,
+    /// A built-in list or one of the user's.
+    pub frequency_list: bool,
+    pub segmentation: SegmentationTier,
+    pub direction: TextDirection,
+}
+
+/// One row per language, the prompts' languages first.
+pub fn language_support(registry: &TtsModelRegistry, data_dir: &Path) -> Vec<LanguageSupport> {
+    let mut extra: Vec<String> = registry
+        .models
+        .iter()
+        .flat_map(|model| model.languages.iter().map(|l| l.to_ascii_lowercase()))
+        .chain(frequency::user_languages(data_dir))
+        .filter(|code| !KNOWN_LANGUAGES.contains(&code.as_str()))
+        .collect();
+    extra.sort();
+    extra.dedup();
+
+    KNOWN_LANGUAGES
+        .iter()
+        .map(|code| code.to_string())
+        .chain(extra)
+        .map(|code| LanguageSupport {
+            name: language_name(&code).to_string(),
+            translation: language_name(&code) != code,
+            tts_models: registry
+                .models
+                .iter()
+                .filter(|model| model.supports(&code))
+                .map(|model| model.id.clone())
+                .collect(),
+            romanization: false,
+            frequency_list: FreqList::available(data_dir, &code),
+            segmentation: segmentation_tier(&code),
+            direction: direction_for_language(&code),
+            code,
+        })
+        .collect()
+}
@@ -0,0 +1,112 @@
+/// Lightweight lemmatization, shared by vocabulary lists, exercises, the
+/// known-word ledger and frequency bands.
+///
+/// Looks up the commonest irregular forms ("went" -> "go", "est" -> "être"),
+/// then strips the most common inflectional suffixes per language so that
+/// "cats"/"cat" or "parlait"/"parler" collapse onto a shared key. It is a
+/// heuristic stemmer, good enough for counting and matching, not linguistics.
+pub fn lemma(word: &str, language: &str) -> String {
+    let w = word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase();
+    if let Some(base) = irregular(&w, language) {
+        return base.to_string();
+    }
+    if w.chars().count() <= 3 {
+        return w;
+    }
+
+    let suffixes: &[&str] = match base_language(language) {
+        "en" => &["ies", "ing", "ied", "ed", "es", "s"],
+        "fr" => &["issements", "issement", "erions", "eraient", "aient", "ement", "ions", "ait", "ant", "ées", "és", "ée", "er", "ez", "es", "s", "x", "e"],
+        "es" => &["aciones", "ación", "amente", "ando", "iendo", "aban", "aron", "ados", "adas", "ado", "ada", "es", "s", "a", "o"],
+        "it" => &["azione", "mente", "ando", "endo", "ato", "ata", "ati", "ate", "i", "e", "a", "o"],
+        "pt" => &["ações", "ação", "mente", "ando", "endo", "ados", "adas", "ado", "ada", "es", "s", "a", "o"],
+        "de" => &["ungen", "ung", "en", "er", "es", "em", "e", "n", "s"],
+        "nl" => &["heden", "heid", "en", "er", "e", "s"],
+        _ => &[],
+    };
+
+    for suffix in suffixes {
+        if let Some(stem) = w.strip_suffix(suffix) {
+            if stem.chars().count() >= 3 {
+                return stem.to_string();
+            }
+        }
+    }
+    w
+}
+
+/// Irregular forms no suffix rule reaches: form, lemma.
+fn irregular(word: &str, language: &str) -> Option<&'static str> {
+    let forms: &[(&str, &str)] = match base_language(language) {
+        "en" => &[
+            ("am", "be"), ("is", "be"), ("are", "be"), ("was", "be"), ("were", "be"), ("been", "be"),
+            ("has", "have"), ("had", "have"), ("does", "do"), ("did", "do"), ("done", "do"),
+            ("went", "go"), ("gone", "go"), ("said", "say"), ("made", "make"), ("took", "take"),
+            ("taken", "take"), ("saw", "see"), ("seen", "see"), ("came", "come"), ("knew", "know"),
+            ("known", "know"), ("got", "get"), ("thought", "think"), ("told", "tell"), ("found", "find"),
+            ("gave", "give"), ("given", "give"), ("ran", "run"), ("ate", "eat"), ("slept", "sleep"),
+            ("children", "child"), ("men", "man"), ("women", "woman"), ("mice", "mouse"),
+            ("feet", "foot"), ("teeth", "tooth"),
+        ],
+        "fr" => &[
+            ("suis", "être"), ("es", "être"), ("est", "être"), ("sommes", "être"), ("êtes", "être"),
+            ("sont", "être"), ("était", "être"), ("étaient", "être"), ("été", "être"), ("fut", "être"),
+            ("ai", "avoir"), ("as", "avoir"), ("a", "avoir"), ("avons", "avoir"), ("avez", "avoir"),
+            ("ont", "avoir"), ("avait", "avoir"), ("eu", "avoir"), ("vais", "aller"), ("va", "aller"),
+            ("vont", "aller"), ("allait", "aller"), ("fais", "faire"), ("fait", "faire"),
+            ("font", "faire"), ("faisait", "faire"), ("peux", "pouvoir"), ("peut", "pouvoir"),
+            ("peuvent", "pouvoir"), ("pouvait", "pouvoir"), ("veux", "vouloir"), ("veut", "vouloir"),
+            ("veulent", "vouloir"), ("voulait", "vouloir"), ("dit", "dire"), ("disait", "dire"),
+            ("dort", "dormir"), ("dorment", "dormir"), ("yeux", "œil"),
+        ],
+        "es" => &[
+            ("soy", "ser"), ("eres", "ser"), ("es", "ser"), ("somos", "ser"), ("son", "ser"),
+            ("era", "ser"), ("fue", "ser"), ("fueron", "ser"), ("estoy", "estar"), ("está", "estar"),
+            ("están", "estar"), ("estaba", "estar"), ("he", "haber"), ("ha", "haber"), ("han", "haber"),
+            ("había", "haber"), ("voy", "ir"), ("va", "ir"), ("van", "ir"), ("iba", "ir"),
+            ("tengo", "tener"), ("tiene", "tener"), ("tienen", "tener"), ("tenía", "tener"),
+            ("hago", "hacer"), ("hace", "hacer"), ("hizo", "hacer"), ("puedo", "poder"),
+            ("puede", "poder"), ("pueden", "poder"), ("podía", "poder"), ("digo", "decir"),
+            ("dice", "decir"), ("dijo", "decir"), ("duerme", "dormir"),
+        ],
+        "de" => &[
+            ("bin", "sein"), ("bist", "sein"), ("ist", "sein"), ("sind", "sein"), ("seid", "sein"),
+            ("war", "sein"), ("waren", "sein"), ("gewesen", "sein"), ("habe", "haben"), ("hast", "haben"),
+            ("hat", "haben"), ("hatte", "haben"), ("hatten", "haben"), ("wird", "werden"),
+            ("wirst", "werden"), ("werde", "werden"), ("wurde", "werden"), ("wurden", "werden"),
+            ("geworden", "werden"), ("kann", "können"), ("kannst", "können"), ("konnte", "können"),
+            ("muss", "müssen"), ("musst", "müssen"), ("musste", "müssen"), ("geht", "gehen"),
+            ("ging", "gehen"), ("gegangen", "gehen"), ("gibt", "geben"), ("gab", "geben"),
+            ("gegeben", "geben"),
+        ],
+        _ => &[],
+    };
+    forms.iter().find(|(form, _)| *form == word).map(|(_, base)| *base)
+}
+
+/// "en-gb" -> "en", "pt-BR" -> "pt".
+pub fn base_language(code: &str) -> &str {
+    code.split(['-', '_']).next().unwrap_or(code)
+}
+
+/// Languages written without spaces between words; counted per character.
+pub fn is_unspaced_script(language: &str) -> bool {
+    matches!(base_language(language), "ja" | "jp" | "zh" | "cn" | "th")
+}
+
+/// Split text into word-like tokens. Unspaced scripts yield one token per
+/// non-punctuation character.
+pub fn words<'a>(text: &'a str, language: &str) -> Vec<&'a str> {
+    if is_unspaced_script(language) {
+        return text
+            .char_indices()
+            .filter(|(_, c)| c.is_alphanumeric())
+            .map(|(i, c)| &text[i..i + c.len_utf8()])
+            .collect();
+    }
+
+    text.split(|c: char| !(c.is_alphanumeric() || c == '\'' || c == '’' || c == '-'))
+        .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|w| !w.is_empty())
+        .collect()
+}
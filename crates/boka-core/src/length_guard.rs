@@ -0,0 +1,108 @@
+//! Guard on the length of base translations. Some models pad a translation
+//! with a preamble ("Here is the translation:") or notes on their word
+//! choices, whatever the prompt says. Known preambles are stripped from
+//! every reply; a reply still much longer than its source trips the guard,
+//! and the segment is asked for once more with a firmer instruction (see
+//! `Client::translate_base_segment` in [`translation`](super::translation)).
+//!
+//! Short replies never trip it: "Sí." against "Yes." or a Japanese line
+//! against two English words says nothing about padding.
+
+use super::settings::SettingsError;
+
+use serde::{Deserialize, Serialize};
+
+/// Replies shorter than this, in characters, are not length-checked.
+pub const MIN_GUARDED_CHARS: usize = 40;
+
+/// Sent with the second request when the guard trips.
+pub const FIRMER_NOTE: &str = "Your previous answer was much longer than the segment. Reply with the translation \
+                               of the segment only: no preamble, notes, explanations or alternatives.";
+
+/// How much longer than its source a base translation may be.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LengthGuard {
+    pub enabled: bool,
+    /// Most characters of translation per character of source.
+    pub max_ratio: f64,
+}
+
+impl Default for LengthGuard {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_ratio: 2.5,
+        }
+    }
+}
+
+impl LengthGuard {
+    /// Bounds of `max_ratio`; below the lower one, wordy languages trip it
+    /// on honest translations.
+    pub const MIN_RATIO: f64 = 1.5;
+    pub const MAX_RATIO: f64 = 10.0;
+
+    pub fn new(enabled: bool, max_ratio: f64) -> Result<Self, SettingsError> {
+        if !(Self::MIN_RATIO..=Self::MAX_RATIO).contains(&max_ratio) {
+            return Err(SettingsError::Invalid(format!(
+                "the length ratio must be between {} and {}",
+                Self::MIN_RATIO,
+                Self::MAX_RATIO
+            )));
+        }
+        Ok(Self { enabled, max_ratio })
+    }
+
+    /// Whether `translation` is too long to be only a translation of `source`.
+    pub fn trips(&self, source: &str, translation: &str) -> bool {
+        let chars = translation.trim().chars().count();
+        self.enabled
+            && chars >= MIN_GUARDED_CHARS
+            && chars as f64 > source.trim().chars().count() as f64 * self.max_ratio
+    }
+}
+
+/// How a preamble starts, lowercased.
+const OPENERS: [&str; 11] = [
+    "here is",
+    "here's",
+    "here are",
+    "below is",
+    "sure",
+    "certainly",
+    "of course",
+    "okay",
+    "the translation",
+    "translation",
+    "translated",
+];
+
+/// A preamble line is at most this long, up to its colon.
+const MAX_PREAMBLE_CHARS: usize = 80;
+
+/// `text` without a leading "Here is the translation:"-style line. A
+/// preamble opens like one, or is a label such as "French translation",
+/// mentions a translation and ends with a colon; the translation may follow
+/// on the same line or the next.
+pub fn strip_preamble(text: &str) -> String {
+    let text = text.trim();
+    let Some(colon) = text.find(':') else {
+        return text.to_string();
+    };
+    let head = text[..colon].to_lowercase();
+    let head = head.trim_start_matches(|c: char| !c.is_alphanumeric());
+    let is_preamble = head.chars().count() <= MAX_PREAMBLE_CHARS
+        && !head.contains('\n')
+        && (OPENERS.iter().any(|o| head.starts_with(o)) || head.ends_with("translation"))
+        && head.contains("translat");
+    // "**Translation:** …" closes its emphasis after the colon.
+    let marks = &text[..text.len() - text.trim_start_matches(['*', '_']).len()];
+    let rest = text[colon + 1..].trim_start();
+    let rest = rest.strip_prefix(marks).unwrap_or(rest).trim();
+    if is_preamble && !rest.is_empty() {
+        rest.to_string()
+    } else {
+        text.to_string()
+    }
+}
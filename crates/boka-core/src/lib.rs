@@ -0,0 +1,80 @@
+//! Boka core: translation pipeline, LLM provider clients, prompts and TTS.
+//! Shared by the Tauri desktop app and the TUI/CLI; has no Tauri dependency.
+
+pub mod analysis;
+pub mod annotate;
+pub mod anthropic;
+pub mod authz;
+pub mod background;
+pub mod bidi;
+pub mod bundle;
+#[cfg(feature = "tts")]
+pub mod audio;
+// Plain wire types, available without `tts` so commands can take them either way.
+pub mod audio_types;
+pub mod cassette;
+pub mod command_error;
+pub mod comprehension;
+pub mod config_watch;
+pub mod content_hash;
+pub mod continuation;
+#[cfg(any(test, feature = "mock"))]
+pub mod demo;
+pub mod doc_cache;
+pub mod experiment;
+pub mod export;
+pub mod frequency;
+pub mod gui_types;
+pub mod i18n;
+pub mod import;
+pub mod jsonl;
+pub mod judge;
+pub mod language_support;
+pub mod lemma;
+pub mod length_guard;
+pub mod library;
+pub mod limits;
+pub mod lint;
+pub mod markup;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod moderation;
+pub mod naming;
+pub mod notes;
+pub mod openai_compat;
+pub mod paths;
+pub mod policy;
+pub mod practice_plan;
+pub mod pricing;
+pub mod privacy;
+pub mod profile;
+pub mod profiles;
+pub mod prompts;
+pub mod provider_check;
+pub mod reasoning;
+pub mod refusal;
+pub mod render;
+pub mod report;
+pub mod runtime_info;
+pub mod settings;
+pub mod simple_format;
+pub mod simplify;
+pub mod speakers;
+pub mod speech_prefetch;
+pub mod starred;
+pub mod stories;
+pub mod story_meta;
+#[cfg(feature = "tts")]
+pub mod system_tts;
+pub mod text;
+pub mod translation;
+pub mod translation_cache;
+pub mod transcript;
+pub mod trash;
+pub mod tutor;
+pub mod tts_models;
+pub mod types;
+pub mod typography;
+pub mod usages;
+pub mod vocab_ledger;
+pub mod word_card;
@@ -0,0 +1,453 @@
+//! Organizing the library: collections of stories and docs, and queries
+//! over `stories.json` by text, language, level, completion, tag,
+//! collection and the provider or model that translated a doc.
+//!
+//! Tags live on the story and translation entries themselves (see
+//! [`stories::set_tags`]). Collections need a home outside the story array,
+//! so they are kept in `collections.json` next to it.
+
+use super::analysis::analyze_text;
+use super::gui_types::{InteractiveDoc, SegmentStage, TranslationSegment};
+use super::paths;
+use super::simplify::CefrLevel;
+use super::stories::{self, now_ms};
+use super::story_meta::{self, StoryMeta};
+use super::types::LlmProviderPreset;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const COLLECTIONS_FILE: &str = "collections.json";
+
+#[derive(Debug, thiserror::Error)]
+pub enum LibraryError {
+    #[error("Library I/O error: {0}")]
+    Io(String),
+
+    #[error("Failed to parse library: {0}")]
+    Parse(String),
+
+    #[error("No collection {0}")]
+    NotFound(String),
+
+    #[error("Invalid collection: {0}")]
+    Invalid(String),
+}
+
+/// A story, or with `language` one of its translations.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryItem {
+    pub story_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Collection {
+    pub id: String,
+    pub name: String,
+    pub created_at: u64,
+    #[serde(default)]
+    pub items: Vec<LibraryItem>,
+}
+
+impl Collection {
+    fn contains(&self, story_id: &str, language: Option<&str>) -> bool {
+        self.items
+            .iter()
+            .any(|i| i.story_id == story_id && i.language.as_deref() == language)
+    }
+}
+
+/// All collections, in creation order.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Collections(pub Vec<Collection>);
+
+impl Collections {
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join(COLLECTIONS_FILE)
+    }
+
+    pub fn load(data_dir: &Path) -> Result<Self, LibraryError> {
+        let path = Self::path(data_dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(&path).map_err(|e| LibraryError::Io(e.to_string()))?;
+        serde_json::from_str(&raw).map_err(|e| LibraryError::Parse(e.to_string()))
+    }
+
+    /// Atomic write: tmp file, then rename.
+    pub fn save(&self, data_dir: &Path) -> Result<(), LibraryError> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| LibraryError::Parse(e.to_string()))?;
+        paths::write_atomic(&Self::path(data_dir), json.as_bytes())
+            .map_err(|e| LibraryError::Io(e.to_string()))
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Collection> {
+        self.0.iter().find(|c| c.id == id)
+    }
+
+    /// Names are trimmed and must be unique, ignoring case.
+    fn check_name(&self, name: &str, except: Option<&str>) -> Result<String, LibraryError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(LibraryError::Invalid("name must not be empty".to_string()));
+        }
+        let taken = self
+            .0
+            .iter()
+            .any(|c| Some(c.id.as_str()) != except && c.name.to_lowercase() == name.to_lowercase());
+        if taken {
+            return Err(LibraryError::Invalid(format!("a collection named `{}` exists", name)));
+        }
+        Ok(name.to_string())
+    }
+
+    pub fn create(&mut self, name: &str, items: Vec<LibraryItem>) -> Result<Collection, LibraryError> {
+        let name = self.check_name(name, None)?;
+        let created_at = now_ms();
+        let mut id = format!("col-{}", created_at);
+        let mut n = 1;
+        while self.get(&id).is_some() {
+            n += 1;
+            id = format!("col-{}-{}", created_at, n);
+        }
+        let collection = Collection {
+            id,
+            name,
+            created_at,
+            items: dedup(items),
+        };
+        self.0.push(collection.clone());
+        Ok(collection)
+    }
+
+    /// Rename the collection and/or replace its items.
+    pub fn update(
+        &mut self,
+        id: &str,
+        name: Option<&str>,
+        items: Option<Vec<LibraryItem>>,
+    ) -> Result<Collection, LibraryError> {
+        let name = name.map(|n| self.check_name(n, Some(id))).transpose()?;
+        let collection = self
+            .0
+            .iter_mut()
+            .find(|c| c.id == id)
+            .ok_or_else(|| LibraryError::NotFound(id.to_string()))?;
+        if let Some(name) = name {
+            collection.name = name;
+        }
+        if let Some(items) = items {
+            collection.items = dedup(items);
+        }
+        Ok(collection.clone())
+    }
+
+    pub fn delete(&mut self, id: &str) -> Result<(), LibraryError> {
+        let before = self.0.len();
+        self.0.retain(|c| c.id != id);
+        if self.0.len() == before {
+            return Err(LibraryError::NotFound(id.to_string()));
+        }
+        Ok(())
+    }
+}
+
+fn dedup(items: Vec<LibraryItem>) -> Vec<LibraryItem> {
+    let mut out: Vec<LibraryItem> = Vec::with_capacity(items.len());
+    for item in items {
+        if !out.contains(&item) {
+            out.push(item);
+        }
+    }
+    out
+}
+
+/// How far a translation got.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CompletionStatus {
+    /// Started and not finished yet.
+    Translating,
+    /// Finished with skipped segments (see `ErrorPolicy`).
+    Partial,
+    Complete,
+    /// Stopped by an error before there was a doc.
+    Failed,
+}
+
+/// Every set field must match. `text` matches stories whose title or source
+/// text contains all of its words, ignoring case.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryFilter {
+    #[serde(default)]
+    pub text: Option<String>,
+    /// Translation language.
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub level: Option<CefrLevel>,
+    #[serde(default)]
+    pub status: Option<CompletionStatus>,
+    /// Tags that must all be present, on the story or the translation.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Collection id.
+    #[serde(default)]
+    pub collection: Option<String>,
+    /// Provider that made any of a translation's calls.
+    #[serde(default)]
+    pub provider: Option<LlmProviderPreset>,
+    /// Part of the id of a model that made any of a translation's calls,
+    /// ignoring case: "claude" finds Claude models on every provider.
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+impl LibraryFilter {
+    fn filters_docs(&self) -> bool {
+        self.language.is_some()
+            || self.level.is_some()
+            || self.status.is_some()
+            || self.provider.is_some()
+            || self.model.is_some()
+    }
+
+    /// Whether one of `models` is of the wanted provider and model.
+    fn matches_models(&self, models: &[DocModel]) -> bool {
+        if self.provider.is_none() && self.model.is_none() {
+            return true;
+        }
+        let wanted = self.model.as_deref().map(|m| m.trim().to_lowercase());
+        models.iter().any(|m| {
+            self.provider.map_or(true, |p| p == m.provider)
+                && wanted.as_deref().map_or(true, |w| m.model.to_lowercase().contains(w))
+        })
+    }
+}
+
+/// A provider and model that made some of a translation's calls.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocModel {
+    pub provider: LlmProviderPreset,
+    pub model: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryDoc {
+    pub language: String,
+    pub status: CompletionStatus,
+    /// The graded-reader level for simplified docs, else an estimate from
+    /// the translated text (see `analysis`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<CefrLevel>,
+    pub graded: bool,
+    pub tags: Vec<String>,
+    /// Ids of the collections holding this translation.
+    pub collections: Vec<String>,
+    /// Shelf title, synopsis and cover, once generated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<StoryMeta>,
+    /// Who translated it: the job's model and any other that answered its
+    /// calls, from the provenance kept in the job and doc.
+    pub models: Vec<DocModel>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryEntry {
+    pub story_id: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    pub source_language: String,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub tags: Vec<String>,
+    /// Ids of the collections holding the story itself.
+    pub collections: Vec<String>,
+    /// The translations that match; all of them when only story-level
+    /// filters were given.
+    pub docs: Vec<LibraryDoc>,
+}
+
+/// Stories matching `filter`, most recently updated first.
+pub fn query(
+    stories: &Value,
+    collections: &Collections,
+    filter: &LibraryFilter,
+) -> Result<Vec<LibraryEntry>, LibraryError> {
+    let list = stories
+        .as_array()
+        .ok_or_else(|| LibraryError::Parse("stories.json is not an array".to_string()))?;
+    let collection = match &filter.collection {
+        Some(id) => Some(collections.get(id).ok_or_else(|| LibraryError::NotFound(id.clone()))?),
+        None => None,
+    };
+    let words: Vec<String> = filter
+        .text
+        .as_deref()
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect();
+    let wanted_tags: Vec<String> = filter.tags.iter().map(|t| t.trim().to_lowercase()).collect();
+
+    let mut entries = Vec::new();
+    for story in list {
+        let field = |name: &str| story.get(name).and_then(Value::as_str).unwrap_or_default().to_string();
+        let story_id = field("id");
+        let title = field("title");
+        let source_text = field("sourceText");
+        let haystack = format!("{}\n{}", title, source_text).to_lowercase();
+        if !words.iter().all(|w| haystack.contains(w.as_str())) {
+            continue;
+        }
+
+        let story_tags = stories::tags_of(story);
+        let in_collection = |language: Option<&str>| collection.map_or(true, |c| c.contains(&story_id, language));
+        let story_level = wanted_tags.iter().all(|t| story_tags.contains(t)) && in_collection(None);
+
+        let mut docs = Vec::new();
+        let translations = story.get("translations").and_then(Value::as_object);
+        for (language, translation) in translations.into_iter().flatten() {
+            let tags = stories::tags_of(translation);
+            let item_level = story_level
+                || (wanted_tags.iter().all(|t| story_tags.contains(t) || tags.contains(t))
+                    && (in_collection(None) || in_collection(Some(language))));
+            if !item_level || filter.language.as_ref().is_some_and(|l| l != language) {
+                continue;
+            }
+            let doc = library_doc(language, translation, tags, collections, &story_id);
+            if filter.status.is_some_and(|s| s != doc.status)
+                || filter.level.is_some_and(|l| doc.level != Some(l))
+                || !filter.matches_models(&doc.models)
+            {
+                continue;
+            }
+            docs.push(doc);
+        }
+
+        let matched = if filter.filters_docs() {
+            !docs.is_empty()
+        } else {
+            story_level || !docs.is_empty()
+        };
+        if !matched {
+            continue;
+        }
+        entries.push(LibraryEntry {
+            collections: collections
+                .0
+                .iter()
+                .filter(|c| c.contains(&story_id, None))
+                .map(|c| c.id.clone())
+                .collect(),
+            story_id,
+            title,
+            category: story.get("category").and_then(Value::as_str).map(str::to_string),
+            source_language: field("sourceLanguage"),
+            created_at: story.get("createdAt").and_then(Value::as_u64).unwrap_or_default(),
+            updated_at: story.get("updatedAt").and_then(Value::as_u64).unwrap_or_default(),
+            tags: story_tags,
+            docs,
+        });
+    }
+    entries.sort_by_key(|e| std::cmp::Reverse(e.updated_at));
+    Ok(entries)
+}
+
+fn library_doc(
+    language: &str,
+    translation: &Value,
+    tags: Vec<String>,
+    collections: &Collections,
+    story_id: &str,
+) -> LibraryDoc {
+    let job = translation.get("job").filter(|j| !j.is_null());
+    let doc = translation
+        .get("doc")
+        .filter(|d| !d.is_null())
+        .and_then(|d| serde_json::from_value::<InteractiveDoc>(d.clone()).ok());
+    let failed = translation
+        .get("errorMessage")
+        .and_then(Value::as_str)
+        .is_some_and(|m| !m.is_empty());
+
+    // Read from the raw job so jobs saved by older versions still count.
+    let running = job.and_then(|j| j.get("ready")).and_then(Value::as_bool) == Some(false);
+    let segments: Vec<TranslationSegment> = job
+        .and_then(|j| j.get("segments"))
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|s| serde_json::from_value(s.clone()).ok())
+        .collect();
+    let skipped = segments
+        .iter()
+        .any(|s| s.base_stage == SegmentStage::Error || s.span_stage == SegmentStage::Error);
+    let status = match &doc {
+        Some(_) if running => CompletionStatus::Translating,
+        Some(_) if skipped => CompletionStatus::Partial,
+        Some(_) => CompletionStatus::Complete,
+        None if failed => CompletionStatus::Failed,
+        None => CompletionStatus::Translating,
+    };
+
+    let graded_level = job
+        .and_then(|j| j.pointer("/metadata/simplifyLevel"))
+        .and_then(|l| serde_json::from_value::<CefrLevel>(l.clone()).ok());
+    let meta = story_meta::meta_of(translation);
+    let level = graded_level.or_else(|| meta.as_ref()?.level).or_else(|| {
+        let text = doc.as_ref()?.block_texts().join("\n\n");
+        CefrLevel::parse(&analyze_text(&text, language).estimated_cefr)
+    });
+
+    LibraryDoc {
+        language: language.to_string(),
+        status,
+        level,
+        graded: graded_level.is_some(),
+        tags,
+        collections: collections
+            .0
+            .iter()
+            .filter(|c| c.contains(story_id, Some(language)))
+            .map(|c| c.id.clone())
+            .collect(),
+        meta,
+        models: doc_models(job, &segments, doc.as_ref()),
+    }
+}
+
+/// Every provider and model in the job's metadata and the provenance of
+/// its segments and the doc's spans, sorted.
+fn doc_models(job: Option<&Value>, segments: &[TranslationSegment], doc: Option<&InteractiveDoc>) -> Vec<DocModel> {
+    let job_model = job.and_then(|j| {
+        let provider = serde_json::from_value(j.pointer("/metadata/provider")?.clone()).ok()?;
+        let model = j.pointer("/metadata/model")?.as_str()?.to_string();
+        Some(DocModel { provider, model })
+    });
+    let calls = segments
+        .iter()
+        .flat_map(|s| &s.provenance)
+        .chain(doc.into_iter().flat_map(|d| d.spans.values()).flat_map(|s| &s.provenance))
+        .map(|c| DocModel {
+            provider: c.provider,
+            model: c.model.clone(),
+        });
+    let mut models: Vec<DocModel> = job_model.into_iter().chain(calls).collect();
+    models.sort_by(|a, b| (format!("{:?}", a.provider), &a.model).cmp(&(format!("{:?}", b.provider), &b.model)));
+    models.dedup();
+    models
+}
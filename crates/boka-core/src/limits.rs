@@ -0,0 +1,215 @@
+use super::analysis::estimate_tokens;
+use super::pricing::{describe_cost, CostView, DisplayCurrency};
+use super::translation::split_into_segments;
+use super::types::{ApiError, LlmProviderConfig, ModelPricing, ModelRegistry, Usage};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Hard cap on a single job's input.
+pub const MAX_INPUT_CHARS: usize = 200_000;
+
+/// Above this, the story is split into chapters and the job must be confirmed.
+pub const CHAPTER_CHARS: usize = 12_000;
+
+/// Rough system-prompt size per call, in tokens, used for cost estimates.
+const PROMPT_OVERHEAD_TOKENS: u32 = 300;
+
+pub fn check_input(text: &str) -> Result<(), ApiError> {
+    let chars = text.chars().count();
+    if chars > MAX_INPUT_CHARS {
+        return Err(ApiError::InputTooLarge {
+            chars,
+            limit: MAX_INPUT_CHARS,
+        });
+    }
+    Ok(())
+}
+
+/// Split `text` into chapters of at most `max_chars`, breaking at blank lines
+/// and, for oversized paragraphs, between sentences. Short texts come back whole.
+pub fn chunk_chapters(text: &str, max_chars: usize) -> Vec<String> {
+    let text = text.trim();
+    if text.chars().count() <= max_chars {
+        return vec![text.to_string()];
+    }
+
+    let mut pieces: Vec<String> = Vec::new();
+    for para in text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        if para.chars().count() <= max_chars {
+            pieces.push(para.to_string());
+        } else {
+            pieces.extend(split_into_segments(para));
+        }
+    }
+
+    let mut chapters: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for piece in pieces {
+        if !current.is_empty() && current.chars().count() + piece.chars().count() + 2 > max_chars {
+            chapters.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push_str("\n\n");
+        }
+        current.push_str(&piece);
+    }
+    if !current.is_empty() {
+        chapters.push(current);
+    }
+    chapters
+}
+
+/// Estimated (input, output) tokens for translating `text`. Base translation
+/// sends the segment's chapter as context; planning and variants see only the
+/// segment.
+pub fn estimate_job_tokens(text: &str) -> (u32, u32) {
+    let mut input = 0;
+    let mut output = 0;
+    for chapter in chunk_chapters(text, CHAPTER_CHARS) {
+        let chapter_tokens = estimate_tokens(&chapter);
+        for seg in split_into_segments(&chapter) {
+            let seg_tokens = estimate_tokens(&seg);
+            input += chapter_tokens + 3 * (PROMPT_OVERHEAD_TOKENS + seg_tokens);
+            output += seg_tokens * 8;
+        }
+    }
+    (input, output)
+}
+
+/// What a job will cost, shown to the user before a large job starts.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobPreflight {
+    pub char_count: usize,
+    pub segment_count: usize,
+    pub chapter_count: usize,
+    pub estimated_input_tokens: u32,
+    pub estimated_output_tokens: u32,
+    pub model: String,
+    /// `None` when the model has no pricing in the registry.
+    pub estimated_cost_usd: Option<f64>,
+    /// The estimate in the display currency, or its tokens without pricing.
+    pub estimated_cost: CostView,
+    pub requires_confirmation: bool,
+    /// Pass back as the job's confirmation to acknowledge this estimate.
+    pub confirmation_token: String,
+}
+
+pub fn preflight(
+    text: &str,
+    provider: &LlmProviderConfig,
+    currency: &DisplayCurrency,
+) -> Result<JobPreflight, ApiError> {
+    check_input(text)?;
+
+    let registry = ModelRegistry::current();
+    let model = provider
+        .model
+        .as_deref()
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .or_else(|| registry.default_model(provider.preset))
+        .unwrap_or_default()
+        .to_string();
+
+    let chapters = chunk_chapters(text, CHAPTER_CHARS);
+    let segment_count = chapters.iter().map(|c| split_into_segments(c).len()).sum();
+    let (input, output) = estimate_job_tokens(text);
+    let usage = Usage {
+        input_tokens: input,
+        output_tokens: output,
+        ..Default::default()
+    };
+    let pricing = registry.pricing(provider.preset, &model);
+
+    Ok(JobPreflight {
+        char_count: text.chars().count(),
+        segment_count,
+        chapter_count: chapters.len(),
+        estimated_input_tokens: input,
+        estimated_output_tokens: output,
+        model: model.clone(),
+        estimated_cost_usd: pricing.map(|p| p.cost_usd(&usage)),
+        estimated_cost: describe_cost(&usage, pricing.as_ref(), currency),
+        requires_confirmation: chapters.len() > 1,
+        confirmation_token: confirmation_token(text, &model),
+    })
+}
+
+/// Share of a budget at which a job warns that it is running out.
+pub const BUDGET_WARNING: f64 = 0.8;
+
+/// Soft spending cap for one job. A job that reaches it pauses until the
+/// user lets it go on. The cost limit only applies to models with pricing
+/// in the registry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobBudget {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cost_usd: Option<f64>,
+}
+
+/// A running job's spend against its budget.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetStatus {
+    pub job_id: String,
+    pub budget: JobBudget,
+    pub tokens: u64,
+    /// `None` when the model has no pricing in the registry.
+    pub cost_usd: Option<f64>,
+    /// Largest share of either limit spent so far.
+    pub fraction: f64,
+    /// The cap is reached and the job waits for confirmation.
+    pub reached: bool,
+}
+
+impl JobBudget {
+    pub fn status(&self, job_id: &str, usage: &Usage, pricing: Option<&ModelPricing>) -> BudgetStatus {
+        let tokens = u64::from(usage.input_tokens) + u64::from(usage.output_tokens);
+        let cost_usd = pricing.map(|p| p.cost_usd(usage));
+        let token_share = self.max_tokens.map(|max| tokens as f64 / max.max(1) as f64);
+        let cost_share = self
+            .max_cost_usd
+            .zip(cost_usd)
+            .map(|(max, cost)| if max > 0.0 { cost / max } else { f64::INFINITY });
+        let fraction = token_share.into_iter().chain(cost_share).fold(0.0, f64::max);
+        BudgetStatus {
+            job_id: job_id.to_string(),
+            budget: *self,
+            tokens,
+            cost_usd,
+            fraction,
+            reached: fraction >= 1.0,
+        }
+    }
+}
+
+/// Ties a confirmation to the exact text and model it was estimated for.
+pub fn confirmation_token(text: &str, model: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(model.as_bytes());
+    hasher.update([0]);
+    hasher.update(text.trim().as_bytes());
+    format!("{:x}", hasher.finalize())[..16].to_string()
+}
+
+/// Identifies a job by what it would produce: the story, the job options and
+/// the provider and model (not the API key). Two starts with the same
+/// fingerprint would spend the same tokens on the same result.
+pub fn job_fingerprint(text: &str, options: &impl Serialize, provider: &LlmProviderConfig) -> String {
+    let provider = LlmProviderConfig {
+        api_key: None,
+        ..provider.clone()
+    };
+    let mut hasher = Sha256::new();
+    hasher.update(serde_json::to_vec(&provider).unwrap_or_default());
+    hasher.update([0]);
+    hasher.update(serde_json::to_vec(options).unwrap_or_default());
+    hasher.update([0]);
+    hasher.update(text.trim().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
@@ -0,0 +1,194 @@
+//! Checks over a finished doc for what the model got subtly wrong: spans
+//! with nothing to read or nothing to choose from, variants that repeat
+//! each other, rambling notes, spans without a neutral register, and
+//! quotes left open across tokens. Lints are warnings; the doc is usable
+//! either way, and each one says what to do about it.
+
+use super::gui_types::{DocToken, InteractiveDoc, Span};
+
+use serde::{Deserialize, Serialize};
+
+/// Longest learner note, in characters, before it is flagged.
+pub const MAX_NOTE_CHARS: usize = 240;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum DocLint {
+    /// The span's active variant has no text, or it has no variants.
+    #[serde(rename_all = "camelCase")]
+    EmptySpan { block: u32, span_id: String },
+    /// Nothing to switch to.
+    #[serde(rename_all = "camelCase")]
+    SingleVariant { block: u32, span_id: String },
+    /// Variants with the same text, by index; the first is kept.
+    #[serde(rename_all = "camelCase")]
+    DuplicateVariants {
+        block: u32,
+        span_id: String,
+        text: String,
+        variants: Vec<u32>,
+    },
+    #[serde(rename_all = "camelCase")]
+    LongNote {
+        block: u32,
+        span_id: String,
+        variant: u32,
+        chars: usize,
+        limit: usize,
+    },
+    /// No variant has the `neutral` register the other registers fall back to.
+    #[serde(rename_all = "camelCase")]
+    MissingNeutral { block: u32, span_id: String },
+    /// `quote` is the pair, e.g. `«»`, or the mark when it pairs with itself.
+    UnbalancedQuotes { block: u32, quote: String },
+}
+
+impl DocLint {
+    pub fn block(&self) -> u32 {
+        match self {
+            DocLint::EmptySpan { block, .. }
+            | DocLint::SingleVariant { block, .. }
+            | DocLint::DuplicateVariants { block, .. }
+            | DocLint::LongNote { block, .. }
+            | DocLint::MissingNeutral { block, .. }
+            | DocLint::UnbalancedQuotes { block, .. } => *block,
+        }
+    }
+
+    /// What is wrong and what to do about it, for the run report.
+    pub fn message(&self) -> String {
+        match self {
+            DocLint::EmptySpan { block, span_id } => {
+                format!("{} in block {} is empty; regenerate the block", span_id, block)
+            }
+            DocLint::SingleVariant { block, span_id } => format!(
+                "{} in block {} has a single variant; regenerate the block or raise the minimum variants",
+                span_id, block
+            ),
+            DocLint::DuplicateVariants {
+                block, span_id, text, ..
+            } => format!(
+                "{} in block {} repeats \"{}\"; turn on variant refinement or regenerate the block",
+                span_id, block, text
+            ),
+            DocLint::LongNote {
+                block,
+                span_id,
+                variant,
+                chars,
+                limit,
+            } => format!(
+                "the note of variant {} of {} in block {} has {} characters (limit {}); shorten it",
+                variant, span_id, block, chars, limit
+            ),
+            DocLint::MissingNeutral { block, span_id } => format!(
+                "{} in block {} has no neutral variant; switching register may skip it",
+                span_id, block
+            ),
+            DocLint::UnbalancedQuotes { block, quote } => {
+                format!("block {} has unbalanced {} quotes; check the spans around them", block, quote)
+            }
+        }
+    }
+}
+
+/// Lints of `doc`, by block, spans in reading order.
+pub fn lint_doc(doc: &InteractiveDoc) -> Vec<DocLint> {
+    let mut lints = Vec::new();
+    let mut block = 0u32;
+    let mut text = String::new();
+    for token in &doc.tokens {
+        match token {
+            DocToken::Text { value, .. } if value == "\n\n" => {
+                lint_quotes(block, &text, &mut lints);
+                text.clear();
+                block += 1;
+            }
+            DocToken::Text { value, .. } => text.push_str(value),
+            DocToken::Span { span_id, .. } => {
+                let Some(span) = doc.spans.get(span_id) else {
+                    continue;
+                };
+                text.push_str(span.active_text().unwrap_or_default());
+                lint_span(block, span, &mut lints);
+            }
+            DocToken::Check { .. } => {}
+        }
+    }
+    lint_quotes(block, &text, &mut lints);
+    lints
+}
+
+fn lint_span(block: u32, span: &Span, lints: &mut Vec<DocLint>) {
+    let span_id = span.id.clone();
+    let active = span.variants.get(span.active_variant_index);
+    if active.map_or(true, |v| v.text.trim().is_empty()) {
+        lints.push(DocLint::EmptySpan { block, span_id });
+        return;
+    }
+    if span.variants.len() == 1 {
+        lints.push(DocLint::SingleVariant {
+            block,
+            span_id: span_id.clone(),
+        });
+    }
+
+    let mut seen: Vec<&str> = Vec::new();
+    for (i, variant) in span.variants.iter().enumerate() {
+        let text = variant.text.trim();
+        if seen.contains(&text) {
+            continue;
+        }
+        seen.push(text);
+        let same: Vec<u32> = (i..span.variants.len())
+            .filter(|&j| span.variants[j].text.trim() == text)
+            .map(|j| j as u32)
+            .collect();
+        if same.len() > 1 {
+            lints.push(DocLint::DuplicateVariants {
+                block,
+                span_id: span_id.clone(),
+                text: text.to_string(),
+                variants: same,
+            });
+        }
+    }
+
+    for (i, variant) in span.variants.iter().enumerate() {
+        let chars = variant.note.as_deref().map_or(0, |n| n.trim().chars().count());
+        if chars > MAX_NOTE_CHARS {
+            lints.push(DocLint::LongNote {
+                block,
+                span_id: span_id.clone(),
+                variant: i as u32,
+                chars,
+                limit: MAX_NOTE_CHARS,
+            });
+        }
+    }
+
+    if !span.variants.iter().any(|v| v.register == "neutral") {
+        lints.push(DocLint::MissingNeutral { block, span_id });
+    }
+}
+
+fn lint_quotes(block: u32, text: &str, lints: &mut Vec<DocLint>) {
+    let count = |mark: char| text.chars().filter(|&c| c == mark).count();
+    // English “…” and German „…“ share a mark: each “ opens a quote closed
+    // by ” or closes one opened by „.
+    let pairs = [
+        ("\"", count('"') % 2 == 0),
+        ("«»", count('«') == count('»')),
+        ("“”", count('“') == count('„') + count('”')),
+        ("「」", count('「') == count('」')),
+        ("『』", count('『') == count('』')),
+    ];
+    for (quote, balanced) in pairs {
+        if !balanced {
+            lints.push(DocLint::UnbalancedQuotes {
+                block,
+                quote: quote.to_string(),
+            });
+        }
+    }
+}
@@ -0,0 +1,276 @@
+//! Emoji and simple Markdown in source text. Before a segment goes to the
+//! model, emoji, inline code and link targets are swapped for numbered
+//! placeholders (`⟦1⟧`) and `*`/`_` emphasis for `<em>`/`<strong>` tags,
+//! which models carry over more reliably than asterisks. The reply gets the
+//! originals back, with emphasis written as `*` and `**`.
+//!
+//! Base translations keep that Markdown. When the doc is built the markers
+//! are taken out of the text and kept as a [`TextStyle`] on its tokens.
+
+use super::gui_types::TextStyle;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+const OPEN: char = '⟦';
+const CLOSE: char = '⟧';
+
+/// A segment ready for the model and what its placeholders stand for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Protected {
+    pub text: String,
+    items: Vec<String>,
+}
+
+impl Protected {
+    /// `reply` with the originals back in place of their placeholders.
+    /// Items the model dropped go at the end; placeholders it made up are
+    /// removed.
+    pub fn restore(&self, reply: &str) -> String {
+        let mut used = vec![false; self.items.len()];
+        let mut out = String::with_capacity(reply.len());
+        let mut rest = reply;
+        while let Some(start) = rest.find(OPEN) {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + OPEN.len_utf8()..];
+            let Some(end) = after.find(CLOSE) else {
+                rest = after;
+                continue;
+            };
+            let item = after[..end].trim().parse::<usize>().ok().and_then(|n| n.checked_sub(1));
+            if let Some(i) = item.filter(|i| *i < self.items.len()) {
+                out.push_str(&self.items[i]);
+                used[i] = true;
+            }
+            rest = &after[end + CLOSE.len_utf8()..];
+        }
+        out.push_str(rest);
+
+        let mut out = untag(&out);
+        for (item, _) in self.items.iter().zip(used).filter(|(_, used)| !used) {
+            out.push(' ');
+            out.push_str(item);
+        }
+        out
+    }
+}
+
+/// Take emoji, inline code, link targets and emphasis out of `text`.
+pub fn protect(text: &str) -> Protected {
+    let emphasis = paired_markers(text);
+    let mut items: Vec<String> = Vec::new();
+    let mut out = String::with_capacity(text.len());
+    let mut placeholder = |out: &mut String, item: &str| {
+        items.push(item.to_string());
+        out.push(OPEN);
+        out.push_str(&items.len().to_string());
+        out.push(CLOSE);
+    };
+
+    let mut i = 0;
+    while i < text.len() {
+        let rest = &text[i..];
+        if let Some(code) = code_span(rest) {
+            placeholder(&mut out, code);
+            i += code.len();
+        } else if let Some(target) = link_target(rest) {
+            out.push_str("](");
+            placeholder(&mut out, target);
+            out.push(')');
+            i += target.len() + 3;
+        } else if let Some(marker) = emphasis.iter().find(|m| m.at == i) {
+            out.push_str(match (marker.strong, marker.opens) {
+                (false, true) => "<em>",
+                (false, false) => "</em>",
+                (true, true) => "<strong>",
+                (true, false) => "</strong>",
+            });
+            i += marker.len();
+        } else {
+            let run: usize = rest.graphemes(true).take_while(|g| is_emoji(g)).map(str::len).sum();
+            if run > 0 {
+                placeholder(&mut out, &rest[..run]);
+                i += run;
+            } else {
+                let c = rest.chars().next().unwrap_or_default();
+                out.push(c);
+                i += c.len_utf8();
+            }
+        }
+    }
+    Protected { text: out, items }
+}
+
+/// Emphasis tags in a model reply written back as Markdown. Spaces just
+/// inside a tag move outside it, where Markdown needs them.
+fn untag(text: &str) -> String {
+    let mut text = text.to_string();
+    for (tag, markdown) in [("strong", "**"), ("em", "*")] {
+        let (open, close) = (format!("<{}>", tag), format!("</{}>", tag));
+        text = text
+            .replace(&format!(" {} ", open), &format!(" {}", open))
+            .replace(&format!("{} ", open), &format!(" {}", open))
+            .replace(&format!(" {} ", close), &format!("{} ", close))
+            .replace(&format!(" {}", close), &format!("{} ", close))
+            .replace(&open, markdown)
+            .replace(&close, markdown);
+    }
+    text
+}
+
+/// The `url` of "](url)" at the start of `text`.
+fn link_target(text: &str) -> Option<&str> {
+    let rest = text.strip_prefix("](")?;
+    rest.find(')').filter(|end| *end > 0).map(|end| &rest[..end])
+}
+
+/// "`code`" at the start of `text`.
+fn code_span(text: &str) -> Option<&str> {
+    let inner = text.strip_prefix('`')?;
+    let end = inner.find('`').filter(|end| *end > 0)?;
+    Some(&text[..end + 2])
+}
+
+/// Emoji, including modifier, keycap and ZWJ sequences. Digits, `#` and
+/// `©` only count when followed by a keycap or emoji presentation mark.
+fn is_emoji(grapheme: &str) -> bool {
+    let mut chars = grapheme.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    let pictographic = matches!(first as u32,
+        0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF | 0x2300..=0x23FF);
+    pictographic || (!first.is_alphabetic() && chars.any(|c| c == '\u{20E3}' || c == '\u{FE0F}'))
+}
+
+/// A run of one or two `*` or `_` that can open or close emphasis.
+#[derive(Debug, Clone, Copy)]
+struct Marker {
+    at: usize,
+    strong: bool,
+    opens: bool,
+    underscore: bool,
+}
+
+impl Marker {
+    fn len(&self) -> usize {
+        if self.strong {
+            2
+        } else {
+            1
+        }
+    }
+}
+
+/// The markers in `text`, each with whether it could open and close
+/// emphasis. A run of three or more is left as text, as is anything inside
+/// inline code.
+fn markers(text: &str) -> Vec<(Marker, bool, bool)> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut found = Vec::new();
+    let mut in_code = false;
+    let mut k = 0;
+    while k < chars.len() {
+        let (at, c) = chars[k];
+        if c == '`' {
+            in_code = !in_code;
+        }
+        if in_code || (c != '*' && c != '_') {
+            k += 1;
+            continue;
+        }
+        let run = chars[k..].iter().take_while(|(_, d)| *d == c).count();
+        let prev = k.checked_sub(1).map(|p| chars[p].1);
+        let next = chars.get(k + run).map(|(_, d)| *d);
+        if run <= 2 {
+            let can_open = next.is_some_and(|n| !n.is_whitespace()) && !prev.is_some_and(char::is_alphanumeric);
+            let can_close = prev.is_some_and(|p| !p.is_whitespace()) && !next.is_some_and(char::is_alphanumeric);
+            let marker = Marker {
+                at,
+                strong: run == 2,
+                opens: false,
+                underscore: c == '_',
+            };
+            found.push((marker, can_open, can_close));
+        }
+        k += run;
+    }
+    found
+}
+
+/// Markers of `text` that pair up, with which of each pair opens.
+fn paired_markers(text: &str) -> Vec<Marker> {
+    let mut paired = Vec::new();
+    let mut open: Vec<Marker> = Vec::new();
+    for (marker, can_open, can_close) in markers(text) {
+        let same = |m: &Marker| m.strong == marker.strong && m.underscore == marker.underscore;
+        match open.iter().rposition(same) {
+            Some(o) if can_close => {
+                let opener = open.remove(o);
+                open.truncate(o);
+                paired.push(Marker { opens: true, ..opener });
+                paired.push(marker);
+            }
+            _ if can_open => open.push(marker),
+            _ => {}
+        }
+    }
+    paired.sort_by_key(|m| m.at);
+    paired
+}
+
+/// Emphasis open at a point of a block. Blocks are read piece by piece,
+/// static text and spans, and emphasis can run from one piece into the next.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Emphasis {
+    em: bool,
+    strong: bool,
+}
+
+impl Emphasis {
+    fn style(self) -> Option<TextStyle> {
+        match (self.em, self.strong) {
+            (_, true) => Some(TextStyle::Strong),
+            (true, false) => Some(TextStyle::Emphasis),
+            (false, false) => None,
+        }
+    }
+
+    /// `text` without its emphasis markers, cut into runs of one style.
+    pub(crate) fn runs(&mut self, text: &str) -> Vec<(String, Option<TextStyle>)> {
+        let mut runs: Vec<(String, Option<TextStyle>)> = Vec::new();
+        let mut from = 0;
+        for (marker, can_open, can_close) in markers(text) {
+            let on = if marker.strong { self.strong } else { self.em };
+            if !((on && can_close) || (!on && can_open)) {
+                continue;
+            }
+            push_run(&mut runs, &text[from..marker.at], self.style());
+            if marker.strong {
+                self.strong = !on;
+            } else {
+                self.em = !on;
+            }
+            from = marker.at + marker.len();
+        }
+        push_run(&mut runs, &text[from..], self.style());
+        runs
+    }
+
+    /// `text` without its emphasis markers and the style most of it is in.
+    pub(crate) fn strip(&mut self, text: &str) -> (String, Option<TextStyle>) {
+        let runs = self.runs(text);
+        let weight = |run: &String| run.chars().filter(|c| !c.is_whitespace()).count();
+        let style = runs.iter().max_by_key(|(run, _)| weight(run)).and_then(|(_, style)| *style);
+        (runs.into_iter().map(|(run, _)| run).collect(), style)
+    }
+}
+
+fn push_run(runs: &mut Vec<(String, Option<TextStyle>)>, text: &str, style: Option<TextStyle>) {
+    if text.is_empty() {
+        return;
+    }
+    match runs.last_mut() {
+        Some((last, last_style)) if *last_style == style => last.push_str(text),
+        _ => runs.push((text.to_string(), style)),
+    }
+}
@@ -0,0 +1,401 @@
+use super::analysis::estimate_tokens;
+use super::anthropic::{PlannedBlock, PlannedVariant};
+use super::demo::{DemoBook, DEMO_BASE_URL};
+use super::import::PageImage;
+use super::judge::{self, JudgeVerdict};
+use super::length_guard::LengthGuard;
+use super::openai_compat::{parse_planned_blocks, parse_variants};
+use super::policy::ContentPolicy;
+use super::reasoning;
+use super::refusal;
+use super::simple_format::{parse_simple_plan, parse_simple_variants, StructuredFormat};
+use super::translation::split_into_segments;
+use super::types::{ApiConfig, ApiError, Usage};
+
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// A single canned provider reply: raw model text, an API error, or a JSON
+/// value that is replayed as its serialized text.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum MockReply {
+    Text(String),
+    Error { status: u16, message: String },
+    Json(serde_json::Value),
+}
+
+/// Canned replies per call kind, consumed in order.
+///
+/// Loaded from a JSON fixture whose path is given as the provider `baseUrl`
+/// ([`DEMO_BASE_URL`] replays the bundled demo instead):
+///
+/// ```json
+/// { "base": ["..."], "plan": [[{ "id": "b1", "segments": [] }]], "variants": [{ "status": 500, "message": "boom" }] }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MockScript {
+    #[serde(default)]
+    pub base: VecDeque<MockReply>,
+    #[serde(default)]
+    pub plan: VecDeque<MockReply>,
+    #[serde(default)]
+    pub variants: VecDeque<MockReply>,
+    #[serde(default)]
+    pub judge: VecDeque<MockReply>,
+    #[serde(default)]
+    pub simplified: VecDeque<MockReply>,
+    #[serde(default)]
+    pub transcribe: VecDeque<MockReply>,
+    #[serde(default)]
+    pub refine: VecDeque<MockReply>,
+    #[serde(default)]
+    pub describe: VecDeque<MockReply>,
+    #[serde(default)]
+    pub check: VecDeque<MockReply>,
+    #[serde(default)]
+    pub notes: VecDeque<MockReply>,
+    #[serde(default)]
+    pub continuation: VecDeque<MockReply>,
+    #[serde(default)]
+    pub tutor: VecDeque<MockReply>,
+    #[serde(default)]
+    pub speakers: VecDeque<MockReply>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum MockCall {
+    Base,
+    Plan,
+    Variants,
+    Judge,
+    Simplified,
+    Transcribe,
+    Refine,
+    Describe,
+    Check,
+    Notes,
+    Continue,
+    Tutor,
+    Speakers,
+}
+
+/// Offline provider that replays a [`MockScript`] or the demo, or echoes the
+/// input deterministically when neither has a reply.
+pub struct MockClient {
+    script: Option<Mutex<MockScript>>,
+    demo: Option<DemoBook>,
+    reasoning_model: bool,
+    length_guard: LengthGuard,
+}
+
+impl MockClient {
+    pub fn new(config: ApiConfig) -> Result<Self, ApiError> {
+        let base_url = config.provider.base_url.as_deref().map(str::trim);
+        let demo = (base_url == Some(DEMO_BASE_URL)).then(|| DemoBook::new(&config.target_language));
+        let script = match base_url {
+            Some(path) if !path.is_empty() && demo.is_none() => {
+                let raw = std::fs::read_to_string(path)
+                    .map_err(|e| ApiError::Parse(format!("Mock fixture {}: {}", path, e)))?;
+                let script: MockScript = serde_json::from_str(&raw)
+                    .map_err(|e| ApiError::Parse(format!("Mock fixture {}: {}", path, e)))?;
+                Some(Mutex::new(script))
+            }
+            _ => None,
+        };
+
+        Ok(Self {
+            script,
+            demo,
+            reasoning_model: config.provider.reasoning_model,
+            length_guard: config.length_guard,
+        })
+    }
+
+    pub fn model(&self) -> &str {
+        "mock"
+    }
+
+    pub fn length_guard(&self) -> LengthGuard {
+        self.length_guard
+    }
+
+    /// The next scripted reply for `call`; `json` says whether the real client
+    /// would have asked for JSON, which matters for reasoning-model clean-up.
+    fn next(&self, call: MockCall, json: bool) -> Option<Result<String, ApiError>> {
+        let script = self.script.as_ref()?;
+        let mut guard = script.lock().unwrap_or_else(|e| e.into_inner());
+        let queue = match call {
+            MockCall::Base => &mut guard.base,
+            MockCall::Plan => &mut guard.plan,
+            MockCall::Variants => &mut guard.variants,
+            MockCall::Judge => &mut guard.judge,
+            MockCall::Simplified => &mut guard.simplified,
+            MockCall::Transcribe => &mut guard.transcribe,
+            MockCall::Refine => &mut guard.refine,
+            MockCall::Describe => &mut guard.describe,
+            MockCall::Check => &mut guard.check,
+            MockCall::Notes => &mut guard.notes,
+            MockCall::Continue => &mut guard.continuation,
+            MockCall::Tutor => &mut guard.tutor,
+            MockCall::Speakers => &mut guard.speakers,
+        };
+
+        let reply = match queue.pop_front() {
+            Some(r) => r,
+            None => {
+                return Some(Err(ApiError::Parse(format!(
+                    "Mock script has no {:?} reply left",
+                    call
+                ))))
+            }
+        };
+
+        Some(match reply {
+            MockReply::Text(t) => {
+                let t = if self.reasoning_model { reasoning::clean_output(&t, json) } else { t };
+                refusal::check("mock", self.model(), &t, false).map(|_| t)
+            }
+            MockReply::Json(v) => Ok(v.to_string()),
+            MockReply::Error { status, message } => Err(ApiError::ApiResponse { status, message }),
+        })
+    }
+
+    pub async fn translate_base_segment(
+        &self,
+        _full_story: &str,
+        segment: &str,
+        _note: Option<&str>,
+    ) -> Result<(String, Usage), ApiError> {
+        let text = match self.next(MockCall::Base, false) {
+            Some(r) => r?.trim().to_string(),
+            None => self.demo_translation(segment),
+        };
+        Ok((text.clone(), mock_usage(segment, &text)))
+    }
+
+    pub async fn translate_simplified_segment(
+        &self,
+        _full_story: &str,
+        segment: &str,
+        _note: Option<&str>,
+    ) -> Result<(String, Usage), ApiError> {
+        let text = match self.next(MockCall::Simplified, false) {
+            Some(r) => r?.trim().to_string(),
+            None => self.demo_translation(segment),
+        };
+        Ok((text.clone(), mock_usage(segment, &text)))
+    }
+
+    /// The demo's translation of `segment`, or the segment itself.
+    fn demo_translation(&self, segment: &str) -> String {
+        self.demo
+            .as_ref()
+            .and_then(|d| d.translation(segment))
+            .unwrap_or_else(|| segment.trim().to_string())
+    }
+
+    pub async fn transcribe_image(&self, image: &PageImage) -> Result<(String, Usage), ApiError> {
+        let text = match self.next(MockCall::Transcribe, false) {
+            Some(r) => r?,
+            None => String::new(),
+        };
+        Ok((text.clone(), mock_usage(&image.data, &text)))
+    }
+
+    pub async fn plan_block_from_base(
+        &self,
+        base_text: &str,
+        format: StructuredFormat,
+    ) -> Result<(PlannedBlock, Usage), ApiError> {
+        let scripted = self.next(MockCall::Plan, format == StructuredFormat::Json);
+        let demo = self.demo.as_ref().and_then(|d| d.plan(base_text));
+        let text = match (scripted, demo) {
+            (Some(r), _) => r?,
+            (None, _) if format == StructuredFormat::Simple => base_text.to_string(),
+            (None, Some(plan)) => plan,
+            (None, None) => serde_json::json!([{
+                "id": "b1",
+                "segments": [{
+                    "type": "swappable",
+                    "id": "s1",
+                    "variants": [{ "text": base_text, "register": "neutral", "note": "", "difficulty": 1 }],
+                }],
+            }])
+            .to_string(),
+        };
+
+        if format == StructuredFormat::Simple {
+            return Ok((parse_simple_plan(&text, base_text)?, mock_usage(base_text, &text)));
+        }
+        let mut blocks = parse_planned_blocks(&text)?;
+        let block = blocks
+            .drain(..)
+            .next()
+            .ok_or_else(|| ApiError::Parse("No block returned".to_string()))?;
+
+        Ok((block, mock_usage(base_text, &text)))
+    }
+
+    pub async fn generate_span_variants(
+        &self,
+        _segment_context: &str,
+        anchor_phrase: &str,
+        _variant_count: u32,
+        format: StructuredFormat,
+    ) -> Result<(Vec<PlannedVariant>, Usage), ApiError> {
+        let scripted = self.next(MockCall::Variants, format == StructuredFormat::Json);
+        let demo = self.demo.as_ref().and_then(|d| d.variants(anchor_phrase));
+        let variants = match (scripted, demo) {
+            (Some(r), _) if format == StructuredFormat::Simple => parse_simple_variants(&r?)?,
+            (Some(r), _) => parse_variants(&r?)?,
+            (None, Some(demo)) => parse_variants(&demo)?,
+            (None, None) => vec![PlannedVariant {
+                text: anchor_phrase.to_string(),
+                register: "neutral".to_string(),
+                note: String::new(),
+                difficulty: 1,
+            }],
+        };
+        let output: String = variants.iter().map(|v| v.text.as_str()).collect();
+        Ok((variants, mock_usage(anchor_phrase, &output)))
+    }
+
+    pub async fn refine_span_variants(
+        &self,
+        _segment_context: &str,
+        anchor_phrase: &str,
+        variants: &[PlannedVariant],
+    ) -> Result<(Vec<PlannedVariant>, Usage), ApiError> {
+        let refined = match self.next(MockCall::Refine, true) {
+            Some(r) => parse_variants(&r?)?,
+            None => variants.to_vec(),
+        };
+        let output: String = refined.iter().map(|v| v.text.as_str()).collect();
+        Ok((refined, mock_usage(anchor_phrase, &output)))
+    }
+
+    /// Unscripted, the title is the translation's first words and the
+    /// synopses are the first sentences.
+    pub async fn describe_story(&self, source: &str, translation: &str) -> Result<(String, Usage), ApiError> {
+        let text = match self.next(MockCall::Describe, true) {
+            Some(r) => r?,
+            None => {
+                let first_sentence = |text: &str| split_into_segments(text).into_iter().next().unwrap_or_default();
+                let title: Vec<&str> = translation.split_whitespace().take(4).collect();
+                serde_json::json!({
+                    "title": title.join(" "),
+                    "synopsisSource": first_sentence(source),
+                    "synopsisTarget": first_sentence(translation),
+                    "emoji": "📖",
+                })
+                .to_string()
+            }
+        };
+        Ok((text.clone(), mock_usage(source, &text)))
+    }
+
+    /// Unscripted, states the passage's first sentence as true.
+    pub async fn comprehension_check(&self, passage: &str) -> Result<(String, Usage), ApiError> {
+        let text = match self.next(MockCall::Check, true) {
+            Some(r) => r?,
+            None => serde_json::json!({
+                "question": split_into_segments(passage).into_iter().next().unwrap_or_default(),
+                "answer": true,
+            })
+            .to_string(),
+        };
+        Ok((text.clone(), mock_usage(passage, &text)))
+    }
+
+    /// Unscripted, the notes come back as they are.
+    pub async fn translate_notes(&self, notes: &[String], _ui_language: &str) -> Result<(String, Usage), ApiError> {
+        let text = match self.next(MockCall::Notes, true) {
+            Some(r) => r?,
+            None => serde_json::json!({ "notes": notes }).to_string(),
+        };
+        Ok((text.clone(), mock_usage(&notes.concat(), &text)))
+    }
+
+    /// Unscripted, the story goes on with the direction, or its last
+    /// sentence again.
+    pub async fn continue_story(
+        &self,
+        story: &str,
+        direction_hint: Option<&str>,
+        _words: u32,
+    ) -> Result<(String, Usage), ApiError> {
+        let text = match self.next(MockCall::Continue, false) {
+            Some(r) => r?,
+            None => match direction_hint.map(str::trim).filter(|h| !h.is_empty()) {
+                Some(hint) => hint.to_string(),
+                None => split_into_segments(story).pop().unwrap_or_default(),
+            },
+        };
+        Ok((text.clone(), mock_usage(story, &text)))
+    }
+
+    /// Unscripted, two speakers take turns, in line order.
+    pub async fn attribute_speakers(
+        &self,
+        _blocks: &[(u32, String)],
+        lines: &[(String, String)],
+        max_speakers: usize,
+    ) -> Result<(String, Usage), ApiError> {
+        let text = match self.next(MockCall::Speakers, true) {
+            Some(r) => r?,
+            None => {
+                let turns = max_speakers.clamp(1, 2);
+                let lines: Vec<serde_json::Value> = lines
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (id, _))| {
+                        serde_json::json!({ "line": id, "speaker": format!("Speaker {}", i % turns + 1) })
+                    })
+                    .collect();
+                serde_json::json!({ "lines": lines }).to_string()
+            }
+        };
+        let input: String = lines.iter().map(|(_, l)| l.as_str()).collect();
+        Ok((text.clone(), mock_usage(&input, &text)))
+    }
+
+    pub async fn answer_question(
+        &self,
+        _source: &str,
+        translation: &str,
+        _earlier: &[(&str, &str)],
+        question: &str,
+        _ui_language: Option<&str>,
+        _policy: Option<&ContentPolicy>,
+    ) -> Result<(String, Usage), ApiError> {
+        let text = match self.next(MockCall::Tutor, false) {
+            Some(r) => r?,
+            None => format!("Mock answer: {}", question),
+        };
+        Ok((text.clone(), mock_usage(translation, &text)))
+    }
+
+    pub async fn score_translation(&self, source: &str, translation: &str) -> Result<(JudgeVerdict, Usage), ApiError> {
+        let text = match self.next(MockCall::Judge, true) {
+            Some(r) => r?,
+            None => r#"{ "adequacy": 5, "fluency": 5, "rationale": "mock" }"#.to_string(),
+        };
+        Ok((judge::parse_verdict(&text)?, mock_usage(source, translation)))
+    }
+
+    pub async fn test_connection(&self) -> Result<(), ApiError> {
+        Ok(())
+    }
+}
+
+/// Token counts estimated from the text, so usage and cost reporting have
+/// something deterministic to aggregate.
+fn mock_usage(input: &str, output: &str) -> Usage {
+    Usage {
+        input_tokens: estimate_tokens(input),
+        output_tokens: estimate_tokens(output),
+        max_token_stops: 0,
+    }
+}
@@ -0,0 +1,31 @@
+/// Offline keyword screen for child-safe output.
+///
+/// This is a coarse backstop behind the prompt instructions, not a classifier:
+/// it matches whole words (and a few stems) from a short multilingual list of
+/// profanity, sexual and drug vocabulary that should never reach a child.
+const BLOCKED_WORDS: &[&str] = &[
+    // en
+    "fuck", "shit", "bitch", "bastard", "cunt", "pussy", "whore", "slut", "porn", "cocaine", "heroin",
+    // fr
+    "putain", "merde", "connard", "connasse", "salope", "pute", "couille", "enculé", "nique", "baise",
+    // es
+    "joder", "mierda", "puta", "coño", "cabrón", "polla", "gilipollas",
+    // de
+    "scheiße", "scheisse", "fotze", "hure", "arschloch", "wichser", "ficken",
+    // it
+    "cazzo", "merda", "puttana", "stronzo", "vaffanculo", "troia",
+    // pt
+    "porra", "caralho", "foder", "buceta",
+];
+
+const BLOCKED_STEMS: &[&str] = &["fuck", "shit", "encul", "baise", "niqu", "scheiß", "cazz"];
+
+/// Returns the offending word if `text` fails the screen.
+pub fn screen(text: &str) -> Option<String> {
+    let lower = text.to_lowercase();
+    lower
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .find(|w| BLOCKED_WORDS.contains(w) || BLOCKED_STEMS.iter().any(|s| w.starts_with(s)))
+        .map(|w| w.to_string())
+}
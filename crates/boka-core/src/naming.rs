@@ -0,0 +1,205 @@
+//! Human titles and file-safe slugs for saved stories. A story saved
+//! without a title gets the one the model suggested for any of its
+//! translations (see [`story_meta`](super::story_meta)), else the start of
+//! its first sentence. Every story gets a slug from its title, unique in
+//! the library, for file names such as exports; it stays put across
+//! frontend writes and only changes when the story is renamed.
+
+use super::stories::{now_ms, StoryError};
+use super::translation::split_into_segments;
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// Words of the first sentence kept in a heuristic title.
+pub const TITLE_WORDS: usize = 8;
+
+/// Longest slug, in characters, before any `-N` suffix.
+pub const MAX_SLUG_CHARS: usize = 60;
+
+const UNTITLED: &str = "Untitled";
+
+/// Names Windows will not create a file under, whatever the extension.
+const RESERVED: [&str; 22] = [
+    "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8", "com9", "lpt1", "lpt2",
+    "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoryName {
+    pub story_id: String,
+    pub title: String,
+    pub slug: String,
+}
+
+/// The start of the first sentence of `text`, at most [`TITLE_WORDS`]
+/// words, without Markdown heading marks, quotes or closing punctuation;
+/// "Untitled" when there are no words.
+pub fn heuristic_title(text: &str) -> String {
+    let first = split_into_segments(text).into_iter().next().unwrap_or_default();
+    let first = first.trim_start_matches(|c: char| c == '#' || c == '>' || c.is_whitespace());
+    let words: Vec<&str> = first.split_whitespace().collect();
+    let mut title = words[..words.len().min(TITLE_WORDS)].join(" ");
+    let quotes = |c: char| "\"'“”„«»‘’「」*_".contains(c);
+    title = title
+        .trim_matches(|c: char| quotes(c) || ".,;:!?…".contains(c) || c.is_whitespace())
+        .to_string();
+    if title.is_empty() {
+        return UNTITLED.to_string();
+    }
+    if words.len() > TITLE_WORDS {
+        title.push('…');
+    }
+    title
+}
+
+/// Lowercase letters and digits of `title`, other runs of characters as a
+/// single `-`, at most [`MAX_SLUG_CHARS`]; "story" when nothing is left.
+pub fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.trim().chars().flat_map(char::to_lowercase) {
+        if slug.chars().count() >= MAX_SLUG_CHARS {
+            break;
+        }
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.ends_with('-') && !slug.is_empty() {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    match slug {
+        "" => "story".to_string(),
+        reserved if RESERVED.contains(&reserved) => format!("{}-story", reserved),
+        slug => slug.to_string(),
+    }
+}
+
+/// `slugify(title)`, with `-2`, `-3`… added until it is not in `taken`.
+pub fn unique_slug(title: &str, taken: &HashSet<String>) -> String {
+    let base = slugify(title);
+    if !taken.contains(&base) {
+        return base;
+    }
+    (2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|slug| !taken.contains(slug))
+        .expect("some suffix is free")
+}
+
+/// The title a story without one gets: a model-suggested title from its
+/// translations' metadata, else [`heuristic_title`] of its source, else of
+/// its first translated doc.
+pub fn derived_title(story: &Value) -> String {
+    let translations = story.get("translations").and_then(Value::as_object);
+    let suggested = translations
+        .into_iter()
+        .flat_map(|t| t.values())
+        .filter_map(|t| t.get("meta")?.get("title")?.as_str())
+        .map(str::trim)
+        .find(|t| !t.is_empty());
+    if let Some(title) = suggested {
+        return title.to_string();
+    }
+    // A private story's source is not kept, so it does not name it either.
+    let private = story.get("private").and_then(Value::as_bool).unwrap_or(false);
+    let source = story.get("sourceText").and_then(Value::as_str).unwrap_or_default();
+    if !private && !source.trim().is_empty() {
+        return heuristic_title(source);
+    }
+    let translated = translations
+        .into_iter()
+        .flat_map(|t| t.values())
+        .filter_map(|t| serde_json::from_value::<super::gui_types::InteractiveDoc>(t.get("doc")?.clone()).ok())
+        .map(|doc| doc.block_texts().join("\n\n"))
+        .find(|text| !text.trim().is_empty());
+    heuristic_title(&translated.unwrap_or_default())
+}
+
+/// Copy the slugs of `saved` into the same stories of `stories`; the
+/// frontend does not keep them.
+pub fn keep_slugs(stories: &mut Value, saved: &Value) {
+    let (Some(list), Some(saved)) = (stories.as_array_mut(), saved.as_array()) else {
+        return;
+    };
+    for story in list {
+        let slug = saved
+            .iter()
+            .find(|s| s.get("id").is_some_and(|id| Some(id) == story.get("id")))
+            .and_then(|s| s.get("slug"))
+            .filter(|slug| slug.as_str().is_some_and(|s| !s.is_empty()));
+        if let (Some(slug), Some(entry)) = (slug, story.as_object_mut()) {
+            entry.insert("slug".to_string(), slug.clone());
+        }
+    }
+}
+
+/// Give stories without a title their [`derived_title`], and stories
+/// without a slug, or with one another story already has, a unique one.
+/// Returns how many stories changed.
+pub fn name_stories(stories: &mut Value) -> usize {
+    let Some(list) = stories.as_array_mut() else {
+        return 0;
+    };
+    let mut taken = HashSet::new();
+    let mut changed = 0;
+    for story in list.iter_mut().filter(|s| s.is_object()) {
+        let mut touched = false;
+        if field(story, "title").trim().is_empty() {
+            story["title"] = Value::from(derived_title(story));
+            touched = true;
+        }
+        let slug = field(story, "slug");
+        if slug.is_empty() || taken.contains(&slug) {
+            story["slug"] = Value::from(unique_slug(&field(story, "title"), &taken));
+            touched = true;
+        }
+        taken.insert(field(story, "slug"));
+        changed += usize::from(touched);
+    }
+    changed
+}
+
+/// Rename story `story_id` to `title`, or to its [`derived_title`] when
+/// `title` is blank, and give it a new slug. Only `stories` is modified;
+/// the caller saves it.
+pub fn rename_story(stories: &mut Value, story_id: &str, title: Option<&str>) -> Result<StoryName, StoryError> {
+    let list = stories
+        .as_array_mut()
+        .ok_or_else(|| StoryError::Parse("stories.json is not an array".to_string()))?;
+    let index = list
+        .iter()
+        .position(|s| s.get("id").and_then(Value::as_str) == Some(story_id))
+        .filter(|&i| list[i].is_object())
+        .ok_or_else(|| StoryError::NotFound(story_id.to_string()))?;
+    let taken: HashSet<String> = list
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| i != index)
+        .map(|(_, s)| field(s, "slug"))
+        .collect();
+
+    let story = &mut list[index];
+    let title = match title.map(str::trim).filter(|t| !t.is_empty()) {
+        Some(title) => title.to_string(),
+        None => {
+            story["title"] = Value::from("");
+            derived_title(story)
+        }
+    };
+    let slug = unique_slug(&title, &taken);
+    story["title"] = Value::from(title.clone());
+    story["slug"] = Value::from(slug.clone());
+    story["updatedAt"] = Value::from(now_ms());
+    Ok(StoryName {
+        story_id: story_id.to_string(),
+        title,
+        slug,
+    })
+}
+
+fn field(story: &Value, name: &str) -> String {
+    story.get(name).and_then(Value::as_str).unwrap_or_default().to_string()
+}
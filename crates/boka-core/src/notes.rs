@@ -0,0 +1,86 @@
+//! Learner notes in the learner's own language. Jobs write the notes of
+//! new variants in their `ui_language`; [`translate_notes`] localizes the
+//! notes of a doc made before, or for another learner.
+
+use super::gui_types::InteractiveDoc;
+use super::text;
+use super::translation::{fill_anthropic_key, Client};
+use super::types::{ApiConfig, ApiError, LlmProviderConfig};
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Notes sent per call; a long doc has hundreds.
+const NOTES_PER_CALL: usize = 40;
+
+/// Translate every variant note of `doc`, a doc in `language`, into
+/// `ui_language`. Each distinct note is sent once. Returns how many variants
+/// got a different note. Nothing is changed when a call fails.
+pub async fn translate_notes(
+    doc: &mut InteractiveDoc,
+    language: &str,
+    ui_language: &str,
+    provider: LlmProviderConfig,
+) -> Result<u32, ApiError> {
+    let mut notes: Vec<String> = Vec::new();
+    for note in doc.spans.values().flat_map(|s| &s.variants).filter_map(|v| v.note.as_deref()) {
+        if !note.trim().is_empty() && !notes.iter().any(|n| n == note) {
+            notes.push(note.to_string());
+        }
+    }
+    if notes.is_empty() {
+        return Ok(0);
+    }
+
+    let mut cfg = ApiConfig::from_env(language, None, false, false);
+    cfg.provider = provider;
+    fill_anthropic_key(&mut cfg);
+    let client = Client::new(cfg)?;
+
+    let mut translated: HashMap<String, String> = HashMap::new();
+    for chunk in notes.chunks(NOTES_PER_CALL) {
+        let (reply, _) = client.translate_notes(chunk, ui_language).await?;
+        let replies = parse_notes(&reply, chunk.len())?;
+        translated.extend(chunk.iter().cloned().zip(replies));
+    }
+
+    let mut changed = 0;
+    for variant in doc.spans.values_mut().flat_map(|s| &mut s.variants) {
+        let Some(note) = variant.note.as_mut() else {
+            continue;
+        };
+        if let Some(new) = translated.get(note.as_str()).filter(|new| *new != note) {
+            *note = new.clone();
+            changed += 1;
+        }
+    }
+    Ok(changed)
+}
+
+/// Parse `{ "notes": [...] }`, tolerating code fences. The model must
+/// return one non-empty string per note sent, or the pairing is lost.
+fn parse_notes(reply: &str, expected: usize) -> Result<Vec<String>, ApiError> {
+    let cleaned = reply
+        .trim()
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim();
+    let output = || text::excerpt(cleaned, text::EXCERPT_LEN);
+    let value: Value = serde_json::from_str(cleaned)
+        .map_err(|e| ApiError::Parse(format!("Notes JSON parse: {} | output: {}", e, output())))?;
+    let notes: Vec<String> = value
+        .get("notes")
+        .and_then(Value::as_array)
+        .map(|items| items.iter().filter_map(Value::as_str).map(|s| s.trim().to_string()).collect())
+        .unwrap_or_default();
+    if notes.len() != expected || notes.iter().any(String::is_empty) {
+        return Err(ApiError::Parse(format!(
+            "Notes JSON parse: expected {} notes, got {} | output: {}",
+            expected,
+            notes.len(),
+            output()
+        )));
+    }
+    Ok(notes)
+}
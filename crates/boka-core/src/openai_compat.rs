@@ -0,0 +1,730 @@
+use super::anthropic::{PlannedBlock, PlannedSegment, PlannedSpan, PlannedVariant};
+use super::cassette;
+use super::import::PageImage;
+use super::judge::{self, JudgeVerdict};
+use super::length_guard::LengthGuard;
+use super::policy::ContentPolicy;
+use super::prompts::{self, PromptSet};
+use super::reasoning;
+use super::refusal;
+use super::simple_format::{parse_simple_plan, parse_simple_variants, StructuredFormat};
+use super::text::{excerpt, EXCERPT_LEN};
+use super::types::{ApiConfig, ApiError, LlmProviderPreset, ModelCapabilities, ModelRegistry, Usage};
+
+use serde_json::Value;
+use std::time::Duration;
+
+pub struct OpenAiCompatClient {
+    client: reqwest::Client,
+    config: ApiConfig,
+    base_url: String,
+    api_key: Option<String>,
+    model: String,
+    caps: ModelCapabilities,
+    prompts: PromptSet,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+fn default_base_url(preset: LlmProviderPreset) -> Option<String> {
+    let url = match preset {
+        LlmProviderPreset::Openai => "https://api.openai.com/v1",
+        LlmProviderPreset::Openrouter => "https://openrouter.ai/api/v1",
+        LlmProviderPreset::Ollama => "http://localhost:11434/v1",
+        LlmProviderPreset::Lmstudio => "http://localhost:1234/v1",
+        LlmProviderPreset::Custom | LlmProviderPreset::Anthropic => return None,
+        #[cfg(any(test, feature = "mock"))]
+        LlmProviderPreset::Mock => return None,
+    };
+    Some(url.to_string())
+}
+
+impl OpenAiCompatClient {
+    pub fn new(config: ApiConfig) -> Result<Self, ApiError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(60))
+            .build()?;
+
+        let preset = config.provider.preset;
+        let registry = ModelRegistry::current();
+        let base_url = config
+            .provider
+            .base_url
+            .clone()
+            .and_then(|u| {
+                let t = u.trim().to_string();
+                if t.is_empty() { None } else { Some(t) }
+            })
+            .or_else(|| default_base_url(preset))
+            .unwrap_or_default()
+            .trim()
+            .trim_end_matches('/')
+            .to_string();
+
+        if base_url.is_empty() {
+            return Err(ApiError::Parse("OpenAI-compatible baseUrl is required".to_string()));
+        }
+
+        let model = config
+            .provider
+            .model
+            .clone()
+            .and_then(|m| {
+                let t = m.trim().to_string();
+                if t.is_empty() { None } else { Some(t) }
+            })
+            .or_else(|| registry.default_model(preset).map(str::to_string))
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+
+        if model.is_empty() {
+            return Err(ApiError::Parse("OpenAI-compatible model is required".to_string()));
+        }
+        let caps = registry.capabilities(preset, &model);
+
+        let api_key = config
+            .provider
+            .api_key
+            .clone()
+            .and_then(|k| {
+                let t = k.trim().to_string();
+                if t.is_empty() {
+                    None
+                } else {
+                    Some(t)
+                }
+            });
+
+        let replay = config.cassette.as_ref().is_some_and(|c| c.is_replay());
+        if matches!(
+            config.provider.preset,
+            LlmProviderPreset::Openai | LlmProviderPreset::Openrouter
+        ) && api_key.is_none()
+            && !replay
+        {
+            return Err(ApiError::NoApiKey {
+                provider: format!("{:?}", config.provider.preset).to_lowercase(),
+            });
+        }
+
+        let prompts = PromptSet::for_config(&config, false);
+        Ok(Self {
+            client,
+            config,
+            base_url,
+            api_key,
+            model,
+            caps,
+            prompts,
+        })
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn preset(&self) -> LlmProviderPreset {
+        self.config.provider.preset
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    pub fn length_guard(&self) -> LengthGuard {
+        self.config.length_guard
+    }
+
+    pub fn capabilities(&self) -> &ModelCapabilities {
+        &self.caps
+    }
+
+    pub fn has_api_key(&self) -> bool {
+        self.api_key.is_some()
+    }
+
+    pub fn chat_completions_url(&self) -> String {
+        format!("{}/chat/completions", self.base_url)
+    }
+
+    async fn chat(
+        &self,
+        system: String,
+        user: impl Into<Value>,
+        max_tokens: u32,
+        format: OutputFormat,
+    ) -> Result<(String, Usage), ApiError> {
+        let url = self.chat_completions_url();
+        let mut system = prompts::assemble_system(self.prompts.preamble.as_deref(), &system);
+        let json_mode = format == OutputFormat::Json && self.caps.json_mode;
+        if json_mode {
+            system.push_str(prompts::JSON_OBJECT_NOTE);
+        }
+
+        let mut body = serde_json::json!({
+            "model": self.model,
+            "messages": [
+                {"role": "system", "content": system},
+                {"role": "user", "content": user.into()},
+            ],
+            "max_tokens": self.caps.max_tokens(max_tokens),
+        });
+        if json_mode {
+            body["response_format"] = serde_json::json!({"type": "json_object"});
+        }
+        let sampling = &self.config.sampling;
+        if let Some(t) = sampling.temperature {
+            body["temperature"] = serde_json::json!(t);
+        }
+        if let Some(p) = sampling.top_p {
+            body["top_p"] = serde_json::json!(p);
+        }
+        if let Some(seed) = sampling.seed {
+            body["seed"] = serde_json::json!(seed);
+        }
+
+        let bearer = self.api_key.as_ref().map(|key| format!("Bearer {}", key));
+        let mut headers: Vec<(&str, &str)> = Vec::new();
+        if let Some(b) = &bearer {
+            headers.push(("authorization", b));
+        }
+
+        let (status, text) = cassette::post_json(
+            &self.client,
+            self.config.cassette.as_deref(),
+            self.config.transcript.as_deref(),
+            &url,
+            &headers,
+            self.api_key.as_deref(),
+            &body,
+        )
+        .await?;
+
+        if !(200..300).contains(&status) {
+            return Err(ApiError::ApiResponse { status, message: text });
+        }
+
+        let raw: Value =
+            serde_json::from_str(&text).map_err(|e| ApiError::Parse(format!("Response body: {}", e)))?;
+
+        let text = raw
+            .get("choices")
+            .and_then(|c| c.get(0))
+            .and_then(|c0| c0.get("message"))
+            .and_then(|m| m.get("content"))
+            .and_then(|c| c.as_str())
+            .unwrap_or("")
+            .trim()
+            .to_string();
+        let text = if self.config.provider.reasoning_model {
+            reasoning::clean_output(&text, format == OutputFormat::Json)
+        } else {
+            text
+        };
+
+        let finish_reason = raw.pointer("/choices/0/finish_reason").and_then(|r| r.as_str());
+        let declined = raw
+            .pointer("/choices/0/message/refusal")
+            .and_then(Value::as_str)
+            .filter(|r| !r.trim().is_empty());
+        let provider = format!("{:?}", self.config.provider.preset).to_lowercase();
+        let flagged = declined.is_some() || finish_reason == Some("content_filter");
+        refusal::check(&provider, &self.model, declined.unwrap_or(&text), flagged)?;
+
+        let usage = Usage {
+            max_token_stops: (finish_reason == Some("length")) as u32,
+            ..raw
+                .get("usage")
+                .and_then(|u| {
+                    Some(Usage {
+                        input_tokens: u.get("prompt_tokens")?.as_u64()? as u32,
+                        output_tokens: u.get("completion_tokens")?.as_u64()? as u32,
+                        max_token_stops: 0,
+                    })
+                })
+                .unwrap_or_default()
+        };
+
+        Ok((text, usage))
+    }
+
+    /// Base translation of `segment`; `note` asks again after a reply failed
+    /// the length guard.
+    pub async fn translate_base_segment(
+        &self,
+        full_story: &str,
+        segment: &str,
+        note: Option<&str>,
+    ) -> Result<(String, Usage), ApiError> {
+        let system = self.prompts.base_translation.clone();
+        let content = prompts::base_translation_user_content(full_story, segment, self.caps.story_context_budget());
+        let content = prompts::with_revision_note(content, note);
+
+        self.chat(system, content, 512, OutputFormat::Text).await
+    }
+
+    /// Graded-reader version of `segment`; needs `ApiConfig::simplify_level`.
+    pub async fn translate_simplified_segment(
+        &self,
+        full_story: &str,
+        segment: &str,
+        note: Option<&str>,
+    ) -> Result<(String, Usage), ApiError> {
+        let system = self
+            .prompts
+            .simplified_translation
+            .clone()
+            .ok_or_else(|| ApiError::Parse("No simplify level configured".to_string()))?;
+        let content = prompts::simplified_translation_user_content(
+            full_story,
+            segment,
+            self.caps.story_context_budget(),
+            note,
+        );
+
+        self.chat(system, content, 512, OutputFormat::Text).await
+    }
+
+    /// Transcribe the text on one page image.
+    pub async fn transcribe_image(&self, image: &PageImage) -> Result<(String, Usage), ApiError> {
+        let user = serde_json::json!([
+            {
+                "type": "image_url",
+                "image_url": { "url": format!("data:{};base64,{}", image.media_type, image.data) },
+            },
+            { "type": "text", "text": prompts::TRANSCRIPTION_REQUEST },
+        ]);
+
+        self.chat(prompts::transcription_system_prompt(), user, 4096, OutputFormat::Text)
+            .await
+    }
+
+    /// Shelf title, synopsis and cover emoji for a translated story, as the
+    /// model's JSON (see `story_meta`).
+    pub async fn describe_story(&self, source: &str, translation: &str) -> Result<(String, Usage), ApiError> {
+        let source_language = self.config.source_language.as_deref();
+        let system = prompts::story_description_system_prompt(&self.config.target_language, source_language);
+        let content = prompts::story_description_user_content(source, translation);
+
+        self.chat(system, content, 512, OutputFormat::Json).await
+    }
+
+    pub async fn comprehension_check(&self, passage: &str) -> Result<(String, Usage), ApiError> {
+        let source_language = self.config.source_language.as_deref();
+        let system = prompts::comprehension_check_system_prompt(&self.config.target_language, source_language);
+
+        self.chat(system, passage.to_string(), 256, OutputFormat::Json).await
+    }
+
+    /// `notes` in `ui_language`, as the model's `{ "notes": [...] }`.
+    pub async fn translate_notes(&self, notes: &[String], ui_language: &str) -> Result<(String, Usage), ApiError> {
+        let system = prompts::note_translation_system_prompt(&self.config.target_language, ui_language);
+        let content = prompts::note_translation_user_content(notes);
+
+        self.chat(system, content, 4096, OutputFormat::Json).await
+    }
+
+    /// About `words` more words of `story`, in its own language.
+    pub async fn continue_story(
+        &self,
+        story: &str,
+        direction_hint: Option<&str>,
+        words: u32,
+    ) -> Result<(String, Usage), ApiError> {
+        let source_language = self.config.source_language.as_deref();
+        let system = prompts::continuation_system_prompt(source_language, words, &self.config.content_policy);
+        let content = prompts::continuation_user_content(story, direction_hint);
+
+        self.chat(system, content, words * 4, OutputFormat::Text).await
+    }
+
+    /// Who says each dialogue line, as the model's `{ "lines": [...] }` (see
+    /// `speakers`).
+    pub async fn attribute_speakers(
+        &self,
+        blocks: &[(u32, String)],
+        lines: &[(String, String)],
+        max_speakers: usize,
+    ) -> Result<(String, Usage), ApiError> {
+        let system = prompts::speaker_attribution_system_prompt(&self.config.target_language, max_speakers);
+        let content = prompts::speaker_attribution_user_content(blocks, lines);
+
+        self.chat(system, content, 2048, OutputFormat::Json).await
+    }
+
+    pub async fn answer_question(
+        &self,
+        source: &str,
+        translation: &str,
+        earlier: &[(&str, &str)],
+        question: &str,
+        ui_language: Option<&str>,
+        policy: Option<&ContentPolicy>,
+    ) -> Result<(String, Usage), ApiError> {
+        let source_language = self.config.source_language.as_deref();
+        let system = prompts::tutor_system_prompt(&self.config.target_language, source_language, ui_language, policy);
+        let content = prompts::tutor_user_content(source, translation, earlier, question);
+
+        self.chat(system, content, 600, OutputFormat::Text).await
+    }
+
+    pub async fn score_translation(&self, source: &str, translation: &str) -> Result<(JudgeVerdict, Usage), ApiError> {
+        let system = prompts::judge_system_prompt(&self.config.target_language, self.config.source_language.as_deref());
+        let content = prompts::judge_user_content(source, translation);
+
+        let (text, usage) = self.chat(system, content, 256, OutputFormat::Json).await?;
+        Ok((judge::parse_verdict(&text)?, usage))
+    }
+
+    pub async fn plan_block_from_base(
+        &self,
+        base_text: &str,
+        format: StructuredFormat,
+    ) -> Result<(PlannedBlock, Usage), ApiError> {
+        if format == StructuredFormat::Simple {
+            let system = prompts::simple_span_planning_system_prompt(&self.config);
+            let (text, usage) = self.chat(system, base_text.to_string(), 2048, OutputFormat::Text).await?;
+            return Ok((parse_simple_plan(&text, base_text)?, usage));
+        }
+        let system = self.prompts.span_planning.clone();
+        let (text, usage) = self.chat(system, base_text.to_string(), 2048, OutputFormat::Json).await?;
+
+        let mut blocks = parse_planned_blocks(&text)?;
+        let block = blocks
+            .drain(..)
+            .next()
+            .ok_or_else(|| ApiError::Parse("No block returned".to_string()))?;
+
+        Ok((block, usage))
+    }
+
+    pub async fn generate_span_variants(
+        &self,
+        segment_context: &str,
+        anchor_phrase: &str,
+        variant_count: u32,
+        format: StructuredFormat,
+    ) -> Result<(Vec<PlannedVariant>, Usage), ApiError> {
+        let content = format!(
+            "SEGMENT CONTEXT:\n{}\n\nANCHOR PHRASE:\n{}",
+            segment_context, anchor_phrase
+        );
+        if format == StructuredFormat::Simple {
+            let system = prompts::simple_span_variants_system_prompt(&self.config, variant_count);
+            let (text, usage) = self.chat(system, content, 2048, OutputFormat::Text).await?;
+            return Ok((parse_simple_variants(&text)?, usage));
+        }
+        let system = self.prompts.span_variants_for(variant_count);
+
+        let (text, usage) = self.chat(system, content, 2048, OutputFormat::Json).await?;
+        let variants = parse_variants(&text)?;
+        Ok((variants, usage))
+    }
+
+    /// Critique-and-fix pass over `variants`; needs `ApiConfig::refine_variants`.
+    pub async fn refine_span_variants(
+        &self,
+        segment_context: &str,
+        anchor_phrase: &str,
+        variants: &[PlannedVariant],
+    ) -> Result<(Vec<PlannedVariant>, Usage), ApiError> {
+        let system = self
+            .prompts
+            .variant_critique
+            .clone()
+            .ok_or_else(|| ApiError::Parse("Variant refinement not enabled".to_string()))?;
+        let content = prompts::variant_critique_user_content(segment_context, anchor_phrase, variants);
+
+        let (text, usage) = self.chat(system, content, 2048, OutputFormat::Json).await?;
+        Ok((parse_variants(&text)?, usage))
+    }
+
+    pub async fn test_connection(&self) -> Result<(), ApiError> {
+        let system = "You are a connectivity test. Reply with OK.".to_string();
+        let user = "ping".to_string();
+        let _ = self.chat(system, user, 1, OutputFormat::Text).await?;
+        Ok(())
+    }
+}
+
+pub(crate) fn parse_variants(json_text: &str) -> Result<Vec<PlannedVariant>, ApiError> {
+    let excerpt = excerpt(json_text.trim(), EXCERPT_LEN);
+    let raw_value = json_payload(json_text, &excerpt)?;
+
+    let raw_variants: Vec<RawVariant> = match raw_value {
+        Value::Array(_) => serde_json::from_value(raw_value)
+            .map_err(|e| ApiError::Parse(format!("JSON parse: {} | output: {}", e, excerpt)))?,
+        Value::Object(ref obj) => {
+            if let Some(vs) = obj.get("variants") {
+                serde_json::from_value(vs.clone())
+                    .map_err(|e| ApiError::Parse(format!("JSON parse: {} | output: {}", e, excerpt)))?
+            } else if obj.get("text").is_some() {
+                vec![serde_json::from_value(raw_value)
+                    .map_err(|e| ApiError::Parse(format!("JSON parse: {} | output: {}", e, excerpt)))?]
+            } else {
+                return Err(ApiError::Parse(format!(
+                    "JSON parse: expected array/object variant | output: {}",
+                    excerpt
+                )));
+            }
+        }
+        _ => {
+            return Err(ApiError::Parse(format!(
+                "JSON parse: expected array/object | output: {}",
+                excerpt
+            )));
+        }
+    };
+
+    let variants = raw_variants
+        .into_iter()
+        .filter(|v| !v.text.trim().is_empty())
+        .map(|v| PlannedVariant {
+            text: v.text,
+            register: v.register,
+            note: v.note.unwrap_or_default(),
+            difficulty: v.difficulty.unwrap_or(2),
+        })
+        .collect();
+
+    Ok(variants)
+}
+
+/// The JSON value in a model reply. Models wrap it in code fences, put prose
+/// before or after it, and leave trailing commas; all of that is dropped.
+/// Only the first bracket that opens an object or an array of objects is
+/// tried, so a reply cut off or garbled mid-value is an error rather than a
+/// guess built from whatever part of it still parses.
+fn json_payload(text: &str, excerpt: &str) -> Result<Value, ApiError> {
+    let cleaned = text
+        .trim()
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim();
+    let error = match serde_json::from_str(cleaned)
+        .or_else(|_| serde_json::from_str(&sanitize_json_trailing_commas(cleaned)))
+    {
+        Ok(value) => return Ok(value),
+        Err(e) => e,
+    };
+
+    let error = match json_start(cleaned) {
+        Some(start) => {
+            let sanitized = sanitize_json_trailing_commas(&cleaned[start..]);
+            // Reads one value and ignores whatever follows it.
+            match serde_json::Deserializer::from_str(&sanitized).into_iter::<Value>().next() {
+                Some(Ok(value)) => return Ok(value),
+                Some(Err(e)) => e,
+                None => error,
+            }
+        }
+        None => error,
+    };
+    Err(ApiError::Parse(format!("JSON parse: {} | output: {}", error, excerpt)))
+}
+
+/// Where the JSON starts in a reply with prose around it: the first `[{`,
+/// `[]`, `{"` or `{}`, so a `[1]` or `{name}` in the prose is passed over.
+fn json_start(text: &str) -> Option<usize> {
+    text.match_indices(['[', '{']).map(|(i, _)| i).find(|&i| {
+        let next = text[i + 1..].trim_start().chars().next();
+        match &text[i..=i] {
+            "[" => matches!(next, Some('{' | ']')),
+            _ => matches!(next, Some('"' | '}')),
+        }
+    })
+}
+
+/// Drop commas that close an array or object (`[1, 2,]`), which models
+/// often emit and `serde_json` rejects. Commas inside strings are kept.
+pub fn sanitize_json_trailing_commas(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    let mut in_string = false;
+    let mut escape = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            if escape {
+                escape = false;
+                continue;
+            }
+            if c == '\\' {
+                escape = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+
+        if c == '"' {
+            in_string = true;
+            out.push(c);
+            continue;
+        }
+
+        if c == ',' {
+            let mut look = chars.clone();
+            while let Some(n) = look.peek() {
+                if n.is_whitespace() {
+                    look.next();
+                } else {
+                    break;
+                }
+            }
+            if matches!(look.peek(), Some(']') | Some('}')) {
+                continue;
+            }
+        }
+
+        out.push(c);
+    }
+
+    out
+}
+
+/// Blocks from a span-plan response: an array of blocks, one block, or a
+/// bare `{ "text" }`, repaired as [`json_payload`] does.
+pub fn parse_planned_blocks(json_text: &str) -> Result<Vec<PlannedBlock>, ApiError> {
+    let excerpt = excerpt(json_text.trim(), EXCERPT_LEN);
+    let value = json_payload(json_text, &excerpt)?;
+
+    let mut raw_blocks: Vec<RawBlock> = Vec::new();
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                if item.get("segments").is_some() {
+                    let rb: RawBlock = serde_json::from_value(item)
+                        .map_err(|e| ApiError::Parse(format!("JSON parse: {} | output: {}", e, excerpt)))?;
+                    raw_blocks.push(rb);
+                } else if let Some(text) = item.get("text").and_then(|v| v.as_str()) {
+                    raw_blocks.push(RawBlock {
+                        id: item
+                            .get("id")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or_default()
+                            .to_string(),
+                        segments: vec![RawSegment {
+                            segment_type: "static".to_string(),
+                            id: None,
+                            text: Some(text.to_string()),
+                            variants: None,
+                        }],
+                    });
+                } else {
+                    return Err(ApiError::Parse(format!(
+                        "JSON parse: block missing `segments` | output: {}",
+                        excerpt
+                    )));
+                }
+            }
+        }
+        Value::Object(_) => {
+            if value.get("segments").is_some() {
+                let rb: RawBlock = serde_json::from_value(value)
+                    .map_err(|e| ApiError::Parse(format!("JSON parse: {} | output: {}", e, excerpt)))?;
+                raw_blocks.push(rb);
+            } else if let Some(text) = value.get("text").and_then(|v| v.as_str()) {
+                raw_blocks.push(RawBlock {
+                    id: value
+                        .get("id")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or_default()
+                        .to_string(),
+                    segments: vec![RawSegment {
+                        segment_type: "static".to_string(),
+                        id: None,
+                        text: Some(text.to_string()),
+                        variants: None,
+                    }],
+                });
+            } else {
+                return Err(ApiError::Parse(format!(
+                    "JSON parse: missing `segments` | output: {}",
+                    excerpt
+                )));
+            }
+        }
+        _ => {
+            return Err(ApiError::Parse(format!(
+                "JSON parse: expected array/object | output: {}",
+                excerpt
+            )));
+        }
+    }
+
+    let blocks = raw_blocks
+        .into_iter()
+        .map(|rb| {
+            let segments = rb
+                .segments
+                .into_iter()
+                .map(|seg| match seg.segment_type.as_str() {
+                    "static" => PlannedSegment::Static(seg.text.unwrap_or_default()),
+                    "swappable" => {
+                        let variants = seg
+                            .variants
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|v| PlannedVariant {
+                                text: v.text,
+                                register: v.register,
+                                note: v.note.unwrap_or_default(),
+                                difficulty: v.difficulty.unwrap_or(2),
+                            })
+                            .collect();
+                        PlannedSegment::Swappable(PlannedSpan {
+                            id: seg.id.unwrap_or_default(),
+                            variants,
+                            provenance: vec![],
+                        })
+                    }
+                    _ => PlannedSegment::Static(seg.text.unwrap_or_default()),
+                })
+                .collect();
+
+            PlannedBlock { id: rb.id, segments }
+        })
+        .collect();
+
+    Ok(blocks)
+}
+
+#[derive(serde::Deserialize)]
+struct RawBlock {
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    segments: Vec<RawSegment>,
+}
+
+#[derive(serde::Deserialize)]
+struct RawSegment {
+    #[serde(default, rename = "type")]
+    segment_type: String,
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    variants: Option<Vec<RawVariant>>,
+}
+
+#[derive(serde::Deserialize)]
+struct RawVariant {
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    register: String,
+    #[serde(default)]
+    note: Option<String>,
+    #[serde(default)]
+    difficulty: Option<u8>,
+}
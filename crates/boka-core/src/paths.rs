@@ -0,0 +1,188 @@
+use super::profiles::{DEFAULT_PROFILE, PROFILES_DIR};
+
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+const APP_DIR: &str = "boka";
+
+#[derive(Debug, thiserror::Error)]
+pub enum PathError {
+    #[error("Could not determine the {0} directory")]
+    Unresolved(&'static str),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+    Macos,
+    Windows,
+    Linux,
+}
+
+impl Platform {
+    pub fn current() -> Self {
+        if cfg!(target_os = "macos") {
+            Platform::Macos
+        } else if cfg!(windows) {
+            Platform::Windows
+        } else {
+            Platform::Linux
+        }
+    }
+}
+
+/// Every on-disk location the app uses, resolved once per platform.
+///
+/// The data dir is shared with the TUI, so its macOS location must not move.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BokaPaths {
+    pub platform: Platform,
+    /// stories.json, settings.json, models.json, tts_models.json.
+    pub data_dir: PathBuf,
+    /// Disposable files; safe to delete.
+    pub cache_dir: PathBuf,
+    /// Shared by all profiles; the audio cache keeps one namespace per
+    /// profile and model version inside it.
+    pub audio_cache_dir: PathBuf,
+    /// Locally managed model files, e.g. installed TTS models under `<id>/`.
+    pub model_dir: PathBuf,
+    /// HuggingFace hub cache, where the TTS model is downloaded.
+    pub hf_cache_dir: PathBuf,
+}
+
+impl BokaPaths {
+    pub fn current() -> Result<Self, PathError> {
+        Self::resolve(Platform::current(), |key| std::env::var(key).ok())
+    }
+
+    /// Resolve for `platform` using `env` for variable lookups, so every
+    /// platform's rules can be exercised from any host.
+    pub fn resolve(platform: Platform, env: impl Fn(&str) -> Option<String>) -> Result<Self, PathError> {
+        // Empty variables count as unset.
+        let var = |key: &str| env(key).filter(|v| !v.trim().is_empty()).map(PathBuf::from);
+        // XDG requires absolute paths; relative ones are ignored.
+        let xdg = |key: &str| var(key).filter(|p| is_absolute(p));
+
+        let home = match platform {
+            Platform::Windows => var("USERPROFILE").or_else(|| {
+                let drive = env("HOMEDRIVE")?;
+                let path = env("HOMEPATH")?;
+                Some(PathBuf::from(format!("{}{}", drive, path)))
+            }),
+            _ => var("HOME"),
+        }
+        .ok_or(PathError::Unresolved("home"))?;
+
+        let (data_dir, cache_dir) = match platform {
+            Platform::Macos => (
+                home.join("Library").join("Application Support").join(APP_DIR),
+                home.join("Library").join("Caches").join(APP_DIR),
+            ),
+            Platform::Windows => {
+                let roaming = var("APPDATA").unwrap_or_else(|| home.join("AppData").join("Roaming"));
+                let local = var("LOCALAPPDATA").unwrap_or_else(|| home.join("AppData").join("Local"));
+                (roaming.join(APP_DIR), local.join(APP_DIR).join("cache"))
+            }
+            Platform::Linux => (
+                xdg("XDG_DATA_HOME")
+                    .unwrap_or_else(|| home.join(".local").join("share"))
+                    .join(APP_DIR),
+                xdg("XDG_CACHE_HOME").unwrap_or_else(|| home.join(".cache")).join(APP_DIR),
+            ),
+        };
+
+        // Mirrors huggingface_hub: explicit hub cache, then HF_HOME, then the
+        // XDG cache (honoured on every OS), then ~/.cache.
+        let hf_cache_dir = var("HF_HUB_CACHE")
+            .or_else(|| var("HUGGINGFACE_HUB_CACHE"))
+            .or_else(|| var("HF_HOME").map(|p| p.join("hub")))
+            .or_else(|| xdg("XDG_CACHE_HOME").map(|p| p.join("huggingface").join("hub")))
+            .unwrap_or_else(|| home.join(".cache").join("huggingface").join("hub"));
+
+        Ok(Self {
+            platform,
+            audio_cache_dir: cache_dir.join("audio_cache"),
+            model_dir: data_dir.join("models"),
+            data_dir,
+            cache_dir,
+            hf_cache_dir,
+        })
+    }
+
+    /// The locations for profile `id`: its own data and cache dirs under
+    /// `profiles/<id>/`, with installed models shared. The default profile
+    /// keeps the top-level dirs.
+    pub fn for_profile(&self, id: &str) -> Self {
+        if id == DEFAULT_PROFILE {
+            return self.clone();
+        }
+        let cache_dir = self.cache_dir.join(PROFILES_DIR).join(id);
+        Self {
+            platform: self.platform,
+            data_dir: self.data_dir.join(PROFILES_DIR).join(id),
+            audio_cache_dir: self.audio_cache_dir.clone(),
+            cache_dir,
+            model_dir: self.model_dir.clone(),
+            hf_cache_dir: self.hf_cache_dir.clone(),
+        }
+    }
+
+    /// Name, path and whether it exists, for the diagnostics screen.
+    pub fn diagnostics(&self) -> Vec<PathStatus> {
+        [
+            ("dataDir", &self.data_dir),
+            ("cacheDir", &self.cache_dir),
+            ("audioCacheDir", &self.audio_cache_dir),
+            ("modelDir", &self.model_dir),
+            ("hfCacheDir", &self.hf_cache_dir),
+        ]
+        .into_iter()
+        .map(|(name, path)| PathStatus {
+            name: name.to_string(),
+            path: path.display().to_string(),
+            exists: path.is_dir(),
+        })
+        .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathStatus {
+    pub name: String,
+    pub path: String,
+    pub exists: bool,
+}
+
+/// Atomic write: `bytes` to a tmp file next to `path`, then renamed over
+/// it, so a reader sees the old file or the new one and never half of one.
+/// Every write has its own tmp file, so two writes of one path cannot
+/// rename each other's. Creates `path`'s directory if needed.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".{}.{}.tmp", std::process::id(), NEXT.fetch_add(1, Ordering::Relaxed)));
+    let written = fs::write(&tmp, bytes).and_then(|()| fs::rename(&tmp, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    written
+}
+
+/// `Path::is_absolute` only knows the host's rules; accept both styles so a
+/// Windows `C:\...` or `\\server\...` path is not rejected on other hosts.
+fn is_absolute(path: &Path) -> bool {
+    let s = path.to_string_lossy();
+    let b = s.as_bytes();
+    path.is_absolute()
+        || s.starts_with('/')
+        || s.starts_with("\\\\")
+        || (b.len() >= 3 && b[0].is_ascii_alphabetic() && b[1] == b':' && (b[2] == b'\\' || b[2] == b'/'))
+}
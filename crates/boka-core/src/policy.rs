@@ -0,0 +1,204 @@
+use super::anthropic::PlannedVariant;
+use super::moderation;
+
+use serde::{Deserialize, Serialize};
+
+pub const ALL_REGISTERS: [&str; 6] = ["formal", "literary", "neutral", "casual", "colloquial", "vulgar"];
+
+/// Map free-form register labels from the model onto the known spectrum.
+pub fn normalize_register(input: &str) -> String {
+    let lower = input.trim().to_lowercase();
+    match ALL_REGISTERS.iter().find(|r| **r == lower) {
+        Some(r) => r.to_string(),
+        None => "neutral".to_string(),
+    }
+}
+
+/// Every register, nearest to `register` first. Ties go to the one closer to
+/// neutral, so "colloquial" falls back to "casual" before "vulgar".
+pub fn register_fallback(register: &str) -> Vec<&'static str> {
+    let index = |r: &str| ALL_REGISTERS.iter().position(|x| *x == r).unwrap_or(2) as i32;
+    let target = index(&normalize_register(register));
+    let neutral = index("neutral");
+    let mut order = ALL_REGISTERS.to_vec();
+    order.sort_by_key(|r| ((index(r) - target).abs(), (index(r) - neutral).abs()));
+    order
+}
+
+/// The words of `text`, split as [`moderation::screen`] splits them, so a
+/// theme matches whole words only ("sex" is not in "Essex").
+fn words(text: &str) -> Vec<&str> {
+    text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()).collect()
+}
+
+/// What the model may produce, independent of the coarse `adult_mode` switch.
+///
+/// Profanity levels: 0 = none, 1 = mild, 2 = strong, 3 = anything authentic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentPolicy {
+    #[serde(default = "default_registers")]
+    pub allowed_registers: Vec<String>,
+    #[serde(default)]
+    pub blocked_themes: Vec<String>,
+    #[serde(default)]
+    pub profanity_level: u8,
+    /// Screen output with the offline keyword list and regenerate on hits.
+    #[serde(default)]
+    pub moderation: bool,
+}
+
+fn default_registers() -> Vec<String> {
+    ContentPolicy::for_adult_mode(false).allowed_registers
+}
+
+impl Default for ContentPolicy {
+    fn default() -> Self {
+        Self::for_adult_mode(false)
+    }
+}
+
+impl ContentPolicy {
+    /// The policy `adult_mode` has always implied.
+    pub fn for_adult_mode(adult_mode: bool) -> Self {
+        let registers = if adult_mode { &ALL_REGISTERS[..] } else { &ALL_REGISTERS[..5] };
+        Self {
+            allowed_registers: registers.iter().map(|r| r.to_string()).collect(),
+            blocked_themes: Vec::new(),
+            profanity_level: if adult_mode { 3 } else { 1 },
+            moderation: false,
+        }
+    }
+
+    /// Strict policy for young learners: formal/neutral/casual only, no
+    /// profanity, common unsafe themes blocked and output screened.
+    pub fn child_safe() -> Self {
+        Self {
+            allowed_registers: ["formal", "neutral", "casual"].iter().map(|r| r.to_string()).collect(),
+            blocked_themes: ["violence", "sex", "drugs", "alcohol", "gambling", "self-harm"]
+                .iter()
+                .map(|t| t.to_string())
+                .collect(),
+            profanity_level: 0,
+            moderation: true,
+        }
+    }
+
+    pub fn profanity(&self) -> u8 {
+        self.profanity_level.min(3)
+    }
+
+    pub fn allows_register(&self, register: &str) -> bool {
+        let register = normalize_register(register);
+        if register == "vulgar" && self.profanity() < 2 {
+            return false;
+        }
+        self.allowed_registers.iter().any(|r| r.trim().eq_ignore_ascii_case(&register))
+    }
+
+    /// Allowed registers in canonical spectrum order.
+    pub fn registers(&self) -> Vec<&'static str> {
+        ALL_REGISTERS.iter().copied().filter(|r| self.allows_register(r)).collect()
+    }
+
+    /// Tone guidance for the base translation prompt.
+    pub fn tone_note(&self) -> String {
+        let mut note = match self.profanity() {
+            0 => "Keep it strictly clean: no profanity, slurs or crude language, even if the source has them.".to_string(),
+            1 => "Keep it family-friendly. Colloquial is fine, vulgar is not; soften strong language.".to_string(),
+            2 => "Keep tone authentic; strong language is allowed where the source uses it, but no slurs.".to_string(),
+            _ => "Keep tone authentic; slang/profanity is allowed if it's in the source.".to_string(),
+        };
+        if let Some(themes) = self.themes_line() {
+            note.push('\n');
+            note.push_str(&themes);
+        }
+        note
+    }
+
+    fn themes_line(&self) -> Option<String> {
+        let themes: Vec<&str> = self
+            .blocked_themes
+            .iter()
+            .map(|t| t.trim())
+            .filter(|t| !t.is_empty())
+            .collect();
+        if themes.is_empty() {
+            None
+        } else {
+            Some(format!(
+                "Do not introduce or elaborate on these themes: {}. Tone them down if the source contains them.",
+                themes.join(", ")
+            ))
+        }
+    }
+
+    /// Register list and constraints for the span variants prompt.
+    pub fn register_instruction(&self) -> String {
+        let list = self
+            .registers()
+            .iter()
+            .map(|r| format!("- {}", r))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut out = format!("Generate variants ONLY in these registers:\n{}", list);
+        match self.profanity() {
+            0 => out.push_str("\n\nNo profanity of any kind, not even mild."),
+            1 => out.push_str("\n\nKeep all variants family-friendly."),
+            _ => {}
+        }
+        if let Some(themes) = self.themes_line() {
+            out.push_str("\n\n");
+            out.push_str(&themes);
+        }
+        out
+    }
+
+    /// Why a variant breaks this policy, if it does.
+    pub fn violation(&self, register: &str, text: &str, note: &str) -> Option<String> {
+        if !self.allows_register(register) {
+            return Some(format!("register `{}` not allowed", normalize_register(register)));
+        }
+
+        let haystack = format!("{} {}", text, note).to_lowercase();
+        let haystack_words = words(&haystack);
+        if let Some(t) = self
+            .blocked_themes
+            .iter()
+            .map(|t| t.trim().to_lowercase())
+            .find(|t| {
+                let theme = words(t);
+                !theme.is_empty() && haystack_words.windows(theme.len()).any(|w| w == theme.as_slice())
+            })
+        {
+            return Some(format!("blocked theme `{}`", t));
+        }
+
+        if self.moderation {
+            return moderation::screen(&haystack).map(|w| format!("moderation: `{}`", w));
+        }
+        None
+    }
+
+    /// Whether moderation flags any variant, including the anchor. Register
+    /// mismatches don't count: those are simply filtered out.
+    pub fn any_unsafe(&self, variants: &[PlannedVariant]) -> bool {
+        self.moderation
+            && variants
+                .iter()
+                .any(|v| moderation::screen(&format!("{} {}", v.text, v.note)).is_some())
+    }
+
+    /// Drop variants that violate the policy. The first variant is the anchor
+    /// that appears in the base text, so it is always kept (and flagged when
+    /// the doc is built).
+    pub fn filter_variants(&self, variants: Vec<PlannedVariant>) -> Vec<PlannedVariant> {
+        variants
+            .into_iter()
+            .enumerate()
+            .filter(|(i, v)| *i == 0 || self.violation(&v.register, &v.text, &v.note).is_none())
+            .map(|(_, v)| v)
+            .collect()
+    }
+}
@@ -0,0 +1,130 @@
+//! Shadowing schedule for one doc: which sentences to repeat aloud, at
+//! which speed, on which day.
+//!
+//! Sentences are taken in reading order, a few new ones a day (fewer at
+//! lower levels). Each group comes back three times, spaced out and a
+//! little faster each time: learned slowly on its first day, reviewed the
+//! next day, then shadowed at full speed two days after that. Harder
+//! sentences get an extra repeat while they are new. Stored as
+//! `practicePlan` on the translation entry (see
+//! [`set_practice_plan`](super::stories::set_practice_plan)).
+
+use super::analysis::segment_difficulty;
+use super::gui_types::DocToken;
+use super::simplify::CefrLevel;
+use super::stories::{now_ms, StoryDoc};
+
+use serde::{Deserialize, Serialize};
+
+/// Days after a group's first day on which it comes back, one per stage.
+const STAGE_OFFSETS: [u32; 3] = [0, 1, 3];
+/// Sentences at least this hard (see [`segment_difficulty`]) get an extra
+/// repeat while they are new.
+const HARD_SENTENCE: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PracticeStage {
+    /// First time, slowest speed.
+    Learn,
+    Review,
+    /// At the level's target speed.
+    Shadow,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PracticeItem {
+    pub span_id: String,
+    pub text: String,
+    pub stage: PracticeStage,
+    /// TTS speed, within the audio preset bounds.
+    pub speed: f32,
+    pub repeats: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PracticeDay {
+    /// 1 for the first day.
+    pub day: u32,
+    /// Reviews first, then the day's new sentences.
+    pub items: Vec<PracticeItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PracticePlan {
+    pub level: CefrLevel,
+    pub sentences: u32,
+    pub days: Vec<PracticeDay>,
+    pub created_at: u64,
+}
+
+/// New sentences a day and the speed of each stage, by level.
+fn pace(level: CefrLevel) -> (usize, [f32; 3]) {
+    match level {
+        CefrLevel::A1 => (3, [0.6, 0.75, 0.9]),
+        CefrLevel::A2 => (4, [0.7, 0.8, 0.95]),
+        CefrLevel::B1 => (5, [0.75, 0.9, 1.0]),
+        CefrLevel::B2 => (6, [0.85, 0.95, 1.0]),
+        CefrLevel::C1 => (8, [0.9, 1.0, 1.1]),
+        CefrLevel::C2 => (10, [1.0, 1.1, 1.25]),
+    }
+}
+
+/// Plan the doc of `story` for a learner at `level`. A doc without text
+/// gets a plan with no days.
+pub fn practice_plan(story: &StoryDoc, level: CefrLevel) -> PracticePlan {
+    let doc = &story.doc;
+    let sentences: Vec<(&str, &str)> = doc
+        .tokens
+        .iter()
+        .filter_map(|token| match token {
+            DocToken::Span { span_id, .. } => doc.spans.get(span_id),
+            DocToken::Text { .. } | DocToken::Check { .. } => None,
+        })
+        .filter_map(|span| {
+            let text = span.active_text()?.trim();
+            (!text.is_empty()).then_some((span.id.as_str(), text))
+        })
+        .collect();
+
+    let (per_day, speeds) = pace(level);
+    let groups: Vec<&[(&str, &str)]> = sentences.chunks(per_day).collect();
+    let day_count = match groups.len() {
+        0 => 0,
+        n => n as u32 + STAGE_OFFSETS[STAGE_OFFSETS.len() - 1],
+    };
+    let mut days: Vec<PracticeDay> = (1..=day_count).map(|day| PracticeDay { day, items: vec![] }).collect();
+
+    let stages = [PracticeStage::Learn, PracticeStage::Review, PracticeStage::Shadow];
+    // Latest stage first, so each day starts with what is best known.
+    for (stage_index, stage) in stages.iter().enumerate().rev() {
+        for (group_index, group) in groups.iter().enumerate() {
+            let day = &mut days[group_index + STAGE_OFFSETS[stage_index] as usize];
+            for (span_id, text) in group.iter() {
+                let hard = segment_difficulty(text, &story.language) >= HARD_SENTENCE;
+                let repeats = match stage {
+                    PracticeStage::Learn => 3,
+                    PracticeStage::Review => 2,
+                    PracticeStage::Shadow => 1,
+                } + u32::from(hard && *stage != PracticeStage::Shadow);
+                day.items.push(PracticeItem {
+                    span_id: span_id.to_string(),
+                    text: text.to_string(),
+                    stage: *stage,
+                    speed: speeds[stage_index],
+                    repeats,
+                });
+            }
+        }
+    }
+
+    PracticePlan {
+        level,
+        sentences: sentences.len() as u32,
+        days,
+        created_at: now_ms(),
+    }
+}
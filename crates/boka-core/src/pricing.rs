@@ -0,0 +1,165 @@
+//! User-edited model prices and the currency costs are shown in. The model
+//! registry ships list prices for the models it knows; OpenRouter and
+//! custom endpoints serve many more, and prices change. Prices set here are
+//! laid over the registry when it is installed (see [`PriceTable::apply`]),
+//! so every cost estimate and budget uses them. Costs stay in USD
+//! internally and are converted at a fixed, user-set rate for display;
+//! models without a price are shown by their token counts only.
+
+use super::settings::SettingsError;
+use super::types::{LlmProviderPreset, ModelEntry, ModelPricing, ModelRegistry, Usage};
+
+use serde::{Deserialize, Serialize};
+
+/// A user price for a model of a provider preset. `model` is an exact id or
+/// a prefix matching a family, as in the registry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceOverride {
+    pub preset: LlmProviderPreset,
+    pub model: String,
+    #[serde(flatten)]
+    pub pricing: ModelPricing,
+}
+
+/// The currency costs are shown in, as units of it per US dollar.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisplayCurrency {
+    /// ISO 4217 code, e.g. `EUR`.
+    pub code: String,
+    pub per_usd: f64,
+}
+
+impl Default for DisplayCurrency {
+    fn default() -> Self {
+        Self {
+            code: "USD".to_string(),
+            per_usd: 1.0,
+        }
+    }
+}
+
+impl DisplayCurrency {
+    pub fn new(code: &str, per_usd: f64) -> Result<Self, SettingsError> {
+        let code = code.trim().to_ascii_uppercase();
+        if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(SettingsError::Invalid(format!("`{}` is not a three-letter currency code", code)));
+        }
+        if !per_usd.is_finite() || per_usd <= 0.0 {
+            return Err(SettingsError::Invalid("the conversion rate must be above zero".to_string()));
+        }
+        Ok(Self { code, per_usd })
+    }
+
+    pub fn convert(&self, usd: f64) -> f64 {
+        usd * self.per_usd
+    }
+}
+
+/// Prices the user set, and the display currency; kept in settings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PriceTable {
+    pub models: Vec<PriceOverride>,
+    pub currency: DisplayCurrency,
+}
+
+impl PriceTable {
+    /// Price `model` of `preset`, replacing any price set before, or go back
+    /// to the registry's with `None`.
+    pub fn set(
+        &mut self,
+        preset: LlmProviderPreset,
+        model: &str,
+        pricing: Option<ModelPricing>,
+    ) -> Result<(), SettingsError> {
+        let model = model.trim();
+        if model.is_empty() {
+            return Err(SettingsError::Invalid("model must not be empty".to_string()));
+        }
+        let valid = |rate: f64| rate.is_finite() && rate >= 0.0;
+        if pricing.is_some_and(|p| !valid(p.input_per_mtok) || !valid(p.output_per_mtok)) {
+            return Err(SettingsError::Invalid("prices must be zero or more per million tokens".to_string()));
+        }
+        self.models.retain(|o| !(o.preset == preset && o.model == model));
+        let Some(pricing) = pricing else {
+            return Ok(());
+        };
+        self.models.push(PriceOverride {
+            preset,
+            model: model.to_string(),
+            pricing,
+        });
+        Ok(())
+    }
+
+    /// `registry` with these prices: an entry for the same preset and model
+    /// takes the price, other models get an entry of their own with the
+    /// capabilities the registry would have given them.
+    pub fn apply(&self, mut registry: ModelRegistry) -> ModelRegistry {
+        for o in &self.models {
+            let existing = registry
+                .models
+                .iter_mut()
+                .find(|m| m.preset == o.preset && m.model == o.model);
+            match existing {
+                Some(entry) => entry.pricing = Some(o.pricing),
+                None => {
+                    let capabilities = registry.capabilities(o.preset, &o.model);
+                    registry.models.push(ModelEntry {
+                        preset: o.preset,
+                        model: o.model.clone(),
+                        default: false,
+                        capabilities,
+                        pricing: Some(o.pricing),
+                    });
+                }
+            }
+        }
+        registry
+    }
+}
+
+/// A cost as shown to the user.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostView {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    /// In `currency`; `None` when the model has no price.
+    pub amount: Option<f64>,
+    pub currency: String,
+    /// E.g. "0.42 EUR", or "12,000 tokens" without a price.
+    pub label: String,
+}
+
+/// What `usage` costs at `pricing`, in `currency`, or its tokens when
+/// there is no price.
+pub fn describe_cost(usage: &Usage, pricing: Option<&ModelPricing>, currency: &DisplayCurrency) -> CostView {
+    let amount = pricing.map(|p| currency.convert(p.cost_usd(usage)));
+    let label = match amount {
+        Some(amount) if amount > 0.0 && amount < 0.01 => format!("< 0.01 {}", currency.code),
+        Some(amount) => format!("{:.2} {}", amount, currency.code),
+        None => format!("{} tokens", thousands(usage.input_tokens as u64 + usage.output_tokens as u64)),
+    };
+    CostView {
+        input_tokens: usage.input_tokens,
+        output_tokens: usage.output_tokens,
+        amount,
+        currency: currency.code.clone(),
+        label,
+    }
+}
+
+fn thousands(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
@@ -0,0 +1,214 @@
+//! What the app keeps of the user's own texts, and for how long. A
+//! [`RetentionPolicy`] in settings is enforced by [`run_maintenance`] when
+//! the app starts: transcripts older than the policy allows are deleted and
+//! private stories are scrubbed. [`purge`] empties stores on demand.
+//!
+//! A story saved with `"private": true` keeps its translations but never its
+//! source: [`scrub_private_sources`] blanks the story's `sourceText` and the
+//! `source` of every job segment, and is applied on every write of
+//! `stories.json`. Private jobs also skip the translation cache and
+//! transcripts, and can't be retried once saved.
+
+use super::stories;
+use super::transcript::transcripts_dir;
+use super::translation_cache::TranslationCache;
+use super::trash::Trash;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// Ten years; longer is the same as keeping them.
+pub const MAX_RETENTION_DAYS: u32 = 3650;
+
+#[derive(Debug, thiserror::Error)]
+pub enum PrivacyError {
+    #[error("Privacy I/O error: {0}")]
+    Io(String),
+
+    /// Loading or saving the stories or the trash failed.
+    #[error("{0}")]
+    Storage(String),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionPolicy {
+    /// Delete job transcripts this many days after they were last written;
+    /// `None` keeps them until purged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcript_days: Option<u32>,
+}
+
+/// What [`purge`] empties. The audio cache is per profile and lives with
+/// the TTS engine, so the caller clears it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PurgeScope {
+    Transcripts,
+    TranslationCache,
+    AudioCache,
+    All,
+}
+
+impl PurgeScope {
+    pub fn includes(self, other: PurgeScope) -> bool {
+        self == PurgeScope::All || self == other
+    }
+}
+
+/// Files deleted and the bytes they held.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PurgeReport {
+    pub files: u32,
+    pub bytes: u64,
+}
+
+impl std::ops::AddAssign for PurgeReport {
+    fn add_assign(&mut self, other: Self) {
+        self.files += other.files;
+        self.bytes += other.bytes;
+    }
+}
+
+/// What one [`run_maintenance`] pass did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceReport {
+    pub expired_transcripts: PurgeReport,
+    /// Private stories and trashed entries that still held source text.
+    pub scrubbed: u32,
+}
+
+/// Empty the stores `scope` names in a profile's `data_dir` and
+/// `cache_dir`, except the audio cache.
+pub fn purge(scope: PurgeScope, data_dir: &Path, cache_dir: &Path) -> Result<PurgeReport, PrivacyError> {
+    let mut report = PurgeReport::default();
+    if scope.includes(PurgeScope::Transcripts) {
+        report += remove_files(&transcripts_dir(data_dir), |_| true)?;
+    }
+    if scope.includes(PurgeScope::TranslationCache) {
+        report += remove_files(&TranslationCache::dir_in(cache_dir), |_| true)?;
+    }
+    Ok(report)
+}
+
+/// Delete transcripts last written more than `days` days before `now`.
+pub fn expire_transcripts_at(data_dir: &Path, days: u32, now: SystemTime) -> Result<PurgeReport, PrivacyError> {
+    let cutoff = now - Duration::from_secs(u64::from(days) * 24 * 60 * 60);
+    remove_files(&transcripts_dir(data_dir), |meta| meta.modified().is_ok_and(|t| t < cutoff))
+}
+
+/// Enforce `policy` and scrub private stories in a profile's `data_dir`.
+pub fn run_maintenance(data_dir: &Path, policy: &RetentionPolicy) -> Result<MaintenanceReport, PrivacyError> {
+    let mut report = MaintenanceReport::default();
+    if let Some(days) = policy.transcript_days {
+        report.expired_transcripts = expire_transcripts_at(data_dir, days, SystemTime::now())?;
+    }
+
+    let mut all = stories::load(data_dir).map_err(|e| PrivacyError::Storage(e.to_string()))?;
+    let scrubbed = scrub_private_sources(&mut all);
+    if scrubbed > 0 {
+        stories::save(data_dir, &all).map_err(|e| PrivacyError::Storage(e.to_string()))?;
+    }
+
+    let mut trash = Trash::load(data_dir).map_err(|e| PrivacyError::Storage(e.to_string()))?;
+    let private = private_story_ids(&all);
+    let mut trashed = 0;
+    for entry in &mut trash.0 {
+        let changed = match &entry.item.language {
+            None => scrub_story(&mut entry.entry),
+            Some(_) if private.contains(&entry.item.story_id.as_str()) => scrub_translation(&mut entry.entry),
+            Some(_) => false,
+        };
+        trashed += u32::from(changed);
+    }
+    if trashed > 0 {
+        trash.save(data_dir).map_err(|e| PrivacyError::Storage(e.to_string()))?;
+    }
+    report.scrubbed = scrubbed + trashed;
+    Ok(report)
+}
+
+/// Blank the source text of every private story in `stories.json`, returning
+/// how many still had some.
+pub fn scrub_private_sources(stories: &mut Value) -> u32 {
+    let Some(list) = stories.as_array_mut() else {
+        return 0;
+    };
+    list.iter_mut().map(|story| u32::from(scrub_story(story))).sum()
+}
+
+fn is_private(story: &Value) -> bool {
+    story.get("private").and_then(Value::as_bool).unwrap_or(false)
+}
+
+fn private_story_ids(stories: &Value) -> Vec<&str> {
+    stories
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|story| is_private(story))
+        .filter_map(|story| story.get("id").and_then(Value::as_str))
+        .collect()
+}
+
+/// A story entry; untouched unless it is private.
+fn scrub_story(story: &mut Value) -> bool {
+    if !is_private(story) {
+        return false;
+    }
+    let mut changed = blank(story.get_mut("sourceText"));
+    if let Some(translations) = story.get_mut("translations").and_then(Value::as_object_mut) {
+        for translation in translations.values_mut() {
+            changed |= scrub_translation(translation);
+        }
+    }
+    changed
+}
+
+fn scrub_translation(translation: &mut Value) -> bool {
+    let Some(segments) = translation.pointer_mut("/job/segments").and_then(Value::as_array_mut) else {
+        return false;
+    };
+    let mut changed = false;
+    for segment in segments {
+        changed |= blank(segment.get_mut("source"));
+    }
+    changed
+}
+
+fn blank(field: Option<&mut Value>) -> bool {
+    match field {
+        Some(value) if value.as_str().is_some_and(|s| !s.is_empty()) => {
+            *value = Value::String(String::new());
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Delete the files directly in `dir` that `expired` picks; a missing
+/// directory holds nothing.
+fn remove_files(dir: &Path, expired: impl Fn(&fs::Metadata) -> bool) -> Result<PurgeReport, PrivacyError> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(PurgeReport::default()),
+        Err(e) => return Err(PrivacyError::Io(e.to_string())),
+    };
+    let mut report = PurgeReport::default();
+    for entry in entries {
+        let entry = entry.map_err(|e| PrivacyError::Io(e.to_string()))?;
+        let meta = entry.metadata().map_err(|e| PrivacyError::Io(e.to_string()))?;
+        if !meta.is_file() || !expired(&meta) {
+            continue;
+        }
+        fs::remove_file(entry.path()).map_err(|e| PrivacyError::Io(e.to_string()))?;
+        report.files += 1;
+        report.bytes += meta.len();
+    }
+    Ok(report)
+}
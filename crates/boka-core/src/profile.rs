@@ -0,0 +1,293 @@
+//! The whole profile as one archive, for moving to another machine or
+//! keeping before a reinstall: everything in the data dir (stories, settings,
+//! collections, trash, prompts, templates, run reports) plus a snapshot of
+//! what the frontend keeps in local storage. Installed models and exports
+//! are left out; they are downloaded or written again.
+//!
+//! API keys are never written: fields named like one are removed from every
+//! JSON file and from the frontend snapshot. A manifest lists each file with
+//! its SHA-256, and imports are refused when anything does not match.
+//! Without the teacher PIN, an import keeps a locked child-safe mode.
+
+use super::export::{self, zip::ZipWriter};
+use super::profiles::PROFILES_DIR;
+use super::settings::Settings;
+use super::stories::now_ms;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const PROFILE_FORMAT: &str = "boka-profile";
+/// Bumped when the archive layout changes; older apps refuse newer profiles.
+pub const PROFILE_VERSION: u32 = 1;
+
+const MANIFEST: &str = "manifest.json";
+const FRONTEND: &str = "frontend.json";
+const DATA_PREFIX: &str = "data/";
+/// Data dir entries left out of profiles. `profiles` holds the other named
+/// profiles, which are exported on their own.
+const SKIPPED_DIRS: [&str; 3] = ["models", export::EXPORTS_DIR, PROFILES_DIR];
+/// Field names (lowercased, without `_` and `-`) that hold credentials.
+const SECRET_SUFFIXES: [&str; 5] = ["apikey", "accesstoken", "authorization", "password", "secret"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProfileError {
+    #[error("Profile I/O error: {0}")]
+    Io(String),
+
+    #[error("Not a profile archive: {0}")]
+    Archive(String),
+
+    #[error("Profile integrity check failed: {0}")]
+    Integrity(String),
+
+    #[error("Profile version {0} is newer than this app supports ({PROFILE_VERSION}); update the app first")]
+    UnsupportedVersion(u32),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileFile {
+    /// Path inside the archive, `/`-separated.
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileManifest {
+    pub format: String,
+    pub version: u32,
+    pub app_version: String,
+    pub created_at: u64,
+    pub files: Vec<ProfileFile>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileImport {
+    pub manifest: ProfileManifest,
+    /// The profile as it was before the import.
+    pub backup: PathBuf,
+    /// Local storage snapshot for the frontend to restore, without API keys.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frontend: Option<Value>,
+}
+
+/// Remove every field whose name marks it as a credential, at any depth.
+pub fn strip_secrets(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|key, _| !is_secret(key));
+            map.values_mut().for_each(strip_secrets);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_secrets),
+        _ => {}
+    }
+}
+
+fn is_secret(key: &str) -> bool {
+    let key: String = key
+        .chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .collect::<String>()
+        .to_lowercase();
+    SECRET_SUFFIXES.iter().any(|s| key.ends_with(s))
+}
+
+/// Write the profile in `data_dir`, plus the frontend's local storage
+/// snapshot when given, to `path`.
+pub fn export_profile(data_dir: &Path, frontend: Option<&Value>, path: &Path) -> Result<ProfileManifest, ProfileError> {
+    let mut files = Vec::new();
+    collect(data_dir, data_dir, &mut files)?;
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    if let Some(frontend) = frontend {
+        let mut frontend = frontend.clone();
+        strip_secrets(&mut frontend);
+        let bytes = serde_json::to_vec_pretty(&frontend).map_err(|e| ProfileError::Io(e.to_string()))?;
+        files.insert(0, (FRONTEND.to_string(), bytes));
+    }
+
+    let manifest = ProfileManifest {
+        format: PROFILE_FORMAT.to_string(),
+        version: PROFILE_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: now_ms(),
+        files: files
+            .iter()
+            .map(|(name, bytes)| ProfileFile {
+                path: name.clone(),
+                size: bytes.len() as u64,
+                sha256: sha256(bytes),
+            })
+            .collect(),
+    };
+
+    let mut zip = ZipWriter::default();
+    let manifest_json = serde_json::to_vec_pretty(&manifest).map_err(|e| ProfileError::Io(e.to_string()))?;
+    zip.add(MANIFEST, &manifest_json);
+    for (name, bytes) in &files {
+        zip.add(name, bytes);
+    }
+    export::write_atomic(path, &zip.finish()).map_err(|e| ProfileError::Io(e.to_string()))?;
+    Ok(manifest)
+}
+
+/// Data dir files as `(archive path, contents)`, JSON without secrets.
+fn collect(root: &Path, dir: &Path, out: &mut Vec<(String, Vec<u8>)>) -> Result<(), ProfileError> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(ProfileError::Io(e.to_string())),
+    };
+    for entry in entries {
+        let path = entry.map_err(|e| ProfileError::Io(e.to_string()))?.path();
+        let relative: Vec<String> = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        if path.is_dir() {
+            if !(relative.len() == 1 && SKIPPED_DIRS.contains(&relative[0].as_str())) {
+                collect(root, &path, out)?;
+            }
+            continue;
+        }
+        // Half-written files from an interrupted atomic write.
+        if path.extension().is_some_and(|e| e == "tmp") {
+            continue;
+        }
+
+        let mut bytes = fs::read(&path).map_err(|e| ProfileError::Io(e.to_string()))?;
+        if path.extension().is_some_and(|e| e == "json") {
+            if let Ok(mut value) = serde_json::from_slice::<Value>(&bytes) {
+                strip_secrets(&mut value);
+                bytes = serde_json::to_vec_pretty(&value).map_err(|e| ProfileError::Io(e.to_string()))?;
+            }
+        }
+        out.push((format!("{}{}", DATA_PREFIX, relative.join("/")), bytes));
+    }
+    Ok(())
+}
+
+/// Where [`import_profile`] keeps the profile it replaces: the exports
+/// folder, which profiles leave out.
+pub fn backup_path(data_dir: &Path) -> PathBuf {
+    data_dir
+        .join(export::EXPORTS_DIR)
+        .join(format!("profile-backup-{}.zip", now_ms()))
+}
+
+/// Check the archive at `path` against its manifest, back up the current
+/// profile to `backup` and write the archive's files into `data_dir`.
+/// Files the archive does not have are left alone. While child-safe mode
+/// has a PIN, the archive's settings keep the current child-safe block
+/// unless `pin` is that PIN.
+pub fn import_profile(
+    data_dir: &Path,
+    path: &Path,
+    backup: &Path,
+    pin: Option<&str>,
+) -> Result<ProfileImport, ProfileError> {
+    let bytes = fs::read(path).map_err(|e| ProfileError::Io(e.to_string()))?;
+    let mut entries = export::zip::read_entries(&bytes).map_err(ProfileError::Archive)?;
+
+    let manifest_index = entries
+        .iter()
+        .position(|(name, _)| name == MANIFEST)
+        .ok_or_else(|| ProfileError::Archive(format!("no {}", MANIFEST)))?;
+    let (_, manifest_bytes) = entries.remove(manifest_index);
+    let manifest: ProfileManifest =
+        serde_json::from_slice(&manifest_bytes).map_err(|e| ProfileError::Archive(format!("{}: {}", MANIFEST, e)))?;
+    if manifest.format != PROFILE_FORMAT {
+        return Err(ProfileError::Archive(format!("format is `{}`", manifest.format)));
+    }
+    if manifest.version > PROFILE_VERSION {
+        return Err(ProfileError::UnsupportedVersion(manifest.version));
+    }
+
+    if entries.len() != manifest.files.len() {
+        return Err(ProfileError::Integrity(format!(
+            "{} files in the archive, {} in the manifest",
+            entries.len(),
+            manifest.files.len()
+        )));
+    }
+    let mut listed = HashSet::new();
+    if let Some(file) = manifest.files.iter().find(|f| !listed.insert(f.path.as_str())) {
+        return Err(ProfileError::Integrity(format!("`{}` is listed twice", file.path)));
+    }
+    if let Some((name, _)) = entries.iter().find(|(name, _)| name != FRONTEND && data_path(data_dir, name).is_none()) {
+        return Err(ProfileError::Integrity(format!("`{}` is not a data file", name)));
+    }
+    for file in &manifest.files {
+        let (_, data) = entries
+            .iter()
+            .find(|(name, _)| *name == file.path)
+            .ok_or_else(|| ProfileError::Integrity(format!("`{}` is missing", file.path)))?;
+        if data.len() as u64 != file.size || sha256(data) != file.sha256 {
+            return Err(ProfileError::Integrity(format!("`{}` does not match the manifest", file.path)));
+        }
+    }
+
+    let current = Settings::load(data_dir).map_err(|e| ProfileError::Io(e.to_string()))?;
+    let locked = current.check_pin(pin).is_err();
+    export_profile(data_dir, None, backup)?;
+    let mut frontend = None;
+    for (name, data) in entries {
+        if name == FRONTEND {
+            let mut value: Value = serde_json::from_slice(&data).map_err(|e| ProfileError::Archive(e.to_string()))?;
+            strip_secrets(&mut value);
+            frontend = Some(value);
+            continue;
+        }
+        let target = data_path(data_dir, &name)
+            .ok_or_else(|| ProfileError::Integrity(format!("`{}` is not a data file", name)))?;
+        let data = if locked && target == Settings::path(data_dir) {
+            with_child_safe(&data, &current)?
+        } else {
+            data
+        };
+        export::write_atomic(&target, &data).map_err(|e| ProfileError::Io(e.to_string()))?;
+    }
+
+    Ok(ProfileImport {
+        manifest,
+        backup: backup.to_path_buf(),
+        frontend,
+    })
+}
+
+/// The settings file `data` with `current`'s child-safe block in place of
+/// its own.
+fn with_child_safe(data: &[u8], current: &Settings) -> Result<Vec<u8>, ProfileError> {
+    let mut settings: Value = serde_json::from_slice(data).map_err(|e| ProfileError::Archive(e.to_string()))?;
+    let child_safe = serde_json::to_value(&current.child_safe).map_err(|e| ProfileError::Io(e.to_string()))?;
+    settings
+        .as_object_mut()
+        .ok_or_else(|| ProfileError::Archive("settings are not an object".to_string()))?
+        .insert("childSafe".to_string(), child_safe);
+    serde_json::to_vec_pretty(&settings).map_err(|e| ProfileError::Io(e.to_string()))
+}
+
+/// Where archive entry `name` goes in `data_dir`; `None` for anything that
+/// could land outside it or in a skipped directory.
+fn data_path(data_dir: &Path, name: &str) -> Option<PathBuf> {
+    let relative = name.strip_prefix(DATA_PREFIX)?;
+    let parts: Vec<&str> = relative.split('/').collect();
+    let unsafe_part = |p: &&str| p.is_empty() || *p == "." || *p == ".." || p.contains(['\\', ':']);
+    if parts.iter().any(unsafe_part) || SKIPPED_DIRS.contains(&parts[0]) {
+        return None;
+    }
+    Some(parts.iter().fold(data_dir.to_path_buf(), |path, part| path.join(part)))
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
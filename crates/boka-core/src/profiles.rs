@@ -0,0 +1,175 @@
+//! Named profiles, for people sharing one machine. Each profile has its own
+//! stories, settings, collections, trash and caches (see
+//! [`BokaPaths::for_profile`](super::paths::BokaPaths::for_profile));
+//! installed models and the model registries are shared.
+//!
+//! The default profile is the data dir itself, so existing installs and the
+//! TUI keep their data. The others live under `profiles/<id>/`, next to
+//! `profiles/profiles.json`, which lists them and records the active one.
+
+use super::paths;
+use super::stories::now_ms;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_PROFILE: &str = "default";
+pub(crate) const PROFILES_DIR: &str = "profiles";
+const PROFILES_FILE: &str = "profiles.json";
+/// The frontend's local storage, kept in a profile's data dir while another
+/// profile is active.
+const FRONTEND_FILE: &str = "frontend.json";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProfilesError {
+    #[error("Profiles I/O error: {0}")]
+    Io(String),
+
+    #[error("Failed to parse profiles: {0}")]
+    Parse(String),
+
+    #[error("Profile not found: {0}")]
+    NotFound(String),
+
+    #[error("Invalid profile: {0}")]
+    Invalid(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    /// Directory name under `profiles/`; `default` for the top-level one.
+    pub id: String,
+    pub name: String,
+    pub created_at: u64,
+}
+
+/// `profiles/profiles.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profiles {
+    pub active: String,
+    pub profiles: Vec<Profile>,
+}
+
+impl Default for Profiles {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_PROFILE.to_string(),
+            profiles: vec![Profile {
+                id: DEFAULT_PROFILE.to_string(),
+                name: "Default".to_string(),
+                created_at: 0,
+            }],
+        }
+    }
+}
+
+impl Profiles {
+    /// `root` is the top-level data dir, not a profile's.
+    pub fn path(root: &Path) -> PathBuf {
+        root.join(PROFILES_DIR).join(PROFILES_FILE)
+    }
+
+    /// The saved profiles; the default one alone when none were created. An
+    /// active id that no longer exists falls back to the default.
+    pub fn load(root: &Path) -> Result<Self, ProfilesError> {
+        let path = Self::path(root);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(&path).map_err(|e| ProfilesError::Io(e.to_string()))?;
+        let mut profiles: Self = serde_json::from_str(&raw).map_err(|e| ProfilesError::Parse(e.to_string()))?;
+        if !profiles.profiles.iter().any(|p| p.id == DEFAULT_PROFILE) {
+            profiles.profiles.insert(0, Self::default().profiles.remove(0));
+        }
+        if profiles.get(&profiles.active).is_none() {
+            profiles.active = DEFAULT_PROFILE.to_string();
+        }
+        Ok(profiles)
+    }
+
+    /// Atomic write: tmp file, then rename.
+    pub fn save(&self, root: &Path) -> Result<(), ProfilesError> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| ProfilesError::Parse(e.to_string()))?;
+        paths::write_atomic(&Self::path(root), json.as_bytes())
+            .map_err(|e| ProfilesError::Io(e.to_string()))
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.id == id)
+    }
+
+    /// Add a profile named `name` (trimmed, unique ignoring case). Its id is
+    /// a slug of the name.
+    pub fn create(&mut self, name: &str) -> Result<Profile, ProfilesError> {
+        let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+        if name.is_empty() {
+            return Err(ProfilesError::Invalid("name is empty".to_string()));
+        }
+        if self.profiles.iter().any(|p| p.name.to_lowercase() == name.to_lowercase()) {
+            return Err(ProfilesError::Invalid(format!("a profile named `{}` exists", name)));
+        }
+
+        let slug = slug(&name);
+        let mut id = slug.clone();
+        let mut n = 1;
+        while self.get(&id).is_some() {
+            n += 1;
+            id = format!("{}-{}", slug, n);
+        }
+        let profile = Profile {
+            id,
+            name,
+            created_at: now_ms(),
+        };
+        self.profiles.push(profile.clone());
+        Ok(profile)
+    }
+
+    /// Make `id` the active profile. Only the registry changes; the caller
+    /// saves it and moves over to the profile's dirs.
+    pub fn switch(&mut self, id: &str) -> Result<Profile, ProfilesError> {
+        let profile = self.get(id).cloned().ok_or_else(|| ProfilesError::NotFound(id.to_string()))?;
+        self.active = profile.id.clone();
+        Ok(profile)
+    }
+}
+
+/// Lowercase ASCII letters and digits, other runs as `-`; `profile` when
+/// nothing is left.
+fn slug(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() || slug == PROFILES_DIR {
+        "profile".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// Keep the frontend's local storage snapshot in a profile's data dir.
+pub fn save_frontend(data_dir: &Path, snapshot: &Value) -> Result<(), ProfilesError> {
+    let json = serde_json::to_string_pretty(snapshot).map_err(|e| ProfilesError::Parse(e.to_string()))?;
+    paths::write_atomic(&data_dir.join(FRONTEND_FILE), json.as_bytes())
+        .map_err(|e| ProfilesError::Io(e.to_string()))
+}
+
+/// The snapshot [`save_frontend`] kept; `None` for a profile never left.
+pub fn load_frontend(data_dir: &Path) -> Result<Option<Value>, ProfilesError> {
+    let path = data_dir.join(FRONTEND_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let raw = fs::read_to_string(&path).map_err(|e| ProfilesError::Io(e.to_string()))?;
+    serde_json::from_str(&raw).map(Some).map_err(|e| ProfilesError::Parse(e.to_string()))
+}
@@ -0,0 +1,838 @@
+use super::analysis::estimate_tokens;
+use super::anthropic::PlannedVariant;
+use super::gui_types::Granularity;
+use super::policy::ContentPolicy;
+use super::simplify::CefrLevel;
+use super::types::ApiConfig;
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const PROMPTS_FILE: &str = "prompts.json";
+/// Per-language addenda live here as `<language>.md`.
+const ADDENDA_DIR: &str = "prompts";
+const ADDENDUM_EXT: &str = "md";
+
+#[derive(Debug, thiserror::Error)]
+pub enum PromptFileError {
+    #[error("Prompts I/O error: {0}")]
+    Io(String),
+
+    #[error("Failed to parse prompts.json: {0}")]
+    Parse(String),
+
+    #[error("Invalid language code: `{0}`")]
+    InvalidLanguage(String),
+}
+
+/// Appended to structured-output system prompts when the model runs in JSON
+/// mode, which only allows a top-level object.
+pub const JSON_OBJECT_NOTE: &str =
+    "\n\nJSON mode is on: the top-level value must be a JSON object. Return a single block as the object itself, \
+     and wrap a list of variants as {\"variants\": [...]}.";
+
+/// Stands in for the per-segment variant count in the span-variants prompt.
+/// Overrides may use it too; without it they are sent unchanged.
+pub const VARIANT_COUNT_PLACEHOLDER: &str = "{variant_count}";
+
+/// Translation guideline for what [`markup::protect`](super::markup::protect)
+/// puts in a segment.
+const MARKUP_RULE: &str = "Keep placeholders such as ⟦1⟧ exactly as written, and keep <em> and <strong> tags around \
+                           the words they mark.";
+
+/// The system prompts a job runs with, recorded for audits. `span_variants`
+/// is a template; see [`PromptSet::span_variants_for`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptSet {
+    pub base_translation: String,
+    pub span_planning: String,
+    pub span_variants: String,
+    /// Graded-reader base prompt; only set when the job simplifies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub simplified_translation: Option<String>,
+    /// Critique-and-fix prompt for variant lists; only set when the job
+    /// refines variants.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant_critique: Option<String>,
+    /// Sent ahead of every system prompt of the job, these and the others
+    /// (judge, transcription, ...); see [`assemble_system`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preamble: Option<String>,
+}
+
+/// Replacement system prompts, e.g. for one arm of a prompt experiment.
+/// Unset fields keep the built-in prompt.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_translation: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span_planning: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span_variants: Option<String>,
+    /// Extra instructions for the target language (politeness levels,
+    /// capitalization rules, ...), appended to every translation prompt,
+    /// overridden or not.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language_addendum: Option<String>,
+    /// Replaces the always-on preamble from settings for this job; blank
+    /// text sends none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_preamble: Option<String>,
+}
+
+impl PromptOverrides {
+    /// `<data_dir>/prompts.json`, the user's standing overrides for every job.
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join(PROMPTS_FILE)
+    }
+
+    /// The user's overrides; none when the file does not exist.
+    pub fn load(data_dir: &Path) -> Result<Self, PromptFileError> {
+        let path = Self::path(data_dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(&path).map_err(|e| PromptFileError::Io(e.to_string()))?;
+        serde_json::from_str(&raw).map_err(|e| PromptFileError::Parse(e.to_string()))
+    }
+}
+
+/// `<data_dir>/prompts`.
+pub fn addenda_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(ADDENDA_DIR)
+}
+
+/// Lowercased `language`, refused when it could not be a file name.
+fn addendum_language(language: &str) -> Result<String, PromptFileError> {
+    let code = language.trim().to_ascii_lowercase();
+    let valid = !code.is_empty() && code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(PromptFileError::InvalidLanguage(language.to_string()));
+    }
+    Ok(code)
+}
+
+/// Where the addendum for `language` is stored.
+pub fn addendum_path(data_dir: &Path, language: &str) -> Result<PathBuf, PromptFileError> {
+    let code = addendum_language(language)?;
+    Ok(addenda_dir(data_dir).join(format!("{}.{}", code, ADDENDUM_EXT)))
+}
+
+/// The addendum saved for `language`, falling back to its base language
+/// (`pt-br` uses `pt.md` when there is no `pt-br.md`).
+pub fn load_addendum(data_dir: &Path, language: &str) -> Result<Option<String>, PromptFileError> {
+    let code = addendum_language(language)?;
+    let base = code.split(['-', '_']).next().unwrap_or(&code).to_string();
+    for candidate in [code, base] {
+        let path = addendum_path(data_dir, &candidate)?;
+        if path.is_file() {
+            let text = fs::read_to_string(&path).map_err(|e| PromptFileError::Io(e.to_string()))?;
+            return Ok(Some(text));
+        }
+    }
+    Ok(None)
+}
+
+/// Save the addendum for `language`; `None` or blank text deletes it.
+pub fn save_addendum(data_dir: &Path, language: &str, text: Option<&str>) -> Result<(), PromptFileError> {
+    let path = addendum_path(data_dir, language)?;
+    match text.map(str::trim).filter(|t| !t.is_empty()) {
+        Some(text) => {
+            fs::create_dir_all(addenda_dir(data_dir)).map_err(|e| PromptFileError::Io(e.to_string()))?;
+            fs::write(&path, format!("{}\n", text)).map_err(|e| PromptFileError::Io(e.to_string()))
+        }
+        None if path.exists() => fs::remove_file(&path).map_err(|e| PromptFileError::Io(e.to_string())),
+        None => Ok(()),
+    }
+}
+
+/// Languages with a saved addendum, sorted.
+pub fn list_addenda(data_dir: &Path) -> Vec<String> {
+    let mut languages: Vec<String> = fs::read_dir(addenda_dir(data_dir))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension()?.to_str()? != ADDENDUM_EXT {
+                return None;
+            }
+            let code = path.file_stem()?.to_str()?;
+            addendum_language(code).ok().filter(|c| c == code)
+        })
+        .collect();
+    languages.sort();
+    languages
+}
+
+/// What the translation prompts say about segment size; nothing for sentences.
+fn translation_granularity_note(granularity: Granularity) -> &'static str {
+    match granularity {
+        Granularity::Clause => {
+            "\n\nSegments are single clauses cut from longer sentences. Translate the clause so it joins up with \
+             the clauses around it in the story, and keep its trailing punctuation."
+        }
+        Granularity::Sentence => "",
+        Granularity::Paragraph => {
+            "\n\nSegments are whole paragraphs. Translate every sentence of the paragraph and keep its line breaks."
+        }
+    }
+}
+
+/// What the span-planning prompts say about segment size.
+fn planning_granularity_note(granularity: Granularity) -> &'static str {
+    match granularity {
+        Granularity::Clause => "\n\nThe segment is a single clause; keep to the lower end of the span count.",
+        Granularity::Sentence => "",
+        Granularity::Paragraph => {
+            "\n\nThe segment is a whole paragraph: aim for that many spans per sentence and spread them over the \
+             paragraph."
+        }
+    }
+}
+
+/// The language addendum as appended to a prompt, or "" without one.
+fn language_notes(overrides: &PromptOverrides) -> String {
+    overrides
+        .language_addendum
+        .as_deref()
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .map(|a| format!("\n\nLanguage notes:\n{}", a))
+        .unwrap_or_default()
+}
+
+impl PromptSet {
+    /// The prompts a client built from `cfg` sends, overrides included.
+    pub fn for_config(cfg: &ApiConfig, json_mode: bool) -> Self {
+        let source = cfg.source_language.as_deref();
+        let ui = cfg.ui_language.as_deref();
+        let json_note = if json_mode { JSON_OBJECT_NOTE } else { "" };
+        let overrides = &cfg.prompt_overrides;
+        let addendum = language_notes(overrides);
+        let translation_note = translation_granularity_note(cfg.granularity);
+        Self {
+            base_translation: overrides.base_translation.clone().unwrap_or_else(|| {
+                base_translation_system_prompt(&cfg.target_language, source, &cfg.content_policy) + translation_note
+            }) + &addendum,
+            span_planning: overrides.span_planning.clone().unwrap_or_else(|| {
+                span_planning_system_prompt(&cfg.target_language, source, cfg.dense_spans)
+                    + planning_granularity_note(cfg.granularity)
+            }) + &addendum
+                + json_note,
+            span_variants: overrides
+                .span_variants
+                .clone()
+                .unwrap_or_else(|| span_variants_template(&cfg.target_language, source, &cfg.content_policy, ui))
+                + &addendum
+                + json_note,
+            simplified_translation: cfg.simplify_level.map(|level| {
+                simplified_translation_system_prompt(&cfg.target_language, source, &cfg.content_policy, level)
+                    + translation_note
+                    + &addendum
+            }),
+            variant_critique: cfg.refine_variants.then(|| {
+                variant_critique_system_prompt(&cfg.target_language, source, &cfg.content_policy, ui)
+                    + &addendum
+                    + json_note
+            }),
+            preamble: overrides
+                .system_preamble
+                .as_deref()
+                .map(str::trim)
+                .filter(|p| !p.is_empty())
+                .map(str::to_string),
+        }
+    }
+
+    /// Span-variants prompt asking for `variant_count` variants.
+    pub fn span_variants_for(&self, variant_count: u32) -> String {
+        self.span_variants
+            .replace(VARIANT_COUNT_PLACEHOLDER, &variant_count.to_string())
+    }
+}
+
+/// A system prompt as it goes to the provider: the preamble, when there is
+/// one, ahead of the prompt for the call. Clients send every system prompt
+/// through here rather than joining the two themselves.
+pub fn assemble_system(preamble: Option<&str>, prompt: &str) -> String {
+    match preamble {
+        Some(preamble) => format!("{}\n\n{}", preamble, prompt),
+        None => prompt.to_string(),
+    }
+}
+
+/// Codes [`language_name`] knows, without their aliases.
+pub const KNOWN_LANGUAGES: [&str; 34] = [
+    "en", "en-gb", "fr", "es", "de", "it", "pt", "ja", "ko", "zh", "nl", "sv", "ru", "ar", "hi", "tr", "pl", "th", "vi",
+    "id", "ms", "uk", "cs", "ro", "el", "he", "da", "fi", "no", "hu", "mn", "ka", "sw", "tl",
+];
+
+pub fn language_name(code: &str) -> &str {
+    match code {
+        "en" => "English",
+        "fr" => "French",
+        "es" => "Spanish",
+        "de" => "German",
+        "it" => "Italian",
+        "pt" => "Portuguese",
+        "ja" | "jp" => "Japanese",
+        "ko" => "Korean",
+        "zh" | "cn" => "Mandarin Chinese",
+        "nl" => "Dutch",
+        "sv" => "Swedish",
+        "ru" => "Russian",
+        "ar" => "Arabic",
+        "hi" => "Hindi",
+        "tr" => "Turkish",
+        "pl" => "Polish",
+        "en-gb" => "British English",
+        "th" => "Thai",
+        "vi" => "Vietnamese",
+        "id" => "Indonesian",
+        "ms" => "Malay",
+        "uk" => "Ukrainian",
+        "cs" => "Czech",
+        "ro" => "Romanian",
+        "el" => "Greek",
+        "he" | "iw" => "Hebrew",
+        "da" => "Danish",
+        "fi" => "Finnish",
+        "no" | "nb" => "Norwegian",
+        "hu" => "Hungarian",
+        "mn" => "Mongolian",
+        "ka" => "Georgian",
+        "sw" => "Swahili",
+        "tl" => "Tagalog",
+        // If someone types the full language name, pass it through
+        _ => code,
+    }
+}
+
+pub fn base_translation_system_prompt(target_language: &str, source_language: Option<&str>, policy: &ContentPolicy) -> String {
+    let lang_name = language_name(target_language);
+    let register_note = policy.tone_note();
+
+    let source_note = match source_language {
+        Some(src) => format!("The source text is written in {}. ", language_name(src)),
+        None => String::new(),
+    };
+
+    format!(
+        r#"You are a {lang_name} translation expert. {source_note}Translate the requested segment into {lang_name}.
+
+Guidelines:
+- Translate naturally, not literally.
+- Preserve meaning, tone, and speaker intent.
+- Keep punctuation and sentence boundaries natural.
+- {markup_rule}
+- Return ONLY the translated text for the segment. No quotes, no markdown, no commentary.
+
+Tone note:
+{register_note}"#,
+        lang_name = lang_name,
+        source_note = source_note,
+        register_note = register_note,
+        markup_rule = MARKUP_RULE,
+    )
+}
+
+/// Graded-reader alternative to [`base_translation_system_prompt`].
+pub fn simplified_translation_system_prompt(
+    target_language: &str,
+    source_language: Option<&str>,
+    policy: &ContentPolicy,
+    level: CefrLevel,
+) -> String {
+    let lang_name = language_name(target_language);
+    let register_note = policy.tone_note();
+    let level_name = level.as_str();
+
+    let source_note = match source_language {
+        Some(src) => format!("The source text is written in {}. ", language_name(src)),
+        None => String::new(),
+    };
+    let sentence_rule = match level.max_sentence_words() {
+        Some(max) => format!("- Keep every sentence under {} words; split longer ones.", max),
+        None => "- Split only sentences that are hard to follow.".to_string(),
+    };
+
+    format!(
+        r#"You are writing a {lang_name} graded reader for {level_name} (CEFR) learners. {source_note}Retell the requested segment in {lang_name} at {level_name} level.
+
+Guidelines:
+- {guidance}
+{sentence_rule}
+- Keep the events, speakers and meaning; drop only detail a {level_name} reader cannot follow.
+- {markup_rule}
+- Return ONLY the rewritten text for the segment. No quotes, no markdown, no commentary.
+
+Tone note:
+{register_note}"#,
+        lang_name = lang_name,
+        level_name = level_name,
+        source_note = source_note,
+        guidance = level.guidance(),
+        sentence_rule = sentence_rule,
+        register_note = register_note,
+        markup_rule = MARKUP_RULE,
+    )
+}
+
+/// User message for a simplified translation call; `note` carries revision
+/// instructions when a previous attempt failed the vocabulary check.
+pub fn simplified_translation_user_content(
+    full_story: &str,
+    segment: &str,
+    context_budget: u32,
+    note: Option<&str>,
+) -> String {
+    with_revision_note(base_translation_user_content(full_story, segment, context_budget), note)
+}
+
+/// `content` with `note` appended, for a call made again after its output
+/// fell short of a check.
+pub fn with_revision_note(content: String, note: Option<&str>) -> String {
+    match note {
+        Some(note) => format!("{}\n\nREVISION NOTE:\n{}", content, note),
+        None => content,
+    }
+}
+
+/// User message for a base translation call. When the full story would not
+/// fit in `context_budget` tokens, only a window around the segment is sent.
+pub fn base_translation_user_content(full_story: &str, segment: &str, context_budget: u32) -> String {
+    let story_tokens = estimate_tokens(full_story);
+    let context = if story_tokens <= context_budget {
+        full_story.to_string()
+    } else {
+        let chars: Vec<char> = full_story.chars().collect();
+        let keep = (chars.len() as u64 * context_budget as u64 / story_tokens as u64) as usize;
+        let center = full_story
+            .find(segment)
+            .map(|byte| full_story[..byte].chars().count() + segment.chars().count() / 2)
+            .unwrap_or(0);
+        let start = center.saturating_sub(keep / 2).min(chars.len().saturating_sub(keep));
+        let window: String = chars[start..(start + keep).min(chars.len())].iter().collect();
+        format!("…{}…", window.trim())
+    };
+
+    format!("FULL STORY (context):\n{}\n\nSEGMENT TO TRANSLATE:\n{}", context, segment)
+}
+
+pub fn span_planning_system_prompt(target_language: &str, _source_language: Option<&str>, dense_spans: bool) -> String {
+    let lang_name = language_name(target_language);
+    let span_density_instruction = if dense_spans {
+        "Aim for 3-5 swappable spans."
+    } else {
+        "Aim for 1-2 swappable spans."
+    };
+
+    format!(
+        r#"You are a {lang_name} language expert creating interactive learning materials.
+
+You will receive a single translated segment in {lang_name}. Your job is to turn it into an interactive block with static text + swappable spans.
+
+Output format:
+Return a JSON array with EXACTLY one block in this schema:
+
+[
+  {{
+    \"id\": \"b1\",
+    \"segments\": [
+      {{ \"type\": \"static\", \"text\": \"...\" }},
+      {{ \"type\": \"swappable\", \"id\": \"s1\", \"variants\": [
+        {{ \"text\": \"...\", \"register\": \"neutral\", \"note\": \"\", \"difficulty\": 2 }}
+      ]}}
+    ]
+  }}
+]
+
+Rules:
+- The block must preserve the meaning of the segment.
+- Each swappable span MUST include a neutral variant that matches the exact text from the segment.
+- Copy emoji and *emphasis* markers along with the words they belong to.
+- Variants arrays should contain ONLY the neutral variant for now (register: \"neutral\").
+- {span_density_instruction}
+
+Return ONLY the JSON array. No markdown."#,
+        lang_name = lang_name,
+        span_density_instruction = span_density_instruction,
+    )
+}
+
+/// Variants of an anchor phrase, with learner notes written in
+/// `ui_language` (English when `None`).
+pub fn span_variants_system_prompt(
+    target_language: &str,
+    source_language: Option<&str>,
+    policy: &ContentPolicy,
+    ui_language: Option<&str>,
+    variant_count: u32,
+) -> String {
+    span_variants_template(target_language, source_language, policy, ui_language)
+        .replace(VARIANT_COUNT_PLACEHOLDER, &variant_count.to_string())
+}
+
+/// Name of the language learner notes are written in.
+fn note_language_name(ui_language: Option<&str>) -> &str {
+    language_name(ui_language.map(str::trim).filter(|l| !l.is_empty()).unwrap_or("en"))
+}
+
+fn span_variants_template(
+    target_language: &str,
+    _source_language: Option<&str>,
+    policy: &ContentPolicy,
+    ui_language: Option<&str>,
+) -> String {
+    let lang_name = language_name(target_language);
+    let note_lang = note_language_name(ui_language);
+    let register_instruction = policy.register_instruction();
+
+    format!(
+        r#"You are a {lang_name} language expert. You will be given a segment context and an anchor phrase within it.
+
+Return a JSON array of variants. Each item:
+{{ \"text\": \"...\", \"register\": \"neutral|formal|literary|casual|colloquial|vulgar\", \"note\": \"{note_lang} learner note\", \"difficulty\": 1-5 }}
+
+Rules:
+- The FIRST variant MUST be the most natural neutral phrasing.
+- Keep meaning consistent with the segment context.
+- Return {{variant_count}} variants total; fewer only if the phrase has no other natural wording.
+
+Register guidance:
+{register_instruction}
+
+Return ONLY the JSON array. No markdown."#,
+        lang_name = lang_name,
+        note_lang = note_lang,
+        register_instruction = register_instruction,
+    )
+}
+
+/// Second pass over a generated variant list: the model reviews its own
+/// output and returns the corrected list.
+pub fn variant_critique_system_prompt(
+    target_language: &str,
+    _source_language: Option<&str>,
+    policy: &ContentPolicy,
+    ui_language: Option<&str>,
+) -> String {
+    let lang_name = language_name(target_language);
+    let note_lang = note_language_name(ui_language);
+    let register_instruction = policy.register_instruction();
+
+    format!(
+        r#"You are a strict {lang_name} language editor. You will be given a segment context, an anchor phrase within it, and a JSON array of variants for that phrase.
+
+Review the variants:
+- Remove variants a native speaker would find unnatural, ungrammatical or wrong for the context.
+- Fix register labels. Slang is colloquial, not casual; swear words and crude terms are vulgar.
+- Fix notes and difficulty ratings that are wrong.
+- Do not add new variants.
+
+Each item:
+{{ "text": "...", "register": "neutral|formal|literary|casual|colloquial|vulgar", "note": "{note_lang} learner note", "difficulty": 1-5 }}
+
+Keep the FIRST variant as the neutral phrasing of the anchor phrase.
+
+Register guidance:
+{register_instruction}
+
+Return ONLY the corrected JSON array. No markdown."#,
+        lang_name = lang_name,
+        note_lang = note_lang,
+        register_instruction = register_instruction,
+    )
+}
+
+pub fn variant_critique_user_content(segment_context: &str, anchor_phrase: &str, variants: &[PlannedVariant]) -> String {
+    let list: Vec<serde_json::Value> = variants
+        .iter()
+        .map(|v| {
+            serde_json::json!({
+                "text": v.text,
+                "register": v.register,
+                "note": v.note,
+                "difficulty": v.difficulty,
+            })
+        })
+        .collect();
+    format!(
+        "SEGMENT CONTEXT:\n{}\n\nANCHOR PHRASE:\n{}\n\nVARIANTS:\n{}",
+        segment_context,
+        anchor_phrase,
+        serde_json::Value::Array(list)
+    )
+}
+
+/// Span planning in the line-based fallback format (see `simple_format`).
+/// Prompt overrides don't apply: they ask for JSON.
+pub fn simple_span_planning_system_prompt(cfg: &ApiConfig) -> String {
+    let lang_name = language_name(&cfg.target_language);
+    let count = if cfg.dense_spans { "3 to 5" } else { "1 or 2" };
+
+    format!(
+        r#"You are a {lang_name} language expert creating interactive learning materials.
+
+You will receive a single translated segment in {lang_name}. Pick {count} short phrases from it that a learner could see reworded.
+
+Write each phrase on its own line, copied exactly from the segment, in the order they appear. Write nothing else."#
+    ) + planning_granularity_note(cfg.granularity)
+        + &language_notes(&cfg.prompt_overrides)
+}
+
+/// Span variants in the line-based fallback format (see `simple_format`).
+pub fn simple_span_variants_system_prompt(cfg: &ApiConfig, variant_count: u32) -> String {
+    let lang_name = language_name(&cfg.target_language);
+    let note_lang = note_language_name(cfg.ui_language.as_deref());
+    let register_instruction = cfg.content_policy.register_instruction();
+
+    format!(
+        r#"You are a {lang_name} language expert. You will be given a segment context and an anchor phrase within it.
+
+Write {variant_count} ways to say the anchor phrase, one per line, as:
+text | register | {note_lang} learner note | difficulty 1-5
+
+Register is one of neutral, formal, literary, casual, colloquial, vulgar.
+
+Rules:
+- The FIRST line MUST be the most natural neutral phrasing.
+- Keep meaning consistent with the segment context.
+- Fewer lines only if the phrase has no other natural wording.
+
+Register guidance:
+{register_instruction}
+
+Write only the lines."#
+    ) + &language_notes(&cfg.prompt_overrides)
+}
+
+/// User text sent next to each page image.
+pub const TRANSCRIPTION_REQUEST: &str = "Transcribe the text on this page.";
+
+pub fn transcription_system_prompt() -> String {
+    r#"You transcribe photographed or scanned book pages.
+
+Rules:
+- Copy the running text exactly as printed, in its original language. Do not translate, summarise or correct it.
+- Keep line breaks and end-of-line hyphens as printed; they are cleaned up afterwards.
+- Separate paragraphs with a blank line.
+- Leave out page numbers, running headers and footers, and captions.
+- If the page has no readable text, return nothing.
+
+Return ONLY the transcribed text. No markdown, no commentary."#
+        .to_string()
+}
+
+pub fn story_description_system_prompt(target_language: &str, source_language: Option<&str>) -> String {
+    let lang_name = language_name(target_language);
+    let source_name = source_language.map(language_name).unwrap_or("the source language");
+
+    format!(
+        r#"You write library shelf entries for stories translated from {source_name} into {lang_name}.
+
+Return a JSON object:
+{{
+  "title": "a short title in {lang_name}",
+  "synopsisSource": "one sentence in {source_name}",
+  "synopsisTarget": "the same sentence in {lang_name}",
+  "emoji": "one emoji for the cover"
+}}
+
+Rules:
+- The title has at most six words and gives nothing away about the ending.
+- The synopsis is ONE sentence a learner can read at a glance.
+
+Return ONLY the JSON object. No markdown."#,
+        lang_name = lang_name,
+        source_name = source_name,
+    )
+}
+
+pub fn story_description_user_content(source: &str, translation: &str) -> String {
+    format!("SOURCE:\n{}\n\nTRANSLATION:\n{}", source, translation)
+}
+
+pub fn comprehension_check_system_prompt(target_language: &str, source_language: Option<&str>) -> String {
+    let lang_name = language_name(target_language);
+    let source_name = source_language.map(language_name).unwrap_or("the source language");
+
+    format!(
+        r#"You write comprehension checks for learners reading a story in {lang_name}. You will be given a passage of the {source_name} original.
+
+Write ONE true/false statement about the passage, in simple {lang_name}.
+
+Return a JSON object:
+{{ "question": "the statement in {lang_name}", "answer": true or false, "explanation": "one short sentence in {source_name}" }}
+
+Rules:
+- The statement is about what happens or is said in THIS passage, not about the rest of the story.
+- A false statement changes one detail of the passage; it is never absurd.
+- Use words a learner has just read, no harder than the passage.
+
+Return ONLY the JSON object. No markdown."#,
+        lang_name = lang_name,
+        source_name = source_name,
+    )
+}
+
+/// Co-writing: the next part of a story, in the story's own language.
+pub fn continuation_system_prompt(source_language: Option<&str>, words: u32, policy: &ContentPolicy) -> String {
+    let language = match source_language {
+        Some(code) => language_name(code).to_string(),
+        None => "the language the story is written in".to_string(),
+    };
+
+    format!(
+        r#"You are co-writing a story with a language learner. You will be given the story so far, and maybe a direction the learner wants it to take.
+
+Write the next part of the story, about {words} words, in {language}.
+
+Rules:
+- Continue from where the story stops; do not repeat or summarize it.
+- Keep the characters, tense, point of view, style and reading level of the story so far.
+- Follow the learner's direction when there is one, without mentioning it.
+- Use paragraphs separated by blank lines, like the story.
+- Return ONLY the new text. No title, no quotes, no markdown, no commentary.
+
+Tone note:
+{tone_note}"#,
+        words = words,
+        language = language,
+        tone_note = policy.tone_note(),
+    )
+}
+
+pub fn continuation_user_content(story: &str, direction_hint: Option<&str>) -> String {
+    match direction_hint.map(str::trim).filter(|h| !h.is_empty()) {
+        Some(hint) => format!("STORY SO FAR:\n{}\n\nDIRECTION:\n{}", story, hint),
+        None => format!("STORY SO FAR:\n{}", story),
+    }
+}
+
+/// Tutor mode: a learner's question about one paragraph of a doc.
+pub fn speaker_attribution_system_prompt(target_language: &str, max_speakers: usize) -> String {
+    let lang_name = language_name(target_language);
+
+    format!(
+        r#"You cast audiobooks of stories in {lang_name}. You will be given the paragraphs of a story that have dialogue, then its dialogue lines, each with an id like "3.0" (paragraph 3, first line).
+
+Say who speaks each line. Return a JSON object:
+{{ "lines": [{{ "line": "3.0", "speaker": "the character's name" }}] }}
+
+Rules:
+- Use at most {max_speakers} different speakers; name them as the story does, or by a short description ("the old man") when it never names them.
+- Use the same name every time for the same character.
+- Give "speaker": null for a line that is not spoken aloud (a sign, a thought, a quoted word) or whose speaker you cannot tell.
+- Return ONLY the JSON object. No markdown."#,
+        lang_name = lang_name,
+        max_speakers = max_speakers,
+    )
+}
+
+pub fn speaker_attribution_user_content(blocks: &[(u32, String)], lines: &[(String, String)]) -> String {
+    let mut content = String::new();
+    for (block, text) in blocks {
+        content.push_str(&format!("PARAGRAPH {}:\n{}\n\n", block, text));
+    }
+    content.push_str("LINES:");
+    for (id, text) in lines {
+        content.push_str(&format!("\n{}: {}", id, text));
+    }
+    content
+}
+
+/// `policy`, when set, adds its tone note, e.g. for child-safe mode.
+pub fn tutor_system_prompt(
+    target_language: &str,
+    source_language: Option<&str>,
+    ui_language: Option<&str>,
+    policy: Option<&ContentPolicy>,
+) -> String {
+    let lang_name = language_name(target_language);
+    let source_name = source_language.map(language_name).unwrap_or("the source language");
+    let answer_lang = note_language_name(ui_language);
+    let tone_note = policy.map(|p| format!("\n\nTone note:\n{}", p.tone_note())).unwrap_or_default();
+
+    format!(
+        r#"You are a patient {lang_name} tutor. A learner is reading a {lang_name} translation of a {source_name} text and asks about one paragraph of it. You will be given the original paragraph when it is available, its translation, maybe earlier questions about it, and the question.
+
+Rules:
+- Answer in {answer_lang}, in at most a few short sentences; quote {lang_name} words as they appear.
+- Answer the question asked: meaning, grammar, word choice, usage or culture.
+- When the question is about a choice in the translation, explain it with the original; say so plainly if the translation is wrong.
+- If the question is not about the paragraph or the language, say briefly that you can only help with the text.
+- Return ONLY the answer. No greeting, no headings, no markdown tables.{tone_note}"#,
+        lang_name = lang_name,
+        source_name = source_name,
+        answer_lang = answer_lang,
+        tone_note = tone_note,
+    )
+}
+
+pub fn tutor_user_content(source: &str, translation: &str, earlier: &[(&str, &str)], question: &str) -> String {
+    let mut content = String::new();
+    if !source.trim().is_empty() {
+        content.push_str(&format!("ORIGINAL:\n{}\n\n", source));
+    }
+    content.push_str(&format!("TRANSLATION:\n{}\n\n", translation));
+    for (q, a) in earlier {
+        content.push_str(&format!("EARLIER QUESTION:\n{}\nEARLIER ANSWER:\n{}\n\n", q, a));
+    }
+    content.push_str(&format!("QUESTION:\n{}", question));
+    content
+}
+
+/// Localize learner notes written for a doc in `target_language`.
+pub fn note_translation_system_prompt(target_language: &str, ui_language: &str) -> String {
+    let lang_name = language_name(target_language);
+    let note_lang = note_language_name(Some(ui_language));
+
+    format!(
+        r#"You translate short learner notes about {lang_name} phrases into {note_lang}.
+
+You will be given a JSON object {{ "notes": [...] }}. Return the same object with every note translated into {note_lang}, in the same order, one item per note.
+
+Rules:
+- Keep {lang_name} words and phrases quoted in a note exactly as they are.
+- Keep each note as short as the original.
+- Return a note that is already in {note_lang} unchanged.
+
+Return ONLY the JSON object. No markdown."#,
+        lang_name = lang_name,
+        note_lang = note_lang,
+    )
+}
+
+pub fn note_translation_user_content(notes: &[String]) -> String {
+    serde_json::json!({ "notes": notes }).to_string()
+}
+
+pub fn judge_system_prompt(target_language: &str, source_language: Option<&str>) -> String {
+    let lang_name = language_name(target_language);
+    let source_name = source_language.map(language_name).unwrap_or("the source language");
+
+    format!(
+        r#"You are a strict reviewer of {source_name} to {lang_name} translations.
+
+Score the translation on two axes, each an integer from 1 (unusable) to 5 (flawless):
+- adequacy: does it convey the full meaning of the source, with nothing added or lost?
+- fluency: does it read as natural {lang_name} a native speaker would write?
+
+Return a JSON object:
+{{ \"adequacy\": 1-5, \"fluency\": 1-5, \"rationale\": \"one short sentence in English\" }}
+
+Return ONLY the JSON object. No markdown."#,
+        lang_name = lang_name,
+        source_name = source_name,
+    )
+}
+
+pub fn judge_user_content(source: &str, translation: &str) -> String {
+    format!("SOURCE:\n{}\n\nTRANSLATION:\n{}", source, translation)
+}
@@ -0,0 +1,200 @@
+//! Sorting a failed provider test into something the settings screen can
+//! act on. Providers report the same problems in different shapes:
+//!
+//! - Anthropic: `{"type":"error","error":{"type":"authentication_error","message":"..."}}`
+//! - OpenAI and most compatible servers: `{"error":{"code":"invalid_api_key","message":"..."}}`
+//! - Ollama: `{"error":"model \"x\" not found, try pulling it first"}`
+//!
+//! The provider's own error type wins; the HTTP status is the fallback.
+//!
+//! Settings screens test often, so results are kept for [`PROBE_TTL`] per
+//! provider config in a [`ProbeCache`].
+
+use super::stories::now_ms;
+use super::text;
+use super::types::{ApiError, LlmProviderConfig};
+
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// How long a probe result is reused.
+pub const PROBE_TTL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProviderFailure {
+    /// No key, or the provider rejected it.
+    InvalidKey,
+    /// The model name is unknown to the provider (or not pulled, for local servers).
+    ModelNotFound,
+    /// Out of credit or over the account's quota.
+    BillingExhausted,
+    RateLimited,
+    /// Nothing answered: wrong base URL, server not running, no network.
+    Unreachable,
+    Other,
+}
+
+/// Error of `boka_test_provider`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderTestError {
+    pub failure: ProviderFailure,
+    /// HTTP status, when the provider answered.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// The provider's own message when it sent one, else the error text.
+    pub message: String,
+    /// When the probe ran, in ms since the epoch.
+    pub checked_at: u64,
+    /// Reused from an earlier probe.
+    pub cached: bool,
+}
+
+/// Result of a probe that got an answer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderProbe {
+    /// `key: value` lines: provider, endpoint, model, auth, latency.
+    pub details: String,
+    pub checked_at: u64,
+    pub cached: bool,
+}
+
+impl ProviderProbe {
+    pub fn new(details: String) -> Self {
+        Self {
+            details,
+            checked_at: now_ms(),
+            cached: false,
+        }
+    }
+}
+
+pub type ProbeResult = Result<ProviderProbe, ProviderTestError>;
+
+/// Probe results by provider config, each kept for the cache's TTL.
+pub struct ProbeCache {
+    ttl: Duration,
+    entries: HashMap<String, (Instant, ProbeResult)>,
+}
+
+impl Default for ProbeCache {
+    fn default() -> Self {
+        Self::new(PROBE_TTL)
+    }
+}
+
+impl ProbeCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Identifies a config without keeping its API key around in clear.
+    pub fn key(provider: &LlmProviderConfig) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(serde_json::to_vec(provider).unwrap_or_default());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// A result for `provider` that is still fresh, marked as cached.
+    pub fn get(&mut self, provider: &LlmProviderConfig) -> Option<ProbeResult> {
+        let ttl = self.ttl;
+        self.entries.retain(|_, (at, _)| at.elapsed() < ttl);
+        let (_, result) = self.entries.get(&Self::key(provider))?;
+        let mut result = result.clone();
+        match &mut result {
+            Ok(probe) => probe.cached = true,
+            Err(error) => error.cached = true,
+        }
+        Some(result)
+    }
+
+    pub fn put(&mut self, provider: &LlmProviderConfig, result: &ProbeResult) {
+        self.entries.insert(Self::key(provider), (Instant::now(), result.clone()));
+    }
+}
+
+impl From<ApiError> for ProviderTestError {
+    fn from(err: ApiError) -> Self {
+        let (failure, status, message) = match &err {
+            ApiError::NoApiKey { .. } => (ProviderFailure::InvalidKey, None, err.to_string()),
+            ApiError::Http(_) => (ProviderFailure::Unreachable, None, err.to_string()),
+            ApiError::ApiResponse { status, message } => {
+                let body = serde_json::from_str::<Value>(message).ok();
+                let failure = body
+                    .as_ref()
+                    .and_then(failure_from_body)
+                    .unwrap_or_else(|| failure_from_status(*status));
+                let message = body
+                    .as_ref()
+                    .and_then(body_message)
+                    .unwrap_or_else(|| message.trim().to_string());
+                (failure, Some(*status), message)
+            }
+            _ => (ProviderFailure::Other, None, err.to_string()),
+        };
+        Self {
+            failure,
+            status,
+            message: text::clip(&message),
+            checked_at: now_ms(),
+            cached: false,
+        }
+    }
+}
+
+/// `error.type` (Anthropic) or `error.code` (OpenAI), then the message for
+/// the cases providers only spell out in prose.
+fn failure_from_body(body: &Value) -> Option<ProviderFailure> {
+    let error = body.get("error")?;
+    let kind = error
+        .get("code")
+        .and_then(Value::as_str)
+        .or_else(|| error.get("type").and_then(Value::as_str))
+        .unwrap_or_default();
+    let by_kind = match kind {
+        "authentication_error" | "permission_error" | "invalid_api_key" => Some(ProviderFailure::InvalidKey),
+        "not_found_error" | "model_not_found" => Some(ProviderFailure::ModelNotFound),
+        "billing_error" | "insufficient_quota" => Some(ProviderFailure::BillingExhausted),
+        "rate_limit_error" | "rate_limit_exceeded" => Some(ProviderFailure::RateLimited),
+        _ => None,
+    };
+    by_kind.or_else(|| {
+        let message = body_message(body)?.to_lowercase();
+        if message.contains("credit balance") || message.contains("quota") || message.contains("billing") {
+            Some(ProviderFailure::BillingExhausted)
+        } else if message.contains("model") && message.contains("not found") {
+            Some(ProviderFailure::ModelNotFound)
+        } else if message.contains("api key") || message.contains("api_key") {
+            Some(ProviderFailure::InvalidKey)
+        } else {
+            None
+        }
+    })
+}
+
+fn failure_from_status(status: u16) -> ProviderFailure {
+    match status {
+        401 | 403 => ProviderFailure::InvalidKey,
+        402 => ProviderFailure::BillingExhausted,
+        404 => ProviderFailure::ModelNotFound,
+        429 => ProviderFailure::RateLimited,
+        _ => ProviderFailure::Other,
+    }
+}
+
+fn body_message(body: &Value) -> Option<String> {
+    let error = body.get("error")?;
+    error
+        .as_str()
+        .or_else(|| error.get("message").and_then(Value::as_str))
+        .map(|m| m.trim().to_string())
+        .filter(|m| !m.is_empty())
+}
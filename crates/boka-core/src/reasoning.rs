@@ -0,0 +1,105 @@
+//! Clean-up for reasoning models (DeepSeek-R1 and its distills, QwQ) that
+//! think out loud before answering:
+//!
+//! ```text
+//! <think>The user wants JSON with a "variants" key…</think>
+//! {"variants": [...]}
+//! ```
+//!
+//! Enabled per provider with `reasoningModel`. The reasoning is dropped, and
+//! when JSON is expected the last JSON value in what remains is kept, so
+//! "Here is the result:" preambles and trailing remarks do not reach the
+//! parsers either.
+
+use serde_json::Value;
+
+const TAGS: [(&str, &str); 2] = [("<think>", "</think>"), ("<thinking>", "</thinking>")];
+
+/// Remove reasoning blocks. A block left open (output cut off while the model
+/// was still thinking) runs to the end, leaving no answer. Some chat templates
+/// put the opening tag in the prompt, so the reply starts mid-reasoning and
+/// only the closing tag shows up; everything before it is dropped.
+pub fn strip_reasoning(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some((start, open, close)) = TAGS
+        .iter()
+        .filter_map(|&(open, close)| rest.find(open).map(|i| (i, open, close)))
+        .min_by_key(|&(i, _, _)| i)
+    {
+        out.push_str(&rest[..start]);
+        let inner = &rest[start + open.len()..];
+        rest = match inner.find(close) {
+            Some(end) => &inner[end + close.len()..],
+            None => "",
+        };
+    }
+    out.push_str(rest);
+
+    for (_, close) in TAGS {
+        if let Some(i) = out.rfind(close) {
+            out.drain(..i + close.len());
+        }
+    }
+    out.trim().to_string()
+}
+
+/// The last complete `{…}` or `[…]` in `text` that parses as JSON, or the last
+/// balanced one when none does (the parsers repair some malformed output).
+pub fn last_json_value(text: &str) -> Option<&str> {
+    let mut spans = Vec::new();
+    let mut stack = Vec::new();
+    let mut start = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, c) in text.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' if !stack.is_empty() => in_string = true,
+            '{' | '[' => {
+                if stack.is_empty() {
+                    start = i;
+                }
+                stack.push(if c == '{' { '}' } else { ']' });
+            }
+            '}' | ']' if !stack.is_empty() => {
+                if stack.pop() != Some(c) {
+                    // Mismatched bracket: not JSON, start over.
+                    stack.clear();
+                } else if stack.is_empty() {
+                    spans.push(&text[start..=i]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    spans
+        .iter()
+        .rev()
+        .find(|span| serde_json::from_str::<Value>(span).is_ok())
+        .or_else(|| spans.last())
+        .copied()
+}
+
+/// Model output with reasoning removed; with `json`, narrowed to the last
+/// JSON value when there is one.
+pub fn clean_output(text: &str, json: bool) -> String {
+    let answer = strip_reasoning(text);
+    if !json {
+        return answer;
+    }
+    match last_json_value(&answer) {
+        Some(value) => value.to_string(),
+        None => answer,
+    }
+}
@@ -0,0 +1,81 @@
+//! Content-policy refusals. A model that will not translate something,
+//! typically adult content on a strict provider, answers in prose ("I'm
+//! sorry, but I can't help with that"), which would otherwise be taken for
+//! the translation or fail as unreadable JSON. A provider that flags the
+//! reply itself (Anthropic's `stop_reason: "refusal"`, OpenAI's
+//! `finish_reason: "content_filter"` or `message.refusal`) is believed;
+//! otherwise a short reply that opens like a refusal and says what it
+//! declines counts as one. Either way the call fails with
+//! [`ApiError::Refused`], which the pipeline does not retry on the same
+//! model.
+
+use super::text;
+use super::types::ApiError;
+
+/// Longest reply still read as a refusal. Translations that merely contain
+/// an apology are longer, or do not open with one.
+pub const MAX_REFUSAL_CHARS: usize = 400;
+
+/// Reason given when the provider flagged the reply without any text.
+const FILTERED: &str = "the provider's content filter stopped the reply";
+
+const OPENERS: [&str; 14] = [
+    "i'm sorry",
+    "i am sorry",
+    "sorry, but",
+    "i apologize",
+    "i can't",
+    "i cannot",
+    "i can not",
+    "i won't",
+    "i will not",
+    "i'm not able",
+    "i am not able",
+    "i'm unable",
+    "i am unable",
+    "as an ai",
+];
+
+/// What a refusal says it declines; phrases a story line rarely uses, so
+/// dialogue like "I can't help with the harvest" is left alone.
+const DECLINED: [&str; 13] = [
+    "help with that",
+    "help with this",
+    "assist with",
+    "translate this",
+    "translating this",
+    "this request",
+    "this content",
+    "policy",
+    "policies",
+    "guidelines",
+    "explicit",
+    "appropriate",
+    "comply",
+];
+
+/// Whether `text`, a whole model reply, reads as a refusal.
+pub fn is_refusal(text: &str) -> bool {
+    let text = text.trim().trim_start_matches(['"', '\'', '“']).replace('’', "'").to_lowercase();
+    if text.is_empty() || text.starts_with(['{', '[']) || text.chars().count() > MAX_REFUSAL_CHARS {
+        return false;
+    }
+    OPENERS.iter().any(|o| text.starts_with(o)) && DECLINED.iter().any(|d| text.contains(d))
+}
+
+/// [`ApiError::Refused`] when the provider `flagged` the reply or `reply`
+/// reads as a refusal.
+pub fn check(provider: &str, model: &str, reply: &str, flagged: bool) -> Result<(), ApiError> {
+    if !flagged && !is_refusal(reply) {
+        return Ok(());
+    }
+    let reason = match reply.trim() {
+        "" => FILTERED.to_string(),
+        reply => text::excerpt(reply, MAX_REFUSAL_CHARS),
+    };
+    Err(ApiError::Refused {
+        provider: provider.to_string(),
+        model: model.to_string(),
+        reason,
+    })
+}
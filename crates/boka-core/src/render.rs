@@ -0,0 +1,95 @@
+//! Plain text of a doc block as the reader has it, with the variants they
+//! picked. The frontend used to rebuild this string itself to speak, export
+//! or copy a block with swapped variants; doing it here keeps the text, and
+//! so the audio cache key, the same everywhere. With no selections the text
+//! is the block's entry in [`InteractiveDoc::block_texts`], which is built
+//! on the same walk, so a block read as saved reuses the audio generated
+//! for the doc.
+
+use super::gui_types::{DocToken, InteractiveDoc, TextStyle};
+
+use std::collections::BTreeMap;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RenderError {
+    #[error("No block {0} in the doc")]
+    NoBlock(usize),
+
+    #[error("Span {span_id} has no variant {index}")]
+    NoVariant { span_id: String, index: usize },
+}
+
+/// Text of block `block` with, for each span in `selections` (span id to
+/// variant index), that variant, and the active one for the other spans.
+/// Selections for spans of other blocks are ignored, so the frontend may
+/// send its choices for the whole doc. Comprehension checks and emphasis
+/// are left out.
+pub fn render_block_text(
+    doc: &InteractiveDoc,
+    block: usize,
+    selections: &BTreeMap<String, usize>,
+) -> Result<String, RenderError> {
+    let tokens = blocks(doc).nth(block).ok_or(RenderError::NoBlock(block))?;
+    render_tokens(doc, tokens, selections)
+}
+
+/// The tokens of each block, without the `"\n\n"` tokens between them.
+pub(crate) fn blocks(doc: &InteractiveDoc) -> impl Iterator<Item = &[DocToken]> {
+    doc.tokens
+        .split(|t| matches!(t, DocToken::Text { value, .. } if value == "\n\n"))
+}
+
+/// Text of `tokens`, one block of `doc`, as in [`render_block_text`].
+pub(crate) fn render_tokens(
+    doc: &InteractiveDoc,
+    tokens: &[DocToken],
+    selections: &BTreeMap<String, usize>,
+) -> Result<String, RenderError> {
+    render_styled(doc, tokens, selections, |value, _| value.to_string())
+}
+
+/// [`render_tokens`], with each run of emphasized text passed through
+/// `styled`; Markdown export wraps it in marks.
+pub(crate) fn render_styled(
+    doc: &InteractiveDoc,
+    tokens: &[DocToken],
+    selections: &BTreeMap<String, usize>,
+    styled: impl Fn(&str, TextStyle) -> String,
+) -> Result<String, RenderError> {
+    let mut text = String::new();
+    for token in tokens {
+        let (value, style) = match token {
+            DocToken::Text { value, style, .. } => (value.as_str(), *style),
+            DocToken::Span { span_id, style } => (span_text(doc, span_id, selections)?, *style),
+            DocToken::Check { .. } => continue,
+        };
+        match style {
+            Some(style) => text.push_str(&styled(value, style)),
+            None => text.push_str(value),
+        }
+    }
+    Ok(text)
+}
+
+/// The picked or active variant of span `span_id`; empty for a span the
+/// doc does not have.
+fn span_text<'a>(
+    doc: &'a InteractiveDoc,
+    span_id: &str,
+    selections: &BTreeMap<String, usize>,
+) -> Result<&'a str, RenderError> {
+    let Some(span) = doc.spans.get(span_id) else {
+        return Ok("");
+    };
+    match selections.get(span_id) {
+        Some(&index) => span
+            .variants
+            .get(index)
+            .map(|variant| variant.text.as_str())
+            .ok_or_else(|| RenderError::NoVariant {
+                span_id: span_id.to_string(),
+                index,
+            }),
+        None => Ok(span.active_text().unwrap_or_default()),
+    }
+}
@@ -0,0 +1,520 @@
+//! Per-job run reports: how long each stage took, tokens spent, retries and
+//! QA warnings per segment, and which models did the work. Written when a
+//! job ends (successfully or not) as `<data_dir>/reports/<job_id>.json`,
+//! with a Markdown rendering next to it for reading by hand.
+//!
+//! The reports double as the only source for [`model_stats`]: reliability
+//! numbers per provider and model, computed locally and never sent anywhere.
+
+use super::gui_types::CallProvenance;
+use super::stories::now_ms;
+use super::text;
+use super::types::{ApiError, LlmProviderPreset, Usage};
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+const REPORTS_DIR: &str = "reports";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReportError {
+    #[error("Report I/O error: {0}")]
+    Io(String),
+
+    #[error("Failed to parse report: {0}")]
+    Parse(String),
+
+    #[error("No report for job: {0}")]
+    NotFound(String),
+
+    #[error("Invalid job id: `{0}`")]
+    InvalidJobId(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RunStatus {
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RunStage {
+    /// Faithful base translation.
+    Translate,
+    /// Graded-reader translation, including its vocabulary retry.
+    Simplify,
+    /// Judge scoring, including an auto-retried translation.
+    Judge,
+    /// Span planning.
+    Plan,
+    /// Span variant generation, including moderation regenerations.
+    Variants,
+    /// Critique-and-fix passes over generated variants.
+    Refine,
+    /// Comprehension questions written once the doc is done.
+    Check,
+}
+
+impl RunStage {
+    fn label(self) -> &'static str {
+        match self {
+            RunStage::Translate => "Translate",
+            RunStage::Simplify => "Simplify",
+            RunStage::Judge => "Judge",
+            RunStage::Plan => "Plan",
+            RunStage::Variants => "Variants",
+            RunStage::Refine => "Refine",
+            RunStage::Check => "Check",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StageTiming {
+    pub stage: RunStage,
+    pub ms: u64,
+    pub calls: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentReport {
+    pub id: String,
+    /// Model that translated the segment.
+    pub model: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub judge_model: Option<String>,
+    pub ms: u64,
+    pub usage: Usage,
+    /// Extra provider calls made to fix up the output (parse, judge,
+    /// vocabulary and moderation retries).
+    pub retries: u32,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    /// Translation and variant calls, with the provider and model of each.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calls: Vec<CallProvenance>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunReport {
+    pub job_id: String,
+    pub status: RunStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub started_at: u64,
+    pub finished_at: u64,
+    /// Unset when the job failed before its client was created.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<LlmProviderPreset>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub target_language: String,
+    /// Tokens spent by the segments that finished.
+    pub usage: Usage,
+    pub stages: Vec<StageTiming>,
+    pub segments: Vec<SegmentReport>,
+    /// Model output that could not be parsed, truncated output included.
+    #[serde(default)]
+    pub parse_failures: u32,
+    /// Output that ended mid-JSON, usually from hitting the token limit.
+    #[serde(default)]
+    pub truncations: u32,
+    /// Segment from which the job used the line-based output format (see
+    /// `simple_format`) because the model kept breaking the JSON one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub simple_format_from: Option<String>,
+    /// Segments that failed and were skipped under a skipping error policy.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped_segments: Vec<String>,
+    /// Index of the segment being worked on while the job runs.
+    #[serde(skip)]
+    current: Option<usize>,
+}
+
+/// Whether `error` means the model's output could not be parsed, as opposed
+/// to a transport or provider error.
+pub(crate) fn is_output_failure(error: &ApiError) -> bool {
+    matches!(error, ApiError::Parse(m) if m.contains("JSON parse") || m.starts_with("Simple format"))
+}
+
+/// `<data_dir>/reports`.
+pub fn reports_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(REPORTS_DIR)
+}
+
+fn report_path(data_dir: &Path, job_id: &str, ext: &str) -> Result<PathBuf, ReportError> {
+    let valid = !job_id.is_empty() && job_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(ReportError::InvalidJobId(job_id.to_string()));
+    }
+    Ok(reports_dir(data_dir).join(format!("{}.{}", job_id, ext)))
+}
+
+impl RunReport {
+    pub fn new(job_id: &str, target_language: &str) -> Self {
+        Self {
+            job_id: job_id.to_string(),
+            status: RunStatus::Completed,
+            error: None,
+            started_at: now_ms(),
+            finished_at: 0,
+            provider: None,
+            model: None,
+            target_language: target_language.to_string(),
+            usage: Usage::default(),
+            stages: vec![],
+            segments: vec![],
+            parse_failures: 0,
+            truncations: 0,
+            simple_format_from: None,
+            skipped_segments: vec![],
+            current: None,
+        }
+    }
+
+    fn current_segment(&mut self) -> Option<&mut SegmentReport> {
+        self.current.and_then(|i| self.segments.get_mut(i))
+    }
+
+    /// Count `error` if it means the model's output was unusable.
+    pub(crate) fn output_failure(&mut self, error: &ApiError) {
+        if is_output_failure(error) {
+            self.parse_failures += 1;
+            // serde_json's wording when the text stops before the value is complete.
+            if error.to_string().contains("EOF while parsing") {
+                self.truncations += 1;
+            }
+        }
+    }
+
+    /// Note that the rest of the job uses the line-based output format.
+    pub(crate) fn simple_format(&mut self) {
+        if let Some(segment) = self.current_segment() {
+            let id = segment.id.clone();
+            segment
+                .warnings
+                .push("repeated parse failures; switched to the simple output format".to_string());
+            self.simple_format_from = Some(id);
+        }
+    }
+
+    /// Start reporting on segment `id`; a segment that is tried again keeps
+    /// its entry.
+    pub(crate) fn begin_segment(&mut self, id: &str, judge_model: Option<&str>) {
+        if self.segments.iter().any(|s| s.id == id) {
+            return self.resume_segment(id);
+        }
+        self.segments.push(SegmentReport {
+            id: id.to_string(),
+            model: self.model.clone().unwrap_or_default(),
+            judge_model: judge_model.map(str::to_string),
+            ms: 0,
+            usage: Usage::default(),
+            retries: 0,
+            warnings: vec![],
+            calls: vec![],
+        });
+        self.current = Some(self.segments.len() - 1);
+    }
+
+    /// Make segment `id` current again, for work that resumes after other
+    /// segments ran (planning after the review gate).
+    pub(crate) fn resume_segment(&mut self, id: &str) {
+        self.current = self.segments.iter().position(|s| s.id == id);
+    }
+
+    /// Record one call of `stage` that began at `started`.
+    pub(crate) fn timed(&mut self, stage: RunStage, started: Instant) {
+        let ms = started.elapsed().as_millis() as u64;
+        match self.stages.iter_mut().find(|s| s.stage == stage) {
+            Some(timing) => {
+                timing.ms += ms;
+                timing.calls += 1;
+            }
+            None => self.stages.push(StageTiming { stage, ms, calls: 1 }),
+        }
+        if let Some(segment) = self.current_segment() {
+            segment.ms += ms;
+        }
+    }
+
+    pub(crate) fn call(&mut self, call: CallProvenance) {
+        if let Some(segment) = self.current_segment() {
+            segment.calls.push(call);
+        }
+    }
+
+    pub(crate) fn retries(&mut self, count: u32) {
+        if let Some(segment) = self.current_segment() {
+            segment.retries += count;
+        }
+    }
+
+    pub(crate) fn warn(&mut self, warning: String) {
+        if let Some(segment) = self.current_segment() {
+            segment.warnings.push(text::clip(&warning));
+        }
+    }
+
+    /// The current segment failed with `error` and the job goes on without it.
+    pub(crate) fn skip_segment(&mut self, error: &ApiError) {
+        self.output_failure(error);
+        if let Some(segment) = self.current_segment() {
+            let id = segment.id.clone();
+            segment.warnings.push(text::clip(&format!("skipped: {}", error)));
+            self.skipped_segments.push(id);
+        }
+    }
+
+    /// Charge the usage since the last call to the current segment; `total`
+    /// is the job's usage so far. Calls cut off at the token limit are also
+    /// a warning, since they leave short variants without failing to parse.
+    pub(crate) fn end_segment(&mut self, total: Usage) {
+        let spent = Usage {
+            input_tokens: total.input_tokens.saturating_sub(self.usage.input_tokens),
+            output_tokens: total.output_tokens.saturating_sub(self.usage.output_tokens),
+            max_token_stops: total.max_token_stops.saturating_sub(self.usage.max_token_stops),
+        };
+        if let Some(segment) = self.current_segment() {
+            segment.usage += spent;
+            if spent.max_token_stops > 0 {
+                segment.warnings.push(format!(
+                    "{} call(s) stopped at the output token limit; output may be cut short",
+                    spent.max_token_stops
+                ));
+            }
+        }
+        self.usage = total;
+    }
+
+    pub(crate) fn finish<T>(&mut self, result: &Result<T, ApiError>) {
+        self.finished_at = now_ms();
+        self.current = None;
+        if let Err(e) = result {
+            self.output_failure(e);
+        }
+        (self.status, self.error) = match result {
+            Ok(_) => (RunStatus::Completed, None),
+            Err(ApiError::Parse(m)) if m == "Cancelled" => (RunStatus::Cancelled, None),
+            Err(e) => (RunStatus::Failed, Some(text::clip(&e.to_string()))),
+        };
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.segments.iter().map(|s| s.warnings.len()).sum()
+    }
+
+    /// Human-readable version of the report.
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("# Run report: {}\n\n", self.job_id);
+        let status = match self.status {
+            RunStatus::Completed => "completed",
+            RunStatus::Failed => "failed",
+            RunStatus::Cancelled => "cancelled",
+        };
+        md.push_str(&format!("- Status: {}\n", status));
+        if let Some(error) = &self.error {
+            md.push_str(&format!("- Error: {}\n", error));
+        }
+        let provider = self.provider.map(|p| format!("{:?}", p).to_lowercase());
+        md.push_str(&format!(
+            "- Provider: {} ({})\n",
+            provider.as_deref().unwrap_or("none"),
+            self.model.as_deref().unwrap_or("no model")
+        ));
+        md.push_str(&format!("- Target language: {}\n", self.target_language));
+        md.push_str(&format!(
+            "- Duration: {:.1} s\n",
+            self.finished_at.saturating_sub(self.started_at) as f64 / 1000.0
+        ));
+        md.push_str(&format!(
+            "- Tokens: {} in, {} out\n",
+            self.usage.input_tokens, self.usage.output_tokens
+        ));
+        md.push_str(&format!(
+            "- Parse failures: {} ({} truncated)\n",
+            self.parse_failures, self.truncations
+        ));
+        if self.usage.max_token_stops > 0 {
+            md.push_str(&format!("- Calls stopped at the token limit: {}\n", self.usage.max_token_stops));
+        }
+        if let Some(segment) = &self.simple_format_from {
+            md.push_str(&format!("- Simple output format from: {}\n", segment));
+        }
+        if !self.skipped_segments.is_empty() {
+            md.push_str(&format!("- Skipped segments: {}\n", self.skipped_segments.join(", ")));
+        }
+        md.push_str(&format!(
+            "- Retries: {}\n- Warnings: {}\n",
+            self.segments.iter().map(|s| s.retries).sum::<u32>(),
+            self.warning_count()
+        ));
+
+        if !self.stages.is_empty() {
+            md.push_str("\n## Stages\n\n| Stage | Calls | Time (ms) |\n| --- | ---: | ---: |\n");
+            for s in &self.stages {
+                md.push_str(&format!("| {} | {} | {} |\n", s.stage.label(), s.calls, s.ms));
+            }
+        }
+
+        if !self.segments.is_empty() {
+            md.push_str("\n## Segments\n\n| Segment | Model | Time (ms) | Tokens in | Tokens out | Retries |\n");
+            md.push_str("| --- | --- | ---: | ---: | ---: | ---: |\n");
+            for s in &self.segments {
+                let model = match &s.judge_model {
+                    Some(judge) => format!("{} (judge: {})", s.model, judge),
+                    None => s.model.clone(),
+                };
+                md.push_str(&format!(
+                    "| {} | {} | {} | {} | {} | {} |\n",
+                    s.id, model, s.ms, s.usage.input_tokens, s.usage.output_tokens, s.retries
+                ));
+            }
+        }
+
+        if self.segments.iter().any(|s| !s.calls.is_empty()) {
+            md.push_str("\n## Calls\n\n| Segment | Provider | Model | Attempt | Time (ms) |\n");
+            md.push_str("| --- | --- | --- | ---: | ---: |\n");
+            for s in &self.segments {
+                for c in &s.calls {
+                    let provider = format!("{:?}", c.provider).to_lowercase();
+                    md.push_str(&format!(
+                        "| {} | {} | {} | {} | {} |\n",
+                        s.id, provider, c.model, c.attempt, c.duration_ms
+                    ));
+                }
+            }
+        }
+
+        if self.warning_count() > 0 {
+            md.push_str("\n## Warnings\n\n");
+            for s in &self.segments {
+                for w in &s.warnings {
+                    md.push_str(&format!("- {}: {}\n", s.id, w));
+                }
+            }
+        }
+        md
+    }
+
+    /// Write `<job_id>.json` and `<job_id>.md` into the reports dir.
+    pub fn save(&self, data_dir: &Path) -> Result<(), ReportError> {
+        let json_path = report_path(data_dir, &self.job_id, "json")?;
+        let md_path = report_path(data_dir, &self.job_id, "md")?;
+        fs::create_dir_all(reports_dir(data_dir)).map_err(|e| ReportError::Io(e.to_string()))?;
+        let json = serde_json::to_string_pretty(self).map_err(|e| ReportError::Parse(e.to_string()))?;
+        fs::write(json_path, json).map_err(|e| ReportError::Io(e.to_string()))?;
+        fs::write(md_path, self.to_markdown()).map_err(|e| ReportError::Io(e.to_string()))
+    }
+
+    pub fn load(data_dir: &Path, job_id: &str) -> Result<Self, ReportError> {
+        let path = report_path(data_dir, job_id, "json")?;
+        if !path.is_file() {
+            return Err(ReportError::NotFound(job_id.to_string()));
+        }
+        let raw = fs::read_to_string(&path).map_err(|e| ReportError::Io(e.to_string()))?;
+        serde_json::from_str(&raw).map_err(|e| ReportError::Parse(e.to_string()))
+    }
+}
+
+/// Reliability of one provider/model across every saved run report.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelStats {
+    pub provider: LlmProviderPreset,
+    pub model: String,
+    pub jobs: u32,
+    pub failed_jobs: u32,
+    pub segments: u32,
+    /// Provider calls made, over all stages.
+    pub calls: u32,
+    pub parse_failures: u32,
+    pub truncations: u32,
+    /// Calls cut off at the output token limit whose output still parsed;
+    /// the ones that broke the JSON are counted as truncations.
+    pub max_token_stops: u32,
+    pub retries: u32,
+    /// `parse_failures / calls`.
+    pub parse_failure_rate: f64,
+    /// `truncations / calls`.
+    pub truncation_rate: f64,
+    /// `retries / segments`.
+    pub retries_per_segment: f64,
+}
+
+fn ratio(part: u32, whole: u32) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+impl ModelStats {
+    /// Totals per provider and model, sorted by both. Reports from jobs
+    /// that never reached a provider are skipped.
+    pub fn from_reports<'a>(reports: impl IntoIterator<Item = &'a RunReport>) -> Vec<Self> {
+        let mut by_model: BTreeMap<(String, String), ModelStats> = BTreeMap::new();
+        for report in reports {
+            let (Some(provider), Some(model)) = (report.provider, report.model.as_ref()) else {
+                continue;
+            };
+            let key = (format!("{:?}", provider), model.clone());
+            let stats = by_model.entry(key).or_insert_with(|| ModelStats {
+                provider,
+                model: model.clone(),
+                jobs: 0,
+                failed_jobs: 0,
+                segments: 0,
+                calls: 0,
+                parse_failures: 0,
+                truncations: 0,
+                max_token_stops: 0,
+                retries: 0,
+                parse_failure_rate: 0.0,
+                truncation_rate: 0.0,
+                retries_per_segment: 0.0,
+            });
+            stats.jobs += 1;
+            stats.failed_jobs += u32::from(report.status == RunStatus::Failed);
+            stats.segments += report.segments.len() as u32;
+            stats.calls += report.stages.iter().map(|s| s.calls).sum::<u32>();
+            stats.parse_failures += report.parse_failures;
+            stats.truncations += report.truncations;
+            stats.max_token_stops += report.usage.max_token_stops;
+            stats.retries += report.segments.iter().map(|s| s.retries).sum::<u32>();
+        }
+        by_model
+            .into_values()
+            .map(|mut s| {
+                s.parse_failure_rate = ratio(s.parse_failures, s.calls);
+                s.truncation_rate = ratio(s.truncations, s.calls);
+                s.retries_per_segment = ratio(s.retries, s.segments);
+                s
+            })
+            .collect()
+    }
+}
+
+/// [`ModelStats`] over every report in `<data_dir>/reports`; unreadable
+/// reports are skipped.
+pub fn model_stats(data_dir: &Path) -> Vec<ModelStats> {
+    let reports: Vec<RunReport> = fs::read_dir(reports_dir(data_dir))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|e| e == "json"))
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .filter_map(|raw| serde_json::from_str(&raw).ok())
+        .collect();
+    ModelStats::from_reports(&reports)
+}
@@ -0,0 +1,116 @@
+//! What the running app has built in and loaded, for the settings screen's
+//! diagnostics: compiled features, which TTS models are on disk and in
+//! memory, cache sizes, and roughly what each subsystem holds.
+//!
+//! Subsystems are created on first use, so a fresh session reports them as
+//! not loaded until something needs them.
+
+use super::tts_models::TtsModelRegistry;
+
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+/// Optional features, in the order they are reported. Only compiled ones
+/// show up in [`RuntimeInfo::features`].
+pub fn enabled_features() -> Vec<String> {
+    let mut features = Vec::new();
+    if cfg!(feature = "tts") {
+        features.push("tts".to_string());
+    }
+    features
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeInfo {
+    pub version: String,
+    pub features: Vec<String>,
+    pub tts_models: Vec<ModelPresence>,
+    pub caches: Vec<CacheUsage>,
+    pub subsystems: Vec<SubsystemInfo>,
+    /// Resident memory of the whole process, where the OS reports it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resident_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPresence {
+    pub id: String,
+    pub installed: bool,
+    /// An engine for it is in memory.
+    pub loaded: bool,
+    pub size_bytes: u64,
+}
+
+/// One cache: how many entries it holds and their size, in memory or on
+/// disk depending on the cache.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheUsage {
+    pub name: String,
+    pub entries: u64,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubsystemInfo {
+    pub name: String,
+    pub loaded: bool,
+    /// Memory it holds, when that can be estimated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approx_bytes: Option<u64>,
+}
+
+/// Registry models with whether they are installed under `model_dir` and
+/// whether one of `loaded` (model ids) is in memory.
+pub fn model_presence(registry: &TtsModelRegistry, model_dir: &Path, loaded: &[String]) -> Vec<ModelPresence> {
+    registry
+        .models
+        .iter()
+        .map(|model| ModelPresence {
+            id: model.id.clone(),
+            installed: model.installed(model_dir),
+            loaded: loaded.contains(&model.id),
+            size_bytes: model.size_bytes,
+        })
+        .collect()
+}
+
+/// Files under `dir` and their total size. A missing directory is empty.
+pub fn dir_usage(name: &str, dir: &Path) -> CacheUsage {
+    fn walk(dir: &Path, usage: &mut CacheUsage) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            if meta.is_dir() {
+                walk(&entry.path(), usage);
+            } else {
+                usage.entries += 1;
+                usage.bytes += meta.len();
+            }
+        }
+    }
+
+    let mut usage = CacheUsage {
+        name: name.to_string(),
+        entries: 0,
+        bytes: 0,
+    };
+    walk(dir, &mut usage);
+    usage
+}
+
+/// Resident set size of this process. Only Linux reports it here.
+pub fn resident_bytes() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
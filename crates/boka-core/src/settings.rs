@@ -0,0 +1,446 @@
+use super::audio_types::PauseOptions;
+use super::background::BackgroundPolicy;
+use super::length_guard::LengthGuard;
+use super::paths;
+use super::pricing::PriceTable;
+use super::privacy::{RetentionPolicy, MAX_RETENTION_DAYS};
+use super::speech_prefetch::PrefetchPolicy;
+use super::tts_models::TtsModelRegistry;
+
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SETTINGS_FILE: &str = "settings.json";
+/// A paragraph of house rules; it is paid for on every provider call.
+pub const MAX_PREAMBLE_CHARS: usize = 2000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SettingsError {
+    #[error("Settings I/O error: {0}")]
+    Io(String),
+
+    #[error("Failed to parse settings: {0}")]
+    Parse(String),
+
+    #[error("Setting is locked: {0}")]
+    Locked(String),
+
+    #[error("Invalid setting: {0}")]
+    Invalid(String),
+}
+
+/// Backend-owned user settings, persisted as `settings.json` in the shared data dir.
+///
+/// Unlike per-job arguments, these cannot be overridden by a single command
+/// call, which is what makes them suitable for locks like child-safe mode.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Settings {
+    #[serde(default)]
+    pub child_safe: ChildSafeSettings,
+    #[serde(default)]
+    pub variant_bounds: VariantBounds,
+    #[serde(default)]
+    pub tts_warmup: WarmupPolicy,
+    /// How far past the reading position speech is synthesized ahead.
+    #[serde(default)]
+    pub tts_prefetch: PrefetchPolicy,
+    /// TTS model id per language code; unlisted languages use the registry default.
+    #[serde(default)]
+    pub tts_models: BTreeMap<String, String>,
+    #[serde(default)]
+    pub audio_presets: AudioPresets,
+    /// When model downloads and other unrequested work may run.
+    #[serde(default)]
+    pub background: BackgroundPolicy,
+    #[serde(default)]
+    pub doc_cache_size: DocCacheSize,
+    /// Instructions sent ahead of the system prompt of every provider call
+    /// (e.g. "never add translator's notes"); a job may replace it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_preamble: Option<String>,
+    /// How long job transcripts are kept; enforced at startup.
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+    /// Language code learner notes are written in (the learner's own
+    /// language); English when unset. A job may ask for another.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ui_language: Option<String>,
+    /// Model prices laid over the registry's, and the currency costs are shown in.
+    #[serde(default)]
+    pub pricing: PriceTable,
+    /// How much longer than its source a base translation may be before it
+    /// is asked for again.
+    #[serde(default)]
+    pub length_guard: LengthGuard,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChildSafeSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Argon2 hash of the teacher PIN with its own random salt, as a PHC
+    /// string; required to turn the mode off when set. A 4-6 digit PIN
+    /// keeps children out of the setting; the slow hash only makes reading
+    /// it back from a copied settings file take hours instead of seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pin_hash: Option<String>,
+    /// Unsalted SHA-256 of a PIN set before `pin_hash` existed. Still
+    /// accepted; the next [`Settings::set_child_safe`] replaces it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pin_sha256: Option<String>,
+}
+
+impl ChildSafeSettings {
+    pub fn pin_set(&self) -> bool {
+        self.pin_hash.is_some() || self.pin_sha256.is_some()
+    }
+
+    fn pin_matches(&self, pin: &str) -> bool {
+        if let Some(hash) = &self.pin_hash {
+            return PasswordHash::new(hash)
+                .is_ok_and(|hash| Argon2::default().verify_password(pin.as_bytes(), &hash).is_ok());
+        }
+        self.pin_sha256
+            .as_deref()
+            .is_some_and(|expected| format!("{:x}", Sha256::digest(pin.as_bytes())) == expected)
+    }
+}
+
+/// How many variants a span may get. Easy segments get `min`, the hardest
+/// get `max`; see [`VariantBounds::target`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariantBounds {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl VariantBounds {
+    /// Hard limits for user-chosen bounds.
+    pub const LIMIT: u32 = 8;
+
+    pub fn new(min: u32, max: u32) -> Result<Self, SettingsError> {
+        if min == 0 || max > Self::LIMIT || min > max {
+            return Err(SettingsError::Invalid(format!(
+                "variant bounds must satisfy 1 <= min <= max <= {}",
+                Self::LIMIT
+            )));
+        }
+        Ok(Self { min, max })
+    }
+
+    /// Variant count for a segment with `difficulty` in 0.0..=1.0.
+    pub fn target(&self, difficulty: f32) -> u32 {
+        let span = self.max.saturating_sub(self.min) as f32;
+        self.min + (difficulty.clamp(0.0, 1.0) * span).round() as u32
+    }
+}
+
+impl Default for VariantBounds {
+    fn default() -> Self {
+        Self { min: 2, max: 4 }
+    }
+}
+
+/// How many parsed docs a session keeps in memory (see `doc_cache`); 0
+/// turns the cache off. Big docs take a few MB each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DocCacheSize(pub u32);
+
+impl DocCacheSize {
+    pub const MAX: u32 = 256;
+}
+
+impl Default for DocCacheSize {
+    fn default() -> Self {
+        Self(16)
+    }
+}
+
+/// When to load the TTS model. Loading takes seconds (and a ~350MB
+/// download on first run), so users who rarely listen can defer it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WarmupPolicy {
+    /// In the background right after launch.
+    #[default]
+    OnStartup,
+    /// When a doc is opened in the reader.
+    OnDocOpen,
+    /// Only when speech is first requested.
+    OnFirstUse,
+}
+
+/// Named speech settings for a learner level, so the frontend asks for
+/// "beginner" instead of hard-coding a speed and pause lengths.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioPreset {
+    pub speed: f32,
+    #[serde(default)]
+    pub pauses: PauseOptions,
+    /// Voice to use instead of the language's default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voice_id: Option<String>,
+}
+
+impl AudioPreset {
+    pub const MIN_SPEED: f32 = 0.5;
+    pub const MAX_SPEED: f32 = 2.0;
+    pub const MAX_PAUSE_MS: u32 = 5_000;
+
+    fn validate(&self) -> Result<(), SettingsError> {
+        if !(Self::MIN_SPEED..=Self::MAX_SPEED).contains(&self.speed) {
+            return Err(SettingsError::Invalid(format!(
+                "preset speed must be between {} and {}",
+                Self::MIN_SPEED,
+                Self::MAX_SPEED
+            )));
+        }
+        let p = self.pauses;
+        if [p.comma_ms, p.sentence_ms, p.paragraph_ms].iter().any(|ms| *ms > Self::MAX_PAUSE_MS) {
+            return Err(SettingsError::Invalid(format!(
+                "preset pauses must be at most {} ms",
+                Self::MAX_PAUSE_MS
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Audio presets by name. Starts with `beginner`, `intermediate` and
+/// `native`; users may change or remove those and add their own.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AudioPresets(pub BTreeMap<String, AudioPreset>);
+
+impl Default for AudioPresets {
+    fn default() -> Self {
+        let preset = |speed: f32, comma_ms: u32, sentence_ms: u32, paragraph_ms: u32| AudioPreset {
+            speed,
+            pauses: PauseOptions {
+                comma_ms,
+                sentence_ms,
+                paragraph_ms,
+            },
+            voice_id: None,
+        };
+        Self(BTreeMap::from([
+            ("beginner".to_string(), preset(0.8, 250, 700, 1200)),
+            ("intermediate".to_string(), preset(0.9, 100, 350, 800)),
+            ("native".to_string(), preset(1.0, 0, 0, 0)),
+        ]))
+    }
+}
+
+/// What the frontend gets to see: never the PIN hash.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsView {
+    pub child_safe_enabled: bool,
+    pub child_safe_pin_set: bool,
+    pub variant_bounds: VariantBounds,
+    pub tts_warmup: WarmupPolicy,
+    pub tts_prefetch: PrefetchPolicy,
+    pub tts_models: BTreeMap<String, String>,
+    pub audio_presets: AudioPresets,
+    pub background: BackgroundPolicy,
+    pub doc_cache_size: u32,
+    pub system_preamble: Option<String>,
+    pub retention: RetentionPolicy,
+    pub ui_language: Option<String>,
+    pub pricing: PriceTable,
+    pub length_guard: LengthGuard,
+}
+
+fn hash_pin(pin: &str) -> Result<String, SettingsError> {
+    let unhashable = |e: String| SettingsError::Invalid(format!("the PIN could not be hashed: {}", e));
+    let mut salt = [0u8; 16];
+    getrandom::getrandom(&mut salt).map_err(|e| unhashable(e.to_string()))?;
+    let salt = SaltString::encode_b64(&salt).map_err(|e| unhashable(e.to_string()))?;
+    Argon2::default()
+        .hash_password(pin.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| unhashable(e.to_string()))
+}
+
+impl Settings {
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join(SETTINGS_FILE)
+    }
+
+    pub fn load(data_dir: &Path) -> Result<Self, SettingsError> {
+        let path = Self::path(data_dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(&path).map_err(|e| SettingsError::Io(e.to_string()))?;
+        serde_json::from_str(&raw).map_err(|e| SettingsError::Parse(e.to_string()))
+    }
+
+    /// Atomic write: tmp file, then rename.
+    pub fn save(&self, data_dir: &Path) -> Result<(), SettingsError> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| SettingsError::Parse(e.to_string()))?;
+        paths::write_atomic(&Self::path(data_dir), json.as_bytes())
+            .map_err(|e| SettingsError::Io(e.to_string()))
+    }
+
+    pub fn view(&self) -> SettingsView {
+        SettingsView {
+            child_safe_enabled: self.child_safe.enabled,
+            child_safe_pin_set: self.child_safe.pin_set(),
+            variant_bounds: self.variant_bounds,
+            tts_warmup: self.tts_warmup,
+            tts_prefetch: self.tts_prefetch,
+            tts_models: self.tts_models.clone(),
+            audio_presets: self.audio_presets.clone(),
+            background: self.background,
+            doc_cache_size: self.doc_cache_size.0,
+            system_preamble: self.system_preamble.clone(),
+            retention: self.retention,
+            ui_language: self.ui_language.clone(),
+            pricing: self.pricing.clone(),
+            length_guard: self.length_guard,
+        }
+    }
+
+    /// Ok when no teacher PIN is set or `pin` is it.
+    pub fn check_pin(&self, pin: Option<&str>) -> Result<(), SettingsError> {
+        let pin = pin.map(str::trim).filter(|p| !p.is_empty());
+        if self.child_safe.pin_set() && !pin.is_some_and(|pin| self.child_safe.pin_matches(pin)) {
+            return Err(SettingsError::Locked("child-safe mode requires the teacher PIN".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Enable or disable child-safe mode. Enabling may set a PIN; once a PIN
+    /// is set, disabling (or changing it) requires the same PIN.
+    pub fn set_child_safe(&mut self, enabled: bool, pin: Option<&str>) -> Result<(), SettingsError> {
+        self.check_pin(pin)?;
+        let pin = pin.map(str::trim).filter(|p| !p.is_empty());
+        self.child_safe.enabled = enabled;
+        self.child_safe.pin_hash = match pin.filter(|_| enabled) {
+            Some(pin) => Some(hash_pin(pin)?),
+            None => None,
+        };
+        self.child_safe.pin_sha256 = None;
+        Ok(())
+    }
+
+    /// Pick the TTS model for `language`, or go back to the default with `None`.
+    pub fn set_tts_model(
+        &mut self,
+        registry: &TtsModelRegistry,
+        language: &str,
+        model: Option<&str>,
+    ) -> Result<(), SettingsError> {
+        let language = language.trim().to_ascii_lowercase();
+        if language.is_empty() {
+            return Err(SettingsError::Invalid("language must not be empty".to_string()));
+        }
+        match model {
+            Some(id) => {
+                registry
+                    .validate(&language, id)
+                    .map_err(|e| SettingsError::Invalid(e.to_string()))?;
+                self.tts_models.insert(language, id.to_string());
+            }
+            None => {
+                self.tts_models.remove(&language);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn audio_preset(&self, name: &str) -> Result<&AudioPreset, SettingsError> {
+        self.audio_presets
+            .0
+            .get(name.trim())
+            .ok_or_else(|| SettingsError::Invalid(format!("unknown audio preset `{}`", name.trim())))
+    }
+
+    /// Add or replace the preset called `name`, or remove it with `None`.
+    pub fn set_audio_preset(&mut self, name: &str, preset: Option<AudioPreset>) -> Result<(), SettingsError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(SettingsError::Invalid("preset name must not be empty".to_string()));
+        }
+        match preset {
+            Some(preset) => {
+                preset.validate()?;
+                self.audio_presets.0.insert(name.to_string(), preset);
+            }
+            None => {
+                self.audio_presets.0.remove(name);
+            }
+        }
+        Ok(())
+    }
+    pub fn set_background(&mut self, policy: BackgroundPolicy) -> Result<(), SettingsError> {
+        if policy.idle_secs > BackgroundPolicy::MAX_IDLE_SECS {
+            return Err(SettingsError::Invalid(format!(
+                "idle time must be at most {} seconds",
+                BackgroundPolicy::MAX_IDLE_SECS
+            )));
+        }
+        self.background = policy;
+        Ok(())
+    }
+
+    /// `None` or blank text removes the preamble.
+    pub fn set_system_preamble(&mut self, text: Option<&str>) -> Result<(), SettingsError> {
+        let text = text.map(str::trim).filter(|t| !t.is_empty());
+        if text.is_some_and(|t| t.chars().count() > MAX_PREAMBLE_CHARS) {
+            return Err(SettingsError::Invalid(format!(
+                "system preamble must be at most {} characters",
+                MAX_PREAMBLE_CHARS
+            )));
+        }
+        self.system_preamble = text.map(str::to_string);
+        Ok(())
+    }
+
+    pub fn set_retention(&mut self, policy: RetentionPolicy) -> Result<(), SettingsError> {
+        if policy.transcript_days.is_some_and(|days| days == 0 || days > MAX_RETENTION_DAYS) {
+            return Err(SettingsError::Invalid(format!(
+                "transcript retention must be between 1 and {} days",
+                MAX_RETENTION_DAYS
+            )));
+        }
+        self.retention = policy;
+        Ok(())
+    }
+
+    /// `None` or a blank code goes back to English notes.
+    pub fn set_ui_language(&mut self, code: Option<&str>) -> Result<(), SettingsError> {
+        let code = code.map(str::trim).filter(|c| !c.is_empty());
+        let valid = |c: &str| c.len() <= 16 && c.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_');
+        if code.is_some_and(|c| !valid(c)) {
+            return Err(SettingsError::Invalid(
+                "UI language must be a language code like \"de\" or \"pt-BR\"".to_string(),
+            ));
+        }
+        self.ui_language = code.map(str::to_ascii_lowercase);
+        Ok(())
+    }
+
+    pub fn set_doc_cache_size(&mut self, size: u32) -> Result<(), SettingsError> {
+        if size > DocCacheSize::MAX {
+            return Err(SettingsError::Invalid(format!(
+                "doc cache size must be at most {}",
+                DocCacheSize::MAX
+            )));
+        }
+        self.doc_cache_size = DocCacheSize(size);
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+//! Line-based fallback for the structured planning and variant calls.
+//! Small local models often break the JSON block schema; after repeated
+//! parse failures a job switches to this format for its remaining calls.
+//!
+//! - Planning: the model lists phrases from the segment, one per line. The
+//!   block is built by finding them in the segment, so the model never has
+//!   to reproduce the segment text.
+//! - Variants: one variant per line, `text | register | note | difficulty`;
+//!   everything after `text` is optional.
+
+use super::anthropic::{PlannedBlock, PlannedSegment, PlannedSpan, PlannedVariant};
+use super::text::{excerpt, EXCERPT_LEN};
+use super::types::ApiError;
+
+use serde::{Deserialize, Serialize};
+
+/// Output format of the planning and variant calls.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StructuredFormat {
+    #[default]
+    Json,
+    Simple,
+}
+
+/// Lines of model output without blank lines, code fences, list bullets or
+/// numbering.
+fn content_lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with("```"))
+        .map(|l| {
+            let l = l.trim_start_matches(['-', '*', '•']).trim_start();
+            let digits = l.len() - l.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            match l[digits..].strip_prefix(['.', ')']) {
+                Some(rest) if digits > 0 => rest.trim_start(),
+                _ => l,
+            }
+        })
+}
+
+/// Build the block for `base_text` from a list of phrases. Phrases are
+/// matched in order, each after the previous one; those not found in the
+/// segment are skipped.
+pub fn parse_simple_plan(text: &str, base_text: &str) -> Result<PlannedBlock, ApiError> {
+    let mut segments = Vec::new();
+    let mut pos = 0;
+    for phrase in content_lines(text) {
+        let phrase = phrase.trim_matches(|c: char| c == '"' || c == '\'' || c == '“' || c == '”');
+        if phrase.is_empty() {
+            continue;
+        }
+        let Some(found) = base_text[pos..].find(phrase).map(|i| pos + i) else {
+            continue;
+        };
+        if found > pos {
+            segments.push(PlannedSegment::Static(base_text[pos..found].to_string()));
+        }
+        let end = found + phrase.len();
+        segments.push(PlannedSegment::Swappable(PlannedSpan {
+            id: format!("s{}", segments.len() + 1),
+            variants: vec![PlannedVariant {
+                text: base_text[found..end].to_string(),
+                register: "neutral".to_string(),
+                note: String::new(),
+                difficulty: 2,
+            }],
+            provenance: vec![],
+        }));
+        pos = end;
+    }
+
+    if !segments.iter().any(|s| matches!(s, PlannedSegment::Swappable(_))) {
+        return Err(ApiError::Parse(format!(
+            "Simple format: no listed phrase appears in the segment | output: {}",
+            excerpt(text.trim(), EXCERPT_LEN)
+        )));
+    }
+    if pos < base_text.len() {
+        segments.push(PlannedSegment::Static(base_text[pos..].to_string()));
+    }
+    Ok(PlannedBlock {
+        id: "b1".to_string(),
+        segments,
+    })
+}
+
+/// Parse `text | register | note | difficulty` lines.
+pub fn parse_simple_variants(text: &str) -> Result<Vec<PlannedVariant>, ApiError> {
+    let variants: Vec<PlannedVariant> = content_lines(text)
+        .filter_map(|line| {
+            let mut fields = line.split('|').map(str::trim);
+            let text = fields.next().filter(|t| !t.is_empty())?;
+            let register = fields.next().filter(|r| !r.is_empty()).unwrap_or("neutral");
+            let note = fields.next().unwrap_or("");
+            let difficulty = fields
+                .next()
+                .and_then(|d| d.parse::<u8>().ok())
+                .map_or(2, |d| d.clamp(1, 5));
+            Some(PlannedVariant {
+                text: text.to_string(),
+                register: register.to_lowercase(),
+                note: note.to_string(),
+                difficulty,
+            })
+        })
+        .collect();
+
+    if variants.is_empty() {
+        return Err(ApiError::Parse(format!(
+            "Simple format: no variants | output: {}",
+            excerpt(text.trim(), EXCERPT_LEN)
+        )));
+    }
+    Ok(variants)
+}
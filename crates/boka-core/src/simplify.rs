@@ -0,0 +1,164 @@
+use super::lemma;
+use super::translation::split_into_segments;
+
+use serde::{Deserialize, Serialize};
+
+/// Target reading level for graded-reader output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum CefrLevel {
+    A1,
+    A2,
+    B1,
+    B2,
+    C1,
+    C2,
+}
+
+impl CefrLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CefrLevel::A1 => "A1",
+            CefrLevel::A2 => "A2",
+            CefrLevel::B1 => "B1",
+            CefrLevel::B2 => "B2",
+            CefrLevel::C1 => "C1",
+            CefrLevel::C2 => "C2",
+        }
+    }
+
+    /// Inverse of [`as_str`](Self::as_str), ignoring case.
+    pub fn parse(code: &str) -> Option<Self> {
+        match code.trim().to_ascii_uppercase().as_str() {
+            "A1" => Some(CefrLevel::A1),
+            "A2" => Some(CefrLevel::A2),
+            "B1" => Some(CefrLevel::B1),
+            "B2" => Some(CefrLevel::B2),
+            "C1" => Some(CefrLevel::C1),
+            "C2" => Some(CefrLevel::C2),
+            _ => None,
+        }
+    }
+
+    /// Longest sentence, in words, a reader at this level should meet.
+    pub fn max_sentence_words(self) -> Option<usize> {
+        match self {
+            CefrLevel::A1 => Some(8),
+            CefrLevel::A2 => Some(12),
+            CefrLevel::B1 => Some(16),
+            CefrLevel::B2 => Some(22),
+            CefrLevel::C1 => Some(30),
+            CefrLevel::C2 => None,
+        }
+    }
+
+    /// Words at least this long count as outside the controlled vocabulary.
+    /// Word length stands in for frequency, which we have no lists for.
+    pub fn hard_word_chars(self) -> Option<usize> {
+        match self {
+            CefrLevel::A1 => Some(9),
+            CefrLevel::A2 => Some(10),
+            CefrLevel::B1 => Some(12),
+            CefrLevel::B2 => Some(14),
+            CefrLevel::C1 | CefrLevel::C2 => None,
+        }
+    }
+
+    /// Prompt guidance describing the level.
+    pub fn guidance(self) -> &'static str {
+        match self {
+            CefrLevel::A1 => "Use only the most frequent everyday words, present tense where possible, and very short simple sentences.",
+            CefrLevel::A2 => "Use common everyday words, simple past and future, and short sentences joined with and/but/because at most.",
+            CefrLevel::B1 => "Use familiar vocabulary, explain or replace idioms, and keep sentences to one main clause plus at most one subordinate clause.",
+            CefrLevel::B2 => "Use general vocabulary, avoid rare or literary words, and split long sentences.",
+            CefrLevel::C1 => "Keep most of the original vocabulary but replace archaic or highly specialised words and split very long sentences.",
+            CefrLevel::C2 => "Stay close to the original; only smooth out the rarest words and most convoluted sentences.",
+        }
+    }
+}
+
+/// Result of checking simplified output against its level's limits.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VocabularyCheck {
+    pub level: CefrLevel,
+    pub passed: bool,
+    /// Sentences longer than the level allows.
+    pub long_sentences: u32,
+    /// Words outside the controlled vocabulary, deduplicated, in text order.
+    pub hard_words: Vec<String>,
+    /// Simplified translations tried; 2 when the first one failed the check.
+    pub attempts: u8,
+}
+
+impl VocabularyCheck {
+    fn violations(&self) -> usize {
+        self.long_sentences as usize + self.hard_words.len()
+    }
+
+    /// Whether `self` is a better result than `other`.
+    pub fn beats(&self, other: &VocabularyCheck) -> bool {
+        self.violations() < other.violations()
+    }
+}
+
+/// Check `text` (in `language`) against the limits of `level`. Unspaced
+/// scripts have no word-length signal, so only sentence length (counted in
+/// characters, at two per word) applies to them.
+pub fn check_vocabulary(text: &str, language: &str, level: CefrLevel) -> VocabularyCheck {
+    let unspaced = lemma::is_unspaced_script(language);
+
+    let long_sentences = match level.max_sentence_words() {
+        Some(max) => {
+            let limit = if unspaced { max * 2 } else { max };
+            split_into_segments(text)
+                .iter()
+                .filter(|s| lemma::words(s, language).len() > limit)
+                .count() as u32
+        }
+        None => 0,
+    };
+
+    let mut hard_words: Vec<String> = Vec::new();
+    if let Some(max_chars) = level.hard_word_chars().filter(|_| !unspaced) {
+        for word in lemma::words(text, language) {
+            // Capitalised words are mostly names, which cannot be simplified;
+            // this also skips sentence-initial words, an accepted miss.
+            if word.chars().next().is_some_and(char::is_uppercase) {
+                continue;
+            }
+            let word = word.to_lowercase();
+            if word.chars().count() >= max_chars && !hard_words.contains(&word) {
+                hard_words.push(word);
+            }
+        }
+    }
+
+    VocabularyCheck {
+        level,
+        passed: long_sentences == 0 && hard_words.is_empty(),
+        long_sentences,
+        hard_words,
+        attempts: 1,
+    }
+}
+
+/// Revision note for a second attempt after a failed check.
+pub fn retry_note(check: &VocabularyCheck) -> String {
+    let mut lines = Vec::new();
+    if check.long_sentences > 0 {
+        if let Some(max) = check.level.max_sentence_words() {
+            lines.push(format!("- Split sentences so none is longer than {} words.", max));
+        }
+    }
+    if !check.hard_words.is_empty() {
+        lines.push(format!(
+            "- Replace these words with simpler, more common ones: {}.",
+            check.hard_words.join(", ")
+        ));
+    }
+    format!(
+        "Your previous version was too hard for {} readers.\n{}",
+        check.level.as_str(),
+        lines.join("\n")
+    )
+}
@@ -0,0 +1,556 @@
+//! Voices for dialogue. Each block of a doc is split into narration and
+//! dialogue lines (quoted text, or a line opened by a dash), and a
+//! [`SpeakerMap`] says who says each line: the model attributes them, the
+//! learner corrects them and picks voices. Rendered audio then reads
+//! narration and unattributed lines with the narrator's voice and each
+//! speaker's lines with theirs. The map is kept on the translation entry as
+//! `speakers`.
+//!
+//! Lines are numbered per block, in the text the doc reads with; switching
+//! a span to a variant that quotes differently can leave a line with the
+//! wrong speaker until it is reassigned.
+
+use super::audio_types::VoiceInfo;
+use super::gui_types::InteractiveDoc;
+use super::stories::{now_ms, DocId, StoryDoc, StoryError};
+use super::text;
+use super::translation::{fill_anthropic_key, Client};
+use super::types::{ApiConfig, ApiError, LlmProviderConfig};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Most speakers a map holds, besides the narrator.
+pub const MAX_SPEAKERS: usize = 8;
+
+/// Characters of dialogue blocks sent to the model; lines past it stay
+/// with the narrator.
+const ATTRIBUTION_TEXT_LEN: usize = 12_000;
+
+/// Dashes that open a line of dialogue in French, Spanish, Russian…
+const DIALOGUE_DASHES: [char; 3] = ['—', '―', '–'];
+
+#[derive(Debug, thiserror::Error)]
+pub enum SpeakerError {
+    #[error("A doc can have at most {0} speakers")]
+    TooManySpeakers(usize),
+
+    #[error("Unknown speaker: {0}")]
+    UnknownSpeaker(String),
+
+    #[error("A speaker needs a name")]
+    EmptyName,
+
+    #[error("Block {block} has no dialogue line {line}")]
+    LineOutOfRange { block: u32, line: u32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PassageKind {
+    Narration,
+    Dialogue,
+}
+
+/// A run of a block's text, read by one voice.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Passage {
+    pub kind: PassageKind,
+    /// With its quotes or dash.
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Speaker {
+    /// `speaker-N`, stable across renames.
+    pub id: String,
+    pub name: String,
+    /// Picked for the speaker when unset; see [`SpeakerMap::voices`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voice: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeakerLine {
+    pub block: u32,
+    /// Index among the block's dialogue passages.
+    pub line: u32,
+    pub speaker: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeakerMap {
+    /// The language's default voice when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub narrator_voice: Option<String>,
+    #[serde(default)]
+    pub speakers: Vec<Speaker>,
+    /// Lines not listed are read by the narrator.
+    #[serde(default)]
+    pub lines: Vec<SpeakerLine>,
+    /// Model that attributed the lines, if one did.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub updated_at: u64,
+}
+
+/// A dialogue line of a doc and who says it, for the speaker editor.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DialogueLine {
+    pub block: u32,
+    pub line: u32,
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+}
+
+/// A speaker map with the dialogue lines it assigns.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeakerView {
+    pub map: SpeakerMap,
+    pub lines: Vec<DialogueLine>,
+}
+
+impl SpeakerView {
+    pub fn new(doc: &InteractiveDoc, map: SpeakerMap) -> Self {
+        Self {
+            lines: dialogue_lines(doc, &map),
+            map,
+        }
+    }
+}
+
+/// One change to a speaker map.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum SpeakerEdit {
+    SetNarratorVoice {
+        voice: Option<String>,
+    },
+    AddSpeaker {
+        name: String,
+        #[serde(default)]
+        voice: Option<String>,
+    },
+    /// Rename a speaker and set (or clear) their voice.
+    UpdateSpeaker {
+        id: String,
+        name: String,
+        #[serde(default)]
+        voice: Option<String>,
+    },
+    /// Their lines go back to the narrator.
+    RemoveSpeaker {
+        id: String,
+    },
+    /// Give a line to `speaker`, or back to the narrator when `None`.
+    AssignLine {
+        block: u32,
+        line: u32,
+        speaker: Option<String>,
+    },
+}
+
+/// The narration and dialogue of `block`, in order. A quote that is never
+/// closed is read as narration; whitespace between passages is dropped.
+pub fn split_dialogue(block: &str) -> Vec<Passage> {
+    let mut passages = Vec::new();
+    let mut push = |kind, text: &str| {
+        if !text.trim().is_empty() {
+            passages.push(Passage {
+                kind,
+                text: text.trim().to_string(),
+            });
+        }
+    };
+    let mut start = 0;
+    let mut closer: Option<char> = None;
+    let mut line_start = true;
+    for (i, c) in block.char_indices() {
+        match closer {
+            None => {
+                let close = if line_start && DIALOGUE_DASHES.contains(&c) {
+                    Some('\n')
+                } else {
+                    closing_quote(c)
+                };
+                let after = i + c.len_utf8();
+                if let Some(close) = close.filter(|&close| close == '\n' || block[after..].contains(close)) {
+                    push(PassageKind::Narration, &block[start..i]);
+                    start = i;
+                    closer = Some(close);
+                }
+            }
+            Some(close) if c == close => {
+                let end = if close == '\n' { i } else { i + c.len_utf8() };
+                push(PassageKind::Dialogue, &block[start..end]);
+                start = end;
+                closer = None;
+            }
+            Some(_) => {}
+        }
+        line_start = c == '\n' || (line_start && c.is_whitespace());
+    }
+    let kind = if closer.is_some() {
+        PassageKind::Dialogue
+    } else {
+        PassageKind::Narration
+    };
+    push(kind, &block[start..]);
+    passages
+}
+
+fn closing_quote(open: char) -> Option<char> {
+    match open {
+        '"' => Some('"'),
+        '“' => Some('”'),
+        '„' => Some('“'),
+        '«' => Some('»'),
+        '‹' => Some('›'),
+        '「' => Some('」'),
+        '『' => Some('』'),
+        _ => None,
+    }
+}
+
+/// The dialogue passages of a block.
+fn dialogue(block: &str) -> Vec<String> {
+    split_dialogue(block)
+        .into_iter()
+        .filter(|p| p.kind == PassageKind::Dialogue)
+        .map(|p| p.text)
+        .collect()
+}
+
+/// Every dialogue line of `doc`, with its speaker from `map`.
+pub fn dialogue_lines(doc: &InteractiveDoc, map: &SpeakerMap) -> Vec<DialogueLine> {
+    doc.block_texts()
+        .iter()
+        .enumerate()
+        .flat_map(|(block, text)| {
+            dialogue(text).into_iter().enumerate().map(move |(line, text)| DialogueLine {
+                block: block as u32,
+                line: line as u32,
+                text,
+                speaker: None,
+            })
+        })
+        .map(|mut line| {
+            line.speaker = map.speaker_of(line.block, line.line).map(str::to_string);
+            line
+        })
+        .collect()
+}
+
+impl SpeakerMap {
+    pub fn is_empty(&self) -> bool {
+        self.narrator_voice.is_none() && self.lines.is_empty()
+    }
+
+    /// Id of the speaker of a line, `None` for the narrator.
+    pub fn speaker_of(&self, block: u32, line: u32) -> Option<&str> {
+        self.lines
+            .iter()
+            .find(|l| l.block == block && l.line == line)
+            .map(|l| l.speaker.as_str())
+    }
+
+    /// Apply `edit`; lines are checked against `doc`. Nothing changes when
+    /// it fails.
+    pub fn apply(&mut self, edit: SpeakerEdit, doc: &InteractiveDoc) -> Result<(), SpeakerError> {
+        let clean = |voice: Option<String>| voice.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        match edit {
+            SpeakerEdit::SetNarratorVoice { voice } => self.narrator_voice = clean(voice),
+            SpeakerEdit::AddSpeaker { name, voice } => {
+                let id = self.add_speaker(&name)?;
+                if let Some(speaker) = self.speakers.iter_mut().find(|s| s.id == id) {
+                    speaker.voice = clean(voice);
+                }
+            }
+            SpeakerEdit::UpdateSpeaker { id, name, voice } => {
+                let speaker = self
+                    .speakers
+                    .iter_mut()
+                    .find(|s| s.id == id)
+                    .ok_or(SpeakerError::UnknownSpeaker(id))?;
+                if !name.trim().is_empty() {
+                    speaker.name = name.trim().to_string();
+                }
+                speaker.voice = clean(voice);
+            }
+            SpeakerEdit::RemoveSpeaker { id } => {
+                if !self.speakers.iter().any(|s| s.id == id) {
+                    return Err(SpeakerError::UnknownSpeaker(id));
+                }
+                self.speakers.retain(|s| s.id != id);
+                self.lines.retain(|l| l.speaker != id);
+            }
+            SpeakerEdit::AssignLine { block, line, speaker } => {
+                let blocks = doc.block_texts();
+                let lines = blocks.get(block as usize).map_or(0, |text| dialogue(text).len());
+                if line as usize >= lines {
+                    return Err(SpeakerError::LineOutOfRange { block, line });
+                }
+                if let Some(id) = speaker.as_ref().filter(|id| !self.speakers.iter().any(|s| &&s.id == id)) {
+                    return Err(SpeakerError::UnknownSpeaker(id.clone()));
+                }
+                self.lines.retain(|l| !(l.block == block && l.line == line));
+                if let Some(speaker) = speaker {
+                    self.lines.push(SpeakerLine { block, line, speaker });
+                    self.lines.sort_by_key(|l| (l.block, l.line));
+                }
+            }
+        }
+        self.updated_at = now_ms();
+        Ok(())
+    }
+
+    /// Add a speaker named `name`, or find the one already called that.
+    /// Returns the id.
+    fn add_speaker(&mut self, name: &str) -> Result<String, SpeakerError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(SpeakerError::EmptyName);
+        }
+        if let Some(speaker) = self.speakers.iter().find(|s| s.name.eq_ignore_ascii_case(name)) {
+            return Ok(speaker.id.clone());
+        }
+        if self.speakers.len() >= MAX_SPEAKERS {
+            return Err(SpeakerError::TooManySpeakers(MAX_SPEAKERS));
+        }
+        let next = (1..).find(|n| !self.speakers.iter().any(|s| s.id == format!("speaker-{}", n)));
+        let id = format!("speaker-{}", next.unwrap_or_default());
+        self.speakers.push(Speaker {
+            id: id.clone(),
+            name: name.to_string(),
+            voice: None,
+        });
+        Ok(id)
+    }
+
+    /// The voice of each speaker: their own, else one of `available` that
+    /// no one else reads with, voices of `language` first. Voices are
+    /// shared round-robin only when there are more speakers than voices.
+    pub fn voices(&self, narrator: &str, available: &[VoiceInfo], language: &str) -> HashMap<String, String> {
+        let base = |l: &str| l.split('-').next().unwrap_or_default().to_ascii_lowercase();
+        let mut pool: Vec<&str> = available
+            .iter()
+            .filter(|v| base(&v.language) == base(language))
+            .chain(available.iter().filter(|v| base(&v.language) != base(language)))
+            .map(|v| v.id.as_str())
+            .filter(|id| *id != narrator && !self.speakers.iter().any(|s| s.voice.as_deref() == Some(id)))
+            .collect();
+        if pool.is_empty() {
+            pool.push(narrator);
+        }
+        let mut unvoiced = 0;
+        self.speakers
+            .iter()
+            .map(|s| {
+                let voice = s.voice.clone().unwrap_or_else(|| {
+                    unvoiced += 1;
+                    pool[(unvoiced - 1) % pool.len()].to_string()
+                });
+                (s.id.clone(), voice)
+            })
+            .collect()
+    }
+
+    /// `text`, block `block` of the doc, cut where the voice changes: each
+    /// piece with the voice that reads it. `voices` is from
+    /// [`SpeakerMap::voices`].
+    pub fn voice_parts(
+        &self,
+        block: u32,
+        text: &str,
+        narrator: &str,
+        voices: &HashMap<String, String>,
+    ) -> Vec<(String, String)> {
+        let mut parts: Vec<(String, String)> = Vec::new();
+        let mut line = 0;
+        for passage in split_dialogue(text) {
+            let voice = match passage.kind {
+                PassageKind::Narration => narrator,
+                PassageKind::Dialogue => {
+                    line += 1;
+                    self.speaker_of(block, line - 1)
+                        .and_then(|id| voices.get(id))
+                        .map_or(narrator, String::as_str)
+                }
+            };
+            match parts.last_mut() {
+                Some((last, last_voice)) if last_voice == voice => {
+                    last.push(' ');
+                    last.push_str(&passage.text);
+                }
+                _ => parts.push((passage.text, voice.to_string())),
+            }
+        }
+        parts
+    }
+}
+
+/// The speaker map stored on a translation; an empty one when there is
+/// none or it no longer parses.
+pub fn speaker_map(stories: &Value, doc_id: &DocId) -> Result<SpeakerMap, StoryError> {
+    let translation = stories
+        .as_array()
+        .ok_or_else(|| StoryError::Parse("stories.json is not an array".to_string()))?
+        .iter()
+        .find(|s| s.get("id").and_then(Value::as_str) == Some(doc_id.story_id.as_str()))
+        .and_then(|s| s.get("translations")?.get(&doc_id.language))
+        .ok_or_else(|| StoryError::NotFound(doc_id.to_string()))?;
+    Ok(translation
+        .get("speakers")
+        .and_then(|m| serde_json::from_value(m.clone()).ok())
+        .unwrap_or_default())
+}
+
+/// Store a speaker map on a translation. Only `stories` is modified; the
+/// caller saves it.
+pub fn set_speaker_map(stories: &mut Value, doc_id: &DocId, map: &SpeakerMap) -> Result<(), StoryError> {
+    let translation = stories
+        .as_array_mut()
+        .ok_or_else(|| StoryError::Parse("stories.json is not an array".to_string()))?
+        .iter_mut()
+        .find(|s| s.get("id").and_then(Value::as_str) == Some(doc_id.story_id.as_str()))
+        .and_then(|s| s.get_mut("translations"))
+        .and_then(|t| t.get_mut(&doc_id.language))
+        .filter(|t| t.is_object())
+        .ok_or_else(|| StoryError::NotFound(doc_id.to_string()))?;
+    translation["speakers"] = serde_json::to_value(map).map_err(|e| StoryError::Parse(e.to_string()))?;
+    Ok(())
+}
+
+/// Ask the model who says each dialogue line of `story`, with at most
+/// `max_speakers` speakers (capped at [`MAX_SPEAKERS`]). The narrator's
+/// voice and the voices of speakers the model names again are kept from
+/// `previous`; earlier line assignments are replaced.
+pub async fn attribute(
+    story: &StoryDoc,
+    previous: &SpeakerMap,
+    max_speakers: usize,
+    provider: LlmProviderConfig,
+) -> Result<SpeakerMap, ApiError> {
+    let max_speakers = max_speakers.clamp(1, MAX_SPEAKERS);
+    let mut map = SpeakerMap {
+        narrator_voice: previous.narrator_voice.clone(),
+        ..Default::default()
+    };
+    let blocks = dialogue_blocks(&story.doc);
+    let lines: Vec<(String, String)> = blocks
+        .iter()
+        .flat_map(|(block, text)| {
+            dialogue(text).into_iter().enumerate().map(move |(i, line)| (format!("{}.{}", block, i), line))
+        })
+        .collect();
+    if lines.is_empty() {
+        map.updated_at = now_ms();
+        return Ok(map);
+    }
+
+    let source_language = Some(story.source_language.as_str()).filter(|l| !l.is_empty());
+    let mut cfg = ApiConfig::from_env(&story.language, source_language, false, false);
+    cfg.provider = provider;
+    fill_anthropic_key(&mut cfg);
+    let client = Client::new(cfg)?;
+    let (reply, _) = client.attribute_speakers(&blocks, &lines, max_speakers).await?;
+
+    for (line, name) in parse_attribution(&reply)? {
+        let Some((block, index)) = line.split_once('.') else {
+            continue;
+        };
+        let (Ok(block), Ok(index)) = (block.trim().parse::<u32>(), index.trim().parse::<u32>()) else {
+            continue;
+        };
+        // Lines the model made up, and speakers past the cap, stay with the narrator.
+        let asked = lines.iter().any(|(id, _)| *id == format!("{}.{}", block, index));
+        if !asked || map.speaker_of(block, index).is_some() {
+            continue;
+        }
+        let known = map.speakers.iter().any(|s| s.name.eq_ignore_ascii_case(&name));
+        if !known && map.speakers.len() >= max_speakers {
+            continue;
+        }
+        let Ok(id) = map.add_speaker(&name) else {
+            continue;
+        };
+        map.lines.push(SpeakerLine {
+            block,
+            line: index,
+            speaker: id,
+        });
+    }
+    for speaker in &mut map.speakers {
+        speaker.voice = previous
+            .speakers
+            .iter()
+            .find(|s| s.name.eq_ignore_ascii_case(&speaker.name))
+            .and_then(|s| s.voice.clone());
+    }
+    map.lines.sort_by_key(|l| (l.block, l.line));
+    map.model = Some(client.model().to_string());
+    map.updated_at = now_ms();
+    Ok(map)
+}
+
+/// The blocks that have dialogue, as `(index, text)`, up to
+/// [`ATTRIBUTION_TEXT_LEN`].
+fn dialogue_blocks(doc: &InteractiveDoc) -> Vec<(u32, String)> {
+    let mut blocks = Vec::new();
+    let mut used = 0;
+    for (block, text) in doc.block_texts().into_iter().enumerate() {
+        if dialogue(&text).is_empty() {
+            continue;
+        }
+        used += text.chars().count();
+        if used > ATTRIBUTION_TEXT_LEN && !blocks.is_empty() {
+            break;
+        }
+        blocks.push((block as u32, text));
+    }
+    blocks
+}
+
+/// Parse `{ "lines": [{ "line": "3.0", "speaker": "Anna" }] }`, tolerating
+/// code fences. Lines with no speaker name are the narrator's and left out.
+fn parse_attribution(reply: &str) -> Result<Vec<(String, String)>, ApiError> {
+    let cleaned = reply
+        .trim()
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim();
+    let value: Value = serde_json::from_str(cleaned).map_err(|e| {
+        ApiError::Parse(format!(
+            "Speaker JSON parse: {} | output: {}",
+            e,
+            text::excerpt(cleaned, text::EXCERPT_LEN)
+        ))
+    })?;
+    let lines = value.get("lines").and_then(Value::as_array).ok_or_else(|| {
+        ApiError::Parse(format!(
+            "Speaker JSON parse: missing `lines` | output: {}",
+            text::excerpt(cleaned, text::EXCERPT_LEN)
+        ))
+    })?;
+    Ok(lines
+        .iter()
+        .filter_map(|l| {
+            let line = match l.get("line")? {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            let speaker = l.get("speaker")?.as_str()?.trim();
+            (!speaker.is_empty()).then(|| (line, speaker.to_string()))
+        })
+        .collect())
+}
@@ -0,0 +1,130 @@
+//! Speech for the sentences the reader is about to reach, synthesized
+//! before playback asks for them. The reader reports its position as it
+//! moves; [`SpeechPrefetcher::next`] hands out the next sentence past it
+//! that has not been synthesized yet, so work follows the reader instead of
+//! rendering the whole doc up front. [`PrefetchPolicy::WholeDoc`] keeps
+//! going to the end of the doc for users who would rather wait once.
+//!
+//! The prefetcher is plain state; the app synthesizes into the audio cache,
+//! where playback of the same text, voice and speed finds it.
+
+use super::gui_types::InteractiveDoc;
+use super::translation::split_into_segments;
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Sentences synthesized past the reading position under
+/// [`PrefetchPolicy::Ahead`].
+pub const AHEAD_SENTENCES: usize = 3;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PrefetchPolicy {
+    Off,
+    /// The next [`AHEAD_SENTENCES`] sentences.
+    #[default]
+    Ahead,
+    /// Everything past the reading position.
+    WholeDoc,
+}
+
+impl PrefetchPolicy {
+    /// How many sentences past the position to synthesize.
+    pub fn window(self) -> usize {
+        match self {
+            PrefetchPolicy::Off => 0,
+            PrefetchPolicy::Ahead => AHEAD_SENTENCES,
+            PrefetchPolicy::WholeDoc => usize::MAX,
+        }
+    }
+}
+
+/// Where the reader is, and how it plays speech.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadingPosition {
+    /// `<storyId>:<language>`.
+    pub doc_id: String,
+    /// Block (paragraph) index, as in [`InteractiveDoc::block_texts`].
+    pub block: u32,
+    /// Sentence within the block.
+    #[serde(default)]
+    pub sentence: u32,
+    /// The language's default voice when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voice_id: Option<String>,
+    #[serde(default = "default_speed")]
+    pub speed: f32,
+}
+
+fn default_speed() -> f32 {
+    1.0
+}
+
+/// One sentence of a doc, as playback speaks it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocSentence {
+    pub block: u32,
+    pub sentence: u32,
+    pub text: String,
+}
+
+/// The doc's sentences in reading order, with the active variants.
+pub fn doc_sentences(doc: &InteractiveDoc) -> Vec<DocSentence> {
+    let mut sentences = Vec::new();
+    for (block, text) in doc.block_texts().iter().enumerate() {
+        for (sentence, text) in split_into_segments(text).into_iter().enumerate() {
+            sentences.push(DocSentence {
+                block: block as u32,
+                sentence: sentence as u32,
+                text,
+            });
+        }
+    }
+    sentences
+}
+
+#[derive(Debug, Default)]
+pub struct SpeechPrefetcher {
+    position: Option<ReadingPosition>,
+    /// Texts synthesized (or given up on) for the current doc, voice and speed.
+    done: HashSet<String>,
+}
+
+impl SpeechPrefetcher {
+    pub fn position(&self) -> Option<&ReadingPosition> {
+        self.position.as_ref()
+    }
+
+    /// Move to `position`. Another doc, voice or speed starts over; moving
+    /// within the doc keeps what is done, since it is still cached.
+    pub fn report(&mut self, position: ReadingPosition) {
+        let same = self.position.as_ref().is_some_and(|current| {
+            current.doc_id == position.doc_id
+                && current.voice_id == position.voice_id
+                && current.speed == position.speed
+        });
+        if !same {
+            self.done.clear();
+        }
+        self.position = Some(position);
+    }
+
+    /// The first sentence past the position, within `policy`'s window, that
+    /// is not done. `sentences` are the current doc's, so an edit since the
+    /// last call (another variant picked) is picked up.
+    pub fn next(&self, sentences: &[DocSentence], policy: PrefetchPolicy) -> Option<DocSentence> {
+        let position = self.position.as_ref()?;
+        sentences
+            .iter()
+            .filter(|s| (s.block, s.sentence) > (position.block, position.sentence))
+            .take(policy.window())
+            .find(|s| !self.done.contains(&s.text))
+            .cloned()
+    }
+
+    pub fn mark_done(&mut self, text: &str) {
+        self.done.insert(text.to_string());
+    }
+}
@@ -0,0 +1,168 @@
+//! Spans and sentences the learner starred while reading. Stars are kept on
+//! the doc's translation entry as `starred`, so they travel with the story
+//! and go when it is deleted; [`starred_items`] gathers them across the
+//! library, with their current text, for flashcards.
+
+use super::speech_prefetch::doc_sentences;
+use super::stories::{self, now_ms, DocId, StoryDoc, StoryError};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum StarTarget {
+    #[serde(rename_all = "camelCase")]
+    Span { span_id: String },
+    /// A sentence of a block, numbered as in
+    /// [`doc_sentences`](super::speech_prefetch::doc_sentences).
+    Sentence { block: u32, sentence: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Star {
+    #[serde(flatten)]
+    pub target: StarTarget,
+    pub starred_at: u64,
+}
+
+/// A star with what it points at, as it reads now.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StarredItem {
+    pub doc_id: String,
+    pub title: String,
+    pub language: String,
+    #[serde(flatten)]
+    pub target: StarTarget,
+    /// The span's active variant, or the sentence.
+    pub text: String,
+    /// The span's source text, or the source of the sentence's segment;
+    /// empty when unknown.
+    pub source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub starred_at: u64,
+}
+
+/// Star or unstar `target` in a doc and return the doc's stars. Only a
+/// span or sentence the doc has can be starred; unstarring always works.
+/// Only `stories` is modified; the caller saves it. Stars are not an edit
+/// of the story, so `updatedAt` is left alone.
+pub fn set_star(
+    stories: &mut Value,
+    doc_id: &DocId,
+    target: StarTarget,
+    starred: bool,
+) -> Result<Vec<Star>, StoryError> {
+    if starred {
+        let story = stories::find_doc(stories, doc_id)?;
+        if resolve(&story, &target).is_none() {
+            let what = match &target {
+                StarTarget::Span { span_id } => format!("span {}", span_id),
+                StarTarget::Sentence { block, sentence } => format!("sentence {} of block {}", sentence, block),
+            };
+            return Err(StoryError::NotFound(format!("{} in {}", what, doc_id)));
+        }
+    }
+
+    let translation = stories
+        .as_array_mut()
+        .ok_or_else(|| StoryError::Parse("stories.json is not an array".to_string()))?
+        .iter_mut()
+        .find(|s| s.get("id").and_then(Value::as_str) == Some(doc_id.story_id.as_str()))
+        .and_then(|s| s.get_mut("translations"))
+        .and_then(|t| t.get_mut(&doc_id.language))
+        .filter(|t| t.is_object())
+        .ok_or_else(|| StoryError::NotFound(doc_id.to_string()))?;
+    let mut stars = stars_of(translation);
+    stars.retain(|s| s.target != target);
+    if starred {
+        stars.push(Star {
+            target,
+            starred_at: now_ms(),
+        });
+    }
+    translation["starred"] = serde_json::to_value(&stars).map_err(|e| StoryError::Parse(e.to_string()))?;
+    Ok(stars)
+}
+
+/// Stars as stored on a translation entry; ones that no longer parse are
+/// dropped.
+pub fn stars_of(translation: &Value) -> Vec<Star> {
+    translation
+        .get("starred")
+        .and_then(Value::as_array)
+        .map(|stars| stars.iter().filter_map(|s| serde_json::from_value(s.clone()).ok()).collect())
+        .unwrap_or_default()
+}
+
+/// Every star in the library, or in docs in `language`, newest first. Stars
+/// on spans or sentences the doc no longer has are left out.
+pub fn starred_items(stories: &Value, language: Option<&str>) -> Vec<StarredItem> {
+    let mut items = Vec::new();
+    for story in stories.as_array().into_iter().flatten() {
+        let story_id = story.get("id").and_then(Value::as_str).unwrap_or_default();
+        let translations = story.get("translations").and_then(Value::as_object);
+        for (doc_language, translation) in translations.into_iter().flatten() {
+            if language.is_some_and(|l| l != doc_language) {
+                continue;
+            }
+            let stars = stars_of(translation);
+            if stars.is_empty() {
+                continue;
+            }
+            let doc_id = DocId {
+                story_id: story_id.to_string(),
+                language: doc_language.clone(),
+            };
+            let Ok(doc) = stories::find_doc(stories, &doc_id) else {
+                continue;
+            };
+            for star in stars {
+                let Some((text, source, note)) = resolve(&doc, &star.target) else {
+                    continue;
+                };
+                items.push(StarredItem {
+                    doc_id: doc_id.to_string(),
+                    title: doc.title.clone(),
+                    language: doc.language.clone(),
+                    target: star.target,
+                    text,
+                    source,
+                    note,
+                    starred_at: star.starred_at,
+                });
+            }
+        }
+    }
+    items.sort_by_key(|item| std::cmp::Reverse(item.starred_at));
+    items
+}
+
+/// Text, source and note of what `target` points at in `story`.
+fn resolve(story: &StoryDoc, target: &StarTarget) -> Option<(String, String, Option<String>)> {
+    match target {
+        StarTarget::Span { span_id } => {
+            let span = story.doc.spans.get(span_id)?;
+            let note = span.variants.get(span.active_variant_index).and_then(|v| v.note.clone());
+            Some((span.active_text()?.trim().to_string(), span.source_text.trim().to_string(), note))
+        }
+        StarTarget::Sentence { block, sentence } => {
+            let found = doc_sentences(&story.doc)
+                .into_iter()
+                .find(|s| s.block == *block && s.sentence == *sentence)?;
+            // Blocks are the job's segments, one each, unless the doc was
+            // reshaped since.
+            let source = story
+                .job
+                .as_ref()
+                .filter(|job| job.segments.len() == story.doc.block_texts().len())
+                .and_then(|job| job.segments.get(*block as usize))
+                .map(|segment| segment.source.trim().to_string())
+                .unwrap_or_default();
+            Some((found.text, source, None))
+        }
+    }
+}
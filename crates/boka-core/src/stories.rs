@@ -0,0 +1,571 @@
+use super::gui_types::{InteractiveDoc, TranslationJob};
+use super::naming;
+use super::paths;
+use super::policy::ALL_REGISTERS;
+use super::practice_plan::PracticePlan;
+use super::story_meta::StoryMeta;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const STORIES_FILE: &str = "stories.json";
+
+#[derive(Debug, thiserror::Error)]
+pub enum StoryError {
+    #[error("Stories I/O error: {0}")]
+    Io(String),
+
+    #[error("Failed to parse stories: {0}")]
+    Parse(String),
+
+    #[error("Invalid doc id `{0}`: expected <storyId>:<language>")]
+    InvalidDocId(String),
+
+    #[error("No document for {0}")]
+    NotFound(String),
+
+    #[error("Unknown register `{0}`")]
+    UnknownRegister(String),
+
+    #[error("Block {block} is out of range for {doc} ({blocks} blocks)")]
+    BlockOutOfRange { doc: String, block: u32, blocks: usize },
+}
+
+/// A story's translation into one language; written `<storyId>:<language>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocId {
+    pub story_id: String,
+    pub language: String,
+}
+
+impl DocId {
+    pub fn parse(raw: &str) -> Result<Self, StoryError> {
+        // Language codes never contain ':', story ids might.
+        match raw.rsplit_once(':') {
+            Some((story, lang)) if !story.is_empty() && !lang.is_empty() => Ok(Self {
+                story_id: story.to_string(),
+                language: lang.to_string(),
+            }),
+            _ => Err(StoryError::InvalidDocId(raw.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for DocId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.story_id, self.language)
+    }
+}
+
+/// `stories.json`, shared by the GUI and TUI.
+pub fn path(data_dir: &Path) -> PathBuf {
+    data_dir.join(STORIES_FILE)
+}
+
+/// All stories; an empty array when the file does not exist yet. Kept as
+/// raw JSON so fields this crate does not model survive a round trip.
+pub fn load(data_dir: &Path) -> Result<Value, StoryError> {
+    let path = path(data_dir);
+    if !path.exists() {
+        return Ok(Value::Array(vec![]));
+    }
+    let raw = fs::read_to_string(&path).map_err(|e| StoryError::Io(e.to_string()))?;
+    serde_json::from_str(&raw).map_err(|e| StoryError::Parse(e.to_string()))
+}
+
+/// Atomic write: tmp file, then rename.
+pub fn save(data_dir: &Path, stories: &Value) -> Result<(), StoryError> {
+    let json = serde_json::to_string_pretty(stories).map_err(|e| StoryError::Parse(e.to_string()))?;
+    let renamed = paths::write_atomic(&path(data_dir), json.as_bytes())
+        .map_err(|e| StoryError::Io(e.to_string()));
+    SAVES.fetch_add(1, Ordering::SeqCst);
+    renamed
+}
+
+/// Saves made by this process, so caches notice writes that leave the
+/// file's size and modification time as they were.
+static SAVES: AtomicU64 = AtomicU64::new(0);
+
+pub(crate) fn save_count() -> u64 {
+    SAVES.load(Ordering::SeqCst)
+}
+
+/// A saved doc plus what exports need from its story.
+#[derive(Debug, Clone)]
+pub struct StoryDoc {
+    pub story_id: String,
+    pub title: String,
+    /// The story's file-safe name; see [`naming`](super::naming).
+    pub slug: String,
+    pub source_text: String,
+    pub source_language: String,
+    pub language: String,
+    pub doc: InteractiveDoc,
+    pub job: Option<TranslationJob>,
+}
+
+pub fn find_doc(stories: &Value, doc_id: &DocId) -> Result<StoryDoc, StoryError> {
+    let story = stories
+        .as_array()
+        .ok_or_else(|| StoryError::Parse("stories.json is not an array".to_string()))?
+        .iter()
+        .find(|s| s.get("id").and_then(Value::as_str) == Some(doc_id.story_id.as_str()))
+        .ok_or_else(|| StoryError::NotFound(doc_id.to_string()))?;
+    let translation = story
+        .get("translations")
+        .and_then(|t| t.get(&doc_id.language))
+        .ok_or_else(|| StoryError::NotFound(doc_id.to_string()))?;
+
+    let doc = translation
+        .get("doc")
+        .filter(|d| !d.is_null())
+        .ok_or_else(|| StoryError::NotFound(doc_id.to_string()))?;
+    let doc: InteractiveDoc = serde_json::from_value(doc.clone()).map_err(|e| StoryError::Parse(e.to_string()))?;
+    // A job that no longer parses only loses export extras, not the export.
+    let job = translation
+        .get("job")
+        .and_then(|j| serde_json::from_value::<TranslationJob>(j.clone()).ok());
+
+    let field = |name: &str| story.get(name).and_then(Value::as_str).unwrap_or_default().to_string();
+    Ok(StoryDoc {
+        story_id: doc_id.story_id.clone(),
+        title: field("title"),
+        slug: Some(field("slug"))
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| naming::slugify(&field("title"))),
+        source_text: field("sourceText"),
+        source_language: field("sourceLanguage"),
+        language: doc_id.language.clone(),
+        doc,
+        job,
+    })
+}
+
+/// Store `doc` as the story's translation into `doc.language`, replacing any
+/// existing one. A story that does not exist yet is created from `doc`'s
+/// title and source; an existing story keeps its own.
+pub fn put_doc(stories: &mut Value, doc: &StoryDoc) -> Result<DocId, StoryError> {
+    let list = stories
+        .as_array_mut()
+        .ok_or_else(|| StoryError::Parse("stories.json is not an array".to_string()))?;
+    let now = now_ms();
+    let translation = serde_json::json!({
+        "language": doc.language,
+        "createdAt": now,
+        "job": serde_json::to_value(&doc.job).map_err(|e| StoryError::Parse(e.to_string()))?,
+        "doc": serde_json::to_value(&doc.doc).map_err(|e| StoryError::Parse(e.to_string()))?,
+    });
+
+    let index = match list
+        .iter()
+        .position(|s| s.get("id").and_then(Value::as_str) == Some(doc.story_id.as_str()))
+    {
+        Some(i) => i,
+        None => {
+            list.push(serde_json::json!({
+                "id": doc.story_id,
+                "title": doc.title,
+                "category": null,
+                "createdAt": now,
+                "updatedAt": now,
+                "sourceText": doc.source_text,
+                "sourceLanguage": doc.source_language,
+                "translations": {},
+            }));
+            list.len() - 1
+        }
+    };
+    let story = &mut list[index];
+    if !story.get("translations").is_some_and(Value::is_object) {
+        story["translations"] = serde_json::json!({});
+    }
+    story["translations"][&doc.language] = translation;
+    story["updatedAt"] = Value::from(now);
+
+    Ok(DocId {
+        story_id: doc.story_id.clone(),
+        language: doc.language.clone(),
+    })
+}
+
+/// The doc whose translation was made by job `job_id`.
+pub fn find_job_doc(stories: &Value, job_id: &str) -> Result<StoryDoc, StoryError> {
+    let doc_id = stories
+        .as_array()
+        .ok_or_else(|| StoryError::Parse("stories.json is not an array".to_string()))?
+        .iter()
+        .find_map(|story| {
+            let (language, _) = story
+                .get("translations")?
+                .as_object()?
+                .iter()
+                .find(|(_, t)| t.get("job").and_then(|j| j.get("id")).and_then(Value::as_str) == Some(job_id))?;
+            Some(DocId {
+                story_id: story.get("id")?.as_str()?.to_string(),
+                language: language.clone(),
+            })
+        })
+        .ok_or_else(|| StoryError::NotFound(format!("job {}", job_id)))?;
+    find_doc(stories, &doc_id)
+}
+
+/// Replace a story's source text, as when it was continued. Its other
+/// translations keep covering the text they were made from. Only `stories`
+/// is modified; the caller saves it.
+pub fn set_source_text(stories: &mut Value, story_id: &str, source_text: &str) -> Result<(), StoryError> {
+    let story = stories
+        .as_array_mut()
+        .ok_or_else(|| StoryError::Parse("stories.json is not an array".to_string()))?
+        .iter_mut()
+        .find(|s| s.get("id").and_then(Value::as_str) == Some(story_id))
+        .ok_or_else(|| StoryError::NotFound(story_id.to_string()))?;
+    story["sourceText"] = Value::from(source_text);
+    story["updatedAt"] = Value::from(now_ms());
+    Ok(())
+}
+
+/// Replace the job and doc of an existing translation, keeping the rest of
+/// its entry (creation time, listening position). Only `stories` is
+/// modified; the caller saves it.
+pub fn update_translation(
+    stories: &mut Value,
+    doc_id: &DocId,
+    job: &TranslationJob,
+    doc: &InteractiveDoc,
+) -> Result<(), StoryError> {
+    let story = stories
+        .as_array_mut()
+        .ok_or_else(|| StoryError::Parse("stories.json is not an array".to_string()))?
+        .iter_mut()
+        .find(|s| s.get("id").and_then(Value::as_str) == Some(doc_id.story_id.as_str()))
+        .ok_or_else(|| StoryError::NotFound(doc_id.to_string()))?;
+    let translation = story
+        .get_mut("translations")
+        .and_then(|t| t.get_mut(&doc_id.language))
+        .ok_or_else(|| StoryError::NotFound(doc_id.to_string()))?;
+    translation["job"] = serde_json::to_value(job).map_err(|e| StoryError::Parse(e.to_string()))?;
+    translation["doc"] = serde_json::to_value(doc).map_err(|e| StoryError::Parse(e.to_string()))?;
+    story["updatedAt"] = Value::from(now_ms());
+    Ok(())
+}
+
+/// Replace the doc of an existing translation, keeping its job and the
+/// rest of its entry. Only `stories` is modified; the caller saves it.
+pub fn set_doc(stories: &mut Value, doc_id: &DocId, doc: &InteractiveDoc) -> Result<(), StoryError> {
+    let story = stories
+        .as_array_mut()
+        .ok_or_else(|| StoryError::Parse("stories.json is not an array".to_string()))?
+        .iter_mut()
+        .find(|s| s.get("id").and_then(Value::as_str) == Some(doc_id.story_id.as_str()))
+        .ok_or_else(|| StoryError::NotFound(doc_id.to_string()))?;
+    let translation = story
+        .get_mut("translations")
+        .and_then(|t| t.get_mut(&doc_id.language))
+        .ok_or_else(|| StoryError::NotFound(doc_id.to_string()))?;
+    translation["doc"] = serde_json::to_value(doc).map_err(|e| StoryError::Parse(e.to_string()))?;
+    story["updatedAt"] = Value::from(now_ms());
+    Ok(())
+}
+
+pub(crate) fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64)
+}
+
+/// Switch every span of one doc to `register` (see
+/// [`InteractiveDoc::switch_register`]) and return the updated doc. Only
+/// `stories` is modified; the caller saves it.
+pub fn set_doc_register(stories: &mut Value, doc_id: &DocId, register: &str) -> Result<InteractiveDoc, StoryError> {
+    let register = register.trim().to_lowercase();
+    if !ALL_REGISTERS.contains(&register.as_str()) {
+        return Err(StoryError::UnknownRegister(register));
+    }
+
+    let story = stories
+        .as_array_mut()
+        .ok_or_else(|| StoryError::Parse("stories.json is not an array".to_string()))?
+        .iter_mut()
+        .find(|s| s.get("id").and_then(Value::as_str) == Some(doc_id.story_id.as_str()))
+        .ok_or_else(|| StoryError::NotFound(doc_id.to_string()))?;
+
+    let slot = story
+        .get_mut("translations")
+        .and_then(|t| t.get_mut(&doc_id.language))
+        .and_then(|t| t.get_mut("doc"))
+        .filter(|d| !d.is_null())
+        .ok_or_else(|| StoryError::NotFound(doc_id.to_string()))?;
+
+    let mut doc: InteractiveDoc =
+        serde_json::from_value(slot.clone()).map_err(|e| StoryError::Parse(e.to_string()))?;
+    if doc.switch_register(&register) > 0 {
+        *slot = serde_json::to_value(&doc).map_err(|e| StoryError::Parse(e.to_string()))?;
+        story["updatedAt"] = Value::from(now_ms());
+    }
+    Ok(doc)
+}
+
+/// Where listening stopped in a doc: a block (paragraph) index and an
+/// offset into that block's audio. Saved in the doc's translation entry as
+/// `listeningPosition`, so it travels with the story.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListeningPosition {
+    pub block_id: u32,
+    pub ms: u64,
+    /// When it was saved, so copies of the library can keep the newest.
+    pub updated_at: u64,
+}
+
+/// Record the listening position for a doc and return it. Only `stories`
+/// is modified; the caller saves it. This is not an edit of the story, so
+/// `updatedAt` is left alone.
+pub fn set_listening_position(
+    stories: &mut Value,
+    doc_id: &DocId,
+    block_id: u32,
+    ms: u64,
+) -> Result<ListeningPosition, StoryError> {
+    let blocks = find_doc(stories, doc_id)?.doc.block_texts().len();
+    if block_id as usize >= blocks {
+        return Err(StoryError::BlockOutOfRange {
+            doc: doc_id.to_string(),
+            block: block_id,
+            blocks,
+        });
+    }
+    let position = ListeningPosition {
+        block_id,
+        ms,
+        updated_at: now_ms(),
+    };
+
+    let translation = stories
+        .as_array_mut()
+        .and_then(|list| {
+            list.iter_mut()
+                .find(|s| s.get("id").and_then(Value::as_str) == Some(doc_id.story_id.as_str()))
+        })
+        .and_then(|s| s.get_mut("translations"))
+        .and_then(|t| t.get_mut(&doc_id.language))
+        .ok_or_else(|| StoryError::NotFound(doc_id.to_string()))?;
+    translation["listeningPosition"] =
+        serde_json::to_value(position).map_err(|e| StoryError::Parse(e.to_string()))?;
+    Ok(position)
+}
+
+/// The saved listening position, if any. One that no longer parses, or
+/// points past the doc's last block, counts as none.
+pub fn listening_position(stories: &Value, doc_id: &DocId) -> Result<Option<ListeningPosition>, StoryError> {
+    let blocks = find_doc(stories, doc_id)?.doc.block_texts().len();
+    Ok(stories
+        .as_array()
+        .and_then(|list| {
+            list.iter()
+                .find(|s| s.get("id").and_then(Value::as_str) == Some(doc_id.story_id.as_str()))
+        })
+        .and_then(|s| s.get("translations")?.get(&doc_id.language)?.get("listeningPosition"))
+        .and_then(|p| serde_json::from_value::<ListeningPosition>(p.clone()).ok())
+        .filter(|p| (p.block_id as usize) < blocks))
+}
+
+/// Copy listening positions from `saved` into `stories` wherever `stories`
+/// has none or an older one, so writing back a stale copy of the library
+/// does not lose positions recorded since it was read.
+pub fn keep_listening_positions(stories: &mut Value, saved: &Value) {
+    let (Some(list), Some(saved)) = (stories.as_array_mut(), saved.as_array()) else {
+        return;
+    };
+    let updated_at = |p: Option<&Value>| p.and_then(|p| p.get("updatedAt")).and_then(Value::as_u64);
+    for story in list {
+        let Some(old) = saved
+            .iter()
+            .find(|s| s.get("id").is_some_and(|id| Some(id) == story.get("id")))
+            .and_then(|s| s.get("translations"))
+            .and_then(Value::as_object)
+        else {
+            continue;
+        };
+        let Some(translations) = story.get_mut("translations").and_then(Value::as_object_mut) else {
+            continue;
+        };
+        for (language, translation) in translations.iter_mut() {
+            let Some(position) = old.get(language).and_then(|t| t.get("listeningPosition")) else {
+                continue;
+            };
+            if !translation.is_object() {
+                continue;
+            }
+            let current = translation.get("listeningPosition");
+            if updated_at(Some(position)) > updated_at(current) {
+                translation["listeningPosition"] = position.clone();
+            }
+        }
+    }
+}
+
+/// Replace the tags of a story, or of its translation into `language`, and
+/// return them as stored: trimmed, lowercased, without blanks or repeats.
+/// Only `stories` is modified; the caller saves it. Tags are not an edit of
+/// the story, so `updatedAt` is left alone.
+pub fn set_tags(
+    stories: &mut Value,
+    story_id: &str,
+    language: Option<&str>,
+    tags: &[String],
+) -> Result<Vec<String>, StoryError> {
+    let story = stories
+        .as_array_mut()
+        .ok_or_else(|| StoryError::Parse("stories.json is not an array".to_string()))?
+        .iter_mut()
+        .find(|s| s.get("id").and_then(Value::as_str) == Some(story_id))
+        .ok_or_else(|| StoryError::NotFound(story_id.to_string()))?;
+    let target = match language {
+        Some(language) => story
+            .get_mut("translations")
+            .and_then(|t| t.get_mut(language))
+            .filter(|t| t.is_object())
+            .ok_or_else(|| StoryError::NotFound(format!("{}:{}", story_id, language)))?,
+        None => story,
+    };
+
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    target["tags"] = Value::from(normalized.clone());
+    Ok(normalized)
+}
+
+/// Tags as stored on a story or translation entry.
+pub(crate) fn tags_of(entry: &Value) -> Vec<String> {
+    entry
+        .get("tags")
+        .and_then(Value::as_array)
+        .map(|tags| tags.iter().filter_map(Value::as_str).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Copy story and translation tags from `saved` into `stories`. Tags are
+/// only changed through [`set_tags`], so the saved ones always win over
+/// whatever a frontend write carries.
+pub fn keep_tags(stories: &mut Value, saved: &Value) {
+    let (Some(list), Some(saved)) = (stories.as_array_mut(), saved.as_array()) else {
+        return;
+    };
+    for story in list {
+        let Some(old) = saved.iter().find(|s| s.get("id").is_some_and(|id| Some(id) == story.get("id"))) else {
+            continue;
+        };
+        let Some(entry) = story.as_object_mut() else {
+            continue;
+        };
+        copy_field(entry, old, "tags");
+        let (Some(old), Some(translations)) = (
+            old.get("translations").and_then(Value::as_object),
+            entry.get_mut("translations").and_then(Value::as_object_mut),
+        ) else {
+            continue;
+        };
+        for (language, translation) in translations.iter_mut() {
+            if let (Some(entry), Some(old)) = (translation.as_object_mut(), old.get(language)) {
+                copy_field(entry, old, "tags");
+            }
+        }
+    }
+}
+
+/// Store shelf metadata on a translation (see `story_meta`). Only `stories`
+/// is modified; the caller saves it.
+pub fn set_meta(stories: &mut Value, doc_id: &DocId, meta: &StoryMeta) -> Result<(), StoryError> {
+    let translation = stories
+        .as_array_mut()
+        .ok_or_else(|| StoryError::Parse("stories.json is not an array".to_string()))?
+        .iter_mut()
+        .find(|s| s.get("id").and_then(Value::as_str) == Some(doc_id.story_id.as_str()))
+        .and_then(|s| s.get_mut("translations"))
+        .and_then(|t| t.get_mut(&doc_id.language))
+        .filter(|t| t.is_object())
+        .ok_or_else(|| StoryError::NotFound(doc_id.to_string()))?;
+    translation["meta"] = serde_json::to_value(meta).map_err(|e| StoryError::Parse(e.to_string()))?;
+    Ok(())
+}
+
+/// Store a practice plan on a translation (see `practice_plan`). Only
+/// `stories` is modified; the caller saves it.
+pub fn set_practice_plan(stories: &mut Value, doc_id: &DocId, plan: &PracticePlan) -> Result<(), StoryError> {
+    let translation = stories
+        .as_array_mut()
+        .ok_or_else(|| StoryError::Parse("stories.json is not an array".to_string()))?
+        .iter_mut()
+        .find(|s| s.get("id").and_then(Value::as_str) == Some(doc_id.story_id.as_str()))
+        .and_then(|s| s.get_mut("translations"))
+        .and_then(|t| t.get_mut(&doc_id.language))
+        .filter(|t| t.is_object())
+        .ok_or_else(|| StoryError::NotFound(doc_id.to_string()))?;
+    translation["practicePlan"] = serde_json::to_value(plan).map_err(|e| StoryError::Parse(e.to_string()))?;
+    Ok(())
+}
+
+/// The stored practice plan, if any; one that no longer parses counts as none.
+pub fn saved_practice_plan(stories: &Value, doc_id: &DocId) -> Result<Option<PracticePlan>, StoryError> {
+    let translation = stories
+        .as_array()
+        .ok_or_else(|| StoryError::Parse("stories.json is not an array".to_string()))?
+        .iter()
+        .find(|s| s.get("id").and_then(Value::as_str) == Some(doc_id.story_id.as_str()))
+        .and_then(|s| s.get("translations")?.get(&doc_id.language))
+        .ok_or_else(|| StoryError::NotFound(doc_id.to_string()))?;
+    Ok(translation
+        .get("practicePlan")
+        .and_then(|p| serde_json::from_value(p.clone()).ok()))
+}
+
+/// Copy backend-written translation fields (`meta`, `practicePlan`,
+/// `starred`, `tutor`, `speakers`, and `shared` for docs imported from a
+/// bundle) from `saved` into `stories`.
+/// Only the backend writes them, so the saved copy always wins.
+pub fn keep_meta(stories: &mut Value, saved: &Value) {
+    let (Some(list), Some(saved)) = (stories.as_array_mut(), saved.as_array()) else {
+        return;
+    };
+    for story in list {
+        let Some(old) = saved
+            .iter()
+            .find(|s| s.get("id").is_some_and(|id| Some(id) == story.get("id")))
+            .and_then(|s| s.get("translations"))
+            .and_then(Value::as_object)
+        else {
+            continue;
+        };
+        let Some(translations) = story.get_mut("translations").and_then(Value::as_object_mut) else {
+            continue;
+        };
+        for (language, translation) in translations.iter_mut() {
+            if let (Some(entry), Some(old)) = (translation.as_object_mut(), old.get(language)) {
+                copy_field(entry, old, "meta");
+                copy_field(entry, old, "shared");
+                copy_field(entry, old, "practicePlan");
+                copy_field(entry, old, "starred");
+                copy_field(entry, old, "tutor");
+                copy_field(entry, old, "speakers");
+            }
+        }
+    }
+}
+
+fn copy_field(entry: &mut Map<String, Value>, saved: &Value, key: &str) {
+    match saved.get(key) {
+        Some(value) => {
+            entry.insert(key.to_string(), value.clone());
+        }
+        None => {
+            entry.remove(key);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Shelf metadata for a saved doc, so the library can show more than a
+//! title without extra calls: a suggested title, a one-sentence synopsis in
+//! both languages and a cover emoji from the model, plus the level, word
+//! count and a cover color seed worked out locally. Stored as `meta` on the
+//! translation entry (see [`set_meta`](super::stories::set_meta)).
+
+use super::analysis::analyze_text;
+use super::simplify::CefrLevel;
+use super::stories::{now_ms, StoryDoc};
+use super::text;
+use super::translation::{fill_anthropic_key, Client};
+use super::types::{ApiConfig, ApiError, LlmProviderConfig};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// Graphemes of each text sent to the model; the opening is enough for a
+/// title and synopsis.
+const DESCRIBE_TEXT_LEN: usize = 4000;
+
+/// Covers for docs the model did not describe, picked by color seed.
+const FALLBACK_EMOJI: [&str; 8] = ["📖", "🌙", "🌿", "🐾", "⛵", "🏔️", "🍂", "✨"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoryMeta {
+    /// Suggested title in the doc's language; `None` when the model was not
+    /// asked or failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub synopsis: Option<Synopsis>,
+    /// The graded-reader level, else an estimate from the translated text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<CefrLevel>,
+    pub word_count: u32,
+    pub cover: Cover,
+    /// Model that wrote the title and synopsis.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub generated_at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Synopsis {
+    pub source: String,
+    pub target: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Cover {
+    pub emoji: String,
+    /// Stable per story; the GUI turns it into a color.
+    pub color_seed: u32,
+}
+
+/// Metadata that needs no model: level, word count and a fallback cover.
+pub fn local_meta(story: &StoryDoc) -> StoryMeta {
+    let translation = story.doc.block_texts().join("\n\n");
+    let stats = analyze_text(&translation, &story.language);
+    let graded = story
+        .job
+        .as_ref()
+        .and_then(|j| j.metadata.as_ref())
+        .and_then(|m| m.simplify_level);
+    let color_seed = color_seed(&story.story_id);
+
+    StoryMeta {
+        title: None,
+        synopsis: None,
+        level: graded.or_else(|| CefrLevel::parse(&stats.estimated_cefr)),
+        word_count: stats.word_count,
+        cover: Cover {
+            emoji: FALLBACK_EMOJI[color_seed as usize % FALLBACK_EMOJI.len()].to_string(),
+            color_seed,
+        },
+        model: None,
+        generated_at: now_ms(),
+    }
+}
+
+/// [`local_meta`] plus the model's title, synopsis and cover emoji.
+pub async fn describe(story: &StoryDoc, provider: LlmProviderConfig) -> Result<StoryMeta, ApiError> {
+    let mut meta = local_meta(story);
+    let source_language = Some(story.source_language.as_str()).filter(|l| !l.is_empty());
+    let mut cfg = ApiConfig::from_env(&story.language, source_language, false, false);
+    cfg.provider = provider;
+    fill_anthropic_key(&mut cfg);
+    let client = Client::new(cfg)?;
+
+    let translation = story.doc.block_texts().join("\n\n");
+    let (reply, _) = client
+        .describe_story(
+            text::truncate(&story.source_text, DESCRIBE_TEXT_LEN),
+            text::truncate(&translation, DESCRIBE_TEXT_LEN),
+        )
+        .await?;
+    let description = parse_description(&reply)?;
+
+    meta.title = Some(description.title).filter(|t| !t.is_empty());
+    meta.synopsis = Some(Synopsis {
+        source: description.synopsis_source,
+        target: description.synopsis_target,
+    });
+    if let Some(emoji) = description.emoji {
+        meta.cover.emoji = emoji;
+    }
+    meta.model = Some(client.model().to_string());
+    Ok(meta)
+}
+
+#[derive(Debug)]
+struct Description {
+    title: String,
+    synopsis_source: String,
+    synopsis_target: String,
+    emoji: Option<String>,
+}
+
+/// Parse `{ "title", "synopsisSource", "synopsisTarget", "emoji" }`,
+/// tolerating code fences. Only the synopses are required; the emoji is cut
+/// to one grapheme and dropped when it is plain text.
+fn parse_description(reply: &str) -> Result<Description, ApiError> {
+    let cleaned = reply
+        .trim()
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim();
+    let output = || text::excerpt(cleaned, text::EXCERPT_LEN);
+    let value: Value = serde_json::from_str(cleaned)
+        .map_err(|e| ApiError::Parse(format!("Description JSON parse: {} | output: {}", e, output())))?;
+
+    let field = |key: &str| value.get(key).and_then(Value::as_str).map(|s| s.trim().to_string());
+    let required = |key: &str| {
+        field(key).filter(|s| !s.is_empty()).ok_or_else(|| {
+            ApiError::Parse(format!("Description JSON parse: missing `{}` | output: {}", key, output()))
+        })
+    };
+
+    Ok(Description {
+        title: field("title").unwrap_or_default(),
+        synopsis_source: required("synopsisSource")?,
+        synopsis_target: required("synopsisTarget")?,
+        emoji: field("emoji")
+            .map(|e| text::truncate(&e, 1).to_string())
+            .filter(|e| e.chars().any(|c| !c.is_alphanumeric() && !c.is_whitespace())),
+    })
+}
+
+fn color_seed(story_id: &str) -> u32 {
+    let hash = Sha256::digest(story_id.as_bytes());
+    u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]])
+}
+
+/// The stored metadata of a translation entry, if it parses.
+pub fn meta_of(translation: &Value) -> Option<StoryMeta> {
+    translation
+        .get("meta")
+        .and_then(|m| serde_json::from_value(m.clone()).ok())
+}
@@ -0,0 +1,270 @@
+//! OS-native speech, the fallback while no Kokoro model is ready (first
+//! run, before the download finishes). Each platform's stock synthesizer is
+//! driven through its command-line front end and renders to a WAV file:
+//! `say` (the AVSpeechSynthesizer voices) on macOS, SAPI through
+//! PowerShell's System.Speech on Windows, and espeak-ng, the default
+//! speech-dispatcher synthesizer, elsewhere. The system picks the voice for
+//! the language; Kokoro voice ids are ignored.
+
+use super::audio::{AudioError, TtsEngine};
+use super::audio_types::{AudioModelStatus, VoiceInfo};
+use super::paths::Platform;
+
+use std::fs;
+use std::future::Future;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Model id the system engine reports, in responses and cache keys.
+pub const SYSTEM_MODEL_ID: &str = "system";
+
+/// Every backend is asked for this rate; anything else is resampled.
+const SAMPLE_RATE: u32 = 22050;
+
+/// Words per minute at speed 1.0 for `say` and espeak-ng.
+const BASE_WPM: f32 = 175.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemBackend {
+    Say,
+    Sapi,
+    EspeakNg,
+}
+
+impl SystemBackend {
+    pub fn for_platform(platform: Platform) -> Self {
+        match platform {
+            Platform::Macos => SystemBackend::Say,
+            Platform::Windows => SystemBackend::Sapi,
+            Platform::Linux => SystemBackend::EspeakNg,
+        }
+    }
+
+    pub fn program(self) -> &'static str {
+        match self {
+            SystemBackend::Say => "say",
+            SystemBackend::Sapi => "powershell",
+            SystemBackend::EspeakNg => "espeak-ng",
+        }
+    }
+
+    /// Whether the synthesizer can be run at all.
+    fn available(self) -> bool {
+        let probe: &[&str] = match self {
+            SystemBackend::Say => &["-v", "?"],
+            SystemBackend::Sapi => &["-NoProfile", "-NonInteractive", "-Command", "Add-Type -AssemblyName System.Speech"],
+            SystemBackend::EspeakNg => &["--version"],
+        };
+        Command::new(self.program())
+            .args(probe)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|s| s.success())
+    }
+
+    /// Arguments that speak the text given on stdin into the WAV file `out`.
+    pub fn args(self, out: &Path, language: &str, speed: f32) -> Vec<String> {
+        let out = out.to_string_lossy().into_owned();
+        // Only a plain language tag reaches a command line.
+        let language: String = language
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+            .take(16)
+            .collect();
+        let wpm = ((BASE_WPM * speed).round() as u32).clamp(80, 450).to_string();
+        match self {
+            SystemBackend::Say => vec![
+                "-o".into(),
+                out,
+                format!("--data-format=LEI16@{}", SAMPLE_RATE),
+                "-r".into(),
+                wpm,
+                "-f".into(),
+                "-".into(),
+            ],
+            SystemBackend::Sapi => {
+                let rate = (((speed - 1.0) * 10.0).round() as i32).clamp(-10, 10);
+                let culture = if language.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        "try {{ $s.SelectVoiceByHints('NotSet', 'NotSet', 0, [Globalization.CultureInfo]'{}') }} catch {{}}; ",
+                        language
+                    )
+                };
+                let script = format!(
+                    "[Console]::InputEncoding = [Text.Encoding]::UTF8; \
+                     Add-Type -AssemblyName System.Speech; \
+                     $s = New-Object System.Speech.Synthesis.SpeechSynthesizer; \
+                     {}$s.Rate = {}; \
+                     $s.SetOutputToWaveFile('{}', (New-Object System.Speech.AudioFormat.SpeechAudioFormatInfo({}, 'Sixteen', 'Mono'))); \
+                     $s.Speak([Console]::In.ReadToEnd()); $s.Dispose()",
+                    culture,
+                    rate,
+                    out.replace('\'', "''"),
+                    SAMPLE_RATE
+                );
+                vec!["-NoProfile".into(), "-NonInteractive".into(), "-Command".into(), script]
+            }
+            SystemBackend::EspeakNg => {
+                let mut args = vec!["-w".into(), out, "-s".into(), wpm];
+                if !language.is_empty() {
+                    args.extend(["-v".into(), language]);
+                }
+                args.push("--stdin".into());
+                args
+            }
+        }
+    }
+}
+
+/// [`TtsEngine`] over the platform synthesizer. "Loading" only checks that
+/// the synthesizer is there.
+pub struct SystemEngine {
+    backend: SystemBackend,
+    available: bool,
+}
+
+impl Default for SystemEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SystemEngine {
+    pub fn new() -> Self {
+        Self::with_backend(SystemBackend::for_platform(Platform::current()))
+    }
+
+    pub fn with_backend(backend: SystemBackend) -> Self {
+        Self {
+            backend,
+            available: false,
+        }
+    }
+
+    pub fn backend(&self) -> SystemBackend {
+        self.backend
+    }
+}
+
+fn temp_wav() -> PathBuf {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    std::env::temp_dir().join(format!(
+        "boka-system-tts-{}-{}.wav",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Mono f32 samples at [`SAMPLE_RATE`] from 16-bit WAV bytes.
+fn read_wav(bytes: &[u8]) -> Result<Vec<f32>, AudioError> {
+    let reader = hound::WavReader::new(Cursor::new(bytes)).map_err(|e| AudioError::GenerationFailed(e.to_string()))?;
+    let spec = reader.spec();
+    let channels = spec.channels.max(1) as usize;
+    let samples: Vec<f32> = reader
+        .into_samples::<i16>()
+        .collect::<Result<Vec<i16>, _>>()
+        .map_err(|e| AudioError::GenerationFailed(e.to_string()))?
+        .chunks(channels)
+        .map(|frame| frame.iter().map(|s| *s as f32 / i16::MAX as f32).sum::<f32>() / channels as f32)
+        .collect();
+    Ok(resample(&samples, spec.sample_rate, SAMPLE_RATE))
+}
+
+/// Linear resampling; good enough for a fallback voice.
+fn resample(samples: &[f32], from: u32, to: u32) -> Vec<f32> {
+    if from == to || from == 0 || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let len = (samples.len() as u64 * to as u64 / from as u64) as usize;
+    (0..len)
+        .map(|i| {
+            let pos = i as f64 * from as f64 / to as f64;
+            let j = pos as usize;
+            let frac = (pos - j as f64) as f32;
+            let a = samples[j.min(samples.len() - 1)];
+            let b = samples[(j + 1).min(samples.len() - 1)];
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+impl TtsEngine for SystemEngine {
+    fn model_id(&self) -> &str {
+        SYSTEM_MODEL_ID
+    }
+
+    fn load_model(&mut self) -> Pin<Box<dyn Future<Output = Result<(), AudioError>> + Send + '_>> {
+        Box::pin(async move {
+            if !self.available {
+                self.available = self.backend.available();
+            }
+            if self.available {
+                Ok(())
+            } else {
+                Err(AudioError::ModelNotInstalled(self.backend.program().to_string()))
+            }
+        })
+    }
+
+    fn is_loaded(&self) -> bool {
+        self.available
+    }
+
+    fn generate(&self, text: &str, _voice_id: &str, speed: f32, language: &str) -> Result<Vec<f32>, AudioError> {
+        if !self.available {
+            return Err(AudioError::ModelNotLoaded);
+        }
+        let out = temp_wav();
+        let failed = |e: std::io::Error| AudioError::GenerationFailed(format!("{}: {}", self.backend.program(), e));
+        let mut child = Command::new(self.backend.program())
+            .args(self.backend.args(&out, language, speed))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(failed)?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes()).map_err(failed)?;
+        }
+        let output = child.wait_with_output().map_err(failed)?;
+        let wav = fs::read(&out);
+        let _ = fs::remove_file(&out);
+        if !output.status.success() {
+            return Err(AudioError::GenerationFailed(format!(
+                "{}: {}",
+                self.backend.program(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        read_wav(&wav.map_err(failed)?)
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn status(&self) -> AudioModelStatus {
+        AudioModelStatus {
+            downloaded: self.available,
+            loading: false,
+            ready: self.available,
+            model_size_bytes: None,
+            error: None,
+        }
+    }
+
+    /// The system chooses by language; there is no voice id to pass.
+    fn default_voice(&self, _language: &str) -> String {
+        String::new()
+    }
+
+    fn voices(&self) -> Vec<VoiceInfo> {
+        vec![]
+    }
+}
@@ -0,0 +1,36 @@
+//! Length limits for model output and errors that end up in messages,
+//! events and reports. Cuts fall on grapheme boundaries, so CJK text,
+//! combining accents and emoji sequences are never split (slicing at a byte
+//! offset panics on the first two).
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Model output quoted in parse errors.
+pub const EXCERPT_LEN: usize = 800;
+
+/// Error details and warnings sent to the frontend or stored in reports.
+/// Providers sometimes answer with a whole HTML page.
+pub const EVENT_TEXT_LEN: usize = 4000;
+
+/// The first `max` graphemes of `text`.
+pub fn truncate(text: &str, max: usize) -> &str {
+    match text.grapheme_indices(true).nth(max) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+/// `text` cut to `max` graphemes, with `…` when something was dropped.
+pub fn excerpt(text: &str, max: usize) -> String {
+    let cut = truncate(text, max);
+    if cut.len() < text.len() {
+        format!("{}…", cut)
+    } else {
+        cut.to_string()
+    }
+}
+
+/// [`excerpt`] at [`EVENT_TEXT_LEN`], for strings leaving the pipeline.
+pub fn clip(text: &str) -> String {
+    excerpt(text, EVENT_TEXT_LEN)
+}
@@ -0,0 +1,80 @@
+//! Opt-in per-job transcripts: every prompt sent to the provider and every
+//! reply, in order, as `<data_dir>/transcripts/<job_id>.jsonl` next to the
+//! run reports. For debugging a bad translation and for auditing what a job
+//! sent to a cloud provider.
+//!
+//! Lines are [`CassetteEntry`]s, so API keys are redacted the same way and a
+//! transcript can be replayed as a cassette. A retry of the job appends to
+//! its transcript.
+
+use super::cassette::{Cassette, CassetteEntry, CassetteMode};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const TRANSCRIPTS_DIR: &str = "transcripts";
+
+#[derive(Debug, thiserror::Error)]
+pub enum TranscriptError {
+    #[error("Transcript I/O error: {0}")]
+    Io(String),
+
+    #[error("Failed to parse transcript line {line}: {message}")]
+    Parse { line: usize, message: String },
+
+    #[error("No transcript for job: {0}")]
+    NotFound(String),
+
+    #[error("Invalid job id: `{0}`")]
+    InvalidJobId(String),
+}
+
+pub fn transcripts_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(TRANSCRIPTS_DIR)
+}
+
+pub fn transcript_path(data_dir: &Path, job_id: &str) -> Result<PathBuf, TranscriptError> {
+    let valid = !job_id.is_empty() && job_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(TranscriptError::InvalidJobId(job_id.to_string()));
+    }
+    Ok(transcripts_dir(data_dir).join(format!("{}.jsonl", job_id)))
+}
+
+/// The recorder a job's clients append to, for `ApiConfig::transcript`.
+pub fn recorder(path: &Path) -> Cassette {
+    Cassette::new(CassetteMode::Record, path)
+}
+
+fn existing_path(data_dir: &Path, job_id: &str) -> Result<PathBuf, TranscriptError> {
+    let path = transcript_path(data_dir, job_id)?;
+    if !path.is_file() {
+        return Err(TranscriptError::NotFound(job_id.to_string()));
+    }
+    Ok(path)
+}
+
+/// The exchanges of job `job_id`, oldest first.
+pub fn load(data_dir: &Path, job_id: &str) -> Result<Vec<CassetteEntry>, TranscriptError> {
+    let raw = fs::read_to_string(existing_path(data_dir, job_id)?).map_err(|e| TranscriptError::Io(e.to_string()))?;
+    raw.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|e| TranscriptError::Parse {
+                line: i + 1,
+                message: e.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Copy job `job_id`'s transcript to `dest`, creating its directory.
+/// Returns the number of bytes written.
+pub fn export(data_dir: &Path, job_id: &str, dest: &Path) -> Result<u64, TranscriptError> {
+    let path = existing_path(data_dir, job_id)?;
+    if let Some(dir) = dest.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| TranscriptError::Io(e.to_string()))?;
+    }
+    fs::copy(&path, dest).map_err(|e| TranscriptError::Io(e.to_string()))
+}
@@ -0,0 +1,2061 @@
+use super::analysis::{estimate_tokens, segment_difficulty};
+use super::annotate::{annotate_doc, AnnotationPlan};
+use super::anthropic::{AnthropicClient, PlannedBlock, PlannedSegment, PlannedVariant};
+use super::bidi::{detect_direction, direction_for_language};
+use super::comprehension;
+use super::gui_types::{
+    CallProvenance, DocToken, ErrorPolicy, Granularity, InteractiveDoc, JobMetadata, SegmentEdit, SegmentScore,
+    SegmentStage, Span, SpanProgress, SpanStage, TextDirection, TranslationJob, TranslationSegment, Variant,
+};
+use super::import::PageImage;
+use super::judge::{JudgeConfig, JudgeVerdict};
+use super::lemma::{base_language, is_unspaced_script};
+use super::length_guard::{self, LengthGuard};
+use super::limits::{self, BudgetStatus, JobBudget, BUDGET_WARNING};
+use super::lint::lint_doc;
+use super::markup::{self, Emphasis};
+#[cfg(any(test, feature = "mock"))]
+use super::mock::MockClient;
+use super::openai_compat::OpenAiCompatClient;
+use super::policy::{normalize_register, ContentPolicy};
+use super::prompts::{PromptOverrides, PromptSet};
+use super::report::{is_output_failure, RunReport, RunStage};
+use super::settings::VariantBounds;
+use super::simple_format::StructuredFormat;
+use super::simplify::{self, check_vocabulary, CefrLevel, VocabularyCheck};
+use super::types::{
+    ApiConfig, ApiError, LlmProviderConfig, LlmProviderPreset, ModelPricing, ModelRegistry, SamplingParams, Usage,
+};
+use super::transcript;
+use super::translation_cache::{ScopedCache, TranslationCache};
+use super::typography;
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Poll;
+use std::time::Instant;
+
+/// Extra variant generations allowed when moderated output is rejected.
+const MODERATION_RETRIES: u32 = 2;
+
+/// Extra attempts at a failed segment step under [`ErrorPolicy::RetryThenSkip`].
+const SEGMENT_RETRIES: u32 = 1;
+/// Punctuation that can end a clause under [`Granularity::Clause`].
+const CLAUSE_MARKS: [char; 5] = [',', ';', ':', '—', '–'];
+/// Shorter clauses are joined to a neighbour.
+const MIN_CLAUSE_WORDS: usize = 3;
+/// Sources above this many tokens are translated in pieces (see
+/// [`split_overlong`]); base and graded-reader output is capped at 512.
+pub const MAX_SEGMENT_TOKENS: u32 = 300;
+/// Words before which an overlong sentence may be cut when it has no clause
+/// punctuation left.
+const CONJUNCTIONS: [&str; 16] = [
+    "and", "but", "or", "so", "because", "et", "mais", "ou", "donc", "y", "pero", "porque", "und", "aber", "oder",
+    "weil",
+];
+
+/// How well [`split_segments`] cuts text written in a language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SegmentationTier {
+    /// Sentences end with other marks (`。`, `।`) or none: whole paragraphs
+    /// arrive as one segment.
+    Poor,
+    /// Sentences and clause punctuation.
+    Basic,
+    /// Overlong sentences can also be cut before conjunctions.
+    Full,
+}
+
+pub fn segmentation_tier(language: &str) -> SegmentationTier {
+    match base_language(&language.to_ascii_lowercase()) {
+        "en" | "fr" | "es" | "de" => SegmentationTier::Full,
+        "ja" | "jp" | "zh" | "cn" | "th" | "hi" => SegmentationTier::Poor,
+        _ => SegmentationTier::Basic,
+    }
+}
+
+/// Sentences of `text`, cut after `.`, `!` and `?`.
+pub fn split_into_segments(text: &str) -> Vec<String> {
+    let t = text.trim();
+    if t.is_empty() {
+        return vec![];
+    }
+
+    let rough: Vec<String> = t
+        .split_inclusive(['.', '!', '?'])
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect();
+
+    if !rough.is_empty() {
+        return rough;
+    }
+
+    vec![t.to_string()]
+}
+
+/// Segments of `text` at `granularity`; see [`Granularity`].
+pub fn split_segments(text: &str, granularity: Granularity) -> Vec<String> {
+    match granularity {
+        Granularity::Clause => split_into_segments(text).iter().flat_map(|s| split_clauses(s)).collect(),
+        Granularity::Sentence => split_into_segments(text),
+        Granularity::Paragraph => {
+            let mut paragraphs: Vec<String> = vec![];
+            let mut current: Vec<&str> = vec![];
+            for line in text.lines().map(str::trim).chain([""]) {
+                if !line.is_empty() {
+                    current.push(line);
+                } else if !current.is_empty() {
+                    paragraphs.push(current.join("\n"));
+                    current.clear();
+                }
+            }
+            paragraphs
+        }
+    }
+}
+
+/// `segment` in pieces of at most `max_tokens` estimated tokens, cut after
+/// clause punctuation, then before conjunctions, then between words, and
+/// packed back together as far as the limit allows. Comes back whole when it
+/// fits.
+pub fn split_overlong(segment: &str, max_tokens: u32) -> Vec<String> {
+    cut_overlong(segment.trim(), max_tokens)
+        .into_iter()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn cut_overlong(text: &str, max_tokens: u32) -> Vec<&str> {
+    if estimate_tokens(text) <= max_tokens {
+        return vec![text];
+    }
+    let word_starts = |text: &str| -> Vec<(usize, String)> {
+        text.char_indices()
+            .zip(text.chars().skip(1))
+            .filter(|((_, c), next)| c.is_whitespace() && !next.is_whitespace())
+            .map(|((i, c), _)| {
+                let start = i + c.len_utf8();
+                let word = text[start..].split_whitespace().next().unwrap_or_default();
+                (start, word.to_lowercase())
+            })
+            .collect()
+    };
+    let conjunction_cuts = |text: &str| -> Vec<usize> {
+        word_starts(text)
+            .into_iter()
+            .filter(|(_, word)| CONJUNCTIONS.contains(&word.as_str()))
+            .map(|(i, _)| i)
+            .collect()
+    };
+    let word_cuts = |text: &str| -> Vec<usize> { word_starts(text).into_iter().map(|(i, _)| i).collect() };
+
+    for cuts in [clause_cuts(text), conjunction_cuts(text), word_cuts(text)] {
+        if cuts.is_empty() {
+            continue;
+        }
+        // Greedily grow each piece while it fits; a piece that cannot fit
+        // even alone is cut again at the next level.
+        let mut pieces = vec![];
+        let (mut start, mut end) = (0, 0);
+        for cut in cuts.into_iter().chain([text.len()]) {
+            if end > start && estimate_tokens(&text[start..cut]) > max_tokens {
+                pieces.push(&text[start..end]);
+                start = end;
+            }
+            end = cut;
+        }
+        pieces.push(&text[start..end]);
+        return pieces.into_iter().flat_map(|p| cut_overlong(p, max_tokens)).collect();
+    }
+    vec![text]
+}
+
+/// Byte offsets just after each clause mark that is followed by a space.
+fn clause_cuts(text: &str) -> Vec<usize> {
+    let mut cuts = vec![];
+    let mut chars = text.char_indices().peekable();
+    while let Some((_, c)) = chars.next() {
+        if let Some((next, _)) = chars.peek().filter(|(_, next)| CLAUSE_MARKS.contains(&c) && next.is_whitespace()) {
+            cuts.push(*next);
+        }
+    }
+    cuts
+}
+
+/// Cut a sentence after clause punctuation followed by a space. Pieces under
+/// [`MIN_CLAUSE_WORDS`] words ("Oui," "Then,") stay with the next one, or the
+/// previous one at the end of the sentence.
+fn split_clauses(sentence: &str) -> Vec<String> {
+    let mut cuts = clause_cuts(sentence);
+    cuts.push(sentence.len());
+
+    let mut clauses: Vec<(usize, usize)> = vec![];
+    let mut start = 0;
+    for end in cuts {
+        if sentence[start..end].split_whitespace().count() >= MIN_CLAUSE_WORDS {
+            clauses.push((start, end));
+            start = end;
+        }
+    }
+    if start < sentence.len() {
+        match clauses.last_mut() {
+            Some(last) => last.1 = sentence.len(),
+            None => clauses.push((0, sentence.len())),
+        }
+    }
+    clauses
+        .into_iter()
+        .map(|(start, end)| sentence[start..end].trim().to_string())
+        .filter(|c| !c.is_empty())
+        .collect()
+}
+
+#[derive(Debug)]
+pub struct TranslationResult {
+    pub job: TranslationJob,
+    pub doc: InteractiveDoc,
+    /// Tokens spent across all provider calls of the job.
+    pub usage: Usage,
+}
+
+/// Provider dispatch for one configured model; shared with `import`.
+pub(crate) enum Client {
+    Anthropic(AnthropicClient),
+    OpenAiCompat(OpenAiCompatClient),
+    #[cfg(any(test, feature = "mock"))]
+    Mock(MockClient),
+}
+
+impl Client {
+    pub(crate) fn new(cfg: ApiConfig) -> Result<Self, ApiError> {
+        Ok(match cfg.provider.preset {
+            LlmProviderPreset::Anthropic => Client::Anthropic(AnthropicClient::new(cfg)?),
+            #[cfg(any(test, feature = "mock"))]
+            LlmProviderPreset::Mock => Client::Mock(MockClient::new(cfg)?),
+            _ => Client::OpenAiCompat(OpenAiCompatClient::new(cfg)?),
+        })
+    }
+    pub(crate) fn model(&self) -> &str {
+        match self {
+            Client::Anthropic(c) => c.model(),
+            Client::OpenAiCompat(c) => c.model(),
+            #[cfg(any(test, feature = "mock"))]
+            Client::Mock(c) => c.model(),
+        }
+    }
+    pub(crate) fn preset(&self) -> LlmProviderPreset {
+        match self {
+            Client::Anthropic(_) => LlmProviderPreset::Anthropic,
+            Client::OpenAiCompat(c) => c.preset(),
+            #[cfg(any(test, feature = "mock"))]
+            Client::Mock(_) => LlmProviderPreset::Mock,
+        }
+    }
+    /// Who answered try `attempt` of a call that began at `started`.
+    fn provenance(&self, attempt: u32, started: Instant) -> CallProvenance {
+        CallProvenance {
+            provider: self.preset(),
+            model: self.model().to_string(),
+            attempt,
+            duration_ms: started.elapsed().as_millis() as u64,
+        }
+    }
+    fn json_mode(&self) -> bool {
+        matches!(self, Client::OpenAiCompat(c) if c.capabilities().json_mode)
+    }
+    /// Whether the model accepts images; the mock always does.
+    pub(crate) fn supports_vision(&self) -> bool {
+        match self {
+            Client::Anthropic(c) => c.capabilities().vision,
+            Client::OpenAiCompat(c) => c.capabilities().vision,
+            #[cfg(any(test, feature = "mock"))]
+            Client::Mock(_) => true,
+        }
+    }
+    pub(crate) async fn transcribe_image(&self, image: &PageImage) -> Result<(String, Usage), ApiError> {
+        match self {
+            Client::Anthropic(c) => c.transcribe_image(image).await,
+            Client::OpenAiCompat(c) => c.transcribe_image(image).await,
+            #[cfg(any(test, feature = "mock"))]
+            Client::Mock(c) => c.transcribe_image(image).await,
+        }
+    }
+    pub(crate) async fn describe_story(&self, source: &str, translation: &str) -> Result<(String, Usage), ApiError> {
+        match self {
+            Client::Anthropic(c) => c.describe_story(source, translation).await,
+            Client::OpenAiCompat(c) => c.describe_story(source, translation).await,
+            #[cfg(any(test, feature = "mock"))]
+            Client::Mock(c) => c.describe_story(source, translation).await,
+        }
+    }
+    pub(crate) async fn attribute_speakers(
+        &self,
+        blocks: &[(u32, String)],
+        lines: &[(String, String)],
+        max_speakers: usize,
+    ) -> Result<(String, Usage), ApiError> {
+        match self {
+            Client::Anthropic(c) => c.attribute_speakers(blocks, lines, max_speakers).await,
+            Client::OpenAiCompat(c) => c.attribute_speakers(blocks, lines, max_speakers).await,
+            #[cfg(any(test, feature = "mock"))]
+            Client::Mock(c) => c.attribute_speakers(blocks, lines, max_speakers).await,
+        }
+    }
+    pub(crate) async fn translate_notes(
+        &self,
+        notes: &[String],
+        ui_language: &str,
+    ) -> Result<(String, Usage), ApiError> {
+        match self {
+            Client::Anthropic(c) => c.translate_notes(notes, ui_language).await,
+            Client::OpenAiCompat(c) => c.translate_notes(notes, ui_language).await,
+            #[cfg(any(test, feature = "mock"))]
+            Client::Mock(c) => c.translate_notes(notes, ui_language).await,
+        }
+    }
+    pub(crate) async fn continue_story(
+        &self,
+        story: &str,
+        direction_hint: Option<&str>,
+        words: u32,
+    ) -> Result<(String, Usage), ApiError> {
+        match self {
+            Client::Anthropic(c) => c.continue_story(story, direction_hint, words).await,
+            Client::OpenAiCompat(c) => c.continue_story(story, direction_hint, words).await,
+            #[cfg(any(test, feature = "mock"))]
+            Client::Mock(c) => c.continue_story(story, direction_hint, words).await,
+        }
+    }
+    pub(crate) async fn answer_question(
+        &self,
+        source: &str,
+        translation: &str,
+        earlier: &[(&str, &str)],
+        question: &str,
+        ui_language: Option<&str>,
+        policy: Option<&ContentPolicy>,
+    ) -> Result<(String, Usage), ApiError> {
+        match self {
+            Client::Anthropic(c) => {
+                c.answer_question(source, translation, earlier, question, ui_language, policy).await
+            }
+            Client::OpenAiCompat(c) => {
+                c.answer_question(source, translation, earlier, question, ui_language, policy).await
+            }
+            #[cfg(any(test, feature = "mock"))]
+            Client::Mock(c) => c.answer_question(source, translation, earlier, question, ui_language, policy).await,
+        }
+    }
+    async fn comprehension_check(&self, passage: &str) -> Result<(String, Usage), ApiError> {
+        match self {
+            Client::Anthropic(c) => c.comprehension_check(passage).await,
+            Client::OpenAiCompat(c) => c.comprehension_check(passage).await,
+            #[cfg(any(test, feature = "mock"))]
+            Client::Mock(c) => c.comprehension_check(passage).await,
+        }
+    }
+
+    fn length_guard(&self) -> LengthGuard {
+        match self {
+            Client::Anthropic(c) => c.length_guard(),
+            Client::OpenAiCompat(c) => c.length_guard(),
+            #[cfg(any(test, feature = "mock"))]
+            Client::Mock(c) => c.length_guard(),
+        }
+    }
+
+    /// Base translation of `segment`, its emoji and markup kept out of the
+    /// model's way (see [`markup`]). Preambles are stripped, and a reply the
+    /// [`LengthGuard`] still finds too long is asked for once more with a
+    /// firmer note; the shorter of the two replies is kept. The first reply
+    /// is usable, so a retry lost to a transport or output failure is
+    /// dropped rather than failing the segment; a cancelled or refused retry
+    /// still ends it.
+    async fn translate_base_segment(&self, full_story: &str, segment: &str) -> Result<(String, Usage), ApiError> {
+        let protected = markup::protect(segment);
+        let segment = protected.text.as_str();
+        let (text, mut usage) = self.base_translation(full_story, segment, None).await?;
+        let mut text = length_guard::strip_preamble(&text);
+        if self.length_guard().trips(segment, &text) {
+            let note = Some(length_guard::FIRMER_NOTE);
+            match self.base_translation(full_story, segment, note).await {
+                Ok((retry, retry_usage)) => {
+                    usage += retry_usage;
+                    let retry = length_guard::strip_preamble(&retry);
+                    if retry.chars().count() < text.chars().count() {
+                        text = retry;
+                    }
+                }
+                Err(ApiError::Http(_) | ApiError::ApiResponse { .. }) => {}
+                Err(e @ ApiError::Parse(_)) if !is_cancellation(&e) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok((protected.restore(&text), usage))
+    }
+    async fn base_translation(
+        &self,
+        full_story: &str,
+        segment: &str,
+        note: Option<&str>,
+    ) -> Result<(String, Usage), ApiError> {
+        match self {
+            Client::Anthropic(c) => c.translate_base_segment(full_story, segment, note).await,
+            Client::OpenAiCompat(c) => c.translate_base_segment(full_story, segment, note).await,
+            #[cfg(any(test, feature = "mock"))]
+            Client::Mock(c) => c.translate_base_segment(full_story, segment, note).await,
+        }
+    }
+    async fn translate_simplified_segment(
+        &self,
+        full_story: &str,
+        segment: &str,
+        note: Option<&str>,
+    ) -> Result<(String, Usage), ApiError> {
+        let protected = markup::protect(segment);
+        let segment = protected.text.as_str();
+        let (text, usage) = match self {
+            Client::Anthropic(c) => c.translate_simplified_segment(full_story, segment, note).await,
+            Client::OpenAiCompat(c) => c.translate_simplified_segment(full_story, segment, note).await,
+            #[cfg(any(test, feature = "mock"))]
+            Client::Mock(c) => c.translate_simplified_segment(full_story, segment, note).await,
+        }?;
+        Ok((protected.restore(&text), usage))
+    }
+    async fn plan_block_from_base(
+        &self,
+        base_text: &str,
+        format: StructuredFormat,
+    ) -> Result<(PlannedBlock, Usage), ApiError> {
+        match self {
+            Client::Anthropic(c) => c.plan_block_from_base(base_text, format).await,
+            Client::OpenAiCompat(c) => c.plan_block_from_base(base_text, format).await,
+            #[cfg(any(test, feature = "mock"))]
+            Client::Mock(c) => c.plan_block_from_base(base_text, format).await,
+        }
+    }
+    async fn score_translation(&self, source: &str, translation: &str) -> Result<(JudgeVerdict, Usage), ApiError> {
+        match self {
+            Client::Anthropic(c) => c.score_translation(source, translation).await,
+            Client::OpenAiCompat(c) => c.score_translation(source, translation).await,
+            #[cfg(any(test, feature = "mock"))]
+            Client::Mock(c) => c.score_translation(source, translation).await,
+        }
+    }
+    async fn generate_span_variants(
+        &self,
+        segment_context: &str,
+        anchor_phrase: &str,
+        variant_count: u32,
+        format: StructuredFormat,
+    ) -> Result<(Vec<PlannedVariant>, Usage), ApiError> {
+        let (context, anchor) = (segment_context, anchor_phrase);
+        match self {
+            Client::Anthropic(c) => c.generate_span_variants(context, anchor, variant_count, format).await,
+            Client::OpenAiCompat(c) => c.generate_span_variants(context, anchor, variant_count, format).await,
+            #[cfg(any(test, feature = "mock"))]
+            Client::Mock(c) => c.generate_span_variants(context, anchor, variant_count, format).await,
+        }
+    }
+    async fn refine_span_variants(
+        &self,
+        segment_context: &str,
+        anchor_phrase: &str,
+        variants: &[PlannedVariant],
+    ) -> Result<(Vec<PlannedVariant>, Usage), ApiError> {
+        match self {
+            Client::Anthropic(c) => c.refine_span_variants(segment_context, anchor_phrase, variants).await,
+            Client::OpenAiCompat(c) => c.refine_span_variants(segment_context, anchor_phrase, variants).await,
+            #[cfg(any(test, feature = "mock"))]
+            Client::Mock(c) => c.refine_span_variants(segment_context, anchor_phrase, variants).await,
+        }
+    }
+}
+
+pub(crate) fn fill_anthropic_key(cfg: &mut ApiConfig) {
+    if matches!(cfg.provider.preset, LlmProviderPreset::Anthropic)
+        && cfg
+            .provider
+            .api_key
+            .as_ref()
+            .map(|k| k.trim().is_empty())
+            .unwrap_or(true)
+    {
+        cfg.provider.api_key = std::env::var("ANTHROPIC_API_KEY").ok();
+    }
+}
+
+/// Parse failures a job tolerates before switching to the simple output format.
+const SIMPLE_FORMAT_AFTER: u32 = 2;
+
+/// After a failed planning or variants call, whether to make it again. Bad
+/// output is retried; once the model has broken the JSON format
+/// `SIMPLE_FORMAT_AFTER` times the rest of the job uses the simple one, and
+/// failures in that format are final.
+fn retry_output(error: &ApiError, format: &mut StructuredFormat, failures: &mut u32, report: &mut RunReport) -> bool {
+    if *format == StructuredFormat::Simple || !is_output_failure(error) {
+        return false;
+    }
+    report.output_failure(error);
+    report.retries(1);
+    *failures += 1;
+    if *failures >= SIMPLE_FORMAT_AFTER {
+        *format = StructuredFormat::Simple;
+        report.simple_format();
+    }
+    true
+}
+
+/// The outcome of the critique-and-fix pass over `variants`. A failed pass
+/// is noted in the report and keeps the generated list.
+fn refined_variants(
+    report: &mut RunReport,
+    anchor: &str,
+    variants: Vec<PlannedVariant>,
+    refined: Result<(Vec<PlannedVariant>, Usage), ApiError>,
+    total_usage: &mut Usage,
+) -> Vec<PlannedVariant> {
+    match refined {
+        Ok((refined, usage)) => {
+            *total_usage += usage;
+            merge_refined(variants, refined)
+        }
+        Err(e) => {
+            report.output_failure(&e);
+            report.warn(format!("variant refinement failed for \"{}\": {}", anchor, e));
+            variants
+        }
+    }
+}
+
+/// Variant calls of one segment in flight at once. There is no limiter
+/// shared across jobs, so this keeps a dense segment from bursting into the
+/// provider's rate limit.
+const MAX_CONCURRENT_SPANS: usize = 4;
+
+/// The next call a span needs.
+enum SpanStep {
+    Generate,
+    Refine(Vec<PlannedVariant>),
+}
+
+/// What a [`SpanStep`] came back with; a refinement hands back the list it
+/// was given.
+enum SpanReply {
+    Generated(Result<(Vec<PlannedVariant>, Usage), ApiError>),
+    Refined(Vec<PlannedVariant>, Result<(Vec<PlannedVariant>, Usage), ApiError>),
+}
+
+/// Calls run together on the job's own task, each tagged with a key and the
+/// time it started. Nothing is spawned, so the calls need no runtime and
+/// are dropped, unfinished, with the set.
+struct InFlight<'a, T> {
+    calls: Vec<(usize, Instant, BoxedCall<'a, T>)>,
+}
+
+type BoxedCall<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+impl<'a, T> InFlight<'a, T> {
+    fn new() -> Self {
+        Self { calls: Vec::new() }
+    }
+
+    fn len(&self) -> usize {
+        self.calls.len()
+    }
+
+    fn push(&mut self, key: usize, call: impl Future<Output = T> + Send + 'a) {
+        self.calls.push((key, Instant::now(), Box::pin(call)));
+    }
+
+    /// The next call to finish, earlier pushes first when several are ready;
+    /// `None` once the set is empty.
+    async fn next(&mut self) -> Option<(usize, Instant, T)> {
+        if self.calls.is_empty() {
+            return None;
+        }
+        std::future::poll_fn(|cx| {
+            for j in 0..self.calls.len() {
+                if let Poll::Ready(out) = self.calls[j].2.as_mut().poll(cx) {
+                    let (key, started, _) = self.calls.remove(j);
+                    return Poll::Ready(Some((key, started, out)));
+                }
+            }
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+/// The critique's list, minus blank and repeated entries, behind the
+/// neutral variant the span was planned from so the span still reads as the
+/// base text. The critique's version of that variant wins if it kept one.
+/// An empty critique keeps the generated list.
+fn merge_refined(generated: Vec<PlannedVariant>, refined: Vec<PlannedVariant>) -> Vec<PlannedVariant> {
+    let refined: Vec<PlannedVariant> = refined.into_iter().filter(|v| !v.text.trim().is_empty()).collect();
+    let Some(first) = generated.first().filter(|_| !refined.is_empty()) else {
+        return if refined.is_empty() { generated } else { refined };
+    };
+    let anchor = refined.iter().find(|v| v.text == first.text).unwrap_or(first).clone();
+    let mut merged = vec![anchor];
+    for v in refined {
+        if !merged.iter().any(|m| m.text == v.text) {
+            merged.push(v);
+        }
+    }
+    merged
+}
+
+/// Base translation of a segment's `pieces` (see [`split_overlong`]),
+/// joined back into one text. Pieces in `cache` cost no call.
+async fn translate_pieces(
+    client: &Client,
+    cache: Option<&ScopedCache>,
+    target_language: &str,
+    story_text: &str,
+    pieces: &[String],
+    total_usage: &mut Usage,
+) -> Result<String, ApiError> {
+    let mut parts = Vec::with_capacity(pieces.len());
+    for piece in pieces {
+        if let Some(text) = cache.and_then(|cache| cache.get(piece)) {
+            parts.push(text);
+            continue;
+        }
+        let (text, usage) = client.translate_base_segment(story_text, piece).await?;
+        *total_usage += usage;
+        if let Some(cache) = cache {
+            cache.put(piece, &text);
+        }
+        parts.push(text);
+    }
+    Ok(parts.join(piece_separator(target_language)))
+}
+
+/// Graded-reader version of [`translate_pieces`]. The vocabulary check of a
+/// split segment covers the joined text.
+async fn simplify_pieces(
+    client: &Client,
+    level: CefrLevel,
+    target_language: &str,
+    story_text: &str,
+    pieces: &[String],
+    total_usage: &mut Usage,
+) -> Result<(String, VocabularyCheck), ApiError> {
+    if let [piece] = pieces {
+        return simplify_segment(client, level, target_language, story_text, piece, total_usage).await;
+    }
+    let mut parts = Vec::with_capacity(pieces.len());
+    let mut attempts = 1;
+    for piece in pieces {
+        let (text, check) = simplify_segment(client, level, target_language, story_text, piece, total_usage).await?;
+        attempts = attempts.max(check.attempts);
+        parts.push(text);
+    }
+    let text = parts.join(piece_separator(target_language));
+    let mut check = check_vocabulary(&text, target_language, level);
+    check.attempts = attempts;
+    Ok((text, check))
+}
+
+fn piece_separator(target_language: &str) -> &'static str {
+    if is_unspaced_script(target_language) {
+        ""
+    } else {
+        " "
+    }
+}
+
+/// Graded-reader translation of `source`, retried once with a revision note
+/// when it fails the level's vocabulary check. Keeps whichever attempt has
+/// fewer violations.
+async fn simplify_segment(
+    client: &Client,
+    level: CefrLevel,
+    target_language: &str,
+    story_text: &str,
+    source: &str,
+    total_usage: &mut Usage,
+) -> Result<(String, VocabularyCheck), ApiError> {
+    let (text, usage) = client.translate_simplified_segment(story_text, source, None).await?;
+    *total_usage += usage;
+    let check = check_vocabulary(&text, target_language, level);
+    if check.passed {
+        return Ok((text, check));
+    }
+
+    let note = simplify::retry_note(&check);
+    let (retry, usage) = client.translate_simplified_segment(story_text, source, Some(&note)).await?;
+    *total_usage += usage;
+    let retry_check = check_vocabulary(&retry, target_language, level);
+
+    let (text, mut check) = if retry_check.beats(&check) { (retry, retry_check) } else { (text, check) };
+    check.attempts = 2;
+    Ok((text, check))
+}
+
+fn note_simplification(report: &mut RunReport, check: &VocabularyCheck) {
+    report.retries(u32::from(check.attempts.saturating_sub(1)));
+    if !check.passed {
+        report.warn(format!(
+            "{} vocabulary check failed: {} long sentences, {} hard words",
+            check.level.as_str(),
+            check.long_sentences,
+            check.hard_words.len()
+        ));
+    }
+}
+
+/// Score `base` with the judge, retrying the translation once when allowed
+/// and it scores low. Returns the translation to keep and its score; a judge
+/// failure leaves the segment unscored rather than failing the job.
+async fn judge_segment(
+    client: &Client,
+    judge: &Client,
+    judge_cfg: &JudgeConfig,
+    story_text: &str,
+    source: &str,
+    base: String,
+    total_usage: &mut Usage,
+) -> (String, Option<SegmentScore>) {
+    let Ok((mut verdict, usage)) = judge.score_translation(source, &base).await else {
+        return (base, None);
+    };
+    *total_usage += usage;
+
+    let mut base = base;
+    let mut attempts = 1;
+    if judge_cfg.auto_retry && verdict.overall() < judge_cfg.min_score {
+        if let Ok((retry, usage)) = client.translate_base_segment(story_text, source).await {
+            *total_usage += usage;
+            attempts = 2;
+            if let Ok((retry_verdict, usage)) = judge.score_translation(source, &retry).await {
+                *total_usage += usage;
+                if retry_verdict.overall() > verdict.overall() {
+                    base = retry;
+                    verdict = retry_verdict;
+                }
+            }
+        }
+    }
+
+    let score = SegmentScore {
+        adequacy: verdict.adequacy,
+        fluency: verdict.fluency,
+        rationale: verdict.rationale.clone(),
+        judge_model: judge.model().to_string(),
+        needs_review: verdict.overall() < judge_cfg.min_score,
+        attempts,
+    };
+    (base, Some(score))
+}
+
+pub async fn run_translation(args: TranslationArgs) -> Result<TranslationResult, ApiError> {
+    run_translation_with_report(args).await.0
+}
+
+/// [`run_translation`] plus the job's run report, which is filled in
+/// however the job ends.
+pub async fn run_translation_with_report(args: TranslationArgs) -> (Result<TranslationResult, ApiError>, RunReport) {
+    let mut report = RunReport::new(&args.job_id, &args.target_language);
+    let result = run_job(args, &mut report).await;
+    report.finish(&result);
+    (result, report)
+}
+
+async fn run_job(args: TranslationArgs, report: &mut RunReport) -> Result<TranslationResult, ApiError> {
+    let TranslationArgs {
+        story_text,
+        job_id,
+        target_language,
+        source_language,
+        adult_mode,
+        content_policy,
+        dense_spans,
+        reproducible,
+        prompt_overrides,
+        judge,
+        variant_bounds,
+        length_guard,
+        simplify_level,
+        dual_output,
+        refine_variants,
+        granularity,
+        comprehension_every,
+        annotations,
+        mut review,
+        error_policy,
+        budget,
+        budget_gate,
+        confirmation,
+        translation_cache,
+        transcript,
+        ui_language,
+        provider,
+        cancelled,
+        mut on_job,
+        mut on_doc,
+    } = args;
+
+    limits::check_input(&story_text)?;
+    let chapters = limits::chunk_chapters(&story_text, limits::CHAPTER_CHARS);
+    let seg_texts: Vec<(usize, String)> = chapters
+        .iter()
+        .enumerate()
+        .flat_map(|(ci, chapter)| split_segments(chapter, granularity).into_iter().map(move |s| (ci, s)))
+        .collect();
+    if seg_texts.is_empty() {
+        return Err(ApiError::Parse("No segments".to_string()));
+    }
+
+    let mut cfg = PromptOptions {
+        target_language,
+        source_language,
+        adult_mode,
+        content_policy,
+        dense_spans,
+        simplify_level,
+        prompt_overrides,
+        refine_variants,
+        granularity,
+        ui_language,
+        provider,
+    }
+    .config();
+    if reproducible {
+        cfg.sampling = SamplingParams::reproducible(cfg.provider.preset);
+    }
+    cfg.transcript = transcript.as_deref().map(|path| Arc::new(transcript::recorder(path)));
+    cfg.length_guard = length_guard;
+    let policy = cfg.content_policy.clone();
+    let direction = direction_for_language(&cfg.target_language);
+
+    fill_anthropic_key(&mut cfg);
+
+    let client = Client::new(cfg.clone())?;
+    report.provider = Some(cfg.provider.preset);
+    report.model = Some(client.model().to_string());
+
+    if chapters.len() > 1 && confirmation.as_deref() != Some(limits::confirmation_token(&story_text, client.model()).as_str()) {
+        return Err(ApiError::ConfirmationRequired {
+            segments: seg_texts.len(),
+        });
+    }
+
+    // The judge scores faithfulness, which simplified output gives up on
+    // purpose; with dual output it scores the faithful translation.
+    let judge = match judge.filter(|_| simplify_level.is_none() || dual_output) {
+        Some(judge_cfg) => {
+            let mut jcfg = cfg.clone();
+            if let Some(p) = judge_cfg.provider.clone() {
+                jcfg.provider = p;
+                fill_anthropic_key(&mut jcfg);
+            }
+            // Scores should not wobble between runs.
+            jcfg.sampling = SamplingParams::reproducible(jcfg.provider.preset);
+            Some((Client::new(jcfg)?, judge_cfg))
+        }
+        None => None,
+    };
+
+    let mut job = TranslationJob {
+        id: job_id,
+        segments: seg_texts
+            .iter()
+            .enumerate()
+            .map(|(i, (ci, s))| TranslationSegment {
+                id: format!("seg-{}", i + 1),
+                source: s.clone(),
+                chapter: *ci as u32,
+                base_text: None,
+                base_stage: SegmentStage::Pending,
+                span_stage: SegmentStage::Pending,
+                variant_count: 0,
+                variant_target: 0,
+                score: None,
+                simplified_text: None,
+                simplification: None,
+                edited: false,
+                auto_split: false,
+                refused: None,
+                provenance: vec![],
+                spans: vec![],
+            })
+            .collect(),
+        ready: false,
+        awaiting_review: false,
+        metadata: None,
+        lints: Vec::new(),
+    };
+
+    on_job.call(&job).await;
+
+    job.metadata = Some(JobMetadata {
+        reproducible,
+        provider: cfg.provider.preset,
+        model: client.model().to_string(),
+        sampling: cfg.sampling,
+        target_language: cfg.target_language.clone(),
+        source_language: cfg.source_language.clone(),
+        dense_spans: cfg.dense_spans,
+        content_policy: cfg.content_policy.clone(),
+        prompts: PromptSet::for_config(&cfg, client.json_mode()),
+        variant_bounds,
+        length_guard,
+        simplify_level,
+        dual_output: dual_output && simplify_level.is_some(),
+        refine_variants,
+        granularity,
+        comprehension_every,
+        review_required: review.is_some(),
+        error_policy,
+        budget,
+        ui_language: cfg.ui_language.clone(),
+        annotations: annotations.passes.clone(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+    });
+
+    let mut budget = budget.map(|budget| BudgetWatch {
+        budget,
+        pricing: ModelRegistry::current().pricing(cfg.provider.preset, client.model()),
+        gate: budget_gate,
+        warned: false,
+        lifted: false,
+    });
+    let segment_count = job.segments.len();
+    let mut runner = SegmentRunner {
+        cache: translation_cache.map(|cache| cache.scoped(&cfg, client.model())),
+        client,
+        judge,
+        target_language: cfg.target_language.clone(),
+        policy: policy.clone(),
+        chapters,
+        simplify_level,
+        dual_output,
+        refine_variants,
+        variant_bounds,
+        cancelled: cancelled.clone(),
+        report,
+        total_usage: Usage::default(),
+        format: StructuredFormat::Json,
+        output_failures: 0,
+    };
+    let mut builder = DocBuilder::new(&policy, direction);
+    let mut steps: VecDeque<Step> = job_steps(segment_count, review.is_some()).into();
+    let mut step_retries = vec![0; segment_count];
+
+    while let Some(step) = steps.pop_front() {
+        if cancelled.load(Ordering::Relaxed) {
+            return Err(ApiError::Parse("Cancelled".to_string()));
+        }
+        if let Some(watch) = budget.as_mut() {
+            let go_on = watch.check(&job.id, &runner.total_usage).await;
+            if cancelled.load(Ordering::Relaxed) {
+                return Err(ApiError::Parse("Cancelled".to_string()));
+            }
+            if !go_on {
+                let tokens = u64::from(runner.total_usage.input_tokens) + u64::from(runner.total_usage.output_tokens);
+                return Err(ApiError::BudgetExceeded { tokens });
+            }
+        }
+        let outcome = match step {
+            Step::Translate(i) => runner.translate(&mut job, i, on_job.as_mut()).await,
+            Step::Review => {
+                job.awaiting_review = true;
+                on_job.call(&job).await;
+                let edits = match review.as_mut() {
+                    Some(gate) => gate.call(&job).await,
+                    None => Some(vec![]),
+                };
+                // The gate gives up when the job is cancelled while waiting.
+                let Some(edits) = edits else {
+                    return Err(ApiError::Parse("Cancelled".to_string()));
+                };
+                job.awaiting_review = false;
+                apply_review_edits(&mut job, edits);
+                on_job.call(&job).await;
+                continue;
+            }
+            // A skipped translation leaves nothing to plan.
+            Step::Plan(i) if job.segments[i].base_text.is_none() => {
+                builder.push(&placeholder_block(&job.segments[i]));
+                Ok(())
+            }
+            Step::Plan(i) => runner
+                .plan(&mut job, i, &mut builder, on_job.as_mut(), on_doc.as_mut())
+                .await
+                .map(|block| builder.push(&block)),
+        };
+
+        if let Err(e) = outcome {
+            let (Step::Translate(i) | Step::Plan(i)) = step else {
+                unreachable!("the review step never fails")
+            };
+            // The same model would refuse again, so a refusal is not retried.
+            let refused = match &e {
+                ApiError::Refused { reason, .. } => Some(reason.clone()),
+                _ => None,
+            };
+            if refused.is_some() {
+                job.segments[i].refused = refused.clone();
+                on_job.call(&job).await;
+            }
+            if is_cancellation(&e) || error_policy == ErrorPolicy::Abort {
+                return Err(e);
+            }
+            if error_policy == ErrorPolicy::RetryThenSkip && refused.is_none() && step_retries[i] < SEGMENT_RETRIES {
+                step_retries[i] += 1;
+                runner.report.retries(1);
+                steps.push_front(step);
+                continue;
+            }
+            runner.report.skip_segment(&e);
+            if let Step::Plan(i) = step {
+                builder.push(&placeholder_block(&job.segments[i]));
+            }
+        }
+        if let Step::Plan(_) = step {
+            on_doc.call(builder.doc()).await;
+        }
+    }
+
+    let mut doc = builder.into_doc();
+    let groups = comprehension::check_groups(job.segments.len(), comprehension_every.unwrap_or(0));
+    if !groups.is_empty() {
+        let mut checks = Vec::with_capacity(groups.len());
+        for group in groups {
+            if cancelled.load(Ordering::Relaxed) {
+                return Err(ApiError::Parse("Cancelled".to_string()));
+            }
+            let last = group.end - 1;
+            if let Some(check) = runner.comprehension_check(&job, group).await {
+                checks.push((last, check));
+            }
+        }
+        comprehension::insert_checks(&mut doc, checks);
+        on_doc.call(&doc).await;
+    }
+    if !annotations.is_empty() {
+        let source_language = cfg.source_language.as_deref();
+        for skipped in annotate_doc(&mut doc, &annotations, &cfg.target_language, source_language) {
+            runner.report.warn(skipped.to_string());
+        }
+        on_doc.call(&doc).await;
+    }
+    job.lints = lint_doc(&doc);
+    job.ready = true;
+    on_job.call(&job).await;
+
+    Ok(TranslationResult {
+        job,
+        doc,
+        usage: runner.total_usage,
+    })
+}
+
+/// A job's [`JobBudget`] and what has been said about it so far.
+struct BudgetWatch {
+    budget: JobBudget,
+    pricing: Option<ModelPricing>,
+    gate: Option<Box<dyn BudgetGate>>,
+    warned: bool,
+    /// Allowed past the cap; the job is not paused again.
+    lifted: bool,
+}
+
+impl BudgetWatch {
+    /// Whether the job may take its next step after spending `usage`.
+    async fn check(&mut self, job_id: &str, usage: &Usage) -> bool {
+        if self.lifted {
+            return true;
+        }
+        let status = self.budget.status(job_id, usage, self.pricing.as_ref());
+        if status.reached {
+            self.lifted = match self.gate.as_mut() {
+                Some(gate) => gate.call(&status).await,
+                None => false,
+            };
+            return self.lifted;
+        }
+        if status.fraction >= BUDGET_WARNING && !self.warned {
+            self.warned = true;
+            if let Some(gate) = self.gate.as_mut() {
+                gate.call(&status).await;
+            }
+        }
+        true
+    }
+}
+
+fn is_cancellation(error: &ApiError) -> bool {
+    matches!(error, ApiError::Parse(m) if m == "Cancelled")
+}
+
+/// Stands in for a segment that failed under a skipping [`ErrorPolicy`]:
+/// its base translation as plain text, or the untranslated source when
+/// even that failed.
+fn placeholder_block(segment: &TranslationSegment) -> PlannedBlock {
+    PlannedBlock {
+        id: "b1".to_string(),
+        segments: vec![PlannedSegment::Static(
+            segment.base_text.clone().unwrap_or_else(|| segment.source.clone()),
+        )],
+    }
+}
+
+/// Translate again the segments a job skipped (see [`ErrorPolicy`]) and
+/// splice their blocks into its doc. Segments that fail again keep their
+/// `Error` stage and placeholder block; only cancellation stops the run.
+pub async fn retry_failed_segments(args: RetryArgs) -> Result<TranslationResult, ApiError> {
+    let RetryArgs {
+        mut job,
+        mut doc,
+        story_text,
+        prompt_overrides,
+        provider,
+        transcript,
+        annotation_data_dir,
+        cancelled,
+        mut on_job,
+        mut on_doc,
+    } = args;
+
+    let Some(meta) = job.metadata.clone() else {
+        return Err(ApiError::Parse("Job has no metadata to retry with".to_string()));
+    };
+    if doc.block_texts().len() != job.segments.len() {
+        return Err(ApiError::Parse("Doc blocks do not match the job's segments".to_string()));
+    }
+
+    let provider = provider.unwrap_or_else(|| LlmProviderConfig {
+        preset: meta.provider,
+        model: Some(meta.model.clone()),
+        ..Default::default()
+    });
+    let mut cfg = PromptOptions {
+        target_language: meta.target_language.clone(),
+        source_language: meta.source_language.clone(),
+        adult_mode: false,
+        content_policy: Some(meta.content_policy.clone()),
+        dense_spans: meta.dense_spans,
+        simplify_level: meta.simplify_level,
+        prompt_overrides,
+        refine_variants: meta.refine_variants,
+        granularity: meta.granularity,
+        ui_language: meta.ui_language.clone(),
+        provider,
+    }
+    .config();
+    if meta.reproducible {
+        cfg.sampling = SamplingParams::reproducible(cfg.provider.preset);
+    }
+    cfg.transcript = transcript.as_deref().map(|path| Arc::new(transcript::recorder(path)));
+    cfg.length_guard = meta.length_guard;
+    let policy = cfg.content_policy.clone();
+    let direction = direction_for_language(&cfg.target_language);
+    fill_anthropic_key(&mut cfg);
+
+    let mut report = RunReport::new(&job.id, &cfg.target_language);
+    let mut runner = SegmentRunner {
+        client: Client::new(cfg.clone())?,
+        // Failed segments never reached the cache; they go to the model again.
+        cache: None,
+        judge: None,
+        target_language: cfg.target_language.clone(),
+        policy: policy.clone(),
+        chapters: limits::chunk_chapters(&story_text, limits::CHAPTER_CHARS),
+        simplify_level: meta.simplify_level,
+        dual_output: meta.dual_output,
+        refine_variants: meta.refine_variants,
+        variant_bounds: meta.variant_bounds,
+        cancelled: cancelled.clone(),
+        report: &mut report,
+        total_usage: Usage::default(),
+        format: StructuredFormat::Json,
+        output_failures: 0,
+    };
+    let annotations = AnnotationPlan {
+        passes: meta.annotations.clone(),
+        data_dir: annotation_data_dir,
+        registry: None,
+    };
+    // The doc only changes once a segment's block is done.
+    let mut no_partial_docs = |_: &InteractiveDoc| async {};
+
+    let failed: Vec<usize> = (0..job.segments.len())
+        .filter(|&i| {
+            let segment = &job.segments[i];
+            segment.base_stage == SegmentStage::Error || segment.span_stage == SegmentStage::Error
+        })
+        .collect();
+    for i in failed {
+        if cancelled.load(Ordering::Relaxed) {
+            return Err(ApiError::Parse("Cancelled".to_string()));
+        }
+        if job.segments[i].base_text.is_none() {
+            job.segments[i].base_stage = SegmentStage::Pending;
+        }
+        job.segments[i].span_stage = SegmentStage::Pending;
+        job.segments[i].refused = None;
+        on_job.call(&job).await;
+
+        let outcome = match job.segments[i].base_text {
+            Some(_) => Ok(()),
+            None => runner.translate(&mut job, i, on_job.as_mut()).await,
+        };
+        let outcome = match outcome {
+            Ok(()) => {
+                let mut scratch = DocBuilder::new(&policy, direction);
+                runner.plan(&mut job, i, &mut scratch, on_job.as_mut(), &mut no_partial_docs).await
+            }
+            Err(e) => Err(e),
+        };
+        match outcome {
+            Ok(block) => {
+                let mut block = build_doc_from_blocks([&block], &policy, direction);
+                let source_language = cfg.source_language.as_deref();
+                for skipped in annotate_doc(&mut block, &annotations, &cfg.target_language, source_language) {
+                    runner.report.warn(skipped.to_string());
+                }
+                doc.replace_block(i, block);
+                on_doc.call(&doc).await;
+            }
+            Err(e) if is_cancellation(&e) => return Err(e),
+            Err(ApiError::Refused { reason, .. }) => {
+                job.segments[i].refused = Some(reason);
+                on_job.call(&job).await;
+            }
+            Err(_) => {}
+        }
+    }
+    job.lints = lint_doc(&doc);
+
+    Ok(TranslationResult {
+        job,
+        doc,
+        usage: runner.total_usage,
+    })
+}
+
+/// The per-segment work of a job, shared by [`run_translation`] and
+/// [`retry_failed_segments`]. A failing step marks its segment's stages as
+/// `Error` before returning the error.
+struct SegmentRunner<'r> {
+    client: Client,
+    cache: Option<ScopedCache>,
+    judge: Option<(Client, JudgeConfig)>,
+    target_language: String,
+    policy: ContentPolicy,
+    /// Large stories are chunked: a segment only sees its own chapter as context.
+    chapters: Vec<String>,
+    simplify_level: Option<CefrLevel>,
+    dual_output: bool,
+    refine_variants: bool,
+    variant_bounds: VariantBounds,
+    cancelled: Arc<AtomicBool>,
+    report: &'r mut RunReport,
+    total_usage: Usage,
+    format: StructuredFormat,
+    output_failures: u32,
+}
+
+impl SegmentRunner<'_> {
+    /// Base translation of segment `i`, judged and simplified as configured.
+    async fn translate(
+        &mut self,
+        job: &mut TranslationJob,
+        i: usize,
+        on_job: &mut dyn JobSink,
+    ) -> Result<(), ApiError> {
+        let seg_src = job.segments[i].source.clone();
+        let context = self.chapters.get(job.segments[i].chapter as usize).map_or(seg_src.as_str(), String::as_str);
+        self.report
+            .begin_segment(&job.segments[i].id, self.judge.as_ref().map(|(j, _)| j.model()));
+
+        let pieces = split_overlong(&seg_src, MAX_SEGMENT_TOKENS);
+        if pieces.len() > 1 {
+            job.segments[i].auto_split = true;
+            self.report.warn(format!("overlong segment translated in {} pieces", pieces.len()));
+        }
+
+        let started = Instant::now();
+        let translated = match self.simplify_level.filter(|_| !self.dual_output) {
+            Some(level) => {
+                let target = &self.target_language;
+                let result =
+                    simplify_pieces(&self.client, level, target, context, &pieces, &mut self.total_usage).await;
+                self.report.timed(RunStage::Simplify, started);
+                result.map(|(text, check)| {
+                    note_simplification(self.report, &check);
+                    job.segments[i].simplification = Some(check);
+                    text
+                })
+            }
+            None => {
+                let target = &self.target_language;
+                let cache = self.cache.as_ref();
+                let result =
+                    translate_pieces(&self.client, cache, target, context, &pieces, &mut self.total_usage).await;
+                self.report.timed(RunStage::Translate, started);
+                result
+            }
+        };
+        let call = self.client.provenance(job.segments[i].provenance.len() as u32 + 1, started);
+        self.report.call(call.clone());
+        job.segments[i].provenance.push(call);
+
+        let base = match translated {
+            Ok(base) => base,
+            Err(e) => {
+                job.segments[i].base_stage = SegmentStage::Error;
+                job.segments[i].span_stage = SegmentStage::Error;
+                on_job.call(job).await;
+                return Err(e);
+            }
+        };
+        let base = match &self.judge {
+            Some((judge_client, judge_cfg)) => {
+                // A retranslation is sent whole and would be cut off again.
+                let judge_cfg = &JudgeConfig {
+                    auto_retry: judge_cfg.auto_retry && pieces.len() == 1,
+                    ..judge_cfg.clone()
+                };
+                let started = Instant::now();
+                let (base, score) =
+                    judge_segment(&self.client, judge_client, judge_cfg, context, &seg_src, base, &mut self.total_usage)
+                        .await;
+                self.report.timed(RunStage::Judge, started);
+                match &score {
+                    Some(score) => {
+                        self.report.retries(u32::from(score.attempts.saturating_sub(1)));
+                        if score.needs_review {
+                            self.report.warn(format!(
+                                "judge flagged for review (adequacy {}, fluency {})",
+                                score.adequacy, score.fluency
+                            ));
+                        }
+                    }
+                    None => self.report.warn("judge failed; segment left unscored".to_string()),
+                }
+                job.segments[i].score = score;
+                base
+            }
+            None => base,
+        };
+        // Dual output: the graded-reader version sits next to the
+        // faithful one; spans are planned on the faithful text only.
+        if let Some(level) = self.simplify_level.filter(|_| self.dual_output) {
+            let started = Instant::now();
+            let target = &self.target_language;
+            let result = simplify_pieces(&self.client, level, target, context, &pieces, &mut self.total_usage).await;
+            self.report.timed(RunStage::Simplify, started);
+            match result {
+                Ok((text, check)) => {
+                    note_simplification(self.report, &check);
+                    job.segments[i].simplified_text = Some(typography::normalize(&text, &self.target_language));
+                    job.segments[i].simplification = Some(check);
+                }
+                Err(e) => {
+                    job.segments[i].base_stage = SegmentStage::Error;
+                    job.segments[i].span_stage = SegmentStage::Error;
+                    on_job.call(job).await;
+                    return Err(e);
+                }
+            }
+        }
+        job.segments[i].base_text = Some(typography::normalize(&base, &self.target_language));
+        job.segments[i].base_stage = SegmentStage::Ready;
+        on_job.call(job).await;
+        self.report.end_segment(self.total_usage);
+        Ok(())
+    }
+
+    /// The check closing the blocks of `group`, or `None` with a warning on
+    /// the group's last segment when the model gives no usable one.
+    async fn comprehension_check(&mut self, job: &TranslationJob, group: Range<usize>) -> Option<DocToken> {
+        let segments = &job.segments[group];
+        let passage: Vec<&str> = segments.iter().map(|s| s.source.trim()).collect();
+        self.report.resume_segment(&segments[segments.len() - 1].id);
+
+        let started = Instant::now();
+        let result = self.client.comprehension_check(&passage.join("\n\n")).await;
+        self.report.timed(RunStage::Check, started);
+        let check = match result {
+            Ok((reply, usage)) => {
+                self.total_usage += usage;
+                comprehension::parse_check(&reply)
+            }
+            Err(e) => Err(e),
+        };
+        let check = match check {
+            Ok(check) => Some(check),
+            Err(e) => {
+                self.report.output_failure(&e);
+                self.report.warn(format!("comprehension check failed: {}", e));
+                None
+            }
+        };
+        self.report.end_segment(self.total_usage);
+        check
+    }
+
+    /// Plan segment `i`'s block from its base text and generate its
+    /// variants, showing it in `doc` as each span's variants arrive. The
+    /// caller pushes the returned block.
+    async fn plan(
+        &mut self,
+        job: &mut TranslationJob,
+        i: usize,
+        doc: &mut DocBuilder,
+        on_job: &mut dyn JobSink,
+        on_doc: &mut dyn DocSink,
+    ) -> Result<PlannedBlock, ApiError> {
+        let base = job.segments[i].base_text.clone().unwrap_or_default();
+        self.report.resume_segment(&job.segments[i].id);
+        job.segments[i].spans.clear();
+
+        let block = loop {
+            let started = Instant::now();
+            let planned = self.client.plan_block_from_base(&base, self.format).await;
+            self.report.timed(RunStage::Plan, started);
+            match planned {
+                Err(e) if retry_output(&e, &mut self.format, &mut self.output_failures, self.report) => continue,
+                planned => break planned,
+            }
+        };
+        let block = match block {
+            Ok((b, usage)) => {
+                self.total_usage += usage;
+                b
+            }
+            Err(e) => {
+                job.segments[i].span_stage = SegmentStage::Error;
+                on_job.call(job).await;
+                return Err(e);
+            }
+        };
+
+        let mut next_block = block;
+        let mut variant_count: u32 = 0;
+        let variant_target = self
+            .variant_bounds
+            .target(segment_difficulty(&base, &self.target_language));
+        job.segments[i].variant_target = variant_target;
+
+        let mut swappable_anchors: Vec<(usize, String)> = Vec::new();
+        for (seg_i, seg) in next_block.segments.iter().enumerate() {
+            let span = match seg {
+                PlannedSegment::Swappable(s) => s,
+                _ => continue,
+            };
+
+            let anchor = span
+                .variants
+                .first()
+                .map(|v| v.text.as_str())
+                .unwrap_or("");
+
+            if anchor.trim().is_empty() {
+                continue;
+            }
+
+            swappable_anchors.push((seg_i, anchor.to_string()));
+        }
+
+        job.segments[i].spans = swappable_anchors
+            .iter()
+            .map(|(_, anchor)| SpanProgress {
+                anchor: anchor.clone(),
+                stage: SpanStage::Pending,
+                variants: 0,
+            })
+            .collect();
+        on_job.call(job).await;
+
+        // Spans are generated concurrently; everything that touches the job,
+        // the report or the output format happens here, one reply at a time.
+        let client = &self.client;
+        let base = base.as_str();
+        let mut steps: VecDeque<(usize, SpanStep)> =
+            (0..swappable_anchors.len()).map(|k| (k, SpanStep::Generate)).collect();
+        let mut attempts = vec![0u32; swappable_anchors.len()];
+        let mut span_calls: Vec<Vec<CallProvenance>> = vec![vec![]; swappable_anchors.len()];
+        let mut calls = InFlight::new();
+        loop {
+            while calls.len() < MAX_CONCURRENT_SPANS {
+                let Some((k, step)) = steps.pop_front() else {
+                    break;
+                };
+                if self.cancelled.load(Ordering::Relaxed) {
+                    return Err(ApiError::Parse("Cancelled".to_string()));
+                }
+                let anchor = swappable_anchors[k].1.as_str();
+                let progress = &mut job.segments[i].spans[k];
+                match step {
+                    SpanStep::Generate => {
+                        progress.stage = SpanStage::Variants;
+                        let format = self.format;
+                        calls.push(k, async move {
+                            let generated = client.generate_span_variants(base, anchor, variant_target, format).await;
+                            SpanReply::Generated(generated)
+                        });
+                    }
+                    SpanStep::Refine(vs) => {
+                        progress.stage = SpanStage::Qa;
+                        progress.variants = vs.len() as u32;
+                        calls.push(k, async move {
+                            let refined = client.refine_span_variants(base, anchor, &vs).await;
+                            SpanReply::Refined(vs, refined)
+                        });
+                    }
+                }
+                on_job.call(job).await;
+            }
+
+            let Some((k, started, reply)) = calls.next().await else {
+                break;
+            };
+            // Calls still in flight are dropped with `calls`.
+            if self.cancelled.load(Ordering::Relaxed) {
+                return Err(ApiError::Parse("Cancelled".to_string()));
+            }
+            let (seg_i, anchor) = &swappable_anchors[k];
+            let vs = match reply {
+                SpanReply::Generated(generated) => {
+                    self.report.timed(RunStage::Variants, started);
+                    let call = client.provenance(span_calls[k].len() as u32 + 1, started);
+                    self.report.call(call.clone());
+                    span_calls[k].push(call);
+                    match generated {
+                        Ok((vs, usage)) => {
+                            self.total_usage += usage;
+                            // Jobs that fell back to the simple format skip the critique, which needs JSON.
+                            if self.refine_variants && self.format == StructuredFormat::Json {
+                                steps.push_front((k, SpanStep::Refine(vs)));
+                                continue;
+                            }
+                            vs
+                        }
+                        Err(e) if retry_output(&e, &mut self.format, &mut self.output_failures, self.report) => {
+                            steps.push_front((k, SpanStep::Generate));
+                            continue;
+                        }
+                        Err(e) => {
+                            job.segments[i].span_stage = SegmentStage::Error;
+                            job.segments[i].spans[k].stage = SpanStage::Error;
+                            on_job.call(job).await;
+                            return Err(e);
+                        }
+                    }
+                }
+                SpanReply::Refined(vs, refined) => {
+                    self.report.timed(RunStage::Refine, started);
+                    refined_variants(self.report, anchor, vs, refined, &mut self.total_usage)
+                }
+            };
+            // Moderated policies reject and regenerate unsafe lists before
+            // falling back to dropping the offending variants.
+            attempts[k] += 1;
+            if attempts[k] <= MODERATION_RETRIES && self.policy.any_unsafe(&vs) {
+                steps.push_front((k, SpanStep::Generate));
+                continue;
+            }
+            self.report.retries(attempts[k] - 1);
+            let generated = vs.len();
+            let variants = self.policy.filter_variants(vs);
+            if variants.len() < generated {
+                self.report.warn(format!(
+                    "moderation dropped {} of {} variants for \"{}\"",
+                    generated - variants.len(),
+                    generated,
+                    anchor
+                ));
+            }
+            let variants_len = variants.len();
+
+            if let Some(PlannedSegment::Swappable(span)) = next_block.segments.get_mut(*seg_i) {
+                span.variants = variants;
+                span.provenance = std::mem::take(&mut span_calls[k]);
+            }
+
+            variant_count += variants_len as u32;
+            job.segments[i].variant_count = variant_count;
+            let progress = &mut job.segments[i].spans[k];
+            progress.stage = SpanStage::Ready;
+            progress.variants = variants_len as u32;
+            on_job.call(job).await;
+
+            doc.update_last(&next_block);
+            on_doc.call(doc.doc()).await;
+        }
+
+        typography::normalize_block(&mut next_block, &self.target_language);
+        job.segments[i].span_stage = SegmentStage::Ready;
+        job.segments[i].variant_count = variant_count;
+        on_job.call(job).await;
+        self.report.end_segment(self.total_usage);
+        Ok(next_block)
+    }
+}
+
+/// Everything that shapes a job's system prompts.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptOptions {
+    pub target_language: String,
+    #[serde(default)]
+    pub source_language: Option<String>,
+    #[serde(default)]
+    pub adult_mode: bool,
+    /// Overrides the policy implied by `adult_mode` when set.
+    #[serde(default)]
+    pub content_policy: Option<ContentPolicy>,
+    #[serde(default)]
+    pub dense_spans: bool,
+    #[serde(default)]
+    pub simplify_level: Option<CefrLevel>,
+    #[serde(default)]
+    pub prompt_overrides: PromptOverrides,
+    #[serde(default)]
+    pub refine_variants: bool,
+    #[serde(default)]
+    pub granularity: Granularity,
+    /// Language of the learner notes; English when unset.
+    #[serde(default)]
+    pub ui_language: Option<String>,
+    /// Decides whether the JSON-mode note is added.
+    #[serde(default)]
+    pub provider: LlmProviderConfig,
+}
+
+impl PromptOptions {
+    pub(crate) fn config(self) -> ApiConfig {
+        let mut cfg = ApiConfig::from_env(
+            &self.target_language,
+            self.source_language.as_deref(),
+            self.adult_mode,
+            self.dense_spans,
+        );
+        cfg.provider = self.provider;
+        if let Some(p) = self.content_policy {
+            cfg.content_policy = p;
+        }
+        cfg.prompt_overrides = self.prompt_overrides;
+        cfg.simplify_level = self.simplify_level;
+        cfg.refine_variants = self.refine_variants;
+        cfg.granularity = self.granularity;
+        cfg.ui_language = self.ui_language;
+        cfg
+    }
+}
+
+/// The system prompts a job would run with, as recorded in its metadata.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptPreview {
+    pub prompts: PromptSet,
+    /// Model the provider would use; `None` when it has no default.
+    pub model: Option<String>,
+    pub json_mode: bool,
+}
+
+/// Assemble the prompts for `options` without creating a client, so it
+/// works without an API key.
+pub fn preview_prompts(options: PromptOptions) -> PromptPreview {
+    let cfg = options.config();
+    let preset = cfg.provider.preset;
+    let registry = ModelRegistry::current();
+    let model = cfg
+        .provider
+        .model
+        .as_deref()
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .or_else(|| registry.default_model(preset))
+        .map(str::to_string);
+    // Only OpenAI-compatible clients switch to JSON mode; see `Client::json_mode`.
+    let openai_compat = match preset {
+        LlmProviderPreset::Anthropic => false,
+        #[cfg(any(test, feature = "mock"))]
+        LlmProviderPreset::Mock => false,
+        _ => true,
+    };
+    let json_mode = openai_compat
+        && model
+            .as_deref()
+            .is_some_and(|m| registry.capabilities(preset, m).json_mode);
+    PromptPreview {
+        prompts: PromptSet::for_config(&cfg, json_mode),
+        model,
+        json_mode,
+    }
+}
+
+pub struct TranslationArgs {
+    pub story_text: String,
+    pub job_id: String,
+    pub target_language: String,
+    pub source_language: Option<String>,
+    pub adult_mode: bool,
+    /// Overrides the policy implied by `adult_mode` when set.
+    pub content_policy: Option<ContentPolicy>,
+    pub dense_spans: bool,
+    /// Greedy sampling with a fixed seed where the provider supports it.
+    pub reproducible: bool,
+    /// Replacement system prompts; empty keeps the built-in ones.
+    pub prompt_overrides: PromptOverrides,
+    /// Score each base translation with a judge model when set.
+    pub judge: Option<JudgeConfig>,
+    /// Per-span variant count range; harder segments ask for more.
+    pub variant_bounds: VariantBounds,
+    /// When a base translation is too long to be only a translation.
+    pub length_guard: LengthGuard,
+    /// Produce a graded-reader version at this level instead of a faithful
+    /// translation. Disables the judge unless `dual_output` is set.
+    pub simplify_level: Option<CefrLevel>,
+    /// With `simplify_level`, keep the faithful translation as `base_text`
+    /// and store the graded-reader one as `simplified_text`.
+    pub dual_output: bool,
+    /// Send each variant list back to the model to drop unnatural phrasings
+    /// and fix register labels. Costs one more call per span.
+    pub refine_variants: bool,
+    /// Size of each segment, and so of each block of the doc.
+    pub granularity: Granularity,
+    /// Close every this many blocks with a true/false comprehension
+    /// question written from their source text. One more call per check.
+    pub comprehension_every: Option<u32>,
+    /// Annotation passes run on the finished doc, such as frequency bands;
+    /// see [`crate::annotate`].
+    pub annotations: AnnotationPlan,
+    /// Pause once every base translation is ready and wait for the gate
+    /// to approve them, with corrections, before any span planning.
+    pub review: Option<Box<dyn ReviewGate>>,
+    /// Whether a failed segment stops the job or is left for
+    /// [`retry_failed_segments`].
+    pub error_policy: ErrorPolicy,
+    /// Spending cap checked between steps; at 80% and at the cap the job
+    /// asks `budget_gate`, and without one it stops at the cap.
+    pub budget: Option<JobBudget>,
+    pub budget_gate: Option<Box<dyn BudgetGate>>,
+    /// `JobPreflight::confirmation_token` for inputs large enough to be chunked.
+    pub confirmation: Option<String>,
+    /// Reuse base translations of sentences seen before, here or in other
+    /// stories, and store new ones.
+    pub translation_cache: Option<Arc<TranslationCache>>,
+    /// Append every provider exchange of the job to this file; see
+    /// [`crate::transcript`].
+    pub transcript: Option<PathBuf>,
+    /// Language the learner notes are written in; English when unset.
+    pub ui_language: Option<String>,
+    pub provider: LlmProviderConfig,
+    pub cancelled: Arc<AtomicBool>,
+    pub on_job: Box<dyn JobSink>,
+    pub on_doc: Box<dyn DocSink>,
+}
+
+/// Input of [`retry_failed_segments`]. Everything else is taken from the
+/// job's metadata.
+pub struct RetryArgs {
+    pub job: TranslationJob,
+    pub doc: InteractiveDoc,
+    /// The story the job translated, for the chapter context.
+    pub story_text: String,
+    pub prompt_overrides: PromptOverrides,
+    /// Defaults to the job's provider and model.
+    pub provider: Option<LlmProviderConfig>,
+    /// Appended to, like `TranslationArgs::transcript`.
+    pub transcript: Option<PathBuf>,
+    /// For the user's data the job's annotation passes read; built-in data
+    /// only when unset.
+    pub annotation_data_dir: Option<PathBuf>,
+    pub cancelled: Arc<AtomicBool>,
+    pub on_job: Box<dyn JobSink>,
+    pub on_doc: Box<dyn DocSink>,
+}
+
+#[allow(clippy::type_complexity)]
+pub trait JobSink: Send {
+    fn call<'a>(&'a mut self, job: &'a TranslationJob) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send + 'a>>;
+}
+
+impl<F, Fut> JobSink for F
+where
+    F: Send + 'static + FnMut(&TranslationJob) -> Fut,
+    Fut: Send + 'static + std::future::Future<Output = ()>,
+{
+    fn call<'a>(
+        &'a mut self,
+        job: &'a TranslationJob,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send + 'a>> {
+        Box::pin((self)(job))
+    }
+}
+
+pub trait DocSink: Send {
+    fn call<'a>(&'a mut self, doc: &'a InteractiveDoc) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
+}
+
+impl<F, Fut> DocSink for F
+where
+    F: Send + 'static + FnMut(&InteractiveDoc) -> Fut,
+    Fut: Send + 'static + Future<Output = ()>,
+{
+    fn call<'a>(&'a mut self, doc: &'a InteractiveDoc) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>> {
+        Box::pin((self)(doc))
+    }
+}
+
+/// Pauses a job once its base translations are ready. Resolves to the
+/// reviewer's edits when the job is approved, or `None` to cancel it.
+pub trait ReviewGate: Send {
+    fn call<'a>(&'a mut self, job: &'a TranslationJob) -> Pin<Box<dyn Future<Output = Option<Vec<SegmentEdit>>> + Send + 'a>>;
+}
+
+impl<F, Fut> ReviewGate for F
+where
+    F: Send + 'static + FnMut(&TranslationJob) -> Fut,
+    Fut: Send + 'static + Future<Output = Option<Vec<SegmentEdit>>>,
+{
+    fn call<'a>(
+        &'a mut self,
+        job: &'a TranslationJob,
+    ) -> Pin<Box<dyn Future<Output = Option<Vec<SegmentEdit>>> + Send + 'a>> {
+        Box::pin((self)(job))
+    }
+}
+
+/// Hears about a job's spend: once at [`BUDGET_WARNING`] of its budget,
+/// where the answer is ignored, and again at the cap, where it resolves to
+/// whether the job may go on. A job let past its cap is not paused again.
+pub trait BudgetGate: Send {
+    fn call<'a>(&'a mut self, status: &'a BudgetStatus) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>>;
+}
+
+impl<F, Fut> BudgetGate for F
+where
+    F: Send + 'static + FnMut(&BudgetStatus) -> Fut,
+    Fut: Send + 'static + Future<Output = bool>,
+{
+    fn call<'a>(&'a mut self, status: &'a BudgetStatus) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+        Box::pin((self)(status))
+    }
+}
+
+/// One unit of work in a job. Without a review gate each segment is
+/// planned right after it is translated; with one, every segment is
+/// translated before the pause and planned after it.
+#[derive(Debug, Clone, Copy)]
+enum Step {
+    Translate(usize),
+    Review,
+    Plan(usize),
+}
+
+fn job_steps(segments: usize, review: bool) -> Vec<Step> {
+    if review {
+        (0..segments)
+            .map(Step::Translate)
+            .chain([Step::Review])
+            .chain((0..segments).map(Step::Plan))
+            .collect()
+    } else {
+        (0..segments).flat_map(|i| [Step::Translate(i), Step::Plan(i)]).collect()
+    }
+}
+
+/// Replace the base texts the reviewer corrected. Edits for unknown
+/// segments, blank ones and ones that change nothing are ignored. The judge
+/// scored the machine translation, so an edited segment loses its score.
+fn apply_review_edits(job: &mut TranslationJob, edits: Vec<SegmentEdit>) {
+    for edit in edits {
+        let text = edit.base_text.trim();
+        let Some(segment) = job.segments.iter_mut().find(|s| s.id == edit.segment_id) else {
+            continue;
+        };
+        if text.is_empty() || segment.base_text.as_deref() == Some(text) {
+            continue;
+        }
+        segment.base_text = Some(text.to_string());
+        segment.score = None;
+        segment.edited = true;
+        // The reviewer translated a segment whose base translation failed.
+        if segment.base_stage == SegmentStage::Error {
+            segment.base_stage = SegmentStage::Ready;
+            segment.span_stage = SegmentStage::Pending;
+        }
+    }
+}
+
+/// The doc for `blocks` in order.
+pub fn build_doc_from_blocks<'a>(
+    blocks: impl IntoIterator<Item = &'a PlannedBlock>,
+    policy: &ContentPolicy,
+    direction: TextDirection,
+) -> InteractiveDoc {
+    let mut builder = DocBuilder::new(policy, direction);
+    for block in blocks {
+        builder.push(block);
+    }
+    builder.into_doc()
+}
+
+/// A job's doc, grown as its blocks are planned. Finished blocks are
+/// appended once; the block being planned is rebuilt in place as its
+/// variants come in, so a partial doc costs one block, not the whole story.
+pub struct DocBuilder {
+    doc: InteractiveDoc,
+    policy: ContentPolicy,
+    /// Spans numbered so far, for `span-N` ids.
+    span_count: usize,
+    /// Where the open block's tokens start, and `span_count` before it.
+    open: Option<(usize, usize)>,
+}
+
+impl DocBuilder {
+    pub fn new(policy: &ContentPolicy, direction: TextDirection) -> Self {
+        Self {
+            doc: InteractiveDoc {
+                tokens: Vec::new(),
+                spans: HashMap::new(),
+                direction,
+                block_directions: Vec::new(),
+                annotations: Default::default(),
+            },
+            policy: policy.clone(),
+            span_count: 0,
+            open: None,
+        }
+    }
+
+    pub fn doc(&self) -> &InteractiveDoc {
+        &self.doc
+    }
+
+    pub fn into_doc(self) -> InteractiveDoc {
+        self.doc
+    }
+
+    /// Show `block` as the one being planned, replacing what it showed
+    /// before.
+    pub fn update_last(&mut self, block: &PlannedBlock) {
+        if let Some((token_start, span_start)) = self.open.take() {
+            self.doc.tokens.truncate(token_start);
+            for n in span_start + 1..=self.span_count {
+                self.doc.spans.remove(&format!("span-{}", n));
+            }
+            self.span_count = span_start;
+            self.doc.block_directions.pop();
+        }
+        self.open = Some((self.doc.tokens.len(), self.span_count));
+        self.append(block);
+    }
+
+    /// Add `block` as finished, in place of the one being planned if any.
+    pub fn push(&mut self, block: &PlannedBlock) {
+        self.update_last(block);
+        self.open = None;
+    }
+
+    fn append(&mut self, b: &PlannedBlock) {
+        let doc = &mut self.doc;
+        if !doc.block_directions.is_empty() {
+            doc.tokens.push(DocToken::Text {
+                value: "\n\n".to_string(),
+                style: None,
+                freq_band: None,
+            });
+        }
+        doc.block_directions.push(block_direction(b).unwrap_or(doc.direction));
+
+        // Markdown emphasis becomes token styles; a span takes the style of
+        // most of its first variant.
+        let mut emphasis = Emphasis::default();
+        for seg in &b.segments {
+            match seg {
+                PlannedSegment::Static(t) => {
+                    for (value, style) in emphasis.runs(t) {
+                        doc.tokens.push(DocToken::Text {
+                            value,
+                            style,
+                            freq_band: None,
+                        });
+                    }
+                }
+                PlannedSegment::Swappable(s) => {
+                    self.span_count += 1;
+                    let span_id = format!("span-{}", self.span_count);
+
+                    let start = emphasis;
+                    let mut style = None;
+                    let mut vars: Vec<Variant> = Vec::with_capacity(s.variants.len());
+                    for (vi, v) in s.variants.iter().enumerate() {
+                        let text;
+                        (text, style) = match vi {
+                            0 => emphasis.strip(&v.text),
+                            _ => {
+                                let mut from_start = start;
+                                (from_start.strip(&v.text).0, style)
+                            }
+                        };
+                        let reg = normalize_register(&v.register);
+                        let id = if vi == 0 {
+                            format!("{}-{}", span_id, reg)
+                        } else {
+                            format!("{}-{}-{}", span_id, reg, vi)
+                        };
+                        let flagged = self.policy.violation(&reg, &text, &v.note);
+                        vars.push(Variant {
+                            id,
+                            register: reg,
+                            text,
+                            note: if v.note.trim().is_empty() { None } else { Some(v.note.clone()) },
+                            difficulty: Some(v.difficulty),
+                            flagged,
+                        });
+                    }
+
+                    let source_text = vars
+                        .first()
+                        .map(|v| v.text.clone())
+                        .unwrap_or_default();
+
+                    doc.spans.insert(
+                        span_id.clone(),
+                        Span {
+                            id: span_id.clone(),
+                            source_text,
+                            variants: vars,
+                            active_variant_index: 0,
+                            provenance: s.provenance.clone(),
+                        },
+                    );
+
+                    doc.tokens.push(DocToken::Span { span_id, style });
+                }
+            }
+        }
+    }
+}
+
+/// First strong direction in the block's text, reading the first variant of
+/// each span.
+fn block_direction(block: &PlannedBlock) -> Option<TextDirection> {
+    block.segments.iter().find_map(|seg| match seg {
+        PlannedSegment::Static(t) => detect_direction(t),
+        PlannedSegment::Swappable(s) => s.variants.first().and_then(|v| detect_direction(&v.text)),
+    })
+}
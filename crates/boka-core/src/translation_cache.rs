@@ -0,0 +1,107 @@
+//! Base translations on disk by [`ContentHash`], so a sentence already
+//! translated, in this story or another, costs no call. An entry is keyed by
+//! the sentence and by everything that shapes its prompt: provider, model,
+//! target language, content policy and prompt overrides.
+//!
+//! Only faithful base translations are cached. Graded-reader output, span
+//! plans and variants depend on more than the sentence and always go to the
+//! model.
+
+use super::content_hash::ContentHash;
+use super::paths;
+use super::types::ApiConfig;
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// One `<hash>.txt` per entry under `dir`.
+#[derive(Debug)]
+pub struct TranslationCache {
+    dir: PathBuf,
+}
+
+impl TranslationCache {
+    /// Nothing is created until the first entry is written.
+    pub fn new(dir: &Path) -> Self {
+        Self { dir: dir.to_path_buf() }
+    }
+
+    /// Where the cache lives in a profile's `cache_dir`.
+    pub fn dir_in(cache_dir: &Path) -> PathBuf {
+        cache_dir.join("translations")
+    }
+
+    fn path(&self, key: ContentHash) -> PathBuf {
+        self.dir.join(format!("{}.txt", key))
+    }
+
+    pub fn get(&self, key: ContentHash) -> Option<String> {
+        fs::read_to_string(self.path(key)).ok().filter(|text| !text.trim().is_empty())
+    }
+
+    /// Written to a temporary file first, so a reader never sees half an
+    /// entry.
+    pub fn put(&self, key: ContentHash, translation: &str) -> io::Result<()> {
+        paths::write_atomic(&self.path(key), translation.as_bytes())
+    }
+
+    /// Entries and their total size in bytes.
+    pub fn stats(&self) -> (u32, u64) {
+        fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "txt"))
+            .fold((0, 0), |(count, bytes), entry| {
+                (count + 1, bytes + entry.metadata().map_or(0, |meta| meta.len()))
+            })
+    }
+
+    pub fn clear(&self) -> io::Result<()> {
+        match fs::remove_dir_all(&self.dir) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// The cache as one job sees it: entries from other providers, models,
+    /// languages or prompts are not served.
+    pub(crate) fn scoped(self: &Arc<Self>, cfg: &ApiConfig, model: &str) -> ScopedCache {
+        let json = |value: serde_json::Result<String>| value.unwrap_or_default();
+        let scope = ContentHash::of(&[
+            &json(serde_json::to_string(&cfg.provider.preset)),
+            model,
+            cfg.provider.base_url.as_deref().unwrap_or_default(),
+            &cfg.target_language,
+            &json(serde_json::to_string(&cfg.content_policy)),
+            &json(serde_json::to_string(&cfg.prompt_overrides)),
+        ]);
+        ScopedCache {
+            cache: self.clone(),
+            scope: scope.hex(),
+        }
+    }
+}
+
+pub(crate) struct ScopedCache {
+    cache: Arc<TranslationCache>,
+    scope: String,
+}
+
+impl ScopedCache {
+    fn key(&self, source: &str) -> ContentHash {
+        ContentHash::of(&[&self.scope, &ContentHash::text(source)])
+    }
+
+    pub(crate) fn get(&self, source: &str) -> Option<String> {
+        self.cache.get(self.key(source))
+    }
+
+    /// A cache that can't be written is skipped; the translation still
+    /// stands.
+    pub(crate) fn put(&self, source: &str, translation: &str) {
+        let _ = self.cache.put(self.key(source), translation);
+    }
+}
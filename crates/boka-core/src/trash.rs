@@ -0,0 +1,236 @@
+//! Soft delete for stories and docs. Trashed entries move out of
+//! `stories.json` into `trash.json`, raw JSON and all, and can be restored
+//! until they have been there for [`RETENTION_DAYS`].
+
+use super::paths;
+use super::stories::now_ms;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const TRASH_FILE: &str = "trash.json";
+
+pub const RETENTION_DAYS: u64 = 30;
+const RETENTION_MS: u64 = RETENTION_DAYS * 24 * 60 * 60 * 1000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TrashError {
+    #[error("Trash I/O error: {0}")]
+    Io(String),
+
+    #[error("Failed to parse trash: {0}")]
+    Parse(String),
+
+    #[error("Nothing to trash or restore for {0}")]
+    NotFound(String),
+
+    #[error("Cannot restore {0}: it exists again")]
+    Exists(String),
+
+    #[error("Cannot restore {0}: its story is gone; restore the story first")]
+    MissingStory(String),
+}
+
+/// What was trashed, without the content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashItem {
+    pub id: String,
+    pub story_id: String,
+    /// Set when a single translation was trashed rather than the story.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    pub title: String,
+    pub trashed_at: u64,
+    /// When the scheduled purge deletes it for good.
+    pub purge_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashEntry {
+    #[serde(flatten)]
+    pub item: TrashItem,
+    /// The story, or the translation under `language`, as it was saved.
+    pub entry: Value,
+}
+
+/// `trash.json`: trashed entries, oldest first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Trash(pub Vec<TrashEntry>);
+
+impl Trash {
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join(TRASH_FILE)
+    }
+
+    pub fn load(data_dir: &Path) -> Result<Self, TrashError> {
+        let path = Self::path(data_dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(&path).map_err(|e| TrashError::Io(e.to_string()))?;
+        serde_json::from_str(&raw).map_err(|e| TrashError::Parse(e.to_string()))
+    }
+
+    /// Atomic write: tmp file, then rename.
+    pub fn save(&self, data_dir: &Path) -> Result<(), TrashError> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| TrashError::Parse(e.to_string()))?;
+        paths::write_atomic(&Self::path(data_dir), json.as_bytes())
+            .map_err(|e| TrashError::Io(e.to_string()))
+    }
+
+    pub fn items(&self) -> Vec<TrashItem> {
+        self.0.iter().map(|e| e.item.clone()).collect()
+    }
+
+    fn push(&mut self, story_id: &str, language: Option<&str>, title: String, entry: Value) -> TrashItem {
+        let trashed_at = now_ms();
+        let mut id = format!("trash-{}", trashed_at);
+        let mut n = 1;
+        while self.0.iter().any(|e| e.item.id == id) {
+            n += 1;
+            id = format!("trash-{}-{}", trashed_at, n);
+        }
+        let item = TrashItem {
+            id,
+            story_id: story_id.to_string(),
+            language: language.map(str::to_string),
+            title,
+            trashed_at,
+            purge_at: trashed_at + RETENTION_MS,
+        };
+        self.0.push(TrashEntry {
+            item: item.clone(),
+            entry,
+        });
+        item
+    }
+
+    /// Move a story, or with `language` one of its translations, from
+    /// `stories` into the trash. Only the two values are modified; the caller
+    /// saves both.
+    pub fn trash(
+        &mut self,
+        stories: &mut Value,
+        story_id: &str,
+        language: Option<&str>,
+    ) -> Result<TrashItem, TrashError> {
+        let target = match language {
+            Some(language) => format!("{}:{}", story_id, language),
+            None => story_id.to_string(),
+        };
+        let list = stories
+            .as_array_mut()
+            .ok_or_else(|| TrashError::Parse("stories.json is not an array".to_string()))?;
+        let index = list
+            .iter()
+            .position(|s| id_of(s) == Some(story_id))
+            .ok_or_else(|| TrashError::NotFound(target.clone()))?;
+        let title = title_of(&list[index]);
+
+        let entry = match language {
+            Some(language) => list[index]
+                .get_mut("translations")
+                .and_then(Value::as_object_mut)
+                .and_then(|t| t.remove(language))
+                .ok_or(TrashError::NotFound(target))?,
+            None => list.remove(index),
+        };
+        Ok(self.push(story_id, language, title, entry))
+    }
+
+    /// Put a trashed entry back into `stories`. A story is appended; a
+    /// translation needs its story to be there and the language to be free.
+    pub fn restore(&mut self, stories: &mut Value, id: &str) -> Result<TrashItem, TrashError> {
+        let index = self
+            .0
+            .iter()
+            .position(|e| e.item.id == id)
+            .ok_or_else(|| TrashError::NotFound(id.to_string()))?;
+        let item = &self.0[index].item;
+        let list = stories
+            .as_array_mut()
+            .ok_or_else(|| TrashError::Parse("stories.json is not an array".to_string()))?;
+        let story = list.iter_mut().find(|s| id_of(s) == Some(item.story_id.as_str()));
+
+        match (&item.language, story) {
+            (None, Some(_)) => return Err(TrashError::Exists(item.story_id.clone())),
+            (None, None) => list.push(self.0[index].entry.clone()),
+            (Some(language), None) => return Err(TrashError::MissingStory(format!("{}:{}", item.story_id, language))),
+            (Some(language), Some(story)) => {
+                if !story.get("translations").is_some_and(Value::is_object) {
+                    story["translations"] = Value::Object(Default::default());
+                }
+                let translations = story["translations"].as_object_mut().expect("translations is an object");
+                if translations.contains_key(language) {
+                    return Err(TrashError::Exists(format!("{}:{}", item.story_id, language)));
+                }
+                translations.insert(language.clone(), self.0[index].entry.clone());
+            }
+        }
+        Ok(self.0.remove(index).item)
+    }
+
+    /// Delete one entry for good, ahead of the scheduled purge.
+    pub fn purge(&mut self, id: &str) -> Result<TrashItem, TrashError> {
+        let index = self
+            .0
+            .iter()
+            .position(|e| e.item.id == id)
+            .ok_or_else(|| TrashError::NotFound(id.to_string()))?;
+        Ok(self.0.remove(index).item)
+    }
+
+    /// Delete the entries due for purging at `now` (ms), returning them.
+    pub fn purge_expired_at(&mut self, now: u64) -> Vec<TrashItem> {
+        let (expired, kept) = std::mem::take(&mut self.0)
+            .into_iter()
+            .partition(|e: &TrashEntry| e.item.purge_at <= now);
+        self.0 = kept;
+        expired.into_iter().map(|e| e.item).collect()
+    }
+
+    pub fn purge_expired(&mut self) -> Vec<TrashItem> {
+        self.purge_expired_at(now_ms())
+    }
+
+    /// Trash what a frontend write dropped: stories in `saved` missing from
+    /// `stories`, and translations missing from a story that is still there.
+    /// Writes replace the whole file, so without this a stale or buggy write
+    /// would lose them for good.
+    pub fn catch_removed(&mut self, stories: &Value, saved: &Value) -> Vec<TrashItem> {
+        let (Some(list), Some(saved)) = (stories.as_array(), saved.as_array()) else {
+            return Vec::new();
+        };
+        let mut trashed = Vec::new();
+        for old in saved {
+            let Some(story_id) = id_of(old) else {
+                continue;
+            };
+            let Some(story) = list.iter().find(|s| id_of(s) == Some(story_id)) else {
+                trashed.push(self.push(story_id, None, title_of(old), old.clone()));
+                continue;
+            };
+            let translations = story.get("translations").and_then(Value::as_object);
+            let old_translations = old.get("translations").and_then(Value::as_object);
+            for (language, translation) in old_translations.into_iter().flatten() {
+                if !translations.is_some_and(|t| t.contains_key(language)) {
+                    trashed.push(self.push(story_id, Some(language), title_of(old), translation.clone()));
+                }
+            }
+        }
+        trashed
+    }
+}
+
+fn id_of(story: &Value) -> Option<&str> {
+    story.get("id").and_then(Value::as_str)
+}
+
+fn title_of(story: &Value) -> String {
+    story.get("title").and_then(Value::as_str).unwrap_or_default().to_string()
+}
@@ -0,0 +1,162 @@
+//! Registry of text-to-speech models the app can use.
+//!
+//! Ships as `data/tts_models.json`; a `tts_models.json` in the data dir
+//! replaces it, so new ONNX voices can be offered without a release. Models
+//! with files are installed under `<model_dir>/<id>/`; the default Kokoro
+//! model has none and is fetched from the HuggingFace hub instead. Which
+//! model speaks a language is a user setting (`Settings::tts_models`).
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, thiserror::Error)]
+pub enum TtsModelError {
+    #[error("Failed to parse TTS model registry: {0}")]
+    Parse(String),
+
+    #[error("Unknown TTS model: {0}")]
+    Unknown(String),
+
+    #[error("TTS model {model} does not support language {language}")]
+    Unsupported { model: String, language: String },
+}
+
+/// Which engine implementation runs a model's files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TtsEngineKind {
+    Kokoro,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TtsQuality {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TtsModelEntry {
+    pub id: String,
+    pub name: String,
+    pub engine: TtsEngineKind,
+    pub languages: Vec<String>,
+    /// Download size, for the settings screen.
+    pub size_bytes: u64,
+    pub quality: TtsQuality,
+    #[serde(default)]
+    pub default: bool,
+    /// ONNX model file name under `<model_dir>/<id>/`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_file: Option<String>,
+    /// Voice data file name under `<model_dir>/<id>/`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voices_file: Option<String>,
+    /// Version of the model and voice pack. Bumping it starts a new audio
+    /// cache namespace, so audio from the old version is not served.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
+}
+
+impl TtsModelEntry {
+    /// Exact match first, then by base language (`en-gb` → `en`).
+    pub fn supports(&self, language: &str) -> bool {
+        let language = language.to_ascii_lowercase();
+        let base = language.split(['-', '_']).next().unwrap_or_default();
+        self.languages.iter().any(|l| *l == language || *l == base)
+    }
+
+    /// Model and voices paths, for models that ship their own files.
+    pub fn files(&self, model_dir: &Path) -> Option<(PathBuf, PathBuf)> {
+        let dir = model_dir.join(&self.id);
+        Some((dir.join(self.model_file.as_ref()?), dir.join(self.voices_file.as_ref()?)))
+    }
+
+    /// Audio cache namespace: the id, with the revision when there is one.
+    pub fn version_key(&self) -> String {
+        match &self.revision {
+            Some(revision) => format!("{}@{}", self.id, revision),
+            None => self.id.clone(),
+        }
+    }
+
+    /// Hub-downloaded models count as installed; they fetch on first load.
+    pub fn installed(&self, model_dir: &Path) -> bool {
+        match self.files(model_dir) {
+            Some((model, voices)) => model.is_file() && voices.is_file(),
+            None => self.model_file.is_none() && self.voices_file.is_none(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TtsModelRegistry {
+    #[serde(default)]
+    pub version: u32,
+    pub models: Vec<TtsModelEntry>,
+}
+
+const BUNDLED_TTS_MODELS: &str = include_str!("../data/tts_models.json");
+
+impl TtsModelRegistry {
+    pub fn from_json(json: &str) -> Result<Self, TtsModelError> {
+        let registry: Self = serde_json::from_str(json).map_err(|e| TtsModelError::Parse(e.to_string()))?;
+        if registry.models.is_empty() {
+            return Err(TtsModelError::Parse("no models listed".to_string()));
+        }
+        Ok(registry)
+    }
+
+    pub fn bundled() -> Self {
+        Self::from_json(BUNDLED_TTS_MODELS).expect("bundled tts_models.json is valid")
+    }
+
+    /// Load `path` if it exists, otherwise the bundled registry.
+    pub fn load_or_bundled(path: &Path) -> Result<Self, TtsModelError> {
+        if !path.exists() {
+            return Ok(Self::bundled());
+        }
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| TtsModelError::Parse(format!("{}: {}", path.display(), e)))?;
+        Self::from_json(&raw)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&TtsModelEntry> {
+        self.models.iter().find(|m| m.id == id)
+    }
+
+    /// The entry marked `default`, else the first.
+    pub fn default_model(&self) -> &TtsModelEntry {
+        self.models.iter().find(|m| m.default).unwrap_or(&self.models[0])
+    }
+
+    /// The model to speak `language` with: the user's choice when it is known
+    /// and supports the language, else the default if it does, else the best
+    /// quality model that does, else the default anyway.
+    pub fn resolve(&self, language: &str, chosen: &BTreeMap<String, String>) -> &TtsModelEntry {
+        let default = self.default_model();
+        chosen
+            .get(language)
+            .and_then(|id| self.get(id))
+            .filter(|m| m.supports(language))
+            .or_else(|| default.supports(language).then_some(default))
+            .or_else(|| self.models.iter().filter(|m| m.supports(language)).max_by_key(|m| m.quality))
+            .unwrap_or(default)
+    }
+
+    /// Check that `id` may be chosen for `language`.
+    pub fn validate(&self, language: &str, id: &str) -> Result<&TtsModelEntry, TtsModelError> {
+        let model = self.get(id).ok_or_else(|| TtsModelError::Unknown(id.to_string()))?;
+        if !model.supports(language) {
+            return Err(TtsModelError::Unsupported {
+                model: id.to_string(),
+                language: language.to_string(),
+            });
+        }
+        Ok(model)
+    }
+}
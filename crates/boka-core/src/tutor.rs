@@ -0,0 +1,167 @@
+//! Tutor mode: the learner asks about a block of a doc in their own words
+//! and the model answers briefly, in the learner's language, from the
+//! block's source and translation. Exchanges are kept on the translation
+//! entry as `tutor`, oldest first, so the reader can show them again and a
+//! follow-up question is asked with the ones before it. Under a moderated
+//! [`ContentPolicy`] (child-safe mode) the tutor is told the policy and an
+//! answer that fails [`moderation::screen`] is refused.
+
+use super::moderation;
+use super::policy::ContentPolicy;
+use super::stories::{now_ms, DocId, StoryDoc, StoryError};
+use super::translation::{fill_anthropic_key, Client};
+use super::types::{ApiConfig, ApiError, LlmProviderConfig};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Longest question sent, in characters.
+pub const MAX_QUESTION_CHARS: usize = 1000;
+
+/// Exchanges kept per doc; the oldest go first.
+pub const MAX_HISTORY: usize = 200;
+
+/// Earlier exchanges about the same block sent with a question.
+const FOLLOW_UPS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TutorExchange {
+    /// Block (paragraph) index, as in `InteractiveDoc::block_texts`.
+    pub block: u32,
+    pub question: String,
+    pub answer: String,
+    pub model: String,
+    pub asked_at: u64,
+}
+
+/// What the tutor is shown of a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockContext {
+    pub block: u32,
+    pub language: String,
+    pub source_language: Option<String>,
+    /// Empty when the doc's blocks no longer line up with its job's
+    /// segments.
+    pub source: String,
+    pub translation: String,
+}
+
+/// The source and translation of block `block` of `story`.
+pub fn block_context(story: &StoryDoc, block: u32) -> Result<BlockContext, StoryError> {
+    let blocks = story.doc.block_texts();
+    let translation = blocks.get(block as usize).ok_or_else(|| StoryError::BlockOutOfRange {
+        doc: format!("{}:{}", story.story_id, story.language),
+        block,
+        blocks: blocks.len(),
+    })?;
+    let source = story
+        .job
+        .as_ref()
+        .filter(|job| job.segments.len() == blocks.len())
+        .and_then(|job| job.segments.get(block as usize))
+        .map(|segment| segment.source.trim().to_string())
+        .unwrap_or_default();
+    Ok(BlockContext {
+        block,
+        language: story.language.clone(),
+        source_language: Some(story.source_language.clone()).filter(|l| !l.is_empty()),
+        source,
+        translation: translation.trim().to_string(),
+    })
+}
+
+/// Answer `question` about `context`, in `ui_language` (English when unset).
+/// The last exchanges about the same block in `history` go along, so "and
+/// the second word?" makes sense. `policy` goes into the tutor's prompt.
+pub async fn ask(
+    context: &BlockContext,
+    question: &str,
+    history: &[TutorExchange],
+    ui_language: Option<&str>,
+    provider: LlmProviderConfig,
+    policy: Option<&ContentPolicy>,
+) -> Result<TutorExchange, ApiError> {
+    let question = question.trim();
+    let chars = question.chars().count();
+    if chars > MAX_QUESTION_CHARS {
+        return Err(ApiError::InputTooLarge {
+            chars,
+            limit: MAX_QUESTION_CHARS,
+        });
+    }
+    let earlier: Vec<&TutorExchange> = history.iter().filter(|e| e.block == context.block).collect();
+    let earlier: Vec<(&str, &str)> = earlier[earlier.len().saturating_sub(FOLLOW_UPS)..]
+        .iter()
+        .map(|e| (e.question.as_str(), e.answer.as_str()))
+        .collect();
+
+    let mut cfg = ApiConfig::from_env(&context.language, context.source_language.as_deref(), false, false);
+    cfg.provider = provider;
+    fill_anthropic_key(&mut cfg);
+    let client = Client::new(cfg)?;
+    let (answer, _) = client
+        .answer_question(&context.source, &context.translation, &earlier, question, ui_language, policy)
+        .await?;
+    let answer = answer.trim().to_string();
+    if answer.is_empty() {
+        return Err(ApiError::Parse("The tutor gave no answer".to_string()));
+    }
+    if let Some(word) = policy.filter(|p| p.moderation).and_then(|_| moderation::screen(&answer)) {
+        return Err(ApiError::Refused {
+            provider: format!("{:?}", client.preset()).to_lowercase(),
+            model: client.model().to_string(),
+            reason: format!("moderation: `{}`", word),
+        });
+    }
+    Ok(TutorExchange {
+        block: context.block,
+        question: question.to_string(),
+        answer,
+        model: client.model().to_string(),
+        asked_at: now_ms(),
+    })
+}
+
+/// The doc's exchanges, oldest first, or only those about `block`.
+pub fn history(stories: &Value, doc_id: &DocId, block: Option<u32>) -> Result<Vec<TutorExchange>, StoryError> {
+    let translation = stories
+        .as_array()
+        .ok_or_else(|| StoryError::Parse("stories.json is not an array".to_string()))?
+        .iter()
+        .find(|s| s.get("id").and_then(Value::as_str) == Some(doc_id.story_id.as_str()))
+        .and_then(|s| s.get("translations")?.get(&doc_id.language))
+        .ok_or_else(|| StoryError::NotFound(doc_id.to_string()))?;
+    Ok(exchanges_of(translation)
+        .into_iter()
+        .filter(|e| block.map_or(true, |b| e.block == b))
+        .collect())
+}
+
+/// Add `exchange` to the doc's history, dropping the oldest past
+/// [`MAX_HISTORY`]. Only `stories` is modified; the caller saves it.
+pub fn record(stories: &mut Value, doc_id: &DocId, exchange: TutorExchange) -> Result<(), StoryError> {
+    let translation = stories
+        .as_array_mut()
+        .ok_or_else(|| StoryError::Parse("stories.json is not an array".to_string()))?
+        .iter_mut()
+        .find(|s| s.get("id").and_then(Value::as_str) == Some(doc_id.story_id.as_str()))
+        .and_then(|s| s.get_mut("translations"))
+        .and_then(|t| t.get_mut(&doc_id.language))
+        .filter(|t| t.is_object())
+        .ok_or_else(|| StoryError::NotFound(doc_id.to_string()))?;
+    let mut exchanges = exchanges_of(translation);
+    exchanges.push(exchange);
+    let excess = exchanges.len().saturating_sub(MAX_HISTORY);
+    exchanges.drain(..excess);
+    translation["tutor"] = serde_json::to_value(&exchanges).map_err(|e| StoryError::Parse(e.to_string()))?;
+    Ok(())
+}
+
+fn exchanges_of(translation: &Value) -> Vec<TutorExchange> {
+    translation
+        .get("tutor")
+        .and_then(Value::as_array)
+        .map(|items| items.iter().filter_map(|e| serde_json::from_value(e.clone()).ok()).collect())
+        .unwrap_or_default()
+}
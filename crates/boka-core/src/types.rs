@@ -0,0 +1,426 @@
+use super::cassette::Cassette;
+use super::gui_types::Granularity;
+use super::length_guard::LengthGuard;
+use super::policy::ContentPolicy;
+use super::prompts::PromptOverrides;
+use super::simplify::CefrLevel;
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::{Arc, OnceLock, RwLock};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LlmProviderPreset {
+    Anthropic,
+    Openai,
+    Openrouter,
+    Ollama,
+    Lmstudio,
+    Custom,
+    /// Offline provider replaying canned responses (tests, demos). Built
+    /// with the `mock` feature only.
+    #[cfg(any(test, feature = "mock"))]
+    Mock,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LlmProviderConfig {
+    pub preset: LlmProviderPreset,
+    #[serde(default)]
+    pub api_key: Option<String>,
+    #[serde(default)]
+    pub base_url: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    /// The model writes `<think>` reasoning before its answer; strip it (see [`crate::reasoning`]).
+    #[serde(default)]
+    pub reasoning_model: bool,
+}
+
+impl Default for LlmProviderConfig {
+    fn default() -> Self {
+        Self {
+            preset: LlmProviderPreset::Anthropic,
+            api_key: None,
+            base_url: None,
+            model: None,
+            reasoning_model: false,
+        }
+    }
+}
+
+/// Sampling parameters sent with every completion; `None` leaves the provider default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SamplingParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+/// Seed used by reproducible jobs on providers that accept one.
+pub const REPRODUCIBLE_SEED: u64 = 1729;
+
+impl SamplingParams {
+    /// Greedy decoding with a fixed seed, limited to what `preset` accepts.
+    /// Anthropic has no seed and rejects `temperature` together with `top_p`.
+    pub fn reproducible(preset: LlmProviderPreset) -> Self {
+        match preset {
+            LlmProviderPreset::Anthropic => Self {
+                temperature: Some(0.0),
+                ..Self::default()
+            },
+            #[cfg(any(test, feature = "mock"))]
+            LlmProviderPreset::Mock => Self::default(),
+            _ => Self {
+                temperature: Some(0.0),
+                top_p: Some(1.0),
+                seed: Some(REPRODUCIBLE_SEED),
+            },
+        }
+    }
+}
+
+/// What a model can do, used to pick token limits and output strategy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelCapabilities {
+    /// Supports OpenAI-style `response_format: json_object`.
+    pub json_mode: bool,
+    pub context_window: u32,
+    pub vision: bool,
+    pub max_output: u32,
+}
+
+impl ModelCapabilities {
+    /// Clamp a desired completion size to what the model can emit.
+    pub fn max_tokens(&self, wanted: u32) -> u32 {
+        wanted.min(self.max_output).max(1)
+    }
+
+    /// Tokens we allow the full-story context to take in a base translation call.
+    pub fn story_context_budget(&self) -> u32 {
+        self.context_window / 2
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelEntry {
+    pub preset: LlmProviderPreset,
+    /// Exact model id, or a prefix (e.g. `claude-`) matching a model family.
+    pub model: String,
+    #[serde(default)]
+    pub default: bool,
+    pub capabilities: ModelCapabilities,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<ModelPricing>,
+}
+
+/// List prices in USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPricing {
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+}
+
+impl ModelPricing {
+    pub fn cost_usd(&self, usage: &Usage) -> f64 {
+        (usage.input_tokens as f64 * self.input_per_mtok + usage.output_tokens as f64 * self.output_per_mtok)
+            / 1_000_000.0
+    }
+}
+
+/// Known models per provider preset with their capabilities.
+///
+/// Ships as `data/models.json`; a `models.json` in the data dir replaces it
+/// at runtime via [`ModelRegistry::install`] so stale defaults can be fixed
+/// without a release.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelRegistry {
+    #[serde(default)]
+    pub version: u32,
+    pub fallback: ModelCapabilities,
+    pub models: Vec<ModelEntry>,
+}
+
+const BUNDLED_MODELS: &str = include_str!("../data/models.json");
+
+fn registry_slot() -> &'static RwLock<Arc<ModelRegistry>> {
+    static SLOT: OnceLock<RwLock<Arc<ModelRegistry>>> = OnceLock::new();
+    SLOT.get_or_init(|| RwLock::new(Arc::new(ModelRegistry::bundled())))
+}
+
+impl ModelRegistry {
+    pub fn from_json(json: &str) -> Result<Self, ApiError> {
+        serde_json::from_str(json).map_err(|e| ApiError::Parse(format!("Model registry: {}", e)))
+    }
+
+    pub fn bundled() -> Self {
+        Self::from_json(BUNDLED_MODELS).expect("bundled models.json is valid")
+    }
+
+    /// Load `path` if it exists, otherwise the bundled registry.
+    pub fn load_or_bundled(path: &Path) -> Result<Self, ApiError> {
+        if !path.exists() {
+            return Ok(Self::bundled());
+        }
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| ApiError::Parse(format!("Model registry {}: {}", path.display(), e)))?;
+        Self::from_json(&raw)
+    }
+
+    /// The registry clients consult right now.
+    pub fn current() -> Arc<ModelRegistry> {
+        registry_slot().read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Replace the active registry; affects clients created afterwards.
+    pub fn install(registry: ModelRegistry) {
+        *registry_slot().write().unwrap_or_else(|e| e.into_inner()) = Arc::new(registry);
+    }
+
+    pub fn default_model(&self, preset: LlmProviderPreset) -> Option<&str> {
+        self.models
+            .iter()
+            .find(|m| m.preset == preset && m.default)
+            .map(|m| m.model.as_str())
+    }
+
+    pub fn models_for(&self, preset: LlmProviderPreset) -> impl Iterator<Item = &ModelEntry> {
+        self.models.iter().filter(move |m| m.preset == preset)
+    }
+
+    /// Exact match for the preset, else the longest prefix match for the
+    /// preset, else the same search across all presets.
+    pub fn lookup(&self, preset: LlmProviderPreset, model: &str) -> Option<&ModelEntry> {
+        let best = |same_preset: bool| {
+            self.models
+                .iter()
+                .filter(|m| !same_preset || m.preset == preset)
+                .filter(|m| model.starts_with(m.model.as_str()))
+                .max_by_key(|m| (m.model == model, m.model.len()))
+        };
+
+        best(true).or_else(|| best(false))
+    }
+
+    pub fn capabilities(&self, preset: LlmProviderPreset, model: &str) -> ModelCapabilities {
+        self.lookup(preset, model)
+            .map(|m| m.capabilities)
+            .unwrap_or(self.fallback)
+    }
+
+    /// `None` for unknown models; local presets are priced at zero in the data.
+    pub fn pricing(&self, preset: LlmProviderPreset, model: &str) -> Option<ModelPricing> {
+        self.lookup(preset, model).and_then(|m| m.pricing)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ApiConfig {
+    pub provider: LlmProviderConfig,
+    pub adult_mode: bool,
+    /// Registers/themes/profanity the output must respect. Defaults from `adult_mode`.
+    pub content_policy: ContentPolicy,
+    pub target_language: String,
+    pub source_language: Option<String>,
+    pub dense_spans: bool,
+    /// Record/replay store for provider traffic, if enabled.
+    pub cassette: Option<Arc<Cassette>>,
+    /// Appends every exchange of a job, redacted, in the cassette format.
+    pub transcript: Option<Arc<Cassette>>,
+    pub sampling: SamplingParams,
+    pub prompt_overrides: PromptOverrides,
+    /// Produce graded-reader output at this level instead of a faithful translation.
+    pub simplify_level: Option<CefrLevel>,
+    /// Send each generated variant list back for a critique-and-fix pass.
+    pub refine_variants: bool,
+    /// Size of the segments the prompts are sent.
+    pub granularity: Granularity,
+    /// Language learner notes are written in; English when unset.
+    pub ui_language: Option<String>,
+    /// Checks base translations for padding; see [`crate::length_guard`].
+    pub length_guard: LengthGuard,
+}
+
+impl ApiConfig {
+    pub fn from_env(target_language: &str, source_language: Option<&str>, adult_mode: bool, dense_spans: bool) -> Self {
+        let provider = LlmProviderConfig {
+            api_key: std::env::var("ANTHROPIC_API_KEY").ok(),
+            ..Default::default()
+        };
+        Self {
+            provider,
+            adult_mode,
+            content_policy: ContentPolicy::for_adult_mode(adult_mode),
+            target_language: target_language.to_string(),
+            source_language: source_language.map(|s| s.to_string()),
+            dense_spans,
+            cassette: Cassette::from_env().map(Arc::new),
+            transcript: None,
+            sampling: SamplingParams::default(),
+            prompt_overrides: PromptOverrides::default(),
+            simplify_level: None,
+            refine_variants: false,
+            granularity: Granularity::default(),
+            ui_language: None,
+            length_guard: LengthGuard::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Usage {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    /// Calls the provider cut off at the output token limit (Anthropic's
+    /// `stop_reason: "max_tokens"`, OpenAI's `finish_reason: "length"`);
+    /// their output is short or truncated.
+    #[serde(default)]
+    pub max_token_stops: u32,
+}
+
+impl std::ops::AddAssign for Usage {
+    fn add_assign(&mut self, other: Self) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.max_token_stops += other.max_token_stops;
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    #[error("No API key set for provider: {provider}")]
+    NoApiKey { provider: String },
+
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("API returned error: {status} — {message}")]
+    ApiResponse { status: u16, message: String },
+
+    #[error("Failed to parse response: {0}")]
+    Parse(String),
+
+    #[error("Input is too large: {chars} characters (limit {limit})")]
+    InputTooLarge { chars: usize, limit: usize },
+
+    #[error("Large input ({segments} segments) must be confirmed before translating")]
+    ConfirmationRequired { segments: usize },
+
+    #[error("Job stopped at its budget after {tokens} tokens")]
+    BudgetExceeded { tokens: u64 },
+
+    /// The model declined the content; see [`refusal`](super::refusal).
+    #[error("{provider} model {model} refused the request: {reason}")]
+    Refused {
+        provider: String,
+        model: String,
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    User,
+    Assistant,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Message {
+    pub role: Role,
+    pub content: MessageContent,
+}
+
+/// Plain text, or a list of parts when a message carries images.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Text(String),
+    Parts(Vec<MessagePart>),
+}
+
+impl From<String> for MessageContent {
+    fn from(text: String) -> Self {
+        MessageContent::Text(text)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum MessagePart {
+    Text { text: String },
+    Image { source: ImageSource },
+}
+
+/// Anthropic's inline image source: always base64 here.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageSource {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub media_type: String,
+    pub data: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MessagesRequest {
+    pub model: String,
+    pub max_tokens: u32,
+    pub system: String,
+    pub messages: Vec<Message>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MessagesResponse {
+    pub content: Vec<ContentBlock>,
+    pub usage: Option<ApiUsage>,
+    /// `end_turn`, `max_tokens`, `stop_sequence`, ...
+    #[serde(default)]
+    pub stop_reason: Option<String>,
+}
+
+impl MessagesResponse {
+    /// Tokens spent, and whether the call hit the token limit.
+    pub fn usage(&self) -> Usage {
+        Usage {
+            max_token_stops: (self.stop_reason.as_deref() == Some("max_tokens")) as u32,
+            ..self.usage.clone().map(Usage::from).unwrap_or_default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContentBlock {
+    #[serde(rename = "type")]
+    pub content_type: String,
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiUsage {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+}
+
+impl From<ApiUsage> for Usage {
+    fn from(u: ApiUsage) -> Self {
+        Self {
+            input_tokens: u.input_tokens,
+            output_tokens: u.output_tokens,
+            max_token_stops: 0,
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Typographic clean-up of model output for the target language: quotation
+//! marks, dashes, ellipses, apostrophes and, in French, the narrow spaces
+//! before high punctuation. Models mix conventions within one story, often
+//! falling back to straight ASCII quotes.
+//!
+//! Applied to base translations and to each planned block before the doc
+//! is built. Languages without rules here are left as they are.
+
+use super::anthropic::{PlannedBlock, PlannedSegment};
+use super::lemma::base_language;
+
+const NARROW_NBSP: char = '\u{202F}';
+const NBSP: char = '\u{A0}';
+
+#[derive(Debug, Clone, Copy)]
+struct Rules {
+    open_quote: &'static str,
+    close_quote: &'static str,
+    /// Replaces a spaced hyphen, a double hyphen or a spaced dash.
+    dash: Option<&'static str>,
+    ellipsis: &'static str,
+    /// `'` between two letters becomes `’`.
+    apostrophe: bool,
+    /// Narrow no-break space before `;`, `!` and `?`, no-break space before `:`.
+    french_spacing: bool,
+}
+
+fn rules(language: &str) -> Option<Rules> {
+    let latin = |open, close, dash| Rules {
+        open_quote: open,
+        close_quote: close,
+        dash: Some(dash),
+        ellipsis: "…",
+        apostrophe: true,
+        french_spacing: false,
+    };
+    let cjk = |open, close| Rules {
+        open_quote: open,
+        close_quote: close,
+        dash: None,
+        ellipsis: "……",
+        apostrophe: false,
+        french_spacing: false,
+    };
+    Some(match base_language(language).to_lowercase().as_str() {
+        "en" => latin("“", "”", "—"),
+        "fr" => Rules {
+            french_spacing: true,
+            ..latin("«\u{202F}", "\u{202F}»", " – ")
+        },
+        "de" => latin("„", "“", " – "),
+        "es" => latin("«", "»", " — "),
+        "it" => latin("«", "»", " – "),
+        "pt" => latin("“", "”", " — "),
+        "ru" => latin("«", "»", " — "),
+        "pl" => latin("„", "”", " – "),
+        "ja" | "jp" => cjk("「", "」"),
+        "zh" | "cn" => cjk("“", "”"),
+        _ => return None,
+    })
+}
+
+/// [`Typographer::apply`] on a text of its own.
+pub fn normalize(text: &str, language: &str) -> String {
+    Typographer::new(language).apply(text)
+}
+
+/// Rewrites consecutive pieces of one text, such as the static parts and
+/// spans of a block, keeping track of open quotes across them.
+#[derive(Debug, Clone)]
+pub struct Typographer {
+    rules: Option<Rules>,
+    in_quote: bool,
+    /// An opening quote ended the last piece; leading space is dropped.
+    after_open: bool,
+}
+
+impl Typographer {
+    pub fn new(language: &str) -> Self {
+        Self {
+            rules: rules(language),
+            in_quote: false,
+            after_open: false,
+        }
+    }
+
+    pub fn apply(&mut self, text: &str) -> String {
+        let Some(rules) = self.rules else {
+            return text.to_string();
+        };
+
+        let mut text = text.replace("...", "…").replace(". . .", "…");
+        if rules.ellipsis != "…" {
+            text = text.replace('…', rules.ellipsis).replace("…………", "……");
+        }
+        if let Some(dash) = rules.dash {
+            for from in [" -- ", "--", " - ", " – ", " — "] {
+                text = text.replace(from, dash);
+            }
+        }
+
+        let chars: Vec<char> = text.chars().collect();
+        let mut out = String::with_capacity(text.len());
+        for (i, &c) in chars.iter().enumerate() {
+            let prev = out.chars().last();
+            let next = chars.get(i + 1).copied();
+            if self.after_open && c.is_whitespace() {
+                continue;
+            }
+            self.after_open = false;
+
+            match c {
+                '"' | '“' | '”' | '„' | '«' | '»' | '「' | '」' => {
+                    let opens = match c {
+                        '„' | '«' | '「' => true,
+                        '»' | '」' => false,
+                        _ => !self.in_quote,
+                    };
+                    if opens {
+                        out.push_str(rules.open_quote);
+                        self.after_open = true;
+                    } else {
+                        if rules.close_quote.starts_with(NARROW_NBSP) {
+                            out.truncate(out.trim_end().len());
+                        }
+                        out.push_str(rules.close_quote);
+                    }
+                    self.in_quote = opens;
+                }
+                '\'' if rules.apostrophe && prev.is_some_and(char::is_alphabetic) => {
+                    out.push(if next.is_some_and(char::is_alphabetic) { '’' } else { c });
+                }
+                ';' | '!' | '?' | ':' if rules.french_spacing => {
+                    let space = if c == ':' { NBSP } else { NARROW_NBSP };
+                    let at_end = next.map_or(true, |n| n.is_whitespace() || "\"”»".contains(n));
+                    match prev {
+                        Some(' ' | NBSP | NARROW_NBSP) => {
+                            out.pop();
+                            out.push(space);
+                        }
+                        Some(p) if at_end && (p.is_alphanumeric() || p == '»') => out.push(space),
+                        _ => {}
+                    }
+                    out.push(c);
+                }
+                _ => out.push(c),
+            }
+        }
+        out
+    }
+}
+
+/// Apply the rules to a planned block: static text and the first variant of
+/// each span read as one text; the other variants start from the quote state
+/// their span starts in.
+pub(crate) fn normalize_block(block: &mut PlannedBlock, language: &str) {
+    let mut typographer = Typographer::new(language);
+    if typographer.rules.is_none() {
+        return;
+    }
+    for segment in &mut block.segments {
+        match segment {
+            PlannedSegment::Static(text) => *text = typographer.apply(text),
+            PlannedSegment::Swappable(span) => {
+                let start = typographer.clone();
+                for (i, variant) in span.variants.iter_mut().enumerate() {
+                    variant.text = match i {
+                        0 => typographer.apply(&variant.text),
+                        _ => start.clone().apply(&variant.text),
+                    };
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Other sentences in the library that use a phrase. [`PhraseIndex`] maps
+//! each lemma of a language's docs to the sentences it occurs in; a phrase
+//! matches a sentence whose lemmas contain the phrase's in a row, so "les
+//! chats noirs" finds "un chat noir".
+//!
+//! [`UsageIndexCache`] keeps one index per language for the session and
+//! rebuilds it when `stories.json` changes, as the doc cache does.
+
+use super::doc_cache::Stamp;
+use super::lemma::{lemma, words};
+use super::speech_prefetch::doc_sentences;
+use super::stories::{self, DocId, StoryError};
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Usages returned per search; a common word is in most sentences.
+pub const MAX_USAGES: usize = 50;
+
+/// A sentence using the phrase, numbered as in
+/// [`doc_sentences`](super::speech_prefetch::doc_sentences).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Usage {
+    pub doc_id: String,
+    pub title: String,
+    pub block: u32,
+    pub sentence: u32,
+    pub text: String,
+}
+
+#[derive(Debug)]
+struct IndexedSentence {
+    usage: Usage,
+    lemmas: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct PhraseIndex {
+    language: String,
+    sentences: Vec<IndexedSentence>,
+    /// Lemma -> sentences it occurs in, ascending, each once.
+    postings: HashMap<String, Vec<u32>>,
+}
+
+impl PhraseIndex {
+    /// Index the sentences of every doc in `language`, with their active
+    /// variants. Docs that no longer parse are left out.
+    pub fn build(stories: &Value, language: &str) -> Self {
+        let mut index = PhraseIndex {
+            language: language.to_string(),
+            ..Default::default()
+        };
+        for story in stories.as_array().into_iter().flatten() {
+            let has_language = story.get("translations").and_then(|t| t.get(language)).is_some();
+            let Some(story_id) = story.get("id").and_then(Value::as_str).filter(|_| has_language) else {
+                continue;
+            };
+            let doc_id = DocId {
+                story_id: story_id.to_string(),
+                language: language.to_string(),
+            };
+            let Ok(doc) = stories::find_doc(stories, &doc_id) else {
+                continue;
+            };
+            for sentence in doc_sentences(&doc.doc) {
+                index.add(Usage {
+                    doc_id: doc_id.to_string(),
+                    title: doc.title.clone(),
+                    block: sentence.block,
+                    sentence: sentence.sentence,
+                    text: sentence.text,
+                });
+            }
+        }
+        index
+    }
+
+    fn add(&mut self, usage: Usage) {
+        let lemmas = self.lemmas(&usage.text);
+        let id = self.sentences.len() as u32;
+        for lemma in &lemmas {
+            let postings = self.postings.entry(lemma.clone()).or_default();
+            if postings.last() != Some(&id) {
+                postings.push(id);
+            }
+        }
+        self.sentences.push(IndexedSentence { usage, lemmas });
+    }
+
+    fn lemmas(&self, text: &str) -> Vec<String> {
+        words(text, &self.language).into_iter().map(|w| lemma(w, &self.language)).collect()
+    }
+
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    pub fn sentence_count(&self) -> usize {
+        self.sentences.len()
+    }
+
+    /// Sentences using `phrase`, in library order, at most `limit`. A phrase
+    /// without words matches nothing.
+    pub fn find(&self, phrase: &str, limit: usize) -> Vec<Usage> {
+        let wanted = self.lemmas(phrase);
+        // Only sentences with the phrase's rarest lemma are looked at.
+        let Some(candidates) = wanted
+            .iter()
+            .map(|lemma| self.postings.get(lemma).map_or(&[][..], Vec::as_slice))
+            .min_by_key(|postings| postings.len())
+        else {
+            return Vec::new();
+        };
+        candidates
+            .iter()
+            .map(|&id| &self.sentences[id as usize])
+            .filter(|s| s.lemmas.windows(wanted.len()).any(|window| window == wanted.as_slice()))
+            .take(limit)
+            .map(|s| s.usage.clone())
+            .collect()
+    }
+}
+
+/// One [`PhraseIndex`] per language, built on first use.
+#[derive(Debug, Default)]
+pub struct UsageIndexCache {
+    stamp: Option<Stamp>,
+    indexes: HashMap<String, Arc<PhraseIndex>>,
+}
+
+impl UsageIndexCache {
+    /// The index of `language` in `data_dir`, rebuilt only when
+    /// `stories.json` changed since it was built.
+    pub fn get(&mut self, data_dir: &Path, language: &str) -> Result<Arc<PhraseIndex>, StoryError> {
+        let stamp = Stamp::of(data_dir);
+        if self.stamp.as_ref() != Some(&stamp) {
+            self.indexes.clear();
+            self.stamp = Some(stamp);
+        }
+        if let Some(index) = self.indexes.get(language) {
+            return Ok(index.clone());
+        }
+        let index = Arc::new(PhraseIndex::build(&stories::load(data_dir)?, language));
+        self.indexes.insert(language.to_string(), index.clone());
+        Ok(index)
+    }
+}
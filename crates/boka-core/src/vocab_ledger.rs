@@ -0,0 +1,127 @@
+//! The words a user has mastered, per language, kept in `vocab_ledger.json`
+//! in the data dir. Words are recorded by lemma (see [`lemma::lemma`]), so
+//! knowing "chat" also covers "chats".
+//!
+//! Exercises consult it: the cloze worksheet does not blank known words, the
+//! quiz and flashcards prefer spans that still have unknown ones.
+
+use super::lemma::{self, base_language};
+use super::paths;
+use super::stories::now_ms;
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const LEDGER_FILE: &str = "vocab_ledger.json";
+
+#[derive(Debug, thiserror::Error)]
+pub enum VocabLedgerError {
+    #[error("Vocabulary ledger I/O error: {0}")]
+    Io(String),
+
+    #[error("Failed to parse vocabulary ledger: {0}")]
+    Parse(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerEntry {
+    /// Surface form the word was marked with.
+    pub word: String,
+    pub mastered_at: u64,
+}
+
+/// Base language code to lemma to entry.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct VocabLedger(pub BTreeMap<String, BTreeMap<String, LedgerEntry>>);
+
+impl VocabLedger {
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join(LEDGER_FILE)
+    }
+
+    pub fn load(data_dir: &Path) -> Result<Self, VocabLedgerError> {
+        let path = Self::path(data_dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(&path).map_err(|e| VocabLedgerError::Io(e.to_string()))?;
+        serde_json::from_str(&raw).map_err(|e| VocabLedgerError::Parse(e.to_string()))
+    }
+
+    /// Atomic write: tmp file, then rename.
+    pub fn save(&self, data_dir: &Path) -> Result<(), VocabLedgerError> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| VocabLedgerError::Parse(e.to_string()))?;
+        paths::write_atomic(&Self::path(data_dir), json.as_bytes())
+            .map_err(|e| VocabLedgerError::Io(e.to_string()))
+    }
+
+    /// Mark every word of `text` as mastered, or no longer mastered.
+    /// Returns how many lemmas changed.
+    pub fn mark(&mut self, language: &str, text: &str, known: bool) -> usize {
+        let key = base_language(language).to_lowercase();
+        let entries = self.0.entry(key.clone()).or_default();
+        let mut changed = 0;
+        for word in lemma::words(text, language) {
+            let lemma = lemma::lemma(word, language);
+            if known && !entries.contains_key(&lemma) {
+                let entry = LedgerEntry {
+                    word: word.to_lowercase(),
+                    mastered_at: now_ms(),
+                };
+                entries.insert(lemma, entry);
+                changed += 1;
+            } else if !known && entries.remove(&lemma).is_some() {
+                changed += 1;
+            }
+        }
+        if entries.is_empty() {
+            self.0.remove(&key);
+        }
+        changed
+    }
+
+    /// The mastered lemmas of `language`, for checking text against.
+    pub fn known_words(&self, language: &str) -> KnownWords {
+        let lemmas = self
+            .0
+            .get(&base_language(language).to_lowercase())
+            .map(|entries| entries.keys().cloned().collect())
+            .unwrap_or_default();
+        KnownWords {
+            language: language.to_string(),
+            lemmas,
+        }
+    }
+}
+
+/// Mastered lemmas of one language. The default knows nothing, which leaves
+/// exercises as they were before the ledger.
+#[derive(Debug, Clone, Default)]
+pub struct KnownWords {
+    language: String,
+    lemmas: HashSet<String>,
+}
+
+impl KnownWords {
+    pub fn is_empty(&self) -> bool {
+        self.lemmas.is_empty()
+    }
+
+    pub fn is_known(&self, word: &str) -> bool {
+        !self.lemmas.is_empty() && self.lemmas.contains(&lemma::lemma(word, &self.language))
+    }
+
+    /// Distinct lemmas in `text` the user has not mastered.
+    pub fn unknown_count(&self, text: &str) -> usize {
+        let unknown: HashSet<String> = lemma::words(text, &self.language)
+            .into_iter()
+            .map(|w| lemma::lemma(w, &self.language))
+            .filter(|l| !self.lemmas.contains(l))
+            .collect();
+        unknown.len()
+    }
+}
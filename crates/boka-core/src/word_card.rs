@@ -0,0 +1,164 @@
+//! Flashcards for single words, mined from the user's own library.
+//!
+//! No dictionary ships with Boka, so a card is built from the spans the word
+//! appears in: the shortest one (a title, a one-word line) glosses the word
+//! when it is no more than a few words long, and the shortest full sentence
+//! is the example, with its source text as translation. Matching is by
+//! lemma (see [`lemma::lemma`]), so "chats" finds "chat".
+//!
+//! Audio is filled in by the caller, which owns the TTS engines and cache.
+
+use super::gui_types::{DocToken, InteractiveDoc};
+use super::lemma::{self, base_language};
+use super::stories::DocId;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Spans up to this many words can gloss a word.
+const GLOSS_MAX_WORDS: usize = 3;
+/// Spans from this many words make an example sentence.
+const EXAMPLE_MIN_WORDS: usize = 4;
+
+#[derive(Debug, thiserror::Error)]
+pub enum WordCardError {
+    #[error("Invalid word: {0}")]
+    Invalid(String),
+
+    #[error("`{word}` does not appear in any {language} story")]
+    NotFound { word: String, language: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CardExample {
+    pub sentence: String,
+    /// Source text of the span the sentence comes from.
+    pub translation: String,
+    pub doc_id: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CardAudio {
+    pub audio_base64: String,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WordCard {
+    pub lemma: String,
+    pub language: String,
+    /// Surface form as used in the example; what is spoken.
+    pub word: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gloss: Option<String>,
+    pub example: CardExample,
+    /// How many spans in the library use the word.
+    pub occurrences: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub word_audio: Option<CardAudio>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub example_audio: Option<CardAudio>,
+}
+
+/// A span using the word.
+struct Hit {
+    text: String,
+    source: String,
+    word: String,
+    words: usize,
+    doc_id: DocId,
+    title: String,
+}
+
+/// Build the card for `word` (any form of it) from every doc in
+/// `language`, or its regional variants, in `stories`.
+pub fn make_word_card(stories: &Value, word: &str, language: &str) -> Result<WordCard, WordCardError> {
+    let lemma = lemma::lemma(word, language);
+    if lemma.is_empty() {
+        return Err(WordCardError::Invalid(word.to_string()));
+    }
+
+    let mut hits: Vec<Hit> = Vec::new();
+    for story in stories.as_array().into_iter().flatten() {
+        let story_id = story.get("id").and_then(Value::as_str).unwrap_or_default();
+        let title = story.get("title").and_then(Value::as_str).unwrap_or_default();
+        let translations = story.get("translations").and_then(Value::as_object);
+        for (doc_language, translation) in translations.into_iter().flatten() {
+            if base_language(doc_language) != base_language(language) {
+                continue;
+            }
+            let Some(doc) = translation
+                .get("doc")
+                .and_then(|d| serde_json::from_value::<InteractiveDoc>(d.clone()).ok())
+            else {
+                continue;
+            };
+            let doc_id = DocId {
+                story_id: story_id.to_string(),
+                language: doc_language.clone(),
+            };
+            collect_hits(&doc, &doc_id, title, &lemma, &mut hits);
+        }
+    }
+    if hits.is_empty() {
+        return Err(WordCardError::NotFound {
+            word: word.to_string(),
+            language: language.to_string(),
+        });
+    }
+
+    // Stable: the first met wins ties.
+    hits.sort_by_key(|h| h.words);
+    let gloss = hits
+        .first()
+        .filter(|h| h.words <= GLOSS_MAX_WORDS && !h.source.is_empty())
+        .map(|h| h.source.clone());
+    let example = hits
+        .iter()
+        .find(|h| h.words >= EXAMPLE_MIN_WORDS)
+        .unwrap_or(&hits[hits.len() - 1]);
+
+    Ok(WordCard {
+        lemma,
+        language: language.to_string(),
+        word: example.word.clone(),
+        gloss,
+        example: CardExample {
+            sentence: example.text.clone(),
+            translation: example.source.clone(),
+            doc_id: example.doc_id.to_string(),
+            title: example.title.clone(),
+        },
+        occurrences: hits.len() as u32,
+        word_audio: None,
+        example_audio: None,
+    })
+}
+
+fn collect_hits(doc: &InteractiveDoc, doc_id: &DocId, title: &str, lemma: &str, hits: &mut Vec<Hit>) {
+    for token in &doc.tokens {
+        let DocToken::Span { span_id, .. } = token else {
+            continue;
+        };
+        let Some(span) = doc.spans.get(span_id) else {
+            continue;
+        };
+        let text = span.active_text().unwrap_or_default().trim();
+        let words = lemma::words(text, &doc_id.language);
+        let Some(word) = words.iter().find(|w| lemma::lemma(w, &doc_id.language) == lemma) else {
+            continue;
+        };
+        hits.push(Hit {
+            text: text.to_string(),
+            source: span.source_text.trim().to_string(),
+            word: word.to_lowercase(),
+            words: words.len(),
+            doc_id: doc_id.clone(),
+            title: title.to_string(),
+        });
+    }
+}
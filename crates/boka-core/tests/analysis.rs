@@ -0,0 +1,59 @@
+//! Text statistics shown before a job starts: counts, reading time and the
+//! rough CEFR estimate.
+
+use boka_core::analysis::analyze_text;
+
+/// One sentence of `word` said `n` times: its length, word length and
+/// variety (a single lemma) are all known.
+fn repeated(word: &str, n: usize) -> String {
+    format!("{}.", vec![word; n].join(" "))
+}
+
+#[test]
+fn words_sentences_and_reading_time_are_counted() {
+    let stats = analyze_text("The cat sleeps. The dog runs!", "en");
+    assert_eq!((stats.word_count, stats.sentence_count, stats.unique_words), (6, 2, 5));
+    assert_eq!(stats.char_count, 29);
+    assert_eq!(stats.avg_sentence_words, 3.0);
+    // 120 words a minute.
+    assert_eq!(stats.reading_time_seconds, 3);
+    assert_eq!(analyze_text(&repeated("cat", 240), "en").reading_time_seconds, 120);
+
+    // Unspaced scripts are read at 250 characters a minute.
+    let stats = analyze_text("猫が寝ている。犬が走る。", "ja");
+    assert_eq!(stats.word_count, 10);
+    assert_eq!(stats.reading_time_seconds, 2);
+
+    let empty = analyze_text("  ", "en");
+    assert_eq!((empty.word_count, empty.sentence_count, empty.reading_time_seconds), (0, 0, 0));
+    assert_eq!((empty.avg_sentence_words, empty.estimated_cefr.as_str()), (0.0, "A1"));
+}
+
+#[test]
+fn cefr_bands_follow_sentence_and_word_length() {
+    // Each pair sits on either side of a cut-off.
+    let cases = [
+        (10, "house", "A1"),
+        (10, "garden", "A2"),
+        (10, "elephant", "A2"),
+        (10, "butterfly", "B1"),
+        (20, "elephant", "B1"),
+        (20, "butterfly", "B2"),
+        (30, "elephant", "B2"),
+        (30, "butterfly", "C1"),
+        (36, "butterfly", "C1"),
+        (36, "playground", "C2"),
+    ];
+    for (n, word, band) in cases {
+        assert_eq!(analyze_text(&repeated(word, n), "en").estimated_cefr, band, "{n} × {word}");
+    }
+
+    let simple = analyze_text("The cat sleeps. The dog runs.", "en");
+    let dense = analyze_text(
+        "Notwithstanding considerable institutional resistance, the administration implemented comprehensive \
+         regulatory reforms addressing longstanding environmental concerns throughout overwhelmingly heterogeneous \
+         metropolitan jurisdictions characterised by extraordinarily complicated infrastructural interdependencies.",
+        "en",
+    );
+    assert_eq!((simple.estimated_cefr.as_str(), dense.estimated_cefr.as_str()), ("A1", "C2"));
+}
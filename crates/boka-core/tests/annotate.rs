@@ -0,0 +1,105 @@
+//! Annotation passes: looked up by id in a registry, run in the plan's
+//! order, each leaving one layer that follows its spans when blocks move.
+
+use boka_core::annotate::{
+    annotate_doc, AnnotatedToken, AnnotationContext, AnnotationError, AnnotationLayer, AnnotationPass, AnnotationPlan,
+    AnnotationRegistry,
+};
+use boka_core::gui_types::InteractiveDoc;
+
+use serde_json::json;
+use std::sync::Arc;
+
+fn doc(span: &str, text: &str) -> InteractiveDoc {
+    serde_json::from_value(json!({
+        "tokens": [{ "type": "span", "spanId": span }],
+        "spans": { span: { "id": span, "sourceText": "A cat sleeps.", "activeVariantIndex": 0, "variants": [
+            { "id": format!("{span}-v1"), "register": "neutral", "text": text }
+        ] } }
+    }))
+    .unwrap()
+}
+
+/// Marks every word with its length; only speaks French.
+struct LengthPass;
+
+impl AnnotationPass for LengthPass {
+    fn id(&self) -> &'static str {
+        "length"
+    }
+
+    fn supports(&self, context: &AnnotationContext) -> bool {
+        context.language == "fr"
+    }
+
+    fn annotate(&self, doc: &InteractiveDoc, _: &AnnotationContext) -> Result<AnnotationLayer, AnnotationError> {
+        Ok(AnnotationLayer::by_variant(doc, |text| {
+            text.split_inclusive(' ')
+                .map(|word| AnnotatedToken {
+                    text: word.to_string(),
+                    value: Some(word.trim().chars().count().to_string()),
+                })
+                .collect()
+        }))
+    }
+}
+
+fn plan(passes: &[&str]) -> AnnotationPlan {
+    let mut registry = AnnotationRegistry::builtin();
+    registry.register(Arc::new(LengthPass));
+    AnnotationPlan {
+        registry: Some(Arc::new(registry)),
+        ..AnnotationPlan::new(passes.iter().map(|p| p.to_string()).collect())
+    }
+}
+
+#[test]
+fn passes_compose_and_unusable_ones_are_skipped() {
+    let mut doc = doc("span-1", "Un chat dort.");
+    let skipped = annotate_doc(&mut doc, &plan(&["length", "frequency", "pinyin", "length"]), "fr", Some("en"));
+    assert_eq!(skipped.len(), 1);
+    assert!(matches!(&skipped[0], AnnotationError::UnknownPass(id) if id == "pinyin"));
+    assert_eq!(doc.annotations.keys().collect::<Vec<_>>(), ["frequency", "length"]);
+
+    let length = &doc.annotations["length"].spans["span-1"][0];
+    let values: Vec<_> = length.iter().map(|t| t.value.as_deref().unwrap()).collect();
+    assert_eq!(values, ["2", "4", "5"]);
+    let frequency = &doc.annotations["frequency"].spans["span-1"][0];
+    assert_eq!(frequency[0], AnnotatedToken { text: "Un".to_string(), value: Some("top-1k".to_string()) });
+    assert_eq!(frequency.iter().map(|t| t.text.as_str()).collect::<String>(), "Un chat dort.");
+
+    let mut other = self::doc("span-1", "Ein Hund.");
+    let skipped = annotate_doc(&mut other, &plan(&["length"]), "xx", None);
+    assert!(matches!(&skipped[0], AnnotationError::Unsupported { pass, .. } if pass == "length"));
+    assert!(other.annotations.is_empty());
+
+    // Layers are saved with the doc, and left out when there are none.
+    let json = serde_json::to_value(&doc).unwrap();
+    assert_eq!(json["annotations"]["length"]["spans"]["span-1"][0][0]["value"], "2");
+    assert!(serde_json::to_value(&other).unwrap().get("annotations").is_none());
+    let registry = AnnotationRegistry::builtin();
+    assert_eq!(registry.ids(), ["frequency"]);
+    let context = AnnotationContext { language: "xx", source_language: None, data_dir: None };
+    assert!(registry.available(&context).is_empty());
+}
+
+#[test]
+fn layers_follow_spans_into_other_docs() {
+    let mut first = doc("span-1", "Un chat dort.");
+    annotate_doc(&mut first, &plan(&["length"]), "fr", None);
+    let mut second = doc("span-1", "Il pleut.");
+    annotate_doc(&mut second, &plan(&["length"]), "fr", None);
+
+    first.append_blocks(second.clone());
+    let layer = &first.annotations["length"];
+    assert_eq!(layer.spans.len(), 2);
+    assert_eq!(layer.spans["span-2"], second.annotations["length"].spans["span-1"]);
+
+    // A replaced block takes its annotations along, and the old ones go.
+    let mut bare = doc("span-1", "Il neige.");
+    first.replace_block(0, bare.clone());
+    assert!(!first.annotations["length"].spans.contains_key("span-1"));
+    annotate_doc(&mut bare, &plan(&["length"]), "fr", None);
+    first.replace_block(1, bare);
+    assert_eq!(first.annotations["length"].spans.keys().collect::<Vec<_>>(), ["span-4"]);
+}
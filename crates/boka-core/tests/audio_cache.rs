@@ -0,0 +1,166 @@
+//! Audio cache integrity (checksum sidecars, repair on read, the
+//! maintenance pass), its per-profile, per-model namespaces, and the
+//! per-doc status probed from it.
+#![cfg(feature = "tts")]
+
+use boka_core::audio::{doc_audio_status, AudioCache, CacheCheck, CacheNamespace};
+use boka_core::gui_types::InteractiveDoc;
+
+use serde_json::json;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("boka-audio-cache-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn files(dir: &Path, extension: &str) -> Vec<PathBuf> {
+    let mut found: Vec<PathBuf> = fs::read_dir(dir)
+        .unwrap()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == extension))
+        .collect();
+    found.sort();
+    found
+}
+
+fn tone() -> Vec<f32> {
+    (0..2400).map(|i| (i as f32 / 10.0).sin() * 0.5).collect()
+}
+
+#[test]
+fn truncated_entries_are_dropped_on_read() {
+    let dir = temp_dir("truncated");
+    let cache = AudioCache::new(&dir, "default").unwrap();
+    let entries = dir.join("default").join("m");
+    cache.put("m", "Bonjour.", "v", 1.0, &tone(), 24000).unwrap();
+    assert_eq!(files(&entries, "sha256").len(), 1);
+    assert_eq!(cache.get("m", "Bonjour.", "v", 1.0).unwrap().duration_ms, 100);
+
+    let wav = files(&entries, "wav").remove(0);
+    let bytes = fs::read(&wav).unwrap();
+    fs::write(&wav, &bytes[..bytes.len() / 2]).unwrap();
+    assert!(cache.get("m", "Bonjour.", "v", 1.0).is_none());
+    assert!(files(&entries, "wav").is_empty() && files(&entries, "sha256").is_empty());
+
+    // The next put writes a fresh, valid entry.
+    cache.put("m", "Bonjour.", "v", 1.0, &tone(), 24000).unwrap();
+    assert!(cache.get("m", "Bonjour.", "v", 1.0).is_some());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn verify_removes_bad_entries_and_adopts_old_ones() {
+    let dir = temp_dir("verify");
+    let cache = AudioCache::new(&dir, "default").unwrap();
+    let entries = dir.join("default").join("m");
+    cache.put("m", "One.", "v", 1.0, &tone(), 24000).unwrap();
+    cache.put("m", "Two.", "v", 1.0, &tone(), 24000).unwrap();
+    cache.put("m", "Three.", "v", 1.0, &tone(), 24000).unwrap();
+
+    let wavs = files(&entries, "wav");
+    let sidecars = files(&entries, "sha256");
+    // Flipped bytes, a cache from before sidecars, and leftovers.
+    let mut bytes = fs::read(&wavs[0]).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xFF;
+    fs::write(&wavs[0], bytes).unwrap();
+    fs::remove_file(&sidecars[1]).unwrap();
+    fs::write(entries.join("gone.sha256"), "00").unwrap();
+    fs::write(entries.join("partial.wav.tmp"), "RIFF").unwrap();
+
+    let check = cache.verify().unwrap();
+    assert_eq!(
+        check,
+        CacheCheck {
+            checked: 3,
+            removed: 1,
+            orphans: 2,
+        }
+    );
+    assert_eq!(files(&entries, "wav").len(), 2);
+    assert_eq!(files(&entries, "sha256").len(), 2);
+    assert_eq!(cache.stats().1, 2);
+    assert_eq!(cache.verify().unwrap().removed, 0);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn profiles_and_model_versions_get_their_own_namespaces() {
+    let dir = temp_dir("namespaces");
+    fs::create_dir_all(&dir).unwrap();
+    // A flat entry from before namespacing can't be attributed; it goes.
+    fs::write(dir.join("0123.wav"), "RIFF").unwrap();
+    let ana = AudioCache::new(&dir, "ana").unwrap();
+    let default = AudioCache::new(&dir, "default").unwrap();
+    assert!(!dir.join("0123.wav").exists());
+
+    ana.put("kokoro@v1.0", "Bonjour.", "v", 1.0, &tone(), 24000).unwrap();
+    ana.put("kokoro@v1.1", "Bonjour.", "v", 1.0, &tone(), 24000).unwrap();
+    default.put("kokoro@v1.0", "Salut.", "v", 1.0, &tone(), 24000).unwrap();
+    assert!(default.get("kokoro@v1.0", "Bonjour.", "v", 1.0).is_none());
+    assert!(ana.get("kokoro@v2.0", "Bonjour.", "v", 1.0).is_none());
+    assert_eq!(ana.stats().1, 2);
+
+    let summary: Vec<(String, String, u32)> = default
+        .namespaces()
+        .into_iter()
+        .map(|CacheNamespace { profile, model, entries, .. }| (profile, model, entries))
+        .collect();
+    let expected = [("ana", "kokoro@v1.0", 1), ("ana", "kokoro@v1.1", 1), ("default", "kokoro@v1.0", 1)];
+    assert_eq!(summary, expected.map(|(p, m, n)| (p.to_string(), m.to_string(), n)));
+
+    ana.clear("ana", Some("kokoro@v1.0")).unwrap();
+    assert!(ana.get("kokoro@v1.0", "Bonjour.", "v", 1.0).is_none());
+    assert!(ana.get("kokoro@v1.1", "Bonjour.", "v", 1.0).is_some());
+    default.clear("ana", None).unwrap();
+    assert_eq!(ana.stats().1, 0);
+    assert_eq!(default.stats().1, 1);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn doc_status_reports_cached_blocks_and_variants() {
+    let dir = temp_dir("doc-status");
+    let cache = AudioCache::new(&dir, "default").unwrap();
+    let doc: InteractiveDoc = serde_json::from_value(json!({
+        "tokens": [
+            { "type": "span", "spanId": "span-1" },
+            { "type": "text", "value": "\n\n" },
+            { "type": "span", "spanId": "span-2" }
+        ],
+        "spans": {
+            "span-1": { "id": "span-1", "sourceText": "Hello.", "activeVariantIndex": 0, "variants": [
+                { "id": "v1", "register": "neutral", "text": "Bonjour." },
+                { "id": "v2", "register": "casual", "text": "Salut." }
+            ] },
+            "span-2": { "id": "span-2", "sourceText": "Bye.", "activeVariantIndex": 0, "variants": [
+                { "id": "v3", "register": "neutral", "text": "Au revoir." }
+            ] }
+        }
+    }))
+    .unwrap();
+    // The first block is its active variant; only the other voice has the second.
+    cache.put("m", "Bonjour.", "v", 1.0, &tone(), 24000).unwrap();
+    cache.put("m", "Salut.", "v", 1.0, &tone()[..1200], 24000).unwrap();
+    cache.put("m", "Au revoir.", "w", 1.0, &tone(), 24000).unwrap();
+
+    let status = doc_audio_status(&cache, "m", "story:fr", &doc, "v", 1.0);
+    assert_eq!((status.cached, status.total), (3, 5));
+    let durations: Vec<(Option<u64>, Vec<Option<u64>>)> = status
+        .blocks
+        .iter()
+        .map(|b| (b.duration_ms, b.variants.iter().map(|v| v.duration_ms).collect()))
+        .collect();
+    assert_eq!(durations, [(Some(100), vec![Some(100), Some(50)]), (None, vec![None])]);
+    assert_eq!(status.blocks[0].variants[1].span_id, "span-1");
+    assert_eq!(status.blocks[0].variants[1].variant_index, 1);
+
+    assert_eq!(doc_audio_status(&cache, "m", "story:fr", &doc, "v", 1.25).cached, 0);
+    assert_eq!(doc_audio_status(&cache, "other", "story:fr", &doc, "v", 1.0).cached, 0);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,119 @@
+//! Sentence chunking, streamed caching and cancellation for long texts.
+#![cfg(feature = "tts")]
+
+use boka_core::audio::{generate_speech, synthesis_chunks, AudioCache, AudioError, TtsEngine, MAX_CHUNK_CHARS};
+use boka_core::audio_types::{AudioModelStatus, VoiceInfo};
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Ten samples per character; can cancel its caller after some calls.
+struct FakeEngine {
+    calls: AtomicUsize,
+    cancel_after: Option<(usize, Arc<AtomicBool>)>,
+}
+
+impl TtsEngine for FakeEngine {
+    fn model_id(&self) -> &str {
+        "fake"
+    }
+
+    fn load_model(&mut self) -> Pin<Box<dyn Future<Output = Result<(), AudioError>> + Send + '_>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn is_loaded(&self) -> bool {
+        true
+    }
+
+    fn generate(&self, text: &str, _voice_id: &str, _speed: f32, _language: &str) -> Result<Vec<f32>, AudioError> {
+        assert!(text.chars().count() <= MAX_CHUNK_CHARS, "chunk too long: {}", text.len());
+        let calls = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+        if let Some((after, cancelled)) = &self.cancel_after {
+            if calls >= *after {
+                cancelled.store(true, Ordering::SeqCst);
+            }
+        }
+        Ok(vec![0.25; text.chars().count() * 10])
+    }
+
+    fn sample_rate(&self) -> u32 {
+        1000
+    }
+
+    fn status(&self) -> AudioModelStatus {
+        AudioModelStatus {
+            downloaded: true,
+            loading: false,
+            ready: true,
+            model_size_bytes: None,
+            error: None,
+        }
+    }
+
+    fn default_voice(&self, _language: &str) -> String {
+        String::new()
+    }
+
+    fn voices(&self) -> Vec<VoiceInfo> {
+        vec![]
+    }
+}
+
+fn long_text() -> String {
+    (0..40).map(|i| format!("Sentence number {i} is here. ")).collect()
+}
+
+#[test]
+fn chunks_pack_sentences_and_split_long_ones() {
+    assert_eq!(
+        synthesis_chunks("One. Two! Three? It was 3.5 km.", 12),
+        ["One. Two!", "Three?", "It was 3.5", "km."]
+    );
+    assert_eq!(synthesis_chunks("猫が寝た。夢を見た。", 5), ["猫が寝た。", "夢を見た。"]);
+    assert_eq!(synthesis_chunks("abcdefgh", 3), ["abc", "def", "gh"]);
+    assert!(synthesis_chunks("  ", 10).is_empty());
+    assert_eq!(synthesis_chunks(&long_text(), 10_000).len(), 1);
+}
+
+#[test]
+fn long_text_streams_into_the_cache() {
+    let dir = std::env::temp_dir().join(format!("boka-audio-chunks-{}", std::process::id()));
+    let cache = AudioCache::new(&dir, "default").unwrap();
+    let engine = FakeEngine {
+        calls: AtomicUsize::new(0),
+        cancel_after: None,
+    };
+    let text = long_text();
+    let cancelled = Arc::new(AtomicBool::new(false));
+
+    let audio = generate_speech(&engine, &cache, &text, "v", 1.0, "en", &cancelled, |_| {}).unwrap();
+    assert!(engine.calls.load(Ordering::SeqCst) > 1);
+    let chars = text.split(". ").map(|s| s.trim().chars().count()).sum::<usize>();
+    assert!(audio.duration_ms as usize >= chars * 10, "{} ms", audio.duration_ms);
+
+    // Served from the cache the second time, with the same length.
+    let again = generate_speech(&engine, &cache, &text, "v", 1.0, "en", &cancelled, |_| {}).unwrap();
+    assert_eq!(again.duration_ms, audio.duration_ms);
+    assert_eq!(cache.stats().1, 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cancelling_stops_between_chunks_and_leaves_no_file() {
+    let dir = std::env::temp_dir().join(format!("boka-audio-cancel-{}", std::process::id()));
+    let cache = AudioCache::new(&dir, "default").unwrap();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let engine = FakeEngine {
+        calls: AtomicUsize::new(0),
+        cancel_after: Some((2, cancelled.clone())),
+    };
+
+    let result = generate_speech(&engine, &cache, &long_text(), "v", 1.0, "en", &cancelled, |_| {});
+    assert!(matches!(result, Err(AudioError::Cancelled)));
+    assert_eq!(engine.calls.load(Ordering::SeqCst), 2);
+    assert_eq!(cache.stats().1, 0);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,38 @@
+//! Pause planning for multi-block audio renders.
+#![cfg(feature = "tts")]
+
+use boka_core::audio::plan_pauses;
+use boka_core::audio_types::PauseOptions;
+
+fn blocks(texts: &[&str]) -> Vec<String> {
+    texts.iter().map(|t| t.to_string()).collect()
+}
+
+#[test]
+fn default_pauses_keep_blocks_whole() {
+    let plan = plan_pauses(&blocks(&["Hello, world. Bye.", "Next one."]), &PauseOptions::default());
+    assert_eq!(
+        plan,
+        [("Hello, world. Bye.".to_string(), 0), ("Next one.".to_string(), 0)]
+    );
+}
+
+#[test]
+fn splits_after_punctuation_with_a_pause() {
+    let pauses = PauseOptions {
+        comma_ms: 300,
+        sentence_ms: 500,
+        paragraph_ms: 900,
+    };
+    let plan = plan_pauses(&blocks(&["He said, \"Stop.\" It was 3.5 km.", "  ", "猫が寝た。夢を見た。"]), &pauses);
+    assert_eq!(
+        plan,
+        [
+            ("He said,".to_string(), 300),
+            ("\"Stop.\"".to_string(), 500),
+            ("It was 3.5 km.".to_string(), 900),
+            ("猫が寝た。".to_string(), 500),
+            ("夢を見た。".to_string(), 0),
+        ]
+    );
+}
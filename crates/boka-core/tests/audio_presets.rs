@@ -0,0 +1,51 @@
+//! Named audio presets in settings.
+
+use boka_core::audio_types::PauseOptions;
+use boka_core::settings::{AudioPreset, Settings};
+
+#[test]
+fn builtin_presets_load_for_old_settings_files() {
+    let settings: Settings = serde_json::from_str(r#"{"ttsWarmup":"on-first-use"}"#).unwrap();
+    let beginner = settings.audio_preset("beginner").unwrap();
+    let native = settings.audio_preset(" native ").unwrap();
+    assert!(beginner.speed < native.speed);
+    assert!(beginner.pauses.sentence_ms > native.pauses.sentence_ms);
+    assert_eq!(native.pauses, PauseOptions::default());
+    assert!(settings.audio_preset("turbo").is_err());
+}
+
+#[test]
+fn presets_are_validated_and_persisted() {
+    let mut settings = Settings::default();
+    let slow = AudioPreset {
+        speed: 0.6,
+        pauses: PauseOptions {
+            comma_ms: 400,
+            sentence_ms: 900,
+            paragraph_ms: 1500,
+        },
+        voice_id: Some("ff_siwis".to_string()),
+    };
+    settings.set_audio_preset("très lent", Some(slow.clone())).unwrap();
+    assert!(settings
+        .set_audio_preset("fast", Some(AudioPreset { speed: 3.0, ..slow.clone() }))
+        .is_err());
+    let long_pause = PauseOptions {
+        paragraph_ms: 60_000,
+        ..slow.pauses
+    };
+    assert!(settings
+        .set_audio_preset("long", Some(AudioPreset { pauses: long_pause, ..slow.clone() }))
+        .is_err());
+    assert!(settings.set_audio_preset("  ", Some(slow.clone())).is_err());
+    settings.set_audio_preset("native", None).unwrap();
+
+    let dir = std::env::temp_dir().join(format!("boka-audio-presets-{}", std::process::id()));
+    settings.save(&dir).unwrap();
+    let loaded = Settings::load(&dir).unwrap();
+    assert_eq!(loaded.audio_preset("très lent").unwrap(), &slow);
+    // A removed built-in stays removed.
+    assert!(loaded.audio_preset("native").is_err());
+    assert_eq!(loaded.view().audio_presets.0.len(), 3);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,96 @@
+//! Access for entry points other than the window: tokens, allowlists and
+//! confirming outside translation jobs.
+
+use boka_core::authz::{await_confirmation, Action, Authz, AuthzError, Confirmations, Decision, Interface};
+
+use std::fs;
+use std::path::PathBuf;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("boka-authz-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn only_the_gui_has_access_by_default() {
+    let authz = Authz::load(&temp_dir("default")).unwrap();
+    assert_eq!(authz.authorize(Interface::Gui, None, Action::StartTranslation).unwrap(), Decision::Allow);
+    assert!(matches!(
+        authz.authorize(Interface::Http, Some("guess"), Action::ReadLibrary),
+        Err(AuthzError::NoGrant(Interface::Http))
+    ));
+}
+
+#[test]
+fn tokens_are_checked_per_interface_and_stored_hashed() {
+    let dir = temp_dir("tokens");
+    let mut authz = Authz::default();
+    let token = authz.issue(Interface::Http, &[Action::ReadLibrary, Action::StartTranslation]).unwrap();
+    authz.save(&dir).unwrap();
+    assert!(!fs::read_to_string(Authz::path(&dir)).unwrap().contains(&token));
+
+    let authz = Authz::load(&dir).unwrap();
+    let http = |token: Option<&str>, action| authz.authorize(Interface::Http, token, action);
+    assert_eq!(http(Some(&token), Action::ReadLibrary).unwrap(), Decision::Allow);
+    // Spending provider tokens waits for the user.
+    assert_eq!(http(Some(&token), Action::StartTranslation).unwrap(), Decision::Confirm);
+    assert!(matches!(http(Some(&token), Action::ModifyLibrary), Err(AuthzError::NotAllowed { .. })));
+    assert!(matches!(http(None, Action::ReadLibrary), Err(AuthzError::BadToken(_))));
+    // A token is only good for the interface it was issued to.
+    assert!(authz.authorize(Interface::Cli, Some(&token), Action::ReadLibrary).is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn reissuing_replaces_the_old_token() {
+    let mut authz = Authz::default();
+    let old = authz.issue(Interface::Cli, &Action::READ_ONLY).unwrap();
+    let new = authz.issue(Interface::Cli, &Action::READ_ONLY).unwrap();
+    assert_ne!(old, new);
+    assert!(new.len() == 64 && new.chars().all(|c| c.is_ascii_hexdigit()));
+    assert_eq!(authz.views().len(), 1);
+    assert!(authz.authorize(Interface::Cli, Some(&old), Action::ReadLibrary).is_err());
+    assert!(authz.authorize(Interface::Cli, Some(&new), Action::ReadLibrary).is_ok());
+
+    assert!(authz.revoke(Interface::Cli));
+    assert!(!authz.revoke(Interface::Cli));
+    assert!(authz.authorize(Interface::Cli, Some(&new), Action::ReadLibrary).is_err());
+}
+
+#[tokio::test]
+async fn outside_jobs_wait_for_an_answer() {
+    let mut confirmations = Confirmations::default();
+    let (first, approved) = confirmations.request(Interface::DeepLink, Action::StartTranslation, "Story → fr");
+    let (second, declined) = confirmations.request(Interface::Http, Action::StartTranslation, "Story → de");
+    let (_, dropped) = confirmations.request(Interface::Http, Action::StartTranslation, "Story → es");
+    let pending: Vec<_> = confirmations.pending().into_iter().map(|r| r.summary).collect();
+    assert_eq!(pending, ["Story → fr", "Story → de", "Story → es"]);
+
+    assert!(confirmations.resolve(&first.id, true));
+    assert!(!confirmations.resolve(&first.id, false));
+    assert!(confirmations.resolve(&second.id, false));
+    assert!(await_confirmation(approved).await.is_ok());
+    assert!(matches!(await_confirmation(declined).await, Err(AuthzError::Declined)));
+
+    drop(confirmations);
+    assert!(await_confirmation(dropped).await.is_err());
+}
+
+#[test]
+fn abandoned_requests_are_dropped() {
+    let mut confirmations = Confirmations::default();
+    let (gone, rx) = confirmations.request(Interface::Http, Action::StartTranslation, "Story → it");
+    let (kept, _waiting) = confirmations.request(Interface::Http, Action::StartTranslation, "Story → nl");
+    drop(rx);
+    let pending: Vec<_> = confirmations.pending().into_iter().map(|r| r.id).collect();
+    assert_eq!(pending, [kept.id.as_str()]);
+    assert!(!confirmations.resolve(&gone.id, true));
+
+    // Given up on after it was listed: answering it reports that nobody heard.
+    let (late, rx) = confirmations.request(Interface::DeepLink, Action::StartTranslation, "Story → pt");
+    assert_eq!(confirmations.pending().len(), 2);
+    drop(rx);
+    assert!(!confirmations.resolve(&late.id, true));
+    assert!(confirmations.resolve(&kept.id, true));
+}
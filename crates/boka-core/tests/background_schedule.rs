@@ -0,0 +1,106 @@
+use boka_core::audio_types::WarmupTrigger;
+use boka_core::background::{
+    parse_nmcli_metered, BackgroundHold, BackgroundMode, BackgroundPolicy, BackgroundScheduler, BackgroundTask,
+};
+use boka_core::settings::Settings;
+
+use std::time::{Duration, Instant};
+
+fn warmup(language: Option<&str>) -> BackgroundTask {
+    BackgroundTask::TtsWarmup {
+        language: language.map(str::to_string),
+        trigger: WarmupTrigger::DocOpen,
+    }
+}
+
+fn later(secs: u64) -> Instant {
+    Instant::now() + Duration::from_secs(secs)
+}
+
+#[test]
+fn idle_policy_waits_for_foreground_work_to_settle() {
+    let mut scheduler = BackgroundScheduler::new(BackgroundPolicy::default());
+    assert!(scheduler.queue(warmup(Some("fr"))));
+    assert!(!scheduler.queue(warmup(Some("fr"))), "duplicates are not queued");
+
+    // Launch counts as activity.
+    assert_eq!(scheduler.hold_at(Instant::now()), Some(BackgroundHold::Busy));
+    assert!(scheduler.take_runnable_at(Instant::now()).is_empty());
+
+    scheduler.begin_foreground();
+    assert_eq!(scheduler.hold_at(later(3_600)), Some(BackgroundHold::Busy));
+    scheduler.end_foreground();
+    assert_eq!(scheduler.hold_at(later(10)), Some(BackgroundHold::Busy));
+
+    let tasks = scheduler.take_runnable_at(later(31));
+    assert_eq!(tasks, [warmup(Some("fr"))]);
+    let status = scheduler.status_at(later(31));
+    assert!(status.pending.is_empty());
+    assert_eq!(status.running, tasks);
+    assert!(!scheduler.queue(warmup(Some("fr"))), "running tasks are not queued again");
+
+    scheduler.finish(&tasks[0]);
+    assert!(scheduler.status().running.is_empty());
+}
+
+#[test]
+fn pause_and_metered_connections_hold_every_mode() {
+    let mut scheduler = BackgroundScheduler::new(BackgroundPolicy {
+        mode: BackgroundMode::Always,
+        ..BackgroundPolicy::default()
+    });
+    scheduler.queue(warmup(None));
+    scheduler.begin_foreground();
+    assert_eq!(scheduler.hold_at(Instant::now()), None, "always ignores foreground work");
+
+    scheduler.set_metered(Some(true));
+    assert_eq!(scheduler.hold_at(Instant::now()), Some(BackgroundHold::Metered));
+    assert!(scheduler.take_runnable_at(Instant::now()).is_empty());
+
+    scheduler.set_policy(BackgroundPolicy {
+        mode: BackgroundMode::Always,
+        avoid_metered: false,
+        ..BackgroundPolicy::default()
+    });
+    assert_eq!(scheduler.hold_at(Instant::now()), None);
+
+    scheduler.set_policy(BackgroundPolicy {
+        mode: BackgroundMode::Paused,
+        ..scheduler.policy()
+    });
+    assert_eq!(scheduler.hold_at(later(3_600)), Some(BackgroundHold::Paused));
+}
+
+#[test]
+fn nmcli_output_is_read_per_device() {
+    let connected = "GENERAL.METERED:no (guessed)\nGENERAL.METERED:unknown\n";
+    assert_eq!(parse_nmcli_metered(connected), Some(false));
+    let tethered = "GENERAL.METERED:no\nGENERAL.METERED:yes\n";
+    assert_eq!(parse_nmcli_metered(tethered), Some(true));
+    assert_eq!(parse_nmcli_metered("GENERAL.METERED:unknown\n"), None);
+    assert_eq!(parse_nmcli_metered(""), None);
+}
+
+#[test]
+fn background_policy_is_stored_in_settings() {
+    let mut settings = Settings::default();
+    assert_eq!(settings.view().background, BackgroundPolicy::default());
+
+    let policy = BackgroundPolicy {
+        mode: BackgroundMode::Paused,
+        idle_secs: 120,
+        avoid_metered: false,
+    };
+    settings.set_background(policy).unwrap();
+    assert_eq!(settings.view().background, policy);
+
+    let too_long = BackgroundPolicy {
+        idle_secs: BackgroundPolicy::MAX_IDLE_SECS + 1,
+        ..policy
+    };
+    assert!(settings.set_background(too_long).is_err());
+
+    // Older settings files have no `background` key.
+    let old: Settings = serde_json::from_str(r#"{ "ttsWarmup": "on-doc-open" }"#).unwrap();
+    assert_eq!(old.background, BackgroundPolicy::default());
+}
@@ -0,0 +1,33 @@
+//! Writing direction from language codes and from text.
+
+use boka_core::bidi::{detect_direction, direction_for_language};
+use boka_core::gui_types::{InteractiveDoc, TextDirection};
+
+#[test]
+fn rtl_languages_and_script_subtags() {
+    for code in ["ar", "ar-EG", "he", "iw", "fa", "ur", "yi", "ckb", "ku-Arab", "pa-Arab"] {
+        assert_eq!(direction_for_language(code), TextDirection::Rtl, "{code}");
+    }
+    for code in ["en", "fr-CA", "ku", "pa", "ja", "", "tr"] {
+        assert_eq!(direction_for_language(code), TextDirection::Ltr, "{code}");
+    }
+}
+
+#[test]
+fn first_strong_character_wins() {
+    assert_eq!(detect_direction("« 2024 » שלום world"), Some(TextDirection::Rtl));
+    assert_eq!(detect_direction("\"OK\", قال"), Some(TextDirection::Ltr));
+    assert_eq!(detect_direction("مرحبا"), Some(TextDirection::Rtl));
+    assert_eq!(detect_direction("123 — ?"), None);
+}
+
+#[test]
+fn docs_saved_without_direction_default_to_ltr() {
+    let doc: InteractiveDoc = serde_json::from_str(r#"{"tokens":[],"spans":{}}"#).unwrap();
+    assert_eq!(doc.direction, TextDirection::Ltr);
+    assert!(doc.block_directions.is_empty());
+
+    let json = serde_json::to_value(InteractiveDoc { direction: TextDirection::Rtl, ..doc }).unwrap();
+    assert_eq!(json["direction"], "rtl");
+    assert_eq!(json["blockDirections"], serde_json::json!([]));
+}
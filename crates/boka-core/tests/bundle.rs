@@ -0,0 +1,110 @@
+//! `.boka` shared-story bundles: export, checks and import.
+
+use boka_core::bundle::{import_bundle, read_bundle, shared_audio, shared_of, write_bundle, BundleError, BundleOptions};
+use boka_core::export::readalong::BlockAudio;
+use boka_core::jsonl::from_jsonl;
+use boka_core::stories::{self, find_doc, put_doc, DocId};
+use boka_core::story_meta::{local_meta, meta_of};
+
+use base64::Engine as _;
+use serde_json::{json, Value};
+
+const DOC: &str = r#"{"type":"story","format":"boka-jsonl","version":1,"storyId":"s1","title":"Cats","sourceLanguage":"en","sourceText":"The cat sleeps. Hello.","language":"fr"}
+{"type":"span","id":"a","sourceText":"The cat sleeps.","activeVariantIndex":0,"variants":[{"id":"a1","register":"neutral","text":"Le chat dort."}]}
+{"type":"text","value":"\n\n"}
+{"type":"span","id":"b","sourceText":"Hello.","activeVariantIndex":0,"variants":[{"id":"b1","register":"neutral","text":"Bonjour."}]}
+"#;
+
+fn library() -> (Value, DocId) {
+    let story = from_jsonl(DOC).unwrap();
+    let mut all = json!([]);
+    let doc_id = put_doc(&mut all, &story).unwrap();
+    stories::set_meta(&mut all, &doc_id, &local_meta(&story)).unwrap();
+    (all, doc_id)
+}
+
+fn options() -> BundleOptions {
+    BundleOptions {
+        author: Some("Ms. Dupont".to_string()),
+        note: Some("Week 3".to_string()),
+        include_audio: true,
+    }
+}
+
+#[test]
+fn bundles_round_trip_with_audio() {
+    let (all, doc_id) = library();
+    let wav = b"RIFF....WAVEfmt ".to_vec();
+    let audio = [
+        Some(BlockAudio { wav_base64: base64::engine::general_purpose::STANDARD.encode(&wav), duration_ms: 1200 }),
+        None,
+    ];
+    let bytes = write_bundle(&all, &doc_id, &audio, &options()).unwrap();
+
+    let bundle = read_bundle(&bytes).unwrap();
+    assert_eq!(bundle.manifest.title, "Cats");
+    assert_eq!(bundle.manifest.author.as_deref(), Some("Ms. Dupont"));
+    assert_eq!(bundle.manifest.audio, [Some(1200), None]);
+    assert_eq!(bundle.audio, [Some(wav.clone()), None]);
+    assert_eq!(bundle.story.doc.block_texts(), ["Le chat dort.", "Bonjour."]);
+
+    let dir = std::env::temp_dir().join(format!("boka-bundle-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let mut theirs = json!([{ "id": "s1", "title": "Their own story", "translations": {} }]);
+    let imported = import_bundle(&mut theirs, &dir, bundle.clone()).unwrap();
+    assert_eq!(imported.story_id, format!("shared-{}", bundle.id));
+    assert_eq!(find_doc(&theirs, &imported).unwrap().title, "Cats");
+    assert_eq!(theirs[0]["title"], "Their own story");
+
+    let translation = &theirs[1]["translations"]["fr"];
+    let shared = shared_of(translation).unwrap();
+    assert_eq!((shared.bundle_id, shared.note.as_deref()), (bundle.id.clone(), Some("Week 3")));
+    assert_eq!(meta_of(translation), bundle.meta);
+    let clips = shared_audio(&theirs, &dir, &imported).unwrap();
+    assert_eq!(clips.len(), 2);
+    let clip = clips[0].as_ref().unwrap();
+    assert_eq!(clip.duration_ms, 1200);
+    assert_eq!(base64::engine::general_purpose::STANDARD.decode(&clip.audio_base64).unwrap(), wav);
+    assert!(clips[1].is_none());
+
+    // Importing again replaces the first copy, and frontend writes keep the mark.
+    let again = import_bundle(&mut theirs, &dir, bundle).unwrap();
+    assert_eq!(again, imported);
+    assert_eq!(theirs.as_array().unwrap().len(), 2);
+    let mut incoming = theirs.clone();
+    incoming[1]["translations"]["fr"].as_object_mut().unwrap().remove("shared");
+    stories::keep_meta(&mut incoming, &theirs);
+    assert!(shared_of(&incoming[1]["translations"]["fr"]).is_some());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn bundles_without_audio_or_meta() {
+    let story = from_jsonl(DOC).unwrap();
+    let mut all = json!([]);
+    let doc_id = put_doc(&mut all, &story).unwrap();
+    let bytes = write_bundle(&all, &doc_id, &[], &BundleOptions::default()).unwrap();
+    let bundle = read_bundle(&bytes).unwrap();
+    assert!(bundle.manifest.audio.is_empty() && bundle.audio.is_empty());
+    assert!(bundle.meta.is_none() && bundle.manifest.author.is_none());
+}
+
+#[test]
+fn foreign_newer_and_damaged_files_are_refused() {
+    let (all, doc_id) = library();
+    let bytes = write_bundle(&all, &doc_id, &[], &options()).unwrap();
+
+    assert!(matches!(read_bundle(b"PK\x03\x04"), Err(BundleError::NotABundle)));
+    assert!(matches!(read_bundle(DOC.as_bytes()), Err(BundleError::NotABundle)));
+
+    let mut newer = bytes.clone();
+    newer[8..12].copy_from_slice(&2u32.to_le_bytes());
+    assert!(matches!(read_bundle(&newer), Err(BundleError::UnsupportedVersion(2))));
+
+    let truncated = &bytes[..bytes.len() - 10];
+    assert!(matches!(read_bundle(truncated), Err(BundleError::Corrupt(_))));
+    let mut flipped = bytes.clone();
+    let last = flipped.len() - 20;
+    flipped[last] ^= 0xff;
+    assert!(matches!(read_bundle(&flipped), Err(BundleError::Corrupt(_))));
+}
@@ -0,0 +1,102 @@
+//! Replays recorded provider traffic from `tests/fixtures/cassettes/` through
+//! the real clients, exercising response parsing without network access.
+
+use boka_core::anthropic::{AnthropicClient, PlannedSegment};
+use boka_core::cassette::{Cassette, CassetteMode};
+use boka_core::openai_compat::OpenAiCompatClient;
+use boka_core::simple_format::StructuredFormat;
+use boka_core::types::{ApiConfig, ApiError, LlmProviderConfig, LlmProviderPreset};
+
+use std::sync::Arc;
+
+fn replay_config(preset: LlmProviderPreset, base_url: Option<&str>, cassette: &str) -> ApiConfig {
+    let path = format!("{}/tests/fixtures/cassettes/{}", env!("CARGO_MANIFEST_DIR"), cassette);
+    ApiConfig {
+        provider: LlmProviderConfig {
+            preset,
+            api_key: None,
+            base_url: base_url.map(str::to_string),
+            model: Some("replay-model".to_string()),
+            reasoning_model: false,
+        },
+        target_language: "fr".to_string(),
+        cassette: Some(Arc::new(Cassette::new(CassetteMode::Replay, path))),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn openai_compat_session_replays_through_parsers() {
+    let cfg = replay_config(LlmProviderPreset::Openai, Some("http://replay.invalid/v1"), "openai_session.jsonl");
+    let client = OpenAiCompatClient::new(cfg).expect("replay needs no api key");
+
+    let (base, usage) = client.translate_base_segment("The cat sleeps.", "The cat sleeps.", None).await.unwrap();
+    assert_eq!(base, "Le chat dort.");
+    assert_eq!((usage.input_tokens, usage.output_tokens), (120, 5));
+
+    let (block, _) = client.plan_block_from_base(&base, StructuredFormat::Json).await.unwrap();
+    assert_eq!(block.segments.len(), 2);
+    assert!(matches!(&block.segments[0], PlannedSegment::Swappable(s) if s.variants[0].text == "Le chat"));
+
+    let (variants, _) = client.generate_span_variants(&base, "Le chat", 3, StructuredFormat::Json).await.unwrap();
+    let texts: Vec<&str> = variants.iter().map(|v| v.text.as_str()).collect();
+    assert_eq!(texts, ["Le chat", "Le matou"]);
+
+    match client.translate_base_segment("x", "x", None).await {
+        Err(ApiError::Parse(msg)) => assert!(msg.contains("no recorded response")),
+        other => panic!("expected exhausted cassette, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn anthropic_session_replays_success_and_error() {
+    let cfg = replay_config(LlmProviderPreset::Anthropic, None, "anthropic_session.jsonl");
+    let client = AnthropicClient::new(cfg).expect("replay needs no api key");
+
+    let (text, usage) = client.translate_base_segment("The dog barks.", "The dog barks.", None).await.unwrap();
+    assert_eq!(text, "Le chien aboie.");
+    assert_eq!(usage.output_tokens, 6);
+
+    match client.translate_base_segment("x", "x", None).await {
+        Err(ApiError::ApiResponse { status, message }) => {
+            assert_eq!(status, 429);
+            assert!(message.contains("rate_limit_error"));
+        }
+        other => panic!("expected recorded API error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn calls_cut_off_at_the_token_limit_are_counted() {
+    let cfg = replay_config(LlmProviderPreset::Anthropic, None, "max_tokens_stops.jsonl");
+    let client = AnthropicClient::new(cfg).expect("replay needs no api key");
+    let (text, usage) = client.translate_base_segment("The dog barks.", "The dog barks.", None).await.unwrap();
+    assert_eq!((text.as_str(), usage.max_token_stops), ("Le chien", 1));
+    let (_, usage) = client.translate_base_segment("The dog barks.", "The dog barks.", None).await.unwrap();
+    assert_eq!(usage.max_token_stops, 0);
+
+    let base_url = Some("http://replay.invalid/v1");
+    let cfg = replay_config(LlmProviderPreset::Openai, base_url, "max_tokens_stops.jsonl");
+    let client = OpenAiCompatClient::new(cfg).expect("replay needs no api key");
+    let (_, mut total) = client.translate_base_segment("The cat sleeps.", "The cat sleeps.", None).await.unwrap();
+    assert_eq!((total.output_tokens, total.max_token_stops), (2, 1));
+    total += usage;
+    assert_eq!((total.output_tokens, total.max_token_stops), (8, 1));
+}
+
+#[test]
+fn recorded_exchanges_redact_the_api_key() {
+    let path = std::env::temp_dir().join(format!("boka-cassette-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let cassette = Cassette::new(CassetteMode::Record, &path);
+
+    let body = serde_json::json!({ "model": "m", "note": "key sk-secret-123 leaked into the prompt" });
+    cassette
+        .record("https://example.invalid/v1?key=sk-secret-123", &body, 200, "{\"echo\":\"sk-secret-123\"}", Some("sk-secret-123"))
+        .unwrap();
+
+    let written = std::fs::read_to_string(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert!(!written.contains("sk-secret-123"));
+    assert_eq!(written.matches("[REDACTED]").count(), 3);
+}
@@ -0,0 +1,50 @@
+use boka_core::settings::{Settings, SettingsError};
+
+#[test]
+fn the_pin_locks_child_safe_mode() {
+    let mut settings = Settings::default();
+    settings.check_pin(None).unwrap();
+    settings.set_child_safe(true, Some(" 1234 ")).unwrap();
+    assert!(settings.view().child_safe_enabled && settings.view().child_safe_pin_set);
+
+    for pin in [None, Some(""), Some("4321")] {
+        assert!(matches!(settings.check_pin(pin), Err(SettingsError::Locked(_))));
+        assert!(matches!(settings.set_child_safe(false, pin), Err(SettingsError::Locked(_))));
+    }
+    settings.check_pin(Some("1234")).unwrap();
+    settings.set_child_safe(false, Some("1234")).unwrap();
+    assert!(!settings.view().child_safe_pin_set);
+    settings.check_pin(None).unwrap();
+}
+
+#[test]
+fn pins_are_stored_salted() {
+    let mut first = Settings::default();
+    first.set_child_safe(true, Some("1234")).unwrap();
+    let mut second = Settings::default();
+    second.set_child_safe(true, Some("1234")).unwrap();
+
+    let hash = first.child_safe.pin_hash.clone().unwrap();
+    assert!(hash.starts_with("$argon2"));
+    assert_ne!(Some(hash), second.child_safe.pin_hash);
+    let saved = serde_json::to_string(&first).unwrap();
+    assert!(saved.contains("pinHash") && !saved.contains("pinSha256"));
+}
+
+#[test]
+fn pins_hashed_before_salting_still_unlock() {
+    // SHA-256 of "1234".
+    let mut settings: Settings = serde_json::from_str(
+        r#"{"childSafe":{"enabled":true,
+            "pinSha256":"03ac674216f3e15c761ee1a5e255f067953623c8b388b4459e13f978d7c846f4"}}"#,
+    )
+    .unwrap();
+    assert!(settings.view().child_safe_pin_set);
+    assert!(settings.check_pin(Some("0000")).is_err());
+    settings.check_pin(Some("1234")).unwrap();
+
+    settings.set_child_safe(true, Some("1234")).unwrap();
+    assert_eq!(settings.child_safe.pin_sha256, None);
+    assert!(settings.child_safe.pin_hash.is_some());
+    settings.check_pin(Some("1234")).unwrap();
+}
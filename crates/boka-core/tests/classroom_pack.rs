@@ -0,0 +1,124 @@
+//! Classroom pack export: cloze, quiz, vocabulary and the zip itself.
+
+use boka_core::export::classroom::{classroom_pack, cloze_worksheet, quiz, ClassroomPackOptions};
+use boka_core::export::vocab::vocabulary;
+use boka_core::stories::{find_doc, DocId, StoryDoc};
+use boka_core::vocab_ledger::{KnownWords, VocabLedger};
+
+use serde_json::json;
+
+fn span(id: &str, source: &str, text: &str) -> serde_json::Value {
+    json!({
+        "id": id,
+        "sourceText": source,
+        "activeVariantIndex": 0,
+        "variants": [{ "id": format!("{id}-v"), "register": "neutral", "text": text }]
+    })
+}
+
+fn story(language: &str, texts: &[(&str, &str)]) -> StoryDoc {
+    let mut tokens = Vec::new();
+    let mut spans = serde_json::Map::new();
+    for (i, (source, text)) in texts.iter().enumerate() {
+        if i > 0 {
+            tokens.push(json!({ "type": "text", "value": " " }));
+        }
+        let id = format!("s{i}");
+        tokens.push(json!({ "type": "span", "spanId": id }));
+        spans.insert(id.clone(), span(&id, source, text));
+    }
+    let library = json!([{
+        "id": "story-1",
+        "title": "Le chat",
+        "translations": { language: { "doc": { "tokens": tokens, "spans": spans } } }
+    }]);
+    find_doc(&library, &DocId::parse(&format!("story-1:{language}")).unwrap()).unwrap()
+}
+
+fn french() -> StoryDoc {
+    story(
+        "fr",
+        &[
+            ("The cat sleeps.", "Le chat dort."),
+            ("Marie watches the garden.", "Marie regarde le jardin."),
+            ("The cat dreams of birds.", "Le chat rêve des oiseaux."),
+            ("Night falls slowly.", "La nuit tombe doucement."),
+        ],
+    )
+}
+
+#[test]
+fn cloze_skips_names_and_short_words() {
+    let cloze = cloze_worksheet(&french(), 2, &KnownWords::default());
+    // Eligible: chat dort regarde jardin chat rêve oiseaux nuit tombe doucement.
+    assert_eq!(cloze.answers, ["dort", "jardin", "rêve", "nuit", "doucement"]);
+    assert!(cloze.blocks[0].starts_with("Le chat (1) ________. Marie regarde le (2) ________."));
+}
+
+#[test]
+fn quiz_has_one_right_answer_per_question() {
+    let questions = quiz(&french().doc, 10, &KnownWords::default());
+    assert_eq!(questions.len(), 4);
+    for (i, q) in questions.iter().enumerate() {
+        assert_eq!(q.options.len(), 4);
+        assert_eq!(q.answer, i % 4);
+    }
+    assert_eq!(questions[1].prompt, "Marie watches the garden.");
+    assert_eq!(questions[1].options[1], "Marie regarde le jardin.");
+
+    let single = story("fr", &[("Hi.", "Salut.")]);
+    assert!(quiz(&single.doc, 10, &KnownWords::default()).is_empty());
+}
+
+#[test]
+fn vocabulary_counts_lemmas_with_span_gloss() {
+    let vocab = vocabulary(&french().doc, "fr");
+    assert_eq!(vocab[0].lemma, "le");
+    assert_eq!(vocab[1].lemma, "chat");
+    assert_eq!(vocab[1].frequency, 2);
+    assert_eq!(vocab[1].gloss, "The cat sleeps.");
+}
+
+#[test]
+fn pack_zips_every_file() {
+    let pack = classroom_pack(&french(), &ClassroomPackOptions::default(), &KnownWords::default()).unwrap();
+    assert_eq!(pack.files, ["doc.json", "story.pdf", "cloze.txt", "vocab.csv", "quiz.txt"]);
+    assert!(pack.bytes.starts_with(b"PK\x03\x04"));
+    // End of central directory: 5 entries.
+    let eocd = &pack.bytes[pack.bytes.len() - 22..];
+    assert_eq!(&eocd[..4], b"PK\x05\x06");
+    assert_eq!(u16::from_le_bytes([eocd[10], eocd[11]]), 5);
+
+    // Scripts the built-in PDF fonts cannot show get an HTML copy instead.
+    let japanese = story("ja", &[("The cat sleeps.", "猫が寝ている。")]);
+    let pack = classroom_pack(&japanese, &ClassroomPackOptions::default(), &KnownWords::default()).unwrap();
+    assert_eq!(pack.files, ["doc.json", "story.html", "cloze.txt", "vocab.csv"]);
+}
+
+#[test]
+fn known_words_are_left_out_of_exercises() {
+    let dir = std::env::temp_dir().join(format!("boka-ledger-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let mut ledger = VocabLedger::load(&dir).unwrap();
+    assert_eq!(ledger.mark("fr-CA", "Le chat dort. La nuit tombe doucement.", true), 7);
+    assert_eq!(ledger.mark("fr", "chats", true), 0);
+    ledger.save(&dir).unwrap();
+    let ledger = VocabLedger::load(&dir).unwrap();
+    let known = ledger.known_words("fr");
+    assert!(known.is_known("Chats") && known.is_known("dorment"));
+    assert_eq!(known.unknown_count("Le chat regarde le jardin."), 2);
+
+    let cloze = cloze_worksheet(&french(), 2, &known);
+    assert_eq!(cloze.answers, ["jardin", "oiseaux"]);
+    let questions = quiz(&french().doc, 2, &known);
+    let prompts: Vec<_> = questions.iter().map(|q| q.prompt.as_str()).collect();
+    assert_eq!(prompts, ["Marie watches the garden.", "The cat dreams of birds."]);
+    // Spans with only known words still fill a longer quiz.
+    assert_eq!(quiz(&french().doc, 10, &known).len(), 4);
+
+    let mut ledger = ledger;
+    assert_eq!(ledger.mark("fr", "chats", false), 1);
+    assert!(!ledger.known_words("fr").is_known("chat"));
+    assert!(ledger.known_words("de").is_empty());
+    let _ = std::fs::remove_dir_all(&dir);
+}
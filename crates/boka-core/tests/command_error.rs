@@ -0,0 +1,62 @@
+//! Command errors: stable codes and details for pipeline, audio and storage
+//! failures, and their wire shape.
+
+use boka_core::command_error::{CommandError, FAILED};
+use boka_core::stories::StoryError;
+use boka_core::types::ApiError;
+
+use serde_json::json;
+
+#[test]
+fn pipeline_errors_keep_their_message_key_and_numbers() {
+    let err = CommandError::from(ApiError::InputTooLarge {
+        chars: 250_000,
+        limit: 200_000,
+    });
+    assert_eq!(err.code, "translation.inputTooLarge");
+    assert_eq!(err.details, Some(json!({ "chars": 250_000, "limit": 200_000 })));
+
+    let err = CommandError::from(ApiError::ApiResponse {
+        status: 429,
+        message: "slow down".to_string(),
+    });
+    assert_eq!(err.code, "translation.providerError");
+    assert!(err.message.contains("slow down"));
+    assert_eq!(err.details, Some(json!({ "status": 429 })));
+
+    let err = CommandError::from(ApiError::Parse("Cancelled".to_string()));
+    assert_eq!((err.code.as_str(), err.details), ("translation.cancelled", None));
+}
+
+#[test]
+fn storage_and_other_errors() {
+    let err = CommandError::from(StoryError::NotFound("s1:fr".to_string()));
+    assert_eq!(err.code, "storage.notFound");
+    assert_eq!(err.details, Some(json!({ "docId": "s1:fr" })));
+    assert_eq!(CommandError::from(StoryError::Parse("eof".to_string())).code, "storage.corrupt");
+
+    let err = CommandError::from("Job j1 is no longer running".to_string());
+    assert_eq!(err, CommandError::new(FAILED, "Job j1 is no longer running"));
+}
+
+#[test]
+fn serializes_camel_case_without_empty_details() {
+    let plain = CommandError::other("disk full");
+    assert_eq!(
+        serde_json::to_value(&plain).unwrap(),
+        json!({ "code": "command.failed", "message": "disk full" })
+    );
+    let detailed = CommandError::from(ApiError::ConfirmationRequired { segments: 80 });
+    assert_eq!(serde_json::to_value(&detailed).unwrap()["details"], json!({ "segments": 80 }));
+}
+
+#[cfg(feature = "tts")]
+#[test]
+fn audio_errors_use_audio_keys() {
+    use boka_core::audio::AudioError;
+
+    let err = CommandError::from(AudioError::ModelNotInstalled("kokoro-82m-q8".to_string()));
+    assert_eq!(err.code, "audio.modelNotLoaded");
+    assert_eq!(err.details, Some(json!({ "model": "kokoro-82m-q8" })));
+    assert_eq!(CommandError::from(AudioError::CacheIo("denied".to_string())).code, "audio.cacheIo");
+}
@@ -0,0 +1,70 @@
+//! Config hot-reload: which files count as config, and the live watcher.
+
+use boka_core::config_watch::{ConfigFile, ConfigWatcher};
+use boka_core::prompts::{PromptFileError, PromptOverrides};
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Duration;
+
+#[test]
+fn config_files_are_classified() {
+    let dir = Path::new("/data/boka");
+    let classify = |rel: &str| ConfigFile::classify(dir, &dir.join(rel));
+    assert_eq!(classify("settings.json"), Some(ConfigFile::Settings));
+    assert_eq!(classify("prompts.json"), Some(ConfigFile::Prompts));
+    assert_eq!(classify("prompts/ja.md"), Some(ConfigFile::Prompts));
+    assert_eq!(classify("models.json"), Some(ConfigFile::Models));
+    assert_eq!(classify("tts_models.json"), Some(ConfigFile::TtsModels));
+    assert_eq!(classify("templates/anki.txt"), Some(ConfigFile::Templates));
+    assert_eq!(classify("templates"), Some(ConfigFile::Templates));
+    for other in ["settings.json.tmp", "stories.json", "templates/.anki.txt.swp", "backup/settings.json"] {
+        assert_eq!(classify(other), None, "{other}");
+    }
+}
+
+#[test]
+fn prompt_overrides_load_from_the_data_dir() {
+    let dir = std::env::temp_dir().join(format!("boka-prompts-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    assert_eq!(PromptOverrides::load(&dir).unwrap(), PromptOverrides::default());
+
+    fs::write(PromptOverrides::path(&dir), r#"{"baseTranslation": "Translate into French."}"#).unwrap();
+    let loaded = PromptOverrides::load(&dir).unwrap();
+    assert_eq!(loaded.base_translation.as_deref(), Some("Translate into French."));
+    assert_eq!(loaded.span_planning, None);
+
+    fs::write(PromptOverrides::path(&dir), "{").unwrap();
+    assert!(matches!(PromptOverrides::load(&dir), Err(PromptFileError::Parse(_))));
+    assert!(ConfigFile::Prompts.check(&dir).is_err());
+    assert!(ConfigFile::Settings.check(&dir).is_ok());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn watcher_reports_edits_once_settled() {
+    let dir = std::env::temp_dir().join(format!("boka-watch-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let (tx, rx) = mpsc::channel();
+    let watcher = ConfigWatcher::spawn(&dir, move |files| {
+        let _ = tx.send(files);
+    })
+    .unwrap();
+
+    fs::write(dir.join("stories.json"), "[]").unwrap();
+    fs::write(PromptOverrides::path(&dir), "{}").unwrap();
+    fs::create_dir_all(dir.join("templates")).unwrap();
+    fs::write(dir.join("templates").join("anki.txt"), "{{ story.title }}").unwrap();
+
+    let mut seen = BTreeSet::new();
+    while seen.len() < 2 {
+        seen.extend(rx.recv_timeout(Duration::from_secs(10)).expect("no change reported"));
+    }
+    assert_eq!(seen, BTreeSet::from([ConfigFile::Prompts, ConfigFile::Templates]));
+
+    drop(watcher);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,18 @@
+//! Content addressing shared by the caches.
+
+use boka_core::content_hash::ContentHash;
+
+#[test]
+fn parts_are_hashed_with_their_boundaries() {
+    assert_ne!(ContentHash::of(&["ab", "c"]), ContentHash::of(&["a", "bc"]));
+    assert_eq!(ContentHash::of(&["a", "b"]), ContentHash::of(&["a", "b"]));
+    assert_eq!(ContentHash::of(&[]).hex().len(), 64);
+}
+
+#[test]
+fn text_ignores_spacing() {
+    assert_eq!(ContentHash::text("  The cat\n sleeps.  "), "The cat sleeps.");
+    let key = |text: &str| ContentHash::of(&["model", &ContentHash::text(text)]);
+    assert_eq!(key("The cat  sleeps."), key(" The cat sleeps."));
+    assert_ne!(key("The cat sleeps."), key("The cat sleeps!"));
+}
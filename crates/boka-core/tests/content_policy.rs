@@ -0,0 +1,17 @@
+//! Content policies: blocked themes match whole words of a variant.
+
+use boka_core::policy::ContentPolicy;
+
+#[test]
+fn blocked_themes_match_whole_words() {
+    let policy = ContentPolicy::child_safe();
+    for (text, note) in [("Essex", "A county"), ("Middlesex", ""), ("Wessex", "Old English kingdom")] {
+        assert_eq!(policy.violation("neutral", text, note), None, "{text}");
+    }
+    assert_eq!(policy.violation("neutral", "Sex", "").as_deref(), Some("blocked theme `sex`"));
+    assert_eq!(
+        policy.violation("casual", "se blesser", "Talks about self-harm").as_deref(),
+        Some("blocked theme `self-harm`")
+    );
+    assert_eq!(policy.violation("neutral", "harm to self", "").as_deref(), None);
+}
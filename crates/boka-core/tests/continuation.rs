@@ -0,0 +1,154 @@
+//! Story continuation: the new part is written, translated on its own and
+//! added after the existing doc, which keeps its spans and job id.
+
+use boka_core::continuation::{continue_story, ContinuationLength, ContinueArgs};
+use boka_core::gui_types::{InteractiveDoc, TranslationJob};
+use boka_core::length_guard::LengthGuard;
+use boka_core::policy::ContentPolicy;
+use boka_core::settings::VariantBounds;
+use boka_core::translation::{run_translation, TranslationArgs, TranslationResult};
+use boka_core::types::{ApiError, LlmProviderConfig, LlmProviderPreset};
+
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+
+fn mock() -> LlmProviderConfig {
+    LlmProviderConfig {
+        preset: LlmProviderPreset::Mock,
+        ..Default::default()
+    }
+}
+
+async fn translate(story: &str) -> TranslationResult {
+    run_translation(TranslationArgs {
+        story_text: story.to_string(),
+        job_id: "job-1".to_string(),
+        target_language: "fr".to_string(),
+        source_language: Some("en".to_string()),
+        adult_mode: false,
+        content_policy: None,
+        dense_spans: false,
+        reproducible: false,
+        prompt_overrides: Default::default(),
+        judge: None,
+        variant_bounds: VariantBounds::default(),
+        length_guard: LengthGuard::default(),
+        simplify_level: None,
+        dual_output: false,
+        refine_variants: false,
+        granularity: Default::default(),
+        comprehension_every: None,
+        annotations: Default::default(),
+        review: None,
+        error_policy: Default::default(),
+        budget: None,
+        budget_gate: None,
+        confirmation: None,
+        translation_cache: None,
+        transcript: None,
+        ui_language: None,
+        provider: mock(),
+        cancelled: Arc::new(AtomicBool::new(false)),
+        on_job: Box::new(|_: &TranslationJob| async {}),
+        on_doc: Box::new(|_: &InteractiveDoc| async {}),
+    })
+    .await
+    .expect("mock translation")
+}
+
+fn args(
+    done: &TranslationResult,
+    story: &str,
+    hint: Option<&str>,
+    jobs: Arc<Mutex<Vec<TranslationJob>>>,
+) -> ContinueArgs {
+    ContinueArgs {
+        job: done.job.clone(),
+        doc: done.doc.clone(),
+        story_text: story.to_string(),
+        direction_hint: hint.map(str::to_string),
+        length: ContinuationLength::Short,
+        prompt_overrides: Default::default(),
+        provider: Some(mock()),
+        content_policy: None,
+        translation_cache: None,
+        transcript: None,
+        annotation_data_dir: None,
+        cancelled: Arc::new(AtomicBool::new(false)),
+        on_job: Box::new(move |job: &TranslationJob| {
+            jobs.lock().unwrap().push(job.clone());
+            async {}
+        }),
+        on_doc: Box::new(|_: &InteractiveDoc| async {}),
+    }
+}
+
+#[tokio::test]
+async fn the_new_part_is_translated_onto_the_doc() {
+    let story = "The cat sleeps.\n\nThe dog barks.";
+    let done = translate(story).await;
+    let before = done.doc.block_texts();
+    let jobs = Arc::new(Mutex::new(Vec::new()));
+
+    let hint = "The bird sings. Night falls.";
+    let continued = continue_story(args(&done, story, Some(hint), jobs.clone())).await.unwrap();
+    assert_eq!(continued.text, hint);
+    assert_eq!(continued.story_text, format!("{}\n\n{}", story, hint));
+
+    let job = &continued.result.job;
+    assert_eq!(job.id, "job-1");
+    assert!(job.ready);
+    let ids: Vec<_> = job.segments.iter().map(|s| s.id.as_str()).collect();
+    assert_eq!(ids.len(), done.job.segments.len() + 2);
+    assert_eq!(ids.last(), Some(&format!("seg-{}", ids.len()).as_str()));
+    assert_eq!(job.segments.last().unwrap().source, "Night falls.");
+
+    // Old blocks and spans are untouched; new spans come after them.
+    let doc = &continued.result.doc;
+    let blocks = doc.block_texts();
+    assert_eq!(blocks.len(), job.segments.len());
+    assert_eq!(&blocks[..before.len()], before.as_slice());
+    for (id, span) in &done.doc.spans {
+        assert_eq!(doc.spans[id].source_text, span.source_text);
+    }
+    assert!(doc.spans.len() > done.doc.spans.len());
+    assert!(doc.spans.iter().all(|(id, span)| *id == span.id));
+    assert_eq!(doc.block_directions.len(), blocks.len());
+
+    // Progress is reported as the whole job.
+    let jobs = jobs.lock().unwrap();
+    assert!(jobs.iter().all(|j| j.id == "job-1" && j.segments.len() == ids.len()));
+}
+
+#[tokio::test]
+async fn without_a_hint_the_model_decides_but_it_needs_the_source() {
+    let done = translate("The cat sleeps.").await;
+    let jobs = Arc::new(Mutex::new(Vec::new()));
+    let unscripted = continue_story(args(&done, "The cat sleeps.", None, jobs.clone())).await.unwrap();
+    assert_eq!(unscripted.text, "The cat sleeps.");
+
+    let private = continue_story(args(&done, "  ", None, jobs.clone())).await;
+    assert!(matches!(private, Err(ApiError::Parse(_))));
+    let mut old = args(&done, "The cat sleeps.", None, jobs);
+    old.job.metadata = None;
+    assert!(matches!(continue_story(old).await, Err(ApiError::Parse(_))));
+}
+
+#[tokio::test]
+async fn child_safe_mode_screens_the_new_part() {
+    let story = "The cat sleeps.";
+    let done = translate(story).await;
+    let jobs = Arc::new(Mutex::new(Vec::new()));
+    let hint = "The cat says shit.";
+    assert!(continue_story(args(&done, story, Some(hint), jobs.clone())).await.is_ok());
+
+    // The job's own policy is not moderated; the one set now is.
+    let mut child_safe = args(&done, story, Some(hint), jobs.clone());
+    child_safe.content_policy = Some(ContentPolicy::child_safe());
+    let refused = continue_story(child_safe).await;
+    assert!(matches!(refused, Err(ApiError::Refused { reason, .. }) if reason == "moderation: `shit`"));
+
+    let mut clean = args(&done, story, Some("The bird sings."), jobs);
+    clean.content_policy = Some(ContentPolicy::child_safe());
+    assert_eq!(continue_story(clean).await.unwrap().text, "The bird sings.");
+}
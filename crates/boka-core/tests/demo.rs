@@ -0,0 +1,83 @@
+//! The bundled offline demo replayed by the mock provider.
+
+use boka_core::demo::{demo_story, DEMO_BASE_URL};
+use boka_core::gui_types::{ErrorPolicy, Granularity, InteractiveDoc, TranslationJob};
+use boka_core::length_guard::LengthGuard;
+use boka_core::settings::VariantBounds;
+use boka_core::translation::{run_translation, TranslationArgs, TranslationResult};
+use boka_core::types::{LlmProviderConfig, LlmProviderPreset};
+
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+async fn translate(story: &str, language: &str) -> TranslationResult {
+    run_translation(TranslationArgs {
+        story_text: story.to_string(),
+        job_id: "job-demo".to_string(),
+        target_language: language.to_string(),
+        source_language: Some("en".to_string()),
+        adult_mode: false,
+        content_policy: None,
+        dense_spans: false,
+        reproducible: false,
+        prompt_overrides: Default::default(),
+        judge: None,
+        variant_bounds: VariantBounds::default(),
+        length_guard: LengthGuard::default(),
+        simplify_level: None,
+        dual_output: false,
+        refine_variants: false,
+        granularity: Granularity::Sentence,
+        comprehension_every: None,
+        annotations: Default::default(),
+        review: None,
+        error_policy: ErrorPolicy::Abort,
+        budget: None,
+        budget_gate: None,
+        confirmation: None,
+        translation_cache: None,
+        transcript: None,
+        ui_language: None,
+        provider: LlmProviderConfig {
+            preset: LlmProviderPreset::Mock,
+            api_key: None,
+            base_url: Some(DEMO_BASE_URL.to_string()),
+            model: None,
+            reasoning_model: false,
+        },
+        cancelled: Arc::new(AtomicBool::new(false)),
+        on_job: Box::new(|_: &TranslationJob| async {}),
+        on_doc: Box::new(|_: &InteractiveDoc| async {}),
+    })
+    .await
+    .expect("demo translation should succeed")
+}
+
+#[tokio::test]
+async fn demo_story_translates_without_a_key() {
+    let story = demo_story();
+    assert_eq!(story.languages, ["es", "fr"]);
+
+    for language in &story.languages {
+        let result = translate(&story.text, language).await;
+        let doc = &result.doc;
+        assert_eq!(doc.block_texts().len(), 4, "{}", language);
+        assert!(doc.spans.len() >= 5, "{}", language);
+        assert!(doc.spans.values().all(|s| s.variants.len() >= 2), "{}", language);
+        assert!(doc.spans.values().all(|s| s.variants[0].register == "neutral"), "{}", language);
+    }
+
+    let fr = translate(&story.text, "fr").await;
+    assert_eq!(fr.doc.block_texts()[0], "Chaque soir, le vieux gardien montait l’escalier du phare.");
+    let keeper = &fr.doc.spans["span-1"];
+    assert_eq!(keeper.variants[2].text, "le gardien chenu");
+    assert_eq!(keeper.variants[2].register, "literary");
+}
+
+#[tokio::test]
+async fn other_text_is_echoed() {
+    let result = translate("A sentence the demo does not know.", "fr").await;
+    assert_eq!(result.doc.block_texts(), ["A sentence the demo does not know."]);
+    let result = translate(&demo_story().text, "de").await;
+    assert_eq!(result.doc.block_texts()[0], "Every evening, the old keeper climbed the stairs of the lighthouse.");
+}
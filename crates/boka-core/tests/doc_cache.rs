@@ -0,0 +1,100 @@
+//! The session doc cache: LRU order, invalidation and its size setting.
+
+use boka_core::doc_cache::DocCache;
+use boka_core::jsonl::from_jsonl;
+use boka_core::settings::{Settings, SettingsError};
+use boka_core::stories::{self, put_doc, DocId};
+
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn doc(story_id: &str, title: &str) -> String {
+    format!(
+        r#"{{"type":"story","format":"boka-jsonl","version":1,"storyId":"{story_id}","title":"{title}","sourceLanguage":"en","sourceText":"Hello.","language":"fr"}}
+{{"type":"span","id":"a","sourceText":"Hello.","activeVariantIndex":0,"variants":[{{"id":"a1","register":"neutral","text":"{title}"}}]}}
+"#
+    )
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("boka-doc-cache-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn library(dir: &Path, titles: &[&str]) -> (Value, Vec<DocId>) {
+    let mut all = json!([]);
+    let ids = titles
+        .iter()
+        .enumerate()
+        .map(|(i, title)| put_doc(&mut all, &from_jsonl(&doc(&format!("s{}", i), title)).unwrap()).unwrap())
+        .collect();
+    stories::save(dir, &all).unwrap();
+    (all, ids)
+}
+
+#[test]
+fn docs_are_cached_until_dropped_or_written() {
+    least_recently_used_docs_are_dropped_first();
+    writes_and_other_data_dirs_drop_cached_docs();
+}
+
+fn least_recently_used_docs_are_dropped_first() {
+    let dir = temp_dir("lru");
+    let (_, ids) = library(&dir, &["One", "Two", "Three"]);
+    let mut cache = DocCache::new(2);
+
+    assert_eq!(cache.get(&dir, &ids[0]).unwrap().title, "One");
+    assert_eq!(cache.get(&dir, &ids[1]).unwrap().title, "Two");
+    cache.get(&dir, &ids[0]).unwrap();
+    cache.get(&dir, &ids[2]).unwrap();
+    let stats = cache.stats();
+    assert_eq!((stats.len, stats.hits, stats.misses), (2, 1, 3));
+
+    // "Two" was the least recently used, so it went; "One" stayed.
+    cache.get(&dir, &ids[0]).unwrap();
+    cache.get(&dir, &ids[1]).unwrap();
+    assert_eq!((cache.stats().hits, cache.stats().misses), (2, 4));
+
+    cache.set_capacity(0);
+    assert_eq!(cache.stats().len, 0);
+    cache.get(&dir, &ids[0]).unwrap();
+    assert_eq!((cache.stats().len, cache.stats().misses), (0, 5));
+    assert!(cache.get(&dir, &DocId::parse("missing:fr").unwrap()).is_err());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+// One test: any save in the process drops the cache, so counting hits next
+// to another test that saves would be racy.
+fn writes_and_other_data_dirs_drop_cached_docs() {
+    let dir = temp_dir("writes");
+    let (mut all, ids) = library(&dir, &["Before"]);
+    let mut cache = DocCache::new(8);
+    assert_eq!(cache.get(&dir, &ids[0]).unwrap().title, "Before");
+    assert_eq!(cache.get(&dir, &ids[0]).unwrap().title, "Before");
+
+    put_doc(&mut all, &from_jsonl(&doc("s0", "After")).unwrap()).unwrap();
+    stories::save(&dir, &all).unwrap();
+    assert_eq!(cache.get(&dir, &ids[0]).unwrap().doc.block_texts(), ["After"]);
+
+    let other = temp_dir("writes-other");
+    library(&other, &["Elsewhere"]);
+    assert_eq!(cache.get(&other, &ids[0]).unwrap().title, "Elsewhere");
+    assert_eq!(cache.stats().len, 1);
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::remove_dir_all(&other);
+}
+
+#[test]
+fn cache_size_setting_is_bounded() {
+    let mut settings = Settings::default();
+    assert_eq!(settings.doc_cache_size.0, 16);
+    settings.set_doc_cache_size(0).unwrap();
+    assert!(matches!(settings.set_doc_cache_size(257), Err(SettingsError::Invalid(_))));
+    assert_eq!(settings.doc_cache_size.0, 0);
+
+    let old: Settings = serde_json::from_str("{}").unwrap();
+    assert_eq!(old.doc_cache_size.0, 16);
+}
@@ -0,0 +1,94 @@
+//! Switching a whole saved doc to one register.
+
+use boka_core::policy::register_fallback;
+use boka_core::stories::{self, DocId, StoryError};
+
+use serde_json::json;
+
+fn variant(id: &str, register: &str, flagged: bool) -> serde_json::Value {
+    let mut v = json!({ "id": id, "register": register, "text": id });
+    if flagged {
+        v["flagged"] = json!("register `vulgar` not allowed");
+    }
+    v
+}
+
+fn library() -> serde_json::Value {
+    json!([{
+        "id": "story-1",
+        "title": "Cats",
+        "customField": 42,
+        "updatedAt": 0,
+        "translations": {
+            "fr": {
+                "language": "fr",
+                "doc": {
+                    "tokens": [{ "type": "span", "spanId": "span-1" }, { "type": "span", "spanId": "span-2" }],
+                    "spans": {
+                        "span-1": {
+                            "id": "span-1",
+                            "sourceText": "a",
+                            "activeVariantIndex": 0,
+                            "variants": [
+                                variant("a-neutral", "neutral", false),
+                                variant("a-vulgar", "vulgar", false),
+                                variant("a-casual", "casual", false),
+                            ]
+                        },
+                        "span-2": {
+                            "id": "span-2",
+                            "sourceText": "b",
+                            "activeVariantIndex": 0,
+                            "variants": [
+                                variant("b-neutral", "neutral", false),
+                                variant("b-colloquial", "colloquial", true),
+                            ]
+                        }
+                    }
+                }
+            }
+        }
+    }])
+}
+
+#[test]
+fn fallback_prefers_nearest_then_less_marked() {
+    assert_eq!(
+        register_fallback("colloquial"),
+        ["colloquial", "casual", "vulgar", "neutral", "literary", "formal"]
+    );
+    assert_eq!(register_fallback("formal")[..3], ["formal", "literary", "neutral"]);
+}
+
+#[test]
+fn switches_every_span_and_keeps_unknown_fields() {
+    let mut all = library();
+    let id = DocId::parse("story-1:fr").unwrap();
+    let doc = stories::set_doc_register(&mut all, &id, "colloquial").unwrap();
+
+    // No colloquial variant in span-1: casual is nearer than vulgar's tie-break.
+    assert_eq!(doc.spans["span-1"].active_variant_index, 2);
+    // The only colloquial variant is flagged, so neutral wins.
+    assert_eq!(doc.spans["span-2"].active_variant_index, 0);
+
+    let saved = &all[0];
+    assert_eq!(saved["customField"], 42);
+    assert_eq!(saved["translations"]["fr"]["doc"]["spans"]["span-1"]["activeVariantIndex"], 2);
+    assert!(saved["updatedAt"].as_u64().unwrap() > 0);
+}
+
+#[test]
+fn rejects_bad_ids_and_registers() {
+    let mut all = library();
+    assert!(matches!(DocId::parse("story-1"), Err(StoryError::InvalidDocId(_))));
+    let missing = DocId::parse("story-1:de").unwrap();
+    assert!(matches!(
+        stories::set_doc_register(&mut all, &missing, "formal"),
+        Err(StoryError::NotFound(_))
+    ));
+    let id = DocId::parse("story-1:fr").unwrap();
+    assert!(matches!(
+        stories::set_doc_register(&mut all, &id, "pirate"),
+        Err(StoryError::UnknownRegister(_))
+    ));
+}
@@ -0,0 +1,128 @@
+//! Doc text for the clipboard: formats, registers, notes and source.
+
+use boka_core::export::text::{doc_text, DocTextOptions, TextFormat};
+use boka_core::stories::{find_doc, DocId, StoryDoc};
+
+use serde_json::{json, Value};
+
+fn segment(id: &str, source: &str) -> Value {
+    json!({ "id": id, "source": source, "baseStage": "ready", "spanStage": "ready", "variantCount": 2 })
+}
+
+fn library(segments: Vec<Value>) -> Value {
+    json!([{
+        "id": "story-1",
+        "title": "The Cat",
+        "sourceLanguage": "en",
+        "sourceText": "The cat sleeps.\n\nIt dreams.",
+        "translations": {
+            "es": {
+                "language": "es",
+                "job": { "id": "j1", "ready": true, "segments": segments },
+                "doc": {
+                    "tokens": [
+                        { "type": "span", "spanId": "s1" },
+                        { "type": "text", "value": " " },
+                        { "type": "text", "value": "mucho", "style": "strong" },
+                        { "type": "text", "value": "\n\n" },
+                        { "type": "span", "spanId": "s2", "style": "emphasis" },
+                        { "type": "check", "question": "¿Sueña el gato?", "answer": true }
+                    ],
+                    "spans": {
+                        "s1": {
+                            "id": "s1",
+                            "sourceText": "The cat sleeps.",
+                            "activeVariantIndex": 0,
+                            "variants": [
+                                { "id": "v1", "register": "neutral", "text": "El gato duerme" },
+                                { "id": "v2", "register": "casual", "text": "El gato sobando",
+                                  "note": "\"sobar\" is slang for sleeping." }
+                            ]
+                        },
+                        "s2": {
+                            "id": "s2",
+                            "sourceText": "It dreams.",
+                            "activeVariantIndex": 0,
+                            "variants": [{ "id": "v3", "register": "neutral", "text": "Sueña." }]
+                        }
+                    }
+                }
+            }
+        }
+    }])
+}
+
+fn story(segments: Vec<Value>) -> StoryDoc {
+    find_doc(&library(segments), &DocId::parse("story-1:es").unwrap()).unwrap()
+}
+
+fn aligned() -> StoryDoc {
+    story(vec![segment("seg-0", "The cat sleeps."), segment("seg-1", "It dreams.")])
+}
+
+#[test]
+fn plain_text_has_title_and_blocks_without_checks() {
+    let text = doc_text(&aligned(), &DocTextOptions::default());
+    assert_eq!(text, "The Cat\n\nEl gato duerme mucho\n\nSueña.");
+}
+
+#[test]
+fn markdown_keeps_title_heading_and_emphasis() {
+    let options = DocTextOptions {
+        format: TextFormat::Markdown,
+        ..Default::default()
+    };
+    let text = doc_text(&aligned(), &options);
+    assert_eq!(text, "# The Cat\n\nEl gato duerme **mucho**\n\n*Sueña.*");
+}
+
+#[test]
+fn register_switches_variants_and_notes_follow_them() {
+    let options = DocTextOptions {
+        register: Some("casual".to_string()),
+        notes: true,
+        ..Default::default()
+    };
+    let story = aligned();
+    let text = doc_text(&story, &options);
+    assert_eq!(
+        text,
+        "The Cat\n\nEl gato sobando mucho\n- El gato sobando: \"sobar\" is slang for sleeping.\n\nSueña."
+    );
+    // The cached doc keeps the reader's own choices.
+    assert_eq!(story.doc.spans["s1"].active_variant_index, 0);
+
+    let neutral = DocTextOptions {
+        notes: true,
+        ..Default::default()
+    };
+    assert!(!doc_text(&story, &neutral).contains("sobar"));
+}
+
+#[test]
+fn source_goes_under_aligned_blocks_else_at_the_end() {
+    let options = DocTextOptions {
+        format: TextFormat::Markdown,
+        source: true,
+        ..Default::default()
+    };
+    let text = doc_text(&aligned(), &options);
+    assert_eq!(
+        text,
+        "# The Cat\n\nEl gato duerme **mucho**\n\n> The cat sleeps.\n\n*Sueña.*\n\n> It dreams."
+    );
+
+    let options = DocTextOptions {
+        source: true,
+        ..Default::default()
+    };
+    let text = doc_text(&story(vec![segment("seg-0", "The cat sleeps.\n\nIt dreams.")]), &options);
+    assert_eq!(text, "The Cat\n\nEl gato duerme mucho\n\nSueña.\n\nSource\n\nThe cat sleeps.\n\nIt dreams.");
+}
+
+#[test]
+fn options_default_from_partial_json() {
+    let options: DocTextOptions = serde_json::from_value(json!({ "format": "markdown", "notes": true })).unwrap();
+    assert_eq!(options.format, TextFormat::Markdown);
+    assert!(options.notes && !options.source && options.register.is_none());
+}
@@ -0,0 +1,94 @@
+//! Word frequency bands.
+
+use boka_core::frequency::{doc_frequency, frequency_dir, FreqBand, FreqList, FreqToken};
+use boka_core::gui_types::DocToken;
+use boka_core::jsonl::from_jsonl;
+
+use std::fs;
+
+const DOC: &str = r#"{"type":"story","format":"boka-jsonl","version":1,"storyId":"s1","title":"Chats","sourceLanguage":"en","sourceText":"The cats sleep.","language":"fr"}
+{"type":"span","id":"a","sourceText":"The cats sleep.","activeVariantIndex":0,"variants":[{"id":"a1","register":"neutral","text":"Les chats dorment."},{"id":"a2","register":"casual","text":"Le chat roupille."}]}
+{"type":"text","value":" "}
+{"type":"text","value":"Il fait nuit."}
+"#;
+
+fn band(list: &FreqList, word: &str) -> Option<FreqBand> {
+    list.band(word, "en")
+}
+
+#[test]
+fn bands_follow_rank_and_list_length() {
+    let mut words: Vec<String> = (0..6000).map(|i| format!("w{}", i)).collect();
+    words[10] = "Cat".to_string();
+    let full = FreqList::parse(&format!("# comment\n\n{}", words.join("\n")), "en");
+    assert_eq!(full.len(), 6000);
+    assert_eq!(band(&full, "w0"), Some(FreqBand::Top1k));
+    assert_eq!(band(&full, "w1000"), Some(FreqBand::Top5k));
+    assert_eq!(band(&full, "w5000"), Some(FreqBand::Rare));
+    assert_eq!(band(&full, "zebra"), Some(FreqBand::Rare));
+    // Case and inflections fall back onto the listed word.
+    assert_eq!(band(&full, "CATS"), Some(FreqBand::Top1k));
+
+    let short = FreqList::parse("the\ncat", "en");
+    assert_eq!(band(&short, "the"), Some(FreqBand::Top1k));
+    assert_eq!(band(&short, "zebra"), None);
+}
+
+#[test]
+fn tokens_keep_the_text_between_words() {
+    let list = FreqList::builtin("en-GB").unwrap();
+    let tokens = list.tokens("The cat, the dog!", "en");
+    let text: String = tokens.iter().map(|t| t.text.as_str()).collect();
+    assert_eq!(text, "The cat, the dog!");
+    assert_eq!(
+        tokens[..2],
+        [
+            FreqToken { text: "The".to_string(), freq_band: Some(FreqBand::Top1k) },
+            FreqToken { text: " ".to_string(), freq_band: None },
+        ]
+    );
+    assert_eq!(serde_json::to_value(&tokens[0]).unwrap()["freqBand"], "top-1k");
+    assert!(FreqList::builtin("xx").is_none());
+}
+
+#[test]
+fn user_lists_replace_builtin_ones() {
+    let dir = std::env::temp_dir().join(format!("boka-frequency-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let story = from_jsonl(DOC).unwrap();
+
+    let builtin = FreqList::load(&dir, "fr").unwrap().unwrap();
+    let freq = doc_frequency(&story.doc, "fr", Some(&builtin));
+    assert!(freq.has_list && freq.list_len < 5000);
+    let variants = &freq.spans["a"];
+    assert_eq!(variants.len(), 2);
+    assert_eq!(variants[0][0].freq_band, Some(FreqBand::Top1k));
+    // Text between spans is split into banded words; runs without words stay whole.
+    let values: Vec<(&str, Option<FreqBand>)> = freq.tokens[1..]
+        .iter()
+        .map(|token| match token {
+            DocToken::Text { value, freq_band, .. } => (value.as_str(), *freq_band),
+            other => panic!("unexpected token {:?}", other),
+        })
+        .collect();
+    assert_eq!(values[..3], [(" ", None), ("Il", Some(FreqBand::Top1k)), (" ", None)]);
+    let text: String = values.iter().map(|(value, _)| *value).collect();
+    assert_eq!(text, " Il fait nuit.");
+    assert!(matches!(freq.tokens[0], DocToken::Span { .. }));
+    assert_eq!(serde_json::to_value(&freq.tokens[2]).unwrap()["freqBand"], "top-1k");
+    // Saved docs never carry a band.
+    assert!(!serde_json::to_value(&story.doc).unwrap().to_string().contains("freqBand"));
+    // The starter list is too short to call a word rare.
+    assert_eq!(builtin.band("roupille", "fr"), None);
+
+    fs::create_dir_all(frequency_dir(&dir)).unwrap();
+    fs::write(frequency_dir(&dir).join("fr.txt"), "roupiller\n").unwrap();
+    let user = FreqList::load(&dir, "fr-CA").unwrap().unwrap();
+    assert_eq!(user.len(), 1);
+    assert_eq!(user.band("les", "fr"), None);
+
+    let none = doc_frequency(&story.doc, "tlh", None);
+    assert!(!none.has_list);
+    assert!(none.spans["a"][1].iter().all(|t| t.freq_band.is_none()));
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,93 @@
+//! Backend message catalog: stable keys, locale codes with an English
+//! fallback, and `{n}` arguments.
+
+use boka_core::i18n::{api_error, message, Locale, MessageKey};
+use boka_core::types::ApiError;
+
+use std::collections::HashSet;
+
+const KEYS: [MessageKey; 19] = [
+    MessageKey::AudioModelLoading,
+    MessageKey::AudioGenerating,
+    MessageKey::AudioEncoding,
+    MessageKey::AudioCacheHit,
+    MessageKey::AudioCacheUnavailable,
+    MessageKey::AudioModelNotLoaded,
+    MessageKey::AudioGenerationFailed,
+    MessageKey::AudioCancelled,
+    MessageKey::AudioCacheIo,
+    MessageKey::AudioWavEncode,
+    MessageKey::TranslationCancelled,
+    MessageKey::TranslationNoApiKey,
+    MessageKey::TranslationNetwork,
+    MessageKey::TranslationProviderError,
+    MessageKey::TranslationBadResponse,
+    MessageKey::TranslationInputTooLarge,
+    MessageKey::TranslationConfirmationRequired,
+    MessageKey::TranslationBudgetExceeded,
+    MessageKey::TranslationRefused,
+];
+
+const LOCALES: [Locale; 5] = [Locale::En, Locale::Fr, Locale::Es, Locale::De, Locale::Ja];
+
+#[test]
+fn keys_are_stable_and_distinct() {
+    assert_eq!(MessageKey::AudioCacheHit.as_str(), "audio.cacheHit");
+    assert_eq!(MessageKey::TranslationInputTooLarge.as_str(), "translation.inputTooLarge");
+    let wire: HashSet<&str> = KEYS.iter().map(|k| k.as_str()).collect();
+    assert_eq!(wire.len(), KEYS.len());
+    assert!(wire.iter().all(|k| k.starts_with("audio.") || k.starts_with("translation.")));
+}
+
+#[test]
+fn unknown_locales_fall_back_to_english() {
+    assert_eq!(Locale::from_code(Some("fr-CA")), Locale::Fr);
+    assert_eq!(Locale::from_code(Some("DE_at")), Locale::De);
+    assert_eq!(Locale::from_code(Some("jp")), Locale::Ja);
+    for code in [Some("pt-BR"), Some(""), None] {
+        assert_eq!(Locale::from_code(code), Locale::En, "{code:?}");
+    }
+
+    let key = MessageKey::AudioCacheHit;
+    assert_eq!(message(key, Locale::from_code(Some("pt")), &[]), "Found in cache");
+    assert_eq!(message(key, Locale::Fr, &[]), "Trouvé dans le cache");
+}
+
+#[test]
+fn arguments_fill_their_placeholders() {
+    let key = MessageKey::TranslationInputTooLarge;
+    let text = message(key, Locale::En, &["120000", "100000"]);
+    assert!(text.contains("120000") && text.contains("100000") && !text.contains('{'), "{text}");
+    // Missing arguments leave the placeholder; extra ones are ignored.
+    assert!(message(key, Locale::En, &[]).contains("{0}"));
+    assert_eq!(message(MessageKey::AudioCacheHit, Locale::En, &["x"]), "Found in cache");
+
+    // Every translation takes the arguments its English text does.
+    for key in KEYS {
+        let count = |locale| message(key, locale, &["\u{1}", "\u{2}"]).matches(['\u{1}', '\u{2}']).count();
+        for locale in LOCALES {
+            let text = message(key, locale, &["\u{1}", "\u{2}"]);
+            assert!(!text.is_empty() && !text.contains('{'), "{} {locale:?}", key.as_str());
+            assert_eq!(count(locale), count(Locale::En), "{} {locale:?}", key.as_str());
+        }
+    }
+}
+
+#[test]
+fn api_errors_map_to_keys_with_their_details() {
+    let (key, text) = api_error(&ApiError::Parse("Cancelled".to_string()), Locale::De);
+    assert_eq!((key, text.as_str()), (MessageKey::TranslationCancelled, message(key, Locale::De, &[]).as_str()));
+    let (key, _) = api_error(&ApiError::Parse("bad json".to_string()), Locale::En);
+    assert_eq!(key, MessageKey::TranslationBadResponse);
+
+    let refused = ApiError::Refused {
+        provider: "openai".to_string(),
+        model: "gpt-4o".to_string(),
+        reason: "I can't help with that.".to_string(),
+    };
+    let (key, text) = api_error(&refused, Locale::Fr);
+    assert_eq!(key, MessageKey::TranslationRefused);
+    assert!(text.starts_with("gpt-4o a refusé"), "{text}");
+    let (_, text) = api_error(&ApiError::BudgetExceeded { tokens: 5000 }, Locale::Es);
+    assert!(text.contains("5000"), "{text}");
+}
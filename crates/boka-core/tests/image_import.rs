@@ -0,0 +1,55 @@
+//! Story import from page photos.
+
+use boka_core::import::{clean_transcription, import_images, ImportError};
+use boka_core::types::{LlmProviderConfig, LlmProviderPreset};
+
+use std::path::PathBuf;
+
+fn page(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/pages").join(name)
+}
+
+fn mock_provider(fixture: &str) -> LlmProviderConfig {
+    LlmProviderConfig {
+        preset: LlmProviderPreset::Mock,
+        api_key: None,
+        base_url: Some(format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), fixture)),
+        model: None,
+        reasoning_model: false,
+    }
+}
+
+#[test]
+fn cleanup_rejoins_hyphens_and_lines() {
+    let raw = "Le vieux mar-\nchand\r\nvendait des\npommes.\n\n\n42\nC'est Jean-\nPierre qui   les achetait.";
+    assert_eq!(
+        clean_transcription(raw),
+        "Le vieux marchand vendait des pommes.\n\nC'est Jean- Pierre qui les achetait."
+    );
+}
+
+#[tokio::test]
+async fn pages_are_transcribed_in_order_and_joined() {
+    let story = import_images(&[page("page1.png"), page("page2.png")], mock_provider("image_import.json"))
+        .await
+        .expect("import should succeed");
+
+    assert_eq!(story.pages, 2);
+    assert_eq!(
+        story.text,
+        "Il était une fois un petit chat qui dormait.\n\nUn jour, le chat se réveilla et partit en voyage."
+    );
+    assert!(story.usage.output_tokens > 0);
+}
+
+#[tokio::test]
+async fn rejects_unsupported_files_before_sending() {
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/image_import.json");
+    let err = import_images(&[page("page1.png"), fixture], mock_provider("image_import.json"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ImportError::UnsupportedFormat(_)));
+
+    let err = import_images(&[], mock_provider("image_import.json")).await.unwrap_err();
+    assert!(matches!(err, ImportError::Empty));
+}
@@ -0,0 +1,160 @@
+//! Input size limits, chapter chunking, the confirmation handshake, job budgets
+//! and duplicate-job fingerprints.
+
+use boka_core::gui_types::{ErrorPolicy, Granularity, InteractiveDoc, TranslationJob};
+use boka_core::length_guard::LengthGuard;
+use boka_core::limits::{
+    check_input, chunk_chapters, job_fingerprint, preflight, JobBudget, CHAPTER_CHARS, MAX_INPUT_CHARS,
+};
+use boka_core::pricing::DisplayCurrency;
+use boka_core::settings::VariantBounds;
+use boka_core::translation::{run_translation, TranslationArgs, TranslationResult};
+use boka_core::types::{ApiError, LlmProviderConfig, LlmProviderPreset, ModelPricing, Usage};
+
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+fn echo_provider() -> LlmProviderConfig {
+    LlmProviderConfig {
+        preset: LlmProviderPreset::Mock,
+        api_key: None,
+        base_url: None,
+        model: None,
+        reasoning_model: false,
+    }
+}
+
+/// Paragraphs of ~1k chars, enough to need several chapters.
+fn long_story(paragraphs: usize) -> String {
+    let para = "The cat sleeps on the warm windowsill all afternoon. ".repeat(19);
+    vec![para.trim().to_string(); paragraphs].join("\n\n")
+}
+
+async fn translate(story: String, confirmation: Option<String>) -> Result<TranslationResult, ApiError> {
+    run_translation(TranslationArgs {
+        story_text: story,
+        job_id: "job-limits".to_string(),
+        target_language: "fr".to_string(),
+        source_language: None,
+        adult_mode: false,
+        content_policy: None,
+        dense_spans: false,
+        reproducible: false,
+        prompt_overrides: Default::default(),
+        judge: None,
+        variant_bounds: VariantBounds::default(),
+        length_guard: LengthGuard::default(),
+        simplify_level: None,
+        dual_output: false,
+        refine_variants: false,
+        granularity: Granularity::Sentence,
+        comprehension_every: None,
+        annotations: Default::default(),
+        review: None,
+        error_policy: ErrorPolicy::Abort,
+        budget: None,
+        budget_gate: None,
+        confirmation,
+        translation_cache: None,
+        transcript: None,
+        ui_language: None,
+        provider: echo_provider(),
+        cancelled: Arc::new(AtomicBool::new(false)),
+        on_job: Box::new(|_: &TranslationJob| async {}),
+        on_doc: Box::new(|_: &InteractiveDoc| async {}),
+    })
+    .await
+}
+
+#[test]
+fn oversized_input_is_rejected() {
+    let text = "a".repeat(MAX_INPUT_CHARS + 1);
+    match check_input(&text) {
+        Err(ApiError::InputTooLarge { chars, limit }) => assert_eq!((chars, limit), (MAX_INPUT_CHARS + 1, MAX_INPUT_CHARS)),
+        other => panic!("expected InputTooLarge, got {:?}", other),
+    }
+}
+
+#[test]
+fn long_text_is_chunked_at_paragraphs() {
+    let story = long_story(30);
+    let chapters = chunk_chapters(&story, CHAPTER_CHARS);
+
+    assert!(chapters.len() > 1);
+    assert!(chapters.iter().all(|c| c.chars().count() <= CHAPTER_CHARS));
+    assert_eq!(chapters.join("\n\n"), story);
+    assert_eq!(chunk_chapters("Short.", CHAPTER_CHARS), ["Short."]);
+}
+
+#[tokio::test]
+async fn large_job_requires_matching_confirmation() {
+    let story = long_story(30);
+
+    match translate(story.clone(), None).await {
+        Err(ApiError::ConfirmationRequired { segments }) => assert_eq!(segments, 30 * 19),
+        other => panic!("expected ConfirmationRequired, got {:?}", other.map(|r| r.job.id)),
+    }
+
+    let check = preflight(&story, &echo_provider(), &DisplayCurrency::default()).expect("preflight");
+    assert!(check.requires_confirmation);
+    assert_eq!(check.segment_count, 30 * 19);
+
+    let result = translate(story, Some(check.confirmation_token)).await.expect("confirmed job runs");
+    let last = result.job.segments.last().expect("segments");
+    assert_eq!(last.chapter as usize, check.chapter_count - 1);
+}
+
+#[test]
+fn budget_status_tracks_the_tighter_limit() {
+    let usage = Usage {
+        input_tokens: 600_000,
+        output_tokens: 200_000,
+        ..Default::default()
+    };
+    let pricing = ModelPricing {
+        input_per_mtok: 1.0,
+        output_per_mtok: 5.0,
+    };
+    let budget = JobBudget {
+        max_tokens: Some(1_000_000),
+        max_cost_usd: Some(2.0),
+    };
+
+    let status = budget.status("job-1", &usage, Some(&pricing));
+    assert_eq!(status.tokens, 800_000);
+    assert_eq!(status.cost_usd, Some(1.6));
+    assert!((status.fraction - 0.8).abs() < 1e-9);
+    assert!(!status.reached);
+
+    // Without pricing only the token limit counts.
+    let tokens_only = JobBudget {
+        max_tokens: Some(500_000),
+        max_cost_usd: Some(0.01),
+    };
+    let status = tokens_only.status("job-1", &usage, None);
+    assert_eq!(status.cost_usd, None);
+    assert!(status.reached);
+    assert_eq!(JobBudget::default().status("job-1", &usage, Some(&pricing)).fraction, 0.0);
+}
+
+#[test]
+fn job_fingerprint_ignores_the_api_key_but_not_the_options() {
+    let options = serde_json::json!({ "targetLanguage": "fr", "denseSpans": false });
+    let provider = echo_provider();
+    let fingerprint = job_fingerprint("The cat sleeps.", &options, &provider);
+
+    let with_key = LlmProviderConfig {
+        api_key: Some("sk-other".to_string()),
+        ..echo_provider()
+    };
+    assert_eq!(job_fingerprint("  The cat sleeps.\n", &options, &with_key), fingerprint);
+
+    let spanish = serde_json::json!({ "targetLanguage": "es", "denseSpans": false });
+    assert_ne!(job_fingerprint("The cat sleeps.", &spanish, &provider), fingerprint);
+    let other_model = LlmProviderConfig {
+        model: Some("other".to_string()),
+        ..echo_provider()
+    };
+    assert_ne!(job_fingerprint("The cat sleeps.", &options, &other_model), fingerprint);
+    assert_ne!(job_fingerprint("The dog sleeps.", &options, &provider), fingerprint);
+}
@@ -0,0 +1,107 @@
+//! JSON Lines interchange: round trip, strict validation and import.
+
+use boka_core::gui_types::{DocToken, TextDirection, TextStyle};
+use boka_core::jsonl::{from_jsonl, to_jsonl, JsonlError};
+use boka_core::stories::{find_doc, put_doc, DocId};
+
+use serde_json::json;
+
+const HEADER: &str = r#"{"type":"story","format":"boka-jsonl","version":1,"storyId":"s1","title":"Cats","sourceLanguage":"en","sourceText":"The cat sleeps. Hello.","language":"ar"}"#;
+
+fn sample() -> String {
+    [
+        HEADER,
+        r#"{"type":"segment","index":0,"source":"The cat sleeps.","text":"القطة نائمة."}"#,
+        r#"{"type":"segment","index":1,"source":"Hello.","text":"Hello."}"#,
+        r#"{"type":"span","id":"a","sourceText":"The cat sleeps.","activeVariantIndex":0,"variants":[{"id":"a1","register":"neutral","text":"القطة نائمة.","difficulty":2}]}"#,
+        r#"{"type":"text","value":"\n\n"}"#,
+        r#"{"type":"span","id":"b","sourceText":"Hello.","activeVariantIndex":1,"variants":[{"id":"b1","register":"neutral","text":"Hello."},{"id":"b2","register":"casual","text":"Hi!","note":"informal"}],"style":"strong"}"#,
+        r#"{"type":"check","question":"Le chat dort ?","answer":true}"#,
+        "",
+    ]
+    .join("\n")
+}
+
+fn line_error(text: &str) -> (usize, String) {
+    match from_jsonl(text) {
+        Err(JsonlError::Invalid { line, message }) => (line, message),
+        other => panic!("expected a line error, got {:?}", other.map(|s| s.story_id)),
+    }
+}
+
+#[test]
+fn parses_and_round_trips() {
+    let story = from_jsonl(&sample()).unwrap();
+    assert_eq!(story.story_id, "s1");
+    assert_eq!(story.doc.direction, TextDirection::Rtl);
+    // The quoted English block keeps its own direction.
+    assert_eq!(story.doc.block_directions, [TextDirection::Rtl, TextDirection::Ltr]);
+    assert_eq!(story.doc.block_texts(), ["القطة نائمة.", "Hi!"]);
+    let job = story.job.as_ref().unwrap();
+    assert_eq!(job.segments.len(), 2);
+    assert_eq!(job.segments[0].base_text.as_deref(), Some("القطة نائمة."));
+    assert!(matches!(story.doc.tokens.last(), Some(DocToken::Check { answer: true, .. })));
+    assert!(matches!(story.doc.tokens[2], DocToken::Span { style: Some(TextStyle::Strong), .. }));
+
+    let again = from_jsonl(&to_jsonl(&story).unwrap()).unwrap();
+    assert_eq!(to_jsonl(&again).unwrap(), to_jsonl(&story).unwrap());
+}
+
+#[test]
+fn rejects_malformed_lines_with_their_number() {
+    let (line, _) = line_error(&sample().replacen(r#""title":"Cats""#, r#""title":"Cats","extra":1"#, 1));
+    assert_eq!(line, 1);
+
+    let (line, message) = line_error(&sample().replace(r#""version":1"#, r#""version":2"#));
+    assert_eq!(line, 1);
+    assert!(message.contains("unsupported version 2"));
+
+    let (line, message) = line_error(&sample().replace(r#""index":1"#, r#""index":3"#));
+    assert_eq!(line, 3);
+    assert!(message.contains("expected 1"));
+
+    let (line, message) = line_error(&sample().replace(r#""activeVariantIndex":1"#, r#""activeVariantIndex":2"#));
+    assert_eq!(line, 6);
+    assert!(message.contains("out of range"));
+
+    let (line, message) = line_error(&sample().replace("\"casual\"", "\"slang\""));
+    assert_eq!(line, 6);
+    assert!(message.contains("unknown register `slang`"));
+
+    let (line, message) = line_error(&sample().replace(r#""id":"b","#, r#""id":"a","#));
+    assert_eq!(line, 6);
+    assert!(message.contains("duplicate span id"));
+
+    let (line, message) = line_error(&sample().replace("Le chat dort ?", " "));
+    assert_eq!(line, 7);
+    assert!(message.contains("check question"));
+
+    let (line, _) = line_error(&sample().lines().skip(1).collect::<Vec<_>>().join("\n"));
+    assert_eq!(line, 1);
+
+    assert!(matches!(from_jsonl("\n\n"), Err(JsonlError::Empty)));
+    let (_, message) = line_error(HEADER);
+    assert!(message.contains("at least one span"));
+}
+
+#[test]
+fn import_creates_or_replaces_the_translation() {
+    let story = from_jsonl(&sample()).unwrap();
+
+    let mut stories = json!([]);
+    let id = put_doc(&mut stories, &story).unwrap();
+    assert_eq!(id.to_string(), "s1:ar");
+    assert_eq!(stories[0]["title"], "Cats");
+    assert_eq!(stories[0]["sourceLanguage"], "en");
+    let found = find_doc(&stories, &DocId::parse("s1:ar").unwrap()).unwrap();
+    assert_eq!(found.doc.block_texts(), story.doc.block_texts());
+    assert_eq!(found.job.unwrap().segments.len(), 2);
+
+    // An existing story keeps its fields; only the translation is replaced.
+    let mut stories = json!([{ "id": "s1", "title": "Mine", "translations": { "fr": { "doc": null } } }]);
+    put_doc(&mut stories, &story).unwrap();
+    assert_eq!(stories.as_array().unwrap().len(), 1);
+    assert_eq!(stories[0]["title"], "Mine");
+    assert!(stories[0]["translations"]["fr"].is_object());
+    assert!(stories[0]["translations"]["ar"]["doc"]["spans"]["a"].is_object());
+}
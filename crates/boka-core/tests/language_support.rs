@@ -0,0 +1,67 @@
+//! The per-language capability table the GUI filters its options with.
+
+use boka_core::gui_types::TextDirection;
+use boka_core::language_support::{language_support, LanguageSupport};
+use boka_core::translation::SegmentationTier;
+use boka_core::tts_models::TtsModelRegistry;
+
+use std::fs;
+use std::path::PathBuf;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("boka-languages-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn row<'a>(rows: &'a [LanguageSupport], code: &str) -> &'a LanguageSupport {
+    rows.iter().find(|r| r.code == code).unwrap_or_else(|| panic!("no row for {}", code))
+}
+
+#[test]
+fn each_language_reports_what_it_supports() {
+    let rows = language_support(&TtsModelRegistry::bundled(), &temp_dir("bundled"));
+    assert_eq!(rows[0].code, "en");
+
+    let fr = row(&rows, "fr");
+    assert_eq!((fr.name.as_str(), fr.translation, fr.frequency_list), ("French", true, true));
+    assert_eq!(fr.tts_models, ["kokoro-82m", "kokoro-82m-q8"]);
+    assert_eq!(fr.segmentation, SegmentationTier::Full);
+
+    // German falls back to an English voice, so no model claims it.
+    let de = row(&rows, "de");
+    assert!(de.tts_models.is_empty() && de.frequency_list);
+
+    let ja = row(&rows, "ja");
+    assert_eq!(ja.segmentation, SegmentationTier::Poor);
+    assert!(!ja.frequency_list && !ja.romanization);
+
+    let ar = row(&rows, "ar");
+    assert_eq!((ar.direction, ar.segmentation), (TextDirection::Rtl, SegmentationTier::Basic));
+    assert!(rows.iter().all(|r| r.translation));
+}
+
+#[test]
+fn user_lists_and_models_add_languages() {
+    let dir = temp_dir("extra");
+    fs::create_dir_all(dir.join("frequency")).unwrap();
+    fs::write(dir.join("frequency").join("ja.txt"), "の\nに\n").unwrap();
+    fs::write(dir.join("frequency").join("eo.txt"), "la\nkaj\n").unwrap();
+    let registry = TtsModelRegistry::from_json(
+        r#"{ "models": [{ "id": "tiny", "name": "Tiny", "engine": "kokoro", "default": true,
+            "languages": ["en", "qu"], "sizeBytes": 1, "quality": "low" }] }"#,
+    )
+    .unwrap();
+    let rows = language_support(&registry, &dir);
+
+    assert!(row(&rows, "ja").frequency_list);
+    assert_eq!(row(&rows, "en-gb").tts_models, ["tiny"]);
+    let eo = row(&rows, "eo");
+    assert!(eo.frequency_list && !eo.translation && eo.tts_models.is_empty());
+    let qu = row(&rows, "qu");
+    assert_eq!((qu.name.as_str(), qu.translation, qu.tts_models.len()), ("qu", false, 1));
+    // Extras come after the known languages, each once.
+    assert_eq!(rows.iter().filter(|r| r.code == "en").count(), 1);
+    assert_eq!(rows[rows.len() - 2..].iter().map(|r| r.code.as_str()).collect::<Vec<_>>(), ["eo", "qu"]);
+    fs::remove_dir_all(&dir).unwrap();
+}
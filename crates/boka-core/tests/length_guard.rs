@@ -0,0 +1,41 @@
+//! The base-translation length guard: preambles stripped, padded replies
+//! caught, short ones left alone.
+
+use boka_core::length_guard::{strip_preamble, LengthGuard, MIN_GUARDED_CHARS};
+
+#[test]
+fn known_preambles_are_stripped() {
+    for reply in [
+        "Here is the translation: Le chat dort.",
+        "Sure! Here's the French translation:\n\nLe chat dort.",
+        "Translation:\nLe chat dort.",
+        "**French translation:** Le chat dort.",
+    ] {
+        assert_eq!(strip_preamble(reply), "Le chat dort.", "{}", reply);
+    }
+    for reply in [
+        "Il dit : « Voici la traduction. »",
+        "Here is the plan: we leave at dawn.",
+        "Translation notes follow below, then the text itself, which is long enough to not be a label:",
+    ] {
+        assert_eq!(strip_preamble(reply), reply);
+    }
+}
+
+#[test]
+fn long_replies_trip_the_guard() {
+    let guard = LengthGuard::default();
+    let source = "The cat sleeps in the sun.";
+    assert!(!guard.trips(source, "Le chat dort au soleil."));
+    let padded = "Le chat dort au soleil. (Note: « dort » is the present tense, which suits a cat asleep right now.)";
+    assert!(guard.trips(source, padded));
+    assert!(!LengthGuard { enabled: false, ..guard }.trips(source, padded));
+
+    // A short reply is never padded enough to tell.
+    let short = "x".repeat(MIN_GUARDED_CHARS - 1);
+    assert!(!guard.trips("Yes.", &short));
+
+    assert!(LengthGuard::new(true, 1.0).is_err());
+    assert!(LengthGuard::new(true, f64::NAN).is_err());
+    assert_eq!(LengthGuard::new(false, 3.0).unwrap().max_ratio, 3.0);
+}
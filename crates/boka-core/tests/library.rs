@@ -0,0 +1,218 @@
+//! Tags, collections and library search.
+
+use boka_core::library::{query, Collections, CompletionStatus, LibraryError, LibraryFilter, LibraryItem};
+use boka_core::simplify::CefrLevel;
+use boka_core::stories::{self, StoryError};
+use boka_core::types::LlmProviderPreset;
+
+use serde_json::{json, Value};
+
+fn doc(text: &str) -> Value {
+    json!({
+        "tokens": [{ "type": "span", "spanId": "s1" }],
+        "spans": { "s1": { "id": "s1", "sourceText": "x", "activeVariantIndex": 0,
+                           "variants": [{ "id": "v", "register": "neutral", "text": text }] } }
+    })
+}
+
+fn segment(stage: &str) -> Value {
+    json!({ "id": "seg-0", "source": "x", "baseStage": stage, "spanStage": stage, "variantCount": 1 })
+}
+
+fn library() -> Value {
+    json!([
+        {
+            "id": "cats", "title": "The Lazy Cat", "category": "animals", "updatedAt": 30,
+            "sourceLanguage": "en", "sourceText": "A cat sleeps in the sun all day.",
+            "translations": {
+                "fr": { "language": "fr", "doc": doc("Un chat dort."),
+                        "job": { "id": "j1", "ready": true, "segments": [segment("ready")],
+                                 "metadata": { "simplifyLevel": "A2" } } },
+                "de": { "language": "de", "doc": doc("Eine Katze schläft."),
+                        "job": { "id": "j2", "ready": true, "segments": [segment("error")],
+                                 "metadata": { "simplifyLevel": "B1" } } }
+            }
+        },
+        {
+            "id": "sea", "title": "Ocean Tales", "updatedAt": 50,
+            "sourceLanguage": "en", "sourceText": "The whale sings to the moon.",
+            "translations": {
+                "fr": { "language": "fr", "doc": null, "job": null, "errorMessage": "rate limited" },
+                "es": { "language": "es", "doc": null,
+                        "job": { "id": "j3", "ready": false, "segments": [segment("pending")] } }
+            }
+        }
+    ])
+}
+
+fn ids(stories: &Value, collections: &Collections, filter: LibraryFilter) -> Vec<String> {
+    query(stories, collections, &filter)
+        .unwrap()
+        .into_iter()
+        .map(|e| e.story_id)
+        .collect()
+}
+
+#[test]
+fn tags_are_normalized_and_survive_frontend_writes() {
+    let mut all = library();
+    let tags = ["  Bedtime  Stories", "bedtime stories", "", "Cats"].map(String::from);
+    let stored = stories::set_tags(&mut all, "cats", None, &tags).unwrap();
+    assert_eq!(stored, ["bedtime stories", "cats"]);
+    assert_eq!(all[0]["updatedAt"], 30);
+
+    stories::set_tags(&mut all, "cats", Some("fr"), &["Homework".to_string()]).unwrap();
+    assert_eq!(all[0]["translations"]["fr"]["tags"], json!(["homework"]));
+    assert!(matches!(
+        stories::set_tags(&mut all, "cats", Some("it"), &[]),
+        Err(StoryError::NotFound(_))
+    ));
+
+    // A frontend write from before the tags were set keeps them.
+    let mut incoming = library();
+    incoming[0]["title"] = json!("The Very Lazy Cat");
+    stories::keep_tags(&mut incoming, &all);
+    assert_eq!(incoming[0]["title"], "The Very Lazy Cat");
+    assert_eq!(incoming[0]["tags"], json!(["bedtime stories", "cats"]));
+    assert_eq!(incoming[0]["translations"]["fr"]["tags"], json!(["homework"]));
+    assert!(incoming[1].get("tags").is_none());
+}
+
+#[test]
+fn collections_are_saved_and_validated() {
+    let dir = std::env::temp_dir().join(format!("boka-collections-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let mut collections = Collections::load(&dir).unwrap();
+    assert!(collections.0.is_empty());
+
+    let item = LibraryItem { story_id: "cats".to_string(), language: None };
+    let reading = collections
+        .create(" Week 1 ", vec![item.clone(), item.clone()])
+        .unwrap();
+    assert_eq!(reading.name, "Week 1");
+    assert_eq!(reading.items, [item]);
+    assert!(matches!(collections.create("week 1", vec![]), Err(LibraryError::Invalid(_))));
+    assert!(matches!(collections.create("  ", vec![]), Err(LibraryError::Invalid(_))));
+    let other = collections.create("Week 2", vec![]).unwrap();
+    assert_ne!(other.id, reading.id);
+    collections.save(&dir).unwrap();
+
+    let mut loaded = Collections::load(&dir).unwrap();
+    assert_eq!(loaded.0, collections.0);
+    let renamed = loaded.update(&reading.id, Some("Week One"), None).unwrap();
+    assert_eq!((renamed.name.as_str(), renamed.items.len()), ("Week One", 1));
+    assert!(matches!(loaded.update(&other.id, Some("WEEK ONE"), None), Err(LibraryError::Invalid(_))));
+    loaded.delete(&reading.id).unwrap();
+    assert!(matches!(loaded.delete(&reading.id), Err(LibraryError::NotFound(_))));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn text_search_covers_titles_and_source_text() {
+    let all = library();
+    let none = Collections::default();
+    let search = |text: &str| LibraryFilter { text: Some(text.to_string()), ..Default::default() };
+
+    assert_eq!(ids(&all, &none, LibraryFilter::default()), ["sea", "cats"]);
+    assert_eq!(ids(&all, &none, search("lazy")), ["cats"]);
+    assert_eq!(ids(&all, &none, search("WHALE moon")), ["sea"]);
+    assert!(ids(&all, &none, search("whale sun")).is_empty());
+}
+
+#[test]
+fn docs_filter_by_language_level_and_status() {
+    let all = library();
+    let none = Collections::default();
+
+    let entries = query(&all, &none, &LibraryFilter::default()).unwrap();
+    let cats = entries.iter().find(|e| e.story_id == "cats").unwrap();
+    let de = cats.docs.iter().find(|d| d.language == "de").unwrap();
+    assert_eq!((de.status, de.level, de.graded), (CompletionStatus::Partial, Some(CefrLevel::B1), true));
+    let sea = entries.iter().find(|e| e.story_id == "sea").unwrap();
+    let statuses: Vec<_> = sea.docs.iter().map(|d| (d.language.as_str(), d.status)).collect();
+    assert!(statuses.contains(&("fr", CompletionStatus::Failed)));
+    assert!(statuses.contains(&("es", CompletionStatus::Translating)));
+
+    let french = query(&all, &none, &LibraryFilter { language: Some("fr".to_string()), ..Default::default() }).unwrap();
+    assert_eq!(french.len(), 2);
+    assert!(french.iter().all(|e| e.docs.len() == 1 && e.docs[0].language == "fr"));
+
+    let a2 = LibraryFilter { level: Some(CefrLevel::A2), ..Default::default() };
+    assert_eq!(ids(&all, &none, a2), ["cats"]);
+    let done = LibraryFilter { status: Some(CompletionStatus::Complete), ..Default::default() };
+    assert_eq!(ids(&all, &none, done), ["cats"]);
+    let failed_de = LibraryFilter {
+        language: Some("de".to_string()),
+        status: Some(CompletionStatus::Failed),
+        ..Default::default()
+    };
+    assert!(ids(&all, &none, failed_de).is_empty());
+}
+
+#[test]
+fn tags_and_collections_match_stories_or_translations() {
+    let mut all = library();
+    stories::set_tags(&mut all, "cats", None, &["animals".to_string()]).unwrap();
+    stories::set_tags(&mut all, "sea", Some("es"), &["animals".to_string(), "class 3b".to_string()]).unwrap();
+    let tagged = |tags: &[&str]| LibraryFilter {
+        tags: tags.iter().map(|t| t.to_string()).collect(),
+        ..Default::default()
+    };
+
+    assert_eq!(ids(&all, &Collections::default(), tagged(&["Animals"])), ["sea", "cats"]);
+    let sea = &query(&all, &Collections::default(), &tagged(&["class 3b"])).unwrap()[0];
+    assert_eq!((sea.story_id.as_str(), sea.docs.len()), ("sea", 1));
+    assert_eq!(sea.docs[0].language, "es");
+
+    let mut collections = Collections::default();
+    let week = collections
+        .create(
+            "Week 1",
+            vec![LibraryItem { story_id: "sea".to_string(), language: Some("fr".to_string()) }],
+        )
+        .unwrap();
+    let in_week = LibraryFilter { collection: Some(week.id.clone()), ..Default::default() };
+    let entries = query(&all, &collections, &in_week).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].docs.len(), 1);
+    assert_eq!(entries[0].docs[0].collections, [week.id]);
+
+    let missing = LibraryFilter { collection: Some("col-0".to_string()), ..Default::default() };
+    assert!(matches!(query(&all, &collections, &missing), Err(LibraryError::NotFound(_))));
+}
+
+#[test]
+fn translations_can_be_found_by_the_models_that_made_them() {
+    let call = |provider: &str, model: &str| {
+        json!({ "provider": provider, "model": model, "attempt": 1, "durationMs": 5 })
+    };
+    let mut all = library();
+    all[0]["translations"]["fr"]["job"]["metadata"] = json!({ "provider": "anthropic", "model": "claude-sonnet-4" });
+    all[0]["translations"]["de"]["job"]["segments"][0]["provenance"] = json!([call("openai", "gpt-4o")]);
+    all[0]["translations"]["de"]["doc"]["spans"]["s1"]["provenance"] =
+        json!([call("openrouter", "anthropic/claude-3.5-haiku")]);
+    let none = Collections::default();
+
+    let entries = query(&all, &none, &LibraryFilter::default()).unwrap();
+    let cats = entries.iter().find(|e| e.story_id == "cats").unwrap();
+    let de = cats.docs.iter().find(|d| d.language == "de").unwrap();
+    let models: Vec<&str> = de.models.iter().map(|m| m.model.as_str()).collect();
+    assert_eq!(models, ["gpt-4o", "anthropic/claude-3.5-haiku"]);
+
+    let claude = LibraryFilter { model: Some("Claude".to_string()), ..Default::default() };
+    let found = query(&all, &none, &claude).unwrap();
+    assert_eq!(found.len(), 1);
+    let languages: Vec<&str> = found[0].docs.iter().map(|d| d.language.as_str()).collect();
+    assert_eq!(languages, ["de", "fr"]);
+
+    let on_anthropic = LibraryFilter {
+        provider: Some(LlmProviderPreset::Anthropic),
+        model: Some("claude".to_string()),
+        ..Default::default()
+    };
+    let found = query(&all, &none, &on_anthropic).unwrap();
+    assert_eq!(found[0].docs.len(), 1);
+    assert_eq!(found[0].docs[0].language, "fr");
+    let ollama = LibraryFilter { provider: Some(LlmProviderPreset::Ollama), ..Default::default() };
+    assert!(ids(&all, &none, ollama).is_empty());
+}
@@ -0,0 +1,85 @@
+//! Doc lints: each check on its own, located by block and span, and kept on
+//! the job that made the doc.
+
+use boka_core::gui_types::InteractiveDoc;
+use boka_core::lint::{lint_doc, DocLint, MAX_NOTE_CHARS};
+
+use serde_json::{json, Value};
+
+fn variant(register: &str, text: &str) -> Value {
+    json!({ "id": format!("{register}-{text}"), "register": register, "text": text })
+}
+
+fn doc(tokens: Value, spans: Value) -> InteractiveDoc {
+    serde_json::from_value(json!({ "tokens": tokens, "spans": spans })).unwrap()
+}
+
+fn span(id: &str, variants: Vec<Value>) -> Value {
+    json!({ "id": id, "sourceText": "The cat.", "activeVariantIndex": 0, "variants": variants })
+}
+
+#[test]
+fn a_clean_doc_has_no_lints() {
+    let doc = doc(
+        json!([{ "type": "span", "spanId": "span-1" }, { "type": "text", "value": " « Miaou ! »" }]),
+        json!({ "span-1": span("span-1", vec![variant("neutral", "Le chat."), variant("casual", "Le minou.")]) }),
+    );
+    assert!(lint_doc(&doc).is_empty());
+}
+
+#[test]
+fn each_problem_is_located_by_block_and_span() {
+    let mut noted = variant("neutral", "Le chat.");
+    noted["note"] = json!("x".repeat(MAX_NOTE_CHARS + 1));
+    let doc = doc(
+        json!([
+            { "type": "span", "spanId": "span-1" },
+            { "type": "text", "value": "\n\n" },
+            { "type": "text", "value": "« " },
+            { "type": "span", "spanId": "span-2" },
+            { "type": "span", "spanId": "span-3" },
+            { "type": "span", "spanId": "span-4" }
+        ]),
+        json!({
+            "span-1": span("span-1", vec![noted]),
+            "span-2": span("span-2", vec![variant("neutral", "  ")]),
+            "span-3": span("span-3", vec![
+                variant("casual", "Le minou."), variant("formal", "Le minou. "), variant("literary", "Le félin.")
+            ]),
+            "span-4": span("span-4", vec![variant("neutral", "„Il dort“, dit-il. “Non.")])
+        }),
+    );
+
+    let lints = lint_doc(&doc);
+    assert_eq!(
+        lints,
+        [
+            DocLint::SingleVariant { block: 0, span_id: "span-1".to_string() },
+            DocLint::LongNote {
+                block: 0,
+                span_id: "span-1".to_string(),
+                variant: 0,
+                chars: MAX_NOTE_CHARS + 1,
+                limit: MAX_NOTE_CHARS
+            },
+            DocLint::EmptySpan { block: 1, span_id: "span-2".to_string() },
+            DocLint::DuplicateVariants {
+                block: 1,
+                span_id: "span-3".to_string(),
+                text: "Le minou.".to_string(),
+                variants: vec![0, 1]
+            },
+            DocLint::MissingNeutral { block: 1, span_id: "span-3".to_string() },
+            DocLint::SingleVariant { block: 1, span_id: "span-4".to_string() },
+            DocLint::UnbalancedQuotes { block: 1, quote: "«»".to_string() },
+            DocLint::UnbalancedQuotes { block: 1, quote: "“”".to_string() },
+        ]
+    );
+    assert!(lints.iter().all(|l| !l.message().is_empty()));
+
+    let json = serde_json::to_value(&lints[3]).unwrap();
+    assert_eq!(
+        json,
+        json!({ "kind": "duplicateVariants", "block": 1, "spanId": "span-3", "text": "Le minou.", "variants": [0, 1] })
+    );
+}
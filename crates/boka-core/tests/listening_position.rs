@@ -0,0 +1,86 @@
+//! Listening positions saved with a doc.
+
+use boka_core::stories::{self, DocId, StoryError};
+
+use serde_json::json;
+
+fn library() -> serde_json::Value {
+    json!([{
+        "id": "story-1",
+        "title": "Cats",
+        "updatedAt": 5,
+        "translations": { "fr": { "language": "fr", "doc": {
+            "tokens": [
+                { "type": "span", "spanId": "s1" },
+                { "type": "text", "value": "\n\n" },
+                { "type": "span", "spanId": "s2" }
+            ],
+            "spans": {
+                "s1": { "id": "s1", "sourceText": "a", "activeVariantIndex": 0,
+                        "variants": [{ "id": "a", "register": "neutral", "text": "Un." }] },
+                "s2": { "id": "s2", "sourceText": "b", "activeVariantIndex": 0,
+                        "variants": [{ "id": "b", "register": "neutral", "text": "Deux." }] }
+            }
+        } } }
+    }])
+}
+
+#[test]
+fn positions_are_saved_with_the_doc() {
+    let id = DocId::parse("story-1:fr").unwrap();
+    let mut all = library();
+    assert_eq!(stories::listening_position(&all, &id).unwrap(), None);
+
+    let saved = stories::set_listening_position(&mut all, &id, 1, 4_250).unwrap();
+    assert_eq!((saved.block_id, saved.ms), (1, 4_250));
+    assert_eq!(stories::listening_position(&all, &id).unwrap(), Some(saved));
+    assert_eq!(all[0]["translations"]["fr"]["listeningPosition"]["blockId"], 1);
+    assert_eq!(all[0]["updatedAt"], 5);
+
+    assert!(matches!(
+        stories::set_listening_position(&mut all, &id, 2, 0),
+        Err(StoryError::BlockOutOfRange { blocks: 2, .. })
+    ));
+    assert!(matches!(
+        stories::listening_position(&all, &DocId::parse("story-1:de").unwrap()),
+        Err(StoryError::NotFound(_))
+    ));
+}
+
+#[test]
+fn stale_writes_keep_newer_positions() {
+    let id = DocId::parse("story-1:fr").unwrap();
+    let mut saved = library();
+    stories::set_listening_position(&mut saved, &id, 1, 900).unwrap();
+
+    // The frontend writes back the copy it read before the position existed.
+    let mut incoming = library();
+    incoming[0]["title"] = json!("Renamed");
+    stories::keep_listening_positions(&mut incoming, &saved);
+    assert_eq!(incoming[0]["title"], "Renamed");
+    assert_eq!(incoming[0]["translations"]["fr"]["listeningPosition"]["ms"], 900);
+
+    // A newer position in the incoming copy wins.
+    incoming[0]["translations"]["fr"]["listeningPosition"]["ms"] = json!(1_200);
+    incoming[0]["translations"]["fr"]["listeningPosition"]["updatedAt"] = json!(u64::MAX);
+    stories::keep_listening_positions(&mut incoming, &saved);
+    assert_eq!(incoming[0]["translations"]["fr"]["listeningPosition"]["ms"], 1_200);
+}
+
+#[test]
+fn retried_jobs_are_saved_in_place() {
+    let id = DocId::parse("story-1:fr").unwrap();
+    let mut all = library();
+    all[0]["translations"]["fr"]["job"] = json!({ "id": "job-7", "segments": [], "ready": true });
+    stories::set_listening_position(&mut all, &id, 1, 300).unwrap();
+
+    let found = stories::find_job_doc(&all, "job-7").unwrap();
+    assert_eq!((found.story_id.as_str(), found.language.as_str()), ("story-1", "fr"));
+    assert!(matches!(stories::find_job_doc(&all, "job-8"), Err(StoryError::NotFound(_))));
+
+    let mut job = found.job.unwrap();
+    job.ready = false;
+    stories::update_translation(&mut all, &id, &job, &found.doc).unwrap();
+    assert_eq!(all[0]["translations"]["fr"]["job"]["ready"], false);
+    assert_eq!(all[0]["translations"]["fr"]["listeningPosition"]["ms"], 300);
+}
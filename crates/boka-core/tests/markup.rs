@@ -0,0 +1,100 @@
+//! Emoji and Markdown in source text: placeholders on the way to the model,
+//! token styles in the doc.
+
+use boka_core::gui_types::{DocToken, ErrorPolicy, Granularity, InteractiveDoc, TextStyle, TranslationJob};
+use boka_core::length_guard::LengthGuard;
+use boka_core::markup::protect;
+use boka_core::settings::VariantBounds;
+use boka_core::translation::{run_translation, TranslationArgs};
+use boka_core::types::{LlmProviderConfig, LlmProviderPreset};
+
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+#[test]
+fn emoji_code_and_links_become_placeholders() {
+    let protected = protect("Hi 👋🏽 *you*, run `ls -l` or see [the **docs**](https://x.io/a_b_c) 🇫🇷!");
+    assert_eq!(
+        protected.text,
+        "Hi ⟦1⟧ <em>you</em>, run ⟦2⟧ or see [the <strong>docs</strong>](⟦3⟧) ⟦4⟧!"
+    );
+    assert_eq!(
+        protected.restore("Salut ⟦1⟧ <em>toi</em>, lance ⟦2⟧ ou vois [la <strong> doc</strong>](⟦3⟧) ⟦4⟧ !"),
+        "Salut 👋🏽 *toi*, lance `ls -l` ou vois [la **doc**](https://x.io/a_b_c) 🇫🇷 !"
+    );
+    // Dropped items go at the end; made-up placeholders go away.
+    assert_eq!(protected.restore("Salut ⟦9⟧ toi."), "Salut  toi. 👋🏽 `ls -l` https://x.io/a_b_c 🇫🇷");
+}
+
+#[test]
+fn stray_markers_and_plain_text_are_left_alone() {
+    for text in ["2 * 3 = 6", "snake_case_name", "***loud***", "Café, 10 €, #1"] {
+        let protected = protect(text);
+        assert_eq!(protected.text, text);
+        assert_eq!(protected.restore(text), text);
+    }
+}
+
+#[tokio::test]
+async fn emphasis_becomes_token_styles() {
+    let result = run_translation(TranslationArgs {
+        story_text: "The *big* dog 🐶 barks.".to_string(),
+        job_id: "job-markup".to_string(),
+        target_language: "fr".to_string(),
+        source_language: None,
+        adult_mode: false,
+        content_policy: None,
+        dense_spans: false,
+        reproducible: false,
+        prompt_overrides: Default::default(),
+        judge: None,
+        variant_bounds: VariantBounds::default(),
+        length_guard: LengthGuard::default(),
+        simplify_level: None,
+        dual_output: false,
+        refine_variants: false,
+        granularity: Granularity::Sentence,
+        comprehension_every: None,
+        annotations: Default::default(),
+        review: None,
+        error_policy: ErrorPolicy::Abort,
+        budget: None,
+        budget_gate: None,
+        confirmation: None,
+        translation_cache: None,
+        transcript: None,
+        ui_language: None,
+        provider: LlmProviderConfig {
+            preset: LlmProviderPreset::Mock,
+            api_key: None,
+            base_url: Some(format!("{}/tests/fixtures/markup.json", env!("CARGO_MANIFEST_DIR"))),
+            model: None,
+            reasoning_model: false,
+        },
+        cancelled: Arc::new(AtomicBool::new(false)),
+        on_job: Box::new(|_: &TranslationJob| async {}),
+        on_doc: Box::new(|_: &InteractiveDoc| async {}),
+    })
+    .await
+    .expect("translation should succeed");
+
+    assert_eq!(result.job.segments[0].base_text.as_deref(), Some("Le *gros* chien aboie 🐶."));
+    let doc = &result.doc;
+    assert_eq!(doc.block_texts(), ["Le gros chien aboie 🐶."]);
+    let styles: Vec<_> = doc
+        .tokens
+        .iter()
+        .map(|t| match t {
+            DocToken::Text { style, .. } | DocToken::Span { style, .. } => *style,
+            DocToken::Check { .. } => None,
+        })
+        .collect();
+    assert_eq!(styles, [None, Some(TextStyle::Emphasis), None]);
+    let span = doc.spans.values().next().unwrap();
+    let texts: Vec<_> = span.variants.iter().map(|v| v.text.as_str()).collect();
+    assert_eq!(texts, ["gros", "grand"]);
+    assert_eq!(
+        serde_json::to_value(&doc.tokens[1]).unwrap(),
+        serde_json::json!({ "type": "span", "spanId": span.id, "style": "emphasis" })
+    );
+}
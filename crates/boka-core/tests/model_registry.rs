@@ -0,0 +1,64 @@
+//! The bundled model registry (`data/models.json`): capabilities by exact
+//! or prefix match, the fallback for unknown models, and each preset's
+//! default model.
+
+use boka_core::types::{LlmProviderPreset, ModelRegistry};
+
+#[test]
+fn capabilities_match_exact_then_longest_prefix() {
+    let registry = ModelRegistry::bundled();
+
+    let sonnet = registry.capabilities(LlmProviderPreset::Anthropic, "claude-sonnet-4-20250514");
+    assert_eq!((sonnet.max_output, sonnet.vision, sonnet.json_mode), (64000, true, false));
+    assert_eq!(registry.capabilities(LlmProviderPreset::Anthropic, "claude-3-5-haiku").max_output, 8192);
+    assert_eq!(registry.capabilities(LlmProviderPreset::Ollama, "qwen2.5").context_window, 32768);
+
+    // Dated ids take the longest entry they start with.
+    let haiku = registry.lookup(LlmProviderPreset::Anthropic, "claude-3-5-haiku-20241022").unwrap();
+    assert_eq!(haiku.model, "claude-3-5-haiku");
+    assert_eq!(registry.lookup(LlmProviderPreset::Openai, "gpt-4o-2024-08-06").unwrap().model, "gpt-4o");
+    assert_eq!(registry.lookup(LlmProviderPreset::Openai, "gpt-4o-mini-2024-07-18").unwrap().model, "gpt-4o-mini");
+    assert_eq!(registry.lookup(LlmProviderPreset::Anthropic, "claude-opus-4-1").unwrap().model, "claude-");
+    assert_eq!(registry.capabilities(LlmProviderPreset::Openai, "gpt-4.1-2025-04-14").context_window, 1_000_000);
+
+    // Another preset's entry beats the fallback.
+    let custom = registry.lookup(LlmProviderPreset::Custom, "gpt-4o").unwrap();
+    assert_eq!((custom.preset, custom.model.as_str()), (LlmProviderPreset::Openai, "gpt-4o"));
+}
+
+#[test]
+fn unknown_models_get_the_fallback() {
+    let registry = ModelRegistry::bundled();
+    let unknown = [
+        (LlmProviderPreset::Ollama, "mistral-nemo"),
+        (LlmProviderPreset::Lmstudio, "phi-3"),
+        (LlmProviderPreset::Custom, "my-model"),
+        (LlmProviderPreset::Openai, "gpt"),
+    ];
+    for (preset, model) in unknown {
+        assert!(registry.lookup(preset, model).is_none(), "{model}");
+        assert_eq!(registry.capabilities(preset, model), registry.fallback);
+        assert_eq!(registry.pricing(preset, model), None);
+    }
+    assert_eq!(registry.fallback.max_tokens(4096), 2048);
+}
+
+#[test]
+fn each_preset_has_its_default() {
+    let registry = ModelRegistry::bundled();
+    let defaults = [
+        (LlmProviderPreset::Anthropic, Some("claude-sonnet-4-20250514")),
+        (LlmProviderPreset::Openai, Some("gpt-4o-mini")),
+        (LlmProviderPreset::Openrouter, Some("openai/gpt-4o-mini")),
+        (LlmProviderPreset::Ollama, Some("llama3.1")),
+        (LlmProviderPreset::Lmstudio, Some("qwen2.5-7b-instruct")),
+        (LlmProviderPreset::Custom, None),
+        (LlmProviderPreset::Mock, Some("mock")),
+    ];
+    for (preset, model) in defaults {
+        assert_eq!(registry.default_model(preset), model, "{preset:?}");
+        if let Some(model) = model {
+            assert_eq!(registry.lookup(preset, model).unwrap().preset, preset);
+        }
+    }
+}
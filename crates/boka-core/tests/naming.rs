@@ -0,0 +1,77 @@
+//! Story titles and slugs: derived when a story has none, unique in the
+//! library, kept across frontend writes and changed only by a rename.
+
+use boka_core::naming::{heuristic_title, keep_slugs, name_stories, rename_story, slugify, StoryName};
+use boka_core::stories::{find_doc, DocId, StoryError};
+
+use serde_json::{json, Value};
+
+fn story(id: &str, title: &str, source: &str) -> Value {
+    json!({ "id": id, "title": title, "sourceText": source, "sourceLanguage": "en", "translations": {} })
+}
+
+#[test]
+fn titles_and_slugs_are_derived_once_and_kept_unique() {
+    assert_eq!(heuristic_title("# The Cat.\n\nIt sleeps."), "The Cat");
+    assert_eq!(
+        heuristic_title("“Once upon a time there lived a very old fox,” she said."),
+        "Once upon a time there lived a very…"
+    );
+    assert_eq!(heuristic_title("  ...  "), "Untitled");
+    assert_eq!(slugify("  Ça va, Émile?! "), "ça-va-émile");
+    assert_eq!(slugify("CON"), "con-story");
+    assert_eq!(slugify("?!"), "story");
+    assert_eq!(slugify(&"a".repeat(100)).len(), 60);
+
+    let mut stories = json!([
+        story("story-1", "", "The cat sleeps. The dog barks."),
+        story("story-2", "The cat sleeps", "Other text."),
+        story("story-3", "Fox", ""),
+    ]);
+    stories[2]["translations"]["fr"] = json!({ "meta": { "title": "Le renard" } });
+    stories[2]["title"] = json!("");
+    assert_eq!(name_stories(&mut stories), 3);
+    let names: Vec<(&str, &str)> = stories
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| (s["title"].as_str().unwrap(), s["slug"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        names,
+        [("The cat sleeps", "the-cat-sleeps"), ("The cat sleeps", "the-cat-sleeps-2"), ("Le renard", "le-renard")]
+    );
+    assert_eq!(name_stories(&mut stories), 0);
+
+    // The frontend writes stories without slugs; the saved ones stay.
+    let saved = stories.clone();
+    let mut written = json!([story("story-2", "The cat sleeps", "Other text.")]);
+    keep_slugs(&mut written, &saved);
+    name_stories(&mut written);
+    assert_eq!(written[0]["slug"], "the-cat-sleeps-2");
+}
+
+#[test]
+fn renaming_changes_the_slug_and_exports_follow_it() {
+    let mut stories = json!([story("story-1", "Fox", "A fox."), story("story-2", "Wolf", "A wolf.")]);
+    stories[1]["translations"]["fr"] = json!({ "language": "fr", "doc": { "tokens": [], "spans": {} } });
+    name_stories(&mut stories);
+
+    let name = rename_story(&mut stories, "story-2", Some(" Fox ")).unwrap();
+    assert_eq!(
+        name,
+        StoryName { story_id: "story-2".to_string(), title: "Fox".to_string(), slug: "fox-2".to_string() }
+    );
+    let doc_id = DocId::parse("story-2:fr").unwrap();
+    let doc = find_doc(&stories, &doc_id).unwrap();
+    assert_eq!(doc.slug, "fox-2");
+    let path = boka_core::export::default_path(std::path::Path::new("/data"), &doc, "html");
+    assert!(path.ends_with("exports/fox-2-fr.html"));
+
+    // A blank title is derived again, and the story keeps its own slug.
+    let name = rename_story(&mut stories, "story-2", Some("  ")).unwrap();
+    assert_eq!((name.title.as_str(), name.slug.as_str()), ("A wolf", "a-wolf"));
+    let name = rename_story(&mut stories, "story-1", None).unwrap();
+    assert_eq!(name.slug, "a-fox");
+    assert!(matches!(rename_story(&mut stories, "story-9", None), Err(StoryError::NotFound(_))));
+}
@@ -0,0 +1,88 @@
+//! Learner notes in the learner's language: asked for in the variant
+//! prompts, and translated on saved docs.
+
+use boka_core::gui_types::InteractiveDoc;
+use boka_core::notes::translate_notes;
+use boka_core::policy::ContentPolicy;
+use boka_core::prompts::{span_variants_system_prompt, PromptSet};
+use boka_core::settings::Settings;
+use boka_core::types::{ApiConfig, ApiError, LlmProviderConfig, LlmProviderPreset};
+
+use serde_json::json;
+
+fn mock_provider(fixture: &str) -> LlmProviderConfig {
+    LlmProviderConfig {
+        preset: LlmProviderPreset::Mock,
+        api_key: None,
+        base_url: Some(format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), fixture)),
+        model: None,
+        reasoning_model: false,
+    }
+}
+
+/// Three variants, two of them with the same note, and one without a note.
+fn doc() -> InteractiveDoc {
+    serde_json::from_value(json!({
+        "tokens": [{ "type": "span", "spanId": "s1" }, { "type": "span", "spanId": "s2" }],
+        "spans": {
+            "s1": { "id": "s1", "sourceText": "A cat sleeps.", "activeVariantIndex": 0, "variants": [
+                { "id": "a", "register": "neutral", "text": "Un chat dort.", "note": "Literally \"a cat sleeps\"." },
+                { "id": "b", "register": "casual", "text": "Un chat roupille.", "note": "Colloquial." }
+            ] },
+            "s2": { "id": "s2", "sourceText": "A cat sleeps.", "activeVariantIndex": 0, "variants": [
+                { "id": "c", "register": "neutral", "text": "Un chat dort.", "note": "Literally \"a cat sleeps\"." },
+                { "id": "d", "register": "formal", "text": "Un chat sommeille." }
+            ] }
+        }
+    }))
+    .unwrap()
+}
+
+fn notes(doc: &InteractiveDoc, span: &str) -> Vec<Option<String>> {
+    doc.spans[span].variants.iter().map(|v| v.note.clone()).collect()
+}
+
+#[tokio::test]
+async fn each_distinct_note_is_translated_once() {
+    let mut doc = doc();
+    let changed = translate_notes(&mut doc, "fr", "de", mock_provider("note_translation.json")).await.unwrap();
+    assert_eq!(changed, 3);
+    let literally = Some("Wörtlich: „eine Katze schläft“.".to_string());
+    assert_eq!(notes(&doc, "s1"), [literally.clone(), Some("Umgangssprachlich.".to_string())]);
+    assert_eq!(notes(&doc, "s2"), [literally, None]);
+}
+
+#[tokio::test]
+async fn a_reply_that_loses_notes_changes_nothing() {
+    let mut doc = doc();
+    let err = translate_notes(&mut doc, "fr", "de", mock_provider("note_translation_short.json")).await.unwrap_err();
+    assert!(matches!(err, ApiError::Parse(ref m) if m.contains("expected 2 notes, got 1")), "{err}");
+    assert_eq!(notes(&doc, "s1"), notes(&self::doc(), "s1"));
+}
+
+#[test]
+fn variant_prompts_ask_for_notes_in_the_ui_language() {
+    let policy = ContentPolicy::default();
+    assert!(span_variants_system_prompt("fr", None, &policy, None, 3).contains("English learner note"));
+    assert!(span_variants_system_prompt("fr", None, &policy, Some("de"), 3).contains("German learner note"));
+
+    let cfg = ApiConfig {
+        target_language: "fr".to_string(),
+        ui_language: Some("es".to_string()),
+        refine_variants: true,
+        ..Default::default()
+    };
+    let prompts = PromptSet::for_config(&cfg, false);
+    assert!(prompts.span_variants.contains("Spanish learner note"));
+    assert!(prompts.variant_critique.unwrap().contains("Spanish learner note"));
+}
+
+#[test]
+fn the_ui_language_setting_takes_language_codes() {
+    let mut settings = Settings::default();
+    settings.set_ui_language(Some(" pt-BR ")).unwrap();
+    assert_eq!(settings.view().ui_language.as_deref(), Some("pt-br"));
+    assert!(settings.set_ui_language(Some("German, please")).is_err());
+    settings.set_ui_language(Some("")).unwrap();
+    assert_eq!(settings.ui_language, None);
+}
@@ -0,0 +1,69 @@
+//! Per-OS path resolution, driven by a fake environment.
+
+use boka_core::paths::{BokaPaths, PathError, Platform};
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+fn resolve(platform: Platform, vars: &[(&str, &str)]) -> Result<BokaPaths, PathError> {
+    let env: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    BokaPaths::resolve(platform, |key| env.get(key).cloned())
+}
+
+#[test]
+fn macos_keeps_shared_data_dir() {
+    let paths = resolve(Platform::Macos, &[("HOME", "/Users/ana")]).unwrap();
+    assert_eq!(paths.data_dir, PathBuf::from("/Users/ana/Library/Application Support/boka"));
+    assert_eq!(paths.audio_cache_dir, PathBuf::from("/Users/ana/Library/Caches/boka/audio_cache"));
+    assert_eq!(paths.model_dir, PathBuf::from("/Users/ana/Library/Application Support/boka/models"));
+    assert_eq!(paths.hf_cache_dir, PathBuf::from("/Users/ana/.cache/huggingface/hub"));
+}
+
+#[test]
+fn linux_follows_xdg_and_ignores_relative_values() {
+    let paths = resolve(
+        Platform::Linux,
+        &[("HOME", "/home/ana"), ("XDG_DATA_HOME", "/data"), ("XDG_CACHE_HOME", "relative/cache")],
+    )
+    .unwrap();
+    assert_eq!(paths.data_dir, PathBuf::from("/data/boka"));
+    assert_eq!(paths.cache_dir, PathBuf::from("/home/ana/.cache/boka"));
+    assert_eq!(paths.hf_cache_dir, PathBuf::from("/home/ana/.cache/huggingface/hub"));
+}
+
+#[test]
+fn windows_uses_appdata_and_local_appdata() {
+    let paths = resolve(
+        Platform::Windows,
+        &[
+            ("USERPROFILE", r"C:\Users\ana"),
+            ("APPDATA", r"C:\Users\ana\AppData\Roaming"),
+            ("LOCALAPPDATA", r"C:\Users\ana\AppData\Local"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(paths.data_dir, PathBuf::from(r"C:\Users\ana\AppData\Roaming").join("boka"));
+    assert_eq!(
+        paths.audio_cache_dir,
+        PathBuf::from(r"C:\Users\ana\AppData\Local").join("boka").join("cache").join("audio_cache")
+    );
+    assert_eq!(
+        paths.hf_cache_dir,
+        PathBuf::from(r"C:\Users\ana").join(".cache").join("huggingface").join("hub")
+    );
+}
+
+#[test]
+fn hf_cache_env_overrides_apply_in_order() {
+    let base = [("HOME", "/home/ana"), ("HF_HOME", "/hf")];
+    assert_eq!(resolve(Platform::Linux, &base).unwrap().hf_cache_dir, PathBuf::from("/hf/hub"));
+
+    let explicit = [("HOME", "/home/ana"), ("HF_HOME", "/hf"), ("HF_HUB_CACHE", "/hub")];
+    assert_eq!(resolve(Platform::Linux, &explicit).unwrap().hf_cache_dir, PathBuf::from("/hub"));
+}
+
+#[test]
+fn missing_home_is_an_error() {
+    assert!(matches!(resolve(Platform::Linux, &[("HOME", "")]), Err(PathError::Unresolved("home"))));
+    assert!(resolve(Platform::Windows, &[("HOMEDRIVE", "D:"), ("HOMEPATH", r"\ana")]).is_ok());
+}
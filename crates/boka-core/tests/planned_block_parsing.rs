@@ -0,0 +1,166 @@
+//! Span-plan replies as models actually send them: valid plans mangled with
+//! trailing commas, code fences, prose around the JSON, truncation and
+//! single quotes. The parser must either recover the plan it was given or
+//! fail with a `JSON parse` error the pipeline can retry on.
+
+use boka_core::anthropic::{PlannedBlock, PlannedSegment};
+use boka_core::openai_compat::parse_planned_blocks;
+use boka_core::types::ApiError;
+
+use proptest::prelude::*;
+use proptest::sample::Index;
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Static(String),
+    Span(String, Vec<(String, String, String, u8)>),
+}
+
+type Block = (String, Vec<Segment>);
+
+/// Story text, with the characters that trip up naive repairs.
+fn text() -> impl Strategy<Value = String> {
+    "[a-zA-Zéü ,.!?'\"\\\\\\[\\]{}]{0,16}"
+}
+
+fn segment() -> impl Strategy<Value = Segment> {
+    let variant = (text(), "neutral|literary|colloquial", text(), 1u8..=5);
+    prop_oneof![
+        text().prop_map(Segment::Static),
+        ("s[0-9]{1,2}", prop::collection::vec(variant, 1..4)).prop_map(|(id, variants)| Segment::Span(id, variants)),
+    ]
+}
+
+fn blocks() -> impl Strategy<Value = Vec<Block>> {
+    prop::collection::vec(("b[0-9]{1,3}", prop::collection::vec(segment(), 1..5)), 0..4)
+}
+
+fn to_json(blocks: &[Block]) -> Value {
+    let segment = |segment: &Segment| match segment {
+        Segment::Static(text) => json!({ "type": "static", "text": text }),
+        Segment::Span(id, variants) => json!({
+            "type": "swappable",
+            "id": id,
+            "variants": variants
+                .iter()
+                .map(|(text, register, note, difficulty)| {
+                    json!({ "text": text, "register": register, "note": note, "difficulty": difficulty })
+                })
+                .collect::<Vec<_>>(),
+        }),
+    };
+    blocks
+        .iter()
+        .map(|(id, segments)| json!({ "id": id, "segments": segments.iter().map(segment).collect::<Vec<_>>() }))
+        .collect()
+}
+
+fn project(blocks: Vec<PlannedBlock>) -> Vec<Block> {
+    blocks
+        .into_iter()
+        .map(|block| {
+            let segments = block
+                .segments
+                .into_iter()
+                .map(|segment| match segment {
+                    PlannedSegment::Static(text) => Segment::Static(text),
+                    PlannedSegment::Swappable(span) => Segment::Span(
+                        span.id,
+                        span.variants.into_iter().map(|v| (v.text, v.register, v.note, v.difficulty)).collect(),
+                    ),
+                })
+                .collect();
+            (block.id, segments)
+        })
+        .collect()
+}
+
+/// A comma after the last item of the containers `mask` picks.
+fn with_trailing_commas(json: &str, mask: &[bool]) -> String {
+    let mut out = String::with_capacity(json.len() * 2);
+    let (mut in_string, mut escape, mut closers) = (false, false, 0);
+    for c in json.chars() {
+        if in_string {
+            in_string = escape || c != '"';
+            escape = !escape && c == '\\';
+        } else if c == '"' {
+            in_string = true;
+        } else if matches!(c, ']' | '}') {
+            let after_item = !matches!(out.trim_end().chars().last(), Some('[' | '{'));
+            if after_item && mask[closers % mask.len()] {
+                out.push(',');
+            }
+            closers += 1;
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Recovered exactly, or a retryable parse error; never a different plan.
+fn recovered_or_parse_error(reply: &str, expected: &[Block]) -> Result<(), TestCaseError> {
+    match parse_planned_blocks(reply) {
+        Ok(parsed) => prop_assert_eq!(project(parsed), expected),
+        Err(ApiError::Parse(message)) => prop_assert!(message.starts_with("JSON parse"), "{}", message),
+        Err(other) => prop_assert!(false, "unexpected error: {:?}", other),
+    }
+    Ok(())
+}
+
+const PREFIXES: [&str; 4] = ["", "Here is the plan:\n", "Sure! See [1] and {notes}.\n\n", "```json\n"];
+const SUFFIXES: [&str; 4] = ["", "\n```", "\n\nLet me know if you need [more].", "\n```\nHope this helps!"];
+
+proptest! {
+    #[test]
+    fn valid_plans_round_trip(blocks in blocks(), pretty in any::<bool>()) {
+        let value = to_json(&blocks);
+        let reply = if pretty { serde_json::to_string_pretty(&value) } else { serde_json::to_string(&value) }.unwrap();
+        prop_assert_eq!(project(parse_planned_blocks(&reply).unwrap()), blocks);
+    }
+
+    #[test]
+    fn trailing_commas_fences_and_prose_are_repaired(
+        blocks in blocks(),
+        mask in prop::collection::vec(any::<bool>(), 1..8),
+        prefix in prop::sample::select(&PREFIXES[..]),
+        suffix in prop::sample::select(&SUFFIXES[..]),
+    ) {
+        let json = with_trailing_commas(&serde_json::to_string_pretty(&to_json(&blocks)).unwrap(), &mask);
+        let reply = format!("{}{}{}", prefix, json, suffix);
+        prop_assert_eq!(project(parse_planned_blocks(&reply).unwrap()), blocks);
+    }
+
+    #[test]
+    fn truncated_replies_fail_cleanly(blocks in blocks(), cut in any::<Index>(), fenced in any::<bool>()) {
+        let json = serde_json::to_string(&to_json(&blocks)).unwrap();
+        let reply = if fenced { format!("```json\n{}\n```", json) } else { json };
+        let chars: Vec<char> = reply.chars().collect();
+        let truncated: String = chars[..cut.index(chars.len())].iter().collect();
+        recovered_or_parse_error(&truncated, &blocks)?;
+    }
+
+    #[test]
+    fn single_quotes_fail_cleanly(blocks in blocks(), mask in prop::collection::vec(any::<bool>(), 1..8)) {
+        let json = serde_json::to_string(&to_json(&blocks)).unwrap();
+        let mut quotes = 0;
+        let reply: String = json
+            .chars()
+            .map(|c| {
+                if c != '"' {
+                    return c;
+                }
+                quotes += 1;
+                if mask[quotes % mask.len()] { '\'' } else { c }
+            })
+            .collect();
+        recovered_or_parse_error(&reply, &blocks)?;
+    }
+
+    #[test]
+    fn arbitrary_text_never_panics(reply in "\\PC{0,64}") {
+        if let Err(err) = parse_planned_blocks(&reply) {
+            prop_assert!(matches!(err, ApiError::Parse(_)), "{:?}", err);
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! Shadowing practice plans and their storage.
+
+use boka_core::practice_plan::{practice_plan, PracticeStage};
+use boka_core::simplify::CefrLevel;
+use boka_core::stories::{self, find_doc, DocId};
+
+use serde_json::{json, Value};
+
+const HARD: &str = "Extraordinairement, l'administration départementale reconsidérait systématiquement \
+                    l'organisation intercommunale.";
+
+fn library(count: usize) -> Value {
+    let mut tokens = Vec::new();
+    let mut spans = serde_json::Map::new();
+    for i in 0..count {
+        let id = format!("s{i}");
+        let text = if i == 1 { HARD.to_string() } else { format!("Phrase {i}.") };
+        tokens.push(json!({ "type": "span", "spanId": id }));
+        tokens.push(json!({ "type": "text", "value": " " }));
+        spans.insert(
+            id.clone(),
+            json!({
+                "id": id,
+                "sourceText": format!("Sentence {i}."),
+                "activeVariantIndex": 0,
+                "variants": [{ "id": format!("{id}-v"), "register": "neutral", "text": text }]
+            }),
+        );
+    }
+    json!([{
+        "id": "story-1",
+        "title": "Phrases",
+        "translations": { "fr": { "doc": { "tokens": tokens, "spans": spans } } }
+    }])
+}
+
+fn doc_id() -> DocId {
+    DocId::parse("story-1:fr").unwrap()
+}
+
+#[test]
+fn groups_come_back_faster_on_later_days() {
+    let story = find_doc(&library(7), &doc_id()).unwrap();
+    let plan = practice_plan(&story, CefrLevel::A1);
+    assert_eq!((plan.sentences, plan.days.len()), (7, 6));
+
+    let day1 = &plan.days[0].items;
+    let ids: Vec<_> = day1.iter().map(|i| i.span_id.as_str()).collect();
+    assert_eq!(ids, ["s0", "s1", "s2"]);
+    assert!(day1.iter().all(|i| i.stage == PracticeStage::Learn && i.speed == 0.6));
+    assert_eq!((day1[0].repeats, day1[1].repeats), (3, 4));
+
+    // Reviews come before the day's new sentences.
+    let day2: Vec<_> = plan.days[1].items.iter().map(|i| (i.span_id.as_str(), i.stage)).collect();
+    assert_eq!(day2[0], ("s0", PracticeStage::Review));
+    assert_eq!(day2[3], ("s3", PracticeStage::Learn));
+
+    let day4: Vec<_> = plan.days[3].items.iter().map(|i| (i.span_id.as_str(), i.stage)).collect();
+    assert_eq!(
+        day4,
+        [
+            ("s0", PracticeStage::Shadow),
+            ("s1", PracticeStage::Shadow),
+            ("s2", PracticeStage::Shadow),
+            ("s6", PracticeStage::Review),
+        ]
+    );
+    assert_eq!(plan.days[3].items[1].repeats, 1);
+
+    let fast = practice_plan(&story, CefrLevel::C2);
+    assert_eq!(fast.days.len(), 4);
+    assert!(practice_plan(&find_doc(&library(0), &doc_id()).unwrap(), CefrLevel::B1).days.is_empty());
+}
+
+#[test]
+fn plans_are_stored_with_the_translation() {
+    let mut all = library(4);
+    assert_eq!(stories::saved_practice_plan(&all, &doc_id()).unwrap(), None);
+    let plan = practice_plan(&find_doc(&all, &doc_id()).unwrap(), CefrLevel::B1);
+    stories::set_practice_plan(&mut all, &doc_id(), &plan).unwrap();
+    assert_eq!(stories::saved_practice_plan(&all, &doc_id()).unwrap(), Some(plan.clone()));
+    assert_eq!(all[0]["translations"]["fr"]["practicePlan"]["level"], "B1");
+
+    // Frontend writes keep the backend's plan.
+    let mut incoming = library(4);
+    stories::keep_meta(&mut incoming, &all);
+    assert_eq!(stories::saved_practice_plan(&incoming, &doc_id()).unwrap(), Some(plan));
+    assert!(stories::saved_practice_plan(&all, &DocId::parse("story-1:de").unwrap()).is_err());
+}
@@ -0,0 +1,60 @@
+//! User prices laid over the model registry, and costs shown in the
+//! display currency or as tokens when a model has no price.
+
+use boka_core::pricing::{describe_cost, DisplayCurrency, PriceTable};
+use boka_core::types::{LlmProviderPreset, ModelPricing, ModelRegistry, Usage};
+
+fn price(input: f64, output: f64) -> ModelPricing {
+    ModelPricing {
+        input_per_mtok: input,
+        output_per_mtok: output,
+    }
+}
+
+#[test]
+fn user_prices_replace_and_extend_the_registry() {
+    let mut table = PriceTable::default();
+    table.set(LlmProviderPreset::Openai, "gpt-4o", Some(price(5.0, 20.0))).unwrap();
+    table.set(LlmProviderPreset::Openrouter, " mistralai/mixtral ", Some(price(0.5, 0.5))).unwrap();
+    table.set(LlmProviderPreset::Openrouter, "mistralai/mixtral", Some(price(0.6, 0.6))).unwrap();
+    assert_eq!(table.models.len(), 2);
+    assert!(table.set(LlmProviderPreset::Openai, "gpt-4o", Some(price(-1.0, 1.0))).is_err());
+    assert!(table.set(LlmProviderPreset::Openai, " ", None).is_err());
+
+    let bundled = ModelRegistry::bundled();
+    let registry = table.apply(bundled.clone());
+    assert_eq!(registry.pricing(LlmProviderPreset::Openai, "gpt-4o"), Some(price(5.0, 20.0)));
+    assert_eq!(registry.pricing(LlmProviderPreset::Openai, "gpt-4o-mini"), Some(price(0.15, 0.6)));
+    let mixtral = "mistralai/mixtral-8x7b-instruct";
+    assert_eq!(registry.pricing(LlmProviderPreset::Openrouter, mixtral), Some(price(0.6, 0.6)));
+    assert_eq!(
+        registry.capabilities(LlmProviderPreset::Openrouter, mixtral),
+        bundled.capabilities(LlmProviderPreset::Openrouter, mixtral)
+    );
+
+    // Removing a price goes back to the registry's.
+    table.set(LlmProviderPreset::Openai, "gpt-4o", None).unwrap();
+    let registry = table.apply(ModelRegistry::bundled());
+    assert_eq!(registry.pricing(LlmProviderPreset::Openai, "gpt-4o"), Some(price(2.5, 10.0)));
+}
+
+#[test]
+fn costs_are_converted_or_shown_as_tokens() {
+    let usage = Usage {
+        input_tokens: 200_000,
+        output_tokens: 50_000,
+        ..Default::default()
+    };
+    let euro = DisplayCurrency::new(" eur ", 0.5).unwrap();
+    let cost = describe_cost(&usage, Some(&price(2.0, 12.0)), &euro);
+    assert_eq!(cost.amount, Some(0.5));
+    assert_eq!(cost.label, "0.50 EUR");
+    assert_eq!(describe_cost(&usage, Some(&price(0.01, 0.01)), &euro).label, "< 0.01 EUR");
+
+    let unknown = describe_cost(&usage, None, &euro);
+    assert_eq!(unknown.amount, None);
+    assert_eq!(unknown.label, "250,000 tokens");
+
+    assert!(DisplayCurrency::new("euro", 1.0).is_err());
+    assert!(DisplayCurrency::new("EUR", 0.0).is_err());
+}
@@ -0,0 +1,123 @@
+//! Retention and scrubbing: private stories lose their source on every save
+//! and at startup, old transcripts expire, and stores can be purged.
+
+use boka_core::privacy::{
+    self, expire_transcripts_at, purge, scrub_private_sources, PurgeReport, PurgeScope, RetentionPolicy,
+};
+use boka_core::settings::Settings;
+use boka_core::stories;
+use boka_core::transcript::transcripts_dir;
+use boka_core::translation_cache::TranslationCache;
+use boka_core::trash::Trash;
+
+use serde_json::{json, Value};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("boka-privacy-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn story(id: &str, private: bool) -> Value {
+    json!({
+        "id": id,
+        "title": "Dear diary",
+        "private": private,
+        "sourceText": "Today I told nobody.",
+        "translations": { "fr": {
+            "language": "fr",
+            "job": { "id": "job-1", "segments": [
+                { "id": "seg-1", "source": "Today I told nobody.", "baseText": "Aujourd'hui…" }
+            ] },
+            "doc": null
+        } }
+    })
+}
+
+#[test]
+fn private_stories_keep_translations_but_not_their_source() {
+    let mut all = json!([story("diary", true), story("public", false)]);
+    assert_eq!(scrub_private_sources(&mut all), 1);
+    assert_eq!(all[0]["sourceText"], "");
+    assert_eq!(all[0]["translations"]["fr"]["job"]["segments"][0]["source"], "");
+    assert_eq!(all[0]["translations"]["fr"]["job"]["segments"][0]["baseText"], "Aujourd'hui…");
+    assert_eq!(all[1], story("public", false));
+    // Nothing left to scrub.
+    assert_eq!(scrub_private_sources(&mut all), 0);
+}
+
+#[test]
+fn maintenance_scrubs_stories_and_trash_and_expires_transcripts() {
+    let dir = temp_dir("maintenance");
+    let mut all = json!([story("diary", true), story("old-diary", true), story("public", false)]);
+    let mut trash = Trash::default();
+    trash.trash(&mut all, "old-diary", None).unwrap();
+    trash.trash(&mut all, "diary", Some("fr")).unwrap();
+    trash.save(&dir).unwrap();
+    all[0]["translations"]["de"] = story("x", false)["translations"]["fr"].clone();
+    stories::save(&dir, &all).unwrap();
+
+    fs::create_dir_all(transcripts_dir(&dir)).unwrap();
+    let old = transcripts_dir(&dir).join("job-old.jsonl");
+    fs::write(&old, "{}\n").unwrap();
+    let month_ago = SystemTime::now() - Duration::from_secs(30 * 24 * 60 * 60);
+    fs::File::options().write(true).open(&old).unwrap().set_modified(month_ago).unwrap();
+    fs::write(transcripts_dir(&dir).join("job-new.jsonl"), "{}\n").unwrap();
+
+    let report = privacy::run_maintenance(&dir, &RetentionPolicy { transcript_days: Some(7) }).unwrap();
+    assert_eq!(report.expired_transcripts, PurgeReport { files: 1, bytes: 3 });
+    assert_eq!(report.scrubbed, 3);
+    assert!(!old.exists());
+    assert!(transcripts_dir(&dir).join("job-new.jsonl").exists());
+
+    let saved = stories::load(&dir).unwrap();
+    assert_eq!(saved[1], story("public", false));
+    let raw = fs::read_to_string(stories::path(&dir)).unwrap() + &fs::read_to_string(Trash::path(&dir)).unwrap();
+    assert_eq!(raw.matches("Today I told nobody.").count(), 2, "only the public story's source is left");
+
+    // Without a policy transcripts are kept.
+    let report = privacy::run_maintenance(&dir, &RetentionPolicy::default()).unwrap();
+    assert_eq!((report.expired_transcripts.files, report.scrubbed), (0, 0));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn expiry_counts_days_from_the_last_write() {
+    let dir = temp_dir("expiry");
+    fs::create_dir_all(transcripts_dir(&dir)).unwrap();
+    fs::write(transcripts_dir(&dir).join("job-1.jsonl"), "{}\n").unwrap();
+    let in_days = |days: u64| SystemTime::now() + Duration::from_secs(days * 24 * 60 * 60);
+    assert_eq!(expire_transcripts_at(&dir, 7, in_days(6)).unwrap().files, 0);
+    assert_eq!(expire_transcripts_at(&dir, 7, in_days(8)).unwrap().files, 1);
+    // A profile that never recorded one has nothing to expire.
+    assert_eq!(expire_transcripts_at(&temp_dir("none"), 7, in_days(8)).unwrap(), PurgeReport::default());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn purging_empties_only_the_scoped_stores() {
+    let dir = temp_dir("purge");
+    let (data, cache) = (dir.join("data"), dir.join("cache"));
+    fs::create_dir_all(transcripts_dir(&data)).unwrap();
+    fs::write(transcripts_dir(&data).join("job-1.jsonl"), "{}\n").unwrap();
+    fs::create_dir_all(TranslationCache::dir_in(&cache)).unwrap();
+    fs::write(TranslationCache::dir_in(&cache).join("abc.txt"), "Le chat dort.").unwrap();
+
+    assert_eq!(purge(PurgeScope::Transcripts, &data, &cache).unwrap(), PurgeReport { files: 1, bytes: 3 });
+    assert_eq!(TranslationCache::new(&TranslationCache::dir_in(&cache)).stats().0, 1);
+    assert_eq!(purge(PurgeScope::All, &data, &cache).unwrap(), PurgeReport { files: 1, bytes: 13 });
+    assert_eq!(purge(PurgeScope::All, &data, &cache).unwrap(), PurgeReport::default());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn retention_must_be_a_sensible_number_of_days() {
+    let mut settings = Settings::default();
+    assert!(settings.set_retention(RetentionPolicy { transcript_days: Some(0) }).is_err());
+    assert!(settings.set_retention(RetentionPolicy { transcript_days: Some(100_000) }).is_err());
+    settings.set_retention(RetentionPolicy { transcript_days: Some(30) }).unwrap();
+    assert_eq!(settings.view().retention.transcript_days, Some(30));
+}
@@ -0,0 +1,156 @@
+//! Exporting and importing the whole profile.
+
+use boka_core::export::zip::ZipWriter;
+use boka_core::profile::{export_profile, import_profile, ProfileError, PROFILE_FORMAT, PROFILE_VERSION};
+use boka_core::settings::Settings;
+
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("boka-profile-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write(dir: &Path, name: &str, contents: &str) {
+    let path = dir.join(name);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+fn read_json(path: &Path) -> Value {
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn profile(dir: &Path) {
+    write(dir, "stories.json", r#"[{ "id": "cats", "title": "Cats", "translations": {} }]"#);
+    write(
+        dir,
+        "settings.json",
+        r#"{ "theme": "dark", "anthropic_api_key": "sk-1", "providers": [{ "name": "x", "apiKey": "sk-2" }] }"#,
+    );
+    write(dir, "templates/card.html", "<p>{{ title }}</p>");
+    write(dir, "stories.json.tmp", "[");
+    write(dir, "models/whisper.bin", "weights");
+    write(dir, "exports/cats.csv", "a,b");
+}
+
+#[test]
+fn profiles_round_trip_without_secrets() {
+    let source = temp_dir("source");
+    let target = temp_dir("target");
+    profile(&source);
+    let archive = source.join("exports").join("profile.zip");
+    let frontend = json!({ "boka.settings": { "providerPreset": "anthropic", "providerApiKey": "sk-3" } });
+
+    let manifest = export_profile(&source, Some(&frontend), &archive).unwrap();
+    assert_eq!((manifest.format.as_str(), manifest.version), (PROFILE_FORMAT, PROFILE_VERSION));
+    let paths: Vec<_> = manifest.files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, ["frontend.json", "data/settings.json", "data/stories.json", "data/templates/card.html"]);
+    let bytes = fs::read(&archive).unwrap();
+    assert!(!bytes.windows(3).any(|w| w == b"sk-"));
+
+    write(&target, "stories.json", "[]");
+    write(&target, "notes.txt", "kept");
+    let backup = target.join("exports").join("backup.zip");
+    let imported = import_profile(&target, &archive, &backup, None).unwrap();
+    assert_eq!(imported.manifest, manifest);
+    assert_eq!(imported.frontend, Some(json!({ "boka.settings": { "providerPreset": "anthropic" } })));
+    assert_eq!(read_json(&target.join("stories.json"))[0]["id"], "cats");
+    assert_eq!(read_json(&target.join("settings.json")), json!({ "theme": "dark", "providers": [{ "name": "x" }] }));
+    assert_eq!(fs::read_to_string(target.join("templates/card.html")).unwrap(), "<p>{{ title }}</p>");
+    assert_eq!(fs::read_to_string(target.join("notes.txt")).unwrap(), "kept");
+    assert!(!target.join("models").exists());
+
+    // The replaced profile can be imported back.
+    import_profile(&target, &backup, &target.join("exports").join("backup-2.zip"), None).unwrap();
+    assert_eq!(read_json(&target.join("stories.json")), json!([]));
+
+    let _ = fs::remove_dir_all(&source);
+    let _ = fs::remove_dir_all(&target);
+}
+
+#[test]
+fn damaged_archives_change_nothing() {
+    let source = temp_dir("damaged");
+    let target = temp_dir("damaged-target");
+    profile(&source);
+    write(&target, "stories.json", "[]");
+    let archive = source.join("exports").join("profile.zip");
+    export_profile(&source, None, &archive).unwrap();
+
+    let mut bytes = fs::read(&archive).unwrap();
+    let at = bytes.windows(4).position(|w| w == b"Cats").unwrap();
+    bytes[at] = b'H';
+    let damaged = source.join("damaged.zip");
+    fs::write(&damaged, &bytes).unwrap();
+    let backup = target.join("backup.zip");
+    assert!(matches!(
+        import_profile(&target, &damaged, &backup, None),
+        Err(ProfileError::Archive(_) | ProfileError::Integrity(_))
+    ));
+    fs::write(&damaged, b"not a zip").unwrap();
+    assert!(matches!(import_profile(&target, &damaged, &backup, None), Err(ProfileError::Archive(_))));
+
+    assert_eq!(read_json(&target.join("stories.json")), json!([]));
+    assert!(!backup.exists());
+    let _ = fs::remove_dir_all(&source);
+    let _ = fs::remove_dir_all(&target);
+}
+
+#[test]
+fn manifests_cannot_hide_entries() {
+    let dir = temp_dir("smuggled");
+    write(&dir, "stories.json", "[]");
+    let file = json!({ "path": "data/stories.json", "size": 2, "sha256": format!("{:x}", Sha256::digest(b"[]")) });
+    let manifest = json!({
+        "format": PROFILE_FORMAT, "version": PROFILE_VERSION, "appVersion": "0", "createdAt": 0,
+        "files": [file, file]
+    });
+    let backup = dir.join("backup.zip");
+
+    // Listed twice, so the count matches and the unlisted entry is never looked up.
+    for hidden in ["data/../escaped.json", "escaped.json"] {
+        let archive = dir.join("crafted.zip");
+        let mut zip = ZipWriter::default();
+        zip.add("manifest.json", &serde_json::to_vec(&manifest).unwrap());
+        zip.add("data/stories.json", b"[]");
+        zip.add(hidden, b"[1]");
+        fs::write(&archive, zip.finish()).unwrap();
+        assert!(matches!(import_profile(&dir, &archive, &backup, None), Err(ProfileError::Integrity(_))));
+    }
+
+    assert!(!backup.exists());
+    assert!(!dir.join("escaped.json").exists() && !dir.parent().unwrap().join("escaped.json").exists());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn imports_keep_a_locked_child_safe_mode() {
+    let source = temp_dir("unlocked");
+    let target = temp_dir("locked");
+    profile(&source);
+    let archive = source.join("exports").join("profile.zip");
+    export_profile(&source, None, &archive).unwrap();
+
+    let mut locked = Settings::default();
+    locked.set_child_safe(true, Some("1234")).unwrap();
+    locked.save(&target).unwrap();
+    let backup = target.join("exports").join("backup.zip");
+    import_profile(&target, &archive, &backup, Some("0000")).unwrap();
+    let settings = read_json(&target.join("settings.json"));
+    assert_eq!(settings["theme"], "dark");
+    assert_eq!(settings["childSafe"], serde_json::to_value(&locked.child_safe).unwrap());
+
+    // With the PIN, the archive's settings apply as they are.
+    import_profile(&target, &archive, &backup, Some("1234")).unwrap();
+    assert!(!Settings::load(&target).unwrap().child_safe.enabled);
+    assert!(read_json(&target.join("settings.json")).get("childSafe").is_none());
+
+    let _ = fs::remove_dir_all(&source);
+    let _ = fs::remove_dir_all(&target);
+}
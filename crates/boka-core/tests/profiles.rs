@@ -0,0 +1,76 @@
+//! Named profiles and their directories.
+
+use boka_core::paths::{BokaPaths, Platform};
+use boka_core::profile::export_profile;
+use boka_core::profiles::{self, Profiles, ProfilesError, DEFAULT_PROFILE};
+
+use serde_json::json;
+use std::fs;
+use std::path::PathBuf;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("boka-profiles-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn profiles_are_created_switched_and_saved() {
+    let root = temp_dir("registry");
+    let mut all = Profiles::load(&root).unwrap();
+    assert_eq!(all.active, DEFAULT_PROFILE);
+    assert_eq!(all.profiles.len(), 1);
+
+    let ana = all.create("  Ana  María ").unwrap();
+    assert_eq!((ana.id.as_str(), ana.name.as_str()), ("ana-mar-a", "Ana María"));
+    assert!(matches!(all.create("ana maría"), Err(ProfilesError::Invalid(_))));
+    assert!(matches!(all.create(" "), Err(ProfilesError::Invalid(_))));
+    let other = all.create("Ana Mar A").unwrap();
+    assert_eq!(other.id, "ana-mar-a-2");
+    assert_eq!(all.create("…").unwrap().id, "profile");
+
+    assert_eq!(all.switch(&ana.id).unwrap(), ana);
+    assert!(matches!(all.switch("nobody"), Err(ProfilesError::NotFound(_))));
+    all.save(&root).unwrap();
+    let loaded = Profiles::load(&root).unwrap();
+    assert_eq!(loaded, all);
+
+    // A removed active profile falls back to the default.
+    let mut edited = loaded.clone();
+    edited.profiles.retain(|p| p.id != ana.id);
+    edited.save(&root).unwrap();
+    assert_eq!(Profiles::load(&root).unwrap().active, DEFAULT_PROFILE);
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn profiles_get_their_own_data_and_cache_dirs() {
+    let paths = BokaPaths::resolve(Platform::Linux, |key| (key == "HOME").then(|| "/home/ana".to_string())).unwrap();
+    assert_eq!(paths.for_profile(DEFAULT_PROFILE), paths);
+
+    let ana = paths.for_profile("ana");
+    assert_eq!(ana.data_dir, PathBuf::from("/home/ana/.local/share/boka/profiles/ana"));
+    assert_eq!(ana.cache_dir, PathBuf::from("/home/ana/.cache/boka/profiles/ana"));
+    // Audio is namespaced per profile inside the one cache dir.
+    assert_eq!(ana.audio_cache_dir, paths.audio_cache_dir);
+    assert_eq!(ana.model_dir, paths.model_dir);
+    assert_eq!(ana.hf_cache_dir, paths.hf_cache_dir);
+}
+
+#[test]
+fn frontend_snapshots_stay_with_their_profile() {
+    let root = temp_dir("frontend");
+    let ana = root.join("profiles").join("ana");
+    assert_eq!(profiles::load_frontend(&ana).unwrap(), None);
+    let snapshot = json!({ "boka.settings": { "providerApiKey": "sk-1" }, "boka.myLanguages": ["fr"] });
+    profiles::save_frontend(&ana, &snapshot).unwrap();
+    assert_eq!(profiles::load_frontend(&ana).unwrap(), Some(snapshot));
+    assert_eq!(profiles::load_frontend(&root).unwrap(), None);
+
+    // The default profile's archive leaves the other profiles out.
+    fs::write(root.join("stories.json"), "[]").unwrap();
+    let manifest = export_profile(&root, None, &root.join("exports").join("profile.zip")).unwrap();
+    let paths: Vec<_> = manifest.files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, ["data/stories.json"]);
+    let _ = fs::remove_dir_all(&root);
+}
@@ -0,0 +1,60 @@
+//! Per-language prompt addenda: storage and how they join the system prompts.
+
+use boka_core::prompts::{
+    addendum_path, list_addenda, load_addendum, save_addendum, PromptFileError, PromptSet, JSON_OBJECT_NOTE,
+};
+use boka_core::simplify::CefrLevel;
+use boka_core::types::ApiConfig;
+
+use std::fs;
+
+#[test]
+fn addenda_are_saved_per_language_with_base_fallback() {
+    let dir = std::env::temp_dir().join(format!("boka-addenda-{}", std::process::id()));
+    assert_eq!(load_addendum(&dir, "ja").unwrap(), None);
+    assert!(list_addenda(&dir).is_empty());
+
+    save_addendum(&dir, "JA", Some("  Use です/ます form throughout.\n")).unwrap();
+    save_addendum(&dir, "pt", Some("Prefer European spelling.")).unwrap();
+    assert_eq!(
+        fs::read_to_string(addendum_path(&dir, "ja").unwrap()).unwrap(),
+        "Use です/ます form throughout.\n"
+    );
+    assert_eq!(list_addenda(&dir), ["ja", "pt"]);
+    assert_eq!(load_addendum(&dir, "pt-BR").unwrap().as_deref(), Some("Prefer European spelling.\n"));
+
+    save_addendum(&dir, "ja", Some("   ")).unwrap();
+    assert_eq!(load_addendum(&dir, "ja").unwrap(), None);
+    save_addendum(&dir, "ja", None).unwrap();
+    assert_eq!(list_addenda(&dir), ["pt"]);
+
+    for bad in ["", "../settings", "de.md", "fr/x"] {
+        assert!(matches!(load_addendum(&dir, bad), Err(PromptFileError::InvalidLanguage(_))), "{bad}");
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn addendum_is_appended_to_every_translation_prompt() {
+    let mut cfg = ApiConfig::from_env("de", Some("en"), false, false);
+    let plain = PromptSet::for_config(&cfg, true);
+
+    cfg.prompt_overrides.language_addendum = Some("Capitalize all nouns.\n".to_string());
+    cfg.prompt_overrides.base_translation = Some("Custom base.".to_string());
+    cfg.simplify_level = Some(CefrLevel::A2);
+    let prompts = PromptSet::for_config(&cfg, true);
+
+    let note = "\n\nLanguage notes:\nCapitalize all nouns.";
+    assert_eq!(prompts.base_translation, format!("Custom base.{note}"));
+    assert!(prompts.span_planning.ends_with(&format!("{note}{JSON_OBJECT_NOTE}")));
+    assert_eq!(
+        prompts.span_variants,
+        plain.span_variants.replace(JSON_OBJECT_NOTE, "") + note + JSON_OBJECT_NOTE
+    );
+    assert!(prompts.simplified_translation.unwrap().ends_with(note));
+
+    cfg.prompt_overrides.language_addendum = Some(" ".to_string());
+    cfg.prompt_overrides.base_translation = None;
+    cfg.simplify_level = None;
+    assert_eq!(PromptSet::for_config(&cfg, true), plain);
+}
@@ -0,0 +1,66 @@
+//! `run_prompt_experiment` across two mock arms.
+
+use boka_core::experiment::{run_prompt_experiment, ExperimentArgs, ExperimentArm};
+use boka_core::types::{LlmProviderConfig, LlmProviderPreset};
+
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+fn arm(label: &str, fixture: Option<&str>) -> ExperimentArm {
+    ExperimentArm {
+        label: label.to_string(),
+        provider: LlmProviderConfig {
+            preset: LlmProviderPreset::Mock,
+            api_key: None,
+            base_url: fixture.map(|f| format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), f)),
+            model: None,
+            reasoning_model: false,
+        },
+        prompts: Default::default(),
+    }
+}
+
+fn args(arms: Vec<ExperimentArm>, max_segments: Option<usize>) -> ExperimentArgs {
+    ExperimentArgs {
+        story_text: "The cat sleeps. The dog barks.".to_string(),
+        target_language: "fr".to_string(),
+        source_language: Some("en".to_string()),
+        content_policy: None,
+        dense_spans: false,
+        max_segments,
+        arms,
+        cancelled: Arc::new(AtomicBool::new(false)),
+    }
+}
+
+#[tokio::test]
+async fn arms_are_compared_side_by_side() {
+    let report = run_prompt_experiment(args(vec![arm("scripted", Some("happy_path.json")), arm("echo", None)], None))
+        .await
+        .expect("experiment should run");
+
+    assert_eq!(report.arms.len(), 2);
+    for outcome in &report.arms {
+        assert!(outcome.error.is_none(), "{:?}", outcome.error);
+        assert!(outcome.usage.input_tokens > 0);
+        assert_eq!(outcome.estimated_cost_usd, Some(0.0));
+    }
+
+    assert_eq!(report.rows.len(), 2);
+    let row = &report.rows[0];
+    assert_eq!(row.source, "The cat sleeps.");
+    assert_eq!(row.outputs, [Some("Le chat dort.".to_string()), Some("The cat sleeps.".to_string())]);
+    assert!(!row.identical);
+}
+
+#[tokio::test]
+async fn failing_arm_is_reported_and_segments_are_capped() {
+    let report = run_prompt_experiment(args(vec![arm("broken", Some("provider_error.json")), arm("echo", None)], Some(1)))
+        .await
+        .expect("experiment should run");
+
+    assert_eq!(report.rows.len(), 1);
+    assert!(report.arms[0].error.as_deref().is_some_and(|e| e.contains("529")));
+    assert_eq!(report.rows[0].outputs[0], None);
+    assert_eq!(report.rows[0].outputs[1].as_deref(), Some("The cat sleeps."));
+}
@@ -0,0 +1,121 @@
+//! `preview_prompts` matches what a job records, and applies JSON mode per provider.
+
+use boka_core::gui_types::{ErrorPolicy, Granularity, InteractiveDoc, TranslationJob};
+use boka_core::length_guard::LengthGuard;
+use boka_core::prompts::{assemble_system, PromptOverrides, JSON_OBJECT_NOTE};
+use boka_core::settings::{Settings, VariantBounds, MAX_PREAMBLE_CHARS};
+use boka_core::translation::{preview_prompts, run_translation, PromptOptions, TranslationArgs};
+use boka_core::types::{LlmProviderConfig, LlmProviderPreset};
+
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+fn options(preset: LlmProviderPreset, base_url: Option<String>) -> PromptOptions {
+    PromptOptions {
+        target_language: "ja".to_string(),
+        source_language: Some("en".to_string()),
+        adult_mode: false,
+        content_policy: None,
+        dense_spans: true,
+        simplify_level: None,
+        refine_variants: false,
+        granularity: Granularity::Sentence,
+        ui_language: None,
+        prompt_overrides: PromptOverrides {
+            span_planning: Some("Plan the block.".to_string()),
+            language_addendum: Some("Use です/ます form.".to_string()),
+            ..Default::default()
+        },
+        provider: LlmProviderConfig {
+            preset,
+            api_key: None,
+            base_url,
+            model: None,
+            reasoning_model: false,
+        },
+    }
+}
+
+#[tokio::test]
+async fn preview_matches_the_prompts_a_job_records() {
+    let fixture = format!("{}/tests/fixtures/happy_path.json", env!("CARGO_MANIFEST_DIR"));
+    let opts = options(LlmProviderPreset::Mock, Some(fixture));
+    let preview = preview_prompts(opts.clone());
+    assert!(!preview.json_mode);
+
+    let result = run_translation(TranslationArgs {
+        story_text: "The cat sleeps. The dog barks.".to_string(),
+        job_id: "job-preview".to_string(),
+        target_language: opts.target_language,
+        source_language: opts.source_language,
+        adult_mode: opts.adult_mode,
+        content_policy: opts.content_policy,
+        dense_spans: opts.dense_spans,
+        reproducible: false,
+        prompt_overrides: opts.prompt_overrides,
+        judge: None,
+        variant_bounds: VariantBounds::default(),
+        length_guard: LengthGuard::default(),
+        simplify_level: opts.simplify_level,
+        dual_output: false,
+        refine_variants: false,
+        granularity: opts.granularity,
+        comprehension_every: None,
+        annotations: Default::default(),
+        review: None,
+        error_policy: ErrorPolicy::Abort,
+        budget: None,
+        budget_gate: None,
+        confirmation: None,
+        translation_cache: None,
+        transcript: None,
+        ui_language: None,
+        provider: opts.provider,
+        cancelled: Arc::new(AtomicBool::new(false)),
+        on_job: Box::new(|_: &TranslationJob| async {}),
+        on_doc: Box::new(|_: &InteractiveDoc| async {}),
+    })
+    .await
+    .expect("translation should succeed");
+
+    let recorded = result.job.metadata.expect("job metadata recorded").prompts;
+    assert_eq!(preview.prompts, recorded);
+    assert!(recorded.span_planning.starts_with("Plan the block.\n\nLanguage notes:"));
+}
+
+#[test]
+fn json_mode_follows_the_provider_model() {
+    let openai = preview_prompts(options(LlmProviderPreset::Openai, None));
+    assert_eq!(openai.model.as_deref(), Some("gpt-4o-mini"));
+    assert!(openai.json_mode);
+    assert!(openai.prompts.span_variants.ends_with(JSON_OBJECT_NOTE));
+    assert!(!openai.prompts.base_translation.ends_with(JSON_OBJECT_NOTE));
+
+    let anthropic = preview_prompts(options(LlmProviderPreset::Anthropic, None));
+    assert!(!anthropic.json_mode);
+    assert!(!anthropic.prompts.span_variants.ends_with(JSON_OBJECT_NOTE));
+}
+
+#[test]
+fn the_system_preamble_goes_ahead_of_every_prompt() {
+    let mut opts = options(LlmProviderPreset::Anthropic, None);
+    opts.prompt_overrides.system_preamble = Some("  Never add translator's notes.\n".to_string());
+    let preamble = preview_prompts(opts.clone()).prompts.preamble;
+    assert_eq!(preamble.as_deref(), Some("Never add translator's notes."));
+    assert_eq!(
+        assemble_system(preamble.as_deref(), "You are a judge."),
+        "Never add translator's notes.\n\nYou are a judge."
+    );
+
+    // A job can turn the one from settings off.
+    opts.prompt_overrides.system_preamble = Some(" ".to_string());
+    assert_eq!(preview_prompts(opts).prompts.preamble, None);
+    assert_eq!(assemble_system(None, "You are a judge."), "You are a judge.");
+
+    let mut settings = Settings::default();
+    settings.set_system_preamble(Some(" Keep names as written. ")).unwrap();
+    assert_eq!(settings.view().system_preamble.as_deref(), Some("Keep names as written."));
+    assert!(settings.set_system_preamble(Some(&"x".repeat(MAX_PREAMBLE_CHARS + 1))).is_err());
+    settings.set_system_preamble(Some("")).unwrap();
+    assert_eq!(settings.system_preamble, None);
+}
@@ -0,0 +1,70 @@
+//! Phoneme alignment for pronunciation practice.
+#![cfg(feature = "tts")]
+
+use boka_core::audio::align_phonemes;
+use boka_core::audio_types::{DetectedPhoneme, ExpectedPhoneme, PhonemeMatch};
+
+fn expected(syllables: &[&[&str]]) -> Vec<ExpectedPhoneme> {
+    syllables
+        .iter()
+        .enumerate()
+        .flat_map(|(s, phonemes)| {
+            phonemes.iter().map(move |p| ExpectedPhoneme {
+                phoneme: p.to_string(),
+                syllable: s as u32,
+            })
+        })
+        .collect()
+}
+
+fn detected(phonemes: &[&str]) -> Vec<DetectedPhoneme> {
+    phonemes
+        .iter()
+        .enumerate()
+        .map(|(i, p)| DetectedPhoneme {
+            phoneme: p.to_string(),
+            start_ms: i as u64 * 100,
+            end_ms: i as u64 * 100 + 90,
+        })
+        .collect()
+}
+
+#[test]
+fn perfect_attempt_ignores_stress_marks() {
+    // "bonjour": bɔ̃.ʒuʁ
+    let exp = expected(&[&["b", "ɔ̃"], &["ʒ", "ˈuː", "ʁ"]]);
+    let result = align_phonemes(&exp, &detected(&["b", "ɔ̃", "ʒ", "u", "ʁ"]));
+    assert_eq!(result.score, 1.0);
+    assert!(result.syllables_off.is_empty());
+    assert_eq!(result.phonemes[3].expected.as_deref(), Some("ˈuː"));
+    assert_eq!((result.phonemes[4].start_ms, result.phonemes[4].end_ms), (Some(400), Some(490)));
+}
+
+#[test]
+fn marks_substituted_missing_and_extra_phonemes_by_syllable() {
+    let exp = expected(&[&["b", "ɔ̃"], &["ʒ", "u", "ʁ"]]);
+    // "bon-zhoo-er": ʒ → z, ʁ dropped, an extra ə.
+    let result = align_phonemes(&exp, &detected(&["b", "ɔ̃", "z", "u", "ə"]));
+    let statuses: Vec<PhonemeMatch> = result.phonemes.iter().map(|p| p.status).collect();
+    assert_eq!(
+        statuses,
+        [
+            PhonemeMatch::Correct,
+            PhonemeMatch::Correct,
+            PhonemeMatch::Substituted,
+            PhonemeMatch::Correct,
+            PhonemeMatch::Substituted,
+        ]
+    );
+    assert_eq!(result.syllables_off, [1]);
+    assert_eq!(result.score, 3.0 / 5.0);
+
+    let result = align_phonemes(&exp, &detected(&["ə", "b", "ɔ̃", "ʒ", "u"]));
+    assert_eq!(result.phonemes[0].status, PhonemeMatch::Extra);
+    assert_eq!(result.phonemes[0].syllable, None);
+    let last = result.phonemes.last().unwrap();
+    assert_eq!((last.status, last.start_ms), (PhonemeMatch::Missing, None));
+    assert_eq!(result.syllables_off, [1]);
+
+    assert_eq!(align_phonemes(&[], &detected(&["a"])).score, 0.0);
+}
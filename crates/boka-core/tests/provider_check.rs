@@ -0,0 +1,104 @@
+//! Sorting provider test failures by what the user has to fix.
+
+use boka_core::provider_check::{ProbeCache, ProbeResult, ProviderFailure, ProviderProbe, ProviderTestError};
+use boka_core::types::{ApiError, LlmProviderConfig, LlmProviderPreset};
+
+use std::time::Duration;
+
+fn response(status: u16, body: &str) -> ProviderTestError {
+    ApiError::ApiResponse {
+        status,
+        message: body.to_string(),
+    }
+    .into()
+}
+
+#[test]
+fn anthropic_error_types_are_recognized() {
+    let err = response(
+        401,
+        r#"{"type":"error","error":{"type":"authentication_error","message":"invalid x-api-key"}}"#,
+    );
+    assert_eq!(err.failure, ProviderFailure::InvalidKey);
+    assert_eq!(err.status, Some(401));
+    assert_eq!(err.message, "invalid x-api-key");
+
+    let err = response(404, r#"{"type":"error","error":{"type":"not_found_error","message":"model: claude-9"}}"#);
+    assert_eq!(err.failure, ProviderFailure::ModelNotFound);
+
+    // Anthropic reports an empty balance as a plain invalid request.
+    let err = response(
+        400,
+        r#"{"type":"error","error":{"type":"invalid_request_error","message":"Your credit balance is too low to access the Anthropic API."}}"#,
+    );
+    assert_eq!(err.failure, ProviderFailure::BillingExhausted);
+}
+
+#[test]
+fn openai_codes_win_over_the_status() {
+    let err = response(
+        429,
+        r#"{"error":{"message":"You exceeded your current quota.","type":"insufficient_quota","code":"insufficient_quota"}}"#,
+    );
+    assert_eq!(err.failure, ProviderFailure::BillingExhausted);
+
+    let err = response(429, r#"{"error":{"message":"Slow down","type":"requests","code":"rate_limit_exceeded"}}"#);
+    assert_eq!(err.failure, ProviderFailure::RateLimited);
+
+    let err = response(
+        404,
+        r#"{"error":{"message":"The model `gpt-9` does not exist","type":"invalid_request_error","code":"model_not_found"}}"#,
+    );
+    assert_eq!(err.failure, ProviderFailure::ModelNotFound);
+}
+
+#[test]
+fn local_servers_and_bare_statuses_fall_back_sensibly() {
+    let err = response(404, r#"{"error":"model \"llama9\" not found, try pulling it first"}"#);
+    assert_eq!(err.failure, ProviderFailure::ModelNotFound);
+    assert_eq!(err.message, "model \"llama9\" not found, try pulling it first");
+
+    let err = response(403, "Forbidden");
+    assert_eq!(err.failure, ProviderFailure::InvalidKey);
+    assert_eq!(err.message, "Forbidden");
+    assert_eq!(response(500, "oops").failure, ProviderFailure::Other);
+
+    let missing: ProviderTestError = ApiError::NoApiKey {
+        provider: "openai".to_string(),
+    }
+    .into();
+    assert_eq!(missing.failure, ProviderFailure::InvalidKey);
+    assert_eq!(missing.status, None);
+}
+
+#[test]
+fn probes_are_reused_until_they_expire() {
+    let provider = LlmProviderConfig {
+        preset: LlmProviderPreset::Ollama,
+        api_key: None,
+        base_url: Some("http://localhost:11434/v1".to_string()),
+        model: Some("llama3".to_string()),
+        reasoning_model: false,
+    };
+    let other_model = LlmProviderConfig {
+        model: Some("qwen2".to_string()),
+        ..provider.clone()
+    };
+
+    let mut cache = ProbeCache::new(Duration::from_secs(60));
+    assert!(cache.get(&provider).is_none());
+    cache.put(&provider, &Ok(ProviderProbe::new("provider: ollama".to_string())));
+
+    let hit = cache.get(&provider).unwrap().unwrap();
+    assert!(hit.cached);
+    assert_eq!(hit.details, "provider: ollama");
+    assert!(cache.get(&other_model).is_none());
+
+    let failed: ProbeResult = Err(response(401, "{}"));
+    cache.put(&other_model, &failed);
+    assert!(cache.get(&other_model).unwrap().unwrap_err().cached);
+
+    let mut expired = ProbeCache::new(Duration::ZERO);
+    expired.put(&provider, &Ok(ProviderProbe::new(String::new())));
+    assert!(expired.get(&provider).is_none());
+}
@@ -0,0 +1,102 @@
+//! Read-along HTML export: word timings, escaping and direction.
+
+use boka_core::export::readalong::{estimate_word_timings, readalong_html, BlockAudio};
+use boka_core::export::{default_path, file_stem};
+use boka_core::stories::{find_doc, DocId};
+
+use serde_json::json;
+use std::path::Path;
+
+fn library() -> serde_json::Value {
+    json!([{
+        "id": "story-1",
+        "title": "Cats & <Dogs>",
+        "translations": {
+            "ar": {
+                "language": "ar",
+                "doc": {
+                    "tokens": [
+                        { "type": "span", "spanId": "s1" },
+                        { "type": "text", "value": "\n\n" },
+                        { "type": "span", "spanId": "s2" }
+                    ],
+                    "spans": {
+                        "s1": {
+                            "id": "s1",
+                            "sourceText": "The cat sleeps.",
+                            "activeVariantIndex": 0,
+                            "variants": [{ "id": "v1", "register": "neutral", "text": "القطة نائمة." }]
+                        },
+                        "s2": {
+                            "id": "s2",
+                            "sourceText": "Hello </script>",
+                            "activeVariantIndex": 0,
+                            "variants": [{ "id": "v2", "register": "neutral", "text": "Hello </script>" }]
+                        }
+                    },
+                    "direction": "rtl",
+                    "blockDirections": ["rtl", "ltr"]
+                }
+            }
+        }
+    }])
+}
+
+#[test]
+fn timings_cover_the_audio_in_order() {
+    let timings = estimate_word_timings("The cat sleeps. It dreams, softly.", "en", 3400);
+    let words: Vec<&str> = timings.iter().map(|t| t.word.as_str()).collect();
+    assert_eq!(words, ["The", "cat", "sleeps", "It", "dreams", "softly"]);
+
+    assert_eq!(timings[0].start_ms, 0);
+    assert_eq!(timings.last().unwrap().end_ms, 3400);
+    for pair in timings.windows(2) {
+        assert_eq!(pair[0].end_ms, pair[1].start_ms);
+    }
+    // A sentence end holds the highlight longer than a same-length word.
+    let span = |i: usize| timings[i].end_ms - timings[i].start_ms;
+    assert!(span(2) > span(4));
+
+    assert!(estimate_word_timings("...", "en", 1000).is_empty());
+}
+
+#[test]
+fn page_escapes_text_and_keeps_block_directions() {
+    let story = find_doc(&library(), &DocId::parse("story-1:ar").unwrap()).unwrap();
+    let audio = vec![
+        Some(BlockAudio {
+            wav_base64: "UklGRg==".to_string(),
+            duration_ms: 1200,
+        }),
+        None,
+    ];
+    let html = readalong_html(&story, &audio).unwrap();
+
+    assert!(html.contains(r#"<html lang="ar" dir="rtl">"#));
+    assert!(html.contains("<title>Cats &amp; &lt;Dogs&gt;</title>"));
+    assert!(html.contains(r#"data-block="0" dir="rtl""#));
+    assert!(html.contains(r#"data-block="1" dir="ltr""#));
+    assert!(html.contains(r#"<span class="w" id="w0-0">القطة</span>"#));
+    assert!(html.contains(r#"<audio id="a0""#));
+    assert!(!html.contains(r#"<audio id="a1""#));
+
+    // Doc text must not close the embedded JSON early.
+    assert!(html.contains(r#"&lt;/<span class="w" id="w1-1">script</span>&gt;"#));
+    assert_eq!(html.matches("</script>").count(), 3);
+
+    let timings = html.split(r#"id="boka-timings">"#).nth(1).unwrap();
+    let timings: serde_json::Value = serde_json::from_str(timings.split("</script>").next().unwrap()).unwrap();
+    assert_eq!(timings[0]["block"], 0);
+    assert_eq!(timings[0]["estimated"], true);
+    assert_eq!(timings[0]["words"][1]["word"], "نائمة");
+}
+
+#[test]
+fn default_export_path_uses_a_safe_name() {
+    let story = find_doc(&library(), &DocId::parse("story-1:ar").unwrap()).unwrap();
+    assert_eq!(
+        default_path(Path::new("/data"), &story, "html"),
+        Path::new("/data/exports/cats-dogs-ar.html")
+    );
+    assert_eq!(file_stem("  ", "fr"), "story-fr");
+}
@@ -0,0 +1,31 @@
+use boka_core::reasoning::{clean_output, last_json_value, strip_reasoning};
+
+#[test]
+fn think_blocks_are_removed() {
+    assert_eq!(strip_reasoning("<think>hmm</think>\nBonjour."), "Bonjour.");
+    assert_eq!(strip_reasoning("A <thinking>x</thinking>B <think>y</think>C"), "A B C");
+    assert_eq!(strip_reasoning("No reasoning here."), "No reasoning here.");
+}
+
+#[test]
+fn open_or_orphaned_tags_are_handled() {
+    // Cut off while still thinking: there is no answer.
+    assert_eq!(strip_reasoning("<think>Let me consider"), "");
+    // Opening tag supplied by the chat template.
+    assert_eq!(strip_reasoning("Let me consider…\n</think>\n\nBonjour."), "Bonjour.");
+}
+
+#[test]
+fn last_json_value_skips_prose_and_strings() {
+    let text = r#"Draft: {"a": 1} final: {"b": "} not the end ]"} done"#;
+    assert_eq!(last_json_value(text), Some(r#"{"b": "} not the end ]"}"#));
+    assert_eq!(last_json_value(r#"[1, 2] then [see above]"#), Some("[1, 2]"));
+    assert_eq!(last_json_value("no json"), None);
+}
+
+#[test]
+fn clean_output_only_narrows_json_calls() {
+    let raw = "<think>{\"draft\": true}</think>Result: {\"adequacy\": 4} Thanks!";
+    assert_eq!(clean_output(raw, true), "{\"adequacy\": 4}");
+    assert_eq!(clean_output(raw, false), "Result: {\"adequacy\": 4} Thanks!");
+}
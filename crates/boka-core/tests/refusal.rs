@@ -0,0 +1,44 @@
+//! Telling a content-policy refusal from a translation that happens to
+//! apologise.
+
+use boka_core::refusal::{check, is_refusal};
+use boka_core::types::ApiError;
+
+#[test]
+fn refusals_are_recognised() {
+    for reply in [
+        "I'm sorry, but I can't help with that.",
+        "I can’t assist with translating explicit content.",
+        "\"I cannot comply with this request.\"",
+        "As an AI, I must follow content guidelines and cannot translate this.",
+    ] {
+        assert!(is_refusal(reply), "{}", reply);
+    }
+}
+
+#[test]
+fn translations_that_apologise_are_not_refusals() {
+    let long = format!("I'm sorry, said the baker. {}", "He could not help with that. ".repeat(20));
+    for reply in [
+        "Je suis désolé, mais je ne peux pas t'aider.",
+        "I can't help with the harvest this year.",
+        "I'm sorry I was late.",
+        "{\"text\": \"I'm sorry, but I can't help with that.\"}",
+        long.as_str(),
+        "",
+    ] {
+        assert!(!is_refusal(reply), "{}", reply);
+    }
+}
+
+#[test]
+fn flagged_replies_are_refused_even_when_empty() {
+    assert!(check("anthropic", "claude", "Le chat dort.", false).is_ok());
+    match check("openai", "gpt", "", true) {
+        Err(ApiError::Refused { provider, model, reason }) => {
+            assert_eq!((provider.as_str(), model.as_str()), ("openai", "gpt"));
+            assert!(reason.contains("content filter"));
+        }
+        other => panic!("expected a refusal, got {:?}", other),
+    }
+}
@@ -0,0 +1,58 @@
+//! Block text with the reader's variant picks, as spoken, exported and copied.
+
+use boka_core::gui_types::InteractiveDoc;
+use boka_core::render::{render_block_text, RenderError};
+
+use serde_json::json;
+use std::collections::BTreeMap;
+
+fn doc() -> InteractiveDoc {
+    let variants = |texts: &[&str]| -> Vec<serde_json::Value> {
+        texts
+            .iter()
+            .enumerate()
+            .map(|(i, t)| json!({ "id": format!("v{}", i), "register": "neutral", "text": t }))
+            .collect()
+    };
+    serde_json::from_value(json!({
+        "tokens": [
+            { "type": "span", "spanId": "span-1", "style": "emphasis" },
+            { "type": "text", "value": " dort." },
+            { "type": "check", "question": "Le chat dort ?", "answer": true },
+            { "type": "text", "value": "\n\n" },
+            { "type": "text", "value": "Le chien " },
+            { "type": "span", "spanId": "span-2" },
+            { "type": "text", "value": "." }
+        ],
+        "spans": {
+            "span-1": { "id": "span-1", "sourceText": "Le chat", "activeVariantIndex": 0,
+                        "variants": variants(&["Le chat", "Le matou"]) },
+            "span-2": { "id": "span-2", "sourceText": "aboie", "activeVariantIndex": 1,
+                        "variants": variants(&["aboie", "jappe"]) }
+        }
+    }))
+    .unwrap()
+}
+
+#[test]
+fn blocks_render_as_saved_without_selections() {
+    let doc = doc();
+    let none = BTreeMap::new();
+    let rendered: Vec<String> = (0..2).map(|b| render_block_text(&doc, b, &none).unwrap()).collect();
+    assert_eq!(rendered, doc.block_texts());
+    assert_eq!(rendered, ["Le chat dort.", "Le chien jappe."]);
+}
+
+#[test]
+fn selections_pick_variants_in_their_own_block() {
+    let doc = doc();
+    let picks = BTreeMap::from([("span-1".to_string(), 1), ("span-2".to_string(), 0)]);
+    assert_eq!(render_block_text(&doc, 0, &picks).unwrap(), "Le matou dort.");
+    assert_eq!(render_block_text(&doc, 1, &picks).unwrap(), "Le chien aboie.");
+
+    assert!(matches!(render_block_text(&doc, 2, &picks), Err(RenderError::NoBlock(2))));
+    let bad = BTreeMap::from([("span-2".to_string(), 5)]);
+    assert!(matches!(render_block_text(&doc, 1, &bad), Err(RenderError::NoVariant { index: 5, .. })));
+    // A bad pick elsewhere in the doc does not matter to this block.
+    assert_eq!(render_block_text(&doc, 0, &bad).unwrap(), "Le chat dort.");
+}
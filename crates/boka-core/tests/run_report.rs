@@ -0,0 +1,163 @@
+//! Run reports: what a job records, and saving/loading them.
+
+use boka_core::gui_types::{ErrorPolicy, Granularity, InteractiveDoc, TranslationJob};
+use boka_core::length_guard::LengthGuard;
+use boka_core::policy::ContentPolicy;
+use boka_core::report::{model_stats, ModelStats, ReportError, RunReport, RunStage, RunStatus};
+use boka_core::settings::VariantBounds;
+use boka_core::translation::{run_translation_with_report, TranslationArgs, TranslationResult};
+use boka_core::types::{ApiError, LlmProviderConfig, LlmProviderPreset};
+
+use std::fs;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+async fn run(
+    story: &str,
+    fixture: &str,
+    content_policy: Option<ContentPolicy>,
+) -> (Result<TranslationResult, ApiError>, RunReport) {
+    run_translation_with_report(TranslationArgs {
+        story_text: story.to_string(),
+        job_id: "job-report".to_string(),
+        target_language: "fr".to_string(),
+        source_language: Some("en".to_string()),
+        adult_mode: false,
+        content_policy,
+        dense_spans: false,
+        reproducible: false,
+        prompt_overrides: Default::default(),
+        judge: None,
+        variant_bounds: VariantBounds::default(),
+        length_guard: LengthGuard::default(),
+        simplify_level: None,
+        dual_output: false,
+        refine_variants: false,
+        granularity: Granularity::Sentence,
+        comprehension_every: None,
+        annotations: Default::default(),
+        review: None,
+        error_policy: ErrorPolicy::Abort,
+        budget: None,
+        budget_gate: None,
+        confirmation: None,
+        translation_cache: None,
+        transcript: None,
+        ui_language: None,
+        provider: LlmProviderConfig {
+            preset: LlmProviderPreset::Mock,
+            api_key: None,
+            base_url: Some(format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), fixture)),
+            model: None,
+            reasoning_model: false,
+        },
+        cancelled: Arc::new(AtomicBool::new(false)),
+        on_job: Box::new(|_: &TranslationJob| async {}),
+        on_doc: Box::new(|_: &InteractiveDoc| async {}),
+    })
+    .await
+}
+
+#[tokio::test]
+async fn completed_job_reports_stages_segments_and_tokens() {
+    let (result, report) = run("The cat sleeps. The dog barks.", "happy_path.json", None).await;
+    let result = result.expect("translation should succeed");
+
+    assert_eq!(report.status, RunStatus::Completed);
+    assert_eq!(report.error, None);
+    assert_eq!(report.provider, Some(LlmProviderPreset::Mock));
+    assert_eq!(report.model.as_deref(), Some("mock"));
+    assert!(report.finished_at >= report.started_at);
+    assert_eq!(report.usage, result.usage);
+
+    let ids: Vec<&str> = report.segments.iter().map(|s| s.id.as_str()).collect();
+    assert_eq!(ids, ["seg-1", "seg-2"]);
+    assert!(report.segments.iter().all(|s| s.model == "mock" && s.retries == 0 && s.warnings.is_empty()));
+
+    let calls = |stage: RunStage| report.stages.iter().find(|s| s.stage == stage).map(|s| s.calls);
+    assert_eq!(calls(RunStage::Translate), Some(2));
+    assert_eq!(calls(RunStage::Plan), Some(2));
+    assert!(calls(RunStage::Variants).is_some());
+    assert_eq!(calls(RunStage::Judge), None);
+}
+
+#[tokio::test]
+async fn moderation_retries_and_drops_are_reported() {
+    let (result, report) = run("The cat sleeps.", "moderation_retry.json", Some(ContentPolicy::child_safe())).await;
+    result.expect("translation should succeed");
+
+    let segment = &report.segments[0];
+    assert_eq!(segment.retries, 1);
+    assert_eq!(segment.warnings.len(), 1);
+    assert!(segment.warnings[0].starts_with("moderation dropped 1 of"), "{:?}", segment.warnings);
+    assert_eq!(report.warning_count(), 1);
+}
+
+#[tokio::test]
+async fn repeated_parse_failures_switch_the_job_to_the_simple_format() {
+    let (result, report) = run("The cat sleeps. The dog barks.", "simple_format_fallback.json", None).await;
+    let result = result.expect("translation should succeed in the simple format");
+
+    assert_eq!(report.status, RunStatus::Completed);
+    assert_eq!(report.simple_format_from.as_deref(), Some("seg-1"));
+    assert_eq!((report.parse_failures, report.truncations), (2, 1));
+    assert_eq!(report.segments[0].retries, 2);
+    assert_eq!(report.segments[0].warnings.len(), 1);
+    assert_eq!(report.segments[1].retries, 0);
+    assert!(report.to_markdown().contains("- Simple output format from: seg-1\n"));
+
+    let counts: Vec<u32> = result.job.segments.iter().map(|s| s.variant_count).collect();
+    assert_eq!(counts, [2, 2]);
+}
+
+#[tokio::test]
+async fn failed_job_is_reported_and_round_trips() {
+    let (result, report) = run("The cat sleeps.", "truncated_plan.json", None).await;
+    assert!(result.is_err());
+    assert_eq!(report.status, RunStatus::Failed);
+    assert!(report.error.as_deref().is_some_and(|e| e.contains("Simple format")));
+    // Two truncated JSON plans, then an unusable simple-format one.
+    assert_eq!((report.parse_failures, report.truncations), (3, 2));
+    assert_eq!(report.segments[0].retries, 2);
+    assert_eq!(report.simple_format_from.as_deref(), Some("seg-1"));
+
+    let dir = std::env::temp_dir().join(format!("boka-reports-{}", std::process::id()));
+    report.save(&dir).unwrap();
+    assert_eq!(RunReport::load(&dir, "job-report").unwrap(), report);
+    let md = fs::read_to_string(dir.join("reports").join("job-report.md")).unwrap();
+    assert!(md.starts_with("# Run report: job-report\n"));
+    assert!(md.contains("- Status: failed\n"));
+    assert!(md.contains("| Plan | 3 |"));
+    assert!(md.contains("- Simple output format from: seg-1\n"));
+
+    assert!(matches!(RunReport::load(&dir, "job-missing"), Err(ReportError::NotFound(_))));
+    assert!(matches!(RunReport::load(&dir, "../settings"), Err(ReportError::InvalidJobId(_))));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn model_stats_aggregate_saved_reports() {
+    let (_, ok) = run("The cat sleeps. The dog barks.", "happy_path.json", None).await;
+    let (_, failed) = run("The cat sleeps.", "truncated_plan.json", None).await;
+
+    let stats = ModelStats::from_reports([&ok, &failed]);
+    assert_eq!(stats.len(), 1);
+    let mock = &stats[0];
+    assert_eq!((mock.provider, mock.model.as_str()), (LlmProviderPreset::Mock, "mock"));
+    assert_eq!((mock.jobs, mock.failed_jobs, mock.segments), (2, 1, 3));
+    assert_eq!((mock.parse_failures, mock.truncations), (3, 2));
+    let calls: u32 = [&ok, &failed].iter().flat_map(|r| &r.stages).map(|s| s.calls).sum();
+    assert_eq!(mock.calls, calls);
+    assert_eq!(mock.parse_failure_rate, 3.0 / calls as f64);
+
+    let dir = std::env::temp_dir().join(format!("boka-stats-{}", std::process::id()));
+    assert!(model_stats(&dir).is_empty());
+    let mut second = failed.clone();
+    second.job_id = "job-other".to_string();
+    for report in [&ok, &second] {
+        report.save(&dir).unwrap();
+    }
+    fs::write(dir.join("reports").join("broken.json"), "{").unwrap();
+    assert_eq!(model_stats(&dir), stats);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,72 @@
+//! The runtime report: features, model presence and cache usage.
+
+use boka_core::doc_cache::DocCache;
+use boka_core::jsonl::from_jsonl;
+use boka_core::runtime_info::{dir_usage, enabled_features, model_presence, resident_bytes, CacheUsage};
+use boka_core::stories::{self, put_doc};
+use boka_core::tts_models::TtsModelRegistry;
+
+use serde_json::json;
+use std::fs;
+use std::path::PathBuf;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("boka-runtime-info-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn features_match_the_build() {
+    assert_eq!(enabled_features().contains(&"tts".to_string()), cfg!(feature = "tts"));
+    assert!(!enabled_features().contains(&"stt".to_string()));
+    if cfg!(target_os = "linux") {
+        assert!(resident_bytes().is_some_and(|bytes| bytes > 0));
+    }
+}
+
+#[test]
+fn models_report_installed_and_loaded_separately() {
+    let dir = temp_dir("models");
+    let registry = TtsModelRegistry::bundled();
+    let presence = model_presence(&registry, &dir, &["kokoro-82m".to_string()]);
+    let summary: Vec<_> = presence.iter().map(|m| (m.id.as_str(), m.installed, m.loaded)).collect();
+    // The hub model fetches on first load, so it counts as installed; the
+    // quantized one needs its files.
+    assert_eq!(summary, [("kokoro-82m", true, true), ("kokoro-82m-q8", false, false)]);
+}
+
+#[test]
+fn cache_usage_counts_files_on_disk_and_docs_in_memory() {
+    let dir = temp_dir("audio");
+    fs::create_dir_all(dir.join("fr")).unwrap();
+    fs::write(dir.join("a.wav"), [0u8; 100]).unwrap();
+    fs::write(dir.join("fr/b.wav"), [0u8; 50]).unwrap();
+    let expected = CacheUsage {
+        name: "audioCache".to_string(),
+        entries: 2,
+        bytes: 150,
+    };
+    assert_eq!(dir_usage("audioCache", &dir), expected);
+    assert_eq!(dir_usage("audioCache", &dir.join("missing")).entries, 0);
+
+    let data = temp_dir("docs");
+    let doc = from_jsonl(concat!(
+        r#"{"type":"story","format":"boka-jsonl","version":1,"storyId":"s1","title":"T","#,
+        r#""sourceLanguage":"en","sourceText":"Hello.","language":"fr"}"#,
+        "\n",
+        r#"{"type":"span","id":"a","sourceText":"Hello.","activeVariantIndex":0,"#,
+        r#""variants":[{"id":"a1","register":"neutral","text":"Bonjour."}]}"#,
+        "\n"
+    ))
+    .unwrap();
+    let mut all = json!([]);
+    let id = put_doc(&mut all, &doc).unwrap();
+    stories::save(&data, &all).unwrap();
+
+    let mut cache = DocCache::new(4);
+    assert_eq!(cache.approx_bytes(), 0);
+    cache.get(&data, &id).unwrap();
+    assert!(cache.approx_bytes() > "Hello.".len() as u64);
+}
@@ -0,0 +1,175 @@
+//! Segment granularity: clause, sentence and paragraph splitting, the prompts
+//! that go with each, and the blocks a job ends up with. Overlong segments
+//! are translated in pieces.
+
+use boka_core::analysis::estimate_tokens;
+use boka_core::gui_types::{ErrorPolicy, Granularity, InteractiveDoc, TranslationJob};
+use boka_core::length_guard::LengthGuard;
+use boka_core::settings::VariantBounds;
+use boka_core::translation::{
+    preview_prompts, run_translation, split_overlong, split_segments, PromptOptions, TranslationArgs,
+    TranslationResult, MAX_SEGMENT_TOKENS,
+};
+use boka_core::types::{LlmProviderConfig, LlmProviderPreset};
+
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+const STORY: &str = "When the rain stopped, the children ran outside, laughing. Oui, d'accord.\n\
+                     It weighs 3,5 kilos.\n  \n\
+                     The end.";
+
+fn echo_provider() -> LlmProviderConfig {
+    LlmProviderConfig {
+        preset: LlmProviderPreset::Mock,
+        api_key: None,
+        base_url: None,
+        model: None,
+        reasoning_model: false,
+    }
+}
+
+async fn translate(story: &str, granularity: Granularity) -> TranslationResult {
+    run_translation(TranslationArgs {
+        story_text: story.to_string(),
+        job_id: "job-granularity".to_string(),
+        target_language: "fr".to_string(),
+        source_language: None,
+        adult_mode: false,
+        content_policy: None,
+        dense_spans: false,
+        reproducible: false,
+        prompt_overrides: Default::default(),
+        judge: None,
+        variant_bounds: VariantBounds::default(),
+        length_guard: LengthGuard::default(),
+        simplify_level: None,
+        dual_output: false,
+        refine_variants: false,
+        granularity,
+        comprehension_every: None,
+        annotations: Default::default(),
+        review: None,
+        error_policy: ErrorPolicy::Abort,
+        budget: None,
+        budget_gate: None,
+        confirmation: None,
+        translation_cache: None,
+        transcript: None,
+        ui_language: None,
+        provider: echo_provider(),
+        cancelled: Arc::new(AtomicBool::new(false)),
+        on_job: Box::new(|_: &TranslationJob| async {}),
+        on_doc: Box::new(|_: &InteractiveDoc| async {}),
+    })
+    .await
+    .expect("translation should succeed")
+}
+
+#[test]
+fn splits_at_each_granularity() {
+    assert_eq!(
+        split_segments(STORY, Granularity::Sentence),
+        [
+            "When the rain stopped, the children ran outside, laughing.",
+            "Oui, d'accord.",
+            "It weighs 3,5 kilos.",
+            "The end."
+        ]
+    );
+    // Short pieces stay with a neighbour; a comma inside a number is no cut.
+    assert_eq!(
+        split_segments(STORY, Granularity::Clause),
+        [
+            "When the rain stopped,",
+            "the children ran outside, laughing.",
+            "Oui, d'accord.",
+            "It weighs 3,5 kilos.",
+            "The end."
+        ]
+    );
+    assert_eq!(
+        split_segments(STORY, Granularity::Paragraph),
+        [
+            "When the rain stopped, the children ran outside, laughing. Oui, d'accord.\nIt weighs 3,5 kilos.",
+            "The end."
+        ]
+    );
+    assert!(split_segments("  \n\n ", Granularity::Paragraph).is_empty());
+}
+
+#[test]
+fn prompts_describe_the_segment_size() {
+    let prompts = |granularity: Granularity| {
+        preview_prompts(PromptOptions {
+            target_language: "fr".to_string(),
+            source_language: None,
+            adult_mode: false,
+            content_policy: None,
+            dense_spans: false,
+            simplify_level: None,
+            prompt_overrides: Default::default(),
+            refine_variants: false,
+            granularity,
+            ui_language: None,
+            provider: echo_provider(),
+        })
+        .prompts
+    };
+    let sentence = prompts(Granularity::Sentence);
+    let paragraph = prompts(Granularity::Paragraph);
+    let clause = prompts(Granularity::Clause);
+    assert!(paragraph.base_translation.contains("whole paragraphs"));
+    assert!(paragraph.span_planning.contains("per sentence"));
+    assert!(clause.base_translation.contains("single clauses"));
+    assert!(!sentence.base_translation.contains("whole paragraphs") && !sentence.base_translation.contains("clauses"));
+}
+
+#[tokio::test]
+async fn jobs_get_one_block_per_segment() {
+    for (granularity, blocks) in [(Granularity::Clause, 5), (Granularity::Sentence, 4), (Granularity::Paragraph, 2)] {
+        let result = translate(STORY, granularity).await;
+        assert_eq!(result.job.segments.len(), blocks, "{:?}", granularity);
+        assert_eq!(result.doc.block_texts().len(), blocks, "{:?}", granularity);
+        assert_eq!(result.job.metadata.unwrap().granularity, granularity);
+    }
+}
+
+/// One run-on sentence of about 600 words.
+fn run_on() -> String {
+    let clause = "the old man walked slowly down the long road towards the sea, and the dog followed him";
+    let mut text = vec![clause; 30].join(" while ");
+    text.push('.');
+    text
+}
+
+#[test]
+fn overlong_segments_are_cut_at_clause_boundaries() {
+    assert_eq!(split_overlong("A short sentence.", 10), ["A short sentence."]);
+
+    let text = run_on();
+    let pieces = split_overlong(&text, MAX_SEGMENT_TOKENS);
+    assert!(pieces.len() > 1);
+    assert!(pieces.iter().all(|p| estimate_tokens(p) <= MAX_SEGMENT_TOKENS));
+    assert!(pieces[..pieces.len() - 1].iter().all(|p| p.ends_with(',')));
+    assert_eq!(pieces.join(" "), text);
+
+    // Without punctuation, cut before a conjunction, then between words.
+    let pieces = split_overlong("one two three and four five six", 5);
+    assert_eq!(pieces, ["one two three", "and four five six"]);
+    let pieces = split_overlong("alpha beta gamma delta epsilon", 3);
+    assert!(pieces.len() > 1 && pieces.join(" ") == "alpha beta gamma delta epsilon");
+}
+
+#[tokio::test]
+async fn overlong_segments_are_translated_in_pieces() {
+    let text = run_on();
+    let result = translate(&text, Granularity::Sentence).await;
+    let segment = &result.job.segments[0];
+    assert!(segment.auto_split);
+    // The echo provider gives each piece back; joined, they are the source.
+    assert_eq!(segment.base_text.as_deref(), Some(text.as_str()));
+
+    let short = translate(STORY, Granularity::Sentence).await;
+    assert!(short.job.segments.iter().all(|s| !s.auto_split));
+}
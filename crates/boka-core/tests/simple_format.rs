@@ -0,0 +1,44 @@
+//! The line-based fallback format for planning and variant calls.
+
+use boka_core::anthropic::PlannedSegment;
+use boka_core::simple_format::{parse_simple_plan, parse_simple_variants};
+use boka_core::types::ApiError;
+
+fn describe(segments: &[PlannedSegment]) -> Vec<String> {
+    segments
+        .iter()
+        .map(|s| match s {
+            PlannedSegment::Static(text) => text.clone(),
+            PlannedSegment::Swappable(span) => format!("[{}]", span.variants[0].text),
+        })
+        .collect()
+}
+
+#[test]
+fn plan_finds_listed_phrases_in_order() {
+    let output = "```\n1. Le vieux chat\n2) \"dort\"\n- pas dans le texte\n```";
+    let block = parse_simple_plan(output, "Le vieux chat dort au soleil.").unwrap();
+    assert_eq!(describe(&block.segments), ["[Le vieux chat]", " ", "[dort]", " au soleil."]);
+}
+
+#[test]
+fn plan_without_any_matching_phrase_is_a_parse_error() {
+    match parse_simple_plan("Le chien", "Le chat dort.") {
+        Err(ApiError::Parse(msg)) => assert!(msg.starts_with("Simple format:"), "{}", msg),
+        other => panic!("expected parse error, got {:?}", other),
+    }
+}
+
+#[test]
+fn variant_fields_after_the_text_are_optional() {
+    let variants = parse_simple_variants("Le chat\n* Le matou | Colloquial | Informal tomcat | 9\n\n").unwrap();
+    let fields: Vec<(&str, &str, &str, u8)> = variants
+        .iter()
+        .map(|v| (v.text.as_str(), v.register.as_str(), v.note.as_str(), v.difficulty))
+        .collect();
+    assert_eq!(
+        fields,
+        [("Le chat", "neutral", "", 2), ("Le matou", "colloquial", "Informal tomcat", 5)]
+    );
+    assert!(parse_simple_variants("```\n```").is_err());
+}
@@ -0,0 +1,26 @@
+//! Vocabulary checks for graded-reader output.
+
+use boka_core::simplify::{check_vocabulary, retry_note, CefrLevel};
+
+#[test]
+fn flags_long_sentences_and_hard_words() {
+    let text = "Marie regarde le chat. Le félin sommeille paisiblement sur le canapé de la grand-mère de son voisin.";
+    let check = check_vocabulary(text, "fr", CefrLevel::A1);
+
+    assert!(!check.passed);
+    assert_eq!(check.long_sentences, 1);
+    assert_eq!(check.hard_words, ["sommeille", "paisiblement", "grand-mère"]);
+
+    let note = retry_note(&check);
+    assert!(note.contains("longer than 8 words"));
+    assert!(note.contains("paisiblement"));
+
+    assert!(check_vocabulary(text, "fr", CefrLevel::C2).passed);
+}
+
+#[test]
+fn levels_round_trip_as_cefr_codes() {
+    assert_eq!(serde_json::to_string(&CefrLevel::B1).unwrap(), "\"B1\"");
+    assert_eq!(serde_json::from_str::<CefrLevel>("\"A2\"").unwrap(), CefrLevel::A2);
+    assert!(CefrLevel::A1 < CefrLevel::C1);
+}
@@ -0,0 +1,166 @@
+//! Dialogue voices: blocks split into narration and dialogue, lines
+//! attributed by the model and edited by hand, and each part read with its
+//! speaker's voice.
+
+use boka_core::audio_types::VoiceInfo;
+use boka_core::speakers::{
+    self, split_dialogue, Passage, PassageKind, SpeakerEdit, SpeakerError, SpeakerMap, SpeakerView,
+};
+use boka_core::stories::{self, DocId};
+use boka_core::types::{LlmProviderConfig, LlmProviderPreset};
+
+use serde_json::{json, Value};
+
+fn mock() -> LlmProviderConfig {
+    LlmProviderConfig {
+        preset: LlmProviderPreset::Mock,
+        ..Default::default()
+    }
+}
+
+fn library() -> Value {
+    json!([
+        { "id": "story-1", "title": "Le loup", "sourceLanguage": "en", "translations": { "fr": {
+            "doc": { "tokens": [
+                { "type": "text", "value": "« Qui est là ? » demanda Anna. « Le loup », dit une voix." },
+                { "type": "text", "value": "\n\n" },
+                { "type": "text", "value": "Il pleuvait.\n— Entre vite !" }
+            ], "spans": {} }
+        } } }
+    ])
+}
+
+fn passage(kind: PassageKind, text: &str) -> Passage {
+    Passage { kind, text: text.to_string() }
+}
+
+fn voice(id: &str, language: &str) -> VoiceInfo {
+    VoiceInfo {
+        id: id.to_string(),
+        name: id.to_string(),
+        language: language.to_string(),
+        sample_url: None,
+        controls: Default::default(),
+    }
+}
+
+#[test]
+fn blocks_split_into_narration_and_dialogue() {
+    use PassageKind::{Dialogue, Narration};
+    assert_eq!(
+        split_dialogue("„Komm her“, rief sie. \"Yes,\" he said."),
+        [
+            passage(Dialogue, "„Komm her“"),
+            passage(Narration, ", rief sie."),
+            passage(Dialogue, "\"Yes,\""),
+            passage(Narration, "he said.")
+        ]
+    );
+    assert_eq!(
+        split_dialogue("Il pleuvait.\n— Entre vite !\n  — Merci."),
+        [passage(Narration, "Il pleuvait."), passage(Dialogue, "— Entre vite !"), passage(Dialogue, "— Merci.")]
+    );
+    // A quote that never closes, or a dash inside a sentence, is narration.
+    let unclosed = "He said « nothing – at all.";
+    assert_eq!(split_dialogue(unclosed), [passage(Narration, unclosed)]);
+    assert_eq!(split_dialogue("「行こう」と言った。")[0], passage(Dialogue, "「行こう」"));
+}
+
+#[tokio::test]
+async fn lines_are_attributed_edited_and_voiced() {
+    let mut all = library();
+    let doc_id = DocId::parse("story-1:fr").unwrap();
+    let story = stories::find_doc(&all, &doc_id).unwrap();
+
+    let previous = SpeakerMap { narrator_voice: Some("ff_siwis".to_string()), ..Default::default() };
+    let map = speakers::attribute(&story, &previous, 4, mock()).await.unwrap();
+    assert_eq!(map.narrator_voice.as_deref(), Some("ff_siwis"));
+    assert_eq!(map.model.as_deref(), Some("mock"));
+    let names: Vec<&str> = map.speakers.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["Speaker 1", "Speaker 2"]);
+
+    let view = SpeakerView::new(&story.doc, map);
+    let lines: Vec<(u32, u32, &str, Option<&str>)> =
+        view.lines.iter().map(|l| (l.block, l.line, l.text.as_str(), l.speaker.as_deref())).collect();
+    assert_eq!(
+        lines,
+        [
+            (0, 0, "« Qui est là ? »", Some("speaker-1")),
+            (0, 1, "« Le loup »", Some("speaker-2")),
+            (1, 0, "— Entre vite !", Some("speaker-1"))
+        ]
+    );
+
+    let mut map = view.map;
+    let edits = [
+        SpeakerEdit::UpdateSpeaker { id: "speaker-1".to_string(), name: "Anna".to_string(), voice: None },
+        SpeakerEdit::AddSpeaker { name: "Le loup".to_string(), voice: Some("am_adam".to_string()) },
+        SpeakerEdit::AssignLine { block: 0, line: 1, speaker: Some("speaker-3".to_string()) },
+        SpeakerEdit::RemoveSpeaker { id: "speaker-2".to_string() },
+        SpeakerEdit::AssignLine { block: 1, line: 0, speaker: None },
+    ];
+    for edit in edits {
+        map.apply(edit, &story.doc).unwrap();
+    }
+    assert!(matches!(
+        map.apply(SpeakerEdit::AssignLine { block: 1, line: 1, speaker: None }, &story.doc),
+        Err(SpeakerError::LineOutOfRange { block: 1, line: 1 })
+    ));
+    assert!(matches!(
+        map.apply(SpeakerEdit::AssignLine { block: 0, line: 0, speaker: Some("x".to_string()) }, &story.doc),
+        Err(SpeakerError::UnknownSpeaker(_))
+    ));
+
+    // Anna gets a voice no one else reads with, French ones first.
+    let available = [voice("ff_siwis", "fr"), voice("am_adam", "en"), voice("ff_other", "fr"), voice("af_bella", "en")];
+    let voices = map.voices("ff_siwis", &available, "fr");
+    assert_eq!(voices["speaker-1"], "ff_other");
+    assert_eq!(voices["speaker-3"], "am_adam");
+    let blocks = story.doc.block_texts();
+    let parts = map.voice_parts(0, &blocks[0], "ff_siwis", &voices);
+    let parts: Vec<(&str, &str)> = parts.iter().map(|(t, v)| (t.as_str(), v.as_str())).collect();
+    assert_eq!(
+        parts,
+        [
+            ("« Qui est là ? »", "ff_other"),
+            ("demanda Anna.", "ff_siwis"),
+            ("« Le loup »", "am_adam"),
+            (", dit une voix.", "ff_siwis")
+        ]
+    );
+    let narrated = [("Il pleuvait. — Entre vite !".to_string(), "ff_siwis".to_string())];
+    assert_eq!(map.voice_parts(1, &blocks[1], "ff_siwis", &voices), narrated);
+
+    // The map is kept on the translation, and across frontend writes.
+    speakers::set_speaker_map(&mut all, &doc_id, &map).unwrap();
+    assert_eq!(speakers::speaker_map(&all, &doc_id).unwrap(), map);
+    let mut written = library();
+    stories::keep_meta(&mut written, &all);
+    assert_eq!(speakers::speaker_map(&written, &doc_id).unwrap(), map);
+}
+
+#[test]
+fn adding_a_taken_name_voices_that_speaker() {
+    let story = stories::find_doc(&library(), &DocId::parse("story-1:fr").unwrap()).unwrap();
+    let mut map = SpeakerMap::default();
+    let edits = [
+        SpeakerEdit::AddSpeaker { name: "Anna".to_string(), voice: None },
+        SpeakerEdit::AddSpeaker { name: "Le loup".to_string(), voice: None },
+        SpeakerEdit::AddSpeaker { name: " anna ".to_string(), voice: Some("ff_siwis".to_string()) },
+    ];
+    for edit in edits {
+        map.apply(edit, &story.doc).unwrap();
+    }
+    let speakers: Vec<(&str, Option<&str>)> =
+        map.speakers.iter().map(|s| (s.name.as_str(), s.voice.as_deref())).collect();
+    assert_eq!(speakers, [("Anna", Some("ff_siwis")), ("Le loup", None)]);
+}
+
+#[test]
+fn speakers_need_a_name() {
+    let story = stories::find_doc(&library(), &DocId::parse("story-1:fr").unwrap()).unwrap();
+    let mut map = SpeakerMap::default();
+    let blank = SpeakerEdit::AddSpeaker { name: "  ".to_string(), voice: Some("ff_siwis".to_string()) };
+    assert!(matches!(map.apply(blank, &story.doc), Err(SpeakerError::EmptyName)));
+    assert!(map.speakers.is_empty());
+}
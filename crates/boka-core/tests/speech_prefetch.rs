@@ -0,0 +1,86 @@
+//! Speech prefetch follows the reader: the next few sentences past the
+//! reported position, or the rest of the doc, each synthesized once.
+
+use boka_core::gui_types::InteractiveDoc;
+use boka_core::speech_prefetch::{doc_sentences, PrefetchPolicy, ReadingPosition, SpeechPrefetcher, AHEAD_SENTENCES};
+
+use serde_json::json;
+
+/// Two blocks; the first sentence is a span with two variants.
+fn doc(active_variant: usize) -> InteractiveDoc {
+    serde_json::from_value(json!({
+        "tokens": [
+            { "type": "span", "spanId": "span-1" },
+            { "type": "text", "value": " Il pleut. Le chien aboie." },
+            { "type": "text", "value": "\n\n" },
+            { "type": "text", "value": "Bonjour. Au revoir! Merci? Fin." }
+        ],
+        "spans": { "span-1": {
+            "id": "span-1",
+            "sourceText": "The cat sleeps.",
+            "variants": [
+                { "id": "v1", "register": "neutral", "text": "Le chat dort." },
+                { "id": "v2", "register": "literary", "text": "Le félin sommeille." }
+            ],
+            "activeVariantIndex": active_variant
+        } }
+    }))
+    .unwrap()
+}
+
+fn at(block: u32, sentence: u32) -> ReadingPosition {
+    serde_json::from_value(json!({ "docId": "story-1:fr", "block": block, "sentence": sentence })).unwrap()
+}
+
+fn texts(prefetcher: &mut SpeechPrefetcher, doc: &InteractiveDoc, policy: PrefetchPolicy) -> Vec<String> {
+    let mut out = Vec::new();
+    while let Some(next) = prefetcher.next(&doc_sentences(doc), policy) {
+        prefetcher.mark_done(&next.text);
+        out.push(next.text);
+    }
+    out
+}
+
+#[test]
+fn sentences_come_from_the_active_variants() {
+    let sentences = doc_sentences(&doc(1));
+    assert_eq!(sentences.len(), 7);
+    assert_eq!(sentences[0].text, "Le félin sommeille.");
+    assert_eq!((sentences[3].block, sentences[3].sentence, sentences[3].text.as_str()), (1, 0, "Bonjour."));
+}
+
+#[test]
+fn only_the_window_past_the_position_is_handed_out_once() {
+    let doc = doc(0);
+    let mut prefetcher = SpeechPrefetcher::default();
+    assert_eq!(prefetcher.next(&doc_sentences(&doc), PrefetchPolicy::Ahead), None, "no position yet");
+
+    prefetcher.report(at(0, 0));
+    let ahead = texts(&mut prefetcher, &doc, PrefetchPolicy::Ahead);
+    assert_eq!(ahead.len(), AHEAD_SENTENCES);
+    assert_eq!(ahead, ["Il pleut.", "Le chien aboie.", "Bonjour."]);
+
+    // One sentence on, one more is due; going back needs nothing.
+    prefetcher.report(at(0, 1));
+    assert_eq!(texts(&mut prefetcher, &doc, PrefetchPolicy::Ahead), ["Au revoir!"]);
+    prefetcher.report(at(0, 0));
+    assert!(texts(&mut prefetcher, &doc, PrefetchPolicy::Ahead).is_empty());
+
+    assert_eq!(texts(&mut prefetcher, &doc, PrefetchPolicy::WholeDoc), ["Merci?", "Fin."]);
+    prefetcher.report(at(1, 0));
+    assert_eq!(prefetcher.next(&doc_sentences(&doc), PrefetchPolicy::Off), None);
+}
+
+#[test]
+fn another_voice_or_speed_starts_over() {
+    let doc = doc(0);
+    let mut prefetcher = SpeechPrefetcher::default();
+    prefetcher.report(at(1, 1));
+    assert_eq!(texts(&mut prefetcher, &doc, PrefetchPolicy::Ahead), ["Merci?", "Fin."]);
+
+    prefetcher.report(ReadingPosition { speed: 0.8, ..at(1, 1) });
+    assert_eq!(texts(&mut prefetcher, &doc, PrefetchPolicy::Ahead).len(), 2);
+    prefetcher.report(ReadingPosition { voice_id: Some("ff_siwis".to_string()), speed: 0.8, ..at(1, 1) });
+    assert_eq!(texts(&mut prefetcher, &doc, PrefetchPolicy::Ahead).len(), 2);
+    assert_eq!(at(0, 0).speed, 1.0);
+}
@@ -0,0 +1,130 @@
+//! Starred spans and sentences, kept with their docs and gathered across
+//! the library for flashcards.
+
+use boka_core::starred::{set_star, starred_items, StarTarget};
+use boka_core::stories::{self, DocId, StoryError};
+
+use serde_json::{json, Value};
+
+fn translation(first: &str, segments: &[&str]) -> Value {
+    let segments: Vec<Value> = segments
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            json!({
+                "id": format!("seg-{i}"), "source": s,
+                "baseStage": "ready", "spanStage": "ready", "variantCount": 2
+            })
+        })
+        .collect();
+    json!({
+        "doc": {
+            "tokens": [
+                { "type": "span", "spanId": "span-1" },
+                { "type": "text", "value": " Il pleut." },
+                { "type": "text", "value": "\n\n" },
+                { "type": "text", "value": "Bonjour. Au revoir!" }
+            ],
+            "spans": { "span-1": {
+                "id": "span-1",
+                "sourceText": "The cat sleeps.",
+                "variants": [
+                    { "id": "v1", "register": "neutral", "text": first, "note": "dormir: to sleep" },
+                    { "id": "v2", "register": "literary", "text": "Le félin sommeille." }
+                ],
+                "activeVariantIndex": 0
+            } }
+        },
+        "job": { "id": "job-1", "segments": segments, "ready": true }
+    })
+}
+
+fn library() -> Value {
+    json!([
+        { "id": "story-1", "title": "Le chat", "translations": {
+            "fr": translation("Le chat dort.", &["The cat sleeps. It rains.", "Hello. Goodbye!"])
+        } },
+        { "id": "story-2", "title": "La pluie", "translations": {
+            "fr": translation("Il fait nuit.", &["Night falls."]),
+            "es": translation("El gato duerme.", &[])
+        } }
+    ])
+}
+
+fn id(raw: &str) -> DocId {
+    DocId::parse(raw).unwrap()
+}
+
+fn span(span_id: &str) -> StarTarget {
+    StarTarget::Span {
+        span_id: span_id.to_string(),
+    }
+}
+
+#[test]
+fn stars_are_kept_on_the_translation_once_each() {
+    let mut all = library();
+    let stars = set_star(&mut all, &id("story-1:fr"), span("span-1"), true).unwrap();
+    assert_eq!(stars.len(), 1);
+    let stars = set_star(&mut all, &id("story-1:fr"), span("span-1"), true).unwrap();
+    assert_eq!(stars.len(), 1, "starring again only moves the star");
+    let sentence = StarTarget::Sentence { block: 1, sentence: 1 };
+    set_star(&mut all, &id("story-1:fr"), sentence.clone(), true).unwrap();
+    let stored = &all[0]["translations"]["fr"]["starred"][1];
+    assert_eq!((&stored["type"], &stored["block"], &stored["sentence"]), (&json!("sentence"), &json!(1), &json!(1)));
+
+    // Only what the doc has can be starred; unstarring anything is fine.
+    let missing = set_star(&mut all, &id("story-1:fr"), span("span-9"), true);
+    assert!(matches!(missing, Err(StoryError::NotFound(_))));
+    let past_end = StarTarget::Sentence { block: 1, sentence: 2 };
+    assert!(set_star(&mut all, &id("story-1:fr"), past_end.clone(), true).is_err());
+    assert_eq!(set_star(&mut all, &id("story-1:fr"), past_end, false).unwrap().len(), 2);
+    assert!(set_star(&mut all, &id("story-3:fr"), span("span-1"), false).is_err());
+
+    let stars = set_star(&mut all, &id("story-1:fr"), span("span-1"), false).unwrap();
+    assert_eq!(stars.iter().map(|s| &s.target).collect::<Vec<_>>(), [&sentence]);
+
+    // Frontend writes keep the backend's stars.
+    let mut incoming = library();
+    stories::keep_meta(&mut incoming, &all);
+    assert_eq!(incoming[0]["translations"]["fr"]["starred"], all[0]["translations"]["fr"]["starred"]);
+}
+
+#[test]
+fn the_library_lists_stars_with_their_current_text() {
+    let mut all = library();
+    set_star(&mut all, &id("story-1:fr"), span("span-1"), true).unwrap();
+    set_star(&mut all, &id("story-1:fr"), StarTarget::Sentence { block: 1, sentence: 1 }, true).unwrap();
+    set_star(&mut all, &id("story-2:fr"), StarTarget::Sentence { block: 0, sentence: 1 }, true).unwrap();
+    set_star(&mut all, &id("story-2:es"), span("span-1"), true).unwrap();
+    // Newest first; the stars above can share a millisecond.
+    all[0]["translations"]["fr"]["starred"][0]["starredAt"] = json!(4);
+    all[0]["translations"]["fr"]["starred"][1]["starredAt"] = json!(3);
+    all[1]["translations"]["fr"]["starred"][0]["starredAt"] = json!(2);
+    all[1]["translations"]["es"]["starred"][0]["starredAt"] = json!(1);
+
+    let items = starred_items(&all, None);
+    let texts: Vec<_> = items.iter().map(|i| i.text.as_str()).collect();
+    assert_eq!(texts, ["Le chat dort.", "Au revoir!", "Il pleut.", "El gato duerme."]);
+    assert_eq!((items[0].source.as_str(), items[0].note.as_deref()), ("The cat sleeps.", Some("dormir: to sleep")));
+    assert_eq!((items[1].doc_id.as_str(), items[1].title.as_str()), ("story-1:fr", "Le chat"));
+    assert_eq!(items[1].source, "Hello. Goodbye!");
+    // One segment for two blocks: the blocks no longer line up with it.
+    assert_eq!(items[2].source, "");
+
+    let json = serde_json::to_value(&items[1]).unwrap();
+    assert_eq!((&json["type"], &json["block"], &json["starredAt"]), (&json!("sentence"), &json!(1), &json!(3)));
+    assert!(json.get("note").is_none());
+
+    // Another variant picked shows in the card; a star on a vanished span
+    // is left out.
+    all[0]["translations"]["fr"]["doc"]["spans"]["span-1"]["activeVariantIndex"] = json!(1);
+    let spans = all[1]["translations"]["es"]["doc"]["spans"].as_object_mut().unwrap();
+    let moved = spans.remove("span-1").unwrap();
+    spans.insert("span-2".to_string(), moved);
+    let items = starred_items(&all, None);
+    assert_eq!((items[0].text.as_str(), items[0].note.as_deref()), ("Le félin sommeille.", None));
+    assert_eq!(items.len(), 3);
+    assert_eq!(starred_items(&all, Some("es")).len(), 0);
+    assert_eq!(starred_items(&all, Some("fr")).len(), 3);
+}
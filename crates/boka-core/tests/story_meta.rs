@@ -0,0 +1,94 @@
+//! Shelf metadata generated for saved docs.
+
+use boka_core::stories::{self, DocId, StoryError};
+use boka_core::story_meta::{describe, local_meta, meta_of};
+use boka_core::types::{ApiError, LlmProviderConfig, LlmProviderPreset};
+
+use serde_json::{json, Value};
+
+fn mock_provider(fixture: Option<&str>) -> LlmProviderConfig {
+    LlmProviderConfig {
+        preset: LlmProviderPreset::Mock,
+        api_key: None,
+        base_url: fixture.map(|f| format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), f)),
+        model: None,
+        reasoning_model: false,
+    }
+}
+
+fn library() -> Value {
+    json!([{
+        "id": "cats", "title": "Untitled", "sourceLanguage": "en",
+        "sourceText": "A cat sleeps. It dreams of fish.",
+        "translations": { "fr": { "language": "fr", "doc": {
+            "tokens": [
+                { "type": "span", "spanId": "s1" },
+                { "type": "text", "value": " " },
+                { "type": "span", "spanId": "s2" }
+            ],
+            "spans": {
+                "s1": { "id": "s1", "sourceText": "a", "activeVariantIndex": 0,
+                        "variants": [{ "id": "a", "register": "neutral", "text": "Un chat dort." }] },
+                "s2": { "id": "s2", "sourceText": "b", "activeVariantIndex": 0,
+                        "variants": [{ "id": "b", "register": "neutral", "text": "Il rêve de poissons." }] }
+            }
+        } } }
+    }])
+}
+
+fn doc_id() -> DocId {
+    DocId::parse("cats:fr").unwrap()
+}
+
+#[test]
+fn local_fields_need_no_model() {
+    let story = stories::find_doc(&library(), &doc_id()).unwrap();
+    let meta = local_meta(&story);
+    assert_eq!(meta.word_count, 7);
+    assert!(meta.level.is_some());
+    assert_eq!((meta.title, meta.synopsis, meta.model), (None, None, None));
+    assert!(!meta.cover.emoji.is_empty());
+
+    // The color seed follows the story, not the text.
+    let mut other = story.clone();
+    other.source_text = "Something else.".to_string();
+    assert_eq!(local_meta(&other).cover.color_seed, local_meta(&story).cover.color_seed);
+}
+
+#[tokio::test]
+async fn model_description_is_parsed_and_cleaned() {
+    let story = stories::find_doc(&library(), &doc_id()).unwrap();
+    let meta = describe(&story, mock_provider(Some("story_meta.json"))).await.unwrap();
+    assert_eq!(meta.title.as_deref(), Some("Le chat paresseux"));
+    let synopsis = meta.synopsis.unwrap();
+    assert_eq!(synopsis.target, "Un chat dort toute la journée.");
+    assert_eq!(meta.cover.emoji, "🐈");
+    assert_eq!(meta.model.as_deref(), Some("mock"));
+}
+
+#[tokio::test]
+async fn descriptions_without_a_synopsis_are_rejected() {
+    let story = stories::find_doc(&library(), &doc_id()).unwrap();
+    let err = describe(&story, mock_provider(Some("story_meta_incomplete.json"))).await.unwrap_err();
+    assert!(matches!(err, ApiError::Parse(ref m) if m.contains("synopsisSource")), "{err}");
+
+    let unscripted = describe(&story, mock_provider(None)).await.unwrap();
+    assert_eq!(unscripted.synopsis.unwrap().source, "A cat sleeps.");
+}
+
+#[tokio::test]
+async fn meta_is_stored_and_survives_frontend_writes() {
+    let mut all = library();
+    let story = stories::find_doc(&all, &doc_id()).unwrap();
+    let meta = describe(&story, mock_provider(None)).await.unwrap();
+    stories::set_meta(&mut all, &doc_id(), &meta).unwrap();
+    assert_eq!(meta_of(&all[0]["translations"]["fr"]), Some(meta.clone()));
+    assert!(matches!(
+        stories::set_meta(&mut all, &DocId::parse("cats:de").unwrap(), &meta),
+        Err(StoryError::NotFound(_))
+    ));
+
+    let mut incoming = library();
+    stories::keep_meta(&mut incoming, &all);
+    assert_eq!(meta_of(&incoming[0]["translations"]["fr"]), Some(meta));
+}
@@ -0,0 +1,38 @@
+//! OS speech fallback: command lines per platform.
+#![cfg(feature = "tts")]
+
+use boka_core::audio::TtsEngine;
+use boka_core::paths::Platform;
+use boka_core::system_tts::{SystemBackend, SystemEngine, SYSTEM_MODEL_ID};
+
+use std::path::Path;
+
+#[test]
+fn each_platform_has_a_backend() {
+    assert_eq!(SystemBackend::for_platform(Platform::Macos).program(), "say");
+    assert_eq!(SystemBackend::for_platform(Platform::Windows).program(), "powershell");
+    assert_eq!(SystemBackend::for_platform(Platform::Linux).program(), "espeak-ng");
+
+    let engine = SystemEngine::with_backend(SystemBackend::EspeakNg);
+    assert_eq!(engine.model_id(), SYSTEM_MODEL_ID);
+    assert!(!engine.is_loaded());
+    assert!(engine.generate("Bonjour", "ff_siwis", 1.0, "fr").is_err());
+}
+
+#[test]
+fn text_goes_through_stdin_and_language_is_sanitized() {
+    let out = Path::new("/tmp/o'ut.wav");
+
+    let espeak = SystemBackend::EspeakNg.args(out, "fr; rm -rf /", 2.0);
+    assert_eq!(espeak, ["-w", "/tmp/o'ut.wav", "-s", "350", "-v", "frrm-rf", "--stdin"]);
+
+    let say = SystemBackend::Say.args(out, "fr", 0.1);
+    assert_eq!(say, ["-o", "/tmp/o'ut.wav", "--data-format=LEI16@22050", "-r", "80", "-f", "-"]);
+
+    let sapi = SystemBackend::Sapi.args(out, "ja-JP", 1.5);
+    let script = sapi.last().unwrap();
+    assert!(script.contains("[Globalization.CultureInfo]'ja-JP'"));
+    assert!(script.contains("$s.Rate = 5;"));
+    assert!(script.contains("SetOutputToWaveFile('/tmp/o''ut.wav'"));
+    assert!(script.contains("[Console]::In.ReadToEnd()"));
+}
@@ -0,0 +1,72 @@
+//! CSV/TSV export of spans and vocabulary.
+
+use boka_core::export::table::{table, Delimiter, TableKind};
+use boka_core::stories::{find_doc, DocId, StoryDoc};
+
+use serde_json::json;
+use std::path::Path;
+
+fn story() -> StoryDoc {
+    let library = json!([{
+        "id": "story-1",
+        "title": "Le chat",
+        "translations": { "fr": { "doc": {
+            "tokens": [
+                { "type": "span", "spanId": "s1" },
+                { "type": "text", "value": " " },
+                { "type": "span", "spanId": "s2" }
+            ],
+            "spans": {
+                "s1": {
+                    "id": "s1",
+                    "sourceText": "Hello, cat.",
+                    "activeVariantIndex": 1,
+                    "variants": [
+                        { "id": "a", "register": "neutral", "text": "Bonjour, le chat.", "difficulty": 2 },
+                        { "id": "b", "register": "casual", "text": "Salut\tle chat.", "note": "Familier, \"salut\"." }
+                    ]
+                },
+                "s2": {
+                    "id": "s2",
+                    "sourceText": "It sleeps.",
+                    "activeVariantIndex": 0,
+                    "variants": [{ "id": "c", "register": "literary", "text": "Il sommeille.", "difficulty": 4 }]
+                }
+            }
+        } } }
+    }]);
+    find_doc(&library, &DocId::parse("story-1:fr").unwrap()).unwrap()
+}
+
+#[test]
+fn spans_csv_quotes_and_packs_variants() {
+    let csv = table(&story(), TableKind::Spans, Delimiter::Comma);
+    let lines: Vec<&str> = csv.trim_start_matches('\u{FEFF}').split("\r\n").collect();
+    assert_eq!(lines[0], "source,neutral,variants,notes,difficulty");
+    assert_eq!(
+        lines[1],
+        "\"Hello, cat.\",\"Bonjour, le chat.\",\"neutral: Bonjour, le chat. | casual: Salut\tle chat.\",\"casual: Familier, \"\"salut\"\".\",2"
+    );
+    // No neutral variant: the active text stands in.
+    assert_eq!(lines[2], "It sleeps.,Il sommeille.,literary: Il sommeille.,,4");
+    assert_eq!(lines[3], "");
+}
+
+#[test]
+fn tsv_replaces_tabs_and_has_no_bom() {
+    assert_eq!(Delimiter::for_path(Path::new("deck.TSV")), Delimiter::Tab);
+    assert_eq!(Delimiter::for_path(Path::new("deck.csv")), Delimiter::Comma);
+
+    let tsv = table(&story(), TableKind::Spans, Delimiter::Tab);
+    assert!(tsv.starts_with("source\tneutral"));
+    assert!(tsv.contains("casual: Salut le chat."));
+}
+
+#[test]
+fn vocab_rows_use_the_active_text() {
+    let tsv = table(&story(), TableKind::Vocab, Delimiter::Tab);
+    let lines: Vec<&str> = tsv.lines().collect();
+    assert_eq!(lines[0], "lemma\tword\tgloss\tfrequency");
+    assert_eq!(lines[1], "salut\tSalut\tHello, cat.\t1");
+    assert!(lines.contains(&"sommeill\tsommeille\tIt sleeps.\t1"));
+}
@@ -0,0 +1,104 @@
+//! Template-driven exports: built-ins, user templates and errors.
+
+use boka_core::export::template::{list_templates, render_source, render_template, templates_dir};
+use boka_core::export::ExportError;
+use boka_core::stories::{find_doc, DocId, StoryDoc};
+
+use serde_json::json;
+use std::fs;
+
+fn story() -> StoryDoc {
+    let library = json!([{
+        "id": "story-1",
+        "title": "Cats & Dogs",
+        "sourceLanguage": "en",
+        "translations": { "fr": { "doc": {
+            "tokens": [
+                { "type": "span", "spanId": "s1" },
+                { "type": "text", "value": " " },
+                { "type": "span", "spanId": "s2" },
+                { "type": "text", "value": "\n\n" },
+                { "type": "span", "spanId": "s3" }
+            ],
+            "spans": {
+                "s1": { "id": "s1", "sourceText": "The cat", "activeVariantIndex": 0,
+                        "variants": [{ "id": "a", "register": "neutral", "text": "Le chat" }] },
+                "s2": { "id": "s2", "sourceText": "sleeps.", "activeVariantIndex": 1,
+                        "variants": [{ "id": "b", "register": "neutral", "text": "dort." },
+                                     { "id": "c", "register": "literary", "text": "<sommeille>.", "note": "soutenu" }] },
+                "s3": { "id": "s3", "sourceText": "The end.", "activeVariantIndex": 0,
+                        "variants": [{ "id": "d", "register": "neutral", "text": "Fin." }] }
+            }
+        } } }
+    }]);
+    find_doc(&library, &DocId::parse("story-1:fr").unwrap()).unwrap()
+}
+
+#[test]
+fn builtin_html_escapes_and_marks_spans() {
+    let dir = std::env::temp_dir().join(format!("boka-templates-none-{}", std::process::id()));
+    let (html, ext) = render_template(&dir, "html", &story()).unwrap();
+    assert_eq!(ext, "html");
+    assert!(html.contains("<title>Cats &amp; Dogs</title>"));
+    assert!(html.contains(r#"<span class="span" data-register="literary" title="sleeps.">&lt;sommeille&gt;.</span>"#));
+    assert!(html.contains("(literary): soutenu"));
+
+    let (md, ext) = render_template(&dir, "markdown", &story()).unwrap();
+    assert_eq!(ext, "md");
+    assert!(md.starts_with("# Cats & Dogs\n"));
+    assert!(md.contains("\nLe chat <sommeille>.\n"));
+    assert!(md.contains("- **Fin.** — The end."));
+}
+
+#[test]
+fn user_templates_are_listed_and_can_replace_builtins() {
+    let dir = std::env::temp_dir().join(format!("boka-templates-{}", std::process::id()));
+    let templates = templates_dir(&dir);
+    fs::create_dir_all(&templates).unwrap();
+    fs::write(
+        templates.join("anki.txt"),
+        "{% for span in spans %}{{ span.sourceText }}\t{{ span.text }}\n{% endfor %}",
+    )
+    .unwrap();
+    fs::write(templates.join("markdown"), "custom {{ story.language }}").unwrap();
+
+    let names: Vec<(String, bool)> = list_templates(&dir).into_iter().map(|t| (t.name, t.builtin)).collect();
+    assert_eq!(
+        names,
+        [
+            ("anki.txt".to_string(), false),
+            ("html".to_string(), true),
+            ("markdown".to_string(), false)
+        ]
+    );
+
+    let (text, ext) = render_template(&dir, "anki.txt", &story()).unwrap();
+    assert_eq!(ext, "txt");
+    assert_eq!(text, "The cat\tLe chat\nsleeps.\t<sommeille>.\nThe end.\tFin.\n");
+    assert_eq!(render_template(&dir, "markdown", &story()).unwrap().0, "custom fr");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn bad_names_and_templates_are_reported() {
+    let dir = std::env::temp_dir();
+    for name in ["nope", "../stories.json", ".hidden", ""] {
+        assert!(matches!(
+            render_template(&dir, name, &story()),
+            Err(ExportError::UnknownTemplate(_))
+        ));
+    }
+    assert!(matches!(
+        render_source("t.txt", "{{ story.missing }}", &story()),
+        Err(ExportError::Template(_))
+    ));
+    assert!(matches!(
+        render_source("t.txt", "{% for %}", &story()),
+        Err(ExportError::Template(_))
+    ));
+    assert_eq!(
+        render_source("t.txt", "{{ blocks | length }} {{ vocab[0].word }}", &story()).unwrap(),
+        "2 Le"
+    );
+}
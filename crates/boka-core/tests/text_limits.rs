@@ -0,0 +1,26 @@
+use boka_core::text::{clip, excerpt, truncate, EVENT_TEXT_LEN};
+
+#[test]
+fn truncate_counts_graphemes_not_bytes() {
+    assert_eq!(truncate("猫が寝ている", 2), "猫が");
+    // "é" as e + combining acute stays whole.
+    assert_eq!(truncate("e\u{301}te\u{301}", 1), "e\u{301}");
+    // A family emoji is one grapheme of several code points.
+    assert_eq!(truncate("👨‍👩‍👧 ok", 1), "👨‍👩‍👧");
+    assert_eq!(truncate("short", 10), "short");
+}
+
+#[test]
+fn excerpt_marks_cuts() {
+    let long = "漢".repeat(900);
+    let cut = excerpt(&long, 800);
+    assert_eq!(cut.chars().count(), 801);
+    assert!(cut.ends_with('…'));
+    assert_eq!(excerpt("漢字", 800), "漢字");
+}
+
+#[test]
+fn clip_bounds_event_strings() {
+    let page = format!("<html>{}</html>", "x".repeat(1_000_000));
+    assert_eq!(clip(&page).chars().count(), EVENT_TEXT_LEN + 1);
+}
@@ -0,0 +1,65 @@
+//! Per-job transcripts: every exchange a client makes is recorded, replayed
+//! ones included, and can be copied out by job id.
+
+use boka_core::cassette::{Cassette, CassetteMode};
+use boka_core::openai_compat::OpenAiCompatClient;
+use boka_core::simple_format::StructuredFormat;
+use boka_core::transcript::{self, transcript_path, TranscriptError};
+use boka_core::types::{ApiConfig, LlmProviderConfig, LlmProviderPreset};
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("boka-transcript-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[tokio::test]
+async fn every_exchange_of_a_job_is_recorded_and_exported() {
+    let dir = temp_dir("record");
+    let cassette = format!("{}/tests/fixtures/cassettes/openai_session.jsonl", env!("CARGO_MANIFEST_DIR"));
+    let cfg = ApiConfig {
+        provider: LlmProviderConfig {
+            preset: LlmProviderPreset::Openai,
+            base_url: Some("http://replay.invalid/v1".to_string()),
+            model: Some("replay-model".to_string()),
+            ..Default::default()
+        },
+        target_language: "fr".to_string(),
+        cassette: Some(Arc::new(Cassette::new(CassetteMode::Replay, cassette))),
+        transcript: Some(Arc::new(transcript::recorder(&transcript_path(&dir, "job-1").unwrap()))),
+        ..Default::default()
+    };
+    let client = OpenAiCompatClient::new(cfg).unwrap();
+    let (base, _) = client.translate_base_segment("The cat sleeps.", "The cat sleeps.", None).await.unwrap();
+    client.plan_block_from_base(&base, StructuredFormat::Json).await.unwrap();
+
+    let entries = transcript::load(&dir, "job-1").unwrap();
+    assert_eq!(entries.len(), 2);
+    assert!(entries.iter().all(|e| e.url == "http://replay.invalid/v1/chat/completions" && e.status == 200));
+    assert!(entries[0].request.to_string().contains("The cat sleeps."));
+    assert!(entries[0].response.contains("Le chat dort."));
+
+    let dest = dir.join("out").join("job-1.jsonl");
+    let bytes = transcript::export(&dir, "job-1", &dest).unwrap();
+    assert_eq!(bytes, fs::metadata(&dest).unwrap().len());
+    // An exported transcript replays like any cassette.
+    let replay = Cassette::new(CassetteMode::Replay, &dest);
+    assert!(replay.replay(&entries[1].url, &entries[1].request).is_ok());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn unknown_and_malformed_job_ids_are_refused() {
+    let dir = temp_dir("missing");
+    let dest = dir.join("out.jsonl");
+    assert!(matches!(transcript::export(&dir, "job-2", &dest), Err(TranscriptError::NotFound(_))));
+    assert!(matches!(
+        transcript::export(&dir, "../settings", &dest),
+        Err(TranscriptError::InvalidJobId(_))
+    ));
+    assert!(!dest.exists());
+}
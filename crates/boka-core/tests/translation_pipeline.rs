@@ -0,0 +1,784 @@
+//! End-to-end tests for `run_translation` driven by the mock provider and the
+//! canned responses in `tests/fixtures/`.
+
+use boka_core::annotate::AnnotationPlan;
+use boka_core::gui_types::{
+    DocToken, ErrorPolicy, Granularity, InteractiveDoc, SegmentEdit, SegmentStage, SpanStage, TextDirection,
+    TranslationJob,
+};
+use boka_core::judge::JudgeConfig;
+use boka_core::length_guard::LengthGuard;
+use boka_core::limits::{BudgetStatus, JobBudget};
+use boka_core::lint::lint_doc;
+use boka_core::policy::ContentPolicy;
+use boka_core::settings::VariantBounds;
+use boka_core::simplify::CefrLevel;
+use boka_core::translation::{
+    retry_failed_segments, run_translation, BudgetGate, RetryArgs, ReviewGate, TranslationArgs, TranslationResult,
+};
+use boka_core::translation_cache::TranslationCache;
+use boka_core::types::{ApiError, LlmProviderConfig, LlmProviderPreset};
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+struct Run {
+    result: Result<TranslationResult, ApiError>,
+    jobs: Vec<TranslationJob>,
+    docs: Vec<InteractiveDoc>,
+}
+
+fn mock_provider(fixture: &str) -> LlmProviderConfig {
+    LlmProviderConfig {
+        preset: LlmProviderPreset::Mock,
+        api_key: None,
+        base_url: Some(format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), fixture)),
+        model: None,
+        reasoning_model: false,
+    }
+}
+
+#[derive(Default)]
+struct Options {
+    /// Raise the cancel flag as soon as any segment reports variants.
+    cancel_after_first_variant: bool,
+    content_policy: Option<ContentPolicy>,
+    judge: Option<JudgeConfig>,
+    simplify_level: Option<CefrLevel>,
+    dual_output: bool,
+    refine_variants: bool,
+    comprehension_every: Option<u32>,
+    annotations: AnnotationPlan,
+    review: Option<Box<dyn ReviewGate>>,
+    error_policy: ErrorPolicy,
+    budget: Option<JobBudget>,
+    budget_gate: Option<Box<dyn BudgetGate>>,
+    reasoning_model: bool,
+    translation_cache: Option<Arc<TranslationCache>>,
+}
+
+async fn run(story: &str, fixture: &str, cancel_after_first_variant: bool) -> Run {
+    let opts = Options {
+        cancel_after_first_variant,
+        ..Options::default()
+    };
+    run_with(story, fixture, opts).await
+}
+
+async fn run_with_policy(story: &str, fixture: &str, content_policy: ContentPolicy) -> Run {
+    let opts = Options {
+        content_policy: Some(content_policy),
+        ..Options::default()
+    };
+    run_with(story, fixture, opts).await
+}
+
+/// Runs the pipeline against a fixture.
+async fn run_with(story: &str, fixture: &str, opts: Options) -> Run {
+    let Options {
+        cancel_after_first_variant,
+        content_policy,
+        judge,
+        simplify_level,
+        dual_output,
+        refine_variants,
+        comprehension_every,
+        annotations,
+        review,
+        error_policy,
+        budget,
+        budget_gate,
+        reasoning_model,
+        translation_cache,
+    } = opts;
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let jobs = Arc::new(Mutex::new(Vec::new()));
+    let docs = Arc::new(Mutex::new(Vec::new()));
+
+    let jobs_sink = jobs.clone();
+    let cancel_flag = cancelled.clone();
+    let on_job = move |job: &TranslationJob| {
+        jobs_sink.lock().unwrap().push(job.clone());
+        if cancel_after_first_variant && job.segments.iter().any(|s| s.variant_count > 0) {
+            cancel_flag.store(true, Ordering::Relaxed);
+        }
+        async {}
+    };
+
+    let docs_sink = docs.clone();
+    let on_doc = move |doc: &InteractiveDoc| {
+        docs_sink.lock().unwrap().push(doc.clone());
+        async {}
+    };
+
+    let result = run_translation(TranslationArgs {
+        story_text: story.to_string(),
+        job_id: "job-test".to_string(),
+        target_language: "fr".to_string(),
+        source_language: Some("en".to_string()),
+        adult_mode: false,
+        content_policy,
+        dense_spans: false,
+        reproducible: false,
+        prompt_overrides: Default::default(),
+        judge,
+        variant_bounds: VariantBounds::default(),
+        length_guard: LengthGuard::default(),
+        simplify_level,
+        dual_output,
+        refine_variants,
+        granularity: Granularity::Sentence,
+        comprehension_every,
+        annotations,
+        review,
+        error_policy,
+        budget,
+        budget_gate,
+        confirmation: None,
+        translation_cache,
+        transcript: None,
+        ui_language: None,
+        provider: LlmProviderConfig {
+            reasoning_model,
+            ..mock_provider(fixture)
+        },
+        cancelled,
+        on_job: Box::new(on_job),
+        on_doc: Box::new(on_doc),
+    })
+    .await;
+
+    let jobs = jobs.lock().unwrap().clone();
+    let docs = docs.lock().unwrap().clone();
+    Run { result, jobs, docs }
+}
+
+fn doc_text(doc: &InteractiveDoc) -> String {
+    doc.tokens
+        .iter()
+        .map(|t| match t {
+            DocToken::Text { value, .. } => value.clone(),
+            DocToken::Span { span_id, .. } => {
+                let span = &doc.spans[span_id];
+                span.variants[span.active_variant_index].text.clone()
+            }
+            DocToken::Check { .. } => String::new(),
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn happy_path_builds_full_doc() {
+    let run = run("The cat sleeps. The dog barks.", "happy_path.json", false).await;
+    let result = run.result.expect("translation should succeed");
+
+    assert!(result.job.ready);
+    assert_eq!(result.job.segments.len(), 2);
+    for seg in &result.job.segments {
+        assert_eq!(seg.base_stage, SegmentStage::Ready);
+        assert_eq!(seg.span_stage, SegmentStage::Ready);
+        assert_eq!(seg.variant_count, 2);
+        assert_eq!(seg.variant_target, 2, "short segments get the minimum");
+    }
+    assert_eq!(result.job.segments[0].base_text.as_deref(), Some("Le chat dort."));
+
+    let doc = &result.doc;
+    assert_eq!(doc.spans.len(), 2);
+    assert_eq!(doc_text(doc), "Le chat dort.\n\nLe chien aboie.");
+    assert_eq!(doc.direction, TextDirection::Ltr);
+    assert_eq!(doc.block_directions, [TextDirection::Ltr, TextDirection::Ltr]);
+
+    let cat = &doc.spans["span-1"];
+    assert_eq!(cat.source_text, "Le chat");
+    let registers: Vec<&str> = cat.variants.iter().map(|v| v.register.as_str()).collect();
+    assert_eq!(registers, ["neutral", "colloquial"]);
+    assert_eq!(cat.variants[1].id, "span-1-colloquial-1");
+    assert_eq!(cat.variants[1].note.as_deref(), Some("Informal word for a tomcat"));
+    assert_eq!(cat.variants[0].note, None);
+
+    let meta = result.job.metadata.as_ref().expect("job metadata recorded");
+    assert_eq!(meta.model, "mock");
+    assert!(!meta.reproducible);
+    assert!(meta.prompts.base_translation.contains("French"));
+    assert_eq!(result.job.lints, lint_doc(doc));
+
+    assert!(run.jobs.first().is_some_and(|j| j.segments.iter().all(|s| s.base_stage == SegmentStage::Pending)));
+    assert!(!run.docs.is_empty());
+}
+
+#[tokio::test]
+async fn partial_docs_grow_one_block_at_a_time() {
+    let run = run("The cat sleeps. The dog barks.", "happy_path.json", false).await;
+    let result = run.result.unwrap();
+
+    let blocks: Vec<usize> = run.docs.iter().map(|doc| doc.block_directions.len()).collect();
+    assert_eq!(blocks, [1, 1, 2, 2]);
+    // While the dog's block is planned, the cat's is already final.
+    let json = |doc: &InteractiveDoc| serde_json::to_value(doc).unwrap();
+    assert_eq!(json(&run.docs[2])["spans"]["span-1"], json(&result.doc)["spans"]["span-1"]);
+    assert_eq!(doc_text(&run.docs[2]), "Le chat dort.\n\nLe chien aboie.");
+    assert_eq!(json(run.docs.last().unwrap()), json(&result.doc));
+}
+
+#[tokio::test]
+async fn truncated_plan_json_marks_segment_error() {
+    let run = run("The cat sleeps.", "truncated_plan.json", false).await;
+
+    match run.result {
+        Err(ApiError::Parse(msg)) => assert!(msg.starts_with("Simple format:"), "unexpected message: {}", msg),
+        other => panic!("expected parse error, got {:?}", other),
+    }
+
+    let last = run.jobs.last().expect("job events emitted");
+    assert!(!last.ready);
+    assert_eq!(last.segments[0].base_stage, SegmentStage::Ready);
+    assert_eq!(last.segments[0].span_stage, SegmentStage::Error);
+}
+
+#[tokio::test]
+async fn cancellation_between_spans_stops_the_job() {
+    let run = run("The old cat sleeps.", "two_spans.json", true).await;
+
+    match run.result {
+        Err(ApiError::Parse(msg)) => assert_eq!(msg, "Cancelled"),
+        other => panic!("expected cancellation, got {:?}", other),
+    }
+
+    let last = run.jobs.last().expect("job events emitted");
+    assert!(!last.ready);
+    assert_eq!(last.segments[0].variant_count, 2);
+    assert_eq!(last.segments[0].span_stage, SegmentStage::Pending);
+}
+
+#[tokio::test]
+async fn spans_of_a_segment_are_generated_together() {
+    let run = run("The old cat sleeps.", "two_spans.json", false).await;
+    let result = run.result.expect("translation should succeed");
+
+    let stages = |job: &TranslationJob| job.segments[0].spans.iter().map(|s| s.stage).collect::<Vec<_>>();
+    assert!(run.jobs.iter().any(|job| stages(job) == [SpanStage::Variants, SpanStage::Variants]));
+    // Each reply lands in its own span: a partial doc per span, then the finished block.
+    let variants = |doc: &InteractiveDoc| {
+        let mut spans: Vec<_> = doc.spans.iter().map(|(id, s)| (id.clone(), s.variants.len())).collect();
+        spans.sort();
+        spans.into_iter().map(|(_, n)| n).collect::<Vec<_>>()
+    };
+    let partial: Vec<_> = run.docs.iter().map(variants).collect();
+    assert_eq!(partial, [vec![2, 1], vec![2, 2], vec![2, 2]]);
+    assert_eq!(variants(&result.doc), [2, 2]);
+}
+
+#[tokio::test]
+async fn loose_provider_output_falls_back_gracefully() {
+    let run = run("The cat sleeps. The dog barks.", "loose_output.json", false).await;
+    let result = run.result.expect("translation should succeed");
+
+    let doc = &result.doc;
+    assert_eq!(doc_text(doc), "Le chat dort.\n\nLe chien aboie.");
+    assert_eq!(doc.spans.len(), 1);
+
+    let span = &doc.spans["span-1"];
+    let texts: Vec<&str> = span.variants.iter().map(|v| v.text.as_str()).collect();
+    assert_eq!(texts, ["Le chien aboie.", "Le clébard gueule."]);
+    assert!(span.variants.iter().all(|v| v.register == "neutral"));
+    assert_eq!(result.job.segments[0].variant_count, 0);
+}
+
+#[tokio::test]
+async fn reasoning_blocks_are_stripped_before_parsing() {
+    let opts = Options {
+        reasoning_model: true,
+        ..Options::default()
+    };
+    let run = run_with("The cat sleeps.", "reasoning_output.json", opts).await;
+    let result = run.result.expect("translation should succeed");
+
+    assert_eq!(result.job.segments[0].base_text.as_deref(), Some("Le chat dort."));
+    assert_eq!(doc_text(&result.doc), "Le chat dort.");
+    let span = &result.doc.spans["span-1"];
+    let texts: Vec<&str> = span.variants.iter().map(|v| v.text.as_str()).collect();
+    assert_eq!(texts, ["Le chat", "Le matou"]);
+}
+
+#[tokio::test]
+async fn reasoning_blocks_break_parsing_without_the_hint() {
+    let run = run("The cat sleeps.", "reasoning_output.json", false).await;
+    assert!(matches!(run.result, Err(ApiError::Parse(_))), "got {:?}", run.result.map(|r| r.job.id));
+}
+
+#[tokio::test]
+async fn long_cjk_output_is_excerpted_on_parse_errors() {
+    let run = run("The cat sleeps.", "cjk_parse_error.json", false).await;
+    match run.result {
+        Err(ApiError::Parse(message)) => assert!(message.ends_with("ている。…"), "got {}", message),
+        other => panic!("expected parse error, got {:?}", other.map(|r| r.job.id)),
+    }
+}
+
+#[tokio::test]
+async fn provider_error_marks_segment_and_propagates() {
+    let run = run("The cat sleeps.", "provider_error.json", false).await;
+
+    match run.result {
+        Err(ApiError::ApiResponse { status, .. }) => assert_eq!(status, 529),
+        other => panic!("expected API error, got {:?}", other),
+    }
+
+    let last = run.jobs.last().expect("job events emitted");
+    assert_eq!(last.segments[0].base_stage, SegmentStage::Error);
+    assert_eq!(last.segments[0].span_stage, SegmentStage::Error);
+}
+
+#[tokio::test]
+async fn content_policy_drops_and_flags_variants() {
+    let policy = ContentPolicy {
+        allowed_registers: vec!["neutral".to_string(), "formal".to_string()],
+        blocked_themes: vec!["chat".to_string()],
+        profanity_level: 0,
+        moderation: false,
+    };
+    let run = run_with_policy("The cat sleeps. The dog barks.", "happy_path.json", policy).await;
+    let doc = run.result.expect("translation should succeed").doc;
+
+    let cat = &doc.spans["span-1"];
+    assert_eq!(cat.variants.len(), 1, "colloquial variant should be dropped");
+    assert_eq!(cat.variants[0].flagged.as_deref(), Some("blocked theme `chat`"));
+
+    let dog = &doc.spans["span-2"];
+    assert_eq!(dog.variants.len(), 1, "casual variant should be dropped");
+    assert_eq!(dog.variants[0].text, "aboie");
+    assert_eq!(dog.variants[0].flagged, None);
+
+    assert_eq!(doc_text(&doc), "Le chat dort.\n\nLe chien aboie.", "anchors are kept");
+}
+
+#[tokio::test]
+async fn child_safe_policy_regenerates_unsafe_variants() {
+    let run = run_with_policy("The cat sleeps.", "moderation_retry.json", ContentPolicy::child_safe()).await;
+    let doc = run.result.expect("translation should succeed").doc;
+
+    let texts: Vec<&str> = doc.spans["span-1"].variants.iter().map(|v| v.text.as_str()).collect();
+    assert_eq!(texts, ["Le chat", "Le minou"], "regenerated list, colloquial dropped");
+}
+
+#[tokio::test]
+async fn judge_retries_low_scoring_segment() {
+    let judge = JudgeConfig {
+        provider: None,
+        min_score: 3,
+        auto_retry: true,
+    };
+    let opts = Options {
+        judge: Some(judge),
+        ..Options::default()
+    };
+    let run = run_with("The cat sleeps.", "judge_retry.json", opts).await;
+    let result = run.result.expect("translation should succeed");
+
+    let seg = &result.job.segments[0];
+    assert_eq!(seg.base_text.as_deref(), Some("Le chat dort."), "better retry is kept");
+    let score = seg.score.as_ref().expect("segment scored");
+    assert_eq!((score.adequacy, score.fluency, score.attempts), (5, 4, 2));
+    assert!(!score.needs_review);
+    assert_eq!(score.judge_model, "mock");
+}
+
+#[tokio::test]
+async fn simplification_retries_when_too_hard() {
+    let opts = Options {
+        simplify_level: Some(CefrLevel::A2),
+        // Ignored while simplifying: the fixture has no judge replies.
+        judge: Some(JudgeConfig {
+            provider: None,
+            min_score: 5,
+            auto_retry: true,
+        }),
+        ..Options::default()
+    };
+    let run = run_with("The feline slumbers peacefully.", "simplify_retry.json", opts).await;
+    let result = run.result.expect("translation should succeed");
+
+    let seg = &result.job.segments[0];
+    assert_eq!(seg.base_text.as_deref(), Some("Le chat dort bien."));
+    assert!(seg.score.is_none());
+    let check = seg.simplification.as_ref().expect("vocabulary checked");
+    assert!(check.passed);
+    assert_eq!(check.attempts, 2);
+    assert_eq!(check.level, CefrLevel::A2);
+
+    let meta = result.job.metadata.as_ref().unwrap();
+    assert_eq!(meta.simplify_level, Some(CefrLevel::A2));
+    assert!(meta.prompts.simplified_translation.as_deref().is_some_and(|p| p.contains("A2")));
+}
+
+#[tokio::test]
+async fn dual_output_keeps_faithful_and_simplified() {
+    let opts = Options {
+        simplify_level: Some(CefrLevel::A2),
+        dual_output: true,
+        judge: Some(JudgeConfig {
+            provider: None,
+            min_score: 3,
+            auto_retry: true,
+        }),
+        ..Options::default()
+    };
+    let run = run_with("The feline slumbers peacefully.", "dual_output.json", opts).await;
+    let result = run.result.expect("translation should succeed");
+
+    let seg = &result.job.segments[0];
+    assert_eq!(seg.base_text.as_deref(), Some("Le félin sommeille paisiblement."));
+    assert_eq!(seg.simplified_text.as_deref(), Some("Le chat dort bien."));
+    assert!(seg.simplification.as_ref().is_some_and(|c| c.passed && c.attempts == 1));
+    assert!(seg.score.as_ref().is_some_and(|s| s.attempts == 1), "judge scores the faithful text");
+    assert_eq!(doc_text(&result.doc), "Le félin sommeille paisiblement.");
+    assert!(result.job.metadata.as_ref().unwrap().dual_output);
+}
+
+#[tokio::test]
+async fn refinement_pass_fixes_registers_and_drops_unnatural_variants() {
+    let opts = Options {
+        refine_variants: true,
+        ..Options::default()
+    };
+    let run = run_with("The cat sleeps.", "refine_variants.json", opts).await;
+    let result = run.result.expect("translation should succeed");
+
+    let cat = &result.doc.spans["span-1"];
+    let variants: Vec<(&str, &str)> = cat.variants.iter().map(|v| (v.text.as_str(), v.register.as_str())).collect();
+    assert_eq!(variants, [("Le chat", "neutral"), ("Le matou", "colloquial")]);
+    assert_eq!(cat.variants[1].note.as_deref(), Some("Informal word for a tomcat"));
+
+    let meta = result.job.metadata.as_ref().unwrap();
+    assert!(meta.refine_variants);
+    assert!(meta.prompts.variant_critique.as_deref().is_some_and(|p| p.contains("Fix register labels")));
+
+    // Each job update shows where the span is.
+    let mut stages: Vec<(SpanStage, u32)> = run
+        .jobs
+        .iter()
+        .filter_map(|j| j.segments[0].spans.first())
+        .map(|p| (p.stage, p.variants))
+        .collect();
+    stages.dedup();
+    assert_eq!(
+        stages,
+        [(SpanStage::Pending, 0), (SpanStage::Variants, 0), (SpanStage::Qa, 3), (SpanStage::Ready, 2)]
+    );
+    assert_eq!(result.job.segments[0].spans[0].anchor, "Le chat");
+}
+
+#[tokio::test]
+async fn comprehension_checks_close_their_blocks() {
+    let opts = Options {
+        comprehension_every: Some(1),
+        ..Options::default()
+    };
+    let run = run_with("The cat sleeps. The dog barks.", "comprehension_checks.json", opts).await;
+    let result = run.result.expect("an unusable check does not fail the job");
+
+    // The first check ends block 0; the second reply was not JSON and is left out.
+    let checks: Vec<(usize, &DocToken)> = result
+        .doc
+        .tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| matches!(t, DocToken::Check { .. }))
+        .collect();
+    assert_eq!(checks.len(), 1);
+    let (at, check) = checks[0];
+    assert!(matches!(&result.doc.tokens[at + 1], DocToken::Text { value, .. } if value == "\n\n"));
+    let DocToken::Check { question, answer, explanation } = check else {
+        unreachable!()
+    };
+    assert_eq!(question, "Le chat aboie.");
+    assert_eq!((*answer, explanation.as_deref()), (false, Some("The cat sleeps.")));
+    assert_eq!(result.doc.block_texts(), ["Le chat dort.", "Le chien aboie."]);
+    assert_eq!(doc_text(&result.doc), "Le chat dort.\n\nLe chien aboie.");
+    assert!(run.docs.last().unwrap().tokens.iter().any(|t| matches!(t, DocToken::Check { .. })));
+    assert_eq!(result.job.metadata.as_ref().unwrap().comprehension_every, Some(1));
+
+    let json = serde_json::to_value(&result.doc.tokens[at]).unwrap();
+    assert_eq!(json["type"], "check");
+    assert_eq!(json["answer"], false);
+}
+
+#[tokio::test]
+async fn annotation_passes_run_on_the_finished_doc() {
+    let opts = Options {
+        annotations: AnnotationPlan::new(vec!["frequency".to_string(), "furigana".to_string()]),
+        ..Options::default()
+    };
+    let run = run_with("The cat sleeps. The dog barks.", "happy_path.json", opts).await;
+    let result = run.result.expect("an unknown pass does not fail the job");
+
+    // Only the registered pass leaves a layer, covering every variant.
+    assert_eq!(result.doc.annotations.keys().collect::<Vec<_>>(), ["frequency"]);
+    let layer = &result.doc.annotations["frequency"];
+    for (id, span) in &result.doc.spans {
+        let variants = &layer.spans[id];
+        assert_eq!(variants.len(), span.variants.len());
+        let joined: String = variants[0].iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(joined, span.variants[0].text);
+    }
+    assert!(run.docs.last().unwrap().annotations.contains_key("frequency"));
+    let meta = result.job.metadata.as_ref().unwrap();
+    assert_eq!(meta.annotations, ["frequency", "furigana"]);
+}
+
+#[tokio::test]
+async fn review_gate_pauses_before_planning_and_applies_edits() {
+    let gate = |job: &TranslationJob| {
+        assert!(job.awaiting_review);
+        assert!(job.segments.iter().all(|s| s.base_stage == SegmentStage::Ready));
+        assert!(job.segments.iter().all(|s| s.span_stage == SegmentStage::Pending && s.variant_count == 0));
+        let edits = vec![
+            SegmentEdit {
+                segment_id: "seg-2".to_string(),
+                base_text: " Le chien jappe. ".to_string(),
+            },
+            SegmentEdit {
+                segment_id: "seg-9".to_string(),
+                base_text: "Ignored.".to_string(),
+            },
+        ];
+        async move { Some(edits) }
+    };
+    let opts = Options {
+        review: Some(Box::new(gate)),
+        ..Options::default()
+    };
+    let run = run_with("The cat sleeps. The dog barks.", "happy_path.json", opts).await;
+    let result = run.result.expect("translation should succeed");
+
+    let segments = &result.job.segments;
+    assert_eq!(segments[0].base_text.as_deref(), Some("Le chat dort."));
+    assert!(!segments[0].edited);
+    assert_eq!(segments[1].base_text.as_deref(), Some("Le chien jappe."));
+    assert!(segments[1].edited);
+    assert!(!result.job.awaiting_review);
+    assert!(result.job.metadata.as_ref().unwrap().review_required);
+    assert_eq!(run.jobs.iter().filter(|j| j.awaiting_review).count(), 1);
+}
+
+#[tokio::test]
+async fn review_gate_can_cancel_the_job() {
+    let opts = Options {
+        review: Some(Box::new(|_: &TranslationJob| async { None })),
+        ..Options::default()
+    };
+    let run = run_with("The cat sleeps. The dog barks.", "happy_path.json", opts).await;
+
+    match run.result {
+        Err(ApiError::Parse(msg)) => assert_eq!(msg, "Cancelled"),
+        other => panic!("expected cancellation, got {:?}", other),
+    }
+    assert!(run.docs.is_empty(), "nothing is planned before approval");
+}
+
+#[tokio::test]
+async fn skip_policy_finishes_the_job_around_a_failed_segment() {
+    let opts = Options {
+        error_policy: ErrorPolicy::Skip,
+        ..Options::default()
+    };
+    let run = run_with("The cat sleeps. The dog barks.", "skipped_segment.json", opts).await;
+    let result = run.result.expect("the job finishes");
+
+    assert!(result.job.ready);
+    let segments = &result.job.segments;
+    assert_eq!(segments[0].span_stage, SegmentStage::Ready);
+    assert_eq!(segments[1].base_stage, SegmentStage::Error);
+    assert_eq!(segments[1].span_stage, SegmentStage::Error);
+    assert_eq!(segments[1].base_text, None);
+    // The failed segment stands in untranslated.
+    assert_eq!(doc_text(&result.doc), "Le chat dort.\n\nThe dog barks.");
+    assert_eq!(result.doc.spans.len(), 1);
+    assert_eq!(result.job.metadata.as_ref().unwrap().error_policy, ErrorPolicy::Skip);
+}
+
+#[tokio::test]
+async fn retry_then_skip_recovers_a_flaky_segment() {
+    let opts = Options {
+        error_policy: ErrorPolicy::RetryThenSkip,
+        ..Options::default()
+    };
+    let run = run_with("The cat sleeps. The dog barks.", "flaky_segment.json", opts).await;
+    let result = run.result.expect("the job finishes");
+
+    assert!(result.job.segments.iter().all(|s| s.span_stage == SegmentStage::Ready));
+    // The failed try is kept in the dog's provenance, as are the span calls.
+    let attempts: Vec<u32> = result.job.segments[1].provenance.iter().map(|c| c.attempt).collect();
+    assert_eq!(attempts, [1, 2]);
+    let call = &result.job.segments[1].provenance[1];
+    assert_eq!((call.provider, call.model.as_str()), (LlmProviderPreset::Mock, "mock"));
+    assert!(result.doc.spans.values().all(|s| s.provenance.len() == 1 && s.provenance[0].model == "mock"));
+    assert_eq!(doc_text(&result.doc), "Le chat dort.\n\nLe chien aboie.");
+}
+
+#[tokio::test]
+async fn padded_translations_are_asked_for_again() {
+    let run = run("The cat sleeps. The dog barks.", "padded_translation.json", false).await;
+    let result = run.result.expect("the job finishes");
+
+    let segments = &result.job.segments;
+    assert_eq!(segments[0].base_text.as_deref(), Some("Le chat dort."));
+    // Short replies are left to the model, preamble or not; only French typography touches it.
+    assert_eq!(segments[1].base_text.as_deref(), Some("Voici\u{a0}: Le chien aboie."));
+}
+
+#[tokio::test]
+async fn a_failed_retry_keeps_the_padded_reply() {
+    let run = run("The cat sleeps. The dog barks.", "padded_failed_retry.json", false).await;
+    let result = run.result.expect("the job finishes");
+
+    let segments = &result.job.segments;
+    let cat = segments[0].base_text.as_deref().unwrap_or_default();
+    assert!(cat.starts_with("Le chat dort.") && cat.contains("dormir"), "{cat}");
+    assert_eq!(segments[1].base_text.as_deref(), Some("Le chien aboie."));
+}
+
+#[tokio::test]
+async fn a_refused_retry_refuses_the_segment() {
+    let opts = Options {
+        error_policy: ErrorPolicy::RetryThenSkip,
+        ..Options::default()
+    };
+    let run = run_with("The cat sleeps. The dog barks.", "padded_refused_retry.json", opts).await;
+    let result = run.result.expect("the job finishes");
+
+    let cat = &result.job.segments[0];
+    assert_eq!(cat.base_stage, SegmentStage::Error);
+    assert!(cat.refused.is_some());
+    assert_eq!(doc_text(&result.doc), "The cat sleeps.\n\nLe chien aboie.");
+}
+
+#[tokio::test]
+async fn refused_segments_are_marked_and_not_retried() {
+    let opts = Options {
+        error_policy: ErrorPolicy::RetryThenSkip,
+        ..Options::default()
+    };
+    let run = run_with("The cat sleeps. The dog barks.", "refused_segment.json", opts).await;
+    let result = run.result.expect("the job finishes");
+
+    let dog = &result.job.segments[1];
+    assert_eq!(dog.base_stage, SegmentStage::Error);
+    assert_eq!(
+        dog.refused.as_deref(),
+        Some("I'm sorry, but I can't help with translating this content.")
+    );
+    assert_eq!(result.job.segments[0].refused, None);
+    // The same model would refuse again, so the scripted third reply is unused.
+    assert_eq!(doc_text(&result.doc), "Le chat dort.\n\nThe dog barks.");
+}
+
+#[tokio::test]
+async fn failed_segments_can_be_retried_later() {
+    let opts = Options {
+        error_policy: ErrorPolicy::Skip,
+        ..Options::default()
+    };
+    let story = "The cat sleeps. The dog barks.";
+    let skipped = run_with(story, "skipped_segment.json", opts).await.result.unwrap();
+
+    let docs = Arc::new(Mutex::new(Vec::new()));
+    let docs_sink = docs.clone();
+    let result = retry_failed_segments(RetryArgs {
+        job: skipped.job,
+        doc: skipped.doc,
+        story_text: story.to_string(),
+        prompt_overrides: Default::default(),
+        provider: Some(mock_provider("retry_segment.json")),
+        transcript: None,
+        annotation_data_dir: None,
+        cancelled: Arc::new(AtomicBool::new(false)),
+        on_job: Box::new(|_: &TranslationJob| async {}),
+        on_doc: Box::new(move |doc: &InteractiveDoc| {
+            docs_sink.lock().unwrap().push(doc.clone());
+            async {}
+        }),
+    })
+    .await
+    .expect("retry should succeed");
+
+    let dog = &result.job.segments[1];
+    assert_eq!(dog.base_stage, SegmentStage::Ready);
+    assert_eq!(dog.span_stage, SegmentStage::Ready);
+    assert_eq!(dog.base_text.as_deref(), Some("Le chien aboie."));
+
+    let doc = &result.doc;
+    assert_eq!(doc_text(doc), "Le chat dort.\n\nLe chien aboie.");
+    assert_eq!(doc.spans["span-1"].source_text, "Le chat");
+    let ids: Vec<&str> = doc.spans["span-2"].variants.iter().map(|v| v.id.as_str()).collect();
+    assert_eq!(ids, ["span-2-neutral", "span-2-casual-1"]);
+    assert_eq!(docs.lock().unwrap().len(), 1);
+}
+
+fn token_budget(max_tokens: u64) -> Option<JobBudget> {
+    Some(JobBudget {
+        max_tokens: Some(max_tokens),
+        max_cost_usd: None,
+    })
+}
+
+#[tokio::test]
+async fn budget_cap_pauses_until_confirmed() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let seen_by_gate = seen.clone();
+    let gate = move |status: &BudgetStatus| {
+        seen_by_gate.lock().unwrap().push(status.clone());
+        async { true }
+    };
+    let opts = Options {
+        budget: token_budget(1),
+        budget_gate: Some(Box::new(gate)),
+        ..Options::default()
+    };
+    let run = run_with("The cat sleeps. The dog barks.", "happy_path.json", opts).await;
+    assert!(run.result.expect("the job goes on once confirmed").job.ready);
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 1, "a confirmed job is not paused again");
+    assert!(seen[0].reached);
+    assert_eq!(seen[0].job_id, "job-test");
+    assert!(seen[0].tokens > 1);
+}
+
+#[tokio::test]
+async fn budget_cap_stops_the_job_when_declined() {
+    let opts = Options {
+        budget: token_budget(1),
+        budget_gate: Some(Box::new(|_: &BudgetStatus| async { false })),
+        ..Options::default()
+    };
+    let run = run_with("The cat sleeps. The dog barks.", "happy_path.json", opts).await;
+
+    match run.result {
+        Err(ApiError::BudgetExceeded { tokens }) => assert!(tokens > 1),
+        other => panic!("expected the budget to stop the job, got {:?}", other.map(|r| r.job.id)),
+    }
+    let last = run.jobs.last().expect("job events emitted");
+    assert!(!last.ready);
+    assert_eq!(last.segments[1].base_stage, SegmentStage::Pending);
+}
+
+#[tokio::test]
+async fn repeated_sentences_are_translated_once() {
+    let dir = std::env::temp_dir().join(format!("boka-translation-cache-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let cache = Arc::new(TranslationCache::new(&dir));
+    let opts = Options {
+        translation_cache: Some(cache.clone()),
+        ..Options::default()
+    };
+    // The fixture has one base reply: the second sentence, spacing aside,
+    // must come from the cache.
+    let run = run_with("The cat sleeps. The cat  sleeps.", "repeated_sentence.json", opts).await;
+    let result = run.result.expect("translation should succeed");
+    assert_eq!(result.doc.block_texts(), ["Le chat dort.", "Le chat dort."]);
+    assert_eq!(cache.stats().0, 1);
+
+    cache.clear().unwrap();
+    assert_eq!(cache.stats(), (0, 0));
+}
@@ -0,0 +1,87 @@
+//! Trashing, restoring and purging stories and docs.
+
+use boka_core::trash::{Trash, TrashError, RETENTION_DAYS};
+
+use serde_json::{json, Value};
+
+fn library() -> Value {
+    json!([
+        { "id": "cats", "title": "Cats", "translations": {
+            "fr": { "language": "fr", "doc": null, "tags": ["homework"] },
+            "de": { "language": "de", "doc": null }
+        } },
+        { "id": "sea", "title": "Sea", "translations": {} }
+    ])
+}
+
+#[test]
+fn trashed_stories_and_docs_can_be_restored() {
+    let mut all = library();
+    let mut trash = Trash::default();
+
+    let fr = trash.trash(&mut all, "cats", Some("fr")).unwrap();
+    assert_eq!((fr.story_id.as_str(), fr.language.as_deref(), fr.title.as_str()), ("cats", Some("fr"), "Cats"));
+    assert_eq!(fr.purge_at - fr.trashed_at, RETENTION_DAYS * 24 * 60 * 60 * 1000);
+    assert!(all[0]["translations"].get("fr").is_none());
+    let cats = trash.trash(&mut all, "cats", None).unwrap();
+    assert_eq!(all.as_array().unwrap().len(), 1);
+    assert_ne!(cats.id, fr.id);
+    assert!(matches!(trash.trash(&mut all, "cats", None), Err(TrashError::NotFound(_))));
+    assert!(matches!(trash.trash(&mut all, "sea", Some("fr")), Err(TrashError::NotFound(_))));
+
+    // A translation needs its story back first.
+    assert!(matches!(trash.restore(&mut all, &fr.id), Err(TrashError::MissingStory(_))));
+    trash.restore(&mut all, &cats.id).unwrap();
+    trash.restore(&mut all, &fr.id).unwrap();
+    assert_eq!(all[1]["translations"]["fr"]["tags"], json!(["homework"]));
+    assert_eq!(all[1]["translations"]["de"]["language"], "de");
+    assert!(trash.0.is_empty());
+    assert!(matches!(trash.restore(&mut all, &fr.id), Err(TrashError::NotFound(_))));
+}
+
+#[test]
+fn restoring_over_a_live_entry_is_refused() {
+    let mut all = library();
+    let mut trash = Trash::default();
+    let sea = trash.trash(&mut all, "sea", None).unwrap();
+    all.as_array_mut().unwrap().push(json!({ "id": "sea", "title": "New sea" }));
+    assert!(matches!(trash.restore(&mut all, &sea.id), Err(TrashError::Exists(_))));
+    assert_eq!(trash.items(), [sea]);
+}
+
+#[test]
+fn frontend_writes_that_drop_entries_are_trashed() {
+    let saved = library();
+    let mut incoming = library();
+    incoming.as_array_mut().unwrap().remove(1);
+    incoming[0]["translations"].as_object_mut().unwrap().remove("de");
+
+    let mut trash = Trash::default();
+    let caught = trash.catch_removed(&incoming, &saved);
+    let caught: Vec<_> = caught.iter().map(|i| (i.story_id.as_str(), i.language.as_deref())).collect();
+    assert_eq!(caught, [("cats", Some("de")), ("sea", None)]);
+    assert!(trash.catch_removed(&saved, &saved).is_empty());
+
+    let id = trash.items()[1].id.clone();
+    trash.restore(&mut incoming, &id).unwrap();
+    assert_eq!(incoming[1]["title"], "Sea");
+}
+
+#[test]
+fn expired_entries_are_purged() {
+    let dir = std::env::temp_dir().join(format!("boka-trash-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let mut all = library();
+    let mut trash = Trash::load(&dir).unwrap();
+    let cats = trash.trash(&mut all, "cats", None).unwrap();
+    let sea = trash.trash(&mut all, "sea", None).unwrap();
+    trash.save(&dir).unwrap();
+
+    let mut loaded = Trash::load(&dir).unwrap();
+    assert_eq!(loaded.items(), [cats.clone(), sea.clone()]);
+    assert!(loaded.purge_expired_at(cats.purge_at - 1).is_empty());
+    assert_eq!(loaded.purge(&sea.id).unwrap(), sea);
+    assert_eq!(loaded.purge_expired_at(cats.purge_at), [cats]);
+    assert!(loaded.0.is_empty());
+    let _ = std::fs::remove_dir_all(&dir);
+}
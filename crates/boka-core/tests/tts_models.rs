@@ -0,0 +1,83 @@
+//! TTS model registry and per-language model choice.
+
+use boka_core::settings::Settings;
+use boka_core::tts_models::{TtsModelError, TtsModelRegistry, TtsQuality};
+
+use std::collections::BTreeMap;
+
+const REGISTRY: &str = r#"{
+  "version": 3,
+  "models": [
+    { "id": "base", "name": "Base", "engine": "kokoro", "default": true,
+      "languages": ["en", "fr"], "sizeBytes": 100, "quality": "medium" },
+    { "id": "small", "name": "Small", "engine": "kokoro",
+      "languages": ["en", "fr"], "sizeBytes": 10, "quality": "low",
+      "modelFile": "small.onnx", "voicesFile": "voices.bin" },
+    { "id": "multi-lo", "name": "Multi", "engine": "kokoro",
+      "languages": ["de"], "sizeBytes": 10, "quality": "low" },
+    { "id": "multi-hi", "name": "Multi HQ", "engine": "kokoro",
+      "languages": ["de", "ko"], "sizeBytes": 10, "quality": "high" }
+  ]
+}"#;
+
+#[test]
+fn bundled_registry_has_a_default_kokoro_model() {
+    let registry = TtsModelRegistry::bundled();
+    let default = registry.default_model();
+    assert_eq!(default.id, "kokoro-82m");
+    assert!(default.supports("fr") && default.supports("en-GB"));
+    assert!(registry.models.iter().all(|m| m.supports("en")));
+}
+
+#[test]
+fn resolves_the_chosen_model_per_language() {
+    let registry = TtsModelRegistry::from_json(REGISTRY).unwrap();
+    let mut chosen = BTreeMap::new();
+    assert_eq!(registry.resolve("fr", &chosen).id, "base");
+
+    chosen.insert("fr".to_string(), "small".to_string());
+    // The choice only applies to its language, and only if the model speaks it.
+    chosen.insert("en".to_string(), "multi-hi".to_string());
+    assert_eq!(registry.resolve("fr", &chosen).id, "small");
+    assert_eq!(registry.resolve("en", &chosen).id, "base");
+    // Not covered by the default: best quality model that speaks it.
+    assert_eq!(registry.resolve("de", &chosen).id, "multi-hi");
+    assert_eq!(registry.get("multi-hi").unwrap().quality, TtsQuality::High);
+    // Nobody speaks it: the default tries anyway.
+    assert_eq!(registry.resolve("sw", &chosen).id, "base");
+}
+
+#[test]
+fn installed_models_need_their_files() {
+    let registry = TtsModelRegistry::from_json(REGISTRY).unwrap();
+    let dir = std::env::temp_dir().join(format!("boka-tts-models-{}", std::process::id()));
+    let small = registry.get("small").unwrap();
+    assert!(registry.default_model().installed(&dir));
+    assert!(!small.installed(&dir));
+
+    std::fs::create_dir_all(dir.join("small")).unwrap();
+    std::fs::write(dir.join("small").join("small.onnx"), b"onnx").unwrap();
+    assert!(!small.installed(&dir));
+    std::fs::write(dir.join("small").join("voices.bin"), b"voices").unwrap();
+    assert!(small.installed(&dir));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn settings_validate_model_choices() {
+    let registry = TtsModelRegistry::from_json(REGISTRY).unwrap();
+    let mut settings = Settings::default();
+    settings.set_tts_model(&registry, "FR", Some("small")).unwrap();
+    assert_eq!(settings.view().tts_models.get("fr").map(String::as_str), Some("small"));
+
+    assert!(settings.set_tts_model(&registry, "fr", Some("nope")).is_err());
+    assert!(settings.set_tts_model(&registry, "ko", Some("small")).is_err());
+    assert!(matches!(
+        registry.validate("ko", "small"),
+        Err(TtsModelError::Unsupported { .. })
+    ));
+
+    settings.set_tts_model(&registry, "fr", None).unwrap();
+    assert!(settings.tts_models.is_empty());
+    assert!(TtsModelRegistry::from_json(r#"{"models":[]}"#).is_err());
+}
@@ -0,0 +1,20 @@
+//! TTS warm-up policy setting.
+
+use boka_core::settings::{Settings, WarmupPolicy};
+
+#[test]
+fn warmup_policy_defaults_to_startup_and_persists() {
+    let dir = std::env::temp_dir().join(format!("boka-tts-warmup-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    // Settings files written before the policy existed still load.
+    std::fs::write(dir.join("settings.json"), r#"{"variantBounds":{"min":2,"max":4}}"#).unwrap();
+    let mut settings = Settings::load(&dir).unwrap();
+    assert_eq!(settings.tts_warmup, WarmupPolicy::OnStartup);
+
+    settings.tts_warmup = WarmupPolicy::OnDocOpen;
+    settings.save(&dir).unwrap();
+    let saved = std::fs::read_to_string(dir.join("settings.json")).unwrap();
+    assert!(saved.contains(r#""ttsWarmup": "on-doc-open""#) || saved.contains(r#""ttsWarmup":"on-doc-open""#));
+    assert_eq!(Settings::load(&dir).unwrap().view().tts_warmup, WarmupPolicy::OnDocOpen);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,123 @@
+//! Tutor mode: questions about a block get an answer from the model and are
+//! kept, capped, with the doc.
+
+use boka_core::policy::ContentPolicy;
+use boka_core::prompts::{tutor_system_prompt, tutor_user_content};
+use boka_core::stories::{self, DocId, StoryError};
+use boka_core::tutor::{self, TutorExchange, MAX_HISTORY, MAX_QUESTION_CHARS};
+use boka_core::types::{ApiError, LlmProviderConfig, LlmProviderPreset};
+
+use serde_json::{json, Value};
+
+fn mock() -> LlmProviderConfig {
+    LlmProviderConfig {
+        preset: LlmProviderPreset::Mock,
+        ..Default::default()
+    }
+}
+
+fn library(segments: &[&str]) -> Value {
+    let segments: Vec<Value> = segments
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            json!({
+                "id": format!("seg-{i}"), "source": s,
+                "baseStage": "ready", "spanStage": "ready", "variantCount": 1
+            })
+        })
+        .collect();
+    json!([
+        { "id": "story-1", "title": "Le chat", "sourceLanguage": "en", "translations": { "fr": {
+            "doc": { "tokens": [
+                { "type": "text", "value": "Le chat dort." },
+                { "type": "text", "value": "\n\n" },
+                { "type": "text", "value": "Il pleut." }
+            ], "spans": {} },
+            "job": { "id": "job-1", "segments": segments, "ready": true }
+        } } }
+    ])
+}
+
+fn id() -> DocId {
+    DocId::parse("story-1:fr").unwrap()
+}
+
+fn exchange(block: u32, question: &str) -> TutorExchange {
+    TutorExchange {
+        block,
+        question: question.to_string(),
+        answer: format!("About {question}"),
+        model: "mock".to_string(),
+        asked_at: 1,
+    }
+}
+
+#[tokio::test]
+async fn questions_are_answered_with_the_block_and_kept() {
+    let mut all = library(&["The cat sleeps.", "It rains."]);
+    let doc = stories::find_doc(&all, &id()).unwrap();
+    let context = tutor::block_context(&doc, 1).unwrap();
+    assert_eq!((context.source.as_str(), context.translation.as_str()), ("It rains.", "Il pleut."));
+    assert!(matches!(tutor::block_context(&doc, 2), Err(StoryError::BlockOutOfRange { blocks: 2, .. })));
+
+    let answer = tutor::ask(&context, "  Why « il » ?  ", &[], Some("de"), mock(), None).await.unwrap();
+    assert_eq!((answer.block, answer.question.as_str()), (1, "Why « il » ?"));
+    assert!(!answer.answer.is_empty());
+    tutor::record(&mut all, &id(), answer.clone()).unwrap();
+    tutor::record(&mut all, &id(), exchange(0, "chat?")).unwrap();
+    assert_eq!(tutor::history(&all, &id(), Some(1)).unwrap(), [answer]);
+    assert_eq!(tutor::history(&all, &id(), None).unwrap().len(), 2);
+
+    let long = "?".repeat(MAX_QUESTION_CHARS + 1);
+    let too_long = tutor::ask(&context, &long, &[], None, mock(), None).await;
+    assert!(matches!(too_long, Err(ApiError::InputTooLarge { .. })));
+
+    // Frontend writes keep the history.
+    let mut written = library(&["The cat sleeps.", "It rains."]);
+    stories::keep_meta(&mut written, &all);
+    assert_eq!(tutor::history(&written, &id(), None).unwrap().len(), 2);
+
+    // Without aligned segments the tutor only sees the translation.
+    let stale = library(&["The cat sleeps."]);
+    let context = tutor::block_context(&stories::find_doc(&stale, &id()).unwrap(), 0).unwrap();
+    assert_eq!((context.source.as_str(), context.translation.as_str()), ("", "Le chat dort."));
+}
+
+#[tokio::test]
+async fn child_safe_answers_are_screened() {
+    let all = library(&["The cat sleeps.", "It rains."]);
+    let context = tutor::block_context(&stories::find_doc(&all, &id()).unwrap(), 0).unwrap();
+    let policy = ContentPolicy::child_safe();
+
+    // The mock answers with the question, so this answer has the word in it.
+    let question = "Is chatte like pussy?";
+    tutor::ask(&context, question, &[], None, mock(), None).await.unwrap();
+    let screened = tutor::ask(&context, question, &[], None, mock(), Some(&policy)).await;
+    assert!(matches!(screened, Err(ApiError::Refused { reason, .. }) if reason == "moderation: `pussy`"));
+    tutor::ask(&context, "Why « dort » ?", &[], None, mock(), Some(&policy)).await.unwrap();
+
+    assert!(!tutor_system_prompt("fr", Some("en"), None, None).contains("Tone note"));
+    let prompt = tutor_system_prompt("fr", Some("en"), None, Some(&policy));
+    assert!(prompt.ends_with(&format!("Tone note:\n{}", policy.tone_note())));
+}
+
+#[test]
+fn history_is_capped_and_follow_ups_carry_context() {
+    let mut all = library(&[]);
+    for i in 0..MAX_HISTORY + 5 {
+        tutor::record(&mut all, &id(), exchange(0, &format!("q{i}"))).unwrap();
+    }
+    let history = tutor::history(&all, &id(), None).unwrap();
+    assert_eq!(history.len(), MAX_HISTORY);
+    assert_eq!(history[0].question, "q5");
+
+    let missing = DocId::parse("story-1:es").unwrap();
+    assert!(matches!(tutor::history(&all, &missing, None), Err(StoryError::NotFound(_))));
+    assert!(matches!(tutor::record(&mut all, &missing, exchange(0, "q")), Err(StoryError::NotFound(_))));
+
+    let content = tutor_user_content("", "Il pleut.", &[("Why il?", "Impersonal.")], "And pleut?");
+    assert!(!content.contains("ORIGINAL"));
+    assert!(content.contains("EARLIER QUESTION:\nWhy il?\nEARLIER ANSWER:\nImpersonal."));
+    assert!(content.ends_with("QUESTION:\nAnd pleut?"));
+}
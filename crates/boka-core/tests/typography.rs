@@ -0,0 +1,85 @@
+//! Per-language typography of model output.
+
+use boka_core::gui_types::{ErrorPolicy, Granularity, InteractiveDoc, TranslationJob};
+use boka_core::length_guard::LengthGuard;
+use boka_core::settings::VariantBounds;
+use boka_core::translation::{run_translation, TranslationArgs};
+use boka_core::types::{LlmProviderConfig, LlmProviderPreset};
+use boka_core::typography::{normalize, Typographer};
+
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+#[test]
+fn quotes_dashes_and_ellipses_follow_the_language() {
+    assert_eq!(
+        normalize(r#"Il dit : "Bonjour... tu viens?" - puis il part!"#, "fr"),
+        "Il dit\u{A0}: «\u{202F}Bonjour… tu viens\u{202F}?\u{202F}» – puis il part\u{202F}!"
+    );
+    assert_eq!(normalize("« Oui »", "fr-CA"), "«\u{202F}Oui\u{202F}»");
+    assert_eq!(normalize(r#"Er sagt "Hallo" -- und geht."#, "de"), "Er sagt „Hallo“ – und geht.");
+    assert_eq!(normalize(r#"He said "don't" - twice..."#, "en"), "He said “don’t”—twice…");
+    assert_eq!(normalize(r#"彼は"はい"と言った..."#, "ja"), "彼は「はい」と言った……");
+    // Times and URLs keep their colons; unknown languages are left alone.
+    assert_eq!(normalize("À 12:30, voir http://x.fr", "fr"), "À 12:30, voir http://x.fr");
+    assert_eq!(normalize(r#"Hij zei "ja" - niet..."#, "nl"), r#"Hij zei "ja" - niet..."#);
+}
+
+#[test]
+fn quote_state_carries_across_pieces() {
+    let mut typographer = Typographer::new("de");
+    let pieces: Vec<String> = ["Sie rief \"", "komm", "\" und lachte."]
+        .iter()
+        .map(|p| typographer.apply(p))
+        .collect();
+    assert_eq!(pieces.concat(), "Sie rief „komm“ und lachte.");
+}
+
+#[tokio::test]
+async fn jobs_store_normalized_translations() {
+    let result = run_translation(TranslationArgs {
+        story_text: r#"Il dit "oui"."#.to_string(),
+        job_id: "job-typography".to_string(),
+        target_language: "fr".to_string(),
+        source_language: None,
+        adult_mode: false,
+        content_policy: None,
+        dense_spans: false,
+        reproducible: false,
+        prompt_overrides: Default::default(),
+        judge: None,
+        variant_bounds: VariantBounds::default(),
+        length_guard: LengthGuard::default(),
+        simplify_level: None,
+        dual_output: false,
+        refine_variants: false,
+        granularity: Granularity::Sentence,
+        comprehension_every: None,
+        annotations: Default::default(),
+        review: None,
+        error_policy: ErrorPolicy::Abort,
+        budget: None,
+        budget_gate: None,
+        confirmation: None,
+        translation_cache: None,
+        transcript: None,
+        ui_language: None,
+        // No fixture: the mock echoes the source back.
+        provider: LlmProviderConfig {
+            preset: LlmProviderPreset::Mock,
+            api_key: None,
+            base_url: None,
+            model: None,
+            reasoning_model: false,
+        },
+        cancelled: Arc::new(AtomicBool::new(false)),
+        on_job: Box::new(|_: &TranslationJob| async {}),
+        on_doc: Box::new(|_: &InteractiveDoc| async {}),
+    })
+    .await
+    .expect("translation should succeed");
+
+    let expected = "Il dit «\u{202F}oui\u{202F}».";
+    assert_eq!(result.job.segments[0].base_text.as_deref(), Some(expected));
+    assert_eq!(result.doc.block_texts(), [expected]);
+}
@@ -0,0 +1,80 @@
+//! Phrase usages across the library: lemmas in a row, per language, with
+//! the index rebuilt when the library changes.
+
+use boka_core::stories;
+use boka_core::usages::{PhraseIndex, UsageIndexCache, MAX_USAGES};
+
+use serde_json::{json, Value};
+use std::fs;
+use std::path::PathBuf;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("boka-usages-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn translation(text: &str) -> Value {
+    let mut tokens = Vec::new();
+    for (i, block) in text.split("\n\n").enumerate() {
+        if i > 0 {
+            tokens.push(json!({ "type": "text", "value": "\n\n" }));
+        }
+        tokens.push(json!({ "type": "text", "value": block }));
+    }
+    json!({ "doc": { "tokens": tokens, "spans": {} } })
+}
+
+fn library() -> Value {
+    json!([
+        { "id": "story-1", "title": "Le chat", "translations": {
+            "fr": translation("Le chat noir dort. Il pleut.\n\nLes chats noirs jouent dehors."),
+            "es": translation("El gato negro duerme.")
+        } },
+        { "id": "story-2", "title": "La nuit", "translations": {
+            "fr": translation("Un chat, noir comme la nuit. Un chat noir passe.")
+        } },
+        { "id": "story-3", "title": "Sans doc", "translations": { "fr": { "doc": null } } }
+    ])
+}
+
+#[test]
+fn phrases_match_lemmas_in_a_row() {
+    let index = PhraseIndex::build(&library(), "fr");
+    assert_eq!((index.language(), index.sentence_count()), ("fr", 5));
+
+    let usages = index.find("chat noir", MAX_USAGES);
+    let found: Vec<_> = usages.iter().map(|u| (u.doc_id.as_str(), u.block, u.sentence)).collect();
+    assert_eq!(found, [("story-1:fr", 0, 0), ("story-1:fr", 1, 0), ("story-2:fr", 0, 0), ("story-2:fr", 0, 1)]);
+    assert_eq!((usages[1].title.as_str(), usages[1].text.as_str()), ("Le chat", "Les chats noirs jouent dehors."));
+
+    // Only words count: not case, not punctuation.
+    assert_eq!(index.find("chat noir comme", MAX_USAGES)[0].text, "Un chat, noir comme la nuit.");
+    assert_eq!(index.find("LE CHAT NOIR", MAX_USAGES).len(), 1);
+    assert_eq!(index.find("chat noir", 1).len(), 1);
+    assert!(index.find("chien noir", MAX_USAGES).is_empty());
+    assert!(index.find(" ... ", MAX_USAGES).is_empty());
+
+    let es = PhraseIndex::build(&library(), "es");
+    assert_eq!(es.find("gato negro", MAX_USAGES)[0].doc_id, "story-1:es");
+    assert!(PhraseIndex::build(&library(), "de").find("chat", MAX_USAGES).is_empty());
+}
+
+#[test]
+fn the_cache_follows_the_library() {
+    let dir = temp_dir("cache");
+    stories::save(&dir, &library()).unwrap();
+    let mut cache = UsageIndexCache::default();
+    let first = cache.get(&dir, "fr").unwrap();
+    assert_eq!(first.find("il pleut", MAX_USAGES).len(), 1);
+    assert!(std::sync::Arc::ptr_eq(&first, &cache.get(&dir, "fr").unwrap()));
+
+    let mut all = library();
+    all[1]["translations"]["fr"] = translation("Il pleut encore.");
+    stories::save(&dir, &all).unwrap();
+    let second = cache.get(&dir, "fr").unwrap();
+    assert_eq!(second.find("il pleut", MAX_USAGES).len(), 2);
+    assert!(second.find("chat noir", MAX_USAGES).iter().all(|u| u.doc_id == "story-1:fr"));
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,49 @@
+//! Difficulty-based variant counts.
+
+use boka_core::analysis::segment_difficulty;
+use boka_core::prompts::span_variants_system_prompt;
+use boka_core::policy::ContentPolicy;
+use boka_core::settings::{Settings, VariantBounds};
+
+#[test]
+fn harder_segments_get_more_variants() {
+    let bounds = VariantBounds::default();
+    let easy = segment_difficulty("Le chat dort.", "fr");
+    let hard = segment_difficulty(
+        "Malgré l'inexorable détérioration des circonstances économiques, l'administration municipale \
+         persistait obstinément dans ses investissements extravagants, indifférente aux protestations.",
+        "fr",
+    );
+
+    assert!(easy < 0.25, "easy = {easy}");
+    assert!(hard > 0.75, "hard = {hard}");
+    assert_eq!(bounds.target(easy), 2);
+    assert_eq!(bounds.target(hard), 4);
+    assert_eq!(segment_difficulty("", "fr"), 0.0);
+}
+
+#[test]
+fn bounds_are_validated_and_persisted() {
+    assert!(VariantBounds::new(0, 3).is_err());
+    assert!(VariantBounds::new(4, 3).is_err());
+    assert!(VariantBounds::new(1, VariantBounds::LIMIT + 1).is_err());
+
+    let single = VariantBounds::new(3, 3).unwrap();
+    assert_eq!(single.target(0.0), 3);
+    assert_eq!(single.target(1.0), 3);
+
+    let dir = std::env::temp_dir().join(format!("boka-variant-bounds-{}", std::process::id()));
+    let mut settings = Settings::load(&dir).unwrap();
+    assert_eq!(settings.variant_bounds, VariantBounds::default());
+    settings.variant_bounds = VariantBounds::new(1, 6).unwrap();
+    settings.save(&dir).unwrap();
+    assert_eq!(Settings::load(&dir).unwrap().variant_bounds, VariantBounds::new(1, 6).unwrap());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn prompt_carries_the_variant_count() {
+    let prompt = span_variants_system_prompt("fr", None, &ContentPolicy::default(), None, 5);
+    assert!(prompt.contains("Return 5 variants"));
+    assert!(!prompt.contains("{variant_count}"));
+}
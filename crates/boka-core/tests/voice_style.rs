@@ -0,0 +1,49 @@
+//! Voice styles: checked against what each voice reports it supports, and
+//! encoded in the voice id the engine generates and caches with.
+#![cfg(feature = "tts")]
+
+use boka_core::audio::{check_style, AudioError, KokoroEngine, TtsEngine};
+use boka_core::audio_types::{VoiceControls, VoiceMix, VoiceStyle};
+
+fn blend(mixes: &[(&str, f32)]) -> VoiceStyle {
+    VoiceStyle {
+        blend: mixes
+            .iter()
+            .map(|&(voice, weight)| VoiceMix { voice_id: voice.to_string(), weight })
+            .collect(),
+        force_style: false,
+    }
+}
+
+#[test]
+fn kokoro_voices_blend_in_tenths_and_can_force_their_style() {
+    let engine = KokoroEngine::new();
+    let voices = engine.voices();
+    assert!(voices.iter().all(|v| v.controls.max_blend == 2 && v.controls.force_style));
+
+    assert_eq!(engine.styled_voice("af_bella", 1.0, &VoiceStyle::default()).unwrap(), "af_bella");
+    let style = blend(&[("af_sarah", 0.3)]);
+    assert_eq!(engine.styled_voice("af_bella", 1.0, &style).unwrap(), "af_bella.7+af_sarah.3");
+    let forced = VoiceStyle { force_style: true, ..blend(&[("ff_siwis", 0.24), ("am_adam", 0.1)]) };
+    assert_eq!(engine.styled_voice("af_bella", 1.2, &forced).unwrap(), "!af_bella.7+ff_siwis.2+am_adam.1");
+
+    for (speed, style) in [
+        (2.5, VoiceStyle::default()),
+        (1.0, blend(&[("af_sarah", 0.95)])),
+        (1.0, blend(&[("af_sarah", 0.5), ("am_adam", 0.5)])),
+        (1.0, blend(&[("af_sarah", 0.1), ("am_adam", 0.1), ("bf_emma", 0.1)])),
+        (1.0, blend(&[("xx_nobody", 0.3)])),
+    ] {
+        let styled = engine.styled_voice("af_bella", speed, &style);
+        assert!(matches!(styled, Err(AudioError::InvalidStyle(_))), "{speed} {style:?}: {styled:?}");
+    }
+}
+
+#[test]
+fn voices_without_controls_take_only_a_speed() {
+    let controls = VoiceControls::default();
+    assert!(check_style(&controls, 0.8, &VoiceStyle::default()).is_ok());
+    assert!(check_style(&controls, 1.0, &blend(&[("af_sarah", 0.3)])).is_err());
+    let forced = VoiceStyle { force_style: true, ..VoiceStyle::default() };
+    assert!(check_style(&controls, 1.0, &forced).is_err());
+}
@@ -0,0 +1,101 @@
+//! Word clips: trimmed of silence, cached apart from sentence audio, and
+//! only for single words or short phrases.
+#![cfg(feature = "tts")]
+
+use boka_core::audio::{
+    generate_word_audio, trim_silence, words_namespace, AudioCache, AudioError, TtsEngine, MAX_WORD_CHARS,
+};
+use boka_core::audio_types::{AudioModelStatus, VoiceInfo};
+
+use std::fs;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Ten samples per character with half a second of near silence either side.
+struct PaddedEngine {
+    calls: AtomicUsize,
+}
+
+impl TtsEngine for PaddedEngine {
+    fn model_id(&self) -> &str {
+        "padded"
+    }
+
+    fn load_model(&mut self) -> Pin<Box<dyn Future<Output = Result<(), AudioError>> + Send + '_>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn is_loaded(&self) -> bool {
+        true
+    }
+
+    fn generate(&self, text: &str, _voice_id: &str, _speed: f32, _language: &str) -> Result<Vec<f32>, AudioError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let mut samples = vec![0.001; 500];
+        samples.extend(vec![0.5; text.chars().count() * 10]);
+        samples.extend(vec![0.001; 500]);
+        Ok(samples)
+    }
+
+    fn sample_rate(&self) -> u32 {
+        1000
+    }
+
+    fn status(&self) -> AudioModelStatus {
+        AudioModelStatus {
+            downloaded: true,
+            loading: false,
+            ready: true,
+            model_size_bytes: None,
+            error: None,
+        }
+    }
+
+    fn default_voice(&self, _language: &str) -> String {
+        String::new()
+    }
+
+    fn voices(&self) -> Vec<VoiceInfo> {
+        vec![]
+    }
+}
+
+#[test]
+fn silence_is_trimmed_to_the_padding() {
+    let mut samples = vec![0.0; 300];
+    samples.extend(vec![0.8; 100]);
+    samples.extend(vec![0.02; 200]);
+    let clip = trim_silence(&samples, 1000, 20);
+    assert_eq!(clip.len(), 20 + 100 + 20);
+    assert_eq!(clip[0], 0.0);
+    assert_eq!(clip[70], 0.8);
+    assert!(trim_silence(&[0.0; 50], 1000, 20).is_empty());
+}
+
+#[test]
+fn word_clips_are_short_and_cached_in_their_own_namespace() {
+    let dir = std::env::temp_dir().join(format!("boka-word-audio-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let cache = AudioCache::new(&dir, "default").unwrap();
+    let engine = PaddedEngine { calls: AtomicUsize::new(0) };
+
+    let clip = generate_word_audio(&engine, &cache, " chat ", "voice", "fr").unwrap();
+    // 40 ms of speech and 40 ms of padding at each end.
+    assert_eq!(clip.duration_ms, 40 + 2 * 40);
+
+    let again = generate_word_audio(&engine, &cache, "chat", "voice", "fr").unwrap();
+    assert_eq!(again.audio_base64, clip.audio_base64);
+    assert_eq!(engine.calls.load(Ordering::SeqCst), 1);
+    let namespaces = cache.namespaces();
+    assert_eq!(namespaces.len(), 1);
+    assert_eq!(namespaces[0].model, words_namespace(&engine.model_version()));
+
+    let long = "x".repeat(MAX_WORD_CHARS + 1);
+    for word in ["", "  ", "chat\nchien", long.as_str()] {
+        let result = generate_word_audio(&engine, &cache, word, "voice", "fr");
+        assert!(matches!(result, Err(AudioError::NotAWord(MAX_WORD_CHARS))));
+    }
+    assert_eq!(engine.calls.load(Ordering::SeqCst), 1);
+    let _ = fs::remove_dir_all(&dir);
+}
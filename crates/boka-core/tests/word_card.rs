@@ -0,0 +1,75 @@
+//! Word cards mined from the library.
+
+use boka_core::word_card::{make_word_card, WordCardError};
+
+use serde_json::{json, Value};
+
+fn doc(texts: &[(&str, &str)]) -> Value {
+    let mut tokens = Vec::new();
+    let mut spans = serde_json::Map::new();
+    for (i, (source, text)) in texts.iter().enumerate() {
+        let id = format!("s{i}");
+        tokens.push(json!({ "type": "span", "spanId": id }));
+        tokens.push(json!({ "type": "text", "value": "\n\n" }));
+        spans.insert(
+            id.clone(),
+            json!({
+                "id": id,
+                "sourceText": source,
+                "activeVariantIndex": 0,
+                "variants": [{ "id": format!("{id}-v"), "register": "neutral", "text": text }]
+            }),
+        );
+    }
+    json!({ "doc": { "tokens": tokens, "spans": spans } })
+}
+
+fn library() -> Value {
+    json!([
+        {
+            "id": "cats",
+            "title": "Cats",
+            "translations": {
+                "fr": doc(&[("The cat", "Le chat"), ("The cats eat.", "Les chats mangent.")]),
+                "de": doc(&[("The cat sleeps on the bed.", "Die Katze schläft auf dem Bett.")])
+            }
+        },
+        {
+            "id": "night",
+            "title": "Night",
+            "translations": {
+                "fr-CA": doc(&[("The cat sleeps on the bed.", "Le chat dort sur le lit.")]),
+                "en": null
+            }
+        }
+    ])
+}
+
+#[test]
+fn cards_take_gloss_and_example_from_the_library() {
+    let card = make_word_card(&library(), "Chats", "fr").unwrap();
+    assert_eq!((card.lemma.as_str(), card.word.as_str()), ("chat", "chat"));
+    assert_eq!(card.gloss.as_deref(), Some("The cat"));
+    assert_eq!(card.example.sentence, "Le chat dort sur le lit.");
+    assert_eq!(card.example.translation, "The cat sleeps on the bed.");
+    assert_eq!((card.example.doc_id.as_str(), card.example.title.as_str()), ("night:fr-CA", "Night"));
+    assert_eq!(card.occurrences, 3);
+    assert!(card.word_audio.is_none() && card.example_audio.is_none());
+
+    let json = serde_json::to_value(&card).unwrap();
+    assert_eq!(json["example"]["docId"], "night:fr-CA");
+    assert!(json.get("wordAudio").is_none());
+}
+
+#[test]
+fn cards_fall_back_without_a_full_sentence() {
+    let card = make_word_card(&library(), "mangent", "fr").unwrap();
+    assert_eq!(card.gloss.as_deref(), Some("The cats eat."));
+    assert_eq!(card.example.sentence, "Les chats mangent.");
+
+    assert!(matches!(
+        make_word_card(&library(), "zèbre", "fr"),
+        Err(WordCardError::NotFound { .. })
+    ));
+    assert!(matches!(make_word_card(&library(), "!!", "fr"), Err(WordCardError::Invalid(_))));
+}
@@ -1,65 +0,0 @@
-use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct AudioRequest {
-    pub text: String,
-    pub language: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub voice_id: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub speed: Option<f32>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct AudioResponse {
-    pub request_id: String,
-    pub audio_base64: String,
-    pub duration_ms: u64,
-    pub sample_rate: u32,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub enum AudioStage {
-    ModelLoading,
-    Generating,
-    Encoding,
-    CacheHit,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct AudioProgressEvent {
-    pub request_id: String,
-    pub stage: AudioStage,
-    pub message: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct AudioErrorEvent {
-    pub request_id: String,
-    pub message: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct AudioModelStatus {
-    pub downloaded: bool,
-    pub loading: bool,
-    pub ready: bool,
-    pub model_size_bytes: Option<u64>,
-    pub error: Option<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct VoiceInfo {
-    pub id: String,
-    pub name: String,
-    pub language: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub sample_url: Option<String>,
-}
@@ -1,65 +0,0 @@
-use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum SegmentStage {
-    Pending,
-    Ready,
-    Error,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct TranslationSegment {
-    pub id: String,
-    pub source: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub base_text: Option<String>,
-    pub base_stage: SegmentStage,
-    pub span_stage: SegmentStage,
-    pub variant_count: u32,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct TranslationJob {
-    pub id: String,
-    pub segments: Vec<TranslationSegment>,
-    pub ready: bool,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Variant {
-    pub id: String,
-    pub register: String,
-    pub text: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub note: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub difficulty: Option<u8>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Span {
-    pub id: String,
-    pub source_text: String,
-    pub variants: Vec<Variant>,
-    pub active_variant_index: usize,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type", rename_all = "lowercase")]
-pub enum DocToken {
-    Text { value: String },
-    #[serde(rename_all = "camelCase")]
-    Span { span_id: String },
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct InteractiveDoc {
-    pub tokens: Vec<DocToken>,
-    pub spans: std::collections::HashMap<String, Span>,
-}
@@ -1,10 +0,0 @@
-pub mod anthropic;
-#[cfg(feature = "tts")]
-pub mod audio;
-#[cfg(feature = "tts")]
-pub mod audio_types;
-pub mod gui_types;
-pub mod openai_compat;
-pub mod prompts;
-pub mod translation;
-pub mod types;
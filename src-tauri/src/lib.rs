@@ -1,68 +1,271 @@
-mod boka;
-
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
 };
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 #[cfg(feature = "tts")]
-use boka::audio::{generate_speech, AudioCache, KokoroEngine};
+use boka_core::audio::{
+    doc_audio_status, generate_speech, generate_word_audio, render_voices, render_with_pauses, AudioCache, AudioError,
+    CacheCheck, CacheNamespace, CachedAudio, TtsEngine, TtsEngines,
+};
+#[cfg(feature = "tts")]
+use boka_core::audio_types::{
+    AudioEngineReadyEvent, AudioErrorEvent, AudioModelStatus, AudioProgressEvent, AudioResponse, AudioStage,
+    DocAudioStatus, VoiceInfo, VoiceStyle, WarmupTrigger,
+};
+use boka_core::audio_types::PauseOptions;
+use boka_core::analysis::{analyze_text, TextStats};
+use boka_core::annotate::{AnnotationContext, AnnotationPlan, AnnotationRegistry};
+use boka_core::background::{
+    detect_metered, BackgroundPolicy, BackgroundScheduler, BackgroundStatus, BackgroundTask,
+};
+use boka_core::authz::{Action, Authz, Confirmations, ExternalRequest, GrantView, Interface};
+use boka_core::bundle::{self, BundleOptions, SharedAudio};
+use boka_core::command_error::CommandError;
+use boka_core::config_watch::{ConfigFile, ConfigReloadedEvent, ConfigWatcher};
+use boka_core::continuation::{continue_story, ContinuationLength, ContinueArgs};
+use boka_core::demo::{demo_story, DemoStory};
+use boka_core::doc_cache::{DocCache, DocCacheStats};
+use boka_core::experiment::{run_prompt_experiment, ExperimentArgs, ExperimentArm, ExperimentReport};
+use boka_core::export::classroom::{classroom_pack, ClassroomPackOptions};
+use boka_core::export::table::{table, Delimiter, TableKind};
+use boka_core::export::template::{list_templates, render_template, TemplateInfo};
+use boka_core::export::text::{doc_text, DocTextOptions};
+use boka_core::export::{self, readalong::{readalong_html, BlockAudio}};
+use boka_core::frequency::{doc_frequency, DocFrequency, FreqList};
+use boka_core::gui_types::{ErrorPolicy, Granularity, InteractiveDoc, SegmentEdit, TranslationJob};
+use boka_core::i18n::{self, Locale, MessageKey};
+use boka_core::import::{import_images, ImportedStory};
+use boka_core::jsonl::{from_jsonl, to_jsonl};
+use boka_core::judge::JudgeConfig;
+use boka_core::language_support::{language_support, LanguageSupport};
+use boka_core::length_guard::LengthGuard;
+use boka_core::library::{self, Collection, Collections, LibraryEntry, LibraryFilter, LibraryItem};
+use boka_core::limits::{job_fingerprint, preflight, BudgetStatus, JobBudget, JobPreflight};
+use boka_core::lint::{lint_doc, DocLint};
+use boka_core::naming::{self, StoryName};
+use boka_core::notes;
+use boka_core::paths::{BokaPaths, PathStatus};
+use boka_core::policy::ContentPolicy;
+use boka_core::practice_plan::{practice_plan, PracticePlan};
+use boka_core::pricing::{describe_cost, CostView, DisplayCurrency};
+use boka_core::profile::{self, export_profile, import_profile, ProfileImport, ProfileManifest};
+use boka_core::privacy::{self, PurgeReport, PurgeScope, RetentionPolicy};
+use boka_core::profiles::{self, Profile, Profiles};
+use boka_core::prompts::{self, PromptOverrides};
+use boka_core::provider_check::{ProbeCache, ProbeResult, ProviderProbe, ProviderTestError};
+use boka_core::render::render_block_text;
+use boka_core::report::{model_stats, ModelStats, RunReport};
+use boka_core::runtime_info::{
+    dir_usage, enabled_features, model_presence, resident_bytes, CacheUsage, RuntimeInfo, SubsystemInfo,
+};
+use boka_core::settings::{AudioPreset, Settings, SettingsView, VariantBounds, WarmupPolicy};
+use boka_core::simplify::CefrLevel;
+use boka_core::speakers::{self, SpeakerEdit, SpeakerView};
 #[cfg(feature = "tts")]
-use boka::audio_types::{AudioErrorEvent, AudioModelStatus, AudioProgressEvent, AudioResponse};
-use boka::gui_types::InteractiveDoc;
-use boka::translation::{run_translation, TranslationArgs};
-use boka::types::{ApiConfig, LlmProviderConfig, LlmProviderPreset};
+use boka_core::speech_prefetch::{doc_sentences, ReadingPosition, SpeechPrefetcher};
+use boka_core::starred::{self, Star, StarTarget, StarredItem};
+use boka_core::speech_prefetch::PrefetchPolicy;
+use boka_core::stories::{self, DocId, ListeningPosition, StoryDoc, StoryError};
+use boka_core::story_meta::{self, StoryMeta};
+use boka_core::text;
+use boka_core::translation::{
+    preview_prompts, retry_failed_segments, run_translation_with_report, BudgetGate, PromptOptions, PromptPreview,
+    RetryArgs, ReviewGate, TranslationArgs,
+};
+use boka_core::transcript::{self, transcript_path};
+use boka_core::translation_cache::TranslationCache;
+use boka_core::trash::{Trash, TrashItem};
+use boka_core::tutor::{self, TutorExchange};
+use boka_core::tts_models::{TtsModelEntry, TtsModelRegistry};
+use boka_core::types::{
+    ApiConfig, ApiError, LlmProviderConfig, LlmProviderPreset, ModelEntry, ModelPricing, ModelRegistry,
+};
+use boka_core::usages::{Usage, UsageIndexCache, MAX_USAGES};
+use boka_core::vocab_ledger::VocabLedger;
+use boka_core::word_card::{make_word_card, CardAudio, WordCard};
 
 use serde::Serialize;
 use tauri::async_runtime::Mutex;
+use tokio::sync::oneshot;
 use tauri::{Emitter, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
 
-/// Shared data directory for cross-app compatibility (TUI + GUI).
-/// Both apps read/write stories.json here.
+/// The active profile's locations (see `boka_core::profiles`).
+fn active_paths() -> Result<BokaPaths, String> {
+    let paths = BokaPaths::current().map_err(|e| e.to_string())?;
+    Ok(paths.for_profile(&active_profile()?))
+}
+
+fn active_profile() -> Result<String, String> {
+    let paths = BokaPaths::current().map_err(|e| e.to_string())?;
+    Ok(Profiles::load(&paths.data_dir).map_err(|e| e.to_string())?.active)
+}
+
+/// The active profile's namespace in the shared audio cache.
+#[cfg(feature = "tts")]
+fn open_audio_cache() -> Result<AudioCache, String> {
+    AudioCache::new(&active_paths()?.audio_cache_dir, &active_profile()?).map_err(|e| e.to_string())
+}
+
+/// The active profile's data directory. The default profile's is shared
+/// with the TUI; both apps read/write stories.json there.
 fn shared_data_dir() -> Result<PathBuf, String> {
-    let home = dirs::home_dir().ok_or("Could not determine home directory")?;
-    Ok(home.join("Library").join("Application Support").join("boka"))
+    Ok(active_paths()?.data_dir)
+}
+
+/// The top-level data directory, for what all profiles share: the model
+/// registries and the profile list.
+fn root_data_dir() -> Result<PathBuf, String> {
+    Ok(BokaPaths::current().map_err(|e| e.to_string())?.data_dir)
+}
+
+fn load_settings() -> Result<Settings, String> {
+    let dir = shared_data_dir()?;
+    Settings::load(&dir).map_err(|e| e.to_string())
 }
 
+/// Held from the load to the save of every read-modify-write of
+/// stories.json, so two commands, or a command and a background task,
+/// cannot overwrite each other's changes. Model calls run before it is
+/// taken; only the merge of their result into a fresh load runs under it.
+#[derive(Clone, Default)]
+struct StoriesLock(Arc<Mutex<()>>);
+
 #[derive(Default)]
 struct TranslationState {
     cancelled_by_job: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+    /// Jobs paused at the review gate; dropping the sender cancels the job.
+    pending_reviews: Arc<Mutex<HashMap<String, oneshot::Sender<Vec<SegmentEdit>>>>>,
+    /// Jobs paused at their budget cap; dropping the sender stops the job.
+    pending_budgets: Arc<Mutex<HashMap<String, oneshot::Sender<bool>>>>,
+    /// Running job ids by `job_fingerprint`.
+    running_by_fingerprint: Arc<Mutex<HashMap<String, String>>>,
 }
 
+/// Borrows the doc: a job emits one after every span, and copying a long
+/// story's doc each time only to serialize it adds up.
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct TranslationDocEvent {
-    job_id: String,
-    doc: InteractiveDoc,
+struct TranslationDocEvent<'a> {
+    job_id: &'a str,
+    doc: &'a InteractiveDoc,
 }
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct TranslationErrorEvent {
     job_id: String,
+    message_key: String,
     message: String,
+    detail: String,
+}
+
+/// Payload of `boka:library:meta`: shelf metadata stored for a doc.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LibraryMetaEvent {
+    doc_id: String,
+    meta: StoryMeta,
 }
 
 #[cfg(feature = "tts")]
 struct AudioState {
-    engine: Arc<Mutex<KokoroEngine>>,
+    engines: Arc<Mutex<TtsEngines>>,
     cache: Arc<Mutex<Option<AudioCache>>>,
     cancelled_by_request: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+    /// Set while a background warm-up holds the engine lock.
+    warming: Arc<AtomicBool>,
 }
 
 #[cfg(feature = "tts")]
 impl Default for AudioState {
     fn default() -> Self {
         Self {
-            engine: Arc::new(Mutex::new(KokoroEngine::new())),
+            engines: Arc::new(Mutex::new(TtsEngines::default())),
             cache: Arc::new(Mutex::new(None)),
             cancelled_by_request: Arc::new(Mutex::new(HashMap::new())),
+            warming: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+#[cfg(feature = "tts")]
+impl AudioState {
+    /// Load the model for `language` (the default model when `None`) in the
+    /// background unless a warm-up is already running. Speech requests made
+    /// meanwhile wait on the engine lock. Returns the warm-up's task when one
+    /// was started.
+    fn warm_up(
+        &self,
+        app: &tauri::AppHandle,
+        language: Option<String>,
+        trigger: WarmupTrigger,
+    ) -> Option<tauri::async_runtime::JoinHandle<()>> {
+        if self.warming.swap(true, Ordering::SeqCst) {
+            return None;
         }
+        let engines = self.engines.clone();
+        let warming = self.warming.clone();
+        let app = app.clone();
+        let task = tauri::async_runtime::spawn(async move {
+            match tts_model_for(language.as_deref()) {
+                Ok((model, model_dir)) => {
+                    let mut guard = engines.lock().await;
+                    let engine = guard.get(&model, &model_dir);
+                    if !engine.is_loaded() {
+                        let started = Instant::now();
+                        let result = engine.load_model().await;
+                        emit_engine_ready(&app, trigger, &model.id, started, result.err());
+                    }
+                }
+                Err(e) => eprintln!("[AUDIO] No TTS model to warm up: {e}"),
+            }
+            warming.store(false, Ordering::SeqCst);
+        });
+        Some(task)
+    }
+}
+
+/// The TTS model that speaks `language` per the user's settings (the
+/// registry default for `None`), and the dir its files install into.
+#[cfg(feature = "tts")]
+fn tts_model_for(language: Option<&str>) -> Result<(TtsModelEntry, PathBuf), String> {
+    let registry = tts_registry()?;
+    let model = match language {
+        Some(language) => registry.resolve(language, &load_settings()?.tts_models),
+        None => registry.default_model(),
+    };
+    let model_dir = BokaPaths::current().map_err(|e| e.to_string())?.model_dir;
+    Ok((model.clone(), model_dir))
+}
+
+#[cfg(feature = "tts")]
+fn emit_engine_ready(
+    app: &tauri::AppHandle,
+    trigger: WarmupTrigger,
+    model_id: &str,
+    started: Instant,
+    error: Option<AudioError>,
+) {
+    match &error {
+        Some(e) => eprintln!("[AUDIO] TTS model {model_id} not available ({trigger:?}): {e}"),
+        None => println!("[AUDIO] TTS model {model_id} loaded ({trigger:?})"),
     }
+    let _ = app.emit(
+        "boka:audio:engine-ready",
+        AudioEngineReadyEvent {
+            trigger,
+            model_id: model_id.to_string(),
+            ready: error.is_none(),
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            error: error.map(|e| e.to_string()),
+        },
+    );
 }
 
 #[cfg(feature = "tts")]
@@ -75,7 +278,13 @@ async fn boka_generate_speech(
     language: String,
     voice_id: Option<String>,
     speed: Option<f32>,
-) -> Result<String, String> {
+    style: Option<VoiceStyle>,
+    pauses: Option<PauseOptions>,
+    preset: Option<String>,
+    locale: Option<String>,
+) -> Result<String, CommandError> {
+    let locale = Locale::from_code(locale.as_deref());
+    let (voice_id, speed, pauses) = apply_audio_preset(preset.as_deref(), voice_id, speed, pauses)?;
     let ts = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .map_err(|e| e.to_string())?
@@ -93,42 +302,92 @@ async fn boka_generate_speech(
     {
         let mut cache_guard = state.cache.lock().await;
         if cache_guard.is_none() {
-            let app_data_dir = app
-                .path()
-                .app_data_dir()
-                .map_err(|e| e.to_string())?;
-            match AudioCache::new(&app_data_dir) {
-                Ok(c) => *cache_guard = Some(c),
-                Err(e) => return Err(e.to_string()),
-            }
+            *cache_guard = Some(open_audio_cache()?);
         }
     }
 
-    let engine = state.engine.clone();
+    let (model, model_dir) = tts_model_for(Some(&language))?;
+    let engines = state.engines.clone();
     let cache = state.cache.clone();
     let cancelled_map = state.cancelled_by_request.clone();
     let rid = request_id.clone();
-    let voice = voice_id.unwrap_or_else(|| {
-        KokoroEngine::default_voice_for_language(&language).to_string()
-    });
     let spd = speed.unwrap_or(1.0);
 
     let lang = language;
+    let foreground = app.state::<BackgroundState>().foreground();
 
     tauri::async_runtime::spawn(async move {
+        let _foreground = foreground;
         let app_handle = app.clone();
         let rid_for_progress = rid.clone();
 
-        let engine_guard = engine.lock().await;
+        let mut engines_guard = engines.lock().await;
+        let mut fallback = false;
+        if !engines_guard.get(&model, &model_dir).is_loaded() {
+            // Speak with the system voice now and load the model in the background.
+            if engines_guard.system().load_model().await.is_ok() {
+                fallback = true;
+                let _ = app.state::<AudioState>().warm_up(&app, Some(lang.clone()), WarmupTrigger::FirstUse);
+            }
+        }
+        let engine = engines_guard.get(&model, &model_dir);
+        if !fallback && !engine.is_loaded() {
+            let stage = AudioStage::ModelLoading;
+            let (key, message) = i18n::audio_stage(stage, locale);
+            let _ = app_handle.emit(
+                "boka:audio:progress",
+                AudioProgressEvent {
+                    request_id: rid.clone(),
+                    stage,
+                    message_key: key.as_str().to_string(),
+                    message,
+                },
+            );
+            let started = Instant::now();
+            let result = engine.load_model().await;
+            emit_engine_ready(&app_handle, WarmupTrigger::FirstUse, &model.id, started, result.err());
+        }
+        let engine: &dyn TtsEngine = if fallback {
+            engines_guard.system()
+        } else {
+            engines_guard.get(&model, &model_dir)
+        };
+        let voice = voice_id.unwrap_or_else(|| engine.default_voice(&lang));
+        // The system voice speaks plain; a style is for the model's voices.
+        let styled = match style.filter(|_| !fallback) {
+            Some(style) => engine.styled_voice(&voice, spd, &style),
+            None => Ok(voice),
+        };
+        let voice = match styled {
+            Ok(voice) => voice,
+            Err(e) => {
+                let (key, message) = i18n::audio_error(&e, locale);
+                let _ = app_handle.emit(
+                    "boka:audio:error",
+                    AudioErrorEvent {
+                        request_id: rid.clone(),
+                        message_key: key.as_str().to_string(),
+                        message,
+                        detail: Some(text::clip(&e.to_string())),
+                    },
+                );
+                cancelled_map.lock().await.remove(&rid);
+                return;
+            }
+        };
+        let model_id = engine.model_id().to_string();
         let cache_guard = cache.lock().await;
         let cache_ref = match cache_guard.as_ref() {
             Some(c) => c,
             None => {
+                let key = MessageKey::AudioCacheUnavailable;
                 let _ = app_handle.emit(
                     "boka:audio:error",
                     AudioErrorEvent {
                         request_id: rid.clone(),
-                        message: "Audio cache not initialized".to_string(),
+                        message_key: key.as_str().to_string(),
+                        message: i18n::message(key, locale, &[]),
+                        detail: None,
                     },
                 );
                 cancelled_map.lock().await.remove(&rid);
@@ -136,25 +395,26 @@ async fn boka_generate_speech(
             }
         };
 
-        let result = generate_speech(
-            &engine_guard,
-            cache_ref,
-            &text,
-            &voice,
-            spd,
-            &lang,
-            &cancelled,
-            |stage, msg| {
-                let _ = app_handle.emit(
-                    "boka:audio:progress",
-                    AudioProgressEvent {
-                        request_id: rid_for_progress.clone(),
-                        stage,
-                        message: msg.to_string(),
-                    },
-                );
-            },
-        );
+        let on_progress = |stage| {
+            let (key, message) = i18n::audio_stage(stage, locale);
+            let _ = app_handle.emit(
+                "boka:audio:progress",
+                AudioProgressEvent {
+                    request_id: rid_for_progress.clone(),
+                    stage,
+                    message_key: key.as_str().to_string(),
+                    message,
+                },
+            );
+        };
+        // With pauses, blank-line separated paragraphs are rendered as blocks.
+        let result = match pauses {
+            Some(pauses) => {
+                let blocks: Vec<String> = text.split("\n\n").map(str::to_string).collect();
+                render_with_pauses(engine, cache_ref, &blocks, &voice, spd, &lang, &pauses, &cancelled, on_progress)
+            }
+            None => generate_speech(engine, cache_ref, &text, &voice, spd, &lang, &cancelled, on_progress),
+        };
 
         match result {
             Ok(cached) => {
@@ -165,15 +425,20 @@ async fn boka_generate_speech(
                         audio_base64: cached.audio_base64,
                         duration_ms: cached.duration_ms,
                         sample_rate: cached.sample_rate,
+                        model_id,
+                        fallback,
                     },
                 );
             }
             Err(e) => {
+                let (key, message) = i18n::audio_error(&e, locale);
                 let _ = app.emit(
                     "boka:audio:error",
                     AudioErrorEvent {
                         request_id: rid.clone(),
-                        message: e.to_string(),
+                        message_key: key.as_str().to_string(),
+                        message,
+                        detail: Some(text::clip(&e.to_string())),
                     },
                 );
             }
@@ -190,7 +455,7 @@ async fn boka_generate_speech(
 async fn boka_cancel_audio(
     state: tauri::State<'_, AudioState>,
     request_id: String,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     let guard = state.cancelled_by_request.lock().await;
     if let Some(flag) = guard.get(&request_id) {
         flag.store(true, Ordering::Relaxed);
@@ -198,26 +463,542 @@ async fn boka_cancel_audio(
     Ok(())
 }
 
+/// Check every cached WAV against its checksum, deleting the ones that
+/// fail so they are generated again when next played.
+#[cfg(feature = "tts")]
+#[tauri::command]
+async fn boka_verify_audio_cache(state: tauri::State<'_, AudioState>) -> Result<CacheCheck, CommandError> {
+    // Holding the lock keeps speech generation from writing mid-check.
+    let guard = state.cache.lock().await;
+    match guard.as_ref() {
+        Some(cache) => cache.verify(),
+        None => open_audio_cache()?.verify(),
+    }
+    .map_err(CommandError::from)
+}
+
+/// Audio cache namespaces of every profile, per model version.
+#[cfg(feature = "tts")]
+#[tauri::command]
+async fn boka_list_audio_cache(state: tauri::State<'_, AudioState>) -> Result<Vec<CacheNamespace>, CommandError> {
+    let guard = state.cache.lock().await;
+    match guard.as_ref() {
+        Some(cache) => Ok(cache.namespaces()),
+        None => Ok(open_audio_cache()?.namespaces()),
+    }
+}
+
+/// Delete cached audio of `profile` (the active one when omitted), or with
+/// `model` only that model version's.
+#[cfg(feature = "tts")]
+#[tauri::command]
+async fn boka_clear_audio_cache(
+    state: tauri::State<'_, AudioState>,
+    profile: Option<String>,
+    model: Option<String>,
+) -> Result<Vec<CacheNamespace>, CommandError> {
+    let profile = match profile {
+        Some(profile) => profile,
+        None => active_profile()?,
+    };
+    let guard = state.cache.lock().await;
+    let opened;
+    let cache = match guard.as_ref() {
+        Some(cache) => cache,
+        None => {
+            opened = open_audio_cache()?;
+            &opened
+        }
+    };
+    cache.clear(&profile, model.as_deref()).map_err(|e| e.to_string())?;
+    Ok(cache.namespaces())
+}
+
 #[cfg(feature = "tts")]
 #[tauri::command]
 async fn boka_get_audio_status(
     state: tauri::State<'_, AudioState>,
-) -> Result<AudioModelStatus, String> {
-    let engine = state.engine.lock().await;
-    Ok(engine.status())
+    language: Option<String>,
+) -> Result<AudioModelStatus, CommandError> {
+    // Don't wait out a warm-up for the engine lock just to report it.
+    if state.warming.load(Ordering::SeqCst) {
+        return Ok(AudioModelStatus {
+            downloaded: false,
+            loading: true,
+            ready: false,
+            model_size_bytes: None,
+            error: None,
+        });
+    }
+    let (model, model_dir) = tts_model_for(language.as_deref())?;
+    let mut engines = state.engines.lock().await;
+    Ok(engines.get(&model, &model_dir).status())
+}
+
+/// Voices of the TTS model for `language`, for picking narrator and
+/// speaker voices, with the speed and style each one takes.
+#[cfg(feature = "tts")]
+#[tauri::command]
+async fn boka_list_voices(
+    state: tauri::State<'_, AudioState>,
+    language: Option<String>,
+) -> Result<Vec<VoiceInfo>, CommandError> {
+    let (model, model_dir) = tts_model_for(language.as_deref())?;
+    let mut engines = state.engines.lock().await;
+    Ok(engines.get(&model, &model_dir).voices())
 }
 
+/// A clip of `word` alone for flashcards and Anki exports, trimmed of the
+/// silence around it and cached apart from sentence audio. Loads the TTS
+/// model for `language` first if needed.
 #[cfg(feature = "tts")]
 #[tauri::command]
-async fn boka_preload_model(
+async fn boka_generate_word_audio(
+    state: tauri::State<'_, AudioState>,
+    word: String,
+    language: String,
+    voice: Option<String>,
+) -> Result<CardAudio, CommandError> {
+    {
+        let mut cache_guard = state.cache.lock().await;
+        if cache_guard.is_none() {
+            *cache_guard = Some(open_audio_cache()?);
+        }
+    }
+
+    let (model, model_dir) = tts_model_for(Some(&language))?;
+    let mut engines = state.engines.lock().await;
+    let engine = engines.get(&model, &model_dir);
+    if !engine.is_loaded() {
+        engine.load_model().await?;
+    }
+    let voice = voice.unwrap_or_else(|| engine.default_voice(&language));
+
+    let cache_guard = state.cache.lock().await;
+    let cache = cache_guard.as_ref().ok_or("Audio cache unavailable")?;
+    let clip = generate_word_audio(engine, cache, &word, &voice, &language)?;
+    Ok(CardAudio {
+        audio_base64: clip.audio_base64,
+        duration_ms: clip.duration_ms,
+    })
+}
+
+/// Which blocks and span variants of a doc already have audio for the
+/// voice (the language's default when unset) and speed, so the reader can
+/// show play and download indicators without generating anything.
+#[cfg(feature = "tts")]
+#[tauri::command]
+async fn boka_get_doc_audio_status(
     state: tauri::State<'_, AudioState>,
+    doc_cache: tauri::State<'_, DocCacheState>,
+    doc_id: String,
+    voice_id: Option<String>,
+    speed: Option<f32>,
+) -> Result<DocAudioStatus, CommandError> {
+    let parsed = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let story = doc_cache.get(&shared_data_dir()?, &parsed)?;
+    {
+        let mut cache_guard = state.cache.lock().await;
+        if cache_guard.is_none() {
+            *cache_guard = Some(open_audio_cache()?);
+        }
+    }
+
+    let (model, model_dir) = tts_model_for(Some(&story.language))?;
+    let mut engines = state.engines.lock().await;
+    let engine = engines.get(&model, &model_dir);
+    let voice = voice_id.unwrap_or_else(|| engine.default_voice(&story.language));
+
+    let cache_guard = state.cache.lock().await;
+    let cache = cache_guard.as_ref().ok_or("Audio cache unavailable")?;
+    Ok(doc_audio_status(
+        cache,
+        &engine.model_version(),
+        &doc_id,
+        &story.doc,
+        &voice,
+        speed.unwrap_or(1.0),
+    ))
+}
+
+/// Called by the reader when a doc is opened; queues a warm-up of the TTS
+/// model for the doc's language when the warm-up policy is `on-doc-open`.
+/// It runs when the background policy allows.
+#[cfg(feature = "tts")]
+#[tauri::command]
+async fn boka_audio_doc_opened(
+    background: tauri::State<'_, BackgroundState>,
+    language: Option<String>,
+) -> Result<(), CommandError> {
+    if load_settings()?.tts_warmup == WarmupPolicy::OnDocOpen {
+        background.lock().queue(BackgroundTask::TtsWarmup {
+            language,
+            trigger: WarmupTrigger::DocOpen,
+        });
+    }
+    Ok(())
+}
+
+/// The reader's position and the speech synthesized ahead of it; see
+/// `boka_core::speech_prefetch`.
+#[cfg(feature = "tts")]
+#[derive(Clone, Default)]
+struct PrefetchState {
+    prefetcher: Arc<std::sync::Mutex<SpeechPrefetcher>>,
+    /// Set while a prefetch runs; reports meanwhile only move the position.
+    running: Arc<AtomicBool>,
+}
+
+#[cfg(feature = "tts")]
+impl PrefetchState {
+    fn lock(&self) -> std::sync::MutexGuard<'_, SpeechPrefetcher> {
+        self.prefetcher.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Called by the reader as it moves through a doc; synthesizes the
+/// sentences past `position` into the audio cache per the prefetch policy,
+/// unless a prefetch is already following the reader.
+#[cfg(feature = "tts")]
+#[tauri::command]
+async fn boka_report_reading_position(
+    app: tauri::AppHandle,
+    prefetch: tauri::State<'_, PrefetchState>,
+    position: ReadingPosition,
+) -> Result<(), CommandError> {
+    let policy = load_settings()?.tts_prefetch;
+    prefetch.lock().report(position);
+    if policy != PrefetchPolicy::Off && !prefetch.running.swap(true, Ordering::SeqCst) {
+        let prefetch = prefetch.inner().clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = run_speech_prefetch(&app, &prefetch, policy).await {
+                eprintln!("[AUDIO] Prefetch stopped: {e}");
+            }
+            prefetch.running.store(false, Ordering::SeqCst);
+        });
+    }
+    Ok(())
+}
+
+/// Synthesize one sentence at a time, re-reading the position and the doc
+/// before each, until the window past the reader is cached. Speech requests
+/// and warm-ups come first: while they hold the engine, or the model is not
+/// loaded yet, the prefetch stops and the next report starts it again.
+#[cfg(feature = "tts")]
+async fn run_speech_prefetch(
+    app: &tauri::AppHandle,
+    prefetch: &PrefetchState,
+    policy: PrefetchPolicy,
 ) -> Result<(), String> {
-    let mut engine = state.engine.lock().await;
-    engine.load_model().await.map_err(|e| e.to_string())
+    let audio = app.state::<AudioState>();
+    let dir = shared_data_dir()?;
+    let never_cancelled = Arc::new(AtomicBool::new(false));
+    loop {
+        let Some(position) = prefetch.lock().position().cloned() else {
+            return Ok(());
+        };
+        let doc_id = DocId::parse(&position.doc_id).map_err(|e| e.to_string())?;
+        let story = app.state::<DocCacheState>().get(&dir, &doc_id).map_err(|e| e.to_string())?;
+        let Some(next) = prefetch.lock().next(&doc_sentences(&story.doc), policy) else {
+            return Ok(());
+        };
+
+        let Ok(mut engines) = audio.engines.try_lock() else {
+            return Ok(());
+        };
+        let (model, model_dir) = tts_model_for(Some(&story.language))?;
+        let engine = engines.get(&model, &model_dir);
+        if !engine.is_loaded() {
+            return Ok(());
+        }
+        let mut cache_guard = audio.cache.lock().await;
+        if cache_guard.is_none() {
+            *cache_guard = Some(open_audio_cache()?);
+        }
+        let Some(cache) = cache_guard.as_ref() else {
+            return Ok(());
+        };
+        let voice = position.voice_id.clone().unwrap_or_else(|| engine.default_voice(&story.language));
+        let result = generate_speech(
+            engine,
+            cache,
+            &next.text,
+            &voice,
+            position.speed,
+            &story.language,
+            &never_cancelled,
+            |_| {},
+        );
+        drop(cache_guard);
+        drop(engines);
+        // A sentence that fails is not retried until the reader starts over.
+        prefetch.lock().mark_done(&next.text);
+        if let Err(e) = result {
+            eprintln!("[AUDIO] Prefetch of block {} sentence {} failed: {e}", next.block, next.sentence);
+        }
+    }
+}
+
+#[cfg(feature = "tts")]
+#[tauri::command]
+async fn boka_preload_model(
+    state: tauri::State<'_, AudioState>,
+    language: Option<String>,
+) -> Result<(), CommandError> {
+    let (model, model_dir) = tts_model_for(language.as_deref())?;
+    let mut engines = state.engines.lock().await;
+    engines.get(&model, &model_dir).load_model().await.map_err(CommandError::from)
+}
+
+/// TTS model registry: `tts_models.json` in the data dir, else the bundled one.
+fn tts_registry() -> Result<TtsModelRegistry, String> {
+    TtsModelRegistry::load_or_bundled(&root_data_dir()?.join("tts_models.json")).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TtsModelView {
+    #[serde(flatten)]
+    model: TtsModelEntry,
+    installed: bool,
+}
+
+/// Registered TTS models, with whether each one's files are installed.
+#[tauri::command]
+async fn boka_list_tts_models() -> Result<Vec<TtsModelView>, CommandError> {
+    let model_dir = BokaPaths::current().map_err(|e| e.to_string())?.model_dir;
+    Ok(tts_registry()?
+        .models
+        .into_iter()
+        .map(|model| TtsModelView {
+            installed: model.installed(&model_dir),
+            model,
+        })
+        .collect())
+}
+
+/// Choose the TTS model for `language`; `None` goes back to the default.
+#[tauri::command]
+async fn boka_set_tts_model(language: String, model_id: Option<String>) -> Result<SettingsView, CommandError> {
+    let dir = shared_data_dir()?;
+    let registry = tts_registry()?;
+    let mut settings = Settings::load(&dir).map_err(|e| e.to_string())?;
+    settings
+        .set_tts_model(&registry, &language, model_id.as_deref())
+        .map_err(|e| e.to_string())?;
+    settings.save(&dir).map_err(|e| e.to_string())?;
+    Ok(settings.view())
+}
+
+/// Transcribe photographed book pages, in the given order, into story text.
+#[tauri::command]
+async fn boka_import_image(paths: Vec<String>, provider: LlmProviderConfig) -> Result<ImportedStory, CommandError> {
+    let paths: Vec<PathBuf> = paths.into_iter().map(PathBuf::from).collect();
+    import_images(&paths, provider).await.map_err(CommandError::other)
+}
+
+/// The bundled demo story; translate it with the mock provider and
+/// `baseUrl: "demo"` to try the app without an API key.
+#[tauri::command]
+async fn boka_demo_story() -> Result<DemoStory, CommandError> {
+    Ok(demo_story())
+}
+
+/// Scheduler for work nobody is waiting on (TTS model downloads and
+/// warm-ups); see `boka_core::background`. A std mutex, so the foreground
+/// guard can release it on drop.
+#[derive(Clone)]
+struct BackgroundState(Arc<std::sync::Mutex<BackgroundScheduler>>);
+
+impl BackgroundState {
+    fn new(policy: BackgroundPolicy) -> Self {
+        Self(Arc::new(std::sync::Mutex::new(BackgroundScheduler::new(policy))))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BackgroundScheduler> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Count foreground work (a job, a speech request) until the guard drops.
+    fn foreground(&self) -> ForegroundWork {
+        self.lock().begin_foreground();
+        ForegroundWork(self.clone())
+    }
+}
+
+struct ForegroundWork(BackgroundState);
+
+impl Drop for ForegroundWork {
+    fn drop(&mut self) {
+        self.0.lock().end_foreground();
+    }
+}
+
+const BACKGROUND_TICK: Duration = Duration::from_secs(5);
+
+/// Ticks between metered-connection checks.
+const METERED_CHECK_TICKS: u32 = 12;
+
+/// Poll the scheduler: check for a metered connection now and then, start
+/// the work it releases and emit `boka:background:status` when the status
+/// changes.
+fn spawn_background_loop(app: tauri::AppHandle, state: BackgroundState) {
+    tauri::async_runtime::spawn(async move {
+        let mut last: Option<BackgroundStatus> = None;
+        let mut tick: u32 = 0;
+        loop {
+            if tick % METERED_CHECK_TICKS == 0 {
+                let metered = tauri::async_runtime::spawn_blocking(detect_metered).await.ok().flatten();
+                state.lock().set_metered(metered);
+            }
+            tick = tick.wrapping_add(1);
+
+            let tasks = state.lock().take_runnable();
+            for task in tasks {
+                run_background_task(&app, &state, task);
+            }
+            let status = state.lock().status();
+            if last.as_ref() != Some(&status) {
+                let _ = app.emit("boka:background:status", &status);
+                last = Some(status);
+            }
+            tokio::time::sleep(BACKGROUND_TICK).await;
+        }
+    });
+}
+
+const TRASH_PURGE_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Delete trash past its retention, in every profile, at startup and every
+/// few hours after.
+fn spawn_trash_purge() {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let dirs = BokaPaths::current().map_err(|e| e.to_string()).and_then(|paths| {
+                let all = Profiles::load(&paths.data_dir).map_err(|e| e.to_string())?;
+                Ok(all.profiles.iter().map(|p| paths.for_profile(&p.id).data_dir).collect::<Vec<_>>())
+            });
+            let purged = dirs.and_then(|dirs| {
+                let mut count = 0;
+                for dir in dirs {
+                    let mut trash = Trash::load(&dir).map_err(|e| e.to_string())?;
+                    let purged = trash.purge_expired();
+                    if !purged.is_empty() {
+                        trash.save(&dir).map_err(|e| e.to_string())?;
+                    }
+                    count += purged.len();
+                }
+                Ok(count)
+            });
+            match purged {
+                Ok(0) => {}
+                Ok(n) => eprintln!("[TRASH] Purged {n} expired entries"),
+                Err(e) => eprintln!("[TRASH] Purge failed: {e}"),
+            }
+            tokio::time::sleep(TRASH_PURGE_INTERVAL).await;
+        }
+    });
+}
+
+/// Enforce each profile's retention policy and scrub private stories, once
+/// at startup.
+fn spawn_privacy_maintenance(stories_lock: StoriesLock) {
+    tauri::async_runtime::spawn(async move {
+        let dirs = BokaPaths::current().map_err(|e| e.to_string()).and_then(|paths| {
+            let all = Profiles::load(&paths.data_dir).map_err(|e| e.to_string())?;
+            Ok(all.profiles.iter().map(|p| paths.for_profile(&p.id).data_dir).collect::<Vec<_>>())
+        });
+        let dirs = match dirs {
+            Ok(dirs) => dirs,
+            Err(e) => return eprintln!("[PRIVACY] Maintenance failed: {e}"),
+        };
+        for dir in dirs {
+            let _stories = stories_lock.0.lock().await;
+            let done = Settings::load(&dir)
+                .map_err(|e| e.to_string())
+                .and_then(|settings| privacy::run_maintenance(&dir, &settings.retention).map_err(|e| e.to_string()));
+            match done {
+                Ok(report) if report.expired_transcripts.files > 0 || report.scrubbed > 0 => eprintln!(
+                    "[PRIVACY] {}: deleted {} expired transcripts, scrubbed {} private entries",
+                    dir.display(),
+                    report.expired_transcripts.files,
+                    report.scrubbed
+                ),
+                Ok(_) => {}
+                Err(e) => eprintln!("[PRIVACY] Maintenance failed in {}: {e}", dir.display()),
+            }
+        }
+    });
 }
 
+#[cfg(feature = "tts")]
+fn run_background_task(app: &tauri::AppHandle, state: &BackgroundState, task: BackgroundTask) {
+    let BackgroundTask::TtsWarmup { language, trigger } = &task;
+    match app.state::<AudioState>().warm_up(app, language.clone(), *trigger) {
+        Some(warming) => {
+            let state = state.clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = warming.await;
+                state.lock().finish(&task);
+            });
+        }
+        // Another warm-up holds the engine; it covers this one.
+        None => state.lock().finish(&task),
+    }
+}
+
+/// TTS warm-ups are the only background work, and need the `tts` feature.
+#[cfg(not(feature = "tts"))]
+fn run_background_task(_app: &tauri::AppHandle, state: &BackgroundState, task: BackgroundTask) {
+    state.lock().finish(&task);
+}
+
+/// Save the background policy and apply it at once.
+#[tauri::command]
+async fn boka_set_background_policy(
+    state: tauri::State<'_, BackgroundState>,
+    policy: BackgroundPolicy,
+) -> Result<BackgroundStatus, CommandError> {
+    let dir = shared_data_dir()?;
+    let mut settings = Settings::load(&dir).map_err(|e| e.to_string())?;
+    settings.set_background(policy).map_err(|e| e.to_string())?;
+    settings.save(&dir).map_err(|e| e.to_string())?;
+    let mut scheduler = state.lock();
+    scheduler.set_policy(policy);
+    Ok(scheduler.status())
+}
+
+#[tauri::command]
+async fn boka_get_background_status(
+    state: tauri::State<'_, BackgroundState>,
+) -> Result<BackgroundStatus, CommandError> {
+    Ok(state.lock().status())
+}
+
+/// Recent probe results, so settings screens can test freely.
+#[derive(Default)]
+struct ProviderProbeState(Mutex<ProbeCache>);
+
+/// Send a one-token request, or reuse a result from the last minute unless
+/// `force` is set. Failures say what to fix (key, model, billing,
+/// reachability) so the settings screen can point at it.
 #[tauri::command]
-async fn boka_test_provider(provider: LlmProviderConfig) -> Result<String, String> {
+async fn boka_test_provider(
+    state: tauri::State<'_, ProviderProbeState>,
+    provider: LlmProviderConfig,
+    force: Option<bool>,
+) -> ProbeResult {
+    if !force.unwrap_or(false) {
+        if let Some(cached) = state.0.lock().await.get(&provider) {
+            return cached;
+        }
+    }
+    let result = probe_provider(provider.clone()).await.map(ProviderProbe::new);
+    state.0.lock().await.put(&provider, &result);
+    result
+}
+
+async fn probe_provider(provider: LlmProviderConfig) -> Result<String, ProviderTestError> {
     let mut cfg = ApiConfig::from_env("fr", None, false, false);
     cfg.provider = provider;
 
@@ -231,19 +1012,10 @@ async fn boka_test_provider(provider: LlmProviderConfig) -> Result<String, Strin
         {
             cfg.provider.api_key = std::env::var("ANTHROPIC_API_KEY").ok();
         }
-        if cfg
-            .provider
-            .model
-            .as_ref()
-            .map(|m| m.trim().is_empty())
-            .unwrap_or(true)
-        {
-            cfg.provider.model = Some("claude-sonnet-4-20250514".to_string());
-        }
 
-        let client = boka::anthropic::AnthropicClient::new(cfg).map_err(|e| e.to_string())?;
+        let client = boka_core::anthropic::AnthropicClient::new(cfg)?;
         let t0 = Instant::now();
-        client.test_connection().await.map_err(|e| e.to_string())?;
+        client.test_connection().await?;
         let ms = t0.elapsed().as_millis();
 
         Ok(format!(
@@ -252,14 +1024,19 @@ async fn boka_test_provider(provider: LlmProviderConfig) -> Result<String, Strin
             client.model(),
             ms
         ))
+    } else if matches!(cfg.provider.preset, LlmProviderPreset::Mock) {
+        let client = boka_core::mock::MockClient::new(cfg)?;
+        client.test_connection().await?;
+
+        Ok(format!("provider: mock\nmodel: {}\nauth: none\nlatencyMs: 0", client.model()))
     } else {
         let preset = format!("{:?}", cfg.provider.preset).to_lowercase();
-        let client = boka::openai_compat::OpenAiCompatClient::new(cfg).map_err(|e| e.to_string())?;
+        let client = boka_core::openai_compat::OpenAiCompatClient::new(cfg)?;
         let endpoint = client.chat_completions_url();
         let auth = if client.has_api_key() { "bearer (set)" } else { "none" };
 
         let t0 = Instant::now();
-        client.test_connection().await.map_err(|e| e.to_string())?;
+        client.test_connection().await?;
         let ms = t0.elapsed().as_millis();
 
         Ok(format!(
@@ -275,21 +1052,201 @@ async fn boka_test_provider(provider: LlmProviderConfig) -> Result<String, Strin
 }
 
 #[tauri::command]
-async fn boka_start_translation(
-    app: tauri::AppHandle,
+async fn boka_analyze_text(story_text: String, language: Option<String>) -> Result<TextStats, CommandError> {
+    let lang = language.unwrap_or_else(|| "en".to_string());
+    Ok(analyze_text(&story_text, &lang))
+}
+
+/// Size, segment count and cost estimate for a story; large stories must echo
+/// the returned `confirmationToken` to `boka_start_translation`.
+#[tauri::command]
+async fn boka_prepare_translation(
+    story_text: String,
+    provider: LlmProviderConfig,
+) -> Result<JobPreflight, CommandError> {
+    let currency = load_settings()?.pricing.currency;
+    preflight(&story_text, &provider, &currency).map_err(CommandError::from)
+}
+
+/// Run the same segments through two or more arms and return the side-by-side report.
+#[tauri::command]
+async fn boka_run_prompt_experiment(
+    story_text: String,
+    target_language: Option<String>,
+    source_language: Option<String>,
+    dense_spans: bool,
+    max_segments: Option<usize>,
+    arms: Vec<ExperimentArm>,
+) -> Result<ExperimentReport, CommandError> {
+    let content_policy = load_settings()?.child_safe.enabled.then(ContentPolicy::child_safe);
+
+    run_prompt_experiment(ExperimentArgs {
+        story_text,
+        target_language: target_language.unwrap_or_else(|| "fr".to_string()),
+        source_language,
+        content_policy,
+        dense_spans,
+        max_segments,
+        arms,
+        cancelled: Arc::new(AtomicBool::new(false)),
+    })
+    .await
+    .map_err(CommandError::from)
+}
+
+/// `prompts.json` plus the addendum for `language` and, unless
+/// `prompts.json` sets its own, the preamble from settings. Read per job so
+/// edits apply from the next job on.
+fn job_prompt_overrides(language: &str) -> Result<PromptOverrides, String> {
+    let dir = shared_data_dir()?;
+    let mut overrides = PromptOverrides::load(&dir).map_err(|e| e.to_string())?;
+    if let Some(addendum) = prompts::load_addendum(&dir, language).map_err(|e| e.to_string())? {
+        overrides.language_addendum = Some(addendum);
+    }
+    if overrides.system_preamble.is_none() {
+        overrides.system_preamble = load_settings()?.system_preamble;
+    }
+    Ok(overrides)
+}
+
+#[tauri::command]
+async fn boka_list_prompt_addenda() -> Result<Vec<String>, CommandError> {
+    Ok(prompts::list_addenda(&shared_data_dir()?))
+}
+
+#[tauri::command]
+async fn boka_get_prompt_addendum(language: String) -> Result<Option<String>, CommandError> {
+    let dir = shared_data_dir()?;
+    let path = prompts::addendum_path(&dir, &language).map_err(|e| e.to_string())?;
+    if !path.is_file() {
+        return Ok(None);
+    }
+    std::fs::read_to_string(path).map(Some).map_err(CommandError::other)
+}
+
+/// Save the addendum for `language`; `None` or blank text removes it.
+#[tauri::command]
+async fn boka_set_prompt_addendum(language: String, text: Option<String>) -> Result<(), CommandError> {
+    prompts::save_addendum(&shared_data_dir()?, &language, text.as_deref()).map_err(CommandError::other)
+}
+
+/// The exact system prompts a job with these options would send, given the
+/// current settings, `prompts.json` and addenda. Overrides set in `options`
+/// (e.g. an unsaved addendum) win over the saved ones.
+#[tauri::command]
+async fn boka_preview_prompts(mut options: PromptOptions) -> Result<PromptPreview, CommandError> {
+    if load_settings()?.child_safe.enabled {
+        options.adult_mode = false;
+        options.content_policy = Some(ContentPolicy::child_safe());
+    }
+    let draft = options.prompt_overrides;
+    let saved = job_prompt_overrides(&options.target_language)?;
+    options.prompt_overrides = PromptOverrides {
+        base_translation: draft.base_translation.or(saved.base_translation),
+        span_planning: draft.span_planning.or(saved.span_planning),
+        span_variants: draft.span_variants.or(saved.span_variants),
+        language_addendum: draft.language_addendum.or(saved.language_addendum),
+        system_preamble: draft.system_preamble.or(saved.system_preamble),
+    };
+    Ok(preview_prompts(options))
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn boka_start_translation(
+    app: tauri::AppHandle,
     state: tauri::State<'_, TranslationState>,
     story_text: String,
     target_language: Option<String>,
     source_language: Option<String>,
     adult_mode: bool,
+    content_policy: Option<ContentPolicy>,
     dense_spans: bool,
+    reproducible: Option<bool>,
+    judge: Option<JudgeConfig>,
+    simplify_level: Option<CefrLevel>,
+    dual_output: Option<bool>,
+    refine_variants: Option<bool>,
+    granularity: Option<Granularity>,
+    comprehension_every: Option<u32>,
+    annotations: Option<Vec<String>>,
+    review_required: Option<bool>,
+    error_policy: Option<ErrorPolicy>,
+    budget: Option<JobBudget>,
+    confirmation_token: Option<String>,
+    allow_duplicate: Option<bool>,
+    locale: Option<String>,
+    system_preamble: Option<String>,
+    transcript: Option<bool>,
+    private: Option<bool>,
+    ui_language: Option<String>,
     provider: LlmProviderConfig,
-) -> Result<String, String> {
+) -> Result<String, CommandError> {
+    let locale = Locale::from_code(locale.as_deref());
+    // Reject oversized or unconfirmed large inputs before any job state exists.
+    let check = preflight(&story_text, &provider, &DisplayCurrency::default())?;
+    if check.requires_confirmation && confirmation_token.as_deref() != Some(check.confirmation_token.as_str()) {
+        return Err(ApiError::ConfirmationRequired {
+            segments: check.segment_count,
+        }
+        .into());
+    }
+
+    let settings = load_settings()?;
+    let lang = target_language.unwrap_or_else(|| "fr".to_string());
+    let mut prompt_overrides = job_prompt_overrides(&lang)?;
+    // This job's own preamble; blank sends none.
+    if system_preamble.is_some() {
+        prompt_overrides.system_preamble = system_preamble;
+    }
+    // A hand-edited settings file may hold bounds the setter would refuse.
+    let variant_bounds = VariantBounds::new(settings.variant_bounds.min, settings.variant_bounds.max).unwrap_or_default();
+    let guard = settings.length_guard;
+    let length_guard = LengthGuard::new(guard.enabled, guard.max_ratio).unwrap_or_default();
+    let ui_language = ui_language.filter(|l| !l.trim().is_empty()).or(settings.ui_language);
+
     let ts = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .map_err(|e| e.to_string())?
         .as_millis();
     let job_id = format!("job-{}", ts);
+    // A private job leaves its source nowhere: no transcript, no cache entries.
+    let private = private.unwrap_or(false);
+    let transcript = match transcript.unwrap_or(false) && !private {
+        true => Some(transcript_path(&shared_data_dir()?, &job_id).map_err(CommandError::other)?),
+        false => None,
+    };
+
+    // The same job started again (a double click on Translate) gets the id of
+    // the running one, so the caller's listeners follow it instead of paying
+    // for a second run. `allow_duplicate` starts it anyway.
+    let options = serde_json::json!({
+        "targetLanguage": &lang,
+        "sourceLanguage": &source_language,
+        "adultMode": &adult_mode,
+        "contentPolicy": &content_policy,
+        "denseSpans": &dense_spans,
+        "reproducible": &reproducible,
+        "judge": &judge,
+        "simplifyLevel": &simplify_level,
+        "dualOutput": &dual_output,
+        "refineVariants": &refine_variants,
+        "granularity": &granularity,
+        "comprehensionEvery": &comprehension_every,
+        "annotations": &annotations,
+        "reviewRequired": &review_required,
+        "errorPolicy": &error_policy,
+        "budget": &budget,
+        "systemPreamble": &prompt_overrides.system_preamble,
+    });
+    let fingerprint = job_fingerprint(&story_text, &options, &provider);
+    {
+        let mut running = state.running_by_fingerprint.lock().await;
+        if let Some(existing) = running.get(&fingerprint).filter(|_| !allow_duplicate.unwrap_or(false)) {
+            return Ok(existing.clone());
+        }
+        running.insert(fingerprint, job_id.clone());
+    }
 
     let cancelled = Arc::new(AtomicBool::new(false));
     state
@@ -298,18 +1255,70 @@ async fn boka_start_translation(
         .await
         .insert(job_id.clone(), cancelled.clone());
 
+    // Child-safe mode is a backend setting: it overrides whatever the job asked for.
+    let (adult_mode, content_policy) = if settings.child_safe.enabled {
+        (false, Some(ContentPolicy::child_safe()))
+    } else {
+        (adult_mode, content_policy)
+    };
+
+    // Pause after the base translations: emit `boka:translation:review` and
+    // wait for `boka_approve_segments`.
+    let review = review_required.unwrap_or(false).then(|| {
+        let app = app.clone();
+        let pending = state.pending_reviews.clone();
+        let gate = move |job: &TranslationJob| {
+            let app = app.clone();
+            let pending = pending.clone();
+            let job = job.clone();
+            async move {
+                let (tx, rx) = oneshot::channel();
+                pending.lock().await.insert(job.id.clone(), tx);
+                let _ = app.emit("boka:translation:review", job);
+                rx.await.ok()
+            }
+        };
+        Box::new(gate) as Box<dyn ReviewGate>
+    });
+
+    // Emit `boka:translation:budget-warning` at 80%; at the cap emit
+    // `boka:translation:budget` and wait for `boka_confirm_budget`.
+    let budget_gate = budget.is_some().then(|| {
+        let app = app.clone();
+        let pending = state.pending_budgets.clone();
+        let gate = move |status: &BudgetStatus| {
+            let app = app.clone();
+            let pending = pending.clone();
+            let status = status.clone();
+            async move {
+                if !status.reached {
+                    let _ = app.emit("boka:translation:budget-warning", status);
+                    return true;
+                }
+                let (tx, rx) = oneshot::channel();
+                pending.lock().await.insert(status.job_id.clone(), tx);
+                let _ = app.emit("boka:translation:budget", status);
+                rx.await.unwrap_or(false)
+            }
+        };
+        Box::new(gate) as Box<dyn BudgetGate>
+    });
+
     let app_for_task = app.clone();
+    let provider_for_meta = provider.clone();
     let state_for_task = state.cancelled_by_job.clone();
+    let running_for_task = state.running_by_fingerprint.clone();
     let job_id_for_task = job_id.clone();
-    let lang = target_language.unwrap_or_else(|| "fr".to_string());
+    let foreground = app.state::<BackgroundState>().foreground();
 
     tauri::async_runtime::spawn(async move {
+        let _foreground = foreground;
         let app_for_emit = app_for_task.clone();
         let job_id_for_emit = job_id_for_task.clone();
         let app_for_doc_emit = app_for_task.clone();
         let job_id_for_doc_emit = job_id_for_task.clone();
 
-        let on_job = move |job: &boka::gui_types::TranslationJob| {
+        let on_job = move |job: &boka_core::gui_types::TranslationJob| {
             let app_for_emit = app_for_emit.clone();
             let payload = job.clone();
             async move {
@@ -317,24 +1326,51 @@ async fn boka_start_translation(
             }
         };
 
-        let on_doc = move |doc: &boka::gui_types::InteractiveDoc| {
-            let app_for_doc_emit = app_for_doc_emit.clone();
-            let payload = TranslationDocEvent {
-                job_id: job_id_for_doc_emit.clone(),
-                doc: doc.clone(),
-            };
-            async move {
-                let _ = app_for_doc_emit.emit("boka:translation:doc", payload);
-            }
+        let on_doc = move |doc: &boka_core::gui_types::InteractiveDoc| {
+            let _ = app_for_doc_emit.emit(
+                "boka:translation:doc",
+                TranslationDocEvent {
+                    job_id: &job_id_for_doc_emit,
+                    doc,
+                },
+            );
+            async {}
         };
 
-        let result = run_translation(TranslationArgs {
+        let (result, report) = run_translation_with_report(TranslationArgs {
             story_text,
             job_id: job_id_for_task.clone(),
             target_language: lang,
             source_language,
             adult_mode,
+            content_policy,
             dense_spans,
+            reproducible: reproducible.unwrap_or(false),
+            prompt_overrides,
+            judge,
+            variant_bounds,
+            length_guard,
+            simplify_level,
+            dual_output: dual_output.unwrap_or(false),
+            refine_variants: refine_variants.unwrap_or(false),
+            granularity: granularity.unwrap_or_default(),
+            comprehension_every: comprehension_every.filter(|n| *n > 0),
+            annotations: AnnotationPlan {
+                passes: annotations.unwrap_or_default(),
+                data_dir: shared_data_dir().ok(),
+                registry: None,
+            },
+            review,
+            error_policy: error_policy.unwrap_or_default(),
+            budget,
+            budget_gate,
+            confirmation: confirmation_token,
+            translation_cache: active_paths()
+                .ok()
+                .filter(|_| !private)
+                .map(|paths| Arc::new(TranslationCache::new(&TranslationCache::dir_in(&paths.cache_dir)))),
+            transcript,
+            ui_language,
             provider,
             cancelled: cancelled.clone(),
             on_job: Box::new(on_job),
@@ -342,117 +1378,1972 @@ async fn boka_start_translation(
         })
         .await;
 
+        if let Err(e) = shared_data_dir().and_then(|dir| report.save(&dir).map_err(|e| e.to_string())) {
+            eprintln!("[REPORT] Failed to save run report: {e}");
+        }
+
         match result {
             Ok(done) => {
                 let _ = app_for_task.emit(
                     "boka:translation:doc",
                     TranslationDocEvent {
-                        job_id: job_id_for_task.clone(),
-                        doc: done.doc,
+                        job_id: &job_id_for_task,
+                        doc: &done.doc,
                     },
                 );
+                tauri::async_runtime::spawn(describe_job_doc(
+                    app_for_task.clone(),
+                    job_id_for_task.clone(),
+                    provider_for_meta,
+                ));
             }
             Err(e) => {
+                let (key, message) = i18n::api_error(&e, locale);
                 let _ = app_for_task.emit(
                     "boka:translation:error",
                     TranslationErrorEvent {
                         job_id: job_id_for_emit,
-                        message: e.to_string(),
+                        message_key: key.as_str().to_string(),
+                        message,
+                        detail: text::clip(&e.to_string()),
                     },
                 );
             }
         }
 
         state_for_task.lock().await.remove(&job_id_for_task);
+        running_for_task.lock().await.retain(|_, id| *id != job_id_for_task);
     });
 
     Ok(job_id)
 }
 
+/// Polls for the finished doc of a job; the frontend saves it a moment
+/// after the final `boka:translation:doc`.
+const DESCRIBE_WAIT: Duration = Duration::from_secs(2);
+const DESCRIBE_WAIT_TRIES: u32 = 15;
+
+/// Store shelf metadata for the doc job `job_id` made, once the frontend has
+/// saved it, and emit `boka:library:meta`. Without a model description the
+/// locally computed fields are stored.
+async fn describe_job_doc(app: tauri::AppHandle, job_id: String, provider: LlmProviderConfig) {
+    let Ok(dir) = shared_data_dir() else {
+        return;
+    };
+    let mut found = None;
+    for _ in 0..DESCRIBE_WAIT_TRIES {
+        tokio::time::sleep(DESCRIBE_WAIT).await;
+        let saved = stories::load(&dir).ok().and_then(|all| stories::find_job_doc(&all, &job_id).ok());
+        if let Some(story) = saved.filter(|s| s.job.as_ref().is_some_and(|j| j.ready)) {
+            found = Some(story);
+            break;
+        }
+    }
+    let Some(story) = found else {
+        eprintln!("[META] Job {job_id} was not saved; no shelf metadata");
+        return;
+    };
+
+    let meta = story_meta::describe(&story, provider).await.unwrap_or_else(|e| {
+        eprintln!("[META] No description for job {job_id}: {e}");
+        story_meta::local_meta(&story)
+    });
+    let doc_id = DocId {
+        story_id: story.story_id,
+        language: story.language,
+    };
+    // Only the meta field goes into a fresh load, under the lock, so a
+    // frontend write made during the call is kept.
+    let stored = {
+        let stories_lock = app.state::<StoriesLock>();
+        let _stories = stories_lock.0.lock().await;
+        stories::load(&dir).and_then(|mut all| {
+            stories::set_meta(&mut all, &doc_id, &meta)?;
+            stories::save(&dir, &all)
+        })
+    };
+    match stored {
+        Ok(()) => {
+            let _ = app.emit(
+                "boka:library:meta",
+                LibraryMetaEvent {
+                    doc_id: doc_id.to_string(),
+                    meta,
+                },
+            );
+        }
+        Err(e) => eprintln!("[META] Failed to store shelf metadata for {doc_id}: {e}"),
+    }
+}
+
+/// Generate (or regenerate) shelf metadata for a saved doc with `provider`.
+/// `doc_id` is `<storyId>:<language>`.
+#[tauri::command]
+async fn boka_describe_doc(
+    stories_lock: tauri::State<'_, StoriesLock>,
+    doc_id: String,
+    provider: LlmProviderConfig,
+) -> Result<StoryMeta, CommandError> {
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let story = stories::find_doc(&stories::load(&dir)?, &doc_id)
+        .map_err(|e| e.to_string())?;
+    let meta = story_meta::describe(&story, provider).await.map_err(|e| e.to_string())?;
+    // Re-read: the library may have changed during the call.
+    let _stories = stories_lock.0.lock().await;
+    let mut all = stories::load(&dir)?;
+    stories::set_meta(&mut all, &doc_id, &meta).map_err(|e| e.to_string())?;
+    stories::save(&dir, &all)?;
+    Ok(meta)
+}
+
+/// The speaker map of a saved doc and its dialogue lines. `doc_id` is
+/// `<storyId>:<language>`.
+#[tauri::command]
+async fn boka_get_speakers(doc_id: String) -> Result<SpeakerView, CommandError> {
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let all = stories::load(&dir)?;
+    let story = stories::find_doc(&all, &doc_id).map_err(|e| e.to_string())?;
+    let map = speakers::speaker_map(&all, &doc_id)?;
+    Ok(SpeakerView::new(&story.doc, map))
+}
+
+/// Ask `provider` who says each dialogue line of a saved doc, with at most
+/// `max_speakers` speakers, and save the map. Voices already picked for the
+/// narrator and for speakers the model names again are kept.
+#[tauri::command]
+async fn boka_attribute_speakers(
+    stories_lock: tauri::State<'_, StoriesLock>,
+    doc_id: String,
+    provider: LlmProviderConfig,
+    max_speakers: Option<u32>,
+) -> Result<SpeakerView, CommandError> {
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let all = stories::load(&dir)?;
+    let story = stories::find_doc(&all, &doc_id).map_err(|e| e.to_string())?;
+    let previous = speakers::speaker_map(&all, &doc_id)?;
+    let max_speakers = max_speakers.map_or(speakers::MAX_SPEAKERS, |n| n as usize);
+    let map = speakers::attribute(&story, &previous, max_speakers, provider)
+        .await
+        .map_err(|e| e.to_string())?;
+    // Re-read: the library may have changed during the call.
+    let _stories = stories_lock.0.lock().await;
+    let mut all = stories::load(&dir)?;
+    speakers::set_speaker_map(&mut all, &doc_id, &map)?;
+    stories::save(&dir, &all)?;
+    Ok(SpeakerView::new(&story.doc, map))
+}
+
+/// Apply `edits` to the speaker map of a saved doc, in order, and save it.
+/// Nothing is saved when one fails.
+#[tauri::command]
+async fn boka_edit_speakers(
+    stories_lock: tauri::State<'_, StoriesLock>,
+    doc_id: String,
+    edits: Vec<SpeakerEdit>,
+) -> Result<SpeakerView, CommandError> {
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let _stories = stories_lock.0.lock().await;
+    let mut all = stories::load(&dir)?;
+    let story = stories::find_doc(&all, &doc_id).map_err(|e| e.to_string())?;
+    let mut map = speakers::speaker_map(&all, &doc_id)?;
+    for edit in edits {
+        map.apply(edit, &story.doc).map_err(|e| e.to_string())?;
+    }
+    speakers::set_speaker_map(&mut all, &doc_id, &map)?;
+    stories::save(&dir, &all)?;
+    Ok(SpeakerView::new(&story.doc, map))
+}
+
+/// Rename the story of a saved doc and give it a new slug. Without a
+/// `title`, the model suggests one when a `provider` is given (and the
+/// doc's shelf metadata is refreshed with it); otherwise it is derived from
+/// the story. `doc_id` is `<storyId>:<language>`.
+#[tauri::command]
+async fn boka_rename_doc(
+    stories_lock: tauri::State<'_, StoriesLock>,
+    doc_id: String,
+    title: Option<String>,
+    provider: Option<LlmProviderConfig>,
+) -> Result<StoryName, CommandError> {
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let mut title = title.filter(|t| !t.trim().is_empty());
+    let mut meta = None;
+    if let (None, Some(provider)) = (&title, provider) {
+        let story = stories::find_doc(&stories::load(&dir)?, &doc_id).map_err(|e| e.to_string())?;
+        let described = story_meta::describe(&story, provider).await.map_err(|e| e.to_string())?;
+        title = described.title.clone();
+        meta = Some(described);
+    }
+    // Re-read: the library may have changed during the call.
+    let _stories = stories_lock.0.lock().await;
+    let mut all = stories::load(&dir)?;
+    if let Some(meta) = &meta {
+        stories::set_meta(&mut all, &doc_id, meta).map_err(|e| e.to_string())?;
+    }
+    let name = naming::rename_story(&mut all, &doc_id.story_id, title.as_deref()).map_err(|e| e.to_string())?;
+    stories::save(&dir, &all)?;
+    Ok(name)
+}
+
+/// Translate the learner notes of a saved doc into `ui_language` with
+/// `provider`, save it and return it. `doc_id` is `<storyId>:<language>`.
+#[tauri::command]
+async fn boka_translate_notes(
+    stories_lock: tauri::State<'_, StoriesLock>,
+    doc_id: String,
+    ui_language: String,
+    provider: LlmProviderConfig,
+) -> Result<InteractiveDoc, CommandError> {
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let mut doc = stories::find_doc(&stories::load(&dir)?, &doc_id).map_err(|e| e.to_string())?.doc;
+    let changed = notes::translate_notes(&mut doc, &doc_id.language, &ui_language, provider).await?;
+    if changed > 0 {
+        // Re-read: the library may have changed during the calls.
+        let _stories = stories_lock.0.lock().await;
+        let mut all = stories::load(&dir)?;
+        stories::set_doc(&mut all, &doc_id, &doc).map_err(|e| e.to_string())?;
+        stories::save(&dir, &all)?;
+    }
+    Ok(doc)
+}
+
+/// Translate again the segments job `job_id` skipped and save the result
+/// into its story. Progress is emitted like a running job's; `provider`
+/// defaults to the job's own provider and model.
+#[tauri::command]
+async fn boka_retry_failed_segments(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, TranslationState>,
+    stories_lock: tauri::State<'_, StoriesLock>,
+    job_id: String,
+    provider: Option<LlmProviderConfig>,
+) -> Result<TranslationJob, CommandError> {
+    let dir = shared_data_dir()?;
+    let found = stories::find_job_doc(&stories::load(&dir)?, &job_id)
+        .map_err(|e| e.to_string())?;
+    let job = found.job.ok_or_else(|| format!("Job {} could not be read", job_id))?;
+    if found.source_text.trim().is_empty() {
+        return Err(format!("Job {} has no source text to retry with; private stories don't keep it", job_id).into());
+    }
+    let prompt_overrides = job_prompt_overrides(&found.language)?;
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    state
+        .cancelled_by_job
+        .lock()
+        .await
+        .insert(job_id.clone(), cancelled.clone());
+
+    let app_for_job = app.clone();
+    let on_job = move |job: &TranslationJob| {
+        let app = app_for_job.clone();
+        let payload = job.clone();
+        async move {
+            let _ = app.emit("boka:translation:job", payload);
+        }
+    };
+    let app_for_doc = app.clone();
+    let job_id_for_doc = job_id.clone();
+    let on_doc = move |doc: &InteractiveDoc| {
+        let _ = app_for_doc.emit(
+            "boka:translation:doc",
+            TranslationDocEvent {
+                job_id: &job_id_for_doc,
+                doc,
+            },
+        );
+        async {}
+    };
+
+    let _foreground = app.state::<BackgroundState>().foreground();
+    let result = retry_failed_segments(RetryArgs {
+        job,
+        doc: found.doc,
+        story_text: found.source_text,
+        prompt_overrides,
+        provider,
+        // Keep recording if the job was started with a transcript.
+        transcript: transcript_path(&dir, &job_id).ok().filter(|path| path.is_file()),
+        annotation_data_dir: Some(dir.clone()),
+        cancelled,
+        on_job: Box::new(on_job),
+        on_doc: Box::new(on_doc),
+    })
+    .await;
+    state.cancelled_by_job.lock().await.remove(&job_id);
+    let done = result.map_err(|e| e.to_string())?;
+
+    // Re-read: the library may have changed while the segments ran.
+    let doc_id = DocId {
+        story_id: found.story_id,
+        language: found.language,
+    };
+    let _stories = stories_lock.0.lock().await;
+    let mut all = stories::load(&dir)?;
+    stories::update_translation(&mut all, &doc_id, &done.job, &done.doc).map_err(|e| e.to_string())?;
+    stories::save(&dir, &all)?;
+    Ok(done.job)
+}
+
+/// Have the model write the next part of a saved story in its source
+/// language, toward `direction_hint` when given, then translate just that
+/// part with the settings of the doc's job and append it to the doc. The
+/// story's source text grows with it. Progress is emitted like a running
+/// job's; `provider` defaults to the job's own provider and model. In
+/// child-safe mode the child-safe policy replaces the job's.
+#[tauri::command]
+async fn boka_continue_story(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, TranslationState>,
+    stories_lock: tauri::State<'_, StoriesLock>,
+    doc_id: String,
+    direction_hint: Option<String>,
+    length: Option<ContinuationLength>,
+    provider: Option<LlmProviderConfig>,
+) -> Result<TranslationJob, CommandError> {
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let found = stories::find_doc(&stories::load(&dir)?, &doc_id).map_err(|e| e.to_string())?;
+    let job = found.job.ok_or_else(|| format!("{} has no job to continue", doc_id))?;
+    if found.source_text.trim().is_empty() {
+        return Err(format!("{} has no source text to continue; private stories don't keep it", doc_id).into());
+    }
+    let job_id = job.id.clone();
+    let prompt_overrides = job_prompt_overrides(&found.language)?;
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    state
+        .cancelled_by_job
+        .lock()
+        .await
+        .insert(job_id.clone(), cancelled.clone());
+
+    let app_for_job = app.clone();
+    let on_job = move |job: &TranslationJob| {
+        let app = app_for_job.clone();
+        let payload = job.clone();
+        async move {
+            let _ = app.emit("boka:translation:job", payload);
+        }
+    };
+    let app_for_doc = app.clone();
+    let job_id_for_doc = job_id.clone();
+    let on_doc = move |doc: &InteractiveDoc| {
+        let _ = app_for_doc.emit(
+            "boka:translation:doc",
+            TranslationDocEvent {
+                job_id: &job_id_for_doc,
+                doc,
+            },
+        );
+        async {}
+    };
+
+    let _foreground = app.state::<BackgroundState>().foreground();
+    let result = continue_story(ContinueArgs {
+        job,
+        doc: found.doc,
+        story_text: found.source_text,
+        direction_hint: direction_hint.filter(|h| !h.trim().is_empty()),
+        length: length.unwrap_or_default(),
+        prompt_overrides,
+        provider,
+        // Child-safe mode is a backend setting: it overrides the job's policy.
+        content_policy: load_settings()?.child_safe.enabled.then(ContentPolicy::child_safe),
+        translation_cache: active_paths()
+            .ok()
+            .map(|paths| Arc::new(TranslationCache::new(&TranslationCache::dir_in(&paths.cache_dir)))),
+        // Keep recording if the job was started with a transcript.
+        transcript: transcript_path(&dir, &job_id).ok().filter(|path| path.is_file()),
+        annotation_data_dir: Some(dir.clone()),
+        cancelled,
+        on_job: Box::new(on_job),
+        on_doc: Box::new(on_doc),
+    })
+    .await;
+    state.cancelled_by_job.lock().await.remove(&job_id);
+    let continued = result.map_err(|e| e.to_string())?;
+    if let Err(e) = continued.report.save(&dir) {
+        eprintln!("[REPORT] Failed to save run report: {e}");
+    }
+
+    // Re-read: the library may have changed while the new part ran.
+    let _stories = stories_lock.0.lock().await;
+    let mut all = stories::load(&dir)?;
+    let done = &continued.result;
+    stories::update_translation(&mut all, &doc_id, &done.job, &done.doc).map_err(|e| e.to_string())?;
+    stories::set_source_text(&mut all, &doc_id.story_id, &continued.story_text).map_err(|e| e.to_string())?;
+    stories::save(&dir, &all)?;
+    Ok(continued.result.job)
+}
+
+/// The run report written when job `job_id` ended.
+#[tauri::command]
+async fn boka_get_job_report(job_id: String) -> Result<RunReport, CommandError> {
+    RunReport::load(&shared_data_dir()?, &job_id).map_err(CommandError::other)
+}
+
+/// Copy job `job_id`'s transcript of provider exchanges to `path`. Only
+/// jobs started with `transcript: true` have one.
+#[tauri::command]
+async fn boka_export_job_transcript(job_id: String, path: String) -> Result<u64, CommandError> {
+    transcript::export(&shared_data_dir()?, &job_id, Path::new(&path)).map_err(CommandError::other)
+}
+
+/// Parse-failure, truncation and retry rates per provider/model, computed
+/// from the saved run reports. Nothing leaves the machine.
+#[tauri::command]
+async fn boka_get_model_stats() -> Result<Vec<ModelStats>, CommandError> {
+    Ok(model_stats(&shared_data_dir()?))
+}
+
 #[tauri::command]
 async fn boka_cancel_translation(
     state: tauri::State<'_, TranslationState>,
     job_id: String,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     let guard = state.cancelled_by_job.lock().await;
     if let Some(flag) = guard.get(&job_id) {
         flag.store(true, std::sync::atomic::Ordering::Relaxed);
     }
+    // A job waiting for review or at its budget would otherwise never see the flag.
+    state.pending_reviews.lock().await.remove(&job_id);
+    state.pending_budgets.lock().await.remove(&job_id);
+    // A cancelled job no longer counts as running for duplicate detection.
+    state.running_by_fingerprint.lock().await.retain(|_, id| *id != job_id);
     Ok(())
 }
 
+/// Approve the base translations of a job paused at the review gate, with
+/// optional corrections, and let it continue to span planning.
 #[tauri::command]
-async fn boka_read_stories() -> Result<serde_json::Value, String> {
-    let dir = shared_data_dir()?;
-    let path = dir.join("stories.json");
+async fn boka_approve_segments(
+    state: tauri::State<'_, TranslationState>,
+    job_id: String,
+    edits: Option<Vec<SegmentEdit>>,
+) -> Result<(), CommandError> {
+    let tx = state
+        .pending_reviews
+        .lock()
+        .await
+        .remove(&job_id)
+        .ok_or_else(|| format!("Job {} is not waiting for review", job_id))?;
+    tx.send(edits.unwrap_or_default())
+        .map_err(|_| format!("Job {} is no longer running", job_id).into())
+}
+
+/// Answer a job paused at its budget cap: `proceed` lets it finish without
+/// further pauses, otherwise it stops.
+#[tauri::command]
+async fn boka_confirm_budget(
+    state: tauri::State<'_, TranslationState>,
+    job_id: String,
+    proceed: bool,
+) -> Result<(), CommandError> {
+    let tx = state
+        .pending_budgets
+        .lock()
+        .await
+        .remove(&job_id)
+        .ok_or_else(|| format!("Job {} is not waiting at its budget", job_id))?;
+    tx.send(proceed)
+        .map_err(|_| format!("Job {} is no longer running", job_id).into())
+}
 
-    if !path.exists() {
-        return Ok(serde_json::Value::Array(vec![]));
+/// Session cache of parsed docs; see `boka_core::doc_cache`. Created on
+/// first use with the size from settings.
+#[derive(Default)]
+struct DocCacheState(std::sync::OnceLock<std::sync::Mutex<DocCache>>);
+
+impl DocCacheState {
+    fn lock(&self) -> std::sync::MutexGuard<'_, DocCache> {
+        self.0
+            .get_or_init(|| {
+                let capacity = load_settings().unwrap_or_default().doc_cache_size.0;
+                std::sync::Mutex::new(DocCache::new(capacity as usize))
+            })
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn is_loaded(&self) -> bool {
+        self.0.get().is_some()
     }
 
-    let contents = std::fs::read_to_string(&path)
-        .map_err(|e| format!("Failed to read stories.json: {}", e))?;
-    let value: serde_json::Value = serde_json::from_str(&contents)
-        .map_err(|e| format!("Failed to parse stories.json: {}", e))?;
+    /// Resize the cache if it exists; otherwise it reads the size when
+    /// first used.
+    fn set_capacity(&self, capacity: u32) {
+        if let Some(cache) = self.0.get() {
+            cache.lock().unwrap_or_else(|e| e.into_inner()).set_capacity(capacity as usize);
+        }
+    }
 
-    Ok(value)
+    fn get(&self, dir: &Path, doc_id: &DocId) -> Result<Arc<StoryDoc>, StoryError> {
+        self.lock().get(dir, doc_id)
+    }
 }
 
+/// One saved doc, from the session cache when it was opened before.
+/// `doc_id` is `<storyId>:<language>`.
 #[tauri::command]
-async fn boka_write_stories(stories: serde_json::Value) -> Result<(), String> {
+async fn boka_get_doc(
+    doc_cache: tauri::State<'_, DocCacheState>,
+    doc_id: String,
+) -> Result<InteractiveDoc, CommandError> {
     let dir = shared_data_dir()?;
-    std::fs::create_dir_all(&dir)
-        .map_err(|e| format!("Failed to create data dir: {}", e))?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    Ok(doc_cache.get(&dir, &doc_id)?.doc.clone())
+}
 
-    let json = serde_json::to_string_pretty(&stories)
-        .map_err(|e| format!("Failed to serialize stories: {}", e))?;
+/// Frequency bands for every word of a doc, from the user's list for its
+/// language or the built-in one.
+#[tauri::command]
+async fn boka_get_doc_frequency(
+    doc_cache: tauri::State<'_, DocCacheState>,
+    doc_id: String,
+) -> Result<DocFrequency, CommandError> {
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let story = doc_cache.get(&dir, &doc_id)?;
+    let list = FreqList::load(&dir, &story.language).map_err(|e| e.to_string())?;
+    Ok(doc_frequency(&story.doc, &story.language, list.as_ref()))
+}
 
-    // Atomic write: write to tmp file, then rename
-    let tmp = dir.join("stories.json.tmp");
-    let path = dir.join("stories.json");
-    std::fs::write(&tmp, &json)
-        .map_err(|e| format!("Failed to write stories: {}", e))?;
-    std::fs::rename(&tmp, &path)
-        .map_err(|e| format!("Failed to finalize stories: {}", e))?;
+/// Lints of a saved doc, such as spans with a single variant or quotes left
+/// open; jobs keep the ones of their doc as it was when they finished.
+#[tauri::command]
+async fn boka_lint_doc(
+    doc_cache: tauri::State<'_, DocCacheState>,
+    doc_id: String,
+) -> Result<Vec<DocLint>, CommandError> {
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    Ok(lint_doc(&doc_cache.get(&dir, &doc_id)?.doc))
+}
 
-    Ok(())
+/// Text of one block of a saved doc with the reader's variant picks (span
+/// id to variant index), for speaking, exporting or copying it.
+#[tauri::command]
+async fn boka_render_block_text(
+    doc_cache: tauri::State<'_, DocCacheState>,
+    doc_id: String,
+    block: usize,
+    selections: Option<BTreeMap<String, usize>>,
+) -> Result<String, CommandError> {
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let doc = &doc_cache.get(&dir, &doc_id)?.doc;
+    let text = render_block_text(doc, block, &selections.unwrap_or_default()).map_err(|e| e.to_string())?;
+    Ok(text)
 }
 
-pub fn run() {
-    let builder = tauri::Builder::default()
-        .plugin(tauri_plugin_updater::Builder::new().build())
-        .manage(TranslationState::default());
+/// Ids of the annotation passes a job in `language` can ask for, such as
+/// `frequency`; see `boka_core::annotate`.
+#[tauri::command]
+async fn boka_list_annotation_passes(language: String) -> Result<Vec<String>, CommandError> {
+    let dir = shared_data_dir()?;
+    let context = AnnotationContext {
+        language: &language,
+        source_language: None,
+        data_dir: Some(&dir),
+    };
+    Ok(AnnotationRegistry::builtin().available(&context).into_iter().map(str::to_string).collect())
+}
 
-    #[cfg(feature = "tts")]
-    let builder = builder
-        .manage(AudioState::default())
-        .setup(|app| {
-            let engine = app.state::<AudioState>().engine.clone();
-            tauri::async_runtime::spawn(async move {
-                let mut guard = engine.lock().await;
-                if let Err(e) = guard.load_model().await {
-                    eprintln!("[AUDIO] Model not available on startup (expected on first run): {e}");
-                } else {
-                    println!("[AUDIO] Kokoro model loaded on startup");
-                }
-            });
-            Ok(())
-        });
+/// Phrase indexes of the library, one per language, built on first search;
+/// see `boka_core::usages`.
+#[derive(Default)]
+struct UsageIndexState(std::sync::Mutex<UsageIndexCache>);
 
-    let builder = builder.invoke_handler(tauri::generate_handler![
-        boka_start_translation,
-        boka_cancel_translation,
-        boka_test_provider,
-        boka_read_stories,
-        boka_write_stories,
-        #[cfg(feature = "tts")]
-        boka_generate_speech,
-        #[cfg(feature = "tts")]
-        boka_cancel_audio,
-        #[cfg(feature = "tts")]
-        boka_get_audio_status,
-        #[cfg(feature = "tts")]
-        boka_preload_model,
+/// Sentences of docs in `language` that use `phrase`, in any inflection,
+/// for "other sentences using this phrase" while reading.
+#[tauri::command]
+async fn boka_find_usages(
+    usages: tauri::State<'_, UsageIndexState>,
+    phrase: String,
+    language: String,
+) -> Result<Vec<Usage>, CommandError> {
+    let dir = shared_data_dir()?;
+    let index = usages.0.lock().unwrap_or_else(|e| e.into_inner()).get(&dir, &language)?;
+    Ok(index.find(&phrase, MAX_USAGES))
+}
+
+/// Per language: whether the prompts know it, which TTS models speak it,
+/// romanization, frequency list and how well its text is segmented.
+#[tauri::command]
+async fn boka_get_language_support() -> Result<Vec<LanguageSupport>, CommandError> {
+    Ok(language_support(&tts_registry()?, &shared_data_dir()?))
+}
+
+#[tauri::command]
+async fn boka_get_doc_cache_stats(doc_cache: tauri::State<'_, DocCacheState>) -> Result<DocCacheStats, CommandError> {
+    Ok(doc_cache.lock().stats())
+}
+
+/// The TTS subsystems and the ids of the models in memory. A busy engine
+/// lock means synthesis or a warm-up is running, so it counts as loaded
+/// without waiting on it.
+#[cfg(feature = "tts")]
+async fn audio_runtime_info(app: &tauri::AppHandle) -> (Vec<SubsystemInfo>, Vec<String>) {
+    let audio = app.state::<AudioState>();
+    let engines = audio.engines.try_lock().ok().map(|engines| engines.loaded());
+    let subsystems = vec![
+        SubsystemInfo {
+            name: "ttsEngines".to_string(),
+            loaded: engines.as_ref().map_or(true, |ids| !ids.is_empty()),
+            approx_bytes: None,
+        },
+        SubsystemInfo {
+            name: "audioCache".to_string(),
+            loaded: audio.cache.lock().await.is_some(),
+            approx_bytes: None,
+        },
+    ];
+    (subsystems, engines.unwrap_or_default())
+}
+
+#[cfg(not(feature = "tts"))]
+async fn audio_runtime_info(_app: &tauri::AppHandle) -> (Vec<SubsystemInfo>, Vec<String>) {
+    (Vec::new(), Vec::new())
+}
+
+/// Compiled features, TTS model presence, cache sizes and what each
+/// subsystem has loaded so far, for the diagnostics panel. Reading it does
+/// not load anything.
+#[tauri::command]
+async fn boka_get_runtime_info(app: tauri::AppHandle) -> Result<RuntimeInfo, CommandError> {
+    let paths = active_paths()?;
+    let doc_cache = app.state::<DocCacheState>();
+    let (entries, bytes) = doc_cache.0.get().map_or((0, 0), |cache| {
+        let cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+        (cache.stats().len as u64, cache.approx_bytes())
+    });
+    let watching = app
+        .try_state::<ConfigWatchState>()
+        .and_then(|state| state.watcher.lock().ok().map(|watcher| watcher.is_some()))
+        .unwrap_or(false);
+
+    let (audio, loaded_models) = audio_runtime_info(&app).await;
+    let mut subsystems = vec![
+        SubsystemInfo {
+            name: "docCache".to_string(),
+            loaded: doc_cache.is_loaded(),
+            approx_bytes: doc_cache.is_loaded().then_some(bytes),
+        },
+        SubsystemInfo {
+            name: "configWatcher".to_string(),
+            loaded: watching,
+            approx_bytes: None,
+        },
+    ];
+    subsystems.extend(audio);
+
+    Ok(RuntimeInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        features: enabled_features(),
+        tts_models: model_presence(&tts_registry()?, &paths.model_dir, &loaded_models),
+        caches: vec![
+            CacheUsage {
+                name: "docCache".to_string(),
+                entries,
+                bytes,
+            },
+            dir_usage("audioCache", &paths.audio_cache_dir),
+            dir_usage("translationCache", &TranslationCache::dir_in(&paths.cache_dir)),
+        ],
+        subsystems,
+        resident_bytes: resident_bytes(),
+    })
+}
+
+#[tauri::command]
+async fn boka_read_stories() -> Result<serde_json::Value, CommandError> {
+    let dir = shared_data_dir()?;
+    stories::load(&dir).map_err(CommandError::from)
+}
+
+#[tauri::command]
+async fn boka_write_stories(
+    stories_lock: tauri::State<'_, StoriesLock>,
+    mut stories: serde_json::Value,
+) -> Result<(), CommandError> {
+    let dir = shared_data_dir()?;
+    let _stories = stories_lock.0.lock().await;
+    // Listening positions, tags and shelf metadata are saved by the backend
+    // between frontend writes.
+    let saved = stories::load(&dir)?;
+    stories::keep_listening_positions(&mut stories, &saved);
+    stories::keep_tags(&mut stories, &saved);
+    stories::keep_meta(&mut stories, &saved);
+    naming::keep_slugs(&mut stories, &saved);
+    naming::name_stories(&mut stories);
+    privacy::scrub_private_sources(&mut stories);
+    // Whatever the write drops goes to the trash first.
+    let mut trash = Trash::load(&dir).map_err(|e| e.to_string())?;
+    if !trash.catch_removed(&stories, &saved).is_empty() {
+        trash.save(&dir).map_err(|e| e.to_string())?;
+    }
+    stories::save(&dir, &stories).map_err(CommandError::from)
+}
+
+/// Switch a whole doc to one register and persist it. `doc_id` is
+/// `<storyId>:<language>`.
+#[tauri::command]
+async fn boka_set_doc_register(
+    stories_lock: tauri::State<'_, StoriesLock>,
+    doc_id: String,
+    register: String,
+) -> Result<InteractiveDoc, CommandError> {
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let _stories = stories_lock.0.lock().await;
+    let mut all = stories::load(&dir)?;
+    let doc = stories::set_doc_register(&mut all, &doc_id, &register).map_err(|e| e.to_string())?;
+    stories::save(&dir, &all)?;
+    Ok(doc)
+}
+
+/// Remember where listening stopped in a doc so playback can resume there,
+/// across sessions. `block_id` is the block (paragraph) index.
+#[tauri::command]
+async fn boka_save_listening_position(
+    stories_lock: tauri::State<'_, StoriesLock>,
+    doc_id: String,
+    block_id: u32,
+    ms: u64,
+) -> Result<ListeningPosition, CommandError> {
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let _stories = stories_lock.0.lock().await;
+    let mut all = stories::load(&dir)?;
+    let position = stories::set_listening_position(&mut all, &doc_id, block_id, ms).map_err(|e| e.to_string())?;
+    stories::save(&dir, &all)?;
+    Ok(position)
+}
+
+#[tauri::command]
+async fn boka_get_listening_position(doc_id: String) -> Result<Option<ListeningPosition>, CommandError> {
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let all = stories::load(&dir)?;
+    stories::listening_position(&all, &doc_id).map_err(CommandError::from)
+}
+
+/// Plan shadowing practice for a doc at `level` (by default the doc's own
+/// level) and store the plan with the translation, replacing any earlier one.
+#[tauri::command]
+async fn boka_generate_practice_plan(
+    stories_lock: tauri::State<'_, StoriesLock>,
+    doc_id: String,
+    level: Option<CefrLevel>,
+) -> Result<PracticePlan, CommandError> {
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let _stories = stories_lock.0.lock().await;
+    let mut all = stories::load(&dir)?;
+    let story = stories::find_doc(&all, &doc_id).map_err(|e| e.to_string())?;
+    let level = level
+        .or_else(|| story_meta::local_meta(&story).level)
+        .unwrap_or(CefrLevel::B1);
+    let plan = practice_plan(&story, level);
+    stories::set_practice_plan(&mut all, &doc_id, &plan).map_err(|e| e.to_string())?;
+    stories::save(&dir, &all)?;
+    Ok(plan)
+}
+
+/// The stored practice plan of a doc; `None` until one is generated.
+#[tauri::command]
+async fn boka_get_practice_plan(doc_id: String) -> Result<Option<PracticePlan>, CommandError> {
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let all = stories::load(&dir)?;
+    stories::saved_practice_plan(&all, &doc_id).map_err(CommandError::from)
+}
+
+async fn set_star(
+    stories_lock: &StoriesLock,
+    doc_id: &str,
+    target: StarTarget,
+    starred: bool,
+) -> Result<Vec<Star>, CommandError> {
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(doc_id).map_err(|e| e.to_string())?;
+    let _stories = stories_lock.0.lock().await;
+    let mut all = stories::load(&dir)?;
+    let stars = starred::set_star(&mut all, &doc_id, target, starred)?;
+    stories::save(&dir, &all)?;
+    Ok(stars)
+}
+
+/// Star a span of a doc, or unstar it with `starred: false`. Returns the
+/// doc's stars.
+#[tauri::command]
+async fn boka_star_span(
+    stories_lock: tauri::State<'_, StoriesLock>,
+    doc_id: String,
+    span_id: String,
+    starred: Option<bool>,
+) -> Result<Vec<Star>, CommandError> {
+    set_star(&stories_lock, &doc_id, StarTarget::Span { span_id }, starred.unwrap_or(true)).await
+}
+
+/// Star a sentence of a doc, numbered within its block as read-along and
+/// speech number them, or unstar it with `starred: false`.
+#[tauri::command]
+async fn boka_star_sentence(
+    stories_lock: tauri::State<'_, StoriesLock>,
+    doc_id: String,
+    block: u32,
+    sentence: u32,
+    starred: Option<bool>,
+) -> Result<Vec<Star>, CommandError> {
+    set_star(&stories_lock, &doc_id, StarTarget::Sentence { block, sentence }, starred.unwrap_or(true)).await
+}
+
+/// Starred spans and sentences across the library, or of docs in
+/// `language`, newest first, for the flashcard deck.
+#[tauri::command]
+async fn boka_list_starred(language: Option<String>) -> Result<Vec<StarredItem>, CommandError> {
+    let dir = shared_data_dir()?;
+    let all = stories::load(&dir)?;
+    Ok(starred::starred_items(&all, language.as_deref()))
+}
+
+/// Ask the tutor about block `block_id` (the block, or paragraph, index) of
+/// a doc and keep the exchange in the doc's history. The answer is in
+/// `ui_language`, which defaults to the settings' UI language. In child-safe
+/// mode the tutor gets the child-safe policy and its answer is screened.
+#[tauri::command]
+async fn boka_ask_about(
+    stories_lock: tauri::State<'_, StoriesLock>,
+    doc_id: String,
+    block_id: u32,
+    question: String,
+    ui_language: Option<String>,
+    provider: LlmProviderConfig,
+) -> Result<TutorExchange, CommandError> {
+    if question.trim().is_empty() {
+        return Err("Question is empty".into());
+    }
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let all = stories::load(&dir)?;
+    let context = tutor::block_context(&stories::find_doc(&all, &doc_id)?, block_id)?;
+    let history = tutor::history(&all, &doc_id, Some(block_id))?;
+    let settings = load_settings()?;
+    let policy = settings.child_safe.enabled.then(ContentPolicy::child_safe);
+    let ui_language = ui_language.filter(|l| !l.trim().is_empty()).or(settings.ui_language);
+    let exchange = tutor::ask(&context, &question, &history, ui_language.as_deref(), provider, policy.as_ref()).await?;
+    // Re-read: the library may have changed during the call.
+    let _stories = stories_lock.0.lock().await;
+    let mut all = stories::load(&dir)?;
+    tutor::record(&mut all, &doc_id, exchange.clone())?;
+    stories::save(&dir, &all)?;
+    Ok(exchange)
+}
+
+/// The tutor exchanges of a doc, oldest first.
+#[tauri::command]
+async fn boka_get_tutor_history(doc_id: String) -> Result<Vec<TutorExchange>, CommandError> {
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let all = stories::load(&dir)?;
+    tutor::history(&all, &doc_id, None).map_err(CommandError::from)
+}
+
+/// Move a story, or with `language` one of its translations, to the trash.
+/// It can be restored for `trash::RETENTION_DAYS`.
+#[tauri::command]
+async fn boka_trash_story(
+    stories_lock: tauri::State<'_, StoriesLock>,
+    story_id: String,
+    language: Option<String>,
+) -> Result<TrashItem, CommandError> {
+    let dir = shared_data_dir()?;
+    let _stories = stories_lock.0.lock().await;
+    let mut all = stories::load(&dir)?;
+    let mut trash = Trash::load(&dir).map_err(|e| e.to_string())?;
+    let item = trash.trash(&mut all, &story_id, language.as_deref()).map_err(|e| e.to_string())?;
+    trash.save(&dir).map_err(|e| e.to_string())?;
+    stories::save(&dir, &all)?;
+    Ok(item)
+}
+
+/// Put a trashed entry back. Returns all stories, so the frontend can replace
+/// its copy before its next write.
+#[tauri::command]
+async fn boka_restore_story(
+    stories_lock: tauri::State<'_, StoriesLock>,
+    trash_id: String,
+) -> Result<serde_json::Value, CommandError> {
+    let dir = shared_data_dir()?;
+    let _stories = stories_lock.0.lock().await;
+    let mut all = stories::load(&dir)?;
+    let mut trash = Trash::load(&dir).map_err(|e| e.to_string())?;
+    trash.restore(&mut all, &trash_id).map_err(|e| e.to_string())?;
+    stories::save(&dir, &all)?;
+    trash.save(&dir).map_err(|e| e.to_string())?;
+    Ok(all)
+}
+
+#[tauri::command]
+async fn boka_list_trash() -> Result<Vec<TrashItem>, CommandError> {
+    let dir = shared_data_dir()?;
+    Ok(Trash::load(&dir).map_err(|e| e.to_string())?.items())
+}
+
+/// Delete a trashed entry for good.
+#[tauri::command]
+async fn boka_purge_trash(trash_id: String) -> Result<(), CommandError> {
+    let dir = shared_data_dir()?;
+    let mut trash = Trash::load(&dir).map_err(|e| e.to_string())?;
+    trash.purge(&trash_id).map_err(|e| e.to_string())?;
+    trash.save(&dir).map_err(CommandError::other)
+}
+
+/// Empty transcripts, the translation cache or the audio cache of the
+/// active profile, or all of them.
+#[tauri::command]
+async fn boka_privacy_purge(app: tauri::AppHandle, scope: PurgeScope) -> Result<PurgeReport, CommandError> {
+    let paths = active_paths()?;
+    #[cfg_attr(not(feature = "tts"), allow(unused_mut))]
+    let mut report = privacy::purge(scope, &paths.data_dir, &paths.cache_dir).map_err(CommandError::other)?;
+    #[cfg(feature = "tts")]
+    {
+        if scope.includes(PurgeScope::AudioCache) {
+            let profile = active_profile()?;
+            let state = app.state::<AudioState>();
+            let guard = state.cache.lock().await;
+            let opened;
+            let cache = match guard.as_ref() {
+                Some(cache) => cache,
+                None => {
+                    opened = open_audio_cache()?;
+                    &opened
+                }
+            };
+            for namespace in cache.namespaces().into_iter().filter(|n| n.profile == profile) {
+                report += PurgeReport {
+                    files: namespace.entries,
+                    bytes: namespace.size_bytes,
+                };
+            }
+            cache.clear(&profile, None).map_err(|e| e.to_string())?;
+        }
+    }
+    #[cfg(not(feature = "tts"))]
+    let _ = app;
+    Ok(report)
+}
+
+/// Replace the tags of a story, or with `language` of one translation.
+/// Returns the tags as stored (trimmed, lowercased, deduplicated).
+#[tauri::command]
+async fn boka_tag_story(
+    stories_lock: tauri::State<'_, StoriesLock>,
+    story_id: String,
+    language: Option<String>,
+    tags: Vec<String>,
+) -> Result<Vec<String>, CommandError> {
+    let dir = shared_data_dir()?;
+    let _stories = stories_lock.0.lock().await;
+    let mut all = stories::load(&dir)?;
+    let tags = stories::set_tags(&mut all, &story_id, language.as_deref(), &tags).map_err(|e| e.to_string())?;
+    stories::save(&dir, &all)?;
+    Ok(tags)
+}
+
+#[tauri::command]
+async fn boka_list_collections() -> Result<Vec<Collection>, CommandError> {
+    let dir = shared_data_dir()?;
+    Ok(Collections::load(&dir).map_err(|e| e.to_string())?.0)
+}
+
+#[tauri::command]
+async fn boka_create_collection(name: String, items: Option<Vec<LibraryItem>>) -> Result<Collection, CommandError> {
+    let dir = shared_data_dir()?;
+    let mut collections = Collections::load(&dir).map_err(|e| e.to_string())?;
+    let collection = collections.create(&name, items.unwrap_or_default()).map_err(|e| e.to_string())?;
+    collections.save(&dir).map_err(|e| e.to_string())?;
+    Ok(collection)
+}
+
+/// Rename a collection and/or replace its items; `None` keeps the field.
+#[tauri::command]
+async fn boka_update_collection(
+    id: String,
+    name: Option<String>,
+    items: Option<Vec<LibraryItem>>,
+) -> Result<Collection, CommandError> {
+    let dir = shared_data_dir()?;
+    let mut collections = Collections::load(&dir).map_err(|e| e.to_string())?;
+    let collection = collections.update(&id, name.as_deref(), items).map_err(|e| e.to_string())?;
+    collections.save(&dir).map_err(|e| e.to_string())?;
+    Ok(collection)
+}
+
+#[tauri::command]
+async fn boka_delete_collection(id: String) -> Result<(), CommandError> {
+    let dir = shared_data_dir()?;
+    let mut collections = Collections::load(&dir).map_err(|e| e.to_string())?;
+    collections.delete(&id).map_err(|e| e.to_string())?;
+    collections.save(&dir).map_err(CommandError::other)
+}
+
+/// Search the library. Levels are estimated from the text for docs that
+/// were not graded, so large libraries take a moment.
+#[tauri::command]
+async fn boka_query_library(filter: LibraryFilter) -> Result<Vec<LibraryEntry>, CommandError> {
+    let dir = shared_data_dir()?;
+    let all = stories::load(&dir)?;
+    let collections = Collections::load(&dir).map_err(|e| e.to_string())?;
+    tauri::async_runtime::spawn_blocking(move || library::query(&all, &collections, &filter))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(CommandError::other)
+}
+
+/// Speak every block of a doc for the read-along export, reusing the audio
+/// cache. Blocks that fail to generate are exported as text only.
+#[cfg(feature = "tts")]
+async fn readalong_audio(
+    app: &tauri::AppHandle,
+    story: &StoryDoc,
+    voice_id: Option<String>,
+    speed: Option<f32>,
+    pauses: PauseOptions,
+) -> Result<Vec<Option<BlockAudio>>, String> {
+    let state = app.state::<AudioState>();
+    {
+        let mut cache_guard = state.cache.lock().await;
+        if cache_guard.is_none() {
+            *cache_guard = Some(open_audio_cache()?);
+        }
+    }
+
+    // Dialogue is read with the doc's speaker voices, when it has a map.
+    let doc_id = DocId {
+        story_id: story.story_id.clone(),
+        language: story.language.clone(),
+    };
+    let map = stories::load(&shared_data_dir()?)
+        .and_then(|all| speakers::speaker_map(&all, &doc_id))
+        .unwrap_or_default();
+
+    let (model, model_dir) = tts_model_for(Some(&story.language))?;
+    let mut engines = state.engines.lock().await;
+    let engine = &*engines.get(&model, &model_dir);
+    let voice = voice_id
+        .or_else(|| map.narrator_voice.clone())
+        .unwrap_or_else(|| engine.default_voice(&story.language));
+    let voices = map.voices(&voice, &engine.voices(), &story.language);
+    let speed = speed.unwrap_or(1.0);
+    let cancelled = Arc::new(AtomicBool::new(false));
+
+    let cache_guard = state.cache.lock().await;
+    let Some(cache) = cache_guard.as_ref() else {
+        return Ok(vec![]);
+    };
+    Ok(story
+        .doc
+        .block_texts()
+        .iter()
+        .enumerate()
+        .map(|(i, text)| {
+            let rendered = if map.lines.is_empty() {
+                let block = [text.clone()];
+                render_with_pauses(engine, cache, &block, &voice, speed, &story.language, &pauses, &cancelled, |_| {})
+            } else {
+                let parts = map.voice_parts(i as u32, text, &voice, &voices);
+                render_voices(engine, cache, &parts, speed, &story.language, &pauses, &cancelled, |_| {})
+            };
+            rendered
+                .map_err(|e| eprintln!("[EXPORT] Read-along audio skipped for a block: {e}"))
+                .ok()
+                .map(|cached| BlockAudio {
+                    wav_base64: cached.audio_base64,
+                    duration_ms: cached.duration_ms,
+                })
+        })
+        .collect())
+}
+
+#[cfg(not(feature = "tts"))]
+async fn readalong_audio(
+    _app: &tauri::AppHandle,
+    _story: &StoryDoc,
+    _voice_id: Option<String>,
+    _speed: Option<f32>,
+    _pauses: PauseOptions,
+) -> Result<Vec<Option<BlockAudio>>, String> {
+    Ok(vec![])
+}
+
+/// Export a doc as a single read-along HTML page with per-block audio and
+/// word highlighting. Written to `output_path`, or under the data dir's
+/// `exports/` folder; returns the path written.
+#[tauri::command]
+async fn boka_export_readalong(
+    app: tauri::AppHandle,
+    doc_id: String,
+    output_path: Option<String>,
+    voice_id: Option<String>,
+    speed: Option<f32>,
+    pauses: Option<PauseOptions>,
+    preset: Option<String>,
+) -> Result<String, CommandError> {
+    let (voice_id, speed, pauses) = apply_audio_preset(preset.as_deref(), voice_id, speed, pauses)?;
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let story = app.state::<DocCacheState>().get(&dir, &doc_id)?;
+
+    let audio = readalong_audio(&app, &story, voice_id, speed, pauses.unwrap_or_default()).await?;
+    let html = readalong_html(&story, &audio).map_err(|e| e.to_string())?;
+
+    let path = output_path
+        .map(PathBuf::from)
+        .unwrap_or_else(|| export::default_path(&dir, &story, "html"));
+    export::write_atomic(&path, html.as_bytes()).map_err(|e| e.to_string())?;
+    Ok(path.display().to_string())
+}
+
+#[tauri::command]
+async fn boka_get_vocab_ledger() -> Result<VocabLedger, CommandError> {
+    VocabLedger::load(&shared_data_dir()?).map_err(CommandError::other)
+}
+
+/// Mark every word of `text` as mastered (or not) in the vocabulary ledger.
+/// Returns how many lemmas changed.
+#[tauri::command]
+async fn boka_mark_words_known(language: String, text: String, known: bool) -> Result<u32, CommandError> {
+    let dir = shared_data_dir()?;
+    let mut ledger = VocabLedger::load(&dir).map_err(|e| e.to_string())?;
+    let changed = ledger.mark(&language, &text, known);
+    if changed > 0 {
+        ledger.save(&dir).map_err(|e| e.to_string())?;
+    }
+    Ok(changed as u32)
+}
+
+/// Speak `texts` for a word card, reusing the audio cache. Texts that fail to
+/// generate get no audio.
+#[cfg(feature = "tts")]
+async fn card_audio(
+    app: &tauri::AppHandle,
+    language: &str,
+    word: &str,
+    sentence: &str,
+) -> Result<(Option<CardAudio>, Option<CardAudio>), String> {
+    let state = app.state::<AudioState>();
+    {
+        let mut cache_guard = state.cache.lock().await;
+        if cache_guard.is_none() {
+            *cache_guard = Some(open_audio_cache()?);
+        }
+    }
+
+    let (model, model_dir) = tts_model_for(Some(language))?;
+    let mut engines = state.engines.lock().await;
+    let engine = &*engines.get(&model, &model_dir);
+    let voice = engine.default_voice(language);
+    let cancelled = Arc::new(AtomicBool::new(false));
+
+    let cache_guard = state.cache.lock().await;
+    let Some(cache) = cache_guard.as_ref() else {
+        return Ok((None, None));
+    };
+    let card_audio = |audio: Result<CachedAudio, AudioError>| {
+        audio
+            .map_err(|e| eprintln!("[AUDIO] Word card audio skipped: {e}"))
+            .ok()
+            .map(|cached| CardAudio {
+                audio_base64: cached.audio_base64,
+                duration_ms: cached.duration_ms,
+            })
+    };
+    Ok((
+        card_audio(generate_word_audio(engine, cache, word, &voice, language)),
+        card_audio(generate_speech(engine, cache, sentence, &voice, 1.0, language, &cancelled, |_| {})),
+    ))
+}
+
+#[cfg(not(feature = "tts"))]
+async fn card_audio(
+    _app: &tauri::AppHandle,
+    _language: &str,
+    _word: &str,
+    _sentence: &str,
+) -> Result<(Option<CardAudio>, Option<CardAudio>), String> {
+    Ok((None, None))
+}
+
+/// A flashcard for one word: gloss and example sentence mined from the
+/// library, with audio of the word and the sentence when TTS is available.
+#[tauri::command]
+async fn boka_make_word_card(app: tauri::AppHandle, lemma: String, language: String) -> Result<WordCard, CommandError> {
+    let all = stories::load(&shared_data_dir()?)?;
+    let mut card = make_word_card(&all, &lemma, &language).map_err(|e| e.to_string())?;
+    (card.word_audio, card.example_audio) = card_audio(&app, &language, &card.word, &card.example.sentence).await?;
+    Ok(card)
+}
+
+/// Distinct unmastered lemmas in each of `texts`, for ordering flashcards.
+#[tauri::command]
+async fn boka_count_unknown_words(language: String, texts: Vec<String>) -> Result<Vec<u32>, CommandError> {
+    let known = VocabLedger::load(&shared_data_dir()?)
+        .map_err(|e| e.to_string())?
+        .known_words(&language);
+    Ok(texts.iter().map(|t| known.unknown_count(t) as u32).collect())
+}
+
+/// Export a doc as a zip of lesson material (doc JSON, printable copy, cloze
+/// worksheet, vocab CSV, quiz), leaving out words the user has mastered
+/// unless `skipKnownWords` is off. Returns the path written.
+#[tauri::command]
+async fn boka_export_classroom_pack(
+    doc_cache: tauri::State<'_, DocCacheState>,
+    doc_id: String,
+    options: Option<ClassroomPackOptions>,
+) -> Result<String, CommandError> {
+    let dir = shared_data_dir()?;
+    let options = options.unwrap_or_default();
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let story = doc_cache.get(&dir, &doc_id)?;
+
+    let ledger = VocabLedger::load(&dir).map_err(|e| e.to_string())?;
+    let pack = classroom_pack(&story, &options, &ledger.known_words(&story.language)).map_err(|e| e.to_string())?;
+    let path = options
+        .output_path
+        .map(PathBuf::from)
+        .unwrap_or_else(|| export::default_path(&dir, &story, "zip"));
+    export::write_atomic(&path, &pack.bytes).map_err(|e| e.to_string())?;
+    Ok(path.display().to_string())
+}
+
+/// Put a doc's text on the system clipboard, as plain text or Markdown, in
+/// a chosen register and with notes or source if asked. Copying from Rust
+/// behaves the same on every platform and from any window, which the
+/// webview clipboard API does not. Returns the text copied.
+#[tauri::command]
+async fn boka_copy_doc_text(
+    app: tauri::AppHandle,
+    doc_cache: tauri::State<'_, DocCacheState>,
+    doc_id: String,
+    options: Option<DocTextOptions>,
+) -> Result<String, CommandError> {
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let story = doc_cache.get(&dir, &doc_id)?;
+
+    let text = doc_text(&story, &options.unwrap_or_default());
+    app.clipboard().write_text(text.clone()).map_err(|e| e.to_string())?;
+    Ok(text)
+}
+
+/// Export a doc's spans or vocabulary as a table. A `.tsv` path gives
+/// tab-separated output, anything else CSV.
+#[tauri::command]
+async fn boka_export_csv(
+    doc_cache: tauri::State<'_, DocCacheState>,
+    doc_id: String,
+    path: String,
+    kind: TableKind,
+) -> Result<(), CommandError> {
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let story = doc_cache.get(&dir, &doc_id)?;
+
+    let path = PathBuf::from(path);
+    let text = table(&story, kind, Delimiter::for_path(&path));
+    export::write_atomic(&path, text.as_bytes()).map_err(CommandError::other)
+}
+
+/// Render a doc with a built-in or user template (see
+/// `boka_core::export::template`). Without `path` the file goes to the
+/// exports folder. Returns the path written.
+#[tauri::command]
+async fn boka_export_with_template(
+    doc_cache: tauri::State<'_, DocCacheState>,
+    doc_id: String,
+    template_name: String,
+    path: Option<String>,
+) -> Result<String, CommandError> {
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let story = doc_cache.get(&dir, &doc_id)?;
+
+    let (text, extension) = render_template(&dir, &template_name, &story).map_err(|e| e.to_string())?;
+    let path = path
+        .map(PathBuf::from)
+        .unwrap_or_else(|| export::default_path(&dir, &story, &extension));
+    export::write_atomic(&path, text.as_bytes()).map_err(|e| e.to_string())?;
+    Ok(path.display().to_string())
+}
+
+#[tauri::command]
+async fn boka_list_export_templates() -> Result<Vec<TemplateInfo>, CommandError> {
+    Ok(list_templates(&shared_data_dir()?))
+}
+
+/// Write a doc in the JSON Lines interchange format (see `boka_core::jsonl`).
+#[tauri::command]
+async fn boka_export_jsonl(
+    doc_cache: tauri::State<'_, DocCacheState>,
+    doc_id: String,
+    path: String,
+) -> Result<(), CommandError> {
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let story = doc_cache.get(&dir, &doc_id)?;
+
+    let text = to_jsonl(&story).map_err(|e| e.to_string())?;
+    export::write_atomic(&PathBuf::from(path), text.as_bytes()).map_err(CommandError::other)
+}
+
+/// Validate a JSON Lines file and store its doc, replacing any existing
+/// translation of that story into the same language. Returns the doc id.
+#[tauri::command]
+async fn boka_import_jsonl(stories_lock: tauri::State<'_, StoriesLock>, path: String) -> Result<String, CommandError> {
+    let text = std::fs::read_to_string(&path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+    let story = from_jsonl(&text).map_err(|e| e.to_string())?;
+
+    let dir = shared_data_dir()?;
+    let _stories = stories_lock.0.lock().await;
+    let mut all = stories::load(&dir)?;
+    let doc_id = stories::put_doc(&mut all, &story).map_err(|e| e.to_string())?;
+    naming::name_stories(&mut all);
+    stories::save(&dir, &all)?;
+    Ok(doc_id.to_string())
+}
+
+/// Pack a doc into a `.boka` bundle for other users, with its audio when
+/// `options.include_audio`. Written to `path`, or under the data dir's
+/// `exports/` folder; returns the path written.
+#[tauri::command]
+async fn boka_export_bundle(
+    app: tauri::AppHandle,
+    doc_id: String,
+    path: Option<String>,
+    options: Option<BundleOptions>,
+) -> Result<String, CommandError> {
+    let options = options.unwrap_or_default();
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let all = stories::load(&dir)?;
+    let story = stories::find_doc(&all, &doc_id).map_err(|e| e.to_string())?;
+
+    let audio = if options.include_audio {
+        readalong_audio(&app, &story, None, None, PauseOptions::default()).await?
+    } else {
+        Vec::new()
+    };
+    let bytes = bundle::write_bundle(&all, &doc_id, &audio, &options).map_err(|e| e.to_string())?;
+    let path = path
+        .map(PathBuf::from)
+        .unwrap_or_else(|| export::default_path(&dir, &story, bundle::EXTENSION));
+    export::write_atomic(&path, &bytes).map_err(|e| e.to_string())?;
+    Ok(path.display().to_string())
+}
+
+/// Import a `.boka` bundle as a read-only shared doc and return its
+/// `storyId:language` id. Importing the same bundle again replaces it.
+#[tauri::command]
+async fn boka_import_bundle(stories_lock: tauri::State<'_, StoriesLock>, path: String) -> Result<String, CommandError> {
+    let bytes = std::fs::read(&path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+    let shared = bundle::read_bundle(&bytes).map_err(|e| e.to_string())?;
+
+    let dir = shared_data_dir()?;
+    let _stories = stories_lock.0.lock().await;
+    let mut all = stories::load(&dir)?;
+    let doc_id = bundle::import_bundle(&mut all, &dir, shared).map_err(|e| e.to_string())?;
+    naming::name_stories(&mut all);
+    stories::save(&dir, &all)?;
+    Ok(doc_id.to_string())
+}
+
+/// The audio a shared doc came with, per block.
+#[tauri::command]
+async fn boka_shared_audio(doc_id: String) -> Result<Vec<Option<SharedAudio>>, CommandError> {
+    let dir = shared_data_dir()?;
+    let doc_id = DocId::parse(&doc_id).map_err(|e| e.to_string())?;
+    let all = stories::load(&dir)?;
+    bundle::shared_audio(&all, &dir, &doc_id).map_err(CommandError::other)
+}
+
+/// Write all user data to one archive at `path`. `frontend` is the
+/// frontend's local storage snapshot. API keys are never written.
+#[tauri::command]
+async fn boka_export_profile(
+    path: String,
+    frontend: Option<serde_json::Value>,
+) -> Result<ProfileManifest, CommandError> {
+    let dir = shared_data_dir()?;
+    export_profile(&dir, frontend.as_ref(), &PathBuf::from(path)).map_err(CommandError::other)
+}
+
+/// Replace the profile with the archive at `path` after checking it. The
+/// current profile is backed up first; the frontend should restore the
+/// returned snapshot and reload. A locked child-safe mode is kept unless
+/// `pin` is the teacher PIN.
+#[tauri::command]
+async fn boka_import_profile(
+    stories_lock: tauri::State<'_, StoriesLock>,
+    path: String,
+    pin: Option<String>,
+) -> Result<ProfileImport, CommandError> {
+    let dir = shared_data_dir()?;
+    let _stories = stories_lock.0.lock().await;
+    import_profile(&dir, &PathBuf::from(path), &profile::backup_path(&dir), pin.as_deref())
+        .map_err(CommandError::other)
+}
+
+#[tauri::command]
+async fn boka_get_settings() -> Result<SettingsView, CommandError> {
+    Ok(load_settings()?.view())
+}
+
+#[tauri::command]
+async fn boka_set_child_safe(enabled: bool, pin: Option<String>) -> Result<SettingsView, CommandError> {
+    let dir = shared_data_dir()?;
+    let mut settings = Settings::load(&dir).map_err(|e| e.to_string())?;
+    settings
+        .set_child_safe(enabled, pin.as_deref())
+        .map_err(|e| e.to_string())?;
+    settings.save(&dir).map_err(|e| e.to_string())?;
+    Ok(settings.view())
+}
+
+/// Fill speech arguments the caller left unset from the named audio preset.
+fn apply_audio_preset(
+    preset: Option<&str>,
+    voice_id: Option<String>,
+    speed: Option<f32>,
+    pauses: Option<PauseOptions>,
+) -> Result<(Option<String>, Option<f32>, Option<PauseOptions>), String> {
+    let Some(name) = preset else {
+        return Ok((voice_id, speed, pauses));
+    };
+    let settings = load_settings()?;
+    let preset = settings.audio_preset(name).map_err(|e| e.to_string())?;
+    Ok((
+        voice_id.or_else(|| preset.voice_id.clone()),
+        speed.or(Some(preset.speed)),
+        pauses.or(Some(preset.pauses)),
+    ))
+}
+
+/// Add or replace an audio preset; `None` removes it.
+#[tauri::command]
+async fn boka_set_audio_preset(name: String, preset: Option<AudioPreset>) -> Result<SettingsView, CommandError> {
+    let dir = shared_data_dir()?;
+    let mut settings = Settings::load(&dir).map_err(|e| e.to_string())?;
+    settings.set_audio_preset(&name, preset).map_err(|e| e.to_string())?;
+    settings.save(&dir).map_err(|e| e.to_string())?;
+    Ok(settings.view())
+}
+
+#[tauri::command]
+async fn boka_set_tts_warmup(policy: WarmupPolicy) -> Result<SettingsView, CommandError> {
+    let dir = shared_data_dir()?;
+    let mut settings = Settings::load(&dir).map_err(|e| e.to_string())?;
+    settings.tts_warmup = policy;
+    settings.save(&dir).map_err(|e| e.to_string())?;
+    Ok(settings.view())
+}
+
+/// How far past the reading position speech is synthesized ahead; applies
+/// from the next position report.
+#[tauri::command]
+async fn boka_set_tts_prefetch(policy: PrefetchPolicy) -> Result<SettingsView, CommandError> {
+    let dir = shared_data_dir()?;
+    let mut settings = Settings::load(&dir).map_err(|e| e.to_string())?;
+    settings.tts_prefetch = policy;
+    settings.save(&dir).map_err(|e| e.to_string())?;
+    Ok(settings.view())
+}
+
+/// How many parsed docs the session keeps; 0 turns the cache off.
+#[tauri::command]
+async fn boka_set_doc_cache_size(
+    doc_cache: tauri::State<'_, DocCacheState>,
+    size: u32,
+) -> Result<SettingsView, CommandError> {
+    let dir = shared_data_dir()?;
+    let mut settings = Settings::load(&dir).map_err(|e| e.to_string())?;
+    settings.set_doc_cache_size(size).map_err(|e| e.to_string())?;
+    settings.save(&dir).map_err(|e| e.to_string())?;
+    doc_cache.set_capacity(size);
+    Ok(settings.view())
+}
+
+/// The preamble sent ahead of every provider call; `None` or blank text
+/// removes it.
+#[tauri::command]
+async fn boka_set_system_preamble(text: Option<String>) -> Result<SettingsView, CommandError> {
+    let dir = shared_data_dir()?;
+    let mut settings = Settings::load(&dir).map_err(|e| e.to_string())?;
+    settings.set_system_preamble(text.as_deref()).map_err(|e| e.to_string())?;
+    settings.save(&dir).map_err(|e| e.to_string())?;
+    Ok(settings.view())
+}
+
+/// The language learner notes are written in for new jobs; `None` or a
+/// blank code goes back to English.
+#[tauri::command]
+async fn boka_set_ui_language(ui_language: Option<String>) -> Result<SettingsView, CommandError> {
+    let dir = shared_data_dir()?;
+    let mut settings = Settings::load(&dir).map_err(|e| e.to_string())?;
+    settings.set_ui_language(ui_language.as_deref()).map_err(|e| e.to_string())?;
+    settings.save(&dir).map_err(|e| e.to_string())?;
+    Ok(settings.view())
+}
+
+/// How long job transcripts are kept. Applied at the next startup.
+#[tauri::command]
+async fn boka_set_retention(policy: RetentionPolicy) -> Result<SettingsView, CommandError> {
+    let dir = shared_data_dir()?;
+    let mut settings = Settings::load(&dir).map_err(|e| e.to_string())?;
+    settings.set_retention(policy).map_err(|e| e.to_string())?;
+    settings.save(&dir).map_err(|e| e.to_string())?;
+    Ok(settings.view())
+}
+
+#[tauri::command]
+async fn boka_set_variant_bounds(min: u32, max: u32) -> Result<SettingsView, CommandError> {
+    let dir = shared_data_dir()?;
+    let mut settings = Settings::load(&dir).map_err(|e| e.to_string())?;
+    settings.variant_bounds = VariantBounds::new(min, max).map_err(|e| e.to_string())?;
+    settings.save(&dir).map_err(|e| e.to_string())?;
+    Ok(settings.view())
+}
+
+/// Turn the base-translation length guard on or off and set its ratio.
+#[tauri::command]
+async fn boka_set_length_guard(enabled: bool, max_ratio: f64) -> Result<SettingsView, CommandError> {
+    let dir = shared_data_dir()?;
+    let mut settings = Settings::load(&dir).map_err(|e| e.to_string())?;
+    settings.length_guard = LengthGuard::new(enabled, max_ratio).map_err(|e| e.to_string())?;
+    settings.save(&dir).map_err(|e| e.to_string())?;
+    Ok(settings.view())
+}
+
+/// Optional override for the bundled model registry, in the shared data dir.
+fn model_registry_path() -> Result<PathBuf, String> {
+    Ok(root_data_dir()?.join("models.json"))
+}
+
+/// Install `models.json` from the data dir (or the bundled copy) with the
+/// user's prices laid over it, and return its version.
+fn install_model_registry() -> Result<u32, String> {
+    let registry = ModelRegistry::load_or_bundled(&model_registry_path()?).map_err(|e| e.to_string())?;
+    let version = registry.version;
+    ModelRegistry::install(load_settings()?.pricing.apply(registry));
+    Ok(version)
+}
+
+/// Price a model for cost estimates and budgets, or go back to the
+/// registry's price with no `pricing`.
+#[tauri::command]
+async fn boka_set_model_price(
+    preset: LlmProviderPreset,
+    model: String,
+    pricing: Option<ModelPricing>,
+) -> Result<SettingsView, CommandError> {
+    let dir = shared_data_dir()?;
+    let mut settings = Settings::load(&dir).map_err(|e| e.to_string())?;
+    settings.pricing.set(preset, &model, pricing).map_err(|e| e.to_string())?;
+    settings.save(&dir).map_err(|e| e.to_string())?;
+    install_model_registry()?;
+    Ok(settings.view())
+}
+
+/// Show costs in `code` at `perUsd` units per US dollar.
+#[tauri::command]
+async fn boka_set_display_currency(code: String, per_usd: f64) -> Result<SettingsView, CommandError> {
+    let dir = shared_data_dir()?;
+    let mut settings = Settings::load(&dir).map_err(|e| e.to_string())?;
+    settings.pricing.currency = DisplayCurrency::new(&code, per_usd).map_err(|e| e.to_string())?;
+    settings.save(&dir).map_err(|e| e.to_string())?;
+    Ok(settings.view())
+}
+
+/// What `usage` of `model` costs in the display currency, or its token
+/// count when the model has no price.
+#[tauri::command]
+async fn boka_describe_cost(
+    preset: LlmProviderPreset,
+    model: String,
+    usage: boka_core::types::Usage,
+) -> Result<CostView, CommandError> {
+    let currency = load_settings()?.pricing.currency;
+    let pricing = ModelRegistry::current().pricing(preset, &model);
+    Ok(describe_cost(&usage, pricing.as_ref(), &currency))
+}
+
+/// Every resolved on-disk location, with whether it exists yet.
+#[tauri::command]
+async fn boka_path_diagnostics() -> Result<Vec<PathStatus>, CommandError> {
+    Ok(active_paths()?.diagnostics())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfileSwitch {
+    profile: Profile,
+    /// The local storage the profile had when it was last left (the one
+    /// sent, when it was already active); `None` for one never used, which
+    /// starts from defaults.
+    frontend: Option<serde_json::Value>,
+}
+
+/// Every profile and the active one.
+#[tauri::command]
+async fn boka_list_profiles() -> Result<Profiles, CommandError> {
+    Profiles::load(&root_data_dir()?).map_err(CommandError::other)
+}
+
+/// While child-safe mode has a PIN, creating a profile needs it: the new
+/// profile would start without the mode.
+#[tauri::command]
+async fn boka_create_profile(name: String, pin: Option<String>) -> Result<Profile, CommandError> {
+    load_settings()?.check_pin(pin.as_deref()).map_err(|e| e.to_string())?;
+    let root = root_data_dir()?;
+    let mut all = Profiles::load(&root).map_err(|e| e.to_string())?;
+    let profile = all.create(&name).map_err(|e| e.to_string())?;
+    all.save(&root).map_err(|e| e.to_string())?;
+    Ok(profile)
+}
+
+/// Make `id` the active profile. `frontend` is the local storage of the
+/// profile being left, kept in its data dir until it is switched back to.
+/// Refused while translations run, since they save into the active profile,
+/// and without the PIN while child-safe mode has one.
+#[tauri::command]
+async fn boka_switch_profile(
+    app: tauri::AppHandle,
+    id: String,
+    frontend: Option<serde_json::Value>,
+    pin: Option<String>,
+) -> Result<ProfileSwitch, CommandError> {
+    if !app.state::<TranslationState>().cancelled_by_job.lock().await.is_empty() {
+        return Err("Finish or cancel running translations before switching profiles".into());
+    }
+    let paths = BokaPaths::current().map_err(|e| e.to_string())?;
+    let mut all = Profiles::load(&paths.data_dir).map_err(|e| e.to_string())?;
+    if all.active == id {
+        let profile = all.switch(&id).map_err(|e| e.to_string())?;
+        return Ok(ProfileSwitch { profile, frontend });
+    }
+    load_settings()?.check_pin(pin.as_deref()).map_err(|e| e.to_string())?;
+
+    if let Some(frontend) = &frontend {
+        let left = paths.for_profile(&all.active).data_dir;
+        profiles::save_frontend(&left, frontend).map_err(|e| e.to_string())?;
+    }
+    let profile = all.switch(&id).map_err(|e| e.to_string())?;
+    all.save(&paths.data_dir).map_err(|e| e.to_string())?;
+    let frontend = profiles::load_frontend(&paths.for_profile(&id).data_dir).map_err(|e| e.to_string())?;
+
+    // Per-profile state read once: the audio cache, the config watcher and
+    // what settings configure. The doc cache notices the new data dir.
+    #[cfg(feature = "tts")]
+    app.state::<AudioState>().cache.lock().await.take();
+    let watcher = spawn_config_watcher(&app)
+        .map_err(|e| eprintln!("[CONFIG] Config changes need a restart: {e}"))
+        .ok();
+    if let Ok(mut current) = app.state::<ConfigWatchState>().watcher.lock() {
+        *current = watcher;
+    }
+    let settings = load_settings().unwrap_or_default();
+    app.state::<BackgroundState>().lock().set_policy(settings.background);
+    app.state::<DocCacheState>().set_capacity(settings.doc_cache_size.0);
+
+    Ok(ProfileSwitch { profile, frontend })
+}
+
+/// Outside requests (CLI, HTTP, deep links) waiting for the user's yes.
+#[derive(Default)]
+struct ExternalRequestState(Mutex<Confirmations>);
+
+/// Interfaces other than the window that have access, without their tokens.
+#[tauri::command]
+async fn boka_list_interface_grants() -> Result<Vec<GrantView>, CommandError> {
+    Ok(Authz::load(&root_data_dir()?)?.views())
+}
+
+/// Give `interface` a new token, replacing its old one. Read-only unless
+/// `allow` says more. The token is returned once and only its hash kept.
+#[tauri::command]
+async fn boka_issue_interface_token(
+    interface: Interface,
+    allow: Option<Vec<Action>>,
+) -> Result<String, CommandError> {
+    let root = root_data_dir()?;
+    let mut authz = Authz::load(&root)?;
+    let token = authz.issue(interface, allow.as_deref().unwrap_or(&Action::READ_ONLY))?;
+    authz.save(&root)?;
+    Ok(token)
+}
+
+/// Whether `interface` had access to take away.
+#[tauri::command]
+async fn boka_revoke_interface(interface: Interface) -> Result<bool, CommandError> {
+    let root = root_data_dir()?;
+    let mut authz = Authz::load(&root)?;
+    let revoked = authz.revoke(interface);
+    authz.save(&root)?;
+    Ok(revoked)
+}
+
+#[tauri::command]
+async fn boka_list_external_requests(
+    state: tauri::State<'_, ExternalRequestState>,
+) -> Result<Vec<ExternalRequest>, CommandError> {
+    Ok(state.0.lock().await.pending())
+}
+
+/// Let an outside request go ahead or turn it down; false when it was
+/// already answered or its caller gave up.
+#[tauri::command]
+async fn boka_resolve_external_request(
+    state: tauri::State<'_, ExternalRequestState>,
+    id: String,
+    approved: bool,
+) -> Result<bool, CommandError> {
+    Ok(state.0.lock().await.resolve(&id, approved))
+}
+
+#[tauri::command]
+async fn boka_list_models(preset: LlmProviderPreset) -> Result<Vec<ModelEntry>, CommandError> {
+    Ok(ModelRegistry::current().models_for(preset).cloned().collect())
+}
+
+/// Re-read `models.json` from the data dir (or the bundled copy) and return its version.
+#[tauri::command]
+async fn boka_reload_model_registry() -> Result<u32, CommandError> {
+    Ok(install_model_registry()?)
+}
+
+/// Keeps the config watcher alive for the app's lifetime; replaced when the
+/// profile changes.
+struct ConfigWatchState {
+    watcher: std::sync::Mutex<Option<ConfigWatcher>>,
+}
+
+/// Watch the active profile's config files. Settings, prompts and templates
+/// are read fresh per command; the model registry is cached and reinstalled
+/// here. Emits `boka:config:reloaded` after each burst of edits.
+fn spawn_config_watcher(handle: &tauri::AppHandle) -> Result<ConfigWatcher, String> {
+    let dir = shared_data_dir()?;
+    let handle = handle.clone();
+    let watch_dir = dir.clone();
+    ConfigWatcher::spawn(&dir, move |files| {
+        let mut errors = Vec::new();
+        for file in &files {
+            let result = match file {
+                ConfigFile::Models => install_model_registry().map(|_| ()),
+                ConfigFile::Settings => load_settings().and_then(|settings| {
+                    handle.state::<BackgroundState>().lock().set_policy(settings.background);
+                    handle.state::<DocCacheState>().set_capacity(settings.doc_cache_size.0);
+                    install_model_registry().map(|_| ())
+                }),
+                other => other.check(&watch_dir),
+            };
+            if let Err(e) = result {
+                errors.push(e);
+            }
+        }
+        let _ = handle.emit(
+            "boka:config:reloaded",
+            ConfigReloadedEvent {
+                files: files.into_iter().collect(),
+                errors,
+            },
+        );
+    })
+    .map_err(|e| e.to_string())
+}
+
+fn watch_config(app: &tauri::App) {
+    let watcher = spawn_config_watcher(app.handle())
+        .map_err(|e| eprintln!("[CONFIG] Config changes need a restart: {e}"))
+        .ok();
+    app.manage(ConfigWatchState {
+        watcher: std::sync::Mutex::new(watcher),
+    });
+}
+
+pub fn run() {
+    if let Err(e) = install_model_registry() {
+        eprintln!("[MODELS] Using bundled model registry: {e}");
+    }
+
+    let builder = tauri::Builder::default()
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .manage(TranslationState::default())
+        .manage(ProviderProbeState::default())
+        .manage(ExternalRequestState::default())
+        .manage(BackgroundState::new(load_settings().map(|s| s.background).unwrap_or_default()))
+        .manage(DocCacheState::default())
+        .manage(UsageIndexState::default())
+        .manage(StoriesLock::default());
+
+    #[cfg(feature = "tts")]
+    let builder = builder.manage(AudioState::default()).manage(PrefetchState::default());
+
+    let builder = builder.setup(|app| {
+        watch_config(app);
+        #[cfg(feature = "tts")]
+        {
+            let policy = load_settings().map(|s| s.tts_warmup).unwrap_or_default();
+            if policy == WarmupPolicy::OnStartup {
+                app.state::<BackgroundState>().lock().queue(BackgroundTask::TtsWarmup {
+                    language: None,
+                    trigger: WarmupTrigger::Startup,
+                });
+            }
+        }
+        spawn_background_loop(app.handle().clone(), app.state::<BackgroundState>().inner().clone());
+        spawn_trash_purge();
+        spawn_privacy_maintenance(app.state::<StoriesLock>().inner().clone());
+        Ok(())
+    });
+
+    let builder = builder.invoke_handler(tauri::generate_handler![
+        boka_prepare_translation,
+        boka_start_translation,
+        boka_cancel_translation,
+        boka_approve_segments,
+        boka_confirm_budget,
+        boka_retry_failed_segments,
+        boka_continue_story,
+        boka_describe_doc,
+        boka_translate_notes,
+        boka_get_job_report,
+        boka_export_job_transcript,
+        boka_get_model_stats,
+        boka_test_provider,
+        boka_import_image,
+        boka_demo_story,
+        boka_analyze_text,
+        boka_list_models,
+        boka_path_diagnostics,
+        boka_list_profiles,
+        boka_create_profile,
+        boka_switch_profile,
+        boka_list_interface_grants,
+        boka_issue_interface_token,
+        boka_revoke_interface,
+        boka_list_external_requests,
+        boka_resolve_external_request,
+        boka_run_prompt_experiment,
+        boka_reload_model_registry,
+        boka_set_model_price,
+        boka_set_display_currency,
+        boka_describe_cost,
+        boka_list_prompt_addenda,
+        boka_get_prompt_addendum,
+        boka_set_prompt_addendum,
+        boka_preview_prompts,
+        boka_get_doc,
+        boka_get_doc_cache_stats,
+        boka_get_runtime_info,
+        boka_get_doc_frequency,
+        boka_list_annotation_passes,
+        boka_lint_doc,
+        boka_render_block_text,
+        boka_find_usages,
+        boka_get_language_support,
+        boka_read_stories,
+        boka_write_stories,
+        boka_rename_doc,
+        boka_get_speakers,
+        boka_attribute_speakers,
+        boka_edit_speakers,
+        boka_set_doc_register,
+        boka_save_listening_position,
+        boka_get_listening_position,
+        boka_generate_practice_plan,
+        boka_get_practice_plan,
+        boka_star_span,
+        boka_star_sentence,
+        boka_list_starred,
+        boka_ask_about,
+        boka_get_tutor_history,
+        boka_trash_story,
+        boka_restore_story,
+        boka_list_trash,
+        boka_purge_trash,
+        boka_privacy_purge,
+        boka_tag_story,
+        boka_list_collections,
+        boka_create_collection,
+        boka_update_collection,
+        boka_delete_collection,
+        boka_query_library,
+        boka_export_readalong,
+        boka_export_classroom_pack,
+        boka_copy_doc_text,
+        boka_get_vocab_ledger,
+        boka_mark_words_known,
+        boka_count_unknown_words,
+        boka_make_word_card,
+        boka_export_csv,
+        boka_export_with_template,
+        boka_list_export_templates,
+        boka_export_jsonl,
+        boka_import_jsonl,
+        boka_export_bundle,
+        boka_import_bundle,
+        boka_shared_audio,
+        boka_export_profile,
+        boka_import_profile,
+        boka_get_settings,
+        boka_set_child_safe,
+        boka_set_variant_bounds,
+        boka_set_length_guard,
+        boka_set_doc_cache_size,
+        boka_set_system_preamble,
+        boka_set_ui_language,
+        boka_set_retention,
+        boka_set_tts_warmup,
+        boka_set_tts_prefetch,
+        boka_set_background_policy,
+        boka_get_background_status,
+        boka_set_audio_preset,
+        boka_set_tts_model,
+        boka_list_tts_models,
+        #[cfg(feature = "tts")]
+        boka_generate_speech,
+        #[cfg(feature = "tts")]
+        boka_cancel_audio,
+        #[cfg(feature = "tts")]
+        boka_verify_audio_cache,
+        #[cfg(feature = "tts")]
+        boka_list_audio_cache,
+        #[cfg(feature = "tts")]
+        boka_clear_audio_cache,
+        #[cfg(feature = "tts")]
+        boka_get_audio_status,
+        #[cfg(feature = "tts")]
+        boka_list_voices,
+        #[cfg(feature = "tts")]
+        boka_generate_word_audio,
+        #[cfg(feature = "tts")]
+        boka_get_doc_audio_status,
+        #[cfg(feature = "tts")]
+        boka_preload_model,
+        #[cfg(feature = "tts")]
+        boka_audio_doc_opened,
+        #[cfg(feature = "tts")]
+        boka_report_reading_position,
     ]);
 
     builder
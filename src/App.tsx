@@ -17,9 +17,10 @@ import type {
   StoryTranslation,
   TranslationJob,
 } from './bokaTypes';
+import { errorMessage } from './commandError';
 import { start_mock_translation } from './mockTranslation';
-import { start_tauri_translation } from './tauriTranslation';
-import { readStoriesFromFile, writeStoriesToFile } from './tauriStorage';
+import { get_tauri_demo_story, start_tauri_translation } from './tauriTranslation';
+import { readStoriesFromFile, starSpan, writeStoriesToFile } from './tauriStorage';
 import { ensureAudioContext, playBase64Wav, stop as stopAudio } from './audioPlayer';
 import { generate_speech, get_audio_status, preload_model } from './tauriAudio';
 import { generate_mock_speech, get_mock_audio_status } from './mockAudio';
@@ -234,6 +235,7 @@ export default function App() {
           apiKey: typeof parsed.providerApiKey === 'string' ? parsed.providerApiKey : undefined,
           baseUrl: typeof parsed.providerBaseUrl === 'string' ? parsed.providerBaseUrl : undefined,
           model: typeof parsed.providerModel === 'string' ? parsed.providerModel : undefined,
+          reasoningModel: parsed.providerReasoningModel === true,
         });
       } else if (typeof parsed.anthropicKey === 'string') {
         setProvider({ preset: 'anthropic', apiKey: parsed.anthropicKey });
@@ -257,6 +259,7 @@ export default function App() {
           providerApiKey: provider.apiKey ?? '',
           providerBaseUrl: provider.baseUrl ?? '',
           providerModel: provider.model ?? '',
+          providerReasoningModel: provider.reasoningModel ?? false,
         }),
       );
     } catch {}
@@ -448,7 +451,7 @@ export default function App() {
         });
         setCancelTranslation(() => handle.cancel);
       } catch (e) {
-        const msg = e instanceof Error ? e.message : String(e);
+        const msg = errorMessage(e);
         console.warn('[boka] tauri translation failed:', e);
         cbs.onError(`TAURI FAILED: ${msg}`);
 
@@ -559,6 +562,21 @@ export default function App() {
           category={category}
           setCategory={setCategory}
           allCategories={allCategories}
+          onLoadDemo={async () => {
+            try {
+              const demo = await get_tauri_demo_story();
+              setStoryTitle(demo.title);
+              setStoryText(demo.text);
+              setSourceLanguage(demo.sourceLanguage);
+              if (!demo.languages.includes(targetLanguage)) setTargetLanguage(demo.languages[0]);
+              // Without a key the demo provider stands in; a configured provider translates it for real.
+              if (!provider.apiKey?.trim() && ['anthropic', 'openai', 'openrouter'].includes(provider.preset)) {
+                setProvider({ preset: 'mock', baseUrl: 'demo' });
+              }
+            } catch (e) {
+              setTranslationError(errorMessage(e));
+            }
+          }}
           onTranslate={() => {
             cancelTranslation?.();
             setCancelTranslation(null);
@@ -665,6 +683,23 @@ export default function App() {
               return nextDoc;
             });
           }}
+          onStarSpan={(spanId, starred) => {
+            const storyId = activeStoryId;
+            const language = activeStoryLanguage;
+            if (!storyId || !language) return;
+            void starSpan(storyId, language, spanId, starred)
+              .then((stars) => {
+                // Stars are not an edit of the story, so updatedAt stays.
+                setStories((prev) =>
+                  prev.map((st) => {
+                    const prevT = st.id === storyId ? st.translations[language] : undefined;
+                    if (!prevT) return st;
+                    return { ...st, translations: { ...st.translations, [language]: { ...prevT, starred: stars } } };
+                  }),
+                );
+              })
+              .catch((e) => console.warn('[boka] Failed to star span:', e));
+          }}
           storyTranslations={activeStoryTranslations}
           onSwitchLanguage={(language) => {
             if (activeStoryId) handleOpenInLanguage(activeStoryId, language);
@@ -729,7 +764,7 @@ export default function App() {
             .then(() => get_audio_status())
             .then((status) => setAudioStatus(status))
             .catch((e) => {
-              const msg = e instanceof Error ? e.message : String(e);
+              const msg = errorMessage(e);
               setAudioStatus((prev) => ({ ...prev, loading: false, error: msg }));
             });
         }}
@@ -6,6 +6,13 @@ export type Variant = {
   text: string;
   note?: string;
   difficulty?: number;
+  flagged?: string;
+};
+
+export type ContentPolicy = {
+  allowedRegisters: RegisterId[];
+  blockedThemes: string[];
+  profanityLevel: 0 | 1 | 2 | 3;
 };
 
 export type StoryTranslation = {
@@ -14,6 +21,58 @@ export type StoryTranslation = {
   job: TranslationJob | null;
   doc: InteractiveDoc | null;
   errorMessage?: string | null;
+  // Written by the backend; see saveListeningPosition.
+  listeningPosition?: ListeningPosition | null;
+  // Written by the backend; see tagStory.
+  tags?: string[];
+  // Written by the backend when a translation finishes; see describeDoc.
+  meta?: StoryMeta;
+  // Set on docs imported from a .boka bundle, which are read-only.
+  shared?: SharedFrom;
+  // Written by the backend; see generatePracticePlan.
+  practicePlan?: PracticePlan;
+  // Written by the backend; see starSpan and starSentence.
+  starred?: Star[];
+  // Written by the backend; see askAbout.
+  tutor?: TutorExchange[];
+  speakers?: SpeakerMap;
+};
+
+// Where a shared doc came from. `audio` holds each block's duration in ms
+// when the bundle had audio; see sharedAudio.
+export type SharedFrom = {
+  bundleId: string;
+  author?: string;
+  note?: string;
+  bundledAt: number;
+  importedAt: number;
+  audio: (number | null)[];
+};
+
+export type BundleOptions = {
+  author?: string;
+  note?: string;
+  // Render audio for every block into the bundle; needs the TTS engine.
+  includeAudio?: boolean;
+};
+
+// Library shelf metadata for a doc. `title` and `synopsis` come from the
+// model and are missing when it could not be asked.
+export type StoryMeta = {
+  title?: string;
+  synopsis?: { source: string; target: string };
+  level?: CefrLevel;
+  wordCount: number;
+  cover: { emoji: string; colorSeed: number };
+  model?: string;
+  generatedAt: number;
+};
+
+export type ListeningPosition = {
+  // Block (paragraph) index.
+  blockId: number;
+  ms: number;
+  updatedAt: number;
 };
 
 export type Story = {
@@ -25,6 +84,149 @@ export type Story = {
   sourceText: string;
   sourceLanguage: string;
   translations: Record<string, StoryTranslation>;
+  // Written by the backend; see tagStory.
+  tags?: string[];
+  // The backend blanks sourceText (and job segment sources) on every save.
+  private?: boolean;
+  // File-safe name, unique in the library; set by the backend. See renameDoc.
+  slug?: string;
+};
+
+export type StoryName = {
+  storyId: string;
+  title: string;
+  slug: string;
+};
+
+// A trashed story, or one translation when `language` is set. Purged for
+// good at `purgeAt`.
+export type TrashItem = {
+  id: string;
+  storyId: string;
+  language?: string;
+  title: string;
+  trashedAt: number;
+  purgeAt: number;
+};
+
+export type ProfileFile = {
+  path: string;
+  size: number;
+  sha256: string;
+};
+
+// The integrity manifest at the root of a profile archive.
+export type ProfileManifest = {
+  format: string;
+  version: number;
+  appVersion: string;
+  createdAt: number;
+  files: ProfileFile[];
+};
+
+// `backup` is the profile as it was before the import; `frontend` is the
+// local storage snapshot saved with the archive, without API keys.
+export type ProfileImport = {
+  manifest: ProfileManifest;
+  backup: string;
+  frontend?: Record<string, unknown>;
+};
+
+// A named profile with its own stories, settings and caches.
+export type Profile = {
+  id: string;
+  name: string;
+  createdAt: number;
+};
+
+export type ProfileList = {
+  active: string;
+  profiles: Profile[];
+};
+
+// `frontend` is the local storage the profile had when it was last left.
+export type ProfileSwitch = {
+  profile: Profile;
+  frontend?: Record<string, unknown> | null;
+};
+
+// Entry points other than the app window. Each needs a token from Settings.
+export type AccessInterface = 'gui' | 'cli' | 'http' | 'deepLink';
+
+export type AccessAction = 'readLibrary' | 'modifyLibrary' | 'export' | 'generateSpeech' | 'startTranslation';
+
+export type InterfaceGrant = {
+  interface: AccessInterface;
+  allow: AccessAction[];
+  issuedAt: number;
+};
+
+// A job an outside interface asked for, waiting for the user to allow it.
+export type ExternalRequest = {
+  id: string;
+  interface: AccessInterface;
+  action: AccessAction;
+  summary: string;
+  requestedAt: number;
+};
+
+// A story, or with `language` one of its translations.
+export type LibraryItem = {
+  storyId: string;
+  language?: string;
+};
+
+export type Collection = {
+  id: string;
+  name: string;
+  createdAt: number;
+  items: LibraryItem[];
+};
+
+export type CompletionStatus = 'translating' | 'partial' | 'complete' | 'failed';
+
+// Every set field must match; `text` searches titles and source text.
+export type LibraryFilter = {
+  text?: string;
+  language?: string;
+  level?: CefrLevel;
+  status?: CompletionStatus;
+  tags?: string[];
+  collection?: string;
+  // Provider that made any of a translation's calls.
+  provider?: LlmProviderPreset;
+  // Part of a model id, ignoring case: 'claude' finds Claude on any provider.
+  model?: string;
+};
+
+export type LibraryDoc = {
+  language: string;
+  status: CompletionStatus;
+  // Graded-reader level, or estimated from the text when `graded` is false.
+  level?: CefrLevel;
+  graded: boolean;
+  tags: string[];
+  collections: string[];
+  meta?: StoryMeta;
+  // Providers and models that answered the translation's calls.
+  models: DocModel[];
+};
+
+export type DocModel = {
+  provider: LlmProviderPreset;
+  model: string;
+};
+
+export type LibraryEntry = {
+  storyId: string;
+  title: string;
+  category?: string;
+  sourceLanguage: string;
+  createdAt: number;
+  updatedAt: number;
+  tags: string[];
+  collections: string[];
+  docs: LibraryDoc[];
 };
 
 export type Span = {
@@ -32,41 +234,692 @@ export type Span = {
   sourceText: string;
   variants: Variant[];
   activeVariantIndex: number;
+  // Variant-generation calls that produced the variants, regenerations included.
+  provenance?: CallProvenance[];
 };
 
+// Emphasis carried over from Markdown in the source text.
+export type TextStyle = 'emphasis' | 'strong';
+
 export type DocToken =
-  | { type: 'text'; value: string }
-  | { type: 'span'; spanId: string };
+  // freqBand is only set on the word tokens of DocFrequency.
+  | { type: 'text'; value: string; style?: TextStyle; freqBand?: FreqBand }
+  | { type: 'span'; spanId: string; style?: TextStyle }
+  // True/false comprehension question closing a block; not part of the text.
+  | { type: 'check'; question: string; answer: boolean; explanation?: string };
+
+export type TextDirection = 'ltr' | 'rtl';
 
 export type InteractiveDoc = {
   tokens: DocToken[];
   spans: Record<string, Span>;
+  // Absent on docs saved before direction metadata existed.
+  direction?: TextDirection;
+  blockDirections?: TextDirection[];
+  // Layers left by the job's annotation passes, by pass id.
+  annotations?: Record<string, AnnotationLayer>;
+};
+
+// A piece of a variant's text and what the pass says about it: a reading, a
+// band, a level or a gloss. Joined, the tokens give back the variant text.
+export type AnnotatedToken = {
+  text: string;
+  value?: string;
+};
+
+// Span id to the tokens of each of its variants, in variant order.
+export type AnnotationLayer = {
+  spans: Record<string, AnnotatedToken[][]>;
+};
+
+// How common a word is in the doc's language, from the bundled or user frequency list. The
+// bundled lists only place top-1k words; 'top-5k' and 'rare' need a user list of 5000+ words.
+export type FreqBand = 'top-1k' | 'top-5k' | 'rare';
+
+// Words of a variant carry a band; the text between them does not. Joined, the tokens give back the text.
+export type FreqToken = {
+  text: string;
+  freqBand?: FreqBand;
+};
+
+// A sentence using a phrase, numbered within its block as read-along
+// numbers it.
+export type Usage = {
+  docId: string;
+  title: string;
+  block: number;
+  sentence: number;
+  text: string;
+};
+
+export type DocFrequency = {
+  language: string;
+  hasList: boolean;
+  // Under 5000 words, unknown words get no band instead of 'rare'.
+  listLen: number;
+  // Span id -> tokens of each variant, in variant order.
+  spans: Record<string, FreqToken[][]>;
+  // The doc's tokens, text runs split into words (with their band) and the text between them.
+  tokens: DocToken[];
+};
+
+// 'poor': sentences end with marks the splitter doesn't know, so whole paragraphs become one segment.
+export type SegmentationTier = 'poor' | 'basic' | 'full';
+
+export type LanguageSupport = {
+  code: string;
+  name: string;
+  // False for codes the prompts don't name; they're sent to the model as is.
+  translation: boolean;
+  // TTS models that speak the language, installed or not; empty means no audio.
+  ttsModels: string[];
+  romanization: boolean;
+  frequencyList: boolean;
+  segmentation: SegmentationTier;
+  direction: TextDirection;
 };
 
 export type SegmentStage = 'pending' | 'ready' | 'error';
 
+// What a job does when a segment keeps failing; skipped segments show as plain text.
+export type ErrorPolicy = 'abort' | 'skip' | 'retry-then-skip';
+
+// How much text one segment, and so one doc block, holds. Defaults to 'sentence'.
+export type Granularity = 'clause' | 'sentence' | 'paragraph';
+
+// Words asked for when co-writing: about 150, 350 or 800.
+export type ContinuationLength = 'short' | 'medium' | 'long';
+
 export type TranslationSegment = {
   id: string;
   source: string;
+  chapter: number;
   baseText?: string;
   baseStage: SegmentStage;
   spanStage: SegmentStage;
   variantCount: number;
+  variantTarget: number;
+  score?: SegmentScore;
+  simplifiedText?: string;
+  simplification?: VocabularyCheck;
+  // baseText was corrected at the review gate.
+  edited?: boolean;
+  // The source was too long for one call and was translated in clause-sized pieces.
+  autoSplit?: boolean;
+  // Per-span progress during variant generation; absent until spans are planned.
+  spans?: SpanProgress[];
+  // The provider declined to translate this segment; what it said instead.
+  refused?: string;
+  // Base-translation calls made for the segment, retries included.
+  provenance?: CallProvenance[];
+};
+
+// Which provider and model answered one call of a job.
+export type CallProvenance = {
+  provider: LlmProviderPreset;
+  model: string;
+  // 1 for the first try, 2 for the first retry, and so on.
+  attempt: number;
+  durationMs: number;
+};
+
+export type SpanStage = 'pending' | 'variants' | 'qa' | 'ready' | 'error';
+
+export type SpanProgress = {
+  anchor: string;
+  stage: SpanStage;
+  // Variants generated so far; after QA, the ones kept.
+  variants: number;
+};
+
+export type CefrLevel = 'A1' | 'A2' | 'B1' | 'B2' | 'C1' | 'C2';
+
+export type PracticeStage = 'learn' | 'review' | 'shadow';
+
+export type PracticeItem = {
+  spanId: string;
+  text: string;
+  stage: PracticeStage;
+  // TTS speed for this item.
+  speed: number;
+  repeats: number;
+};
+
+// Reviews come first in `items`, then the day's new sentences.
+export type PracticeDay = {
+  day: number;
+  items: PracticeItem[];
+};
+
+export type PracticePlan = {
+  level: CefrLevel;
+  sentences: number;
+  days: PracticeDay[];
+  createdAt: number;
+};
+
+// A sentence is numbered within its block, as read-along numbers it.
+export type StarTarget =
+  | { type: 'span'; spanId: string }
+  | { type: 'sentence'; block: number; sentence: number };
+
+export type Star = StarTarget & { starredAt: number };
+
+// `text` is the span's active variant or the sentence; `source` is empty
+// when the original is unknown.
+export type StarredItem = StarTarget & {
+  docId: string;
+  title: string;
+  language: string;
+  text: string;
+  source: string;
+  note?: string;
+  starredAt: number;
+};
+
+// A question to the tutor about block `block` of a doc, and its answer.
+export type TutorExchange = {
+  block: number;
+  question: string;
+  answer: string;
+  model: string;
+  askedAt: number;
+};
+
+// Who says each dialogue line of a doc, for multi-voice audio. Lines are
+// numbered per block among its quoted or dash-opened passages.
+export type Speaker = {
+  id: string;
+  name: string;
+  voice?: string;
+};
+
+export type SpeakerMap = {
+  narratorVoice?: string;
+  speakers: Speaker[];
+  lines: { block: number; line: number; speaker: string }[];
+  model?: string;
+  updatedAt: number;
+};
+
+export type DialogueLine = {
+  block: number;
+  line: number;
+  text: string;
+  speaker?: string;
+};
+
+export type SpeakerView = {
+  map: SpeakerMap;
+  lines: DialogueLine[];
+};
+
+export type SpeakerEdit =
+  | { op: 'setNarratorVoice'; voice: string | null }
+  | { op: 'addSpeaker'; name: string; voice?: string | null }
+  | { op: 'updateSpeaker'; id: string; name: string; voice?: string | null }
+  | { op: 'removeSpeaker'; id: string }
+  | { op: 'assignLine'; block: number; line: number; speaker: string | null };
+
+export type VocabularyCheck = {
+  level: CefrLevel;
+  passed: boolean;
+  longSentences: number;
+  hardWords: string[];
+  attempts: number;
+};
+
+export type SegmentScore = {
+  adequacy: number;
+  fluency: number;
+  rationale: string;
+  judgeModel: string;
+  needsReview: boolean;
+  attempts: number;
+};
+
+export type JobPreflight = {
+  charCount: number;
+  segmentCount: number;
+  chapterCount: number;
+  estimatedInputTokens: number;
+  estimatedOutputTokens: number;
+  model: string;
+  estimatedCostUsd: number | null;
+  estimatedCost: CostView;
+  requiresConfirmation: boolean;
+  confirmationToken: string;
+};
+
+// Soft spending cap for one job; the cost limit needs model pricing.
+export type JobBudget = {
+  maxTokens?: number;
+  maxCostUsd?: number;
+};
+
+// Payload of `boka:translation:budget-warning` (80%) and `boka:translation:budget` (cap reached).
+export type BudgetStatus = {
+  jobId: string;
+  budget: JobBudget;
+  tokens: number;
+  costUsd: number | null;
+  fraction: number;
+  reached: boolean;
+};
+
+export type JudgeConfig = {
+  provider?: LlmProviderConfig;
+  minScore?: number;
+  autoRetry?: boolean;
 };
 
 export type TranslationJob = {
   id: string;
   segments: TranslationSegment[];
   ready: boolean;
+  // Base translations are done; planning waits for approve_tauri_segments.
+  awaitingReview?: boolean;
+  metadata?: JobMetadata;
+  // Lints of the finished doc; see lintDoc.
+  lints?: DocLint[];
+};
+
+// A warning about a finished doc. `quote` is the pair, e.g. '«»', or the
+// mark when it pairs with itself.
+export type DocLint =
+  | { kind: 'emptySpan'; block: number; spanId: string }
+  | { kind: 'singleVariant'; block: number; spanId: string }
+  | { kind: 'duplicateVariants'; block: number; spanId: string; text: string; variants: number[] }
+  | { kind: 'longNote'; block: number; spanId: string; variant: number; chars: number; limit: number }
+  | { kind: 'missingNeutral'; block: number; spanId: string }
+  | { kind: 'unbalancedQuotes'; block: number; quote: string };
+
+export type SegmentEdit = {
+  segmentId: string;
+  baseText: string;
+};
+
+export type SamplingParams = {
+  temperature?: number;
+  topP?: number;
+  seed?: number;
+};
+
+export type PromptSet = {
+  baseTranslation: string;
+  spanPlanning: string;
+  spanVariants: string;
+  simplifiedTranslation?: string;
+  variantCritique?: string;
+  // Sent ahead of every system prompt of the job.
+  preamble?: string;
+};
+
+export type JobMetadata = {
+  reproducible: boolean;
+  provider: LlmProviderPreset;
+  model: string;
+  sampling: SamplingParams;
+  targetLanguage: string;
+  sourceLanguage?: string;
+  denseSpans: boolean;
+  contentPolicy: ContentPolicy;
+  prompts: PromptSet;
+  variantBounds: VariantBounds;
+  lengthGuard?: LengthGuard;
+  simplifyLevel?: CefrLevel;
+  dualOutput: boolean;
+  refineVariants?: boolean;
+  granularity?: Granularity;
+  comprehensionEvery?: number;
+  reviewRequired?: boolean;
+  errorPolicy?: ErrorPolicy;
+  budget?: JobBudget;
+  // Language the learner notes were written in; English when unset.
+  uiLanguage?: string;
+  // Annotation passes asked for, by id.
+  annotations?: string[];
+  appVersion: string;
+};
+
+export type VariantBounds = {
+  min: number;
+  max: number;
+};
+
+// A base translation longer than maxRatio times its source (after known
+// preambles are stripped) is asked for once more with a firmer prompt.
+export type LengthGuard = {
+  enabled: boolean;
+  maxRatio: number;
+};
+
+export type WarmupPolicy = 'on-startup' | 'on-doc-open' | 'on-first-use';
+
+// How far past the reading position speech is synthesized ahead: off, the
+// next few sentences, or the rest of the doc.
+export type PrefetchPolicy = 'off' | 'ahead' | 'whole-doc';
+
+export type ReadingPosition = {
+  docId: string;
+  // Block (paragraph) index.
+  block: number;
+  // Sentence within the block; 0 when omitted.
+  sentence?: number;
+  // Same voice and speed as playback, so prefetched audio is found in the cache.
+  voiceId?: string;
+  speed?: number;
+};
+
+export type BackendSettings = {
+  childSafeEnabled: boolean;
+  childSafePinSet: boolean;
+  variantBounds: VariantBounds;
+  ttsWarmup: WarmupPolicy;
+  ttsPrefetch: PrefetchPolicy;
+  // TTS model id per language code; unlisted languages use the default model.
+  ttsModels: Record<string, string>;
+  // Named speech settings per learner level ('beginner', 'intermediate', 'native', ...).
+  audioPresets: Record<string, AudioPreset>;
+  background: BackgroundPolicy;
+  // Parsed docs kept in memory per session; 0 turns the cache off.
+  docCacheSize: number;
+  // Sent ahead of the system prompt of every provider call.
+  systemPreamble: string | null;
+  retention: RetentionPolicy;
+  // Language code learner notes are written in; null means English.
+  uiLanguage: string | null;
+  pricing: PriceTable;
+  lengthGuard: LengthGuard;
+};
+
+export type RetentionPolicy = {
+  // Job transcripts are deleted at startup once this many days old; unset keeps them.
+  transcriptDays?: number;
 };
 
-export type LlmProviderPreset = 'anthropic' | 'openai' | 'openrouter' | 'ollama' | 'lmstudio' | 'custom';
+export type PurgeScope = 'transcripts' | 'translationCache' | 'audioCache' | 'all';
+
+export type PurgeReport = {
+  files: number;
+  bytes: number;
+};
+
+export type DocCacheStats = {
+  capacity: number;
+  len: number;
+  hits: number;
+  misses: number;
+};
+
+// What the running app has compiled in and loaded so far.
+export type RuntimeInfo = {
+  version: string;
+  features: string[];
+  ttsModels: { id: string; installed: boolean; loaded: boolean; sizeBytes: number }[];
+  // In memory for the doc cache, on disk for the audio cache.
+  caches: { name: string; entries: number; bytes: number }[];
+  subsystems: { name: string; loaded: boolean; approxBytes?: number }[];
+  // Only reported on Linux.
+  residentBytes?: number;
+};
+
+export type BackgroundMode = 'always' | 'when-idle' | 'paused';
+
+// When unrequested heavy work (TTS model downloads and warm-ups) may run.
+export type BackgroundPolicy = {
+  mode: BackgroundMode;
+  // Seconds without a running job or speech request before the app counts as idle.
+  idleSecs: number;
+  // Hold work on metered connections; only detected where the system reports it (NetworkManager).
+  avoidMetered: boolean;
+};
+
+export type BackgroundTask = { type: 'tts-warmup'; language: string | null; trigger: WarmupTrigger };
+
+export type BackgroundHold = 'paused' | 'metered' | 'busy';
+
+// Payload of `boka:background:status`, sent when it changes.
+export type BackgroundStatus = {
+  policy: BackgroundPolicy;
+  // Why pending work waits; absent when it may run.
+  heldBy?: BackgroundHold;
+  metered: boolean | null;
+  pending: BackgroundTask[];
+  running: BackgroundTask[];
+};
+
+export type ConfigFile = 'settings' | 'prompts' | 'templates' | 'models' | 'ttsModels';
+
+// Payload of `boka:config:reloaded`, sent after files in the data dir are edited.
+export type ConfigReloadedEvent = {
+  files: ConfigFile[];
+  // Files that no longer parse; the previous config stays in effect for them.
+  errors: string[];
+};
+
+export type AudioPreset = {
+  speed: number;
+  pauses: PauseOptions;
+  voiceId?: string;
+};
+
+export type ImportedStory = {
+  text: string;
+  pages: number;
+  model: string;
+  usage: Usage;
+};
+
+// The bundled offline demo; translate it with { preset: 'mock', baseUrl: 'demo' }.
+export type DemoStory = {
+  title: string;
+  sourceLanguage: string;
+  text: string;
+  languages: string[];
+};
+
+export type LlmProviderPreset = 'anthropic' | 'openai' | 'openrouter' | 'ollama' | 'lmstudio' | 'custom' | 'mock';
 
 export type LlmProviderConfig = {
   preset: LlmProviderPreset;
   apiKey?: string;
   baseUrl?: string;
   model?: string;
+  /** Strip `<think>` reasoning before parsing the reply. */
+  reasoningModel?: boolean;
+};
+
+export type ProviderFailure =
+  | 'invalidKey'
+  | 'modelNotFound'
+  | 'billingExhausted'
+  | 'rateLimited'
+  | 'unreachable'
+  | 'other';
+
+// What test_tauri_provider rejects with.
+export type ProviderTestError = {
+  failure: ProviderFailure;
+  status?: number;
+  message: string;
+  checkedAt: number;
+  // Reused from a probe in the last minute.
+  cached: boolean;
+};
+
+export type ProviderProbe = {
+  // `key: value` lines: provider, endpoint, model, auth, latency.
+  details: string;
+  checkedAt: number;
+  cached: boolean;
+};
+
+export type ModelCapabilities = {
+  jsonMode: boolean;
+  contextWindow: number;
+  vision: boolean;
+  maxOutput: number;
+};
+
+export type ModelEntry = {
+  preset: LlmProviderPreset;
+  model: string;
+  default: boolean;
+  capabilities: ModelCapabilities;
+  pricing?: ModelPricing;
+};
+
+export type ModelPricing = {
+  inputPerMtok: number;
+  outputPerMtok: number;
+};
+
+// A user price for a model (exact id or family prefix), laid over the registry's.
+export type PriceOverride = ModelPricing & {
+  preset: LlmProviderPreset;
+  model: string;
+};
+
+// Costs are shown in `code` at a fixed `perUsd` units per US dollar.
+export type DisplayCurrency = {
+  code: string;
+  perUsd: number;
+};
+
+export type PriceTable = {
+  models: PriceOverride[];
+  currency: DisplayCurrency;
+};
+
+// A cost in the display currency; `amount` is null and `label` counts
+// tokens when the model has no price.
+export type CostView = {
+  inputTokens: number;
+  outputTokens: number;
+  amount: number | null;
+  currency: string;
+  label: string;
+};
+
+export type Usage = {
+  input_tokens: number;
+  output_tokens: number;
+  // Calls the provider cut off at the output token limit.
+  max_token_stops?: number;
+};
+
+export type RunStage = 'translate' | 'simplify' | 'judge' | 'plan' | 'variants' | 'refine' | 'check';
+
+export type SegmentReport = {
+  id: string;
+  model: string;
+  judgeModel?: string;
+  ms: number;
+  usage: Usage;
+  // Extra calls made to fix up output (parse, judge, vocabulary and moderation retries).
+  retries: number;
+  warnings: string[];
+  // Translation and variant calls, with the provider and model of each.
+  calls?: CallProvenance[];
+};
+
+// Written to the data dir's reports folder (JSON + Markdown) when a job ends.
+export type RunReport = {
+  jobId: string;
+  status: 'completed' | 'failed' | 'cancelled';
+  error?: string;
+  startedAt: number;
+  finishedAt: number;
+  provider?: LlmProviderPreset;
+  model?: string;
+  targetLanguage: string;
+  usage: Usage;
+  stages: { stage: RunStage; ms: number; calls: number }[];
+  segments: SegmentReport[];
+  parseFailures: number;
+  truncations: number;
+  // Segment from which the job used the simple line-based output format.
+  simpleFormatFrom?: string;
+  // Segments that failed and were left for a later retry.
+  skippedSegments?: string[];
+};
+
+// Reliability of one provider/model across all saved run reports (local only).
+export type ModelStats = {
+  provider: LlmProviderPreset;
+  model: string;
+  jobs: number;
+  failedJobs: number;
+  segments: number;
+  calls: number;
+  parseFailures: number;
+  truncations: number;
+  maxTokenStops: number;
+  retries: number;
+  parseFailureRate: number;
+  truncationRate: number;
+  retriesPerSegment: number;
+};
+
+export type PromptOverrides = {
+  baseTranslation?: string;
+  spanPlanning?: string;
+  spanVariants?: string;
+  // Appended to every translation prompt; jobs fill it from the saved per-language addendum.
+  languageAddendum?: string;
+  // Replaces the preamble from settings; '' sends none.
+  systemPreamble?: string;
+};
+
+export type PromptOptions = {
+  targetLanguage: string;
+  sourceLanguage?: string;
+  adultMode?: boolean;
+  contentPolicy?: ContentPolicy;
+  denseSpans?: boolean;
+  simplifyLevel?: CefrLevel;
+  promptOverrides?: PromptOverrides;
+  refineVariants?: boolean;
+  granularity?: Granularity;
+  uiLanguage?: string;
+  // Decides whether the JSON-mode note is added; defaults to Anthropic.
+  provider?: LlmProviderConfig;
+};
+
+export type PromptPreview = {
+  prompts: PromptSet;
+  model: string | null;
+  jsonMode: boolean;
+};
+
+export type ExperimentArm = {
+  label: string;
+  provider: LlmProviderConfig;
+  prompts?: PromptOverrides;
+};
+
+export type ArmOutcome = {
+  label: string;
+  model: string;
+  usage: Usage;
+  estimatedCostUsd: number | null;
+  elapsedMs: number;
+  job?: TranslationJob;
+  doc?: InteractiveDoc;
+  error?: string;
+};
+
+export type ComparisonRow = {
+  segmentId: string;
+  source: string;
+  outputs: (string | null)[];
+  identical: boolean;
+};
+
+export type ExperimentReport = {
+  arms: ArmOutcome[];
+  rows: ComparisonRow[];
 };
 
 export type Script = {
@@ -85,6 +938,7 @@ export type AudioStage = 'loading_model' | 'generating' | 'encoding' | 'cached';
 export type AudioProgressEvent = {
   requestId: string;
   stage: AudioStage;
+  messageKey: string;
   message: string;
 };
 
@@ -93,11 +947,17 @@ export type AudioReadyEvent = {
   audioBase64: string;
   durationMs: number;
   sampleRate: number;
+  // TTS registry id of the model that spoke, or 'system'.
+  modelId: string;
+  // True when the OS voice stood in because the model wasn't ready yet.
+  fallback: boolean;
 };
 
 export type AudioErrorEvent = {
   requestId: string;
+  messageKey: string;
   message: string;
+  detail?: string;
 };
 
 export type AudioModelStatus = {
@@ -108,11 +968,116 @@ export type AudioModelStatus = {
   error: string | null;
 };
 
+// Result of checking the audio cache; removed entries are regenerated on next play.
+export type AudioCacheCheck = {
+  checked: number;
+  removed: number;
+  orphans: number;
+};
+
+// Cached audio of one profile from one model version ('kokoro-82m@v1.0').
+export type AudioCacheNamespace = {
+  profile: string;
+  model: string;
+  entries: number;
+  sizeBytes: number;
+};
+
+export type WarmupTrigger = 'startup' | 'doc-open' | 'first-use';
+
+export type AudioEngineReadyEvent = {
+  trigger: WarmupTrigger;
+  modelId: string;
+  ready: boolean;
+  elapsedMs: number;
+  error: string | null;
+};
+
+// Cached audio of a doc for one voice and speed: each block as the reader
+// speaks it, and each variant of its spans. Durations are unset when missing.
+export type VariantAudioStatus = {
+  spanId: string;
+  variantIndex: number;
+  durationMs?: number;
+};
+
+export type BlockAudioStatus = {
+  block: number;
+  durationMs?: number;
+  variants: VariantAudioStatus[];
+};
+
+export type DocAudioStatus = {
+  docId: string;
+  modelVersion: string;
+  voiceId: string;
+  speed: number;
+  blocks: BlockAudioStatus[];
+  cached: number;
+  total: number;
+};
+
+export type PhonemeMatch = 'correct' | 'substituted' | 'missing' | 'extra';
+
+export type AlignedPhoneme = {
+  status: PhonemeMatch;
+  expected: string | null;
+  detected: string | null;
+  syllable: number | null;
+  startMs: number | null;
+  endMs: number | null;
+};
+
+export type PronunciationComparison = {
+  phonemes: AlignedPhoneme[];
+  syllablesOff: number[];
+  score: number;
+};
+
+export type TtsModelInfo = {
+  id: string;
+  name: string;
+  engine: 'kokoro';
+  languages: string[];
+  sizeBytes: number;
+  quality: 'low' | 'medium' | 'high';
+  default: boolean;
+  modelFile?: string;
+  voicesFile?: string;
+  // Model and voice pack version; a new one starts a fresh audio cache.
+  revision?: string;
+  installed: boolean;
+};
+
+export type ControlRange = {
+  min: number;
+  max: number;
+  default: number;
+  step?: number;
+};
+
+// What can be tuned on a voice; see VoiceStyle.
+export type VoiceControls = {
+  speed: ControlRange;
+  // Absent when the voice can't be blended.
+  blendWeight?: ControlRange;
+  maxBlend: number;
+  forceStyle: boolean;
+};
+
 export type VoiceInfo = {
   id: string;
   name: string;
   language: string;
-  sampleRate: number;
+  sampleUrl?: string;
+  controls: VoiceControls;
+};
+
+// Blends other voices into the speaking one, and keeps the voice's own
+// accent in other languages when forceStyle is set.
+export type VoiceStyle = {
+  blend?: { voiceId: string; weight: number }[];
+  forceStyle?: boolean;
 };
 
 export type AudioRequest = {
@@ -120,4 +1085,95 @@ export type AudioRequest = {
   language: string;
   voiceId?: string;
   speed?: number;
+  style?: VoiceStyle;
+};
+
+// Milliseconds of silence; 0 leaves the break to the voice.
+export type PauseOptions = {
+  commaMs?: number;
+  sentenceMs?: number;
+  paragraphMs?: number;
+};
+
+export type PathStatus = {
+  name: string;
+  path: string;
+  exists: boolean;
+};
+
+export type ClassroomPackOptions = {
+  // Blank every n-th eligible word in the cloze worksheet (default 7).
+  clozeEvery?: number;
+  quizQuestions?: number;
+  // Leave mastered words out of the cloze and quiz (default true); off for a whole class.
+  skipKnownWords?: boolean;
+  outputPath?: string;
+};
+
+export type DocTextFormat = 'plain' | 'markdown';
+
+export type DocTextOptions = {
+  format?: DocTextFormat;
+  // Read every span in the variant closest to this register; saved choices when unset.
+  register?: string;
+  // List the notes of the variants read under each block.
+  notes?: boolean;
+  source?: boolean;
+};
+
+export type CardExample = {
+  sentence: string;
+  // Source text of the span the sentence comes from.
+  translation: string;
+  docId: string;
+  title: string;
+};
+
+export type CardAudio = {
+  audioBase64: string;
+  durationMs: number;
+};
+
+// A flashcard for one word, mined from the library. No dictionary ships, so
+// `gloss` is only set when a short span (a title, a one-word line) has the word.
+export type WordCard = {
+  lemma: string;
+  language: string;
+  word: string;
+  gloss?: string;
+  example: CardExample;
+  occurrences: number;
+  // Absent without TTS or when generation failed.
+  wordAudio?: CardAudio;
+  exampleAudio?: CardAudio;
+};
+
+export type LedgerEntry = {
+  word: string;
+  masteredAt: number;
+};
+
+// Base language code -> lemma -> entry.
+export type VocabLedger = Record<string, Record<string, LedgerEntry>>;
+
+// `spans`: source, neutral, all variants, notes, difficulty per span.
+// `vocab`: lemma, word, gloss, frequency per lemma.
+export type TableKind = 'spans' | 'vocab';
+
+export type TemplateInfo = {
+  name: string;
+  builtin: boolean;
+  extension: string;
+};
+
+// ── Command errors ──
+
+// What every Tauri command rejects with. `code` is stable: `translation.*`
+// and `audio.*` match the event message keys, `storage.*` covers saved
+// stories, `authz.*` outside access, and `command.failed` everything else.
+export type CommandError = {
+  code: string;
+  message: string;
+  // Numbers behind the code, e.g. `{ status }` or `{ chars, limit }`.
+  details?: Record<string, unknown>;
 };
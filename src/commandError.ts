@@ -0,0 +1,11 @@
+import type { CommandError } from './bokaTypes';
+
+export function isCommandError(e: unknown): e is CommandError {
+  return typeof e === 'object' && e !== null && 'code' in e && 'message' in e;
+}
+
+// Text for a rejected command, a thrown Error, or anything else.
+export function errorMessage(e: unknown): string {
+  if (isCommandError(e) || e instanceof Error) return e.message;
+  return String(e);
+}
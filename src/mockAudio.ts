@@ -20,7 +20,7 @@ export function generate_mock_speech(args: {
   timers.push(
     window.setTimeout(() => {
       if (cancelled) return;
-      onProgress({ requestId, stage: 'generating', message: 'Generating speech (mock)...' });
+      onProgress({ requestId, stage: 'generating', messageKey: 'audio.generating', message: 'Generating speech (mock)...' });
     }, 100),
   );
 
@@ -44,6 +44,8 @@ export function generate_mock_speech(args: {
           audioBase64: '',
           durationMs: 0,
           sampleRate: 0,
+          modelId: 'system',
+          fallback: true,
         });
       };
 
@@ -79,10 +79,12 @@ export function start_mock_translation(args: {
   const segs: TranslationSegment[] = seg_texts.map((s, i) => ({
     id: `seg-${i + 1}`,
     source: s,
+    chapter: 0,
     baseText: undefined,
     baseStage: 'pending',
     spanStage: 'pending',
     variantCount: 0,
+    variantTarget: 0,
   }));
 
   const job_id = `job-${Date.now()}`;
@@ -1,6 +1,27 @@
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
-import type { AudioErrorEvent, AudioModelStatus, AudioProgressEvent, AudioReadyEvent } from './bokaTypes';
+import type {
+  AudioCacheCheck,
+  AudioCacheNamespace,
+  AudioEngineReadyEvent,
+  AudioErrorEvent,
+  AudioModelStatus,
+  AudioProgressEvent,
+  AudioPreset,
+  AudioReadyEvent,
+  BackendSettings,
+  BackgroundPolicy,
+  BackgroundStatus,
+  CardAudio,
+  DocAudioStatus,
+  PauseOptions,
+  PrefetchPolicy,
+  ReadingPosition,
+  TtsModelInfo,
+  VoiceInfo,
+  VoiceStyle,
+  WarmupPolicy,
+} from './bokaTypes';
 
 function isTauriRuntime(): boolean {
   return (
@@ -14,11 +35,18 @@ export async function generate_speech(args: {
   language: string;
   voiceId?: string;
   speed?: number;
+  // Checked against the voice's controls (see list_voices).
+  style?: VoiceStyle;
+  // Silence after punctuation and between blank-line separated paragraphs.
+  pauses?: PauseOptions;
+  // Named preset from settings; fills in voiceId, speed and pauses when unset.
+  preset?: string;
+  locale?: string;
   onProgress: (event: AudioProgressEvent) => void;
   onReady: (event: AudioReadyEvent) => void;
   onError: (message: string) => void;
 }): Promise<{ cancel: () => void; requestId: string }> {
-  const { text, language, voiceId, speed, onProgress, onReady, onError } = args;
+  const { text, language, voiceId, speed, style, pauses, preset, locale, onProgress, onReady, onError } = args;
 
   if (!isTauriRuntime()) {
     throw new Error('Not running in Tauri runtime');
@@ -51,6 +79,10 @@ export async function generate_speech(args: {
       language,
       voiceId: voiceId ?? null,
       speed: speed ?? null,
+      style: style ?? null,
+      pauses: pauses ?? null,
+      preset: preset ?? null,
+      locale: locale ?? navigator.language,
     });
   } catch (e) {
     unlistenProgress();
@@ -74,16 +106,150 @@ export async function generate_speech(args: {
   };
 }
 
-export async function get_audio_status(): Promise<AudioModelStatus> {
+// Without a language, reports on the default TTS model.
+export async function get_audio_status(language?: string): Promise<AudioModelStatus> {
   if (!isTauriRuntime()) {
     throw new Error('Not running in Tauri runtime');
   }
-  return invoke<AudioModelStatus>('boka_get_audio_status');
+  return invoke<AudioModelStatus>('boka_get_audio_status', { language: language ?? null });
 }
 
-export async function preload_model(): Promise<void> {
+// What of the doc is already cached, without generating anything; null when unavailable.
+export async function get_doc_audio_status(
+  storyId: string,
+  language: string,
+  voiceId?: string,
+  speed?: number,
+): Promise<DocAudioStatus | null> {
+  if (!isTauriRuntime()) return null;
+  try {
+    return await invoke<DocAudioStatus>('boka_get_doc_audio_status', {
+      docId: `${storyId}:${language}`,
+      voiceId: voiceId ?? null,
+      speed: speed ?? null,
+    });
+  } catch (e) {
+    console.warn('[boka] Failed to get doc audio status:', e);
+    return null;
+  }
+}
+
+// Voices of the TTS model for the language, for narrator and speaker voices.
+export async function list_voices(language?: string): Promise<VoiceInfo[]> {
+  if (!isTauriRuntime()) return [];
+  return invoke<VoiceInfo[]>('boka_list_voices', { language: language ?? null });
+}
+
+// A silence-trimmed clip of a single word, for flashcards and Anki exports.
+export async function generate_word_audio(word: string, language: string, voice?: string): Promise<CardAudio> {
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+  return invoke<CardAudio>('boka_generate_word_audio', { word, language, voice: voice ?? null });
+}
+
+export async function verify_audio_cache(): Promise<AudioCacheCheck> {
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+  return invoke<AudioCacheCheck>('boka_verify_audio_cache');
+}
+
+export async function list_audio_cache(): Promise<AudioCacheNamespace[]> {
+  if (!isTauriRuntime()) return [];
+  return invoke<AudioCacheNamespace[]>('boka_list_audio_cache');
+}
+
+// Without a profile, clears the active one; without a model, all its models.
+export async function clear_audio_cache(profile?: string, model?: string): Promise<AudioCacheNamespace[]> {
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+  return invoke<AudioCacheNamespace[]>('boka_clear_audio_cache', { profile: profile ?? null, model: model ?? null });
+}
+
+export async function preload_model(language?: string): Promise<void> {
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+  await invoke('boka_preload_model', { language: language ?? null });
+}
+
+export async function list_tts_models(): Promise<TtsModelInfo[]> {
+  if (!isTauriRuntime()) return [];
+  return invoke<TtsModelInfo[]>('boka_list_tts_models');
+}
+
+// Pass null to go back to the default model for the language.
+export async function set_tts_model(language: string, modelId: string | null): Promise<BackendSettings> {
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+  return invoke<BackendSettings>('boka_set_tts_model', { language, modelId });
+}
+
+// Pass null to remove the preset.
+export async function set_audio_preset(name: string, preset: AudioPreset | null): Promise<BackendSettings> {
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+  return invoke<BackendSettings>('boka_set_audio_preset', { name, preset });
+}
+
+export async function set_tts_warmup(policy: WarmupPolicy): Promise<BackendSettings> {
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+  return invoke<BackendSettings>('boka_set_tts_warmup', { policy });
+}
+
+export async function set_tts_prefetch(policy: PrefetchPolicy): Promise<BackendSettings> {
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+  return invoke<BackendSettings>('boka_set_tts_prefetch', { policy });
+}
+
+export async function set_background_policy(policy: BackgroundPolicy): Promise<BackgroundStatus> {
   if (!isTauriRuntime()) {
     throw new Error('Not running in Tauri runtime');
   }
-  await invoke('boka_preload_model');
+  return invoke<BackgroundStatus>('boka_set_background_policy', { policy });
+}
+
+export async function get_background_status(): Promise<BackgroundStatus> {
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+  return invoke<BackgroundStatus>('boka_get_background_status');
+}
+
+export async function on_background_status(handler: (status: BackgroundStatus) => void): Promise<() => void> {
+  if (!isTauriRuntime()) return () => {};
+  return listen<BackgroundStatus>('boka:background:status', (ev) => {
+    if (ev.payload) handler(ev.payload);
+  });
+}
+
+// Lets the backend warm up the doc language's model when the policy is 'on-doc-open'.
+export async function audio_doc_opened(language?: string): Promise<void> {
+  if (!isTauriRuntime()) return;
+  await invoke('boka_audio_doc_opened', { language: language ?? null });
+}
+
+// Cheap enough to call on every sentence; the backend synthesizes what comes next.
+export async function report_reading_position(position: ReadingPosition): Promise<void> {
+  if (!isTauriRuntime()) return;
+  try {
+    await invoke('boka_report_reading_position', { position });
+  } catch (e) {
+    console.warn('[boka] Failed to report reading position:', e);
+  }
+}
+
+export async function on_engine_ready(handler: (event: AudioEngineReadyEvent) => void): Promise<() => void> {
+  if (!isTauriRuntime()) return () => {};
+  return listen<AudioEngineReadyEvent>('boka:audio:engine-ready', (ev) => {
+    if (ev.payload) handler(ev.payload);
+  });
 }
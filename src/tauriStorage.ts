@@ -1,5 +1,56 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { Story } from './bokaTypes';
+import { listen } from '@tauri-apps/api/event';
+import type {
+  AccessAction,
+  AccessInterface,
+  BackendSettings,
+  BundleOptions,
+  CefrLevel,
+  ClassroomPackOptions,
+  Collection,
+  ConfigReloadedEvent,
+  DocCacheStats,
+  DocFrequency,
+  DocLint,
+  DocTextOptions,
+  ExternalRequest,
+  InteractiveDoc,
+  InterfaceGrant,
+  LanguageSupport,
+  LibraryEntry,
+  LibraryFilter,
+  LibraryItem,
+  ListeningPosition,
+  LlmProviderConfig,
+  LlmProviderPreset,
+  ModelPricing,
+  PauseOptions,
+  PracticePlan,
+  Profile,
+  ProfileImport,
+  ProfileList,
+  ProfileSwitch,
+  ProfileManifest,
+  PurgeReport,
+  PurgeScope,
+  RetentionPolicy,
+  RuntimeInfo,
+  SpeakerEdit,
+  SpeakerView,
+  Star,
+  StarredItem,
+  Story,
+  StoryMeta,
+  StoryName,
+  TableKind,
+  TemplateInfo,
+  TrashItem,
+  TutorExchange,
+  Usage,
+  VocabLedger,
+  WordCard,
+} from './bokaTypes';
+import type { RegisterId } from './registers';
 
 function isTauriRuntime(): boolean {
   return (
@@ -29,3 +80,776 @@ export async function writeStoriesToFile(stories: Story[]): Promise<boolean> {
     return false;
   }
 }
+
+// One saved doc, served from the backend's session cache when it was opened
+// before. Returns null outside Tauri or on failure.
+export async function getDoc(storyId: string, language: string): Promise<InteractiveDoc | null> {
+  if (!isTauriRuntime()) return null;
+  try {
+    return await invoke<InteractiveDoc>('boka_get_doc', { docId: `${storyId}:${language}` });
+  } catch (e) {
+    console.warn('[boka] Failed to load doc:', e);
+    return null;
+  }
+}
+
+// Frequency bands for every word of a saved doc. Returns null outside Tauri or on failure.
+export async function getDocFrequency(storyId: string, language: string): Promise<DocFrequency | null> {
+  if (!isTauriRuntime()) return null;
+  try {
+    return await invoke<DocFrequency>('boka_get_doc_frequency', { docId: `${storyId}:${language}` });
+  } catch (e) {
+    console.warn('[boka] Failed to load word frequencies:', e);
+    return null;
+  }
+}
+
+// Lints of a saved doc, such as spans with a single variant or quotes left open.
+export async function lintDoc(storyId: string, language: string): Promise<DocLint[]> {
+  if (!isTauriRuntime()) return [];
+  try {
+    return await invoke<DocLint[]>('boka_lint_doc', { docId: `${storyId}:${language}` });
+  } catch (e) {
+    console.warn('[boka] Failed to lint doc:', e);
+    return [];
+  }
+}
+
+// Text of block `block` of a saved doc with the reader's picks (span id to
+// variant index), for TTS, exports and copying. Returns null outside Tauri
+// or on failure.
+export async function renderBlockText(
+  storyId: string,
+  language: string,
+  block: number,
+  selections?: Record<string, number>,
+): Promise<string | null> {
+  if (!isTauriRuntime()) return null;
+  try {
+    return await invoke<string>('boka_render_block_text', {
+      docId: `${storyId}:${language}`,
+      block,
+      selections: selections ?? null,
+    });
+  } catch (e) {
+    console.warn('[boka] Failed to render block text:', e);
+    return null;
+  }
+}
+
+// Ids of the annotation passes a job in `language` can ask for.
+export async function listAnnotationPasses(language: string): Promise<string[]> {
+  if (!isTauriRuntime()) return [];
+  try {
+    return await invoke<string[]>('boka_list_annotation_passes', { language });
+  } catch (e) {
+    console.warn('[boka] Failed to list annotation passes:', e);
+    return [];
+  }
+}
+
+// Sentences of the library's docs in `language` that use `phrase`, in any
+// inflection. Returns [] outside Tauri or on failure.
+export async function findUsages(phrase: string, language: string): Promise<Usage[]> {
+  if (!isTauriRuntime()) return [];
+  try {
+    return await invoke<Usage[]>('boka_find_usages', { phrase, language });
+  } catch (e) {
+    console.warn('[boka] Failed to find usages:', e);
+    return [];
+  }
+}
+
+// What each language supports, for hiding options that would silently degrade. Returns null outside Tauri or on failure.
+export async function getLanguageSupport(): Promise<LanguageSupport[] | null> {
+  if (!isTauriRuntime()) return null;
+  try {
+    return await invoke<LanguageSupport[]>('boka_get_language_support');
+  } catch (e) {
+    console.warn('[boka] Failed to load language support:', e);
+    return null;
+  }
+}
+
+export async function getDocCacheStats(): Promise<DocCacheStats | null> {
+  if (!isTauriRuntime()) return null;
+  return invoke<DocCacheStats>('boka_get_doc_cache_stats');
+}
+
+export async function getRuntimeInfo(): Promise<RuntimeInfo | null> {
+  if (!isTauriRuntime()) return null;
+  return invoke<RuntimeInfo>('boka_get_runtime_info');
+}
+
+// How many parsed docs the backend keeps in memory; 0 turns the cache off.
+export async function setDocCacheSize(size: number): Promise<BackendSettings> {
+  if (!isTauriRuntime()) throw new Error('Not running in Tauri runtime');
+  return invoke<BackendSettings>('boka_set_doc_cache_size', { size });
+}
+
+// Instructions sent ahead of every provider call, e.g. "never add translator's notes"; null or '' removes them.
+export async function setSystemPreamble(text: string | null): Promise<BackendSettings> {
+  if (!isTauriRuntime()) throw new Error('Not running in Tauri runtime');
+  return invoke<BackendSettings>('boka_set_system_preamble', { text });
+}
+
+// Language code new jobs write learner notes in, e.g. 'de'; null goes back to English.
+export async function setUiLanguage(uiLanguage: string | null): Promise<BackendSettings> {
+  if (!isTauriRuntime()) throw new Error('Not running in Tauri runtime');
+  return invoke<BackendSettings>('boka_set_ui_language', { uiLanguage });
+}
+
+// How long job transcripts are kept; enforced when the app starts.
+export async function setRetention(policy: RetentionPolicy): Promise<BackendSettings> {
+  if (!isTauriRuntime()) throw new Error('Not running in Tauri runtime');
+  return invoke<BackendSettings>('boka_set_retention', { policy });
+}
+
+// Price a model for cost estimates and budgets; null goes back to the registry's price.
+export async function setModelPrice(
+  preset: LlmProviderPreset,
+  model: string,
+  pricing: ModelPricing | null,
+): Promise<BackendSettings> {
+  if (!isTauriRuntime()) throw new Error('Not running in Tauri runtime');
+  return invoke<BackendSettings>('boka_set_model_price', { preset, model, pricing });
+}
+
+// Show costs in `code` (e.g. 'EUR') at a fixed `perUsd` rate.
+export async function setDisplayCurrency(code: string, perUsd: number): Promise<BackendSettings> {
+  if (!isTauriRuntime()) throw new Error('Not running in Tauri runtime');
+  return invoke<BackendSettings>('boka_set_display_currency', { code, perUsd });
+}
+
+// Turn the base-translation length guard on or off; maxRatio is 1.5 to 10.
+export async function setLengthGuard(enabled: boolean, maxRatio: number): Promise<BackendSettings> {
+  if (!isTauriRuntime()) throw new Error('Not running in Tauri runtime');
+  return invoke<BackendSettings>('boka_set_length_guard', { enabled, maxRatio });
+}
+
+// Switches every span of one saved doc to the closest variant in `register`
+// and persists it. Returns null outside Tauri or on failure.
+export async function setDocRegister(storyId: string, language: string, register: RegisterId): Promise<InteractiveDoc | null> {
+  if (!isTauriRuntime()) return null;
+  try {
+    return await invoke<InteractiveDoc>('boka_set_doc_register', { docId: `${storyId}:${language}`, register });
+  } catch (e) {
+    console.warn('[boka] Failed to set doc register:', e);
+    return null;
+  }
+}
+
+// Remembers where listening stopped (block index + offset into its audio).
+export async function saveListeningPosition(
+  storyId: string,
+  language: string,
+  blockId: number,
+  ms: number,
+): Promise<ListeningPosition | null> {
+  if (!isTauriRuntime()) return null;
+  try {
+    return await invoke<ListeningPosition>('boka_save_listening_position', {
+      docId: `${storyId}:${language}`,
+      blockId,
+      ms: Math.max(0, Math.round(ms)),
+    });
+  } catch (e) {
+    console.warn('[boka] Failed to save listening position:', e);
+    return null;
+  }
+}
+
+export async function getListeningPosition(storyId: string, language: string): Promise<ListeningPosition | null> {
+  if (!isTauriRuntime()) return null;
+  try {
+    return await invoke<ListeningPosition | null>('boka_get_listening_position', { docId: `${storyId}:${language}` });
+  } catch (e) {
+    console.warn('[boka] Failed to read listening position:', e);
+    return null;
+  }
+}
+
+// Plans shadowing practice for a doc at `level` (the doc's own level when
+// omitted) and stores it with the translation.
+export async function generatePracticePlan(
+  storyId: string,
+  language: string,
+  level?: CefrLevel,
+): Promise<PracticePlan> {
+  if (!isTauriRuntime()) throw new Error('Practice plans need the desktop app');
+  return invoke<PracticePlan>('boka_generate_practice_plan', { docId: `${storyId}:${language}`, level });
+}
+
+export async function getPracticePlan(storyId: string, language: string): Promise<PracticePlan | null> {
+  if (!isTauriRuntime()) return null;
+  try {
+    return await invoke<PracticePlan | null>('boka_get_practice_plan', { docId: `${storyId}:${language}` });
+  } catch (e) {
+    console.warn('[boka] Failed to read practice plan:', e);
+    return null;
+  }
+}
+
+// Stars a span, or unstars it with `starred` false; returns the doc's stars.
+export async function starSpan(
+  storyId: string,
+  language: string,
+  spanId: string,
+  starred = true,
+): Promise<Star[]> {
+  if (!isTauriRuntime()) throw new Error('Not running in Tauri runtime');
+  return invoke<Star[]>('boka_star_span', { docId: `${storyId}:${language}`, spanId, starred });
+}
+
+export async function starSentence(
+  storyId: string,
+  language: string,
+  block: number,
+  sentence: number,
+  starred = true,
+): Promise<Star[]> {
+  if (!isTauriRuntime()) throw new Error('Not running in Tauri runtime');
+  return invoke<Star[]>('boka_star_sentence', { docId: `${storyId}:${language}`, block, sentence, starred });
+}
+
+// Starred spans and sentences across the library, newest first.
+export async function listStarred(language?: string): Promise<StarredItem[]> {
+  if (!isTauriRuntime()) return [];
+  try {
+    return await invoke<StarredItem[]>('boka_list_starred', { language });
+  } catch (e) {
+    console.warn('[boka] Failed to list starred items:', e);
+    return [];
+  }
+}
+
+// Asks the tutor about a block (paragraph) of a doc; the exchange is kept in
+// the doc's history. The answer is in `uiLanguage`, or the settings' one.
+export async function askAbout(
+  storyId: string,
+  language: string,
+  block: number,
+  question: string,
+  provider: LlmProviderConfig,
+  uiLanguage?: string,
+): Promise<TutorExchange> {
+  if (!isTauriRuntime()) throw new Error('Not running in Tauri runtime');
+  return invoke<TutorExchange>('boka_ask_about', {
+    docId: `${storyId}:${language}`,
+    blockId: block,
+    question,
+    uiLanguage,
+    provider,
+  });
+}
+
+export async function getTutorHistory(storyId: string, language: string): Promise<TutorExchange[]> {
+  if (!isTauriRuntime()) return [];
+  try {
+    return await invoke<TutorExchange[]>('boka_get_tutor_history', { docId: `${storyId}:${language}` });
+  } catch (e) {
+    console.warn('[boka] Failed to read tutor history:', e);
+    return [];
+  }
+}
+
+export async function getSpeakers(storyId: string, language: string): Promise<SpeakerView | null> {
+  if (!isTauriRuntime()) return null;
+  try {
+    return await invoke<SpeakerView>('boka_get_speakers', { docId: `${storyId}:${language}` });
+  } catch (e) {
+    console.warn('[boka] Failed to read speakers:', e);
+    return null;
+  }
+}
+
+// Asks the model who says each dialogue line; replaces the line assignments
+// and keeps the voices already picked.
+export async function attributeSpeakers(
+  storyId: string,
+  language: string,
+  provider: LlmProviderConfig,
+  maxSpeakers?: number,
+): Promise<SpeakerView> {
+  if (!isTauriRuntime()) throw new Error('Not running in Tauri runtime');
+  return await invoke<SpeakerView>('boka_attribute_speakers', {
+    docId: `${storyId}:${language}`,
+    provider,
+    maxSpeakers: maxSpeakers ?? null,
+  });
+}
+
+export async function editSpeakers(storyId: string, language: string, edits: SpeakerEdit[]): Promise<SpeakerView> {
+  if (!isTauriRuntime()) throw new Error('Not running in Tauri runtime');
+  return await invoke<SpeakerView>('boka_edit_speakers', { docId: `${storyId}:${language}`, edits });
+}
+
+// Moves a story, or one translation when `language` is given, to the trash.
+// Stories dropped by writeStoriesToFile are trashed the same way.
+export async function trashStory(storyId: string, language?: string): Promise<TrashItem | null> {
+  if (!isTauriRuntime()) return null;
+  try {
+    return await invoke<TrashItem>('boka_trash_story', { storyId, language: language ?? null });
+  } catch (e) {
+    console.warn('[boka] Failed to trash story:', e);
+    return null;
+  }
+}
+
+// Restores a trashed entry and returns all stories, which should replace the
+// frontend's copy before the next write. Throws with the reason (e.g. the
+// story exists again) so it can be shown.
+export async function restoreStory(trashId: string): Promise<Story[]> {
+  if (!isTauriRuntime()) throw new Error('Not running in Tauri runtime');
+  return await invoke<Story[]>('boka_restore_story', { trashId });
+}
+
+export async function listTrash(): Promise<TrashItem[]> {
+  if (!isTauriRuntime()) return [];
+  try {
+    return await invoke<TrashItem[]>('boka_list_trash');
+  } catch (e) {
+    console.warn('[boka] Failed to list trash:', e);
+    return [];
+  }
+}
+
+// Deletes transcripts and/or cached translations and audio of the active profile now.
+export async function privacyPurge(scope: PurgeScope): Promise<PurgeReport> {
+  if (!isTauriRuntime()) throw new Error('Not running in Tauri runtime');
+  return invoke<PurgeReport>('boka_privacy_purge', { scope });
+}
+
+export async function purgeTrash(trashId: string): Promise<boolean> {
+  if (!isTauriRuntime()) return false;
+  try {
+    await invoke('boka_purge_trash', { trashId });
+    return true;
+  } catch (e) {
+    console.warn('[boka] Failed to purge trash:', e);
+    return false;
+  }
+}
+
+// Generates (or regenerates) shelf metadata for a saved doc. Finished
+// translations get it automatically; see onLibraryMeta.
+export async function describeDoc(storyId: string, language: string, provider: LlmProviderConfig): Promise<StoryMeta> {
+  if (!isTauriRuntime()) throw new Error('Not running in Tauri runtime');
+  return await invoke<StoryMeta>('boka_describe_doc', { docId: `${storyId}:${language}`, provider });
+}
+
+// Renames the story of a saved doc and gives it a new slug. Without a title,
+// `provider` suggests one; without either, it is derived from the story.
+export async function renameDoc(
+  storyId: string,
+  language: string,
+  title?: string,
+  provider?: LlmProviderConfig,
+): Promise<StoryName> {
+  if (!isTauriRuntime()) throw new Error('Not running in Tauri runtime');
+  return await invoke<StoryName>('boka_rename_doc', {
+    docId: `${storyId}:${language}`,
+    title: title ?? null,
+    provider: provider ?? null,
+  });
+}
+
+// Rewrites the learner notes of a saved doc in `uiLanguage` and returns the saved doc.
+export async function translateNotes(
+  storyId: string,
+  language: string,
+  uiLanguage: string,
+  provider: LlmProviderConfig,
+): Promise<InteractiveDoc> {
+  if (!isTauriRuntime()) throw new Error('Not running in Tauri runtime');
+  return await invoke<InteractiveDoc>('boka_translate_notes', {
+    docId: `${storyId}:${language}`,
+    uiLanguage,
+    provider,
+  });
+}
+
+// Fires when shelf metadata was stored for a doc after its translation
+// finished. Returns the unlisten function.
+export async function onLibraryMeta(
+  handler: (storyId: string, language: string, meta: StoryMeta) => void,
+): Promise<() => void> {
+  if (!isTauriRuntime()) return () => {};
+  return listen<{ docId: string; meta: StoryMeta }>('boka:library:meta', (ev) => {
+    if (!ev.payload) return;
+    // Language codes never contain ':', story ids might.
+    const split = ev.payload.docId.lastIndexOf(':');
+    handler(ev.payload.docId.slice(0, split), ev.payload.docId.slice(split + 1), ev.payload.meta);
+  });
+}
+
+// Replaces the tags of a story, or of one translation when `language` is
+// given. Returns the stored tags (lowercased, deduplicated), or null outside
+// Tauri or on failure.
+export async function tagStory(storyId: string, tags: string[], language?: string): Promise<string[] | null> {
+  if (!isTauriRuntime()) return null;
+  try {
+    return await invoke<string[]>('boka_tag_story', { storyId, language: language ?? null, tags });
+  } catch (e) {
+    console.warn('[boka] Failed to tag story:', e);
+    return null;
+  }
+}
+
+export async function listCollections(): Promise<Collection[]> {
+  if (!isTauriRuntime()) return [];
+  try {
+    return await invoke<Collection[]>('boka_list_collections');
+  } catch (e) {
+    console.warn('[boka] Failed to list collections:', e);
+    return [];
+  }
+}
+
+// Throws on invalid or duplicate names so the form can show why.
+export async function createCollection(name: string, items: LibraryItem[] = []): Promise<Collection> {
+  if (!isTauriRuntime()) throw new Error('Not running in Tauri runtime');
+  return await invoke<Collection>('boka_create_collection', { name, items });
+}
+
+export async function updateCollection(
+  id: string,
+  changes: { name?: string; items?: LibraryItem[] },
+): Promise<Collection> {
+  if (!isTauriRuntime()) throw new Error('Not running in Tauri runtime');
+  return await invoke<Collection>('boka_update_collection', { id, ...changes });
+}
+
+export async function deleteCollection(id: string): Promise<boolean> {
+  if (!isTauriRuntime()) return false;
+  try {
+    await invoke('boka_delete_collection', { id });
+    return true;
+  } catch (e) {
+    console.warn('[boka] Failed to delete collection:', e);
+    return false;
+  }
+}
+
+export async function queryLibrary(filter: LibraryFilter): Promise<LibraryEntry[] | null> {
+  if (!isTauriRuntime()) return null;
+  try {
+    return await invoke<LibraryEntry[]>('boka_query_library', { filter });
+  } catch (e) {
+    console.warn('[boka] Failed to query library:', e);
+    return null;
+  }
+}
+
+// Exports a saved doc as a self-contained read-along HTML page (audio per
+// block plus word highlighting). `outputPath` defaults to the data dir's
+// exports folder. Returns the written path, or null outside Tauri or on failure.
+export async function exportReadAlong(
+  storyId: string,
+  language: string,
+  options: { outputPath?: string; voiceId?: string; speed?: number; pauses?: PauseOptions; preset?: string } = {},
+): Promise<string | null> {
+  if (!isTauriRuntime()) return null;
+  try {
+    return await invoke<string>('boka_export_readalong', { docId: `${storyId}:${language}`, ...options });
+  } catch (e) {
+    console.warn('[boka] Failed to export read-along:', e);
+    return null;
+  }
+}
+
+// Exports a saved doc as a zip of lesson material: doc JSON, printable copy,
+// cloze worksheet, vocab CSV and quiz. Returns the written path, or null
+// outside Tauri or on failure.
+export async function exportClassroomPack(
+  storyId: string,
+  language: string,
+  options: ClassroomPackOptions = {},
+): Promise<string | null> {
+  if (!isTauriRuntime()) return null;
+  try {
+    return await invoke<string>('boka_export_classroom_pack', { docId: `${storyId}:${language}`, options });
+  } catch (e) {
+    console.warn('[boka] Failed to export classroom pack:', e);
+    return null;
+  }
+}
+
+// Copies the doc's text to the system clipboard and returns it, or null on failure.
+export async function copyDocText(
+  storyId: string,
+  language: string,
+  options: DocTextOptions = {},
+): Promise<string | null> {
+  if (!isTauriRuntime()) return null;
+  try {
+    return await invoke<string>('boka_copy_doc_text', { docId: `${storyId}:${language}`, options });
+  } catch (e) {
+    console.warn('[boka] Failed to copy doc text:', e);
+    return null;
+  }
+}
+
+export async function getVocabLedger(): Promise<VocabLedger> {
+  if (!isTauriRuntime()) return {};
+  try {
+    return await invoke<VocabLedger>('boka_get_vocab_ledger');
+  } catch (e) {
+    console.warn('[boka] Failed to read vocabulary ledger:', e);
+    return {};
+  }
+}
+
+// Marks every word of `text` as mastered (or not). Returns how many lemmas changed.
+export async function markWordsKnown(language: string, text: string, known: boolean): Promise<number> {
+  if (!isTauriRuntime()) return 0;
+  return invoke<number>('boka_mark_words_known', { language, text, known });
+}
+
+// Unmastered lemmas per text; empty outside Tauri or on failure.
+export async function countUnknownWords(language: string, texts: string[]): Promise<number[]> {
+  if (!isTauriRuntime()) return [];
+  try {
+    return await invoke<number[]>('boka_count_unknown_words', { language, texts });
+  } catch (e) {
+    console.warn('[boka] Failed to check known words:', e);
+    return [];
+  }
+}
+
+// Builds a flashcard for `lemma` (any form of the word) from the library.
+export async function makeWordCard(lemma: string, language: string): Promise<WordCard> {
+  if (!isTauriRuntime()) throw new Error('Word cards need the desktop app');
+  return invoke<WordCard>('boka_make_word_card', { lemma, language });
+}
+
+// Writes a doc's spans or vocabulary to `path` as CSV, or TSV when the path
+// ends in .tsv. Returns false outside Tauri or on failure.
+export async function exportTable(storyId: string, language: string, path: string, kind: TableKind): Promise<boolean> {
+  if (!isTauriRuntime()) return false;
+  try {
+    await invoke('boka_export_csv', { docId: `${storyId}:${language}`, path, kind });
+    return true;
+  } catch (e) {
+    console.warn('[boka] Failed to export table:', e);
+    return false;
+  }
+}
+
+// Writes a doc in the JSON Lines interchange format shared with the TUI and
+// CLI. Returns false outside Tauri or on failure.
+export async function exportJsonl(storyId: string, language: string, path: string): Promise<boolean> {
+  if (!isTauriRuntime()) return false;
+  try {
+    await invoke('boka_export_jsonl', { docId: `${storyId}:${language}`, path });
+    return true;
+  } catch (e) {
+    console.warn('[boka] Failed to export JSONL:', e);
+    return false;
+  }
+}
+
+// Imports a JSON Lines doc into stories.json and returns its `storyId:language`
+// id. Throws the validation message (with the offending line) on bad input.
+export async function importJsonl(path: string): Promise<string> {
+  if (!isTauriRuntime()) throw new Error('JSONL import needs the desktop app');
+  return invoke<string>('boka_import_jsonl', { path });
+}
+
+// Local storage keys that belong in a profile. Stories live in stories.json
+// in the desktop app; the browser-only copies are left out.
+const PROFILE_SKIPPED_KEYS = ['boka.stories', 'boka.scripts'];
+
+function localStorageSnapshot(): Record<string, unknown> {
+  const snapshot: Record<string, unknown> = {};
+  for (let i = 0; i < localStorage.length; i++) {
+    const key = localStorage.key(i);
+    if (!key || !key.startsWith('boka.') || PROFILE_SKIPPED_KEYS.includes(key)) continue;
+    const raw = localStorage.getItem(key);
+    if (raw === null) continue;
+    // Parsed so the backend can strip API keys from nested settings.
+    try {
+      snapshot[key] = JSON.parse(raw);
+    } catch {
+      snapshot[key] = raw;
+    }
+  }
+  return snapshot;
+}
+
+// Packs a doc into a .boka bundle for other users; `path` defaults to the
+// exports folder. Returns the path written.
+export async function exportBundle(
+  storyId: string,
+  language: string,
+  options: BundleOptions = {},
+  path?: string,
+): Promise<string> {
+  if (!isTauriRuntime()) throw new Error('Bundle export needs the desktop app');
+  return invoke<string>('boka_export_bundle', { docId: `${storyId}:${language}`, path, options });
+}
+
+// Imports a .boka bundle as a read-only shared doc and returns its
+// `storyId:language` id; re-read the stories afterwards. Throws when the file
+// is not a bundle, is damaged or comes from a newer app.
+export async function importBundle(path: string): Promise<string> {
+  if (!isTauriRuntime()) throw new Error('Bundle import needs the desktop app');
+  return invoke<string>('boka_import_bundle', { path });
+}
+
+// Per-block audio a shared doc came with; null for blocks without.
+export async function sharedAudio(
+  storyId: string,
+  language: string,
+): Promise<({ audioBase64: string; durationMs: number } | null)[]> {
+  if (!isTauriRuntime()) return [];
+  try {
+    return await invoke('boka_shared_audio', { docId: `${storyId}:${language}` });
+  } catch (e) {
+    console.warn('[boka] Failed to load shared audio:', e);
+    return [];
+  }
+}
+
+// Writes all user data, including this window's settings, to one archive at
+// `path` for moving to another machine. API keys are never included.
+export async function exportProfile(path: string): Promise<ProfileManifest> {
+  if (!isTauriRuntime()) throw new Error('Profile export needs the desktop app');
+  return invoke<ProfileManifest>('boka_export_profile', { path, frontend: localStorageSnapshot() });
+}
+
+// Replaces the profile with the archive at `path` once its manifest checks
+// out, and restores the saved settings here while keeping the current API
+// key. Reload the app afterwards: a write from the old state would undo the
+// import. Throws when the archive is damaged or from a newer app. Without the
+// teacher `pin`, a locked child-safe mode stays as it is.
+export async function importProfile(path: string, pin?: string): Promise<ProfileImport> {
+  if (!isTauriRuntime()) throw new Error('Profile import needs the desktop app');
+  const result = await invoke<ProfileImport>('boka_import_profile', { path, pin });
+  let current: Record<string, unknown> = {};
+  try {
+    current = JSON.parse(localStorage.getItem('boka.settings') ?? '{}') ?? {};
+  } catch {}
+  const keys = { providerApiKey: current.providerApiKey, anthropicKey: current.anthropicKey };
+  const settings = result.frontend?.['boka.settings'];
+  const frontend: Record<string, unknown> = { ...result.frontend };
+  if (settings && typeof settings === 'object') frontend['boka.settings'] = { ...settings, ...keys };
+  restoreLocalStorage(frontend, false);
+  return result;
+}
+
+// Writes a snapshot from `localStorageSnapshot` back. With `replace`, profile
+// keys the snapshot lacks are removed.
+function restoreLocalStorage(snapshot: Record<string, unknown>, replace: boolean) {
+  const profileKey = (key: string) => key.startsWith('boka.') && !PROFILE_SKIPPED_KEYS.includes(key);
+  if (replace) {
+    const keys = Array.from({ length: localStorage.length }, (_, i) => localStorage.key(i));
+    for (const key of keys) {
+      if (key && profileKey(key)) localStorage.removeItem(key);
+    }
+  }
+  for (const [key, value] of Object.entries(snapshot)) {
+    if (!profileKey(key)) continue;
+    localStorage.setItem(key, typeof value === 'string' ? value : JSON.stringify(value));
+  }
+}
+
+// All profiles and the active one; just the default outside Tauri.
+export async function listProfiles(): Promise<ProfileList | null> {
+  if (!isTauriRuntime()) return null;
+  try {
+    return await invoke<ProfileList>('boka_list_profiles');
+  } catch (e) {
+    console.warn('[boka] Failed to list profiles:', e);
+    return null;
+  }
+}
+
+// Throws when the name is empty or taken, or without the teacher `pin`
+// while child-safe mode has one.
+export async function createProfile(name: string, pin?: string): Promise<Profile> {
+  if (!isTauriRuntime()) throw new Error('Profiles need the desktop app');
+  return invoke<Profile>('boka_create_profile', { name, pin });
+}
+
+// Switches to profile `id`, keeping this profile's local storage for when it
+// is switched back to and loading the new one's. Reload the app afterwards:
+// stories, settings and caches are all per profile. Throws while
+// translations are running, and without the teacher `pin` while child-safe
+// mode has one.
+export async function switchProfile(id: string, pin?: string): Promise<Profile> {
+  if (!isTauriRuntime()) throw new Error('Profiles need the desktop app');
+  const result = await invoke<ProfileSwitch>('boka_switch_profile', { id, frontend: localStorageSnapshot(), pin });
+  restoreLocalStorage(result.frontend ?? {}, true);
+  return result.profile;
+}
+
+export async function listInterfaceGrants(): Promise<InterfaceGrant[]> {
+  if (!isTauriRuntime()) return [];
+  try {
+    return await invoke<InterfaceGrant[]>('boka_list_interface_grants');
+  } catch (e) {
+    console.warn('[boka] Failed to list interface grants:', e);
+    return [];
+  }
+}
+
+// Returns the new token; show it once, it can't be read back. Read-only
+// access unless `allow` says more.
+export async function issueInterfaceToken(iface: AccessInterface, allow?: AccessAction[]): Promise<string> {
+  if (!isTauriRuntime()) throw new Error('Outside access needs the desktop app');
+  return invoke<string>('boka_issue_interface_token', { interface: iface, allow });
+}
+
+export async function revokeInterface(iface: AccessInterface): Promise<boolean> {
+  if (!isTauriRuntime()) return false;
+  return invoke<boolean>('boka_revoke_interface', { interface: iface });
+}
+
+export async function listExternalRequests(): Promise<ExternalRequest[]> {
+  if (!isTauriRuntime()) return [];
+  try {
+    return await invoke<ExternalRequest[]>('boka_list_external_requests');
+  } catch (e) {
+    console.warn('[boka] Failed to list external requests:', e);
+    return [];
+  }
+}
+
+// False when the request was already answered or withdrawn.
+export async function resolveExternalRequest(id: string, approved: boolean): Promise<boolean> {
+  if (!isTauriRuntime()) return false;
+  return invoke<boolean>('boka_resolve_external_request', { id, approved });
+}
+
+// Built-in export templates plus the user's, from the data dir's templates folder.
+export async function listExportTemplates(): Promise<TemplateInfo[]> {
+  if (!isTauriRuntime()) return [];
+  try {
+    return await invoke<TemplateInfo[]>('boka_list_export_templates');
+  } catch (e) {
+    console.warn('[boka] Failed to list export templates:', e);
+    return [];
+  }
+}
+
+// Renders a doc with a template; `path` defaults to the exports folder.
+// Throws the template error so authors can fix their template.
+export async function exportWithTemplate(
+  storyId: string,
+  language: string,
+  templateName: string,
+  path?: string,
+): Promise<string> {
+  if (!isTauriRuntime()) throw new Error('Template export needs the desktop app');
+  return invoke<string>('boka_export_with_template', { docId: `${storyId}:${language}`, templateName, path });
+}
+
+// Fires when settings.json, prompts.json, models.json or a template is edited
+// on disk; refetch anything derived from them. Returns the unlisten function.
+export async function onConfigReloaded(handler: (event: ConfigReloadedEvent) => void): Promise<() => void> {
+  if (!isTauriRuntime()) return () => {};
+  return listen<ConfigReloadedEvent>('boka:config:reloaded', (ev) => {
+    if (ev.payload) handler(ev.payload);
+  });
+}
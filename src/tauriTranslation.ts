@@ -1,6 +1,32 @@
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
-import type { InteractiveDoc, LlmProviderConfig, TranslationJob } from './bokaTypes';
+import type {
+  BudgetStatus,
+  CefrLevel,
+  ContentPolicy,
+  ContinuationLength,
+  CostView,
+  DemoStory,
+  ErrorPolicy,
+  ExperimentArm,
+  Granularity,
+  ExperimentReport,
+  ImportedStory,
+  InteractiveDoc,
+  JobBudget,
+  JobPreflight,
+  JudgeConfig,
+  LlmProviderConfig,
+  LlmProviderPreset,
+  ModelStats,
+  PromptOptions,
+  PromptPreview,
+  ProviderProbe,
+  RunReport,
+  SegmentEdit,
+  TranslationJob,
+  Usage,
+} from './bokaTypes';
 
 function isTauriRuntime(): boolean {
   return (
@@ -16,21 +42,71 @@ type DocEvent = {
 
 type ErrorEvent = {
   jobId: string;
+  messageKey: string;
   message: string;
+  detail: string;
 };
 
+export async function prepare_tauri_translation(args: {
+  storyText: string;
+  provider: LlmProviderConfig;
+}): Promise<JobPreflight> {
+  const { storyText, provider } = args;
+
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+
+  return invoke<JobPreflight>('boka_prepare_translation', { storyText, provider });
+}
+
 export async function start_tauri_translation(args: {
   storyText: string;
   targetLanguage?: string;
   sourceLanguage?: string;
   adultMode: boolean;
+  contentPolicy?: ContentPolicy;
   denseSpans: boolean;
+  reproducible?: boolean;
+  judge?: JudgeConfig;
+  simplifyLevel?: CefrLevel;
+  // With simplifyLevel: keep the faithful translation and add the simplified one per segment.
+  dualOutput?: boolean;
+  // Second pass that drops unnatural variants and fixes register labels; one more call per span.
+  refineVariants?: boolean;
+  // Close every N blocks with a true/false question on them; one more call per question.
+  // Clause segments give short spans; paragraph segments give one block per paragraph.
+  granularity?: Granularity;
+  comprehensionEvery?: number;
+  // Annotation passes run on the finished doc, by id; see listAnnotationPasses.
+  annotations?: string[];
+  // Pause once the base translations are ready; resume with approve_tauri_segments.
+  reviewRequired?: boolean;
+  // 'skip' and 'retry-then-skip' finish the job around failed segments; see retry_tauri_failed_segments.
+  errorPolicy?: ErrorPolicy;
+  // At the cap the job pauses until confirm_tauri_budget; without onBudget it stops there.
+  budget?: JobBudget;
+  confirmationToken?: string;
+  // Identical text, options and provider as a running job returns that job's id; set to start a second run.
+  allowDuplicate?: boolean;
+  locale?: string;
+  // Replaces the always-on preamble from settings for this job; '' sends none.
+  systemPreamble?: string;
+  // Record every prompt and reply of the job, keys redacted; see export_tauri_job_transcript.
+  transcript?: boolean;
+  // Journals and the like: no transcript and no translation cache; mark the story private when saving it.
+  private?: boolean;
+  // Language of the learner notes; defaults to the one in settings, else English.
+  uiLanguage?: string;
   provider: LlmProviderConfig;
   onJob: (job: TranslationJob) => void;
   onDoc: (doc: InteractiveDoc) => void;
   onError: (message: string) => void;
+  onReview?: (job: TranslationJob) => void;
+  onBudgetWarning?: (status: BudgetStatus) => void;
+  onBudget?: (status: BudgetStatus) => void;
 }): Promise<{ cancel: () => void; jobId: string }> {
-  const { storyText, targetLanguage, sourceLanguage, adultMode, denseSpans, provider, onJob, onDoc, onError } = args;
+  const { storyText, targetLanguage, sourceLanguage, adultMode, contentPolicy, denseSpans, reproducible, judge, simplifyLevel, dualOutput, refineVariants, granularity, comprehensionEvery, annotations, reviewRequired, errorPolicy, budget, confirmationToken, allowDuplicate, locale, systemPreamble, transcript, private: isPrivate, uiLanguage, provider, onJob, onDoc, onError, onReview, onBudgetWarning, onBudget } = args;
 
   if (!isTauriRuntime()) {
     throw new Error('Not running in Tauri runtime');
@@ -56,6 +132,28 @@ export async function start_tauri_translation(args: {
     onError(ev.payload.message);
   });
 
+  const unlistenReview = await listen<TranslationJob>('boka:translation:review', (ev) => {
+    if (!ev.payload) return;
+    if (jobId && ev.payload.id !== jobId) return;
+    onReview?.(ev.payload);
+  });
+
+  const unlistenBudgetWarning = await listen<BudgetStatus>('boka:translation:budget-warning', (ev) => {
+    if (!ev.payload) return;
+    if (jobId && ev.payload.jobId !== jobId) return;
+    onBudgetWarning?.(ev.payload);
+  });
+
+  const unlistenBudget = await listen<BudgetStatus>('boka:translation:budget', (ev) => {
+    if (!ev.payload) return;
+    if (jobId && ev.payload.jobId !== jobId) return;
+    if (onBudget) {
+      onBudget(ev.payload);
+    } else {
+      void invoke('boka_confirm_budget', { jobId: ev.payload.jobId, proceed: false });
+    }
+  });
+
   let startedJobId: string;
   try {
     startedJobId = await invoke<string>('boka_start_translation', {
@@ -63,13 +161,35 @@ export async function start_tauri_translation(args: {
       targetLanguage: targetLanguage ?? null,
       sourceLanguage: sourceLanguage ?? null,
       adultMode,
+      contentPolicy: contentPolicy ?? null,
       denseSpans,
+      reproducible: reproducible ?? false,
+      judge: judge ?? null,
+      simplifyLevel: simplifyLevel ?? null,
+      dualOutput: dualOutput ?? false,
+      refineVariants: refineVariants ?? false,
+      granularity: granularity ?? null,
+      comprehensionEvery: comprehensionEvery ?? null,
+      annotations: annotations ?? null,
+      reviewRequired: reviewRequired ?? false,
+      errorPolicy: errorPolicy ?? null,
+      budget: budget ?? null,
+      confirmationToken: confirmationToken ?? null,
+      allowDuplicate: allowDuplicate ?? false,
+      locale: locale ?? navigator.language,
+      systemPreamble: systemPreamble ?? null,
+      transcript: transcript ?? false,
+      private: isPrivate ?? false,
+      uiLanguage: uiLanguage ?? null,
       provider,
     });
   } catch (e) {
     unlistenJob();
     unlistenDoc();
     unlistenErr();
+    unlistenReview();
+    unlistenBudgetWarning();
+    unlistenBudget();
     throw e;
   }
 
@@ -84,18 +204,233 @@ export async function start_tauri_translation(args: {
       unlistenJob();
       unlistenDoc();
       unlistenErr();
+      unlistenReview();
+      unlistenBudgetWarning();
+      unlistenBudget();
     },
   };
 }
 
-export async function test_tauri_provider(args: { provider: LlmProviderConfig }): Promise<string> {
-  const { provider } = args;
+export async function approve_tauri_segments(args: { jobId: string; edits?: SegmentEdit[] }): Promise<void> {
+  const { jobId, edits } = args;
+
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+
+  await invoke('boka_approve_segments', { jobId, edits: edits ?? null });
+}
+
+export async function confirm_tauri_budget(args: { jobId: string; proceed: boolean }): Promise<void> {
+  const { jobId, proceed } = args;
+
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+
+  await invoke('boka_confirm_budget', { jobId, proceed });
+}
+
+// Translate again the segments a finished job skipped; the story is saved by the backend.
+export async function retry_tauri_failed_segments(args: {
+  jobId: string;
+  // Defaults to the job's own provider and model.
+  provider?: LlmProviderConfig;
+  onJob?: (job: TranslationJob) => void;
+  onDoc?: (doc: InteractiveDoc) => void;
+}): Promise<TranslationJob> {
+  const { jobId, provider, onJob, onDoc } = args;
+
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+
+  const unlistenJob = await listen<TranslationJob>('boka:translation:job', (ev) => {
+    if (ev.payload?.id === jobId) onJob?.(ev.payload);
+  });
+  const unlistenDoc = await listen<DocEvent>('boka:translation:doc', (ev) => {
+    if (ev.payload?.jobId === jobId) onDoc?.(ev.payload.doc);
+  });
+
+  try {
+    return await invoke<TranslationJob>('boka_retry_failed_segments', { jobId, provider: provider ?? null });
+  } finally {
+    unlistenJob();
+    unlistenDoc();
+  }
+}
+
+// Have the model write the next part of a saved story, then translate it onto the doc. Progress comes as the
+// doc's job `jobId`, extended; the story and its source text are saved by the backend.
+export async function continue_tauri_story(args: {
+  storyId: string;
+  language: string;
+  jobId: string;
+  directionHint?: string;
+  length?: ContinuationLength;
+  // Defaults to the job's own provider and model.
+  provider?: LlmProviderConfig;
+  onJob?: (job: TranslationJob) => void;
+  onDoc?: (doc: InteractiveDoc) => void;
+}): Promise<TranslationJob> {
+  const { storyId, language, jobId, directionHint, length, provider, onJob, onDoc } = args;
+
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+
+  const unlistenJob = await listen<TranslationJob>('boka:translation:job', (ev) => {
+    if (ev.payload?.id === jobId) onJob?.(ev.payload);
+  });
+  const unlistenDoc = await listen<DocEvent>('boka:translation:doc', (ev) => {
+    if (ev.payload?.jobId === jobId) onDoc?.(ev.payload.doc);
+  });
+
+  try {
+    return await invoke<TranslationJob>('boka_continue_story', {
+      docId: `${storyId}:${language}`,
+      directionHint: directionHint ?? null,
+      length: length ?? null,
+      provider: provider ?? null,
+    });
+  } finally {
+    unlistenJob();
+    unlistenDoc();
+  }
+}
+
+// Rejects with a ProviderTestError saying what to fix. Results are reused for a minute unless `force` is set.
+export async function test_tauri_provider(args: { provider: LlmProviderConfig; force?: boolean }): Promise<ProviderProbe> {
+  const { provider, force } = args;
 
   if (!isTauriRuntime()) {
     throw new Error('Not running in Tauri runtime');
   }
 
-  return invoke<string>('boka_test_provider', {
+  return invoke<ProviderProbe>('boka_test_provider', {
     provider,
+    force: force ?? false,
+  });
+}
+
+export async function import_tauri_images(args: {
+  paths: string[];
+  provider: LlmProviderConfig;
+}): Promise<ImportedStory> {
+  const { paths, provider } = args;
+
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+
+  return invoke<ImportedStory>('boka_import_image', { paths, provider });
+}
+
+export async function get_tauri_demo_story(): Promise<DemoStory> {
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+
+  return invoke<DemoStory>('boka_demo_story');
+}
+
+export async function run_tauri_prompt_experiment(args: {
+  storyText: string;
+  targetLanguage?: string;
+  sourceLanguage?: string;
+  denseSpans: boolean;
+  maxSegments?: number;
+  arms: ExperimentArm[];
+}): Promise<ExperimentReport> {
+  const { storyText, targetLanguage, sourceLanguage, denseSpans, maxSegments, arms } = args;
+
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+
+  return invoke<ExperimentReport>('boka_run_prompt_experiment', {
+    storyText,
+    targetLanguage: targetLanguage ?? null,
+    sourceLanguage: sourceLanguage ?? null,
+    denseSpans,
+    maxSegments: maxSegments ?? null,
+    arms,
   });
 }
+
+// Languages with a saved prompt addendum (extra instructions for that target language).
+export async function list_tauri_prompt_addenda(): Promise<string[]> {
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+
+  return invoke<string[]>('boka_list_prompt_addenda');
+}
+
+export async function get_tauri_prompt_addendum(language: string): Promise<string | null> {
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+
+  return invoke<string | null>('boka_get_prompt_addendum', { language });
+}
+
+// Null or blank text removes the addendum.
+export async function set_tauri_prompt_addendum(language: string, text: string | null): Promise<void> {
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+
+  return invoke<void>('boka_set_prompt_addendum', { language, text });
+}
+
+// The exact system prompts a job with these options would send, given the
+// current settings, prompts.json and addenda. Overrides in `options.promptOverrides`
+// (e.g. an unsaved addendum) win over the saved ones.
+export async function preview_tauri_prompts(options: PromptOptions): Promise<PromptPreview> {
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+
+  return invoke<PromptPreview>('boka_preview_prompts', { options });
+}
+
+// The run report of a finished (or failed) job. Throws when none was written.
+export async function get_tauri_job_report(jobId: string): Promise<RunReport> {
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+
+  return invoke<RunReport>('boka_get_job_report', { jobId });
+}
+
+// Copies the transcript of a job started with `transcript: true` to `path`; resolves to its size in bytes.
+export async function export_tauri_job_transcript(jobId: string, path: string): Promise<number> {
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+
+  return invoke<number>('boka_export_job_transcript', { jobId, path });
+}
+
+// What a run's usage costs in the display currency, or its tokens when the model has no price.
+export async function describe_tauri_cost(args: {
+  preset: LlmProviderPreset;
+  model: string;
+  usage: Usage;
+}): Promise<CostView> {
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+
+  return invoke<CostView>('boka_describe_cost', args);
+}
+
+// Parse-failure, truncation and retry rates per provider/model, from local run reports.
+export async function get_tauri_model_stats(): Promise<ModelStats[]> {
+  if (!isTauriRuntime()) {
+    throw new Error('Not running in Tauri runtime');
+  }
+
+  return invoke<ModelStats[]>('boka_get_model_stats');
+}
@@ -1,12 +1,36 @@
 import React from 'react';
-import type { InteractiveDoc, StoryTranslation, TranslationJob } from '../bokaTypes';
+import type {
+  InteractiveDoc,
+  StoryTranslation,
+  TextStyle,
+  TranslationJob,
+  TranslationSegment,
+  Usage,
+} from '../bokaTypes';
 import CategoryPicker from '../components/CategoryPicker';
 import LanguagePicker from '../components/LanguagePicker';
 import RegisterChip from '../components/RegisterChip';
 import { REGISTER_CSS_VAR, type RegisterId } from '../registers';
+import { findUsages } from '../tauriStorage';
 
 export type ViewMode = 'expanded' | 'interactive';
 
+// Span-stage label: PLANNING, VARIANTS 2/4 (spans done of planned) or QA while a span is reviewed.
+function spanStatus(seg: TranslationSegment, planning: boolean): string {
+  const spans = seg.spans ?? [];
+  if (seg.spanStage !== 'pending' || (spans.length === 0 && !planning)) return seg.spanStage.toUpperCase();
+  if (spans.length === 0) return 'PLANNING';
+  if (spans.some((s) => s.stage === 'qa')) return 'QA';
+  const done = spans.filter((s) => s.stage === 'ready').length;
+  return `VARIANTS ${done}/${spans.length}`;
+}
+
+function withStyle(style: TextStyle | undefined, text: string): React.ReactNode {
+  if (style === 'strong') return <strong>{text}</strong>;
+  if (style === 'emphasis') return <em>{text}</em>;
+  return text;
+}
+
 export default function CompilerView(props: {
   title: string;
   sourceText: string;
@@ -24,6 +48,7 @@ export default function CompilerView(props: {
   isAudioPlaying: boolean;
   activeLanguage: string | null;
   onSetActiveVariant: (spanId: string, variantIndex: number) => void;
+  onStarSpan: (spanId: string, starred: boolean) => void;
   storyTranslations: Record<string, StoryTranslation>;
   onSwitchLanguage: (language: string) => void;
   category: string | null;
@@ -47,6 +72,7 @@ export default function CompilerView(props: {
     isAudioPlaying,
     activeLanguage,
     onSetActiveVariant,
+    onStarSpan,
     storyTranslations,
     onSwitchLanguage,
     category,
@@ -55,6 +81,12 @@ export default function CompilerView(props: {
   } = props;
 
   const [editingTitle, setEditingTitle] = React.useState(false);
+  // Other sentences using the selected text, or the span's text.
+  const [usages, setUsages] = React.useState<{ spanId: string; phrase: string; items: Usage[] } | null>(null);
+  const starredSpanIds = React.useMemo(() => {
+    const stars = activeLanguage ? storyTranslations[activeLanguage]?.starred ?? [] : [];
+    return new Set(stars.flatMap((s) => (s.type === 'span' ? [s.spanId] : [])));
+  }, [activeLanguage, storyTranslations]);
   const [titleDraft, setTitleDraft] = React.useState(title);
 
   const ready = job?.ready ?? false;
@@ -65,6 +97,11 @@ export default function CompilerView(props: {
   const activeMenuRef = React.useRef<HTMLDivElement | null>(null);
 
   const baseReady = job ? job.segments.filter((s) => s.baseStage === 'ready').length : 0;
+  // Segments are planned in order; the first with a base but no spans yet is the one in progress.
+  const planningIdx =
+    job && !job.awaitingReview
+      ? job.segments.findIndex((s) => s.baseStage === 'ready' && s.spanStage === 'pending')
+      : -1;
   const spanReady = job ? job.segments.filter((s) => s.spanStage === 'ready').length : 0;
   const total = job ? job.segments.length : 0;
 
@@ -99,6 +136,7 @@ export default function CompilerView(props: {
         out += tok.value;
         continue;
       }
+      if (tok.type === 'check') continue;
       const span = doc.spans[tok.spanId];
       if (!span || span.variants.length === 0) {
         out += '…';
@@ -301,10 +339,21 @@ export default function CompilerView(props: {
             {!doc ? (
               <div className="muted">Document not ready yet.</div>
             ) : (
-              <div className="doc">
+              <div className="doc" dir={doc.direction ?? 'auto'}>
                 {doc.tokens.map((t, i) => {
                   if (t.type === 'text') {
-                    return <React.Fragment key={`t-${i}`}>{t.value}</React.Fragment>;
+                    return <React.Fragment key={`t-${i}`}>{withStyle(t.style, t.value)}</React.Fragment>;
+                  }
+                  if (t.type === 'check') {
+                    return (
+                      <details key={`c-${i}`} className="doc-check">
+                        <summary>{t.question}</summary>
+                        <span className="muted">
+                          {t.answer ? 'True' : 'False'}
+                          {t.explanation ? ` · ${t.explanation}` : ''}
+                        </span>
+                      </details>
+                    );
                   }
 
                   const span = doc.spans[t.spanId];
@@ -331,7 +380,7 @@ export default function CompilerView(props: {
                         onClick={() => onSelectSpan(t.spanId)}
                         type="button"
                       >
-                        {label}
+                        {withStyle(t.style, label)}
                       </button>
 
                       {isActive ? (
@@ -348,6 +397,14 @@ export default function CompilerView(props: {
                             right: menuAlign === 'right' ? 0 : 'auto',
                           }}
                         >
+                          <button
+                            className={starredSpanIds.has(t.spanId) ? 'span-menu-item active' : 'span-menu-item'}
+                            onClick={() => onStarSpan(t.spanId, !starredSpanIds.has(t.spanId))}
+                            type="button"
+                            title="Starred spans become flashcards in Review"
+                          >
+                            {starredSpanIds.has(t.spanId) ? '\u2605 STARRED' : '\u2606 STAR'}
+                          </button>
                           {items.map(({ v, idx }) => {
                             const activeItem = idx === (span?.activeVariantIndex ?? 0);
                             return (
@@ -382,6 +439,41 @@ export default function CompilerView(props: {
                             );
                           })}
                           {items.length === 0 ? <div className="mono muted">No visible variants.</div> : null}
+                          <button
+                            className="span-menu-item"
+                            type="button"
+                            disabled={!activeLanguage}
+                            // Keep the reader's text selection for the search.
+                            onMouseDown={(e) => e.preventDefault()}
+                            onClick={() => {
+                              if (!activeLanguage) return;
+                              const phrase = window.getSelection()?.toString().trim() || label;
+                              void findUsages(phrase, activeLanguage).then((found) => {
+                                const others = found.filter((u) => u.text.trim() !== label.trim());
+                                setUsages({ spanId: t.spanId, phrase, items: others });
+                              });
+                            }}
+                            title="Other sentences in your library using the selected words, or this span"
+                          >
+                            USAGES
+                          </button>
+                          {usages?.spanId === t.spanId ? (
+                            <div className="mono" style={{ display: 'flex', flexDirection: 'column', gap: 4 }}>
+                              <div className="muted" style={{ fontSize: 12 }}>
+                                {usages.items.length === 0
+                                  ? `No other sentences use "${usages.phrase}".`
+                                  : usages.phrase}
+                              </div>
+                              {usages.items.map((u) => (
+                                <div key={`${u.docId}:${u.block}:${u.sentence}`}>
+                                  <span className="muted" style={{ fontSize: 12 }}>
+                                    {u.title} ·{' '}
+                                  </span>
+                                  {u.text}
+                                </div>
+                              ))}
+                            </div>
+                          ) : null}
                         </div>
                       ) : null}
                     </span>
@@ -475,6 +567,13 @@ export default function CompilerView(props: {
                       ) : null}
                     </div>
 
+                    {seg.simplifiedText ? (
+                      <div className="expanded-line">
+                        <div className="expanded-line-label">{seg.simplification?.level ?? 'SIMPLE'}</div>
+                        <div className="expanded-line-text">{seg.simplifiedText}</div>
+                      </div>
+                    ) : null}
+
                     {visibleRegisters.map((r) => {
                       const text = hasBlock ? renderBlockForRegister(idx, r) : baseText;
                       const bg = registerTint
@@ -490,11 +589,13 @@ export default function CompilerView(props: {
                           <div className="expanded-line-label">
                             <RegisterChip register={r} />
                           </div>
-                          <div className="expanded-line-text">{text || '…'}</div>
+                          <div className="expanded-line-text" dir={doc?.blockDirections?.[idx] ?? doc?.direction ?? 'auto'}>
+                            {text || '…'}
+                          </div>
                           {!ready ? (
                             <div className="expanded-line-meta">
                               <span className={seg.spanStage === 'ready' ? 'status ready' : 'status'}>
-                                {seg.spanStage.toUpperCase()}
+                                {spanStatus(seg, idx === planningIdx)}
                               </span>
                             </div>
                           ) : null}
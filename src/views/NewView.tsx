@@ -9,6 +9,7 @@ export default function NewView(props: {
   category: string | null;
   setCategory: (v: string | null) => void;
   allCategories: string[];
+  onLoadDemo: () => void;
   onTranslate: () => void;
 }) {
   const {
@@ -19,6 +20,7 @@ export default function NewView(props: {
     category,
     setCategory,
     allCategories,
+    onLoadDemo,
     onTranslate,
   } = props;
 
@@ -72,6 +74,9 @@ export default function NewView(props: {
         <button onClick={() => { setStoryText(''); setStoryTitle(''); setCategory(null); }} disabled={storyText.length === 0 && storyTitle.length === 0}>
           CLEAR
         </button>
+        <button onClick={onLoadDemo} title="A short story with offline translations; no API key needed">
+          DEMO STORY
+        </button>
       </div>
     </div>
   );
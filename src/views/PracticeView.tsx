@@ -74,6 +74,7 @@ export default function PracticeView(props: {
           sourceText += tok.value;
           continue;
         }
+        if (tok.type === 'check') continue;
         const span = doc.spans[tok.spanId];
         if (!span || span.variants.length === 0) {
           text += '...';
@@ -1,8 +1,9 @@
 import React from 'react';
-import type { Story } from '../bokaTypes';
+import type { StarredItem, Story } from '../bokaTypes';
 import RegisterChip from '../components/RegisterChip';
 import StoryPicker from '../components/StoryPicker';
 import type { RegisterId } from '../registers';
+import { countUnknownWords, listStarred, markWordsKnown } from '../tauriStorage';
 
 type Flashcard = {
   id: string;
@@ -12,10 +13,12 @@ type Flashcard = {
   spanId: string;
   sourceText: string;
   variantId: string;
-  register: RegisterId;
+  // Unset on cards for starred sentences.
+  register?: RegisterId;
   text: string;
   contextMasked?: string;
   note?: string;
+  starred: boolean;
 };
 
 const DELETED_KEY = 'boka.flashcards.deleted';
@@ -32,10 +35,15 @@ export default function ReviewView(props: {
   const [selectedLanguage, setSelectedLanguage] = React.useState<string | null>(null);
   const [selectedRegister, setSelectedRegister] = React.useState<RegisterId | null>(null);
   const [showDeleted, setShowDeleted] = React.useState(false);
+  const [starredOnly, setStarredOnly] = React.useState(false);
+  const [starredItems, setStarredItems] = React.useState<StarredItem[]>([]);
   const [sessionMode, setSessionMode] = React.useState(false);
   const [reveal, setReveal] = React.useState(false);
   const [cursor, setCursor] = React.useState(0);
   const [sessionOrder, setSessionOrder] = React.useState<string[] | null>(null);
+  // Card id -> unmastered words on its back, from the vocabulary ledger.
+  const [unknownCounts, setUnknownCounts] = React.useState<Map<string, number>>(new Map());
+  const [ledgerVersion, setLedgerVersion] = React.useState(0);
 
   const [deletedIds, setDeletedIds] = React.useState<Set<string>>(() => {
     try {
@@ -55,6 +63,18 @@ export default function ReviewView(props: {
     } catch {}
   }, [deletedIds]);
 
+  // Starred sentences come from the backend, which splits blocks the way
+  // read-along does; reloaded whenever the library changes.
+  React.useEffect(() => {
+    let cancelled = false;
+    void listStarred().then((items) => {
+      if (!cancelled) setStarredItems(items);
+    });
+    return () => {
+      cancelled = true;
+    };
+  }, [stories]);
+
   const resetSession = React.useCallback(() => {
     setCursor(0);
     setReveal(false);
@@ -84,6 +104,7 @@ export default function ReviewView(props: {
           out += tok.value;
           continue;
         }
+        if (tok.type === 'check') continue;
         if (tok.spanId === targetSpanId) {
           out += '____';
           continue;
@@ -123,6 +144,7 @@ export default function ReviewView(props: {
       for (const tr of Object.values(story.translations)) {
         const doc = tr.doc;
         if (!doc) continue;
+        const starredSpans = new Set((tr.starred ?? []).flatMap((s) => (s.type === 'span' ? [s.spanId] : [])));
 
         for (const [spanId, span] of Object.entries(doc.spans)) {
           const sourceText = span.sourceText || '';
@@ -143,18 +165,34 @@ export default function ReviewView(props: {
               text: v.text,
               contextMasked,
               note: v.note,
+              starred: starredSpans.has(spanId),
             });
           }
         }
       }
     }
+    for (const item of starredItems) {
+      if (item.type !== 'sentence') continue;
+      const [storyId] = item.docId.split(':');
+      out.push({
+        id: `${item.docId}:sentence:${item.block}:${item.sentence}`,
+        storyId,
+        storyTitle: item.title,
+        language: item.language,
+        spanId: '',
+        sourceText: item.source,
+        variantId: '',
+        text: item.text,
+        starred: true,
+      });
+    }
     out.sort((a, b) => {
       if (a.storyTitle !== b.storyTitle) return a.storyTitle.localeCompare(b.storyTitle);
       if (a.language !== b.language) return a.language.localeCompare(b.language);
       return a.sourceText.localeCompare(b.sourceText);
     });
     return out;
-  }, [buildMaskedContext, contentFilterEnabled, stories]);
+  }, [buildMaskedContext, contentFilterEnabled, starredItems, stories]);
 
   const deckCards = React.useMemo(() => {
     return selectedStoryId ? allCards.filter((c) => c.storyId === selectedStoryId) : allCards;
@@ -168,9 +206,10 @@ export default function ReviewView(props: {
     return deckCards.filter((c) => {
       if (selectedLanguage && c.language !== selectedLanguage) return false;
       if (selectedRegister && c.register !== selectedRegister) return false;
+      if (starredOnly && !c.starred) return false;
       return true;
     });
-  }, [deckCards, selectedLanguage, selectedRegister]);
+  }, [deckCards, selectedLanguage, selectedRegister, starredOnly]);
 
   const visibleCards = React.useMemo(() => {
     const filtered = showDeleted
@@ -183,7 +222,7 @@ export default function ReviewView(props: {
     setCursor(0);
     setReveal(false);
     setSessionOrder(null);
-  }, [selectedLanguage, selectedRegister, selectedStoryId, showDeleted, sessionMode]);
+  }, [selectedLanguage, selectedRegister, selectedStoryId, showDeleted, sessionMode, starredOnly]);
 
   React.useEffect(() => {
     if (!sessionMode) return;
@@ -199,6 +238,28 @@ export default function ReviewView(props: {
     setCursor(0);
   }, [cursor, sessionMode, sessionOrder]);
 
+  React.useEffect(() => {
+    if (!sessionMode) return;
+    let cancelled = false;
+    const byLanguage = new Map<string, Flashcard[]>();
+    for (const c of visibleCards) {
+      const cards = byLanguage.get(c.language) ?? [];
+      cards.push(c);
+      byLanguage.set(c.language, cards);
+    }
+    void (async () => {
+      const next = new Map<string, number>();
+      for (const [language, cards] of byLanguage) {
+        const counts = await countUnknownWords(language, cards.map((c) => c.text));
+        counts.forEach((n, i) => next.set(cards[i].id, n));
+      }
+      if (!cancelled) setUnknownCounts(next);
+    })();
+    return () => {
+      cancelled = true;
+    };
+  }, [ledgerVersion, sessionMode, visibleCards]);
+
   const visibleById = React.useMemo(() => {
     const map = new Map<string, Flashcard>();
     for (const c of visibleCards) map.set(c.id, c);
@@ -213,8 +274,10 @@ export default function ReviewView(props: {
       const c = visibleById.get(id);
       if (c) ordered.push(c);
     }
-    return ordered;
-  }, [sessionMode, sessionOrder, visibleById, visibleCards]);
+    // Cards whose words are all mastered come last.
+    const mastered = (c: Flashcard) => unknownCounts.get(c.id) === 0;
+    return [...ordered.filter((c) => !mastered(c)), ...ordered.filter(mastered)];
+  }, [sessionMode, sessionOrder, unknownCounts, visibleById, visibleCards]);
 
   const active = sessionCards.length > 0 ? sessionCards[cursor % sessionCards.length] : null;
 
@@ -237,6 +300,12 @@ export default function ReviewView(props: {
                   return m;
                 }, [allCards])}
               />
+              <button
+                className={starredOnly ? 'nav-item active' : 'nav-item'}
+                onClick={() => setStarredOnly((v) => !v)}
+              >
+                {starredOnly ? '\u2605 STARRED ONLY' : '\u2606 STARRED ONLY'}
+              </button>
               <hr />
               <div className="mono muted" style={{ fontSize: 12 }}>
                 LANGUAGE
@@ -351,7 +420,7 @@ export default function ReviewView(props: {
                     </div>
                     <div style={{ display: 'flex', justifyContent: 'space-between', gap: 10, paddingBottom: 8 }}>
                       <div style={{ fontWeight: 600 }}>FRONT</div>
-                      <RegisterChip register={active.register as any} />
+                      {active.register ? <RegisterChip register={active.register} /> : null}
                     </div>
                     <div className="mono" style={{ whiteSpace: 'pre-wrap', paddingBottom: 10 }}>
                       {active.contextMasked || active.sourceText || '…'}
@@ -400,6 +469,18 @@ export default function ReviewView(props: {
                   >
                     NEXT
                   </button>
+                  <button
+                    onClick={() => {
+                      if (!active) return;
+                      void markWordsKnown(active.language, active.text, true).then((changed) => {
+                        if (changed > 0) setLedgerVersion((v) => v + 1);
+                      });
+                      setReveal(false);
+                    }}
+                    disabled={!active || unknownCounts.get(active.id) === 0}
+                  >
+                    KNOWN
+                  </button>
                   <button
                     onClick={() => {
                       if (!active) return;
@@ -428,7 +509,7 @@ export default function ReviewView(props: {
                         {c.storyTitle} · {c.language.toUpperCase()}
                       </div>
                       <div style={{ display: 'flex', gap: 8, alignItems: 'center', paddingBottom: 6 }}>
-                        <RegisterChip register={c.register as any} />
+                        {c.register ? <RegisterChip register={c.register} /> : null}
                         <div className="mono" style={{ whiteSpace: 'pre-wrap' }}>
                           {c.sourceText || '…'}
                         </div>
@@ -1,11 +1,25 @@
 import React from 'react';
 import { version as appVersion } from '../../package.json';
 import type { AudioModelStatus } from '../bokaTypes';
-import type { LlmProviderConfig, LlmProviderPreset } from '../bokaTypes';
+import type { LlmProviderConfig, LlmProviderPreset, ProviderFailure, ProviderTestError } from '../bokaTypes';
+import { errorMessage } from '../commandError';
 import { test_tauri_provider } from '../tauriTranslation';
 import { TTS_LANGUAGES, OTHER_LANGUAGES, ALL_LANGUAGES, hasTts } from '../languages';
 import UpdatePanel from '../components/update/UpdatePanel';
 
+const PROVIDER_FAILURE_HINTS: Record<ProviderFailure, string> = {
+  invalidKey: 'The API key is missing or was rejected. Paste a fresh key from the provider dashboard.',
+  modelNotFound: 'The provider does not know this model. Check the spelling, or pull it first on a local server.',
+  billingExhausted: 'The account is out of credit or over its quota. Add credit or raise the limit, then retry.',
+  rateLimited: 'Too many requests right now. Wait a minute and test again.',
+  unreachable: 'Nothing answered. Check the base URL, that the local server is running, and your network.',
+  other: 'The provider returned an unexpected error.',
+};
+
+function isProviderTestError(e: unknown): e is ProviderTestError {
+  return typeof e === 'object' && e !== null && 'failure' in e && 'message' in e;
+}
+
 export default function SettingsView(props: {
   theme: 'light' | 'dark';
   setTheme: (t: 'light' | 'dark') => void;
@@ -44,9 +58,37 @@ export default function SettingsView(props: {
   } = props;
 
   const [providerTestStatus, setProviderTestStatus] = React.useState<
-    { state: 'idle' | 'running' | 'ok' | 'error'; message?: string }
+    { state: 'idle' | 'running' | 'ok' | 'error'; message?: string; cached?: boolean }
   >({ state: 'idle' });
 
+  const testProvider = async (force: boolean) => {
+    setProviderTestStatus({ state: 'running' });
+    const stamp = (checkedAt: number, cached: boolean) =>
+      `checkedAt: ${new Date(checkedAt).toLocaleTimeString()}${cached ? ' (cached)' : ''}`;
+    try {
+      const probe = await test_tauri_provider({
+        provider: {
+          preset: provider.preset,
+          apiKey: provider.apiKey,
+          baseUrl: provider.baseUrl,
+          model: provider.model,
+          reasoningModel: provider.reasoningModel,
+        },
+        force,
+      });
+      setProviderTestStatus({
+        state: 'ok',
+        message: `${probe.details}\n${stamp(probe.checkedAt, probe.cached)}`,
+        cached: probe.cached,
+      });
+    } catch (e) {
+      const message = isProviderTestError(e)
+        ? `${PROVIDER_FAILURE_HINTS[e.failure]}\n${e.status ? `HTTP ${e.status}: ` : ''}${e.message}\n${stamp(e.checkedAt, e.cached)}`
+        : errorMessage(e);
+      setProviderTestStatus({ state: 'error', message, cached: isProviderTestError(e) && e.cached });
+    }
+  };
+
   const PROVIDERS: Array<{ id: LlmProviderPreset; label: string }> = [
     { id: 'anthropic', label: 'Anthropic' },
     { id: 'openai', label: 'OpenAI' },
@@ -54,6 +96,7 @@ export default function SettingsView(props: {
     { id: 'ollama', label: 'Ollama (local)' },
     { id: 'lmstudio', label: 'LM Studio (local)' },
     { id: 'custom', label: 'Custom (OpenAI-compatible)' },
+    { id: 'mock', label: 'Demo (offline, no key)' },
   ];
 
   const getDefaults = (preset: LlmProviderPreset): { baseUrl?: string; model?: string } => {
@@ -62,6 +105,7 @@ export default function SettingsView(props: {
     if (preset === 'ollama') return { baseUrl: 'http://localhost:11434/v1', model: 'llama3.1' };
     if (preset === 'lmstudio') return { baseUrl: 'http://localhost:1234/v1', model: 'llama3.1' };
     if (preset === 'anthropic') return { model: 'claude-sonnet-4-20250514' };
+    if (preset === 'mock') return { baseUrl: 'demo' };
     return {};
   };
 
@@ -500,27 +544,16 @@ export default function SettingsView(props: {
           <div style={{ display: 'flex', alignItems: 'center', gap: 10 }}>
             <div style={{ width: 140 }} />
             <button
-              onClick={async () => {
-                setProviderTestStatus({ state: 'running' });
-                try {
-                  const msg = await test_tauri_provider({
-                    provider: {
-                      preset: provider.preset,
-                      apiKey: provider.apiKey,
-                      baseUrl: provider.baseUrl,
-                      model: provider.model,
-                    },
-                  });
-                  setProviderTestStatus({ state: 'ok', message: msg });
-                } catch (e) {
-                  const message = e instanceof Error ? e.message : String(e);
-                  setProviderTestStatus({ state: 'error', message });
-                }
-              }}
+              onClick={() => void testProvider(false)}
               disabled={providerTestStatus.state === 'running'}
             >
               {providerTestStatus.state === 'running' ? 'TESTING…' : 'TEST PROVIDER'}
             </button>
+            {providerTestStatus.cached ? (
+              <button onClick={() => void testProvider(true)} title="This result is from the last minute">
+                RETEST
+              </button>
+            ) : null}
             {providerTestStatus.state === 'ok' ? (
               <div className="mono" style={{ fontSize: 12, color: 'var(--register-casual)' }}>
                 OK
@@ -615,6 +648,29 @@ export default function SettingsView(props: {
             </button>
           </div>
 
+          {provider.preset !== 'anthropic' ? (
+            <div style={{ display: 'flex', alignItems: 'center', gap: 10 }}>
+              <div style={{ width: 140 }}>Reasoning Model</div>
+              <button
+                onClick={() => setProvider({ ...provider, reasoningModel: true })}
+                className={provider.reasoningModel ? 'nav-item active' : 'nav-item'}
+              >
+                ON
+              </button>
+              <button
+                onClick={() => setProvider({ ...provider, reasoningModel: false })}
+                className={!provider.reasoningModel ? 'nav-item active' : 'nav-item'}
+              >
+                OFF
+              </button>
+              <div className="muted" style={{ fontSize: 12 }}>
+                {provider.reasoningModel
+                  ? 'Strips <think> blocks and keeps the last JSON value.'
+                  : 'For DeepSeek-R1-style models that think before answering.'}
+              </div>
+            </div>
+          ) : null}
+
           <div className="muted" style={{ fontSize: 12 }}>
             {provider.preset === 'ollama'
               ? `OpenAI-compatible local server. Default: ${effectiveBaseUrl}`
@@ -626,7 +682,9 @@ export default function SettingsView(props: {
                     ? 'Uses Authorization: Bearer <OPENAI_API_KEY> and base URL https://api.openai.com/v1'
                     : provider.preset === 'anthropic'
                       ? 'Uses x-api-key: <ANTHROPIC_API_KEY>'
-                      : 'OpenAI-compatible: configure base URL, model, and (optional) key.'}
+                      : provider.preset === 'mock'
+                        ? 'Canned translations of the demo story (NEW → DEMO STORY); other text is echoed back.'
+                        : 'OpenAI-compatible: configure base URL, model, and (optional) key.'}
           </div>
         </div>
       </div>